@@ -0,0 +1,77 @@
+//! Admission throughput of the state cache
+//!
+//! Many tasks admit transactions concurrently, each for its own sender, the
+//! way the API validates submissions: read the sender's account, then bump
+//! its nonce. Compares a single shard, where every admission waits on one
+//! lock, against several shard counts, both in memory and over a store
+//! flushed on every write (where an admission holds its shard's lock while
+//! the write is flushed).
+//!
+//! Run with `cargo bench --bench state_cache`.
+
+use ethers::types::{Address, H256};
+use sequencer::config::{FlushPolicy, StateStoreConfig};
+use sequencer::state::{StateCache, StateStore};
+use std::time::{Duration, Instant};
+
+/// Concurrent senders, one task each
+const SENDERS: u64 = 256;
+/// Runs per configuration, the best one being reported
+const RUNS: usize = 3;
+
+/// Admit `per_sender` transactions from each sender, returning how long it took
+async fn admit_all(cache: StateCache, per_sender: u64) -> Duration {
+    let start = Instant::now();
+    let senders: Vec<_> = (1..=SENDERS)
+        .map(|id| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                let sender = Address::from_low_u64_be(id);
+                for nonce in 0..per_sender {
+                    let account = cache.get_or_init_account(&sender).await;
+                    assert_eq!(account.nonce, nonce);
+                    cache.increment_nonce(&sender, H256::from_low_u64_be(id << 32 | nonce)).await;
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.await.unwrap();
+    }
+    start.elapsed()
+}
+
+/// Report the best of `RUNS` runs over caches made by `cache`, for each shard count
+fn bench(runtime: &tokio::runtime::Runtime, name: &str, per_sender: u64, cache: impl Fn(usize, usize) -> StateCache) {
+    let admissions = SENDERS * per_sender;
+    println!("{}: {} admissions from {} concurrent senders", name, admissions, SENDERS);
+    for shards in [1, 4, 16, 64] {
+        let best = (0..RUNS)
+            .map(|run| runtime.block_on(admit_all(cache(shards, run), per_sender)))
+            .min()
+            .unwrap();
+        println!(
+            "  {:>3} shard(s): {:>8.1} ms, {:>10.0} admissions/s",
+            shards,
+            best.as_secs_f64() * 1000.0,
+            admissions as f64 / best.as_secs_f64()
+        );
+    }
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap();
+
+    bench(&runtime, "In memory", 200, |shards, _| StateCache::new().with_shards(shards));
+
+    let dir = std::env::temp_dir().join(format!("sequencer-state-bench-{}", std::process::id()));
+    bench(&runtime, "Store flushed on every write", 4, |shards, run| {
+        let config = StateStoreConfig {
+            path: dir.join(format!("{}-{}", shards, run)).to_string_lossy().into_owned(),
+            flush: FlushPolicy::EveryWrite,
+            flush_interval_ms: 0,
+        };
+        StateCache::new().with_shards(shards).with_store(StateStore::open(&config).unwrap())
+    });
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,66 @@
+//! API Authentication Module
+//! 
+//! Helpers for the credentials the API understands:
+//! - **Admin token**: `Authorization: Bearer <token>`, required for `admin_*` methods
+//! - **Proposer token**: `Authorization: Bearer <token>`, required for `proposer_*` methods
+//! - **API key**: `X-Api-Key: <key>`, identifies a metered client and its quota
+
+use crate::config::{ApiConfig, ProposerConfig};
+use axum::http::HeaderMap;
+use ethers::types::H256;
+use serde_json::Value;
+
+/// Header carrying a client's API key
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Check whether the request carries the configured admin token
+/// 
+/// Always `false` when no admin token is configured, which disables the admin API.
+pub(crate) fn is_admin(headers: &HeaderMap, config: &ApiConfig) -> bool {
+    has_bearer(headers, config.admin_token.as_deref())
+}
+
+/// Check whether the request carries the configured proposer token
+/// 
+/// Always `false` unless the proposer API is enabled (token and signing key set).
+pub(crate) fn is_proposer(headers: &HeaderMap, config: &ProposerConfig) -> bool {
+    config.enabled() && has_bearer(headers, config.token.as_deref())
+}
+
+/// Check the `Authorization: Bearer` header against `expected` (never matches `None`)
+fn has_bearer(headers: &HeaderMap, expected: Option<&str>) -> bool {
+    let Some(expected) = expected else {
+        return false;
+    };
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| token == expected)
+}
+
+/// Extract the client's API key from the request headers, if present
+pub(crate) fn api_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Number of transactions a request submits, charged against the API key's quota
+/// 
+/// Every submission method counts, whatever form the transaction takes; a
+/// bundle counts each of its transactions. Other methods submit none.
+pub(crate) fn submitted_transactions(method: &str, params: &Value) -> u64 {
+    match method {
+        "sendTransaction" | "eth_sendRawTransaction" | "sendUserOperation" | "sendEncryptedTransaction" => 1,
+        // Malformed bundles are still charged, as one transaction
+        "sendBundle" => params.as_array().map_or(1, |txs| txs.len().max(1) as u64),
+        _ => 0,
+    }
+}
+
+/// Generate a new random API key (64 hex characters)
+pub(crate) fn generate_api_key() -> String {
+    format!("{:x}", H256::random())
+}
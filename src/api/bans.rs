@@ -0,0 +1,142 @@
+//! Sender Ban Module
+//!
+//! Tracks validation failures per sender and temporarily bans senders that
+//! keep submitting garbage, so they are refused before any signature check or
+//! state lookup is spent on them.
+//!
+//! A failure only counts if the sender must have produced it: the claimed
+//! `from` is not authenticated until the signature checks out, so anyone could
+//! post garbage in a victim's name to get it banned. A failure is therefore
+//! only counted once the failed transaction's own signature verifies, and what
+//! counts is a nonce further than `nonce_tolerance` from the expected one.
+//! Insufficient balance and slightly-off nonces are ordinary client mistakes.
+
+use crate::{config::SenderBanConfig, validation::Validator, UserTransaction, ValidationError};
+use ethers::types::Address;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tracked senders above which expired records are pruned
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Failure history of one sender
+struct SenderRecord {
+    /// Failures counted in the current window
+    failures: u32,
+    /// When the current window started
+    window_start: Instant,
+    /// End of the sender's ban, if banned
+    banned_until: Option<Instant>,
+}
+
+/// Temporary bans for senders with repeated validation failures
+pub(crate) struct SenderBans {
+    config: SenderBanConfig,
+    records: Mutex<HashMap<Address, SenderRecord>>,
+}
+
+impl SenderBans {
+    /// Creates an empty ban list
+    pub(crate) fn new(config: SenderBanConfig) -> Self {
+        Self {
+            config,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Time left on `sender`'s ban, or `None` if it is not banned
+    pub(crate) fn remaining(&self, sender: &Address) -> Option<Duration> {
+        let records = self.records.lock().unwrap();
+        let banned_until = records.get(sender)?.banned_until?;
+        banned_until.checked_duration_since(Instant::now()).filter(|left| !left.is_zero())
+    }
+
+    /// Count the validation failure of `tx` against its sender
+    ///
+    /// Nothing is counted unless `tx` is signed by its claimed sender, whatever
+    /// rule failed and whether or not `validator` checks signatures at all.
+    ///
+    /// # Returns
+    /// `true` if this failure got the sender banned
+    pub(crate) fn record_rejection(&self, validator: &Validator, tx: &UserTransaction, error: &ValidationError) -> bool {
+        // Verified last, so only failures that would count pay for recovery
+        self.counts(error) && validator.verify_signature(tx).is_ok() && self.record_failure(tx.from, error)
+    }
+
+    /// Count a validation failure against `sender`
+    ///
+    /// Callers must have authenticated `sender` (see [`SenderBans::record_rejection`]).
+    ///
+    /// # Returns
+    /// `true` if this failure got the sender banned
+    pub(crate) fn record_failure(&self, sender: Address, error: &ValidationError) -> bool {
+        if !self.counts(error) {
+            return false;
+        }
+        let now = Instant::now();
+        let window = Duration::from_secs(self.config.window_secs);
+        let mut records = self.records.lock().unwrap();
+        if records.len() >= PRUNE_THRESHOLD {
+            records.retain(|_, record| {
+                record.banned_until.is_some_and(|until| until > now)
+                    || now.duration_since(record.window_start) < window
+            });
+        }
+
+        let record = records.entry(sender).or_insert(SenderRecord {
+            failures: 0,
+            window_start: now,
+            banned_until: None,
+        });
+        if record.banned_until.is_some_and(|until| until > now) {
+            return false;
+        }
+        if now.duration_since(record.window_start) >= window {
+            record.failures = 0;
+            record.window_start = now;
+        }
+        record.failures += 1;
+        if record.failures < self.config.max_failures {
+            return false;
+        }
+
+        // Banned; the count starts over once the ban ends
+        record.failures = 0;
+        record.window_start = now;
+        record.banned_until = Some(now + Duration::from_secs(self.config.ban_secs));
+        true
+    }
+
+    /// Check whether a validation failure counts towards a ban
+    fn counts(&self, error: &ValidationError) -> bool {
+        match error {
+            ValidationError::InvalidNonce { expected, got } => {
+                expected.abs_diff(*got) > self.config.nonce_tolerance
+            }
+            // Found before the sender is authenticated, so they can be forged in anyone's name
+            ValidationError::InvalidSignature
+            | ValidationError::MalformedSignature
+            | ValidationError::InvalidRawTransaction { .. }
+            | ValidationError::AccountValidationFailed { .. } => false,
+            ValidationError::InsufficientBalance { .. }
+            | ValidationError::WrongChain { .. }
+            | ValidationError::IncompleteFeeFields
+            | ValidationError::PriorityFeeAboveMaxFee { .. }
+            | ValidationError::FeeCapBelowBaseFee { .. }
+            | ValidationError::BelowFeeFloor { .. }
+            | ValidationError::IntrinsicGasTooLow { .. }
+            | ValidationError::NonceTooFarAhead { .. }
+            | ValidationError::ValueTooHigh { .. }
+            | ValidationError::CalldataTooLarge { .. }
+            | ValidationError::TimestampTooFarAhead { .. }
+            | ValidationError::BelowMinimumBalance { .. }
+            | ValidationError::InsufficientStake { .. }
+            | ValidationError::SenderIsContract { .. }
+            | ValidationError::InvalidAttestation { .. }
+            | ValidationError::InvalidEncryption { .. }
+            | ValidationError::InvalidDeadline { .. }
+            | ValidationError::RuleViolated { .. } => false,
+        }
+    }
+}
@@ -3,5 +3,12 @@
 //! This module handles the JSON-RPC API for receiving user transactions.
 //! It provides the HTTP endpoint that clients use to submit transactions.
 
+mod auth;
+mod bans;
 mod server;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
 pub use server::Server;
\ No newline at end of file
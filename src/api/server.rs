@@ -3,17 +3,55 @@
 //! This module implements a JSON-RPC server for handling transaction submissions.
 //! It provides an HTTP endpoint that accepts transactions, validates them,
 //! and adds them to the transaction pool if valid.
+//! 
+//! When the proposer API is enabled, `proposer_submitBatch` lets an external
+//! proposer hand in a fully-ordered batch for the sequencer to seal and sign.
+//! 
+//! On an RPC ingress node (with a remote pool configured), `sendTransaction`
+//! only checks the signature and forwards the transaction to the central
+//! sequencer instead. The central sequencer takes forwarded transactions on
+//! the gRPC `SendTransaction` method, served on the same port.
 
+use super::auth;
+use super::bans::SenderBans;
 use crate::{
-    config::Config,
-    validation::Validator,
-    pool::TransactionPool,
-    state::StateCache,
+    batch::{BatchOrchestrator, SafeMode, WithdrawalProof},
+    config::{ApiConfig, Config, ProposerConfig},
+    l1::L1GasOracle,
+    logging::LogFilter,
+    scheduler::{PolicyKind, SchedulingPolicyType},
+    validation::{decode_raw_transaction, AccountValidator, BlsCommittee, BlsSigner, UserOpValidator, ValidationWorkers, Validator},
+    pool::{
+        BundlePool, EncryptedPool, ForwardHandler, ForwardReply, ForwardRequest, ForwardServer, PoolImportReport, PoolSnapshot,
+        RejectedImport, RemotePool, TransactionPool, UserOpPool,
+    },
+    state::{StateCache, StateCheckpoint, StateReader},
+    registry::{QuotaCheck, Registry},
+    AccountState,
+    ApiKeyUsage,
+    AuctionBid,
+    BatchMetadata,
+    BlsAttestation,
+    Bundle,
+    BundleTxResult,
+    BundleValidation,
+    EncryptedTransaction,
+    FeeEstimate,
+    PoolError,
+    ProposalError,
+    RemoteError,
+    SignedBatch,
+    UserOperation,
     UserTransaction,
     SoftConfirmation,
     ConfirmationStatus,
+    TransactionOutcome,
+    TransactionRecord,
+    ValidationError,
 };
-use axum::{Router, routing::post, Json, extract::State};
+use axum::{Router, routing::{get, post}, Json, extract::State, http::HeaderMap};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, H256, U256};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
@@ -23,14 +61,92 @@ use tracing::{info, warn, error};
 /// 
 /// This struct holds references to key components that need to be shared
 /// across multiple concurrent requests:
-/// - `validator`: Validates incoming transactions
+/// - `validation`: Validates incoming transactions on a worker pool
 /// - `tx_pool`: Stores pending transactions waiting to be batched
 /// - `state_cache`: Maintains account state (balances, nonces)
+/// - `registry`: Records transaction outcomes and answers history queries
+/// - `api_config`: Authentication and quota settings
+/// - `remote_pool`: Upstream sequencer to forward to (ingress nodes only)
+/// - `proposer_config`: Proposer API credentials
+/// - `proposer`: Orchestrator and signing key for proposed batches (if enabled)
+/// - `log_filter`: Handle for changing the log filter at runtime
+/// - `sender_bans`: Temporary bans for senders failing validation (if enabled)
+/// - `user_ops`: User operation validation and lane (if account abstraction is enabled)
+/// - `attestor`: BLS key attesting batches and soft confirmations (multi-sequencer mode)
+/// - `committee`: Sequencers whose BLS attestations `verifyAttestation` accepts
+/// - `checkpoints`: Orchestrator exporting state checkpoints (sequencing nodes only)
+/// - `gas_oracle`: L1 fees and posting costs reported by `estimateFee` (if enabled)
+/// - `safe_mode`: Safe mode reported by `getSafeMode` and `/metrics` (if enabled)
+/// - `scheduling`: Orchestrator whose scheduling policy the admin API switches,
+///   and whose scheduling metrics are served (sequencing nodes only)
+/// - `auction`: Orchestrator whose auctions `submitBid` places bids in
+///   (sequencing nodes only)
+/// - `encrypted`: Lane taking encrypted transactions and epoch keys (if the
+///   encrypted mempool is enabled)
+/// - `bundles`: Lane taking atomic bundles (if enabled)
 #[derive(Clone)]
 pub struct AppState {
-    validator: Arc<Validator>,
+    validation: Arc<ValidationWorkers>,
     tx_pool: Arc<TransactionPool>,
     state_cache: StateCache,
+    state_reader: StateReader,
+    registry: Arc<Registry>,
+    api_config: ApiConfig,
+    remote_pool: Option<Arc<RemotePool>>,
+    proposer_config: ProposerConfig,
+    proposer: Option<BatchProposer>,
+    log_filter: Option<LogFilter>,
+    sender_bans: Option<Arc<SenderBans>>,
+    user_ops: Option<UserOps>,
+    attestor: Option<Arc<BlsSigner>>,
+    committee: Option<Arc<BlsCommittee>>,
+    checkpoints: Option<Checkpoints>,
+    gas_oracle: Option<GasOracle>,
+    safe_mode: Option<Arc<SafeMode>>,
+    scheduling: Option<Arc<BatchOrchestrator>>,
+    auction: Option<Auctions>,
+    encrypted: Option<Arc<EncryptedPool>>,
+    bundles: Option<Arc<BundlePool>>,
+}
+
+/// What the server needs to seal and sign externally proposed batches
+#[derive(Clone)]
+struct BatchProposer {
+    orchestrator: Arc<BatchOrchestrator>,
+    signer: LocalWallet,
+}
+
+/// What the server needs to export state checkpoints
+#[derive(Clone)]
+struct Checkpoints {
+    orchestrator: Arc<BatchOrchestrator>,
+    chain_id: u64,
+}
+
+/// What the server needs to take sealed bids
+#[derive(Clone)]
+struct Auctions {
+    orchestrator: Arc<BatchOrchestrator>,
+    /// Chain the bid signatures are bound to
+    chain_id: u64,
+}
+
+/// What the server needs to report L1 posting costs
+#[derive(Clone)]
+struct GasOracle {
+    oracle: Arc<L1GasOracle>,
+    /// Transactions in a full batch, which share its posting cost
+    batch_size: usize,
+}
+
+/// What the server needs to accept user operations
+#[derive(Clone)]
+struct UserOps {
+    validator: Arc<UserOpValidator>,
+    lane: Arc<UserOpPool>,
+    /// Held from validation until the wallet's nonce is bumped, so two
+    /// operations can't both claim the same nonce
+    admission: Arc<tokio::sync::Mutex<()>>,
 }
 
 /// The main API server struct
@@ -49,6 +165,7 @@ impl Server {
     /// * `config` - Server configuration (host, port, etc.)
     /// * `state_cache` - The state cache for account data
     /// * `tx_pool` - The transaction pool for pending normal transactions
+    /// * `registry` - The registry for transaction history
     /// 
     /// # Returns
     /// A new `Server` instance with initialized components
@@ -56,34 +173,177 @@ impl Server {
         config: Config,
         state_cache: StateCache,
         tx_pool: Arc<TransactionPool>,
+        registry: Arc<Registry>,
     ) -> Self {
-        // Initialize the transaction validator with access to state
-        let validator = Arc::new(Validator::new(state_cache.clone()));
+        let validation = Self::validation_workers(&config, &state_cache, &tx_pool, None);
         
         // Bundle all shared state into AppState
         let state = AppState {
-            validator,
+            validation,
             tx_pool,
+            state_reader: StateReader::new(state_cache.clone()),
             state_cache,
+            registry,
+            api_config: config.api.clone(),
+            remote_pool: None,
+            proposer_config: config.proposer.clone(),
+            proposer: None,
+            log_filter: None,
+            sender_bans: config.api.sender_bans.clone().map(|bans| Arc::new(SenderBans::new(bans))),
+            user_ops: None,
+            attestor: None,
+            committee: None,
+            checkpoints: None,
+            gas_oracle: None,
+            safe_mode: None,
+            scheduling: None,
+            auction: None,
+            encrypted: None,
+            bundles: None,
         };
         
         Self { config, state }
     }
     
+    /// The validation workers a server built from `config` checks submissions with
+    /// 
+    /// Components admitting transactions outside the API share them through
+    /// [`Server::with_validation`], so all admissions take turns. Balances
+    /// are checked against pending `bundles` too, if given.
+    pub fn validation_workers(
+        config: &Config,
+        state_cache: &StateCache,
+        tx_pool: &Arc<TransactionPool>,
+        bundles: Option<&Arc<BundlePool>>,
+    ) -> Arc<ValidationWorkers> {
+        // Initialize the transaction validator with access to state
+        let mut validator = Validator::new(state_cache.clone())
+            .with_signing(&config.signing)
+            .with_config(&config.validation)
+            .with_base_fee(U256::from(config.batch.base_fee_wei))
+            .with_pool(tx_pool.clone(), config.pool.nonce_lookahead)
+            .with_deadlines(&config.deadlines);
+        if let Some(bundles) = bundles {
+            validator = validator.with_bundles(bundles.clone());
+        }
+        Arc::new(ValidationWorkers::new(Arc::new(validator), config.api.validation_workers))
+    }
+    
+    /// Check submissions with `validation` (see [`Server::validation_workers`])
+    pub fn with_validation(mut self, validation: Arc<ValidationWorkers>) -> Self {
+        self.state.validation = validation;
+        self
+    }
+    
+    /// Forward submitted transactions to a central sequencer instead of the local pool
+    /// 
+    /// Used on RPC ingress nodes (see [`RemotePool`]).
+    pub fn with_remote_pool(mut self, remote_pool: Arc<RemotePool>) -> Self {
+        self.state.remote_pool = Some(remote_pool);
+        self
+    }
+    
+    /// Accept externally proposed batches, sealed by `orchestrator` and signed by `signer`
+    /// 
+    /// Only reachable with the proposer token (see [`ProposerConfig`]).
+    pub fn with_proposer(mut self, orchestrator: Arc<BatchOrchestrator>, signer: LocalWallet) -> Self {
+        self.state.proposer = Some(BatchProposer { orchestrator, signer });
+        self
+    }
+    
+    /// Accept user operations into `lane`, authorized by the `accounts` hook
+    pub fn with_user_ops(mut self, lane: Arc<UserOpPool>, accounts: Arc<dyn AccountValidator>) -> Self {
+        let validator = UserOpValidator::new(self.state.state_cache.clone(), accounts)
+            .with_signing(&self.config.signing)
+            .with_base_fee(U256::from(self.config.batch.base_fee_wei))
+            .with_lane(lane.clone());
+        self.state.user_ops = Some(UserOps {
+            validator: Arc::new(validator),
+            lane,
+            admission: Arc::new(tokio::sync::Mutex::new(())),
+        });
+        self
+    }
+    
+    /// Attest sealed proposals and accepted soft confirmations with `signer`'s BLS key
+    pub fn with_attestor(mut self, signer: BlsSigner) -> Self {
+        self.state.attestor = Some(Arc::new(signer));
+        self
+    }
+    
+    /// Serve `verifyAttestation` for attestations by `committee`
+    pub fn with_committee(mut self, committee: BlsCommittee) -> Self {
+        self.state.committee = Some(Arc::new(committee));
+        self
+    }
+    
+    /// Report L1 fees and each transaction's share of posting a batch in `estimateFee`
+    pub fn with_gas_oracle(mut self, oracle: Arc<L1GasOracle>) -> Self {
+        let batch_size = self.config.batch.max_batch_size;
+        self.state.gas_oracle = Some(GasOracle { oracle, batch_size });
+        self
+    }
+    
+    /// Report `safe_mode` in `getSafeMode` and `/metrics`
+    pub fn with_safe_mode(mut self, safe_mode: Arc<SafeMode>) -> Self {
+        self.state.safe_mode = Some(safe_mode);
+        self
+    }
+    
+    /// Allow the admin API to export state checkpoints, between batches sealed by `orchestrator`
+    pub fn with_checkpoints(mut self, orchestrator: Arc<BatchOrchestrator>) -> Self {
+        let chain_id = self.config.signing.chain_id;
+        self.state.checkpoints = Some(Checkpoints { orchestrator, chain_id });
+        self
+    }
+    
+    /// Allow the admin API to switch the scheduling policy of `orchestrator`
+    pub fn with_policy_switching(mut self, orchestrator: Arc<BatchOrchestrator>) -> Self {
+        self.state.scheduling = Some(orchestrator);
+        self
+    }
+    
+    /// Take sealed bids for the auctions of `orchestrator` (while its policy is `Auction`)
+    pub fn with_auction(mut self, orchestrator: Arc<BatchOrchestrator>) -> Self {
+        let chain_id = self.config.signing.chain_id;
+        self.state.auction = Some(Auctions { orchestrator, chain_id });
+        self
+    }
+    
+    /// Accept encrypted transactions into `lane`, and the keys of its epochs
+    pub fn with_encrypted_mempool(mut self, lane: Arc<EncryptedPool>) -> Self {
+        self.state.encrypted = Some(lane);
+        self
+    }
+    
+    /// Accept atomic bundles into `lane`
+    /// 
+    /// The validation workers should count the lane's pending spend (see
+    /// [`Server::validation_workers`]).
+    pub fn with_bundles(mut self, lane: Arc<BundlePool>) -> Self {
+        self.state.bundles = Some(lane);
+        self
+    }
+    
+    /// Allow the admin API to change the log filter through `log_filter`
+    pub fn with_log_filter(mut self, log_filter: LogFilter) -> Self {
+        self.state.log_filter = Some(log_filter);
+        self
+    }
+    
     /// Starts the API server and begins listening for incoming requests
     /// 
     /// This method:
-    /// 1. Creates an Axum router with a single POST endpoint at "/"
+    /// 1. Creates an Axum router with a POST endpoint at "/" for JSON-RPC, a
+    ///    GET endpoint at "/metrics" for Prometheus scrapes, and the gRPC
+    ///    service ingress nodes forward transactions to
     /// 2. Binds the router to the configured host and port
     /// 3. Starts serving requests asynchronously
     /// 
     /// # Returns
     /// `Ok(())` if the server starts successfully, or an error if binding fails
     pub async fn start(self) -> anyhow::Result<()> {
-        // Create the router with a single POST endpoint that handles JSON-RPC requests
-        let app = Router::new()
-            .route("/", post(handle_rpc))
-            .with_state(self.state);
+        let app = self.router();
         
         // Format the listening address from config
         let addr = format!("{}:{}", self.config.api.host, self.config.api.port);
@@ -95,6 +355,18 @@ impl Server {
         
         Ok(())
     }
+    
+    /// The routes [`Server::start`] serves
+    pub fn router(&self) -> Router {
+        // JSON-RPC requests on "/", unauthenticated metrics scrapes on "/metrics"
+        // Forwarded transactions over gRPC (HTTP/2) on the same port
+        let forwarding = tonic::service::Routes::new(ForwardServer::new(self.state.clone())).into_axum_router();
+        Router::new()
+            .route("/", post(handle_rpc))
+            .route("/metrics", get(handle_metrics))
+            .with_state(self.state.clone())
+            .merge(forwarding)
+    }
 }
 
 /// JSON-RPC 2.0 request structure
@@ -107,6 +379,7 @@ impl Server {
 /// - `id`: Request identifier for matching responses
 #[derive(Debug, Deserialize)]
 struct JsonRpcRequest {
+    #[allow(dead_code)] // Part of the JSON-RPC envelope, not inspected
     jsonrpc: String,
     method: String,
     params: Value,
@@ -142,6 +415,55 @@ struct JsonRpcError {
     message: String,
 }
 
+/// Standard JSON-RPC error code for method not found
+const METHOD_NOT_FOUND: i32 = -32601;
+/// Standard JSON-RPC error code for invalid params
+const INVALID_PARAMS: i32 = -32602;
+/// Standard JSON-RPC error code for internal errors
+const INTERNAL_ERROR: i32 = -32603;
+/// Server error: missing or invalid admin token / API key
+const UNAUTHORIZED: i32 = -32001;
+/// Server error: transaction rejected because the pool is full
+const POOL_FULL: i32 = -32003;
+/// Server error: an identical transaction is already pending
+const ALREADY_KNOWN: i32 = -32004;
+/// Server error: API key quota exhausted for the current window
+const LIMIT_EXCEEDED: i32 = -32005;
+/// Server error: the upstream sequencer could not be reached (ingress nodes)
+const UPSTREAM_UNAVAILABLE: i32 = -32006;
+/// Server error: the sender is on the pool denylist
+const SENDER_DENIED: i32 = -32007;
+/// Server error: the gas price is below the pool's fee floor
+const UNDERPRICED: i32 = -32008;
+/// Server error: an externally proposed batch was rejected
+const PROPOSAL_REJECTED: i32 = -32009;
+/// Server error: the sender is temporarily banned after repeated validation failures
+const SENDER_BANNED: i32 = -32010;
+/// Server error: permissioned mode and the sender is not on the allowlist
+const SENDER_NOT_ALLOWLISTED: i32 = -32011;
+/// Server error: a raw transaction failed validation (the code Ethereum nodes use)
+const TRANSACTION_REJECTED: i32 = -32000;
+
+/// Build a successful JSON-RPC response
+fn success_response(id: Value, result: Value) -> Json<JsonRpcResponse> {
+    Json(JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        result: Some(result),
+        error: None,
+        id,
+    })
+}
+
+/// Build a JSON-RPC error response
+fn error_response(id: Value, code: i32, message: String) -> Json<JsonRpcResponse> {
+    Json(JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        result: None,
+        error: Some(JsonRpcError { code, message }),
+        id,
+    })
+}
+
 /// Main RPC request handler
 /// 
 /// This function is called for every POST request to the "/" endpoint.
@@ -155,23 +477,145 @@ struct JsonRpcError {
 /// A JSON-RPC response (either success or error)
 async fn handle_rpc(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(request): Json<JsonRpcRequest>,
 ) -> Json<JsonRpcResponse> {
     info!("Received RPC request: {}", request.method);
     
+    // Check credentials (admin token or API key quota) before doing any work
+    if let Err(response) = authorize(&state, &headers, &request).await {
+        return response;
+    }
+    
     // Route to the appropriate handler based on the method name
     match request.method.as_str() {
         "sendTransaction" => handle_send_transaction(state, request).await,
+        "eth_sendRawTransaction" => handle_send_raw_transaction(state, request).await,
+        "sendUserOperation" => handle_send_user_operation(state, request).await,
+        "getUsage" => handle_get_usage(state, auth::api_key(&headers), request).await,
+        "admin_createApiKey" => handle_create_api_key(state, request).await,
+        "admin_revokeApiKey" => handle_revoke_api_key(state, request).await,
+        "admin_getBondForfeitures" => handle_get_bond_forfeitures(state, request).await,
+        "admin_denySender" => handle_deny_sender(state, request).await,
+        "admin_allowSender" => handle_allow_sender(state, request).await,
+        "admin_getDenylist" => handle_get_denylist(state, request).await,
+        "admin_getValidationFailures" => handle_get_validation_failures(state, request).await,
+        "admin_approveSender" => handle_approve_sender(state, request).await,
+        "admin_revokeSender" => handle_revoke_sender(state, request).await,
+        "admin_getAllowlist" => handle_get_allowlist(state, request).await,
+        "admin_exportPool" => handle_export_pool(state, request).await,
+        "admin_importPool" => handle_import_pool(state, request).await,
+        "admin_getLogFilter" => handle_get_log_filter(state, request).await,
+        "admin_setLogFilter" => handle_set_log_filter(state, request).await,
+        "admin_getPolicy" => handle_get_policy(state, request).await,
+        "admin_setPolicy" => handle_set_policy(state, request).await,
+        "admin_getStateJournal" => handle_get_state_journal(state, request).await,
+        "admin_exportCheckpoint" => handle_export_checkpoint(state, request).await,
+        "proposer_submitBatch" => handle_submit_batch(state, request).await,
+        "estimateFee" => handle_estimate_fee(state, request).await,
+        "validateBundle" => handle_validate_bundle(state, request).await,
+        "verifyAttestation" => handle_verify_attestation(state, request).await,
+        "getBalance" => handle_get_balance(state, request).await,
+        "getNonce" => handle_get_nonce(state, request).await,
+        "getAccounts" => handle_get_accounts(state, request).await,
+        "getStorageAt" => handle_get_storage_at(state, request).await,
+        "getNonceHistory" => handle_get_nonce_history(state, request).await,
+        "getDuplicateNonces" => handle_get_duplicate_nonces(state, request).await,
+        "getGenesis" => handle_get_genesis(state, request).await,
+        "getBatchWitness" => handle_get_batch_witness(state, request).await,
+        "getBatchStatus" => handle_get_batch_status(state, request).await,
+        "getDepositStatus" => handle_get_deposit_status(state, request).await,
+        "getL1Finality" => handle_get_l1_finality(state, request).await,
+        "getSafeMode" => handle_get_safe_mode(state, request).await,
+        "getWithdrawalProof" => handle_get_withdrawal_proof(state, request).await,
+        "submitBid" => handle_submit_bid(state, request).await,
+        "getAuctionResults" => handle_get_auction_results(state, request).await,
+        "getSchedulingMetrics" => handle_get_scheduling_metrics(state, request).await,
+        "sendBundle" => handle_send_bundle(state, request).await,
+        "getBundleStatus" => handle_get_bundle_status(state, request).await,
+        "sendEncryptedTransaction" => handle_send_encrypted_transaction(state, request).await,
+        "submitDecryptionKey" => handle_submit_decryption_key(state, request).await,
+        "getEncryptionInfo" => handle_get_encryption_info(state, request).await,
         // Return "Method not found" error for unsupported methods
-        _ => Json(JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            result: None,
-            error: Some(JsonRpcError {
-                code: -32601, // Standard JSON-RPC error code for method not found
-                message: "Method not found".to_string(),
-            }),
-            id: request.id,
-        }),
+        _ => error_response(request.id, METHOD_NOT_FOUND, "Method not found".to_string()),
+    }
+}
+
+/// Serve pool gauges, validation failure counters, state cache and scheduling
+/// metrics in the Prometheus text format
+async fn handle_metrics(State(state): State<AppState>) -> String {
+    let mut metrics = state.tx_pool.metrics().await.to_prometheus();
+    metrics.push_str(&state.validation.validator().telemetry().to_prometheus());
+    metrics.push_str(&state.state_cache.metrics().await.to_prometheus());
+    if let Some(orchestrator) = &state.scheduling {
+        metrics.push_str(&orchestrator.fairness().to_prometheus());
+    }
+    if let Some(safe_mode) = &state.safe_mode {
+        metrics.push_str(&safe_mode.to_prometheus());
+    }
+    metrics
+}
+
+/// Authorize a request before it is routed
+/// 
+/// - `admin_*` methods require the configured admin token
+/// - `proposer_*` methods require the configured proposer token
+/// - When `require_api_key` is set, every other method requires a known API key,
+///   and the request (plus the transactions it submits, see
+///   `auth::submitted_transactions`) is charged against that key's quota
+/// 
+/// # Returns
+/// `Err(response)` with the error to send back if the request is not allowed
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: &JsonRpcRequest,
+) -> Result<(), Json<JsonRpcResponse>> {
+    if request.method.starts_with("admin_") {
+        if auth::is_admin(headers, &state.api_config) {
+            return Ok(());
+        }
+        warn!("Rejected unauthorized admin call {}", request.method);
+        return Err(error_response(request.id.clone(), UNAUTHORIZED, "Unauthorized".to_string()));
+    }
+    
+    if request.method.starts_with("proposer_") {
+        if auth::is_proposer(headers, &state.proposer_config) {
+            return Ok(());
+        }
+        warn!("Rejected unauthorized proposer call {}", request.method);
+        return Err(error_response(request.id.clone(), UNAUTHORIZED, "Unauthorized".to_string()));
+    }
+    
+    if !state.api_config.require_api_key {
+        return Ok(());
+    }
+    
+    let Some(key) = auth::api_key(headers) else {
+        return Err(error_response(request.id.clone(), UNAUTHORIZED, "Missing API key".to_string()));
+    };
+    let transactions = auth::submitted_transactions(&request.method, &request.params);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    
+    match state.registry
+        .consume_api_quota(&key, now, state.api_config.quota_window_secs, transactions)
+        .await
+    {
+        Ok(QuotaCheck::Allowed(_)) => Ok(()),
+        Ok(QuotaCheck::Exceeded(usage)) => {
+            warn!("API key {} ({}) exceeded its quota", usage.label, request.method);
+            Err(error_response(request.id.clone(), LIMIT_EXCEEDED, "API key quota exceeded".to_string()))
+        }
+        Ok(QuotaCheck::UnknownKey) => {
+            Err(error_response(request.id.clone(), UNAUTHORIZED, "Unknown API key".to_string()))
+        }
+        Err(e) => {
+            error!("Quota check failed: {:?}", e);
+            Err(error_response(request.id.clone(), INTERNAL_ERROR, "Quota check failed".to_string()))
+        }
     }
 }
 
@@ -182,6 +626,14 @@ async fn handle_rpc(
 /// 2. Validates the transaction (signature, nonce, balance)
 /// 3. If valid: adds to the pool and returns a soft confirmation
 /// 4. If invalid: returns a rejection confirmation with the reason
+/// 5. If the pool is full: returns a `POOL_FULL` JSON-RPC error
+/// 6. If an identical transaction is pending: returns an `ALREADY_KNOWN` JSON-RPC error
+/// 7. If the sender is denylisted: returns a `SENDER_DENIED` JSON-RPC error
+/// 8. If the gas price is below the fee floor: returns an `UNDERPRICED` JSON-RPC error
+/// 9. If the sender is temporarily banned: returns a `SENDER_BANNED` JSON-RPC error
+/// 10. If the pool is permissioned and the sender is not allowlisted: returns a
+///     `SENDER_NOT_ALLOWLISTED` JSON-RPC error
+/// 11. If it names an unknown priority class: returns an `INVALID_PARAMS` JSON-RPC error
 /// 
 /// # Arguments
 /// * `state` - Shared application state
@@ -199,53 +651,169 @@ async fn handle_send_transaction(
         Err(e) => {
             error!("Failed to deserialize transaction: {}", e);
             // Return invalid params error if deserialization fails
-            return Json(JsonRpcResponse {
-                jsonrpc: "2.0".to_string(),
-                result: None,
-                error: Some(JsonRpcError {
-                    code: -32602, // Standard JSON-RPC error code for invalid params
-                    message: format!("Invalid params: {}", e),
-                }),
-                id: request.id,
-            });
+            return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e));
+        }
+    };
+    
+    submit_transaction(state, tx, request.id).await
+}
+
+/// Handles the "eth_sendRawTransaction" RPC method
+/// 
+/// Decodes a signed Ethereum transaction and submits it like `sendTransaction`.
+/// Answers the way Ethereum nodes do: with the transaction hash if it was
+/// accepted, or a `TRANSACTION_REJECTED` error with the reason if it failed
+/// validation. The hash is the sequencer's own transaction hash, not the
+/// Ethereum one.
+async fn handle_send_raw_transaction(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let (raw,): (Bytes,) = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let tx = match decode_raw_transaction(&raw, now) {
+        Ok(tx) => tx,
+        Err(e) => {
+            warn!("Failed to decode raw transaction: {}", e);
+            return error_response(request.id, INVALID_PARAMS, e.to_string());
         }
     };
     
+    let Json(response) = submit_transaction(state, tx, request.id).await;
+    let confirmation = response
+        .result
+        .clone()
+        .and_then(|result| serde_json::from_value::<SoftConfirmation>(result).ok());
+    match confirmation {
+        Some(SoftConfirmation { tx_hash, status: ConfirmationStatus::Accepted, .. }) => {
+            success_response(response.id, serde_json::to_value(tx_hash).unwrap())
+        }
+        Some(SoftConfirmation { status: ConfirmationStatus::Rejected { reason }, .. }) => {
+            error_response(response.id, TRANSACTION_REJECTED, reason)
+        }
+        // Already an error response
+        None => Json(response),
+    }
+}
+
+/// Submit a parsed transaction: steps 2-10 of `handle_send_transaction`
+async fn submit_transaction(
+    state: AppState,
+    mut tx: UserTransaction,
+    id: Value,
+) -> Json<JsonRpcResponse> {
     // Compute the transaction hash for logging and tracking
     let tx_hash = tx.hash();
     info!("Processing transaction {:?} from {:?}", tx_hash, tx.from);
     
+    // Refuse banned senders before spending any validation (or a registry write) on them
+    if let Some(remaining) = state.sender_bans.as_ref().and_then(|bans| bans.remaining(&tx.from)) {
+        warn!("Transaction {:?} rejected: sender {:?} is banned", tx_hash, tx.from);
+        return error_response(
+            id,
+            SENDER_BANNED,
+            format!("Sender {:?} is temporarily banned for {}s", tx.from, remaining.as_secs().max(1)),
+        );
+    }
+    
+    // A priority class stands for a deadline counted from now
+    if let Err(e) = state.validation.validator().resolve_priority_class(&mut tx) {
+        warn!("Transaction {:?} rejected: {}", tx_hash, e);
+        return error_response(id, INVALID_PARAMS, e.to_string());
+    }
+    
+    // Ingress nodes hand the transaction to the central sequencer
+    if let Some(remote_pool) = state.remote_pool.clone() {
+        return handle_forward_transaction(state, remote_pool, tx, id).await;
+    }
+    
+    // Reject re-submissions before validation, which would otherwise report a
+    // misleading nonce error (the original already consumed the nonce)
+    if state.tx_pool.contains(&tx_hash).await {
+        warn!("Transaction {:?} already known", tx_hash);
+        return error_response(id, ALREADY_KNOWN, "already known".to_string());
+    }
+    
+    // Refuse denylisted senders without spending a signature check on them
+    if state.tx_pool.is_denied(&tx.from).await {
+        return reject_denied(&state, &tx, id).await;
+    }
+    if !state.tx_pool.is_allowlisted(&tx.from).await {
+        return reject_not_allowlisted(&state, &tx, id).await;
+    }
+    
     // Step 2: Validate the transaction (signature, nonce, balance)
-    match state.validator.validate(&tx).await {
+    // The signature is checked on a worker; the rest waits for this submission's turn
+    match state.validation.validate(&tx).await {
         // Validation succeeded - process the transaction
-        Ok(()) => {
+        Ok(turn) => {
             info!("Transaction {:?} validated successfully", tx_hash);
             
-            // Step 3: Update state cache to reflect the new nonce
-            // This prevents nonce reuse attacks and ensures sequential ordering
-            state.state_cache.increment_nonce(&tx.from).await;
-            
-            // Step 4: Add the transaction to the pool for batching
-            state.tx_pool.add(tx.clone()).await;
+            // Step 3: Add the transaction to the pool for batching
+            // The pool may evict a cheaper transaction, or refuse this one if full
+            match state.tx_pool.add(tx.clone()).await {
+                Ok(Some(evicted)) => {
+                    warn!("Transaction {:?} evicted from full pool", evicted.hash());
+                    record_outcome(&state, &evicted, TransactionOutcome::Evicted).await;
+                }
+                Ok(None) => {}
+                Err(PoolError::AlreadyKnown { .. }) => {
+                    // Lost a race with a concurrent identical submission
+                    return error_response(id, ALREADY_KNOWN, "already known".to_string());
+                }
+                Err(PoolError::Denied { .. }) => {
+                    // Denylisted while the transaction was being validated
+                    return reject_denied(&state, &tx, id).await;
+                }
+                Err(PoolError::NotAllowlisted { .. }) => {
+                    // Revoked while the transaction was being validated
+                    return reject_not_allowlisted(&state, &tx, id).await;
+                }
+                Err(pool_error) => {
+                    warn!("Transaction {:?} rejected by pool: {}", tx_hash, pool_error);
+                    record_outcome(&state, &tx, TransactionOutcome::Rejected {
+                        reason: pool_error.to_string(),
+                    }).await;
+                    let code = match pool_error {
+                        PoolError::Underpriced { .. } => UNDERPRICED,
+                        _ => POOL_FULL,
+                    };
+                    return error_response(id, code, pool_error.to_string());
+                }
+            }
             info!("Transaction {:?} added to pool", tx_hash);
             
+            // Step 4: Update state cache to reflect the new nonce
+            // This prevents nonce reuse attacks and ensures sequential ordering
+            state.state_cache.increment_nonce(&tx.from, tx.hash()).await;
+            // Handed off; the next submission can be checked against the new state
+            drop(turn);
+            record_outcome(&state, &tx, TransactionOutcome::Accepted).await;
+            
             // Step 5: Create a soft confirmation to send back to the client
             // This gives the user immediate feedback that their transaction was accepted
-            let confirmation = SoftConfirmation {
+            let confirmation = attest_confirmation(&state, SoftConfirmation {
                 tx_hash,
                 status: ConfirmationStatus::Accepted,
                 timestamp: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_secs(),
-            };
+                attestation: None,
+            });
             
             // Return the soft confirmation as a successful result
             Json(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 result: Some(serde_json::to_value(confirmation).unwrap()),
                 error: None,
-                id: request.id,
+                id,
             })
         }
         // Validation failed - reject the transaction
@@ -254,6 +822,14 @@ async fn handle_send_transaction(
                 "Transaction {:?} validation failed: {}",
                 tx_hash, validation_error
             );
+            record_validation_failure(&state, &tx, &validation_error);
+            record_outcome(&state, &tx, TransactionOutcome::Rejected {
+                reason: validation_error.to_string(),
+            }).await;
+            // Same error as a pool refusal, so wallets can re-price and retry
+            if let ValidationError::BelowFeeFloor { .. } = validation_error {
+                return error_response(id, UNDERPRICED, validation_error.to_string());
+            }
             
             // Create a rejection confirmation with the failure reason
             // This informs the user why their transaction was rejected
@@ -266,6 +842,7 @@ async fn handle_send_transaction(
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_secs(),
+                attestation: None,
             };
             
             // Return the rejection confirmation as a successful response
@@ -274,8 +851,1678 @@ async fn handle_send_transaction(
                 jsonrpc: "2.0".to_string(),
                 result: Some(serde_json::to_value(confirmation).unwrap()),
                 error: None,
-                id: request.id,
+                id,
             })
         }
     }
-}
\ No newline at end of file
+}
+
+/// Handles the "sendUserOperation" RPC method
+/// 
+/// Validates a user operation from a smart-contract wallet and adds it to the
+/// user operation lane. Answers with a soft confirmation, like `sendTransaction`.
+async fn handle_send_user_operation(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(user_ops) = state.user_ops.clone() else {
+        return error_response(request.id, METHOD_NOT_FOUND, "User operations are not enabled".to_string());
+    };
+    let op: UserOperation = match serde_json::from_value(request.params) {
+        Ok(op) => op,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    let op_hash = op.hash();
+    info!("Processing user operation {:?} from wallet {:?}", op_hash, op.sender);
+    
+    if let Some(remaining) = state.sender_bans.as_ref().and_then(|bans| bans.remaining(&op.sender)) {
+        return error_response(
+            request.id,
+            SENDER_BANNED,
+            format!("Sender {:?} is temporarily banned for {}s", op.sender, remaining.as_secs().max(1)),
+        );
+    }
+    if user_ops.lane.contains(&op_hash).await {
+        return error_response(request.id, ALREADY_KNOWN, "already known".to_string());
+    }
+    if state.tx_pool.is_denied(&op.sender).await {
+        return error_response(request.id, SENDER_DENIED, PoolError::Denied { sender: op.sender }.to_string());
+    }
+    if !state.tx_pool.is_allowlisted(&op.sender).await {
+        let reason = PoolError::NotAllowlisted { sender: op.sender }.to_string();
+        return error_response(request.id, SENDER_NOT_ALLOWLISTED, reason);
+    }
+    
+    let admission = user_ops.admission.lock().await;
+    let outcome = match user_ops.validator.validate(&op).await {
+        Ok(()) => match user_ops.lane.add(op.clone()).await {
+            Ok(()) => {
+                state.state_cache.increment_nonce(&op.sender, op.hash()).await;
+                TransactionOutcome::Accepted
+            }
+            Err(PoolError::AlreadyKnown { .. }) => {
+                return error_response(request.id, ALREADY_KNOWN, "already known".to_string());
+            }
+            Err(pool_error) => {
+                warn!("User operation {:?} rejected by lane: {}", op_hash, pool_error);
+                drop(admission);
+                record_user_op_outcome(&state, &op, TransactionOutcome::Rejected { reason: pool_error.to_string() }).await;
+                return error_response(request.id, POOL_FULL, pool_error.to_string());
+            }
+        },
+        Err(validation_error) => {
+            warn!("User operation {:?} validation failed: {}", op_hash, validation_error);
+            // The account hook authenticates the wallet before its nonce is checked
+            if let Some(bans) = &state.sender_bans
+                && bans.record_failure(op.sender, &validation_error)
+            {
+                warn!("Wallet {:?} temporarily banned after repeated validation failures", op.sender);
+            }
+            TransactionOutcome::Rejected { reason: validation_error.to_string() }
+        }
+    };
+    drop(admission);
+    record_user_op_outcome(&state, &op, outcome.clone()).await;
+    
+    let status = match outcome {
+        TransactionOutcome::Rejected { reason } => ConfirmationStatus::Rejected { reason },
+        _ => ConfirmationStatus::Accepted,
+    };
+    let confirmation = attest_confirmation(&state, SoftConfirmation {
+        tx_hash: op_hash,
+        status,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        attestation: None,
+    });
+    success_response(request.id, serde_json::to_value(confirmation).unwrap())
+}
+
+/// Record what happened to a user operation in the registry
+async fn record_user_op_outcome(state: &AppState, op: &UserOperation, outcome: TransactionOutcome) {
+    let record = TransactionRecord {
+        tx_hash: op.hash(),
+        sender: op.sender,
+        nonce: op.nonce,
+        outcome,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+    if let Err(e) = state.registry.record_transaction(record).await {
+        error!("Failed to record outcome for user operation {:?}: {:?}", op.hash(), e);
+    }
+}
+
+/// Count a validation failure towards a temporary ban of the sender
+/// 
+/// Nothing is counted unless the transaction's signature verifies: the
+/// sender of any other failure is unauthenticated.
+fn record_validation_failure(state: &AppState, tx: &UserTransaction, error: &ValidationError) {
+    if let Some(bans) = &state.sender_bans
+        && bans.record_rejection(state.validation.validator(), tx, error)
+    {
+        warn!("Sender {:?} temporarily banned after repeated validation failures", tx.from);
+    }
+}
+
+/// Attach this sequencer's BLS attestation to an accepted confirmation
+/// 
+/// Rejections are not attested: they commit the sequencer to nothing.
+fn attest_confirmation(state: &AppState, mut confirmation: SoftConfirmation) -> SoftConfirmation {
+    if let Some(attestor) = &state.attestor
+        && matches!(confirmation.status, ConfirmationStatus::Accepted)
+    {
+        confirmation.attestation = Some(attestor.attest(confirmation.digest()));
+    }
+    confirmation
+}
+
+/// Refuse a transaction from a denylisted sender
+async fn reject_denied(state: &AppState, tx: &UserTransaction, id: Value) -> Json<JsonRpcResponse> {
+    let reason = PoolError::Denied { sender: tx.from }.to_string();
+    warn!("Transaction {:?} rejected: {}", tx.hash(), reason);
+    record_outcome(state, tx, TransactionOutcome::Rejected { reason: reason.clone() }).await;
+    error_response(id, SENDER_DENIED, reason)
+}
+
+/// Refuse a transaction from a sender missing from the allowlist (permissioned mode)
+async fn reject_not_allowlisted(state: &AppState, tx: &UserTransaction, id: Value) -> Json<JsonRpcResponse> {
+    let reason = PoolError::NotAllowlisted { sender: tx.from }.to_string();
+    warn!("Transaction {:?} rejected: {}", tx.hash(), reason);
+    record_outcome(state, tx, TransactionOutcome::Rejected { reason: reason.clone() }).await;
+    error_response(id, SENDER_NOT_ALLOWLISTED, reason)
+}
+
+/// Forward a submitted transaction to the central sequencer (ingress nodes)
+/// 
+/// Only the signature is checked locally: ingress nodes hold no account state,
+/// so nonce and balance checks happen upstream. The upstream soft confirmation
+/// is returned as-is; upstream JSON-RPC errors are passed through with their codes.
+async fn handle_forward_transaction(
+    state: AppState,
+    remote_pool: Arc<RemotePool>,
+    tx: UserTransaction,
+    id: Value,
+) -> Json<JsonRpcResponse> {
+    let tx_hash = tx.hash();
+    
+    if let Err(validation_error) = state.validation.verify_signature(&tx).await {
+        warn!("Transaction {:?} validation failed: {}", tx_hash, validation_error);
+        record_validation_failure(&state, &tx, &validation_error);
+        let confirmation = SoftConfirmation {
+            tx_hash,
+            status: ConfirmationStatus::Rejected {
+                reason: validation_error.to_string(),
+            },
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            attestation: None,
+        };
+        return success_response(id, serde_json::to_value(confirmation).unwrap());
+    }
+    
+    match remote_pool.forward(&tx).await {
+        Ok(confirmation) => {
+            info!("Transaction {:?} forwarded to {}", tx_hash, remote_pool.upstream_url());
+            success_response(id, serde_json::to_value(confirmation).unwrap())
+        }
+        Err(RemoteError::AlreadyKnown { .. }) => {
+            error_response(id, ALREADY_KNOWN, "already known".to_string())
+        }
+        Err(remote_error @ RemoteError::Busy { .. }) => {
+            warn!("Transaction {:?} refused: {}", tx_hash, remote_error);
+            error_response(id, POOL_FULL, remote_error.to_string())
+        }
+        Err(RemoteError::Rpc { code, message }) => error_response(id, code, message),
+        Err(remote_error @ RemoteError::Unavailable { .. }) => {
+            error!("Failed to forward transaction {:?}: {}", tx_hash, remote_error);
+            error_response(id, UPSTREAM_UNAVAILABLE, remote_error.to_string())
+        }
+    }
+}
+
+/// Transactions forwarded by ingress nodes over gRPC
+/// 
+/// Authorized and submitted exactly like a `sendTransaction` call carrying
+/// the same API key, with JSON-RPC errors passed back as the reply's code.
+#[async_trait::async_trait]
+impl ForwardHandler for AppState {
+    async fn send_transaction(&self, request: tonic::Request<ForwardRequest>) -> ForwardReply {
+        let headers = request.metadata().clone().into_headers();
+        let Json(response) = match serde_json::from_slice(&request.into_inner().transaction) {
+            Ok(params) => {
+                let request = JsonRpcRequest {
+                    jsonrpc: "2.0".to_string(),
+                    method: "sendTransaction".to_string(),
+                    params,
+                    id: Value::Null,
+                };
+                match authorize(self, &headers, &request).await {
+                    Ok(()) => handle_send_transaction(self.clone(), request).await,
+                    Err(response) => response,
+                }
+            }
+            Err(e) => error_response(Value::Null, INVALID_PARAMS, format!("Invalid params: {}", e)),
+        };
+        match response.error {
+            Some(error) => ForwardReply { error_code: error.code, error_message: error.message, ..ForwardReply::default() },
+            None => ForwardReply {
+                confirmation: serde_json::to_vec(&response.result.unwrap_or_default()).unwrap(),
+                ..ForwardReply::default()
+            },
+        }
+    }
+}
+
+/// Record a transaction outcome in the registry
+/// 
+/// Registry failures are logged rather than surfaced: losing a history entry
+/// must never cause a transaction submission to fail.
+async fn record_outcome(state: &AppState, tx: &UserTransaction, outcome: TransactionOutcome) {
+    let record = TransactionRecord {
+        tx_hash: tx.hash(),
+        sender: tx.from,
+        nonce: tx.nonce,
+        outcome,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+    if let Err(e) = state.registry.record_transaction(record).await {
+        error!("Failed to record outcome for {:?}: {:?}", tx.hash(), e);
+    }
+}
+
+/// Parameters for the "getNonceHistory" RPC method
+#[derive(Debug, Deserialize)]
+struct NonceHistoryParams {
+    sender: Address,
+    nonce: u64,
+    /// Only include observations made by the time this batch was sealed
+    #[serde(default, rename = "withBatch")]
+    with_batch: Option<u64>,
+}
+
+/// Parameters for the "getBalance" and "getNonce" RPC methods
+#[derive(Debug, Deserialize)]
+struct AccountParams {
+    address: Address,
+    /// Read the state as of this batch instead of the live state
+    #[serde(default, rename = "withBatch")]
+    with_batch: Option<u64>,
+}
+
+/// Resolve a `withBatch` context parameter to the batch it pins reads to
+/// 
+/// # Returns
+/// `Err(response)` with the error to send back if the batch isn't sealed
+async fn resolve_batch(
+    state: &AppState,
+    batch_id: u64,
+    id: &Value,
+) -> Result<BatchMetadata, Json<JsonRpcResponse>> {
+    match state.registry.batch(batch_id).await {
+        Ok(Some(batch)) => Ok(batch),
+        Ok(None) => Err(error_response(id.clone(), INVALID_PARAMS, format!("Batch {} is not sealed", batch_id))),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            Err(error_response(id.clone(), INTERNAL_ERROR, "Registry query failed".to_string()))
+        }
+    }
+}
+
+/// Read an account, live or as of the requested batch
+/// 
+/// Historical reads resolve to the latest snapshot at or before the batch;
+/// accounts no batch had touched by then read as empty. Batches whose
+/// history was pruned are refused.
+async fn read_account(
+    state: &AppState,
+    params: &AccountParams,
+    id: &Value,
+) -> Result<AccountState, Json<JsonRpcResponse>> {
+    let Some(batch_id) = params.with_batch else {
+        return Ok(state.state_reader.account(&params.address).await);
+    };
+    resolve_batch(state, batch_id, id).await?;
+    match state.registry.pruned_before().await {
+        Ok(pruned_before) if batch_id < pruned_before => {
+            let reason = format!("History of batch {} is pruned (kept from batch {})", batch_id, pruned_before);
+            return Err(error_response(id.clone(), INVALID_PARAMS, reason));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            return Err(error_response(id.clone(), INTERNAL_ERROR, "Registry query failed".to_string()));
+        }
+    }
+    
+    match state.registry.account_at_batch(params.address, batch_id).await {
+        Ok(account) => Ok(account.unwrap_or(AccountState {
+            address: params.address,
+            balance: U256::zero(),
+            nonce: 0,
+            ..Default::default()
+        })),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            Err(error_response(id.clone(), INTERNAL_ERROR, "Registry query failed".to_string()))
+        }
+    }
+}
+
+/// Parameters for the "proposer_submitBatch" RPC method
+/// 
+/// `transactions` lists the batch in its final order: the queued forced
+/// transactions first (by `l1_tx_hash`), then pending transaction hashes.
+#[derive(Debug, Deserialize)]
+struct SubmitBatchParams {
+    transactions: Vec<H256>,
+}
+
+/// Handles the "proposer_submitBatch" RPC method
+/// 
+/// Verifies the proposal against the forced queue and the pool, seals it in
+/// the proposer's order, and returns the `SignedBatch`.
+async fn handle_submit_batch(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(proposer) = state.proposer else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Proposer API not enabled".to_string());
+    };
+    let params: SubmitBatchParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let batch = match proposer.orchestrator.seal_proposal(&params.transactions).await {
+        Ok(batch) => batch,
+        Err(e) => {
+            warn!("Rejected proposed batch: {}", e);
+            let code = match e {
+                ProposalError::Empty | ProposalError::Duplicate { .. } => INVALID_PARAMS,
+                _ => PROPOSAL_REJECTED,
+            };
+            return error_response(request.id, code, e.to_string());
+        }
+    };
+    
+    let batch_hash = batch.hash();
+    match proposer.signer.sign_hash(batch_hash) {
+        Ok(signature) => {
+            info!("Sealed proposed batch #{} ({:?})", batch.batch_id, batch_hash);
+            let signed = SignedBatch {
+                signer: proposer.signer.address(),
+                batch,
+                batch_hash,
+                signature,
+                attestation: state.attestor.as_ref().map(|attestor| attestor.attest(batch_hash)),
+            };
+            success_response(request.id, serde_json::to_value(signed).unwrap())
+        }
+        Err(e) => {
+            // The batch is already sealed; only the signature is missing
+            error!("Failed to sign proposed batch #{}: {:?}", batch.batch_id, e);
+            error_response(request.id, INTERNAL_ERROR, format!("Failed to sign batch: {}", e))
+        }
+    }
+}
+
+/// Handles the "estimateFee" RPC method
+/// 
+/// Advertises the pool's current fee floor, which rises with congestion, and
+/// the base fee, so wallets can price transactions that will be admitted.
+/// With the gas oracle, it also reports the L1 fees and a transaction's share
+/// of posting a full batch to L1.
+async fn handle_estimate_fee(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let l1_fees = state.gas_oracle.as_ref().and_then(|gas| gas.oracle.fees());
+    let estimate = FeeEstimate {
+        min_gas_price: state.tx_pool.fee_floor().await,
+        base_fee: state.validation.validator().base_fee(),
+        l1_base_fee: l1_fees.map(|fees| fees.base_fee),
+        l1_blob_base_fee: l1_fees.and_then(|fees| fees.blob_base_fee),
+        l1_fee_per_tx: state.gas_oracle.as_ref().and_then(|gas| gas.oracle.cost_per_tx(gas.batch_size)),
+    };
+    success_response(request.id, serde_json::to_value(estimate).unwrap())
+}
+
+/// Handles the "verifyAttestation" RPC method
+/// 
+/// Checks a BLS attestation, typically aggregated from several sequencers,
+/// against the configured committee. Returns `true`, or an `INVALID_PARAMS`
+/// error saying why the attestation does not hold.
+async fn handle_verify_attestation(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(committee) = state.committee else {
+        return error_response(request.id, METHOD_NOT_FOUND, "No attestation committee configured".to_string());
+    };
+    let attestation: BlsAttestation = match serde_json::from_value(request.params) {
+        Ok(attestation) => attestation,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    match committee.verify(&attestation) {
+        Ok(()) => success_response(request.id, Value::Bool(true)),
+        Err(e) => error_response(request.id, INVALID_PARAMS, e.to_string()),
+    }
+}
+
+/// Handles the "validateBundle" RPC method
+/// 
+/// Validates a list of transactions in order, each against the state the
+/// earlier ones would leave, without admitting any of them. Lets searchers
+/// check that a bundle would be accepted before submitting it.
+async fn handle_validate_bundle(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let txs: Vec<UserTransaction> = match serde_json::from_value(request.params) {
+        Ok(txs) => txs,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    if txs.len() > state.api_config.max_bundle_size {
+        return error_response(
+            request.id,
+            INVALID_PARAMS,
+            format!("Bundle of {} transactions exceeds the maximum of {}", txs.len(), state.api_config.max_bundle_size),
+        );
+    }
+    
+    let outcomes = state.validation.validator().validate_bundle(&txs).await;
+    let results: Vec<BundleTxResult> = txs
+        .iter()
+        .zip(outcomes)
+        .map(|(tx, outcome)| BundleTxResult {
+            tx_hash: tx.hash(),
+            error: outcome.err().map(|e| e.to_string()),
+        })
+        .collect();
+    let validation = BundleValidation {
+        valid: results.iter().all(|result| result.error.is_none()),
+        results,
+    };
+    success_response(request.id, serde_json::to_value(validation).unwrap())
+}
+
+/// Result of the "sendBundle" RPC method
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleReceipt {
+    bundle_hash: H256,
+    /// Hashes of the bundle's transactions, in order
+    tx_hashes: Vec<H256>,
+}
+
+/// Handles the "sendBundle" RPC method
+/// 
+/// Takes the same parameters as "validateBundle", and queues the bundle if
+/// every transaction is valid against the state the earlier ones leave. The
+/// bundle is sealed whole, its transactions contiguous and in order, or not
+/// at all. Its senders may not have transactions pending in the pool, which
+/// would have to be sealed first.
+async fn handle_send_bundle(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(lane) = state.bundles.clone() else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Bundles not enabled on this node".to_string());
+    };
+    let txs: Vec<UserTransaction> = match serde_json::from_value(request.params) {
+        Ok(txs) => txs,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    if txs.is_empty() || txs.len() > state.api_config.max_bundle_size {
+        return error_response(
+            request.id,
+            INVALID_PARAMS,
+            format!("A bundle holds 1 to {} transactions, not {}", state.api_config.max_bundle_size, txs.len()),
+        );
+    }
+    let bundle = Bundle { transactions: txs };
+    let bundle_hash = bundle.hash();
+    info!("Processing bundle {:?} of {} transactions", bundle_hash, bundle.len());
+    
+    for tx in &bundle.transactions {
+        if let Some(remaining) = state.sender_bans.as_ref().and_then(|bans| bans.remaining(&tx.from)) {
+            return error_response(
+                request.id,
+                SENDER_BANNED,
+                format!("Sender {:?} is temporarily banned for {}s", tx.from, remaining.as_secs().max(1)),
+            );
+        }
+        if state.tx_pool.contains(&tx.hash()).await || lane.contains_transaction(&tx.hash()).await {
+            return error_response(request.id, ALREADY_KNOWN, "already known".to_string());
+        }
+        if state.tx_pool.is_denied(&tx.from).await {
+            return error_response(request.id, SENDER_DENIED, PoolError::Denied { sender: tx.from }.to_string());
+        }
+        if !state.tx_pool.is_allowlisted(&tx.from).await {
+            let reason = PoolError::NotAllowlisted { sender: tx.from }.to_string();
+            return error_response(request.id, SENDER_NOT_ALLOWLISTED, reason);
+        }
+    }
+    
+    let turn = match state.validation.validate_bundle(&bundle.transactions).await {
+        Ok(turn) => turn,
+        Err((index, validation_error)) => {
+            let tx = &bundle.transactions[index];
+            warn!("Bundle {:?} rejected: transaction {:?} invalid: {}", bundle_hash, tx.hash(), validation_error);
+            record_validation_failure(&state, tx, &validation_error);
+            let reason = format!("Transaction {} ({:?}) invalid: {}", index, tx.hash(), validation_error);
+            for tx in &bundle.transactions {
+                record_outcome(&state, tx, TransactionOutcome::Rejected { reason: reason.clone() }).await;
+            }
+            return error_response(request.id, TRANSACTION_REJECTED, reason);
+        }
+    };
+    // Checked in turn, so no submission slips into the pool meanwhile
+    for tx in &bundle.transactions {
+        if !state.tx_pool.pending_nonces(&tx.from).await.is_empty() {
+            let reason = PoolError::PendingOutsideBundle { sender: tx.from }.to_string();
+            return error_response(request.id, TRANSACTION_REJECTED, reason);
+        }
+    }
+    match lane.add(bundle.clone()).await {
+        Ok(()) => {}
+        Err(PoolError::AlreadyKnown { .. }) => {
+            return error_response(request.id, ALREADY_KNOWN, "already known".to_string());
+        }
+        Err(pool_error) => {
+            warn!("Bundle {:?} rejected by lane: {}", bundle_hash, pool_error);
+            return error_response(request.id, POOL_FULL, pool_error.to_string());
+        }
+    }
+    for tx in &bundle.transactions {
+        state.state_cache.increment_nonce(&tx.from, tx.hash()).await;
+    }
+    drop(turn);
+    for tx in &bundle.transactions {
+        record_outcome(&state, tx, TransactionOutcome::Accepted).await;
+    }
+    info!("Bundle {:?} queued", bundle_hash);
+    
+    let receipt = BundleReceipt {
+        bundle_hash,
+        tx_hashes: bundle.transactions.iter().map(UserTransaction::hash).collect(),
+    };
+    success_response(request.id, serde_json::to_value(receipt).unwrap())
+}
+
+/// Parameters for the "getBundleStatus" RPC method
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleStatusParams {
+    bundle_hash: H256,
+}
+
+/// Result of the "getBundleStatus" RPC method
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+enum BundleStatus {
+    /// Waiting in the lane
+    Pending,
+    /// Sealed at `position` of batch `batchId`
+    #[serde(rename_all = "camelCase")]
+    Included { batch_id: u64, position: usize, size: usize },
+    /// Never queued, or dropped
+    Unknown,
+}
+
+/// Handles the "getBundleStatus" RPC method
+/// 
+/// Reports whether a bundle is pending, or where it was sealed.
+async fn handle_get_bundle_status(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(lane) = state.bundles.clone() else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Bundles not enabled on this node".to_string());
+    };
+    let params: BundleStatusParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let status = match state.registry.bundle_inclusion(params.bundle_hash).await {
+        Ok(Some(inclusion)) => BundleStatus::Included {
+            batch_id: inclusion.batch_id,
+            position: inclusion.position,
+            size: inclusion.size,
+        },
+        Ok(None) if lane.contains(&params.bundle_hash).await => BundleStatus::Pending,
+        Ok(None) => BundleStatus::Unknown,
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            return error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string());
+        }
+    };
+    success_response(request.id, serde_json::to_value(status).unwrap())
+}
+
+/// Handles the "getBalance" RPC method
+/// 
+/// Accepts an optional `withBatch` to read the balance as of a sealed batch,
+/// so indexers can backfill consistently while new batches are produced.
+async fn handle_get_balance(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: AccountParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match read_account(&state, &params, &request.id).await {
+        Ok(account) => success_response(request.id, serde_json::to_value(account.balance).unwrap()),
+        Err(response) => response,
+    }
+}
+
+/// Handles the "getNonce" RPC method
+/// 
+/// Without `withBatch`, returns the next nonce the sequencer will accept
+/// (pending transactions included). With it, returns the next nonce as of
+/// that batch, counting only batched transactions.
+async fn handle_get_nonce(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: AccountParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match read_account(&state, &params, &request.id).await {
+        Ok(account) => success_response(request.id, Value::from(account.nonce)),
+        Err(response) => response,
+    }
+}
+
+/// Parameters for the "getAccounts" RPC method
+#[derive(Debug, Deserialize)]
+struct AccountsParams {
+    addresses: Vec<Address>,
+}
+
+/// Handles the "getAccounts" RPC method
+/// 
+/// Reads the balances and nonces of several accounts at one point in time,
+/// with the latest sealed batch and the state root they reflect.
+async fn handle_get_accounts(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: AccountsParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    if params.addresses.len() > state.api_config.max_accounts_per_read {
+        return error_response(
+            request.id,
+            INVALID_PARAMS,
+            format!("Read of {} accounts exceeds the maximum of {}",
+                    params.addresses.len(), state.api_config.max_accounts_per_read),
+        );
+    }
+    
+    let snapshot = state.state_reader.snapshot(&params.addresses).await;
+    success_response(request.id, serde_json::to_value(snapshot).unwrap())
+}
+
+/// Parameters for the "getStorageAt" RPC method
+#[derive(Debug, Deserialize)]
+struct StorageParams {
+    address: Address,
+    slot: H256,
+}
+
+/// Handles the "getStorageAt" RPC method
+/// 
+/// Returns the value of a contract account's storage slot (zero if never set).
+async fn handle_get_storage_at(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: StorageParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let value = state.state_reader.storage_at(&params.address, &params.slot).await;
+    success_response(request.id, serde_json::to_value(value).unwrap())
+}
+
+/// Handles the "getGenesis" RPC method
+/// 
+/// Returns the chain ID and genesis hash the chain was started from, or
+/// `null` if it was started without a genesis file.
+async fn handle_get_genesis(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    match state.registry.genesis().await {
+        Ok(genesis) => success_response(request.id, serde_json::to_value(genesis).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Parameters for the "getBatchWitness" RPC method
+#[derive(Debug, Deserialize)]
+struct BatchWitnessParams {
+    #[serde(rename = "batchId")]
+    batch_id: u64,
+}
+
+/// Handles the "getBatchWitness" RPC method
+/// 
+/// Returns the execution witness of a sealed batch: every account it touches,
+/// with Merkle proofs against the state roots before and after execution, or
+/// `null` if none was recorded.
+async fn handle_get_batch_witness(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: BatchWitnessParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.registry.batch_witness(params.batch_id).await {
+        Ok(witness) => success_response(request.id, serde_json::to_value(witness).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Handles the "getBatchStatus" RPC method
+/// 
+/// Returns where a batch is in its L1 lifecycle (sealed, submitted, committed,
+/// verified, finalized or failed), with the L1 transactions committing and
+/// verifying it, or `null` if the batch is unknown. Takes the same parameters as "getBatchWitness".
+async fn handle_get_batch_status(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: BatchWitnessParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.registry.batch_status(params.batch_id).await {
+        Ok(status) => success_response(request.id, serde_json::to_value(status).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Parameters for the "getDepositStatus" RPC method
+#[derive(Debug, Deserialize)]
+struct DepositStatusParams {
+    #[serde(rename = "l1TxHash")]
+    l1_tx_hash: H256,
+    #[serde(rename = "logIndex", default)]
+    log_index: Option<u64>,
+}
+
+/// Handles the "getDepositStatus" RPC method
+/// 
+/// Returns the deposits emitted by an L1 transaction, each with its stage
+/// (detected, confirmed, included, credited or reorged) and the batch
+/// crediting it, and how final its L1 block is (unsafe, safe or finalized)
+/// if the finality tags are tracked. With `logIndex`, only the deposit of
+/// that log is returned. The list is empty if the sequencer has not seen any.
+async fn handle_get_deposit_status(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: DepositStatusParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.registry.deposit_status(params.l1_tx_hash).await {
+        Ok(mut deposits) => {
+            if let Some(log_index) = params.log_index {
+                deposits.retain(|deposit| deposit.l1_log_index == log_index);
+            }
+            success_response(request.id, serde_json::to_value(deposits).unwrap())
+        }
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Handles the "getL1Finality" RPC method
+/// 
+/// Returns the latest L1 blocks tagged `safe` and `finalized`, each `null`
+/// until the finality tracker has read it.
+async fn handle_get_l1_finality(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    match state.registry.l1_finality().await {
+        Ok(tags) => success_response(request.id, serde_json::to_value(tags).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Handles the "getSafeMode" RPC method
+/// 
+/// Returns whether the sequencer is sealing forced transactions only, and
+/// why: the rollup contract's escape hatch is open, or commitments have been
+/// failing for too long. `null` if safe mode is not configured.
+async fn handle_get_safe_mode(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let status = state.safe_mode.as_ref().map(|safe_mode| safe_mode.status());
+    success_response(request.id, serde_json::to_value(status).unwrap())
+}
+
+/// Handles the "getWithdrawalProof" RPC method
+/// 
+/// Returns the withdrawal a forced exit made, with the Merkle proof of it
+/// against its batch's withdrawal root, or `null` if the exit has not been
+/// debited. Takes the same parameters as "getDepositStatus"; without
+/// `logIndex`, the transaction's first forced exit is proven. The withdrawal
+/// can be claimed on L1 once "getBatchStatus" reports its batch committed.
+async fn handle_get_withdrawal_proof(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: DepositStatusParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let proof = match state.registry.withdrawal(params.l1_tx_hash, params.log_index).await {
+        Ok(Some(withdrawal)) => state.registry
+            .batch_withdrawals(withdrawal.batch_id)
+            .await
+            .map(|withdrawals| WithdrawalProof::new(&withdrawals, withdrawal.index as usize)),
+        Ok(None) => Ok(None),
+        Err(e) => Err(e),
+    };
+    match proof {
+        Ok(proof) => success_response(request.id, serde_json::to_value(proof).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Result of the "submitBid" RPC method
+#[derive(Debug, Serialize)]
+struct BidReceipt {
+    #[serde(rename = "txHash")]
+    tx_hash: H256,
+    /// Whether the bid replaced an earlier one for the transaction
+    replaced: bool,
+}
+
+/// Handles the "submitBid" RPC method
+/// 
+/// Places a sealed bid for a top slot of the next batch. The bid must be for
+/// a pending transaction and signed by its sender (see `AuctionBid::digest`);
+/// it stays sealed until the batch is, and a new bid for the same
+/// transaction replaces it. Only accepted while the policy is `Auction`.
+async fn handle_submit_bid(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(auction) = state.auction else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Auctions not available on this node".to_string());
+    };
+    let bid: AuctionBid = match serde_json::from_value(request.params) {
+        Ok(bid) => bid,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    if !matches!(auction.orchestrator.policy(), SchedulingPolicyType::Auction { .. }) {
+        return error_response(request.id, INVALID_PARAMS, "Top slots are not auctioned under the current policy".to_string());
+    }
+    if bid.amount.is_zero() {
+        return error_response(request.id, INVALID_PARAMS, "amount must be positive".to_string());
+    }
+    let Some(tx) = state.tx_pool.get(&bid.tx_hash).await else {
+        return error_response(request.id, INVALID_PARAMS, format!("Transaction {:?} is not pending", bid.tx_hash));
+    };
+    if bid.signature.recover(bid.digest(auction.chain_id)).ok() != Some(tx.from) {
+        return error_response(request.id, INVALID_PARAMS, "Bid not signed by the transaction's sender".to_string());
+    }
+    
+    let replaced = auction.orchestrator.auction_book().place(bid.tx_hash, tx.from, bid.amount);
+    success_response(request.id, serde_json::to_value(BidReceipt { tx_hash: bid.tx_hash, replaced }).unwrap())
+}
+
+/// Handles the "getAuctionResults" RPC method
+/// 
+/// Returns the bids that won a batch's top slots, top slot first, once the
+/// batch is sealed. Takes the same parameters as "getBatchWitness".
+async fn handle_get_auction_results(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: BatchWitnessParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.registry.auction_wins(params.batch_id).await {
+        Ok(wins) => success_response(request.id, serde_json::to_value(wins).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Parameters for the "getSchedulingMetrics" RPC method
+#[derive(Debug, Default, Deserialize)]
+struct SchedulingMetricsParams {
+    /// Batch to report (all recent batches if unset)
+    #[serde(default, rename = "batchId")]
+    batch_id: Option<u64>,
+}
+
+/// Handles the "getSchedulingMetrics" RPC method
+/// 
+/// Returns how the recent batches were reordered from arrival order, and
+/// their inclusion latency per sender, newest first. With a `batchId`,
+/// returns that batch's `BatchFairness`, or `null` if it is not recent.
+async fn handle_get_scheduling_metrics(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(orchestrator) = state.scheduling else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Scheduling not available on this node".to_string());
+    };
+    let params: SchedulingMetricsParams = if request.params.is_null() {
+        SchedulingMetricsParams::default()
+    } else {
+        match serde_json::from_value(request.params) {
+            Ok(params) => params,
+            Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+        }
+    };
+    
+    let fairness = orchestrator.fairness();
+    let result = match params.batch_id {
+        Some(batch_id) => serde_json::to_value(fairness.batch(batch_id)),
+        None => serde_json::to_value(fairness.recent()),
+    };
+    success_response(request.id, result.unwrap())
+}
+
+/// Handles the "sendEncryptedTransaction" RPC method
+/// 
+/// Adds a transaction encrypted to an epoch to the back of the encrypted
+/// lane. It is decrypted and validated once the epoch is over and its key is
+/// released; until then only its declared gas limit is known. Returns an
+/// `EncryptionReceipt` committing to its position in the lane.
+async fn handle_send_encrypted_transaction(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(lane) = state.encrypted else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Encrypted mempool not enabled on this node".to_string());
+    };
+    let encrypted: EncryptedTransaction = match serde_json::from_value(request.params) {
+        Ok(encrypted) => encrypted,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match lane.add(encrypted) {
+        Ok(receipt) => {
+            info!("Encrypted transaction {:?} queued at position {}", receipt.tx_hash, receipt.position);
+            success_response(request.id, serde_json::to_value(receipt).unwrap())
+        }
+        Err(PoolError::AlreadyKnown { .. }) => error_response(request.id, ALREADY_KNOWN, "already known".to_string()),
+        Err(e @ PoolError::EncryptedLaneFull { .. }) => error_response(request.id, POOL_FULL, e.to_string()),
+        Err(e) => error_response(request.id, INVALID_PARAMS, e.to_string()),
+    }
+}
+
+/// Parameters for the "submitDecryptionKey" RPC method
+#[derive(Debug, Deserialize)]
+struct DecryptionKeyParams {
+    epoch: u64,
+    /// Compressed BLS signature (96 bytes) of the epoch under the master key
+    key: Bytes,
+}
+
+/// Handles the "submitDecryptionKey" RPC method
+/// 
+/// Releases the decryption key of an epoch. Anyone may submit it, as it is
+/// checked against the master public key; encrypted transactions of the
+/// epoch are decrypted once it is over.
+async fn handle_submit_decryption_key(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(lane) = state.encrypted else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Encrypted mempool not enabled on this node".to_string());
+    };
+    let params: DecryptionKeyParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match lane.submit_key(params.epoch, params.key) {
+        Ok(()) => {
+            info!("Decryption key of epoch {} released", params.epoch);
+            success_response(request.id, Value::Bool(true))
+        }
+        Err(e) => error_response(request.id, INVALID_PARAMS, e.to_string()),
+    }
+}
+
+/// Result of the "getEncryptionInfo" RPC method
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionInfo {
+    /// Key to encrypt transactions to
+    master_public_key: Bytes,
+    epoch_ms: u64,
+    /// Earliest epoch a transaction can be encrypted to
+    current_epoch: u64,
+    /// Encrypted transactions waiting for their epoch key
+    pending: usize,
+}
+
+/// Handles the "getEncryptionInfo" RPC method
+/// 
+/// Returns what a wallet needs to encrypt a transaction.
+async fn handle_get_encryption_info(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(lane) = state.encrypted else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Encrypted mempool not enabled on this node".to_string());
+    };
+    let info = EncryptionInfo {
+        master_public_key: lane.master_key().clone(),
+        epoch_ms: lane.epoch_ms(),
+        current_epoch: lane.current_epoch(),
+        pending: lane.len(),
+    };
+    success_response(request.id, serde_json::to_value(info).unwrap())
+}
+
+/// Parameters for the "getDuplicateNonces" RPC method
+#[derive(Debug, Deserialize)]
+struct DuplicateNoncesParams {
+    sender: Address,
+}
+
+/// Handles the "getNonceHistory" RPC method
+/// 
+/// Returns every transaction ever observed for a (sender, nonce) pair,
+/// including rejected and evicted ones, with their outcomes in observation order.
+/// With `withBatch`, only observations made by the time that batch was sealed
+/// are returned (to the second, as observations carry second timestamps).
+async fn handle_get_nonce_history(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: NonceHistoryParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    // Pinned to a batch: drop observations made after it was sealed
+    let until = match params.with_batch {
+        Some(batch_id) => match resolve_batch(&state, batch_id, &request.id).await {
+            Ok(batch) => Some(batch.timestamp),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+    
+    match state.registry.transactions_by_sender_nonce(params.sender, params.nonce).await {
+        Ok(mut records) => {
+            if let Some(until) = until {
+                records.retain(|record| record.timestamp <= until);
+            }
+            success_response(request.id, serde_json::to_value(records).unwrap())
+        }
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Handles the "getDuplicateNonces" RPC method
+/// 
+/// Returns the nonces of a sender that were claimed by more than one distinct
+/// transaction hash.
+async fn handle_get_duplicate_nonces(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: DuplicateNoncesParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.registry.duplicate_nonces(params.sender).await {
+        Ok(nonces) => success_response(request.id, serde_json::to_value(nonces).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Parameters for the "admin_createApiKey" RPC method
+#[derive(Debug, Deserialize)]
+struct CreateApiKeyParams {
+    label: String,
+    #[serde(default)]
+    max_requests: Option<u64>,
+    #[serde(default)]
+    max_transactions: Option<u64>,
+}
+
+/// Parameters for the "admin_revokeApiKey" RPC method
+#[derive(Debug, Deserialize)]
+struct RevokeApiKeyParams {
+    key: String,
+}
+
+/// Handles the "admin_createApiKey" RPC method
+/// 
+/// Issues a new random API key with the requested quotas and returns it
+/// (including the secret key, which is only shown once).
+async fn handle_create_api_key(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: CreateApiKeyParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let usage = ApiKeyUsage {
+        key: auth::generate_api_key(),
+        label: params.label,
+        max_requests: params.max_requests,
+        max_transactions: params.max_transactions,
+        requests: 0,
+        transactions: 0,
+        window_start: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+    
+    match state.registry.create_api_key(&usage).await {
+        Ok(()) => {
+            info!("Issued API key for {}", usage.label);
+            success_response(request.id, serde_json::to_value(usage).unwrap())
+        }
+        Err(e) => {
+            error!("Failed to create API key: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Failed to create API key".to_string())
+        }
+    }
+}
+
+/// Handles the "admin_revokeApiKey" RPC method
+/// 
+/// Returns `true` if the key existed and was revoked.
+async fn handle_revoke_api_key(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: RevokeApiKeyParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.registry.revoke_api_key(&params.key).await {
+        Ok(revoked) => success_response(request.id, Value::Bool(revoked)),
+        Err(e) => {
+            error!("Failed to revoke API key: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Failed to revoke API key".to_string())
+        }
+    }
+}
+
+/// Handles the "getUsage" RPC method
+/// 
+/// Returns the quotas and current-window usage of the caller's API key.
+async fn handle_get_usage(
+    state: AppState,
+    api_key: Option<String>,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(key) = api_key else {
+        return error_response(request.id, UNAUTHORIZED, "Missing API key".to_string());
+    };
+    
+    match state.registry.api_key_usage(&key).await {
+        Ok(Some(usage)) => success_response(request.id, serde_json::to_value(usage).unwrap()),
+        Ok(None) => error_response(request.id, UNAUTHORIZED, "Unknown API key".to_string()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Handles the "admin_getBondForfeitures" RPC method
+/// 
+/// Lists express-lane bonds forfeited for spam exits, so the operator can
+/// claim them on L1.
+async fn handle_get_bond_forfeitures(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    match state.registry.bond_forfeitures().await {
+        Ok(forfeitures) => success_response(request.id, serde_json::to_value(forfeitures).unwrap()),
+        Err(e) => {
+            error!("Registry query failed: {:?}", e);
+            error_response(request.id, INTERNAL_ERROR, "Registry query failed".to_string())
+        }
+    }
+}
+
+/// Parameters for the sender denylist and allowlist RPC methods
+#[derive(Debug, Deserialize)]
+struct SenderParams {
+    address: Address,
+}
+
+/// Handles the "admin_denySender" RPC method
+/// 
+/// Refuses further transactions from the sender until it is allowed again.
+/// Returns `true` if the sender was not already denylisted.
+async fn handle_deny_sender(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: SenderParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let added = state.tx_pool.deny(params.address).await;
+    info!("Denylisted sender {:?}", params.address);
+    success_response(request.id, Value::Bool(added))
+}
+
+/// Handles the "admin_allowSender" RPC method
+/// 
+/// Returns `true` if the sender was denylisted.
+async fn handle_allow_sender(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: SenderParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let removed = state.tx_pool.allow(&params.address).await;
+    info!("Removed sender {:?} from the denylist", params.address);
+    success_response(request.id, Value::Bool(removed))
+}
+
+/// Handles the "admin_getDenylist" RPC method
+async fn handle_get_denylist(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let denylist = state.tx_pool.denylist().await;
+    success_response(request.id, serde_json::to_value(denylist).unwrap())
+}
+
+/// Handles the "admin_getValidationFailures" RPC method
+/// 
+/// Returns validation failure counts by reason and the most recent failures.
+async fn handle_get_validation_failures(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let report = state.validation.validator().telemetry().report();
+    success_response(request.id, serde_json::to_value(report).unwrap())
+}
+
+/// Handles the "admin_approveSender" RPC method
+/// 
+/// Adds the sender to the allowlist. Returns `true` if it was not already on
+/// it, or an `INVALID_PARAMS` error if the pool is not permissioned.
+async fn handle_approve_sender(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: SenderParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.tx_pool.approve(params.address).await {
+        Some(added) => {
+            info!("Allowlisted sender {:?}", params.address);
+            success_response(request.id, Value::Bool(added))
+        }
+        None => error_response(request.id, INVALID_PARAMS, "Pool is not permissioned".to_string()),
+    }
+}
+
+/// Handles the "admin_revokeSender" RPC method
+/// 
+/// Returns `true` if the sender was allowlisted, or an `INVALID_PARAMS` error
+/// if the pool is not permissioned.
+async fn handle_revoke_sender(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: SenderParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    match state.tx_pool.revoke(&params.address).await {
+        Some(removed) => {
+            info!("Removed sender {:?} from the allowlist", params.address);
+            success_response(request.id, Value::Bool(removed))
+        }
+        None => error_response(request.id, INVALID_PARAMS, "Pool is not permissioned".to_string()),
+    }
+}
+
+/// Handles the "admin_getAllowlist" RPC method
+/// 
+/// Returns `null` if the pool is not permissioned.
+async fn handle_get_allowlist(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let allowlist = state.tx_pool.allowlist().await;
+    success_response(request.id, serde_json::to_value(allowlist).unwrap())
+}
+
+/// Parameters for the "admin_exportPool" and "admin_importPool" RPC methods
+#[derive(Debug, Deserialize)]
+struct PoolSnapshotParams {
+    /// Snapshot file on the sequencer's filesystem
+    path: String,
+}
+
+/// Handles the "admin_exportPool" RPC method
+/// 
+/// Writes every pending transaction to a JSON snapshot file without removing
+/// anything from the pool. Returns the number of transactions written.
+async fn handle_export_pool(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: PoolSnapshotParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let snapshot = state.tx_pool.snapshot().await;
+    match snapshot.write(&params.path) {
+        Ok(()) => {
+            info!("Exported {} pending transactions to {}", snapshot.transactions.len(), params.path);
+            success_response(request.id, Value::from(snapshot.transactions.len()))
+        }
+        Err(e) => {
+            error!("Failed to export pool to {}: {:?}", params.path, e);
+            error_response(request.id, INTERNAL_ERROR, format!("Failed to export pool: {}", e))
+        }
+    }
+}
+
+/// Handles the "admin_importPool" RPC method
+/// 
+/// Submits every transaction of a snapshot file, in queue order, through the
+/// same admission path as `sendTransaction`. Admission times restart on import.
+/// Returns a `PoolImportReport`.
+async fn handle_import_pool(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: PoolSnapshotParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    let snapshot = match PoolSnapshot::read(&params.path) {
+        Ok(snapshot) => snapshot,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid snapshot: {}", e)),
+    };
+    
+    let mut report = PoolImportReport::default();
+    for entry in snapshot.transactions {
+        let tx = entry.tx;
+        let admitted = match state.validation.validate(&tx).await {
+            Ok(turn) => state.tx_pool.add(tx.clone()).await.map(|evicted| (turn, evicted)).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match admitted {
+            Ok((turn, evicted)) => {
+                state.state_cache.increment_nonce(&tx.from, tx.hash()).await;
+                drop(turn);
+                if let Some(evicted) = evicted {
+                    record_outcome(&state, &evicted, TransactionOutcome::Evicted).await;
+                }
+                record_outcome(&state, &tx, TransactionOutcome::Accepted).await;
+                report.imported += 1;
+            }
+            Err(reason) => {
+                record_outcome(&state, &tx, TransactionOutcome::Rejected { reason: reason.clone() }).await;
+                report.rejected.push(RejectedImport { tx_hash: tx.hash(), reason });
+            }
+        }
+    }
+    
+    info!("Imported {} transactions from {} ({} rejected)",
+          report.imported, params.path, report.rejected.len());
+    success_response(request.id, serde_json::to_value(report).unwrap())
+}
+
+/// Parameters for the "admin_exportCheckpoint" RPC method
+#[derive(Debug, Deserialize)]
+struct CheckpointParams {
+    /// Checkpoint file on the sequencer's filesystem
+    path: String,
+}
+
+/// Handles the "admin_exportCheckpoint" RPC method
+/// 
+/// Writes the whole state after the latest sealed batch to a checkpoint file,
+/// which a new node can start from (`state.checkpoint`). Sealing waits until
+/// the state is collected. Returns a `CheckpointSummary`.
+async fn handle_export_checkpoint(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(checkpoints) = state.checkpoints else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Checkpoints not available on this node".to_string());
+    };
+    let params: CheckpointParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let genesis = match state.registry.genesis().await {
+        Ok(genesis) => genesis,
+        Err(e) => return error_response(request.id, INTERNAL_ERROR, format!("Failed to read genesis: {}", e)),
+    };
+    let exported = checkpoints.orchestrator.checkpoint().await.and_then(|checkpoint| {
+        let checkpoint = StateCheckpoint { chain_id: checkpoints.chain_id, genesis, ..checkpoint };
+        checkpoint.write(&params.path)?;
+        Ok(checkpoint.summary())
+    });
+    match exported {
+        Ok(summary) => {
+            info!("Exported checkpoint at batch #{} with {} accounts to {}",
+                  summary.batch_id, summary.accounts, params.path);
+            success_response(request.id, serde_json::to_value(summary).unwrap())
+        }
+        Err(e) => {
+            error!("Failed to export checkpoint to {}: {:?}", params.path, e);
+            error_response(request.id, INTERNAL_ERROR, format!("Failed to export checkpoint: {}", e))
+        }
+    }
+}
+
+/// Parameters for the "admin_setLogFilter" RPC method
+#[derive(Debug, Deserialize)]
+struct LogFilterParams {
+    /// Filter directives, e.g. `info,sequencer::scheduler=debug`
+    filter: String,
+}
+
+/// Handles the "admin_getLogFilter" RPC method
+/// 
+/// Returns the active log filter directives.
+async fn handle_get_log_filter(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(log_filter) = state.log_filter else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Log filter not reloadable".to_string());
+    };
+    success_response(request.id, Value::from(log_filter.current()))
+}
+
+/// Handles the "admin_setLogFilter" RPC method
+/// 
+/// Replaces the log filter; invalid directives leave the current one active.
+/// Returns the new filter directives.
+async fn handle_set_log_filter(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(log_filter) = state.log_filter else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Log filter not reloadable".to_string());
+    };
+    let params: LogFilterParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    if let Err(e) = log_filter.set(&params.filter) {
+        return error_response(request.id, INVALID_PARAMS, format!("Invalid log filter: {}", e));
+    }
+    info!("Log filter changed to {}", log_filter.current());
+    success_response(request.id, Value::from(log_filter.current()))
+}
+
+/// Parameters for the "admin_setPolicy" RPC method
+#[derive(Debug, Deserialize)]
+struct SetPolicyParams {
+    /// Policy name: "FCFS", "FeePriority", "TimeBoost", "FairBFT", "Auction",
+    /// "WeightedFair" or that of a registered custom policy
+    policy: PolicyKind,
+    /// Window of the TimeBoost policy (required for it)
+    #[serde(default, rename = "timeWindowMs")]
+    time_window_ms: Option<u64>,
+    /// Auctioned slots of the Auction policy (required for it)
+    #[serde(default, rename = "auctionSlots")]
+    auction_slots: Option<usize>,
+    /// Whether the WeightedFair policy weights senders by fee (default: no)
+    #[serde(default, rename = "feeWeighted")]
+    fee_weighted: bool,
+}
+
+/// Scheduling policy returned by "admin_getPolicy" and "admin_setPolicy"
+#[derive(Debug, Serialize)]
+struct PolicyReport {
+    policy: String,
+    #[serde(rename = "timeWindowMs")]
+    time_window_ms: Option<u64>,
+    #[serde(rename = "auctionSlots")]
+    auction_slots: Option<usize>,
+    #[serde(rename = "feeWeighted")]
+    fee_weighted: Option<bool>,
+}
+
+impl PolicyReport {
+    fn of(orchestrator: &BatchOrchestrator) -> Self {
+        let (time_window_ms, auction_slots, fee_weighted) = match orchestrator.policy() {
+            SchedulingPolicyType::TimeBoost { time_window_ms } => (Some(time_window_ms), None, None),
+            SchedulingPolicyType::Auction { top_slots } => (None, Some(top_slots), None),
+            SchedulingPolicyType::WeightedFair { fee_weighted } => (None, None, Some(fee_weighted)),
+            _ => (None, None, None),
+        };
+        Self { policy: orchestrator.policy_name(), time_window_ms, auction_slots, fee_weighted }
+    }
+}
+
+/// Handles the "admin_getPolicy" RPC method
+/// 
+/// Returns the scheduling policy the next batch is ordered by.
+async fn handle_get_policy(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(orchestrator) = state.scheduling else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Scheduling not available on this node".to_string());
+    };
+    success_response(request.id, serde_json::to_value(PolicyReport::of(&orchestrator)).unwrap())
+}
+
+/// Handles the "admin_setPolicy" RPC method
+/// 
+/// Switches the scheduling policy from the next batch on; a batch being
+/// sealed keeps its policy. Returns the new policy.
+async fn handle_set_policy(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let Some(orchestrator) = state.scheduling else {
+        return error_response(request.id, METHOD_NOT_FOUND, "Scheduling not available on this node".to_string());
+    };
+    let params: SetPolicyParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    let time_window_ms = match (&params.policy, params.time_window_ms) {
+        (PolicyKind::TimeBoost, None) => {
+            return error_response(request.id, INVALID_PARAMS, "timeWindowMs is required for TimeBoost".to_string());
+        }
+        (PolicyKind::TimeBoost, Some(0)) => {
+            return error_response(request.id, INVALID_PARAMS, "timeWindowMs must be positive".to_string());
+        }
+        (_, window) => window.unwrap_or_default(),
+    };
+    let auction_slots = match (&params.policy, params.auction_slots) {
+        (PolicyKind::Auction, None) => {
+            return error_response(request.id, INVALID_PARAMS, "auctionSlots is required for Auction".to_string());
+        }
+        (PolicyKind::Auction, Some(0)) => {
+            return error_response(request.id, INVALID_PARAMS, "auctionSlots must be positive".to_string());
+        }
+        (_, slots) => slots.unwrap_or_default(),
+    };
+    
+    orchestrator.set_policy(params.policy.to_policy_type(time_window_ms, auction_slots, params.fee_weighted));
+    success_response(request.id, serde_json::to_value(PolicyReport::of(&orchestrator)).unwrap())
+}
+
+/// Parameters for the "admin_getStateJournal" RPC method (exactly one filter)
+#[derive(Debug, Deserialize)]
+struct StateJournalParams {
+    #[serde(default)]
+    address: Option<Address>,
+    #[serde(default, rename = "txHash")]
+    tx_hash: Option<H256>,
+    #[serde(default, rename = "batchId")]
+    batch_id: Option<u64>,
+}
+
+/// Handles the "admin_getStateJournal" RPC method
+/// 
+/// Returns the journaled state changes of an account, a transaction or a
+/// batch, oldest first. Only the most recent changes are kept.
+async fn handle_get_state_journal(
+    state: AppState,
+    request: JsonRpcRequest,
+) -> Json<JsonRpcResponse> {
+    let params: StateJournalParams = match serde_json::from_value(request.params) {
+        Ok(params) => params,
+        Err(e) => return error_response(request.id, INVALID_PARAMS, format!("Invalid params: {}", e)),
+    };
+    
+    let journal = state.state_cache.journal();
+    let changes = match (params.address, params.tx_hash, params.batch_id) {
+        (Some(address), None, None) => journal.for_account(address),
+        (None, Some(tx_hash), None) => journal.for_transaction(tx_hash),
+        (None, None, Some(batch_id)) => journal.for_batch(batch_id),
+        _ => {
+            let reason = "Expected exactly one of address, txHash and batchId".to_string();
+            return error_response(request.id, INVALID_PARAMS, reason);
+        }
+    };
+    success_response(request.id, serde_json::to_value(changes).unwrap())
+}
@@ -0,0 +1,223 @@
+//! Tests for the API helpers
+//! 
+//! Verifies:
+//! - Only failures the claimed sender must have produced count towards a ban
+//! - Forged transactions never count against the claimed sender
+//! - A banned sender is refused until the ban ends
+//! - Every submission method is charged against an API key's transaction quota
+//! - Transactions forwarded over gRPC are authorized and admitted like
+//!   `sendTransaction` calls
+//! - `admin_setLogFilter` swaps in valid filters and refuses invalid ones
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{auth::submitted_transactions, bans::SenderBans, Server},
+        config::{BuiltinRule, Config, DatabaseConfig, RemotePoolConfig, SenderBanConfig, SigningConfig, ValidationConfig},
+        pool::{RemotePool, TransactionPool},
+        registry::{QuotaCheck, Registry},
+        state::StateCache,
+        test_utils::create_test_tx,
+        validation::Validator,
+        logging,
+        AccountState, ApiKeyUsage, ConfirmationStatus, RemoteError, UserTransaction, ValidationError,
+    };
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Address, H256, U256};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn create_bans(max_failures: u32) -> SenderBans {
+        SenderBans::new(SenderBanConfig {
+            max_failures,
+            window_secs: 60,
+            ban_secs: 600,
+            nonce_tolerance: 16,
+        })
+    }
+
+    #[test]
+    fn test_forgeable_failures_do_not_ban_the_claimed_sender() {
+        let bans = create_bans(2);
+        let victim = Address::from_low_u64_be(0xaa);
+        
+        // Anyone can send these in the victim's name
+        for _ in 0..10 {
+            assert!(!bans.record_failure(victim, &ValidationError::InvalidSignature));
+            assert!(!bans.record_failure(victim, &ValidationError::MalformedSignature));
+            let raw = ValidationError::InvalidRawTransaction { reason: "garbage".to_string() };
+            assert!(!bans.record_failure(victim, &raw));
+            let hook = ValidationError::AccountValidationFailed { reason: "bad signature".to_string() };
+            assert!(!bans.record_failure(victim, &hook));
+        }
+        assert!(bans.remaining(&victim).is_none());
+    }
+
+    #[test]
+    fn test_forged_transactions_do_not_ban_the_claimed_sender() {
+        let bans = create_bans(2);
+        let domain = SigningConfig::default().domain();
+        let victim = LocalWallet::from_bytes(H256::from_low_u64_be(1).as_bytes()).unwrap();
+        let attacker = LocalWallet::from_bytes(H256::from_low_u64_be(2).as_bytes()).unwrap();
+        let signed_by = |wallet: &LocalWallet, tx: UserTransaction| UserTransaction {
+            signature: wallet.sign_hash(tx.signing_hash(&domain)).unwrap(),
+            ..tx
+        };
+        let far_off = ValidationError::InvalidNonce { expected: 5, got: 1_000 };
+        
+        // Well-formed signatures by someone else, in the victim's name
+        let wrong_chain = UserTransaction { from: victim.address(), chain_id: 1, ..create_test_tx(1_000, 10) };
+        let forged = signed_by(&attacker, wrong_chain);
+        let wrong_chain_error = ValidationError::WrongChain { expected: 31_337, got: 1 };
+        let forged_nonce = signed_by(&attacker, UserTransaction { from: victim.address(), ..create_test_tx(1_000, 10) });
+        // Rejected before signatures are checked, or with them switched off
+        let unsigned = ValidationConfig { disabled_rules: vec![BuiltinRule::Signature], ..ValidationConfig::default() };
+        for validator in [Validator::new(StateCache::new()), Validator::new(StateCache::new()).with_config(&unsigned)] {
+            for _ in 0..10 {
+                assert!(!bans.record_rejection(&validator, &forged, &wrong_chain_error));
+                assert!(!bans.record_rejection(&validator, &forged_nonce, &far_off));
+            }
+        }
+        assert!(bans.remaining(&victim.address()).is_none());
+        
+        // The victim's own far-off nonces still count
+        let genuine = signed_by(&victim, UserTransaction { from: victim.address(), ..create_test_tx(1_000, 10) });
+        let validator = Validator::new(StateCache::new());
+        assert!(!bans.record_rejection(&validator, &genuine, &far_off));
+        assert!(bans.record_rejection(&validator, &genuine, &far_off));
+        assert!(bans.remaining(&victim.address()).is_some());
+    }
+
+    #[test]
+    fn test_far_off_nonces_ban_the_sender() {
+        let bans = create_bans(2);
+        let sender = Address::from_low_u64_be(0xbb);
+        
+        // Within the tolerance: an honest mistake
+        for _ in 0..10 {
+            assert!(!bans.record_failure(sender, &ValidationError::InvalidNonce { expected: 5, got: 21 }));
+        }
+        assert!(bans.remaining(&sender).is_none());
+        
+        assert!(!bans.record_failure(sender, &ValidationError::InvalidNonce { expected: 5, got: 1_000 }));
+        assert!(bans.record_failure(sender, &ValidationError::InvalidNonce { expected: 5, got: 1_000 }));
+        assert!(bans.remaining(&sender).is_some_and(|left| left.as_secs() > 590));
+        assert!(bans.remaining(&Address::from_low_u64_be(0xcc)).is_none());
+    }
+
+    #[test]
+    fn test_every_submission_method_is_charged() {
+        for method in ["sendTransaction", "eth_sendRawTransaction", "sendUserOperation", "sendEncryptedTransaction"] {
+            assert_eq!(submitted_transactions(method, &json!({})), 1, "{}", method);
+        }
+        assert_eq!(submitted_transactions("sendBundle", &json!([{}, {}, {}])), 3);
+        assert_eq!(submitted_transactions("sendBundle", &json!("garbage")), 1);
+        assert_eq!(submitted_transactions("validateBundle", &json!([{}, {}])), 0);
+        assert_eq!(submitted_transactions("getBalance", &json!({})), 0);
+    }
+
+    #[tokio::test]
+    async fn test_raw_transactions_count_against_the_quota() {
+        let registry = Registry::new(&DatabaseConfig { url: "sqlite::memory:".to_string() }).await.unwrap();
+        registry.create_api_key(&ApiKeyUsage {
+            key: "k".to_string(),
+            label: "customer".to_string(),
+            max_requests: None,
+            max_transactions: Some(1),
+            requests: 0,
+            transactions: 0,
+            window_start: 0,
+        }).await.unwrap();
+        let raw = submitted_transactions("eth_sendRawTransaction", &json!(["0x02f8"]));
+        
+        assert!(matches!(registry.consume_api_quota("k", 10, 100, raw).await.unwrap(), QuotaCheck::Allowed(_)));
+        assert!(matches!(registry.consume_api_quota("k", 20, 100, raw).await.unwrap(), QuotaCheck::Exceeded(_)));
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_transactions_are_admitted_over_grpc() {
+        let mut config = Config::load("config/default.toml").unwrap();
+        config.api.require_api_key = true;
+        let registry = Arc::new(Registry::new(&DatabaseConfig { url: "sqlite::memory:".to_string() }).await.unwrap());
+        registry.create_api_key(&ApiKeyUsage {
+            key: "k".to_string(),
+            label: "ingress".to_string(),
+            max_requests: None,
+            max_transactions: Some(1),
+            requests: 0,
+            transactions: 0,
+            window_start: 0,
+        }).await.unwrap();
+        let wallet = LocalWallet::from_bytes(&[5u8; 32]).unwrap();
+        let state_cache = StateCache::new();
+        state_cache.update(AccountState { address: wallet.address(), balance: U256::exp10(18), ..Default::default() }).await;
+        let tx_pool = Arc::new(TransactionPool::default());
+        let sign = |nonce: u64| {
+            let mut tx = UserTransaction {
+                from: wallet.address(),
+                chain_id: config.signing.chain_id,
+                ..create_test_tx(nonce, 1_000_000_000)
+            };
+            tx.signature = wallet.sign_hash(tx.signing_hash(&config.signing.domain())).unwrap();
+            tx
+        };
+        let (first, second) = (sign(0), sign(1));
+        
+        // The central sequencer, serving gRPC on its API port
+        let app = Server::new(config.clone(), state_cache, tx_pool.clone(), registry).router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let remote = |api_key: Option<&str>| RemotePool::new(RemotePoolConfig {
+            upstream_url: upstream_url.clone(),
+            api_key: api_key.map(str::to_string),
+            max_in_flight: 4,
+            timeout_ms: 5_000,
+            dedup_capacity: 16,
+        }).unwrap();
+        
+        let confirmation = remote(Some("k")).forward(&first).await.unwrap();
+        assert!(matches!(confirmation.status, ConfirmationStatus::Accepted));
+        assert!(tx_pool.contains(&first.hash()).await);
+        
+        // Charged against the forwarding node's API key like any submission
+        let result = remote(Some("k")).forward(&second).await;
+        assert!(matches!(result, Err(RemoteError::Rpc { code: -32005, .. })));
+        let result = remote(None).forward(&second).await;
+        assert!(matches!(result, Err(RemoteError::Rpc { code: -32001, .. })));
+        assert!(!tx_pool.contains(&second.hash()).await);
+    }
+
+    #[tokio::test]
+    async fn test_log_filter_changes_only_to_valid_directives() {
+        let mut config = Config::load("config/default.toml").unwrap();
+        config.api.admin_token = Some("admin".to_string());
+        let registry = Arc::new(Registry::new(&DatabaseConfig { url: "sqlite::memory:".to_string() }).await.unwrap());
+        // Kept alive for the filter to stay reloadable, but not installed
+        let (_subscriber, log_filter) = logging::subscriber().unwrap();
+        let app = Server::new(config, StateCache::new(), Arc::new(TransactionPool::default()), registry)
+            .with_log_filter(log_filter.clone())
+            .router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let set_filter = |filter: &'static str| {
+            let request = reqwest::Client::new()
+                .post(&url)
+                .bearer_auth("admin")
+                .json(&json!({ "jsonrpc": "2.0", "method": "admin_setLogFilter", "params": { "filter": filter }, "id": 1 }));
+            async move { request.send().await.unwrap().json::<serde_json::Value>().await.unwrap() }
+        };
+        assert_eq!(log_filter.current(), logging::DEFAULT_FILTER);
+        
+        let response = set_filter("warn,sequencer::scheduler=debug").await;
+        assert!(response["error"].is_null(), "{}", response);
+        let current = log_filter.current();
+        assert_eq!(response["result"], json!(current));
+        assert!(current.contains("sequencer::scheduler=debug") && current.contains("warn"), "{}", current);
+        
+        let response = set_filter("sequencer=loudest").await;
+        assert_eq!(response["error"]["code"], json!(-32602));
+        assert_eq!(log_filter.current(), current);
+    }
+}
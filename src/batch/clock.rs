@@ -0,0 +1,35 @@
+//! Batch Clock Module
+//!
+//! This module defines where batch timestamps come from. The batch engine
+//! never lets timestamps go backwards, whatever the source reports (see
+//! `BatchEngine::create_batch`).
+//!
+//! # Sources
+//! - **WallClock**: The local system clock (default)
+//! - **L1Head**: The timestamp of the latest L1 block (see `l1::L1HeadClock`)
+
+use async_trait::async_trait;
+
+/// Source of batch timestamps, in unix seconds
+#[async_trait]
+pub trait BatchClock: Send + Sync {
+    /// Current timestamp according to this source
+    ///
+    /// # Returns
+    /// * `Ok(timestamp)` in unix seconds
+    /// * `Err` if the source is unavailable (the previous batch's timestamp is reused)
+    async fn now(&self) -> anyhow::Result<u64>;
+}
+
+/// Batch timestamps from the local system clock
+///
+/// Can jump backwards on NTP corrections or across restarts; the batch
+/// engine clamps those jumps.
+pub struct WallClock;
+
+#[async_trait]
+impl BatchClock for WallClock {
+    async fn now(&self) -> anyhow::Result<u64> {
+        Ok(chrono::Utc::now().timestamp() as u64)
+    }
+}
@@ -0,0 +1,111 @@
+//! Batch Compression Module
+//!
+//! Encodes sealed batches as the data posted to L1. A batch is serialized to
+//! compact JSON and, if a codec is configured, compressed. Compressed data
+//! starts with one byte naming the codec, so a reader can decompress it
+//! without knowing the sequencer's configuration:
+//!
+//! | Byte   | Codec                   |
+//! |--------|-------------------------|
+//! | `0x01` | zlib                    |
+//! | `0x02` | brotli                  |
+//! | `0x03` | zstd                    |
+//!
+//! Uncompressed data is the bare JSON, which starts with `{`.
+
+use crate::{config::BatchCompression, Batch};
+use std::io::{Read, Write};
+
+/// zlib level when none is configured
+const DEFAULT_ZLIB_LEVEL: u32 = 6;
+
+/// brotli quality when none is configured
+const DEFAULT_BROTLI_QUALITY: u32 = 11;
+
+/// brotli window size (log2)
+const BROTLI_WINDOW: u32 = 22;
+
+/// zstd level when none is configured
+const DEFAULT_ZSTD_LEVEL: u32 = 3;
+
+/// Buffer size of the brotli streams
+const BROTLI_BUFFER: usize = 4096;
+
+impl BatchCompression {
+    /// Codec byte prefixed to data compressed with this codec
+    fn tag(self) -> Option<u8> {
+        match self {
+            BatchCompression::None => None,
+            BatchCompression::Zlib => Some(0x01),
+            BatchCompression::Brotli => Some(0x02),
+            BatchCompression::Zstd => Some(0x03),
+        }
+    }
+
+    /// Codec named by a codec byte
+    fn from_tag(tag: u8) -> Option<Self> {
+        [BatchCompression::Zlib, BatchCompression::Brotli, BatchCompression::Zstd]
+            .into_iter()
+            .find(|codec| codec.tag() == Some(tag))
+    }
+}
+
+/// Compress a batch's JSON encoding into the data posted to L1
+///
+/// # Arguments
+/// * `json` - The batch's compact JSON encoding
+/// * `codec` - Compression codec
+/// * `level` - Compression level (the codec's default if `None`)
+pub fn compress_batch(json: &[u8], codec: BatchCompression, level: Option<u32>) -> anyhow::Result<Vec<u8>> {
+    let Some(tag) = codec.tag() else {
+        return Ok(json.to_vec());
+    };
+
+    let mut data = vec![tag];
+    match codec {
+        BatchCompression::None => unreachable!("uncompressed batches have no codec byte"),
+        BatchCompression::Zlib => {
+            let level = flate2::Compression::new(level.unwrap_or(DEFAULT_ZLIB_LEVEL));
+            let mut encoder = flate2::write::ZlibEncoder::new(data, level);
+            encoder.write_all(json)?;
+            data = encoder.finish()?;
+        }
+        BatchCompression::Brotli => {
+            let quality = level.unwrap_or(DEFAULT_BROTLI_QUALITY);
+            let mut encoder = brotli::CompressorWriter::new(data, BROTLI_BUFFER, quality, BROTLI_WINDOW);
+            encoder.write_all(json)?;
+            data = encoder.into_inner();
+        }
+        BatchCompression::Zstd => {
+            let level = level.unwrap_or(DEFAULT_ZSTD_LEVEL) as i32;
+            data.extend(zstd::encode_all(json, level)?);
+        }
+    }
+    Ok(data)
+}
+
+/// Decode batch data produced by [`compress_batch`], whatever its codec
+pub fn decode_batch(data: &[u8]) -> anyhow::Result<Batch> {
+    let Some((&tag, compressed)) = data.split_first() else {
+        anyhow::bail!("empty batch data");
+    };
+    let codec = match BatchCompression::from_tag(tag) {
+        Some(codec) => codec,
+        None => return Ok(serde_json::from_slice(data)?),
+    };
+
+    let mut json = Vec::new();
+    match codec {
+        BatchCompression::None => unreachable!("uncompressed batches have no codec byte"),
+        BatchCompression::Zlib => {
+            flate2::read::ZlibDecoder::new(compressed).read_to_end(&mut json)?;
+        }
+        BatchCompression::Brotli => {
+            brotli::Decompressor::new(compressed, BROTLI_BUFFER).read_to_end(&mut json)?;
+        }
+        BatchCompression::Zstd => {
+            json = zstd::decode_all(compressed)?;
+        }
+    }
+    Ok(serde_json::from_slice(&json)?)
+}
@@ -2,9 +2,12 @@
 //! 
 //! This module is responsible for creating sealed batches from transactions.
 //! Each batch is assigned a unique sequential ID and timestamp.
+//! Timestamps never decrease, even if the clock they come from goes backwards.
+//! Each batch commits to the state root left by the batch before it.
 
-use crate::{Batch, Transaction, config::BatchConfig};
+use crate::{Batch, Transaction, config::BatchConfig, state::EMPTY_STATE_ROOT};
 use ethers::types::H256;
+use tracing::warn;
 
 /// Batch creation engine
 /// 
@@ -15,6 +18,10 @@ pub struct BatchEngine {
     config: BatchConfig,
     /// Next batch ID to assign (starts at 1, increments for each batch)
     next_batch_id: u64,
+    /// Timestamp of the last sealed batch (the floor for the next one)
+    last_timestamp: u64,
+    /// State root after the last sealed batch (the next batch's `prev_state_root`)
+    state_root: H256,
 }
 
 impl BatchEngine {
@@ -26,9 +33,31 @@ impl BatchEngine {
         Self {
             config,
             next_batch_id: 1, // Batches start from ID 1
+            last_timestamp: 0,
+            state_root: EMPTY_STATE_ROOT,
         }
     }
     
+    /// Continue timestamps from a batch sealed before a restart
+    /// 
+    /// Later batches are never timestamped earlier than `last_timestamp`.
+    pub fn resume_timestamps(&mut self, last_timestamp: u64) {
+        self.last_timestamp = self.last_timestamp.max(last_timestamp);
+    }
+    
+    /// Continue batch IDs after `last_batch_id`, sealed before a restart or
+    /// carried over by a state checkpoint
+    pub fn resume_batch_ids(&mut self, last_batch_id: u64) {
+        self.next_batch_id = self.next_batch_id.max(last_batch_id + 1);
+    }
+    
+    /// Record the state root reached after the last sealed batch
+    /// 
+    /// The next batch carries it as its `prev_state_root`.
+    pub fn set_state_root(&mut self, state_root: H256) {
+        self.state_root = state_root;
+    }
+    
     /// Create a new batch from transactions
     /// 
     /// Seals the transactions into a batch with a unique ID and timestamp.
@@ -36,16 +65,25 @@ impl BatchEngine {
     /// 
     /// # Arguments
     /// * `transactions` - Ordered list of transactions (forced first, then normal)
+    /// * `timestamp` - Current time from the batch clock, in unix seconds.
+    ///   Raised to the previous batch's timestamp if it is earlier.
     /// 
     /// # Returns
     /// A sealed `Batch` ready to be executed and posted to L1
-    pub fn create_batch(&mut self, transactions: Vec<Transaction>) -> Batch {
+    pub fn create_batch(&mut self, transactions: Vec<Transaction>, timestamp: u64) -> Batch {
+        if timestamp < self.last_timestamp {
+            warn!("Batch clock went backwards ({} < {}), keeping the previous timestamp",
+                  timestamp, self.last_timestamp);
+        }
+        self.last_timestamp = self.last_timestamp.max(timestamp);
+        
         // Create the batch structure
         let batch = Batch {
             batch_id: self.next_batch_id,
             transactions,
-            prev_state_root: H256::zero(), // TODO: Track actual state root
-            timestamp: chrono::Utc::now().timestamp() as u64,
+            prev_state_root: self.state_root,
+            timestamp: self.last_timestamp,
+            lanes: None,
         };
         
         // Increment ID for next batch
@@ -2,11 +2,31 @@
 //! 
 //! This module handles batch creation and sealing:
 //! - BatchEngine: Creates sealed batches from ordered transactions
-//! - Trigger: Determines when batches should be sealed (planned)
+//! - Trigger: Holds partial batches while L1 posting is too expensive (economic trigger),
+//!   and seals them early for forced-inclusion deadlines
+//! - Offline: Builds batches from a transaction file without running the server
+//! - Clock: Sources of batch timestamps
+//! - Compression: Batch data as posted to L1, optionally compressed
+//! - Withdrawals: L1 payouts of forced exits, committed to in a Merkle root per batch
+//! - Safe mode: Forced-transaction-only batches while L1 won't take the sequencer's
 
 mod engine;
 mod trigger;
+mod clock;
+mod compression;
+mod withdrawals;
+mod safe_mode;
 pub mod orchestrator;
+pub mod offline;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
 
 pub use engine::BatchEngine;
-pub use orchestrator::BatchOrchestrator;
\ No newline at end of file
+pub use clock::{BatchClock, WallClock};
+pub use trigger::{EconomicTrigger, ForcedDeadlines, PostingCosts};
+pub use compression::{compress_batch, decode_batch};
+pub use withdrawals::{batch_withdrawals, withdrawal_root, WithdrawalProof, EMPTY_WITHDRAWAL_ROOT};
+pub use safe_mode::{SafeMode, SafeModeStatus};
+pub use orchestrator::{BatchOrchestrator, SealedBatch};
\ No newline at end of file
@@ -0,0 +1,193 @@
+//! Offline Batch Construction Module
+//!
+//! This module builds sealed batches from a file (or stdin) of presigned
+//! transactions without starting the API server or the L1 listener.
+//!
+//! # Use Cases
+//! - **Air-gapped setups**: Build batches on a machine with no network access
+//! - **Reproducibility**: Re-run a production batch in CI from its input transactions
+//!
+//! # Input Format
+//! One JSON-encoded `UserTransaction` per line (JSONL). Blank lines are ignored.
+//!
+//! # Pipeline
+//! Each transaction goes through the same path as an RPC submission:
+//! 1. Validation against the (optionally seeded) state cache
+//! 2. Nonce increment and admission into a local `TransactionPool`
+//! 3. Batch production via `BatchOrchestrator::produce_batch` until the pool is empty
+//!
+//! The artifact reports how the policy reordered each batch, so policies can
+//! be compared on the same input (see the scheduler's metrics module).
+
+use crate::{
+    pool::{ForcedQueue, TransactionPool},
+    state::StateCache,
+    validation::Validator,
+    batch::BatchOrchestrator,
+    config::{Config, DatabaseConfig, PoolConfig},
+    registry::Registry,
+    scheduler::batch_fairness,
+    AccountState, Batch, BatchFairness, UserTransaction,
+};
+use anyhow::Context;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// A transaction that failed validation during offline construction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedTransaction {
+    /// Line number in the input (1-based)
+    pub line: usize,
+    /// Hash of the rejected transaction
+    pub tx_hash: H256,
+    /// Human-readable rejection reason
+    pub reason: String,
+}
+
+/// Output artifact of an offline batch build
+///
+/// Contains every batch sealed from the input, plus the transactions that
+/// were rejected by validation (so the run can be audited).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineBatchArtifact {
+    /// Scheduling policy used to order normal transactions
+    pub scheduling_policy: String,
+    /// Sealed batches, in production order
+    pub batches: Vec<Batch>,
+    /// Transactions rejected during validation
+    pub rejected: Vec<RejectedTransaction>,
+    /// How each batch was reordered from arrival order, in batch order
+    #[serde(default)]
+    pub fairness: Vec<BatchFairness>,
+}
+
+/// Parse presigned transactions from a JSONL reader
+///
+/// # Arguments
+/// * `reader` - Source of JSONL data (file or stdin)
+///
+/// # Returns
+/// The parsed transactions, each paired with its 1-based line number
+pub fn read_transactions<R: BufRead>(reader: R) -> anyhow::Result<Vec<(usize, UserTransaction)>> {
+    let mut txs = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let tx: UserTransaction = serde_json::from_str(&line)
+            .with_context(|| format!("invalid transaction on line {}", idx + 1))?;
+        txs.push((idx + 1, tx));
+    }
+    Ok(txs)
+}
+
+/// Build sealed batches offline from a list of presigned transactions
+///
+/// # Arguments
+/// * `config` - Sequencer configuration (batch limits and scheduling policy)
+/// * `accounts` - Initial account state to seed the state cache with
+/// * `transactions` - Input transactions with their line numbers
+///
+/// # Returns
+/// An `OfflineBatchArtifact` containing all sealed batches and rejections
+pub async fn build_batches(
+    config: &Config,
+    accounts: Vec<AccountState>,
+    transactions: Vec<(usize, UserTransaction)>,
+) -> anyhow::Result<OfflineBatchArtifact> {
+    // Seed the state cache so balance and nonce checks have something to check against
+    let state_cache = StateCache::new();
+    for account in accounts {
+        state_cache.update(account).await;
+    }
+
+    let validator = Validator::new(state_cache.clone())
+        .with_signing(&config.signing)
+        .with_config(&config.validation)
+        .with_base_fee(config.batch.base_fee_wei.into())
+        .with_deadlines(&config.deadlines);
+    // No nonce lookahead: the whole input is admitted before anything is batched
+    // The pool is unbounded here: evicting offline would silently drop input transactions
+    let tx_pool = Arc::new(TransactionPool::new(PoolConfig {
+        max_size: usize::MAX,
+        denylist: config.pool.denylist.clone(),
+        fee_floor: config.pool.fee_floor.clone(),
+        locals: config.pool.locals.clone(),
+        ..PoolConfig::default()
+    }));
+    let forced_queue = Arc::new(ForcedQueue::new());
+    // Offline runs keep their registry in memory so they never touch the production database
+    let registry = Arc::new(Registry::new(&DatabaseConfig {
+        url: "sqlite::memory:".to_string(),
+    }).await?);
+
+    // Run each transaction through the same admission path as the RPC server
+    let mut rejected = Vec::new();
+    for (line, mut tx) in transactions {
+        let tx_hash = tx.hash();
+        let sender = tx.from;
+        let admitted = match validator.resolve_priority_class(&mut tx) {
+            Ok(()) => validator.validate(&tx).await,
+            Err(e) => Err(e),
+        };
+        let admitted = match admitted {
+            Ok(()) => tx_pool.add(tx).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match admitted {
+            Ok(_) => {
+                state_cache.increment_nonce(&sender, tx_hash).await;
+            }
+            Err(reason) => {
+                warn!("Line {}: transaction {:?} rejected: {}", line, tx_hash, reason);
+                rejected.push(RejectedTransaction {
+                    line,
+                    tx_hash,
+                    reason,
+                });
+            }
+        }
+    }
+
+    let orchestrator = BatchOrchestrator::new(
+        forced_queue,
+        tx_pool,
+        state_cache,
+        registry,
+        config.batch.clone(),
+        config.scheduling.to_policy_type(),
+    )
+    .with_min_distinct_senders(config.scheduling.min_distinct_senders())
+    .with_execution_lanes(config.scheduling.execution_lanes());
+    let orchestrator = if config.deadlines.enabled {
+        orchestrator.with_deadlines(config.deadlines.margin_secs)
+    } else {
+        orchestrator
+    };
+
+    // Drain the pool into as many batches as it takes
+    let mut batches = Vec::new();
+    while let Some(batch) = orchestrator.produce_batch().await? {
+        info!("Sealed offline batch #{} with {} transactions",
+              batch.batch_id,
+              batch.transactions.len());
+        batches.push(batch);
+    }
+
+    let scheduling_policy = orchestrator.policy_name();
+    let base_fee = U256::from(config.batch.base_fee_wei);
+    let fairness = batches
+        .iter()
+        .map(|batch| batch_fairness(batch, &scheduling_policy, base_fee))
+        .collect();
+    Ok(OfflineBatchArtifact {
+        scheduling_policy,
+        batches,
+        rejected,
+        fairness,
+    })
+}
@@ -5,22 +5,78 @@
 //! by pulling transactions from pools, scheduling them, and creating sealed batches.
 //! 
 //! # Architecture Flow
-//! 1. Check trigger conditions (timeout or size threshold)
-//! 2. Pull forced transactions from `ForcedQueue`
-//! 3. Pull normal transactions from `TransactionPool` (up to max batch size)
-//! 4. Pass both to `Scheduler` for ordering (forced txs always first)
-//! 5. Create sealed batch via `BatchEngine`
-//! 6. Log batch creation (future: send to executor)
+//! 1. Check trigger conditions (timeout, size threshold, express-lane exit, or a
+//!    forced-inclusion or transaction deadline nearing), holding partial batches
+//!    while L1 posting is too expensive (if enabled)
+//! 2. Pull forced transactions from a `ForcedSource` (normally the `ForcedQueue`),
+//!    returning those the gas limit leaves no room for to the front of the queue
+//! 3. Pull user operations from their lane (if account abstraction is enabled),
+//!    up to their share of the batch, and decrypt the encrypted transactions
+//!    whose epoch key is out (if the encrypted mempool is enabled), admitting
+//!    each one as if it had just been submitted, then whole bundles from their
+//!    lane (if enabled) while they fit
+//! 4. Pull normal transactions from a `PoolSource` (normally the `TransactionPool`, up to max batch size):
+//!    first those whose deadline would pass before the next batch (if deadlines are enabled),
+//!    then by policy, best fee first under fee ordering, diversifying senders if the sender guard is enabled
+//! 5. Pass them to `Scheduler` for ordering (forced txs always first, then
+//!    decrypted ones in the order they were encrypted, then each bundle
+//!    contiguously, in the order they arrived)
+//! 6. Create sealed batch via `BatchEngine`, committing to the previous state root,
+//!    and group its transactions into execution lanes touching no common account (if enabled;
+//!    one serial lane if any runs contract code)
+//! 7. Apply the batch to the `StateCache`: deposits, forced exits, then transfers and fees,
+//!    proving every touched account before and after for the execution witness
+//! 8. Record batch metadata (with the raw and compressed size of the data posted to L1),
+//!    its witness, the withdrawals of its forced exits, transaction outcomes and
+//!    `Sealed` status in the `Registry`, and how its policy reordered it in the
+//!    scheduling metrics
+//! 9. Compute the new state root for the next batch
+//! 10. Archive accounts that have been inactive for too long (if enabled)
+//! 11. Prune history older than the retention window (if enabled)
+//! 12. Hand the batch, with its state and withdrawal roots, to the L1 batch submitter (if enabled)
+//! 
+//! A sender's pooled transactions wait while it has bundles pending, so they
+//! are never sealed ahead of the bundles' earlier nonces. Bundles are recorded
+//! in the registry with their batch, so reinjection returns them to their lane whole.
+//! 
+//! In safe mode (see the safe mode module), steps 3 and 4 are skipped: batches
+//! carry forced transactions only, and the size trigger is ignored.
+//! 
+//! If a sealed batch later fails to post to L1 or is reverted, `reinject` returns
+//! its transactions to the front of their pools so they are not lost
+//! (`reinject_failed` also takes back the later batches awaiting submission).
+//! 
+//! The scheduling policy can be switched at runtime (`set_policy`). Each batch
+//! is scheduled by the policy active when its sealing starts, and records
+//! that policy's name in its metadata. Under the `Auction` policy, the bids
+//! that won the batch's top slots are recorded in the registry with it.
+//! 
+//! An external proposer can also hand in a fully-ordered batch (`seal_proposal`).
+//! It replaces steps 2-5: the proposal is checked against the forced queue and
+//! the pool, then sealed in the proposer's order.
 
 use crate::{
-    pool::{ForcedQueue, TransactionPool},
-    scheduler::{Scheduler, SchedulingPolicyType, create_policy},
-    batch::BatchEngine,
-    config::BatchConfig,
-    Batch, Transaction,
+    pool::{decrypt_transaction, BundlePool, EncryptedPool, ForcedSource, PoolSource, UserOpPool},
+    scheduler::{
+        batch_fairness, execution_lanes, AuctionBook, AuctionPolicy, OpenedBid, Scheduler, SchedulingPolicyType,
+        SchedulingTelemetry, DEFAULT_RECENT_BATCHES,
+    },
+    batch::{
+        batch_withdrawals, compress_batch, withdrawal_root, BatchClock, BatchEngine, EconomicTrigger, ForcedDeadlines,
+        SafeMode, WallClock,
+    },
+    config::{BatchCompression, BatchConfig},
+    registry::Registry,
+    state::{BatchWitness, StateCache, StateCheckpoint},
+    validation::ValidationWorkers,
+    AccountState, AuctionWin, Batch, BatchMetadata, Bundle, BundleInclusion, BatchStatus, BatchSubmission, BondForfeiture, ForcedEventType, ForcedTransaction, SenderGuardAdjustment,
+    ProposalError, Transaction, TransactionOutcome, TransactionRecord, UserOperation, UserTransaction,
+    EncryptedTransaction, ValidationError,
 };
+use ethers::types::{Address, Bytes, H256, U256};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::time::{sleep, Duration, Instant};
 use tracing::{info, debug, warn};
 
@@ -30,42 +86,342 @@ use tracing::{info, debug, warn};
 /// conditions and orchestrating transaction flow through the scheduling and
 /// batching components.
 pub struct BatchOrchestrator {
-    /// Forced transaction queue (L1-originated transactions)
-    forced_queue: Arc<ForcedQueue>,
-    /// Normal transaction pool (user-submitted transactions)
-    tx_pool: Arc<TransactionPool>,
-    /// Scheduler for ordering transactions within batches
-    scheduler: Scheduler,
+    /// Forced transaction source (L1-originated transactions)
+    forced_queue: Arc<dyn ForcedSource>,
+    /// Normal transaction source (user-submitted transactions)
+    tx_pool: Arc<dyn PoolSource>,
+    /// User operation lane (account abstraction disabled if `None`)
+    user_ops: Option<Arc<UserOpPool>>,
+    /// Most user operations per batch
+    max_user_ops_per_batch: usize,
+    /// Encrypted transaction lane (encrypted mempool disabled if `None`)
+    encrypted: Option<EncryptedLane>,
+    /// Bundle lane (bundles disabled if `None`)
+    bundles: Option<Arc<BundlePool>>,
+    /// Most bundled transactions per batch
+    max_bundled_per_batch: usize,
+    /// Scheduling policy ordering transactions within batches, switchable between batches
+    policy: std::sync::RwLock<ActivePolicy>,
+    /// Sealed bids opened by the `Auction` policy
+    auction: Arc<AuctionBook>,
+    /// Reordering and latency metrics of sealed batches
+    fairness: Arc<SchedulingTelemetry>,
     /// Batch engine for creating sealed batches (wrapped in RwLock for mutable access)
     batch_engine: RwLock<BatchEngine>,
     /// Batch configuration (size limits, timeout, etc.)
     config: BatchConfig,
+    /// Registry for batch metadata and transaction history
+    registry: Arc<Registry>,
+    /// Account state (used to detect express exits that cannot be honored)
+    state_cache: StateCache,
+    /// Minimum distinct senders per batch under fee ordering, when the pool has them (sender guard)
+    min_distinct_senders: Option<usize>,
+    /// Most execution lanes each batch is grouped into (no lanes if `None`)
+    execution_lanes: Option<usize>,
+    /// Archive accounts idle for this many batches (archival disabled if `None`)
+    archive_after_batches: Option<u64>,
+    /// Keep the history of this many recent batches (all kept if `None`)
+    keep_history_batches: Option<u64>,
+    /// Source of batch timestamps
+    clock: Arc<dyn BatchClock>,
+    /// Codec compressing the batch data posted to L1
+    compression: BatchCompression,
+    /// Level of that codec (its default if `None`)
+    compression_level: Option<u32>,
+    /// Where sealed batches are sent for submission to L1 (not submitted if `None`)
+    submissions: Option<mpsc::UnboundedSender<SealedBatch>>,
+    /// Holds partial batches while L1 posting is too expensive (disabled if `None`)
+    economic_trigger: Option<EconomicTrigger>,
+    /// Seals batches early for forced-inclusion deadlines (disabled if `None`)
+    forced_deadlines: Option<ForcedDeadlines>,
+    /// Seconds ahead of a transaction deadline its batch is sealed (deadlines ignored if `None`)
+    deadline_margin_secs: Option<u64>,
+    /// Restricts batches to forced transactions while on (never if `None`)
+    safe_mode: Option<Arc<SafeMode>>,
+    /// Held while pulling and sealing, so produced and proposed batches don't interleave
+    seal_lock: Mutex<()>,
+}
+
+/// Sealed batch on its way to L1
+/// 
+/// # Fields
+/// - `batch`: The sealed batch
+/// - `state_root`: State root after applying the batch
+/// - `withdrawal_root`: Merkle root of the batch's withdrawals
+/// - `data`: Batch data to post, compressed if configured
+#[derive(Debug, Clone)]
+pub struct SealedBatch {
+    pub batch: Batch,
+    pub state_root: H256,
+    pub withdrawal_root: H256,
+    pub data: Vec<u8>,
+}
+
+/// Scheduling policy in use
+#[derive(Clone)]
+struct ActivePolicy {
+    /// Its type, with its parameters
+    policy_type: SchedulingPolicyType,
+    /// Scheduler applying it
+    scheduler: Arc<Scheduler>,
+}
+
+impl ActivePolicy {
+    /// The policy of `policy_type`, opening the bids of `auction` if it is an auction
+    fn new(policy_type: SchedulingPolicyType, auction: &Arc<AuctionBook>) -> Self {
+        let scheduler = match policy_type {
+            SchedulingPolicyType::Auction { top_slots } => {
+                Scheduler::new(Box::new(AuctionPolicy::new(top_slots, auction.clone())))
+            }
+            _ => Scheduler::from_type(policy_type.clone()),
+        };
+        Self { policy_type, scheduler: Arc::new(scheduler) }
+    }
+    
+    /// Whether to pull the best-paying transactions instead of the oldest
+    /// 
+    /// Under fee ordering, the pool's priority index picks the batch candidates.
+    fn fee_ordered(&self) -> bool {
+        matches!(self.policy_type, SchedulingPolicyType::FeePriority | SchedulingPolicyType::Auction { .. })
+    }
+    
+    /// Whether to pull transactions round-robin across senders, and the base
+    /// fee to weight them by if fee-weighted
+    /// 
+    /// Under fair queuing, the pool serves senders in rounds, so a sender
+    /// with a deep backlog cannot crowd the others out of the candidates.
+    fn fair_share(&self, base_fee: U256) -> Option<Option<U256>> {
+        match self.policy_type {
+            SchedulingPolicyType::WeightedFair { fee_weighted } => Some(fee_weighted.then_some(base_fee)),
+            _ => None,
+        }
+    }
+}
+
+/// Encrypted lane, and the validation its decrypted transactions go through
+struct EncryptedLane {
+    lane: Arc<EncryptedPool>,
+    /// Admits decrypted transactions in turn with the submissions
+    validation: Arc<ValidationWorkers>,
+    /// Most decrypted transactions per batch
+    max_per_batch: usize,
+}
+
+/// Batch data as posted to L1
+struct EncodedBatch {
+    /// The data, compressed with `codec`
+    data: Vec<u8>,
+    /// Codec actually used
+    codec: BatchCompression,
+    /// Size of the uncompressed JSON encoding
+    raw_size: usize,
 }
 
+/// Scheduling policy recorded for externally proposed batches
+const EXTERNAL_PROPOSER_POLICY: &str = "ExternalProposer";
+
 impl BatchOrchestrator {
     /// Creates a new batch orchestrator
     /// 
     /// # Arguments
-    /// * `forced_queue` - Shared reference to the forced transaction queue
-    /// * `tx_pool` - Shared reference to the normal transaction pool
+    /// * `forced_queue` - Shared forced transaction source (usually a `ForcedQueue`)
+    /// * `tx_pool` - Shared normal transaction source (usually a `TransactionPool`)
+    /// * `state_cache` - Shared account state cache
+    /// * `registry` - Shared reference to the batch registry
     /// * `batch_config` - Batch configuration settings
-    /// * `scheduling_policy` - Scheduling policy type (FCFS, FeePriority, TimeBoost, or FairBFT)
+    /// * `scheduling_policy` - Scheduling policy type (FCFS, FeePriority, TimeBoost, FairBFT, Auction, WeightedFair
+    ///   or a registered custom policy)
     pub fn new(
-        forced_queue: Arc<ForcedQueue>,
-        tx_pool: Arc<TransactionPool>,
+        forced_queue: Arc<dyn ForcedSource>,
+        tx_pool: Arc<dyn PoolSource>,
+        state_cache: StateCache,
+        registry: Arc<Registry>,
         batch_config: BatchConfig,
         scheduling_policy: SchedulingPolicyType,
     ) -> Self {
-        // Create policy instance using factory function
-        let policy = create_policy(scheduling_policy);
-        
+        let auction = Arc::new(AuctionBook::new());
         Self {
             forced_queue,
             tx_pool,
-            scheduler: Scheduler::new(policy),
+            user_ops: None,
+            max_user_ops_per_batch: 0,
+            encrypted: None,
+            bundles: None,
+            max_bundled_per_batch: 0,
+            policy: std::sync::RwLock::new(ActivePolicy::new(scheduling_policy, &auction)),
+            auction,
             batch_engine: RwLock::new(BatchEngine::new(batch_config.clone())),
             config: batch_config,
+            registry,
+            state_cache,
+            min_distinct_senders: None,
+            execution_lanes: None,
+            archive_after_batches: None,
+            keep_history_batches: None,
+            clock: Arc::new(WallClock),
+            compression: BatchCompression::None,
+            compression_level: None,
+            submissions: None,
+            economic_trigger: None,
+            forced_deadlines: None,
+            deadline_margin_secs: None,
+            safe_mode: None,
+            fairness: Arc::new(SchedulingTelemetry::new(DEFAULT_RECENT_BATCHES)),
+            seal_lock: Mutex::new(()),
+        }
+    }
+    
+    /// Take batch timestamps from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn BatchClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Include user operations from `lane`, at most `max_per_batch` per batch
+    pub fn with_user_ops(mut self, lane: Arc<UserOpPool>, max_per_batch: usize) -> Self {
+        self.user_ops = Some(lane);
+        self.max_user_ops_per_batch = max_per_batch;
+        self
+    }
+    
+    /// Include the transactions of the encrypted `lane` once decrypted, at most
+    /// `max_per_batch` per batch, validated by `validation`
+    pub fn with_encrypted_mempool(
+        mut self,
+        lane: Arc<EncryptedPool>,
+        validation: Arc<ValidationWorkers>,
+        max_per_batch: usize,
+    ) -> Self {
+        self.encrypted = Some(EncryptedLane { lane, validation, max_per_batch });
+        self
+    }
+    
+    /// Include whole bundles from `lane`, at most `max_per_batch` bundled transactions per batch
+    pub fn with_bundles(mut self, lane: Arc<BundlePool>, max_per_batch: usize) -> Self {
+        self.bundles = Some(lane);
+        self.max_bundled_per_batch = max_per_batch;
+        self
+    }
+    
+    /// Archive accounts that haven't been modified for `batches` batches
+    /// 
+    /// Only takes effect if the state cache has an archive attached.
+    /// `None` disables archival.
+    pub fn with_state_archival(mut self, batches: Option<u64>) -> Self {
+        self.archive_after_batches = batches;
+        self
+    }
+    
+    /// Prune the registry's history of batches older than the last `batches`
+    /// 
+    /// `None` keeps the history of every batch (archival mode).
+    pub fn with_history_pruning(mut self, batches: Option<u64>) -> Self {
+        self.keep_history_batches = batches;
+        self
+    }
+    
+    /// Compress the batch data posted to L1 with `codec`, at `level` (its default if `None`)
+    pub fn with_compression(mut self, codec: BatchCompression, level: Option<u32>) -> Self {
+        self.compression = codec;
+        self.compression_level = level;
+        self
+    }
+    
+    /// Send every sealed batch, with its resulting state root, to `submissions`
+    /// 
+    /// The receiving end normally belongs to the L1 batch submitter.
+    pub fn with_submissions(mut self, submissions: mpsc::UnboundedSender<SealedBatch>) -> Self {
+        self.submissions = Some(submissions);
+        self
+    }
+    
+    /// Hold partial batches past the timeout while `trigger` finds them too expensive to post
+    pub fn with_economic_trigger(mut self, trigger: EconomicTrigger) -> Self {
+        self.economic_trigger = Some(trigger);
+        self
+    }
+    
+    /// Seal a batch right away whenever a queued forced transaction nears a deadline of `deadlines`
+    pub fn with_forced_deadlines(mut self, deadlines: ForcedDeadlines) -> Self {
+        self.forced_deadlines = Some(deadlines);
+        self
+    }
+    
+    /// Take transactions due before the next batch ahead of the scheduling
+    /// policy, sealing a batch right away once one is `margin_secs` from its deadline
+    pub fn with_deadlines(mut self, margin_secs: u64) -> Self {
+        self.deadline_margin_secs = Some(margin_secs);
+        self
+    }
+    
+    /// Seal forced transactions only while `safe_mode` is on
+    pub fn with_safe_mode(mut self, safe_mode: Arc<SafeMode>) -> Self {
+        self.safe_mode = Some(safe_mode);
+        self
+    }
+    
+    /// Whether only forced transactions are sealed for now
+    fn in_safe_mode(&self) -> bool {
+        self.safe_mode.as_ref().is_some_and(|safe_mode| safe_mode.is_active())
+    }
+    
+    /// Require each batch to contain at least `min` distinct senders when available
+    /// 
+    /// Mitigates a single actor capturing whole batches under fee ordering, so
+    /// the guard only applies while the policy is `FeePriority` or `Auction`.
+    /// `None` disables the guard.
+    pub fn with_min_distinct_senders(mut self, min: Option<usize>) -> Self {
+        self.min_distinct_senders = min;
+        self
+    }
+    
+    /// Group each batch's transactions into at most `max` lanes that touch no
+    /// common account, for a parallel executor
+    /// 
+    /// A batch running contract code is a single lane. `None` seals batches
+    /// without lanes.
+    pub fn with_execution_lanes(mut self, max: Option<usize>) -> Self {
+        self.execution_lanes = max;
+        self
+    }
+    
+    /// Get the name of the scheduling policy used by this orchestrator
+    pub fn policy_name(&self) -> String {
+        self.policy.read().unwrap().scheduler.policy_name().to_string()
+    }
+    
+    /// Scheduling policy the next batch is ordered by
+    pub fn policy(&self) -> SchedulingPolicyType {
+        self.policy.read().unwrap().policy_type.clone()
+    }
+    
+    /// Switch the scheduling policy
+    /// 
+    /// Takes effect at the next batch boundary: a batch already being sealed
+    /// keeps the policy it started with.
+    pub fn set_policy(&self, policy_type: SchedulingPolicyType) {
+        let active = ActivePolicy::new(policy_type, &self.auction);
+        let previous = std::mem::replace(&mut *self.policy.write().unwrap(), active.clone());
+        // Bids placed for auctions that will not be held are discarded
+        if !matches!(active.policy_type, SchedulingPolicyType::Auction { .. }) {
+            self.auction.clear();
         }
+        info!(
+            "Scheduling policy switched from {} to {} ({:?}) for the next batch",
+            previous.scheduler.policy_name(),
+            active.scheduler.policy_name(),
+            active.policy_type
+        );
+    }
+    
+    /// Reordering and latency metrics of the batches sealed so far
+    pub fn fairness(&self) -> Arc<SchedulingTelemetry> {
+        self.fairness.clone()
+    }
+    
+    /// Book the `Auction` policy opens, where `submitBid` places bids
+    pub fn auction_book(&self) -> Arc<AuctionBook> {
+        self.auction.clone()
     }
     
     /// Start the batch orchestrator background loop
@@ -76,10 +432,17 @@ impl BatchOrchestrator {
     /// # Trigger Conditions
     /// - **Timeout trigger**: Produce batch after timeout expires (even if not full)
     /// - **Size trigger**: Produce batch when max size is reached
+    /// - **Express trigger**: Produce batch immediately when a bonded forced exit arrives
+    /// - **Deadline trigger**: Produce batch immediately when a queued forced
+    ///   transaction nears its inclusion deadline, or a pending transaction
+    ///   comes within the margin of its deadline
+    /// - **Economic trigger**: Hold a partial batch past the timeout while its
+    ///   L1 cost per transaction is too high, unless forced transactions wait
+    ///   or a transaction deadline nears
     /// 
     /// # Returns
     /// An error if the orchestrator fails to start
-    pub async fn start(self) -> anyhow::Result<()> {
+    pub async fn start(&self) -> anyhow::Result<()> {
         info!("Batch orchestrator starting...");
         info!("Configuration: max_batch_size={}, timeout_interval_ms={}, min_batch_size={}, max_gas_limit={}", 
               self.config.max_batch_size, 
@@ -87,25 +450,74 @@ impl BatchOrchestrator {
               self.config.min_batch_size,
               self.config.max_gas_limit);
         
+        // Never timestamp a batch earlier than one sealed before the restart
+        let last_timestamp = self.registry.latest_batch_timestamp().await?;
+        if let Some(last_timestamp) = last_timestamp {
+            self.batch_engine.write().await.resume_timestamps(last_timestamp);
+        }
+        // The first batch follows the state picked up at startup, and commits to it
+        let mut engine = self.batch_engine.write().await;
+        engine.resume_batch_ids(self.state_cache.current_batch());
+        engine.set_state_root(self.state_cache.state_root());
+        drop(engine);
+        
         let timeout_duration = Duration::from_millis(self.config.timeout_interval_ms);
         let mut last_batch_time = Instant::now();
         
         loop {
-            // Sleep for a short interval to avoid busy-waiting
-            // This allows the system to process other tasks
-            sleep(Duration::from_millis(100)).await;
+            // Sleep for a short interval to avoid busy-waiting, waking early
+            // if an express-lane exit needs to be sealed right away
+            let express = tokio::select! {
+                _ = sleep(Duration::from_millis(100)) => false,
+                _ = self.forced_queue.express_ready() => true,
+            };
             
             // Check if timeout has expired
             let timeout_expired = last_batch_time.elapsed() >= timeout_duration;
             
-            // Get current pool sizes (for logging and trigger detection)
-            // Note: We don't have a direct way to check pool size without reading,
-            // so we rely on timeout triggers primarily for now
+            // Check if enough transactions are waiting to fill a batch
+            let pending = self.tx_pool.len().await;
+            let pool_full = pending >= self.config.max_batch_size && !self.in_safe_mode();
             
-            // Trigger batch production if timeout expired
-            if timeout_expired {
-                debug!("Batch timeout triggered ({}ms elapsed)", 
-                       last_batch_time.elapsed().as_millis());
+            // Check if a forced transaction is running out of time
+            let deadline = match &self.forced_deadlines {
+                Some(deadlines) => deadlines.due(self.forced_queue.oldest_l1_block().await, self.forced_queue.l1_head()),
+                None => false,
+            };
+            
+            // Check if a pending transaction is running out of time
+            let tx_deadline = match self.deadline_margin_secs.filter(|_| !self.in_safe_mode()) {
+                Some(margin) => match self.tx_pool.earliest_deadline().await {
+                    Some(earliest) => earliest <= self.now().await.saturating_add(margin),
+                    None => false,
+                },
+                None => false,
+            };
+            
+            // Let a partial batch wait for more transactions to share its L1 cost,
+            // but never keep forced transactions or transaction deadlines waiting
+            if timeout_expired && !pool_full && !express && !deadline && !tx_deadline
+                && let Some(trigger) = &self.economic_trigger
+                && trigger.should_wait(pending, last_batch_time.elapsed().saturating_sub(timeout_duration))
+                && self.forced_queue.is_empty().await
+            {
+                continue;
+            }
+            
+            // Trigger batch production on timeout, a full batch, an express exit, or a deadline
+            if timeout_expired || pool_full || express || deadline || tx_deadline {
+                if express {
+                    info!("Express-lane exit queued, sealing batch immediately");
+                } else if deadline {
+                    info!("Forced-inclusion deadline nearing, sealing batch immediately");
+                } else if tx_deadline {
+                    info!("Transaction deadline nearing, sealing batch immediately");
+                } else if pool_full {
+                    debug!("Size trigger: at least {} transactions pending", self.config.max_batch_size);
+                } else {
+                    debug!("Batch timeout triggered ({}ms elapsed)", 
+                           last_batch_time.elapsed().as_millis());
+                }
                 
                 match self.produce_batch().await {
                     Ok(Some(batch)) => {
@@ -113,9 +525,6 @@ impl BatchOrchestrator {
                               batch.batch_id, 
                               batch.transactions.len());
                         
-                        // TODO: Send batch to executor component
-                        // For now, we just log the batch creation
-                        
                         // Reset timer after successful batch creation
                         last_batch_time = Instant::now();
                     }
@@ -131,10 +540,6 @@ impl BatchOrchestrator {
                     }
                 }
             }
-            
-            // TODO: Add size-based trigger
-            // This would require exposing a non-blocking "peek size" method
-            // on TransactionPool and ForcedQueue, which we can add later
         }
     }
     
@@ -142,9 +547,16 @@ impl BatchOrchestrator {
     /// 
     /// This is the core batch production logic:
     /// 1. Pull all forced transactions (always included first)
-    /// 2. Pull normal transactions respecting both size and gas limits
-    /// 3. Schedule them (forced first, then normal by policy)
-    /// 4. Create sealed batch
+    /// 2. Pull user operations, up to their share of the batch, decrypt the
+    ///    ready encrypted transactions, up to theirs, and take whole bundles
+    /// 3. Pull normal transactions respecting both size and gas limits, those
+    ///    due before the next batch first
+    /// 4. Schedule them (forced first, then decrypted in lane order, then
+    ///    bundles, then normal by policy, then user operations)
+    /// 5. Create sealed batch
+    /// 
+    /// Public so that offline tooling (see [`crate::batch::offline`]) can drive
+    /// batch production without the background trigger loop.
     /// 
     /// # Gas Limit Enforcement
     /// The engine tracks cumulative gas consumption as transactions are added,
@@ -155,7 +567,11 @@ impl BatchOrchestrator {
     /// * `Ok(Some(Batch))` if a batch was created
     /// * `Ok(None)` if no transactions were available
     /// * `Err` if batch creation failed
-    async fn produce_batch(&self) -> anyhow::Result<Option<Batch>> {
+    pub async fn produce_batch(&self) -> anyhow::Result<Option<Batch>> {
+        let _sealing = self.seal_lock.lock().await;
+        // The whole batch is scheduled by the policy active now
+        let policy = self.policy.read().unwrap().clone();
+        
         // Step 1: Get all forced transactions from L1
         let forced_txs = self.forced_queue.get_all().await;
         
@@ -165,66 +581,1064 @@ impl BatchOrchestrator {
         // Step 1a: Filter forced transactions to respect gas limit
         // Forced txs have priority, but we still need to respect gas limits
         let mut accepted_forced_txs = Vec::new();
+        let mut deferred_forced_txs = Vec::new();
         for tx in forced_txs {
             let wrapped_tx = Transaction::Forced(tx);
             if engine.can_add_transaction(&accepted_forced_txs, &wrapped_tx) {
                 accepted_forced_txs.push(wrapped_tx);
-            } else {
-                warn!("Forced transaction exceeds gas limit, deferring to next batch");
-                // In production, this transaction should be re-queued
+            } else if let Transaction::Forced(tx) = wrapped_tx {
+                warn!("Forced transaction {:?} exceeds gas limit, deferring to next batch", tx.l1_tx_hash);
+                deferred_forced_txs.push(tx);
+            }
+        }
+        if !deferred_forced_txs.is_empty() {
+            self.forced_queue.requeue(deferred_forced_txs.clone()).await;
+        }
+        
+        // In safe mode, only forced transactions are sealed
+        let safe_mode = self.in_safe_mode();
+        
+        // Step 1b: Take user operations from their lane, up to their share of the batch
+        let mut accepted_user_ops = Vec::new();
+        if let Some(lane) = self.user_ops.as_ref().filter(|_| !safe_mode) {
+            let room = self.max_user_ops_per_batch
+                .min(self.config.max_batch_size.saturating_sub(accepted_forced_txs.len()));
+            let mut combined_txs = accepted_forced_txs.clone();
+            let mut deferred = Vec::new();
+            for op in lane.take(room).await {
+                let wrapped_op = Transaction::UserOp(op);
+                // Once one doesn't fit, later ones wait too, keeping wallet nonce order
+                if deferred.is_empty() && engine.can_add_transaction(&combined_txs, &wrapped_op) {
+                    combined_txs.push(wrapped_op.clone());
+                    accepted_user_ops.push(wrapped_op);
+                } else if let Transaction::UserOp(op) = wrapped_op {
+                    deferred.push(op);
+                }
+            }
+            if !deferred.is_empty() {
+                lane.requeue(deferred).await;
+            }
+        }
+        
+        // Step 1c: Decrypt the encrypted transactions whose epoch key is out, up to their share
+        let mut decrypted = Vec::new();
+        if let Some(encrypted) = self.encrypted.as_ref().filter(|_| !safe_mode) {
+            let room = encrypted.max_per_batch
+                .min(self.config.max_batch_size.saturating_sub(accepted_forced_txs.len() + accepted_user_ops.len()));
+            // Selected by their declared gas limit, which the decrypted transaction may not exceed
+            let mut gas: u64 = accepted_forced_txs.iter().chain(&accepted_user_ops).map(|tx| tx.gas_limit()).sum();
+            let ready = encrypted.lane.take_ready(room, |gas_limit| {
+                let fits = gas.saturating_add(gas_limit) <= self.config.max_gas_limit;
+                if fits {
+                    gas += gas_limit;
+                }
+                fits
+            });
+            decrypted = self.admit_decrypted(encrypted, ready).await;
+        }
+        
+        // Step 1d: Take whole bundles from their lane, up to their share of the batch
+        let mut bundles = Vec::new();
+        if let Some(lane) = self.bundles.as_ref().filter(|_| !safe_mode) {
+            let room = self.max_bundled_per_batch
+                .min(self.config.max_batch_size.saturating_sub(accepted_forced_txs.len() + decrypted.len() + accepted_user_ops.len()));
+            let mut gas: u64 = accepted_forced_txs.iter().chain(&accepted_user_ops).map(|tx| tx.gas_limit()).sum::<u64>()
+                + decrypted.iter().map(|tx| tx.gas_limit).sum::<u64>();
+            let mut deferred = Vec::new();
+            for bundle in lane.take(room).await {
+                // All of a bundle or none of it; once one doesn't fit, later ones wait too
+                if deferred.is_empty() && gas.saturating_add(bundle.gas_limit()) <= self.config.max_gas_limit {
+                    gas += bundle.gas_limit();
+                    bundles.push(bundle);
+                } else {
+                    deferred.push(bundle);
+                }
+            }
+            if !deferred.is_empty() {
+                lane.requeue(deferred).await;
             }
         }
+        let bundled = bundles.iter().map(Bundle::len).sum::<usize>();
         
         // Step 2: Get normal transactions from pool with gas limit enforcement
-        // Calculate how many we can take (leave room for forced txs if any)
-        let max_normal_txs = if accepted_forced_txs.is_empty() {
-            self.config.max_batch_size
-        } else {
-            self.config.max_batch_size.saturating_sub(accepted_forced_txs.len())
+        // Calculate how many we can take (leave room for forced txs, decrypted and bundled txs, and user operations)
+        let max_normal_txs = self.config.max_batch_size
+            .saturating_sub(accepted_forced_txs.len() + decrypted.len() + bundled + accepted_user_ops.len());
+        
+        // Transactions whose deadline would pass before the next batch go ahead of the policy
+        let due = match self.deadline_margin_secs.filter(|_| !safe_mode) {
+            Some(margin) => {
+                let next_batch = self.config.timeout_interval_ms.div_ceil(1000);
+                let before = self.now().await.saturating_add(next_batch).saturating_add(margin);
+                self.tx_pool.take_due(before, max_normal_txs).await
+            }
+            None => Vec::new(),
         };
+        let room = max_normal_txs - due.len();
         
-        let normal_txs = self.tx_pool.get_pending(max_normal_txs).await;
+        let (taken, sender_guard) = if safe_mode {
+            (Vec::new(), None)
+        } else {
+            if policy.fee_ordered() {
+                let normal_txs = self.tx_pool.take_by_priority(room).await;
+                self.apply_sender_guard(normal_txs, room).await
+            } else if let Some(base_fee) = policy.fair_share(U256::from(self.config.base_fee_wei)) {
+                (self.tx_pool.take_fair(room, base_fee).await, None)
+            } else {
+                (self.tx_pool.take(room).await, None)
+            }
+        };
+        let normal_txs: Vec<_> = due.into_iter().chain(taken).collect();
         
         // Step 2a: Filter normal transactions to respect gas limit
         let mut accepted_normal_txs = Vec::new();
         let mut combined_txs = accepted_forced_txs.clone();
+        combined_txs.extend(accepted_user_ops.iter().cloned());
+        combined_txs.extend(decrypted.iter().cloned().map(Transaction::Normal));
+        combined_txs.extend(bundles.iter().flat_map(|bundle| bundle.transactions.iter().cloned()).map(Transaction::Normal));
         
-        for tx in normal_txs {
+        // Taken but not included; returned to the pool so they keep their nonces
+        let mut held = Vec::new();
+        let mut normal_txs = normal_txs.into_iter();
+        while let Some(tx) = normal_txs.next() {
+            // Senders with bundles still pending wait for them, keeping nonce order
+            if let Some(lane) = &self.bundles
+                && lane.has_pending(&tx.from).await
+            {
+                held.push(tx);
+                continue;
+            }
             let wrapped_tx = Transaction::Normal(tx);
             if engine.can_add_transaction(&combined_txs, &wrapped_tx) {
                 combined_txs.push(wrapped_tx.clone());
                 accepted_normal_txs.push(wrapped_tx);
-            } else {
-                // Gas limit reached, stop adding transactions
+            } else if let Transaction::Normal(tx) = wrapped_tx {
+                // Gas limit reached, stop adding transactions; the rest wait for the next batch
                 debug!("Gas limit reached, stopping transaction addition");
-                break;
+                held.push(tx);
+                held.extend(normal_txs.by_ref());
             }
         }
         
+        if !held.is_empty() {
+            self.tx_pool.requeue(held).await;
+        }
+        
         // Release the read lock before scheduling
         drop(engine);
         
         // If no transactions at all, return None
-        if accepted_forced_txs.is_empty() && accepted_normal_txs.is_empty() {
+        if accepted_forced_txs.is_empty() && decrypted.is_empty() && bundles.is_empty()
+            && accepted_normal_txs.is_empty() && accepted_user_ops.is_empty()
+        {
             return Ok(None);
         }
+        self.track_forced_deadlines(&accepted_forced_txs, &deferred_forced_txs);
         
-        debug!("Scheduling {} forced + {} normal transactions", 
+        debug!("Scheduling {} forced + {} decrypted + {} bundled + {} normal transactions + {} user operations", 
                accepted_forced_txs.len(), 
-               accepted_normal_txs.len());
+               decrypted.len(),
+               bundled,
+               accepted_normal_txs.len(),
+               accepted_user_ops.len());
         
-        // Step 3: Combine all accepted transactions in order (forced first, then normal)
-        let mut all_txs = accepted_forced_txs;
-        all_txs.extend(accepted_normal_txs);
+        // Step 3: Order the accepted transactions (forced first, then normal by policy, then user operations)
+        // Decrypted transactions go right after the forced ones, in the order committed to on
+        // arrival, then each bundle as a unit
+        let forced: Vec<_> = accepted_forced_txs
+            .into_iter()
+            .filter_map(|tx| match tx {
+                Transaction::Forced(tx) => Some(tx),
+                _ => None,
+            })
+            .collect();
+        let normal: Vec<_> = accepted_normal_txs
+            .into_iter()
+            .filter_map(|tx| match tx {
+                Transaction::Normal(tx) => Some(tx),
+                _ => None,
+            })
+            .collect();
+        let user_ops: Vec<UserOperation> = accepted_user_ops
+            .into_iter()
+            .filter_map(|tx| match tx {
+                Transaction::UserOp(op) => Some(op),
+                _ => None,
+            })
+            .collect();
+        let forced_count = forced.len();
+        let mut all_txs = policy.scheduler.schedule_with_user_ops(
+            forced,
+            normal,
+            user_ops,
+            U256::from(self.config.base_fee_wei),
+        );
+        let front: Vec<_> = decrypted
+            .into_iter()
+            .chain(bundles.iter().flat_map(|bundle| bundle.transactions.iter().cloned()))
+            .map(Transaction::Normal)
+            .collect();
+        all_txs.splice(forced_count..forced_count, front);
+        let auction_winners = self.auction.take_winners();
         
         // Calculate and log total gas
         let total_gas: u64 = all_txs.iter().map(|tx| tx.gas_limit()).sum();
         debug!("Batch total gas: {} / {}", total_gas, self.config.max_gas_limit);
         
+        // Steps 4-6: Seal, record, and archive
+        let batch = self.seal(all_txs, sender_guard, policy.scheduler.policy_name()).await;
+        self.warn_missed_deadlines(&batch);
+        self.record_auction(&batch, auction_winners).await;
+        self.record_bundles(&batch, &bundles).await;
+        Ok(Some(batch))
+    }
+    
+    /// Warn about transactions sealed after their deadline
+    /// 
+    /// They are still sealed: the deadline is a scheduling hint, not part of
+    /// what the sender signed.
+    fn warn_missed_deadlines(&self, batch: &Batch) {
+        for tx in &batch.transactions {
+            if let Transaction::Normal(tx) = tx
+                && let Some(deadline) = tx.deadline
+                && deadline < batch.timestamp
+            {
+                warn!("Transaction {:?} sealed {}s past its deadline", tx.hash(), batch.timestamp - deadline);
+            }
+        }
+    }
+    
+    /// Current time by the batch clock, or the system clock if it is unavailable
+    async fn now(&self) -> u64 {
+        match self.clock.now().await {
+            Ok(now) => now,
+            Err(_) => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+    
+    /// Record where a batch's bundles were sealed
+    async fn record_bundles(&self, batch: &Batch, bundles: &[Bundle]) {
+        if bundles.is_empty() {
+            return;
+        }
+        let inclusions: Vec<BundleInclusion> = bundles
+            .iter()
+            .filter_map(|bundle| {
+                let first = bundle.transactions.first()?.hash();
+                let position = batch.transactions
+                    .iter()
+                    .position(|tx| matches!(tx, Transaction::Normal(tx) if tx.hash() == first))?;
+                Some(BundleInclusion { batch_id: batch.batch_id, position, bundle_hash: bundle.hash(), size: bundle.len() })
+            })
+            .collect();
+        if let Err(e) = self.registry.record_bundle_inclusions(&inclusions).await {
+            warn!("Failed to record the bundles of batch #{}: {:?}", batch.batch_id, e);
+        }
+    }
+    
+    /// Decrypt ready encrypted transactions and admit each like a submission
+    /// 
+    /// A decrypted transaction must stay within the gas limit it declared and
+    /// pass validation. Its sender must have no transaction pending in the
+    /// pool, which the batch would otherwise place after it, out of nonce order.
+    /// 
+    /// # Returns
+    /// The admitted transactions, in lane order
+    async fn admit_decrypted(
+        &self,
+        encrypted: &EncryptedLane,
+        ready: Vec<(EncryptedTransaction, Bytes)>,
+    ) -> Vec<UserTransaction> {
+        let mut admitted = Vec::with_capacity(ready.len());
+        for (envelope, key) in ready {
+            let tx = match decrypt_transaction(&envelope, &key) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    warn!("Encrypted transaction {:?} dropped: {}", envelope.hash(), e);
+                    continue;
+                }
+            };
+            let outcome = match self.admit_decrypted_tx(encrypted, &tx, envelope.gas_limit).await {
+                Ok(()) => {
+                    admitted.push(tx.clone());
+                    TransactionOutcome::Accepted
+                }
+                Err(e) => {
+                    warn!("Decrypted transaction {:?} rejected: {}", tx.hash(), e);
+                    TransactionOutcome::Rejected { reason: e.to_string() }
+                }
+            };
+            let record = TransactionRecord {
+                tx_hash: tx.hash(),
+                sender: tx.from,
+                nonce: tx.nonce,
+                outcome,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            };
+            if let Err(e) = self.registry.record_transaction(record).await {
+                warn!("Failed to record outcome for decrypted transaction {:?}: {:?}", tx.hash(), e);
+            }
+        }
+        admitted
+    }
+    
+    /// Admission of one decrypted transaction (see [`Self::admit_decrypted`])
+    async fn admit_decrypted_tx(
+        &self,
+        encrypted: &EncryptedLane,
+        tx: &UserTransaction,
+        declared_gas: u64,
+    ) -> Result<(), ValidationError> {
+        if tx.gas_limit > declared_gas {
+            return Err(ValidationError::InvalidEncryption {
+                reason: format!("gas limit {} above the {} declared", tx.gas_limit, declared_gas),
+            });
+        }
+        let turn = encrypted.validation.validate(tx).await?;
+        if let Some(pool) = encrypted.validation.validator().pool()
+            && !pool.pending_nonces(&tx.from).await.is_empty()
+        {
+            return Err(ValidationError::InvalidEncryption {
+                reason: format!("sender {:?} has transactions pending in the pool", tx.from),
+            });
+        }
+        if let Some(lane) = &self.bundles
+            && lane.has_pending(&tx.from).await
+        {
+            return Err(ValidationError::InvalidEncryption {
+                reason: format!("sender {:?} has bundles pending", tx.from),
+            });
+        }
+        self.state_cache.increment_nonce(&tx.from, tx.hash()).await;
+        drop(turn);
+        Ok(())
+    }
+    
+    /// Record the winning bids of a batch's auction, at their final positions
+    async fn record_auction(&self, batch: &Batch, winners: Vec<OpenedBid>) {
+        if winners.is_empty() {
+            return;
+        }
+        let positions: HashMap<H256, usize> = batch.transactions
+            .iter()
+            .enumerate()
+            .filter_map(|(position, tx)| match tx {
+                Transaction::Normal(tx) => Some((tx.hash(), position)),
+                _ => None,
+            })
+            .collect();
+        let wins: Vec<AuctionWin> = winners
+            .into_iter()
+            .filter_map(|bid| {
+                Some(AuctionWin {
+                    batch_id: batch.batch_id,
+                    position: *positions.get(&bid.tx_hash)?,
+                    tx_hash: bid.tx_hash,
+                    bidder: bid.bidder,
+                    amount: bid.amount,
+                })
+            })
+            .collect();
+        let proceeds = wins.iter().fold(U256::zero(), |total, win| total + win.amount);
+        info!("Batch #{} auctioned {} top slots for {} wei", batch.batch_id, wins.len(), proceeds);
+        if let Err(e) = self.registry.record_auction_wins(&wins).await {
+            warn!("Failed to record the auction of batch #{}: {:?}", batch.batch_id, e);
+        }
+    }
+    
+    /// Seal an externally proposed batch in the proposer's order
+    /// 
+    /// The proposal lists transaction hashes (`l1_tx_hash` for forced ones) and
+    /// is accepted only if:
+    /// - it starts with exactly the forced transactions the next produced batch
+    ///   would include, in L1 order
+    /// - every other transaction is pending, with each sender's nonces in order
+    ///   from its earliest pending one
+    /// - it fits the batch size and gas limits
+    /// 
+    /// Nothing is consumed from the pools when a proposal is rejected.
+    /// 
+    /// # Returns
+    /// * `Ok(Batch)` once sealed and recorded
+    /// * `Err(ProposalError)` if the proposal breaks any of the rules above
+    pub async fn seal_proposal(&self, hashes: &[H256]) -> Result<Batch, ProposalError> {
+        if hashes.is_empty() {
+            return Err(ProposalError::Empty);
+        }
+        if hashes.len() > self.config.max_batch_size {
+            return Err(ProposalError::TooLarge {
+                count: hashes.len(),
+                max_batch_size: self.config.max_batch_size,
+            });
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = hashes.iter().find(|hash| !seen.insert(**hash)) {
+            return Err(ProposalError::Duplicate { tx_hash: *duplicate });
+        }
+        
+        let _sealing = self.seal_lock.lock().await;
+        
+        // Forced transactions are selected exactly as for a produced batch
+        let engine = self.batch_engine.read().await;
+        let mut forced = Vec::new();
+        let mut deferred = Vec::new();
+        for tx in self.forced_queue.get_all().await {
+            let wrapped_tx = Transaction::Forced(tx);
+            if engine.can_add_transaction(&forced, &wrapped_tx) {
+                forced.push(wrapped_tx);
+            } else if let Transaction::Forced(tx) = wrapped_tx {
+                deferred.push(tx);
+            }
+        }
+        drop(engine);
+        
+        let expected: Vec<H256> = forced
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Forced(tx) => Some(tx.l1_tx_hash),
+                _ => None,
+            })
+            .collect();
+        if !hashes.starts_with(&expected) {
+            self.return_forced(forced, deferred).await;
+            return Err(ProposalError::ForcedInclusion { expected });
+        }
+        
+        let normal = match self.tx_pool.take_exact(&hashes[expected.len()..]).await {
+            Ok(normal) => normal,
+            Err(tx_hash) => {
+                self.return_forced(forced, deferred).await;
+                return Err(ProposalError::NotPending { tx_hash });
+            }
+        };
+        
+        let gas: u64 = forced.iter().map(|tx| tx.gas_limit()).sum::<u64>()
+            + normal.iter().map(|tx| tx.gas_limit).sum::<u64>();
+        if gas > self.config.max_gas_limit {
+            self.tx_pool.requeue(normal).await;
+            self.return_forced(forced, deferred).await;
+            return Err(ProposalError::GasLimitExceeded { gas, max_gas_limit: self.config.max_gas_limit });
+        }
+        self.track_forced_deadlines(&forced, &deferred);
+        if !deferred.is_empty() {
+            self.forced_queue.requeue(deferred).await;
+        }
+        
+        let mut all_txs = forced;
+        all_txs.extend(normal.into_iter().map(Transaction::Normal));
+        info!("Sealing externally proposed batch of {} transactions", all_txs.len());
+        Ok(self.seal(all_txs, None, EXTERNAL_PROPOSER_POLICY).await)
+    }
+    
+    /// Record which forced transactions a batch about to be sealed includes,
+    /// and which it leaves out, against their deadlines
+    fn track_forced_deadlines(&self, included: &[Transaction], left_out: &[ForcedTransaction]) {
+        let Some(deadlines) = &self.forced_deadlines else {
+            return;
+        };
+        let included: Vec<ForcedTransaction> = included
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Forced(tx) => Some(tx.clone()),
+                _ => None,
+            })
+            .collect();
+        deadlines.record_batch(&included, left_out, self.forced_queue.l1_head());
+    }
+    
+    /// Put forced transactions taken for a rejected proposal back, in L1 order
+    async fn return_forced(&self, accepted: Vec<Transaction>, deferred: Vec<ForcedTransaction>) {
+        let mut txs: Vec<ForcedTransaction> = accepted
+            .into_iter()
+            .filter_map(|tx| match tx {
+                Transaction::Forced(tx) => Some(tx),
+                _ => None,
+            })
+            .collect();
+        txs.extend(deferred);
+        if !txs.is_empty() {
+            self.forced_queue.requeue(txs).await;
+        }
+    }
+    
+    /// Export the whole state after the latest sealed batch
+    /// 
+    /// Waits for any batch being sealed or reinjected, and holds off the next
+    /// one until the state is collected (see [`StateCache::export_checkpoint`]).
+    pub async fn checkpoint(&self) -> anyhow::Result<StateCheckpoint> {
+        let _sealing = self.seal_lock.lock().await;
+        self.state_cache.export_checkpoint().await
+    }
+    
+    /// Seal ordered transactions into a batch, record it, and archive idle accounts
+    async fn seal(
+        &self,
+        all_txs: Vec<Transaction>,
+        mut sender_guard: Option<SenderGuardAdjustment>,
+        policy: &str,
+    ) -> Batch {
         // Step 4: Create sealed batch
+        // If the clock is unavailable, the engine reuses the previous timestamp
+        let now = self.clock.now().await.unwrap_or_else(|e| {
+            warn!("Batch clock unavailable: {:?}", e);
+            0
+        });
         let mut engine = self.batch_engine.write().await;
-        let batch = engine.create_batch(all_txs);
+        let mut batch = engine.create_batch(all_txs, now);
+        drop(engine);
+        let touched = touched_accounts(&batch);
+        if let Some(max) = self.execution_lanes {
+            let mut contracts = HashSet::new();
+            for account in &touched {
+                if self.state_cache.get_account(account).await.is_contract() {
+                    contracts.insert(*account);
+                }
+            }
+            batch.lanes = Some(execution_lanes(&batch.transactions, max, &contracts));
+        }
+        self.fairness.record(batch_fairness(&batch, policy, U256::from(self.config.base_fee_wei)));
         
-        Ok(Some(batch))
+        // Step 5: Apply the batch to the state, proving the touched accounts
+        // before and after for the prover
+        let pre_state = self.state_cache.witness(&touched).await;
+        let unpaid = self.execute(&batch).await;
+        let post_state = self.state_cache.witness(&touched).await;
+        let witness = BatchWitness { batch_id: batch.batch_id, pre_state, post_state };
+        let withdrawals = batch_withdrawals(&batch, &unpaid);
+        
+        // Step 6: Record the batch, its witness and its withdrawals in the
+        // registry, along with the size of its data before and after compression
+        // Registry failures are logged but don't discard an already-sealed batch
+        if let Some(adjustment) = &mut sender_guard {
+            adjustment.batch_id = batch.batch_id;
+        }
+        let encoded = self.encode(&batch);
+        if let Err(e) = self.record_batch(&batch, sender_guard.as_ref(), policy, &unpaid, &encoded).await {
+            warn!("Failed to record batch #{} in registry: {:?}", batch.batch_id, e);
+        }
+        if let Err(e) = self.registry.record_batch_witness(&witness).await {
+            warn!("Failed to record the witness of batch #{}: {:?}", batch.batch_id, e);
+        }
+        if let Err(e) = self.registry.record_withdrawals(&withdrawals).await {
+            warn!("Failed to record the withdrawals of batch #{}: {:?}", batch.batch_id, e);
+        }
+        let status = BatchSubmission {
+            batch_id: batch.batch_id,
+            status: BatchStatus::Sealed,
+            l1_tx_hash: None,
+            l1_block_number: None,
+            verified_tx_hash: None,
+            verified_block_number: None,
+            updated_at: batch.timestamp,
+        };
+        if let Err(e) = self.registry.set_batch_status(&status).await {
+            warn!("Failed to record the status of batch #{}: {:?}", batch.batch_id, e);
+        }
+        
+        // Step 7: Commit to the resulting state for the next batch
+        self.state_cache.advance_batch(batch.batch_id);
+        let state_root = self.state_cache.state_root();
+        self.batch_engine.write().await.set_state_root(state_root);
+        debug!("State root after batch #{}: {:?}", batch.batch_id, state_root);
+        
+        // Step 8: Move long-idle accounts out of the hot state
+        if let Some(inactive_batches) = self.archive_after_batches {
+            match self.state_cache.archive_inactive(inactive_batches).await {
+                Ok(0) => {}
+                Ok(count) => info!("Archived {} inactive accounts", count),
+                Err(e) => warn!("Failed to archive inactive accounts: {:?}", e),
+            }
+        }
+        
+        // Step 9: Drop history that fell out of the retention window
+        if let Some(keep) = self.keep_history_batches {
+            let before = (batch.batch_id + 1).saturating_sub(keep.max(1));
+            match self.registry.prune_history(before).await {
+                Ok(0) => {}
+                Ok(count) => debug!("Pruned {} historical records before batch #{}", count, before),
+                Err(e) => warn!("Failed to prune history before batch #{}: {:?}", before, e),
+            }
+        }
+        
+        // Step 10: Hand the batch to the L1 submitter
+        if let Some(submissions) = &self.submissions {
+            let sealed = SealedBatch {
+                batch: batch.clone(),
+                state_root,
+                withdrawal_root: withdrawal_root(&withdrawals),
+                data: encoded.data,
+            };
+            if submissions.send(sealed).is_err() {
+                warn!("L1 batch submitter stopped, batch #{} will not be submitted", batch.batch_id);
+            }
+        }
+        
+        batch
+    }
+    
+    /// Return the transactions of a failed or reverted batch to their pools
+    /// 
+    /// Normal transactions go back to the front of the transaction pool,
+    /// forced transactions to the front of the forced queue and user operations
+    /// to the front of their lane, all in their batch order, so they are picked
+    /// up first by the next batch. Each normal transaction and user operation is
+    /// recorded as `Reinjected` in the registry, forced transactions are no
+    /// longer recorded as included, and the batch's withdrawals are forgotten.
+    /// 
+    /// The state changes made while sealing the batch are rolled back, along
+    /// with those of every later batch, and the next batch is sealed on the
+    /// restored state root. The nonces of the returned transactions and user
+    /// operations are reserved again, as they are pending once more. When
+    /// several batches fail, reinject the newest first.
+    /// 
+    /// # Arguments
+    /// * `batch` - The batch that failed to post or was reverted
+    pub async fn reinject(&self, batch: Batch) {
+        let _sealing = self.seal_lock.lock().await;
+        self.reinject_sealed(batch).await;
+    }
+    
+    /// Reinject a batch that failed on L1, along with every later batch awaiting submission
+    /// 
+    /// The later batches were sealed on top of the failed one, so they are
+    /// taken from `pending` and reinjected too, newest first. Sealing is held
+    /// off meanwhile, so no batch is sealed on the failed one's state after
+    /// `pending` was drained.
+    /// 
+    /// # Returns
+    /// The IDs of the reinjected batches, oldest first
+    pub async fn reinject_failed(&self, batch: Batch, pending: &mut mpsc::UnboundedReceiver<SealedBatch>) -> Vec<u64> {
+        let _sealing = self.seal_lock.lock().await;
+        let mut batches = vec![batch];
+        while let Ok(later) = pending.try_recv() {
+            batches.push(later.batch);
+        }
+        let batch_ids = batches.iter().map(|batch| batch.batch_id).collect();
+        for batch in batches.into_iter().rev() {
+            self.reinject_sealed(batch).await;
+        }
+        batch_ids
     }
+    
+    /// Body of `reinject`, for callers already holding the seal lock
+    async fn reinject_sealed(&self, batch: Batch) {
+        let batch_id = batch.batch_id;
+        match self.state_cache.rollback(batch_id).await {
+            Ok(count) => info!("Rolled back {} account changes of batch #{}", count, batch_id),
+            Err(e) => warn!("Failed to roll back the state of batch #{}: {:?}", batch_id, e),
+        }
+        self.batch_engine.write().await.set_state_root(self.state_cache.state_root());
+        
+        // Bundles go back to their lane whole (their transactions to the pool if it is gone)
+        let inclusions = match self.registry.bundle_inclusions(batch_id).await {
+            Ok(inclusions) => inclusions,
+            Err(e) => {
+                warn!("Failed to read the bundles of batch #{}: {:?}", batch_id, e);
+                Vec::new()
+            }
+        };
+        let mut transactions = batch.transactions;
+        let mut bundles = Vec::new();
+        if self.bundles.is_some() {
+            // From the back, so earlier positions stay valid
+            for inclusion in inclusions.iter().rev() {
+                let end = (inclusion.position + inclusion.size).min(transactions.len());
+                let start = inclusion.position.min(end);
+                let bundled = transactions
+                    .drain(start..end)
+                    .filter_map(|tx| match tx {
+                        Transaction::Normal(tx) => Some(tx),
+                        _ => None,
+                    })
+                    .collect();
+                bundles.push(Bundle { transactions: bundled });
+            }
+            bundles.reverse();
+        }
+        
+        let mut forced = Vec::new();
+        let mut normal = Vec::new();
+        let mut user_ops = Vec::new();
+        for tx in transactions {
+            match tx {
+                Transaction::Forced(tx) => forced.push(tx),
+                Transaction::Normal(tx) => normal.push(tx),
+                Transaction::UserOp(op) => user_ops.push(op),
+            }
+        }
+        warn!("Reinjecting batch #{}: {} forced + {} normal transactions + {} bundles + {} user operations",
+              batch_id,
+              forced.len(),
+              normal.len(),
+              bundles.len(),
+              user_ops.len());
+        
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let records = normal
+            .iter()
+            .chain(bundles.iter().flat_map(|bundle| &bundle.transactions))
+            .map(|tx| (tx.hash(), tx.from, tx.nonce))
+            .chain(user_ops.iter().map(|op| (op.hash(), op.sender, op.nonce)));
+        for (tx_hash, sender, nonce) in records {
+            self.state_cache.reservations().reserve(sender, nonce, tx_hash);
+            let record = TransactionRecord {
+                tx_hash,
+                sender,
+                nonce,
+                outcome: TransactionOutcome::Reinjected { batch_id },
+                timestamp,
+            };
+            if let Err(e) = self.registry.record_transaction(record).await {
+                warn!("Failed to record reinjection of {:?}: {:?}", tx_hash, e);
+            }
+        }
+        
+        if !forced.is_empty() {
+            if let Err(e) = self.registry.mark_forced_included(&forced, None).await {
+                warn!("Failed to record reinjection of forced transactions: {:?}", e);
+            }
+            if let Err(e) = self.registry.forget_withdrawals(batch_id).await {
+                warn!("Failed to forget the withdrawals of batch #{}: {:?}", batch_id, e);
+            }
+            self.forced_queue.requeue(forced).await;
+        }
+        if !normal.is_empty() {
+            if let Err(e) = self.registry.forget_auction_wins(batch_id).await {
+                warn!("Failed to forget the auction of batch #{}: {:?}", batch_id, e);
+            }
+            self.tx_pool.requeue(normal).await;
+        }
+        if !inclusions.is_empty()
+            && let Err(e) = self.registry.forget_bundle_inclusions(batch_id).await
+        {
+            warn!("Failed to forget the bundles of batch #{}: {:?}", batch_id, e);
+        }
+        if let Some(lane) = &self.bundles
+            && !bundles.is_empty()
+        {
+            lane.requeue(bundles).await;
+        }
+        if !user_ops.is_empty() {
+            match &self.user_ops {
+                Some(lane) => lane.requeue(user_ops).await,
+                None => warn!("No user operation lane, dropping {} reinjected user operations", user_ops.len()),
+            }
+        }
+    }
+    
+    /// Enforce the minimum distinct senders guard on a selection of normal transactions
+    /// 
+    /// If the selection has too few senders, the earliest pending transaction of
+    /// other senders is pulled from the pool. Room is made by returning the latest
+    /// transactions of the most frequent senders to the front of the pool; a
+    /// sender is never displaced entirely.
+    /// 
+    /// # Returns
+    /// The adjusted selection, and the adjustment made (if any). The adjustment's
+    /// `batch_id` is filled in once the batch is sealed.
+    async fn apply_sender_guard(
+        &self,
+        mut normal: Vec<UserTransaction>,
+        max: usize,
+    ) -> (Vec<UserTransaction>, Option<SenderGuardAdjustment>) {
+        let Some(min) = self.min_distinct_senders else {
+            return (normal, None);
+        };
+        let senders: HashSet<Address> = normal.iter().map(|tx| tx.from).collect();
+        if senders.len() >= min {
+            return (normal, None);
+        }
+        
+        let mut extra = self.tx_pool.take_from_new_senders(&senders, min - senders.len()).await;
+        if extra.is_empty() {
+            // The pool has no other senders right now
+            return (normal, None);
+        }
+        
+        // Pick displaced transactions: the latest one of the currently most frequent sender
+        let mut counts: HashMap<Address, usize> = HashMap::new();
+        for tx in &normal {
+            *counts.entry(tx.from).or_default() += 1;
+        }
+        let mut displaced_idx = HashSet::new();
+        let mut room = max.saturating_sub(normal.len());
+        while room < extra.len() {
+            let Some(sender) = counts
+                .iter()
+                .filter(|(_, count)| **count > 1)
+                .max_by_key(|(sender, count)| (**count, **sender))
+                .map(|(sender, _)| *sender)
+            else {
+                break;
+            };
+            if let Some(idx) = (0..normal.len())
+                .rev()
+                .find(|idx| !displaced_idx.contains(idx) && normal[*idx].from == sender)
+            {
+                displaced_idx.insert(idx);
+            }
+            *counts.entry(sender).or_default() -= 1;
+            room += 1;
+        }
+        
+        // Return displaced transactions (and any extras that still don't fit) in queue order
+        let overflow = extra.split_off(room.min(extra.len()));
+        let mut requeued = Vec::new();
+        let mut kept = Vec::new();
+        for (idx, tx) in normal.drain(..).enumerate() {
+            if displaced_idx.contains(&idx) {
+                requeued.push(tx);
+            } else {
+                kept.push(tx);
+            }
+        }
+        let displaced = requeued.len();
+        requeued.extend(overflow);
+        if !requeued.is_empty() {
+            self.tx_pool.requeue(requeued).await;
+        }
+        if extra.is_empty() {
+            return (kept, None);
+        }
+        
+        let adjustment = SenderGuardAdjustment {
+            batch_id: 0,
+            senders_before: senders.len(),
+            senders_after: senders.len() + extra.len(),
+            displaced,
+        };
+        info!("Sender guard raised distinct senders from {} to {} ({} transactions displaced)",
+              adjustment.senders_before,
+              adjustment.senders_after,
+              adjustment.displaced);
+        kept.extend(extra);
+        (kept, Some(adjustment))
+    }
+    
+    /// Apply a sealed batch to the state, in batch order
+    /// 
+    /// Deposits credit their recipient and forced exits debit their sender.
+    /// Normal transactions move their value and pay their effective gas price
+    /// at the configured base fee for their whole gas limit; user operations
+    /// pay the same way. The changes are recorded in a snapshot of the batch,
+    /// so `reinject` can undo them.
+    /// 
+    /// # Returns
+    /// The L2 balance of each forced exit's sender that the exit exceeded, by L1 event.
+    /// Those exits are not debited.
+    async fn execute(&self, batch: &Batch) -> HashMap<(H256, u64), U256> {
+        let batch_id = batch.batch_id;
+        let base_fee = U256::from(self.config.base_fee_wei);
+        self.state_cache.snapshot(batch_id);
+        let mut unpaid = HashMap::new();
+        for tx in &batch.transactions {
+            match tx {
+                Transaction::Forced(tx) => {
+                    if let Err(ValidationError::InsufficientBalance { available, .. }) =
+                        self.state_cache.apply_forced(batch_id, tx).await
+                    {
+                        warn!("Forced exit {:?} of {} exceeds the L2 balance {} of {:?}, not debited",
+                              tx.l1_tx_hash, tx.value, available, tx.from);
+                        unpaid.insert(tx.l1_event_key(), available);
+                    }
+                }
+                Transaction::Normal(tx) => {
+                    let fee = tx.effective_gas_price(base_fee).saturating_mul(U256::from(tx.gas_limit));
+                    if let Err(e) = self.state_cache.apply_transfer(batch_id, tx.hash(), &tx.from, &tx.to, tx.value, fee).await {
+                        warn!("Transaction {:?} reverted in batch #{}: {}", tx.hash(), batch_id, e);
+                    }
+                }
+                Transaction::UserOp(op) => {
+                    let price = op.max_fee_per_gas.min(base_fee.saturating_add(op.max_priority_fee_per_gas));
+                    let fee = price.saturating_mul(U256::from(op.gas_limit()));
+                    if let Err(e) = self.state_cache.apply_transfer(batch_id, op.hash(), &op.sender, &op.sender, U256::zero(), fee).await {
+                        warn!("User operation {:?} reverted in batch #{}: {}", op.hash(), batch_id, e);
+                    }
+                }
+            }
+        }
+        unpaid
+    }
+    
+    /// Encode a batch as posted to L1, compressed with the configured codec
+    /// 
+    /// Should compression fail, the batch is posted uncompressed.
+    fn encode(&self, batch: &Batch) -> EncodedBatch {
+        let json = serde_json::to_vec(batch).expect("batch serialization cannot fail");
+        let raw_size = json.len();
+        match compress_batch(&json, self.compression, self.compression_level) {
+            Ok(data) => EncodedBatch { data, codec: self.compression, raw_size },
+            Err(e) => {
+                warn!("Failed to compress batch #{}, posting it uncompressed: {:?}", batch.batch_id, e);
+                EncodedBatch { data: json, codec: BatchCompression::None, raw_size }
+            }
+        }
+    }
+    
+    /// Store batch metadata and mark each normal transaction and user operation as batched
+    /// 
+    /// Also records the sender guard adjustment made for this batch, if any.
+    /// `policy` is the scheduling policy stored with the batch metadata,
+    /// `unpaid` the forced exits that exceeded their sender's balance, and
+    /// `encoded` the batch data posted to L1.
+    async fn record_batch(
+        &self,
+        batch: &Batch,
+        sender_guard: Option<&SenderGuardAdjustment>,
+        policy: &str,
+        unpaid: &HashMap<(H256, u64), U256>,
+        encoded: &EncodedBatch,
+    ) -> anyhow::Result<()> {
+        let forced_tx_count = batch.transactions
+            .iter()
+            .filter(|tx| matches!(tx, Transaction::Forced(_)))
+            .count();
+        
+        self.registry.store(BatchMetadata {
+            batch_id: batch.batch_id,
+            tx_count: batch.transactions.len(),
+            forced_tx_count,
+            timestamp: batch.timestamp,
+            scheduling_policy: policy.to_string(),
+            compression: encoded.codec.as_str().to_string(),
+            raw_size: encoded.raw_size,
+            compressed_size: encoded.data.len(),
+        }).await?;
+        
+        if let Some(adjustment) = sender_guard {
+            self.registry.record_sender_guard(adjustment).await?;
+        }
+        
+        let forced: Vec<ForcedTransaction> = batch.transactions
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Forced(tx) => Some(tx.clone()),
+                _ => None,
+            })
+            .collect();
+        self.registry.mark_forced_included(&forced, Some(batch.batch_id)).await?;
+        // Deposits are credited once their batch is committed to L1, or right
+        // away if no batch is submitted
+        if self.submissions.is_none() {
+            self.registry.credit_deposits(batch.batch_id).await?;
+        }
+        
+        // Sealing a batch moves its senders' nonces and the balances of every
+        // account it touches; snapshot those accounts so reads can be pinned
+        // to this batch later
+        let mut nonces: HashMap<Address, u64> = HashMap::new();
+        let mut touched = Vec::new();
+        for tx in &batch.transactions {
+            let (sender, nonce) = match tx {
+                Transaction::Normal(tx) => {
+                    touched.push(tx.to);
+                    (tx.from, tx.nonce)
+                }
+                Transaction::UserOp(op) => (op.sender, op.nonce),
+                Transaction::Forced(tx) => {
+                    touched.push(match tx.event_type {
+                        ForcedEventType::Deposit | ForcedEventType::Message => tx.to,
+                        ForcedEventType::ForcedExit => tx.from,
+                    });
+                    continue;
+                }
+            };
+            let next = nonces.entry(sender).or_default();
+            *next = (*next).max(nonce + 1);
+        }
+        // Only senders' nonces move: others keep their last snapshotted one
+        for address in touched {
+            if let Entry::Vacant(entry) = nonces.entry(address) {
+                let previous = self.registry.account_at_batch(address, batch.batch_id).await?;
+                entry.insert(previous.map(|account| account.nonce).unwrap_or_default());
+            }
+        }
+        let mut snapshots = Vec::with_capacity(nonces.len());
+        for (address, nonce) in nonces {
+            let balance = self.state_cache.get_balance(&address).await.unwrap_or_default();
+            snapshots.push(AccountState { address, balance, nonce, ..Default::default() });
+        }
+        self.registry.record_account_snapshots(batch.batch_id, &snapshots).await?;
+        
+        for tx in &batch.transactions {
+            match tx {
+                Transaction::Normal(tx) => {
+                    self.registry.record_transaction(TransactionRecord {
+                        tx_hash: tx.hash(),
+                        sender: tx.from,
+                        nonce: tx.nonce,
+                        outcome: TransactionOutcome::Batched { batch_id: batch.batch_id },
+                        timestamp: batch.timestamp,
+                    }).await?;
+                }
+                Transaction::UserOp(op) => {
+                    self.registry.record_transaction(TransactionRecord {
+                        tx_hash: op.hash(),
+                        sender: op.sender,
+                        nonce: op.nonce,
+                        outcome: TransactionOutcome::Batched { batch_id: batch.batch_id },
+                        timestamp: batch.timestamp,
+                    }).await?;
+                }
+                Transaction::Forced(tx) => {
+                    if let Some(forfeiture) = self.check_express_exit(tx, batch.batch_id, unpaid) {
+                        warn!("Express exit {:?} is spam, bond forfeited: {}",
+                              forfeiture.l1_tx_hash, forfeiture.reason);
+                        self.registry.record_bond_forfeiture(&forfeiture).await?;
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check whether a bonded express exit is spam
+    /// 
+    /// An express exit that asks to withdraw more than the sender's L2 balance
+    /// can never be honored; it only jumped the batch queue, so its bond is forfeited.
+    /// `unpaid` holds the exits of the batch that exceeded their sender's balance.
+    /// 
+    /// # Returns
+    /// `Some(BondForfeiture)` if the bond should be forfeited
+    fn check_express_exit(
+        &self,
+        tx: &ForcedTransaction,
+        batch_id: u64,
+        unpaid: &HashMap<(H256, u64), U256>,
+    ) -> Option<BondForfeiture> {
+        let bond = tx.bond?;
+        if !matches!(tx.event_type, ForcedEventType::ForcedExit) {
+            return None;
+        }
+        let balance = *unpaid.get(&tx.l1_event_key())?;
+        
+        Some(BondForfeiture {
+            l1_tx_hash: tx.l1_tx_hash,
+            sender: tx.from,
+            bond,
+            reason: format!("exit value {} exceeds L2 balance {}", tx.value, balance),
+            batch_id,
+        })
+    }
+}
+
+/// Accounts whose state a batch's execution reads or changes
+/// 
+/// Senders and recipients of normal transactions, user operation senders,
+/// deposit and message recipients and forced exit senders.
+fn touched_accounts(batch: &Batch) -> Vec<Address> {
+    batch.transactions
+        .iter()
+        .flat_map(Transaction::touched_accounts)
+        .collect()
 }
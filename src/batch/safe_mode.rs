@@ -0,0 +1,127 @@
+//! Safe Mode Module
+//!
+//! The sequencer falls back to a safe mode when its batches may no longer
+//! land on L1: the rollup contract has opened its escape hatch (the
+//! sequencer is considered down and only forced transactions are accepted),
+//! or its own commitments have kept failing for too long. In safe mode the
+//! orchestrator seals batches of forced transactions only, so users relying
+//! on L1 are served first, while pool transactions wait. Entering and leaving
+//! safe mode are logged as errors and warnings for operators to alert on, and
+//! reported in `/metrics` and `getSafeMode`.
+
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+/// Why and since when the sequencer is in safe mode
+///
+/// # Fields
+/// - `active`: Whether the sequencer is in safe mode
+/// - `escape_hatch`: Whether the rollup contract's escape hatch is open
+/// - `submissions_failing_secs`: How long commitments have been failing
+///   (`None` if the last one succeeded)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SafeModeStatus {
+    pub active: bool,
+    pub escape_hatch: bool,
+    pub submissions_failing_secs: Option<u64>,
+}
+
+/// Switch between normal operation and safe mode
+pub struct SafeMode {
+    /// Longest commitments may keep failing before safe mode (never if `None`)
+    max_submission_failure: Option<Duration>,
+    /// Whether the rollup contract's escape hatch is open
+    escape_hatch: AtomicBool,
+    /// When commitments started failing (`None` while they succeed)
+    failing_since: Mutex<Option<Instant>>,
+    /// Whether safe mode was on when last checked
+    active: AtomicBool,
+}
+
+impl SafeMode {
+    /// Creates a switch entering safe mode once commitments have failed for
+    /// `max_submission_failure`, or the escape hatch opens
+    pub fn new(max_submission_failure: Option<Duration>) -> Self {
+        Self {
+            max_submission_failure,
+            escape_hatch: AtomicBool::new(false),
+            failing_since: Mutex::new(None),
+            active: AtomicBool::new(false),
+        }
+    }
+
+    /// Record whether the rollup contract's escape hatch is open
+    pub fn set_escape_hatch(&self, open: bool) {
+        self.escape_hatch.store(open, Ordering::Relaxed);
+        self.is_active();
+    }
+
+    /// Record a failed attempt to commit a batch
+    pub fn submission_failed(&self) {
+        self.failing_since.lock().unwrap().get_or_insert_with(Instant::now);
+        self.is_active();
+    }
+
+    /// Record a committed batch
+    pub fn submission_succeeded(&self) {
+        *self.failing_since.lock().unwrap() = None;
+        self.is_active();
+    }
+
+    /// Why and since when the sequencer is in safe mode
+    pub fn status(&self) -> SafeModeStatus {
+        let escape_hatch = self.escape_hatch.load(Ordering::Relaxed);
+        let failing_for = self.failing_since.lock().unwrap().map(|since| since.elapsed());
+        let submissions_down = matches!(
+            (failing_for, self.max_submission_failure),
+            (Some(failing_for), Some(max)) if failing_for >= max
+        );
+        SafeModeStatus {
+            active: escape_hatch || submissions_down,
+            escape_hatch,
+            submissions_failing_secs: failing_for.map(|failing_for| failing_for.as_secs()),
+        }
+    }
+
+    /// Whether the sequencer is in safe mode
+    ///
+    /// Alerts when this changes since the last check.
+    pub fn is_active(&self) -> bool {
+        let status = self.status();
+        if self.active.swap(status.active, Ordering::Relaxed) != status.active {
+            if status.active {
+                error!(
+                    "Entering safe mode (escape hatch open: {}, commitments failing for {:?}s): sealing forced transactions only",
+                    status.escape_hatch, status.submissions_failing_secs
+                );
+            } else {
+                warn!("Leaving safe mode: sealing pool transactions again");
+            }
+        }
+        status.active
+    }
+
+    /// Safe mode gauges in the Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        let status = self.status();
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# HELP sequencer_safe_mode Whether only forced transactions are being sealed");
+        let _ = writeln!(out, "# TYPE sequencer_safe_mode gauge");
+        let _ = writeln!(out, "sequencer_safe_mode {}", status.active as u8);
+
+        let _ = writeln!(out, "# HELP sequencer_escape_hatch_open Whether the rollup contract's escape hatch is open");
+        let _ = writeln!(out, "# TYPE sequencer_escape_hatch_open gauge");
+        let _ = writeln!(out, "sequencer_escape_hatch_open {}", status.escape_hatch as u8);
+
+        let _ = writeln!(out, "# HELP sequencer_submissions_failing_seconds How long batch commitments have been failing");
+        let _ = writeln!(out, "# TYPE sequencer_submissions_failing_seconds gauge");
+        let _ = writeln!(out, "sequencer_submissions_failing_seconds {}", status.submissions_failing_secs.unwrap_or(0));
+
+        out
+    }
+}
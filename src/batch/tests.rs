@@ -0,0 +1,1376 @@
+//! Tests for the batch orchestrator
+//! 
+//! Drives `BatchOrchestrator::produce_batch` with scripted mock pools, and
+//! `reinject_failed` with the batches handed to a mock L1 submitter
+//! 
+//! Verifies:
+//! - Batch data survives every compression codec, calldata included
+//! - The economic trigger holds costly batches, but not for too long
+//! - Transactions left out for gas are requeued; forced ones meet deadlines
+//! - Forced exits make withdrawals provable against the submitted root
+//! - Bonded exits seal a batch at once, and forfeit their bond if unpayable
+//! - Safe mode seals forced transactions only once commitments keep failing
+//! - A switched scheduling policy orders batches from the next one on
+//! - Auctioned top slots go to the winning bids, recorded with their batch
+//! - Decrypted transactions are sealed right after the forced ones
+//! - Bundles are sealed whole, hold back their senders and requeue whole
+//! - Transactions due before the next batch go ahead of the policy
+//! - Batches carry execution lanes once enabled, and are measured
+//! - Offline builds read input line by line and seal what validation admits
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::{create_test_tx, create_test_tx_from},
+        batch::{
+            offline, compress_batch, decode_batch, withdrawal_root, BatchClock, BatchOrchestrator, EconomicTrigger,
+            ForcedDeadlines, PostingCosts, SafeMode, WithdrawalProof, EMPTY_WITHDRAWAL_ROOT,
+        },
+        config::{
+            BatchCompression, BatchConfig, BuiltinRule, Config, DatabaseConfig, EncryptedMempoolConfig, ForcedInclusionConfig,
+            TimestampSource, ValidationConfig,
+        },
+        pool::{encrypt_transaction, BundlePool, EncryptedPool, ForcedQueue, ForcedSource, PoolSource, UserOpPool},
+        registry::Registry,
+        scheduler::{fair_order, SchedulingPolicyType},
+        state::StateCache,
+        validation::{ValidationWorkers, Validator},
+        AccountState, AuctionWin, Batch, BatchStatus, Bundle, ForcedEventType, ForcedTransaction, ProposalError, SenderGuardAdjustment, Transaction,
+        TransactionOutcome, UserOperation, UserTransaction,
+    };
+    use async_trait::async_trait;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Address, Bytes, H256, U256};
+    use std::collections::{HashSet, VecDeque};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// Pool that hands out a fixed script of transactions and records each request size
+    #[derive(Default)]
+    struct MockPool {
+        pending: Mutex<VecDeque<UserTransaction>>,
+        requests: Mutex<Vec<usize>>,
+    }
+
+    impl MockPool {
+        fn with(txs: Vec<UserTransaction>) -> Self {
+            Self {
+                pending: Mutex::new(txs.into()),
+                requests: Mutex::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PoolSource for MockPool {
+        async fn len(&self) -> usize {
+            self.pending.lock().unwrap().len()
+        }
+
+        async fn take(&self, max: usize) -> Vec<UserTransaction> {
+            self.requests.lock().unwrap().push(max);
+            let mut pending = self.pending.lock().unwrap();
+            let len = pending.len();
+            pending.drain(..max.min(len)).collect()
+        }
+
+        async fn take_by_priority(&self, max: usize) -> Vec<UserTransaction> {
+            self.requests.lock().unwrap().push(max);
+            let mut pending = self.pending.lock().unwrap();
+            let mut taken = Vec::new();
+            while taken.len() < max {
+                // Each sender's first pending transaction is a candidate
+                let mut seen = HashSet::new();
+                let best = pending
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tx)| seen.insert(tx.from))
+                    .max_by_key(|(idx, tx)| (tx.gas_price, std::cmp::Reverse(*idx)))
+                    .map(|(idx, _)| idx);
+                match best.and_then(|idx| pending.remove(idx)) {
+                    Some(tx) => taken.push(tx),
+                    None => break,
+                }
+            }
+            taken
+        }
+
+        async fn take_fair(&self, max: usize, base_fee: Option<U256>) -> Vec<UserTransaction> {
+            self.requests.lock().unwrap().push(max);
+            let mut pending = self.pending.lock().unwrap();
+            let served = fair_order(pending.iter().cloned().enumerate().collect(), |(_, tx)| tx, base_fee);
+            let taken: Vec<_> = served.into_iter().take(max).collect();
+            let picked: HashSet<usize> = taken.iter().map(|(idx, _)| *idx).collect();
+            let mut idx = 0;
+            pending.retain(|_| {
+                idx += 1;
+                !picked.contains(&(idx - 1))
+            });
+            taken.into_iter().map(|(_, tx)| tx).collect()
+        }
+
+        async fn take_due(&self, before: u64, max: usize) -> Vec<UserTransaction> {
+            let mut pending = self.pending.lock().unwrap();
+            let mut due: Vec<UserTransaction> = Vec::new();
+            pending.retain(|tx| {
+                if due.len() >= max || tx.deadline.is_none_or(|deadline| deadline > before) {
+                    return true;
+                }
+                due.push(tx.clone());
+                false
+            });
+            due.sort_by_key(|tx| tx.deadline);
+            due
+        }
+
+        async fn earliest_deadline(&self) -> Option<u64> {
+            self.pending.lock().unwrap().iter().filter_map(|tx| tx.deadline).min()
+        }
+
+        async fn take_from_new_senders(&self, exclude: &HashSet<Address>, n: usize) -> Vec<UserTransaction> {
+            let mut pending = self.pending.lock().unwrap();
+            let mut seen = HashSet::new();
+            let mut taken = Vec::new();
+            pending.retain(|tx| {
+                if taken.len() >= n || exclude.contains(&tx.from) || !seen.insert(tx.from) {
+                    return true;
+                }
+                taken.push(tx.clone());
+                false
+            });
+            taken
+        }
+
+        async fn take_exact(&self, hashes: &[H256]) -> Result<Vec<UserTransaction>, H256> {
+            let mut pending = self.pending.lock().unwrap();
+            let mut remaining = pending.clone();
+            let mut taken = Vec::new();
+            for hash in hashes {
+                // Only a sender's earliest remaining transaction can be taken
+                let idx = remaining.iter().position(|tx| tx.hash() == *hash).ok_or(*hash)?;
+                if remaining.iter().take(idx).any(|tx| tx.from == remaining[idx].from) {
+                    return Err(*hash);
+                }
+                taken.extend(remaining.remove(idx));
+            }
+            *pending = remaining;
+            Ok(taken)
+        }
+
+        async fn requeue(&self, txs: Vec<UserTransaction>) {
+            let mut pending = self.pending.lock().unwrap();
+            for tx in txs.into_iter().rev() {
+                pending.push_front(tx);
+            }
+        }
+    }
+
+    /// Forced source that hands out a fixed script of transactions once
+    #[derive(Default)]
+    struct MockForced {
+        queued: Mutex<Vec<ForcedTransaction>>,
+    }
+
+    #[async_trait]
+    impl ForcedSource for MockForced {
+        async fn get_all(&self) -> Vec<ForcedTransaction> {
+            std::mem::take(&mut *self.queued.lock().unwrap())
+        }
+
+        async fn len(&self) -> usize {
+            self.queued.lock().unwrap().len()
+        }
+
+        async fn requeue(&self, txs: Vec<ForcedTransaction>) {
+            let mut queued = self.queued.lock().unwrap();
+            queued.splice(0..0, txs);
+        }
+    }
+
+    /// Helper function to create a test forced transaction
+    fn create_forced_tx(nonce: u64) -> ForcedTransaction {
+        ForcedTransaction {
+            tx_hash: H256::zero(),
+            from: Address::zero(),
+            to: Address::zero(),
+            value: U256::from(1000),
+            nonce,
+            gas_limit: 21000,
+            l1_tx_hash: H256::zero(),
+            l1_block_number: 1,
+            l1_log_index: 0,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        }
+    }
+
+    async fn create_registry() -> Arc<Registry> {
+        Arc::new(Registry::new(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+        }).await.unwrap())
+    }
+
+    async fn create_orchestrator(
+        forced: Arc<MockForced>,
+        pool: Arc<MockPool>,
+        policy: SchedulingPolicyType,
+    ) -> BatchOrchestrator {
+        create_orchestrator_with_registry(forced, pool, policy, create_registry().await)
+    }
+
+    fn create_orchestrator_with_registry(
+        forced: Arc<MockForced>,
+        pool: Arc<MockPool>,
+        policy: SchedulingPolicyType,
+        registry: Arc<Registry>,
+    ) -> BatchOrchestrator {
+        create_orchestrator_with_state(forced, pool, policy, registry, StateCache::new())
+    }
+
+    fn create_orchestrator_with_state(
+        forced: Arc<MockForced>,
+        pool: Arc<MockPool>,
+        policy: SchedulingPolicyType,
+        registry: Arc<Registry>,
+        state_cache: StateCache,
+    ) -> BatchOrchestrator {
+        BatchOrchestrator::new(forced, pool, state_cache, registry, create_batch_config(), policy)
+    }
+
+    fn create_batch_config() -> BatchConfig {
+        BatchConfig {
+            max_batch_size: 3,
+            timeout_interval_ms: 1000,
+            min_batch_size: 1,
+            max_gas_limit: 30_000_000,
+            timestamp_source: TimestampSource::WallClock,
+            base_fee_wei: 0,
+            max_l1_cost_per_tx_wei: None,
+            max_economic_delay_ms: 60_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_sources_produce_no_batch() {
+        let pool = Arc::new(MockPool::default());
+        let orchestrator = create_orchestrator(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+        ).await;
+
+        assert!(orchestrator.produce_batch().await.unwrap().is_none());
+        assert_eq!(*pool.requests.lock().unwrap(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_user_operations_get_their_share_of_the_batch_last() {
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx(1, 10),
+            create_test_tx(2, 10),
+            create_test_tx(3, 10),
+        ]));
+        let lane = Arc::new(UserOpPool::new(8));
+        for nonce in 0..3 {
+            lane.add(UserOperation {
+                sender: Address::from_low_u64_be(0xaa),
+                nonce,
+                call_data: Bytes::new(),
+                call_gas_limit: 50_000,
+                verification_gas_limit: 50_000,
+                pre_verification_gas: 21_000,
+                max_fee_per_gas: U256::from(10),
+                max_priority_fee_per_gas: U256::from(1),
+                chain_id: 31_337,
+                timestamp: nonce,
+                signature: Bytes::new(),
+            }).await.unwrap();
+        }
+        let orchestrator = create_orchestrator(Arc::new(MockForced::default()), pool.clone(), SchedulingPolicyType::Fcfs)
+            .await
+            .with_user_ops(lane.clone(), 2);
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        
+        // Two slots went to user operations, which come after the normal transaction
+        assert_eq!(*pool.requests.lock().unwrap(), vec![1]);
+        assert!(matches!(batch.transactions[0], Transaction::Normal(_)));
+        assert!(matches!(&batch.transactions[1], Transaction::UserOp(op) if op.nonce == 0));
+        assert!(matches!(&batch.transactions[2], Transaction::UserOp(op) if op.nonce == 1));
+        assert_eq!(lane.len().await, 1);
+        
+        // Reinjected user operations return to the front of their lane
+        orchestrator.reinject(batch).await;
+        let nonces: Vec<u64> = lane.take(3).await.iter().map(|op| op.nonce).collect();
+        assert_eq!(nonces, vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_forced_transactions_reserve_batch_slots() {
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![create_forced_tx(0)]),
+        });
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx(1, 10),
+            create_test_tx(2, 10),
+            create_test_tx(3, 10),
+        ]));
+        let orchestrator = create_orchestrator(forced, pool.clone(), SchedulingPolicyType::Fcfs).await;
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+
+        // One slot went to the forced transaction, so only two were requested
+        assert_eq!(*pool.requests.lock().unwrap(), vec![2]);
+        assert_eq!(batch.transactions.len(), 3);
+        assert!(matches!(batch.transactions[0], Transaction::Forced(_)));
+        assert_eq!(pool.pending.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sealed_forced_transactions_move_balances() {
+        let user = Address::from_low_u64_be(7);
+        let forced_tx = |log_index, event_type, value: u64, bond: Option<U256>| ForcedTransaction {
+            from: user,
+            to: user,
+            value: U256::from(value),
+            l1_log_index: log_index,
+            event_type,
+            bond,
+            ..create_forced_tx(0)
+        };
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![
+                forced_tx(0, ForcedEventType::Deposit, 1_000, None),
+                forced_tx(1, ForcedEventType::ForcedExit, 400, None),
+            ]),
+        });
+        let registry = create_registry().await;
+        let state_cache = StateCache::new();
+        let orchestrator = create_orchestrator_with_state(
+            forced.clone(),
+            Arc::new(MockPool::default()),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+            state_cache.clone(),
+        );
+
+        let first = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(state_cache.get_balance(&user).await, Some(U256::from(600)));
+        assert_eq!(state_cache.get_nonce(&user).await, Some(0));
+        let snapshot = registry.account_at_batch(user, first.batch_id).await.unwrap().unwrap();
+        assert_eq!(snapshot.balance, U256::from(600));
+
+        // An exit beyond the balance is not debited, and forfeits its express bond
+        forced.queued.lock().unwrap().push(forced_tx(2, ForcedEventType::ForcedExit, 5_000, Some(U256::from(1))));
+        let second = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(state_cache.get_balance(&user).await, Some(U256::from(600)));
+        assert_eq!(registry.bond_forfeitures().await.unwrap().len(), 1);
+
+        // The next batch commits to the state the deposit left
+        assert_eq!(first.prev_state_root, H256::zero());
+        assert_eq!(second.prev_state_root, state_cache.state_root());
+        assert_ne!(second.prev_state_root, H256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_bonded_exits_seal_a_batch_without_waiting_for_the_timeout() {
+        let forced = Arc::new(ForcedQueue::new());
+        let (submissions, mut sealed) = mpsc::unbounded_channel();
+        let orchestrator = Arc::new(BatchOrchestrator::new(
+            forced.clone(),
+            Arc::new(MockPool::default()),
+            StateCache::new(),
+            create_registry().await,
+            BatchConfig { timeout_interval_ms: 60_000, ..create_batch_config() },
+            SchedulingPolicyType::Fcfs,
+        ).with_submissions(submissions));
+        let running = orchestrator.clone();
+        let task = tokio::spawn(async move { running.start().await });
+        let exit = |log_index, bond: Option<U256>| ForcedTransaction {
+            event_type: ForcedEventType::ForcedExit,
+            l1_log_index: log_index,
+            bond,
+            ..create_forced_tx(0)
+        };
+        
+        // An ordinary exit waits for the timeout
+        assert!(forced.add(exit(0, None)).await);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(sealed.try_recv().is_err());
+        
+        // A bonded one is sealed right away, taking the waiting exit along
+        assert!(forced.add(exit(1, Some(U256::from(1)))).await);
+        let sealed = tokio::time::timeout(Duration::from_secs(5), sealed.recv()).await.unwrap().unwrap();
+        let log_indices: Vec<u64> = sealed.batch.transactions.iter().map(|tx| match tx {
+            Transaction::Forced(forced) => forced.l1_log_index,
+            other => panic!("unexpected transaction {:?}", other),
+        }).collect();
+        assert_eq!(log_indices, vec![0, 1]);
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_unpayable_express_exits_forfeit_their_bond() {
+        let user = Address::from_low_u64_be(7);
+        let exit = |log_index, value: u64, bond: Option<U256>| ForcedTransaction {
+            from: user,
+            to: user,
+            value: U256::from(value),
+            l1_tx_hash: H256::from_low_u64_be(log_index),
+            l1_log_index: log_index,
+            event_type: ForcedEventType::ForcedExit,
+            bond,
+            ..create_forced_tx(0)
+        };
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![
+                exit(0, 400, Some(U256::from(7))),
+                exit(1, 5_000, None),
+                exit(2, 5_000, Some(U256::from(9))),
+            ]),
+        });
+        let registry = create_registry().await;
+        let state_cache = StateCache::new();
+        state_cache.update(AccountState { address: user, balance: U256::from(1_000), ..Default::default() }).await;
+        let orchestrator = create_orchestrator_with_state(
+            forced,
+            Arc::new(MockPool::default()),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+            state_cache.clone(),
+        );
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        
+        // Only the bonded exit that could not be paid out forfeits its bond
+        assert_eq!(state_cache.get_balance(&user).await, Some(U256::from(600)));
+        let forfeitures = registry.bond_forfeitures().await.unwrap();
+        assert_eq!(forfeitures.len(), 1);
+        assert_eq!(forfeitures[0].l1_tx_hash, H256::from_low_u64_be(2));
+        assert_eq!(forfeitures[0].sender, user);
+        assert_eq!(forfeitures[0].bond, U256::from(9));
+        assert_eq!(forfeitures[0].batch_id, batch.batch_id);
+    }
+
+    #[tokio::test]
+    async fn test_forced_exits_make_provable_withdrawals() {
+        let (user, recipient) = (Address::from_low_u64_be(7), Address::from_low_u64_be(9));
+        let forced_tx = |log_index, event_type, value: u64| ForcedTransaction {
+            from: user,
+            to: if matches!(event_type, ForcedEventType::Deposit) { user } else { recipient },
+            value: U256::from(value),
+            l1_log_index: log_index,
+            event_type,
+            ..create_forced_tx(0)
+        };
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![
+                forced_tx(0, ForcedEventType::Deposit, 1_000),
+                forced_tx(1, ForcedEventType::ForcedExit, 400),
+                forced_tx(2, ForcedEventType::ForcedExit, 300),
+            ]),
+        });
+        let registry = create_registry().await;
+        let (submissions, mut pending) = mpsc::unbounded_channel();
+        let orchestrator = create_orchestrator_with_registry(
+            forced.clone(),
+            Arc::new(MockPool::default()),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        )
+        .with_submissions(submissions);
+
+        let first = orchestrator.produce_batch().await.unwrap().unwrap();
+        let withdrawals = registry.batch_withdrawals(first.batch_id).await.unwrap();
+        assert_eq!(withdrawals.iter().map(|w| (w.index, w.amount.as_u64())).collect::<Vec<_>>(), vec![(0, 400), (1, 300)]);
+        assert!(withdrawals.iter().all(|w| w.account == user && w.recipient == recipient));
+        let sealed = pending.recv().await.unwrap();
+        assert_eq!(sealed.withdrawal_root, withdrawal_root(&withdrawals));
+
+        // An odd number of withdrawals is proven too, but not a tampered one
+        forced.queued.lock().unwrap().extend((3..6).map(|log_index| forced_tx(log_index, ForcedEventType::ForcedExit, 100)));
+        let second = orchestrator.produce_batch().await.unwrap().unwrap();
+        let withdrawals = registry.batch_withdrawals(second.batch_id).await.unwrap();
+        assert_eq!(withdrawals.len(), 3);
+        let root = pending.recv().await.unwrap().withdrawal_root;
+        for index in 0..3 {
+            let proof = WithdrawalProof::new(&withdrawals, index).unwrap();
+            assert_eq!(proof.withdrawal_root, root);
+            assert!(proof.verify());
+        }
+        let mut tampered = WithdrawalProof::new(&withdrawals, 2).unwrap();
+        tampered.withdrawal.amount = U256::from(1_000);
+        assert!(!tampered.verify());
+        let found = registry.withdrawal(withdrawals[1].l1_tx_hash, Some(4)).await.unwrap();
+        assert_eq!(found, Some(withdrawals[1].clone()));
+
+        // An exit beyond the balance is not debited, so it pays nothing out
+        forced.queued.lock().unwrap().push(forced_tx(6, ForcedEventType::ForcedExit, 5_000));
+        let third = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert!(registry.batch_withdrawals(third.batch_id).await.unwrap().is_empty());
+        assert_eq!(pending.recv().await.unwrap().withdrawal_root, EMPTY_WITHDRAWAL_ROOT);
+
+        // Failed batches take their withdrawals with them
+        let (first_id, second_id) = (first.batch_id, second.batch_id);
+        for batch in [third, second, first] {
+            orchestrator.reinject(batch).await;
+        }
+        assert!(registry.batch_withdrawals(first_id).await.unwrap().is_empty());
+        assert!(registry.batch_withdrawals(second_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sealed_transfers_move_value_and_charge_fees() {
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx_from(1, 0, 10),
+            create_test_tx_from(1, 1, 10),
+            create_test_tx_from(2, 0, 10),
+        ]));
+        let state_cache = StateCache::new().with_snapshots(4);
+        let funded = Address::from_low_u64_be(1);
+        let unfunded = Address::from_low_u64_be(2);
+        let recipient = Address::zero();
+        state_cache.update(AccountState { address: funded, balance: U256::from(1_000_000), nonce: 2, ..Default::default() }).await;
+        state_cache.update(AccountState { address: unfunded, balance: U256::from(5_000), nonce: 1, ..Default::default() }).await;
+        let orchestrator = create_orchestrator_with_state(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+            create_registry().await,
+            state_cache.clone(),
+        );
+
+        // Each transfer costs its value (1000) plus 21000 gas at 10 wei
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(state_cache.get_balance(&funded).await, Some(U256::from(578_000)));
+        assert_eq!(state_cache.get_balance(&recipient).await, Some(U256::from(2_000)));
+        // The unfunded transfer reverts, costing the whole balance short of the fee
+        assert_eq!(state_cache.get_balance(&unfunded).await, Some(U256::zero()));
+        assert_eq!(state_cache.get_nonce(&funded).await, Some(2));
+
+        // Reinjecting the batch rolls its transfers back
+        orchestrator.reinject(batch).await;
+        assert_eq!(state_cache.get_balance(&funded).await, Some(U256::from(1_000_000)));
+        assert_eq!(state_cache.get_balance(&unfunded).await, Some(U256::from(5_000)));
+        assert_eq!(state_cache.get_balance(&recipient).await, None);
+        assert_eq!(pool.pending.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_sealed_batches_record_an_execution_witness() {
+        let pool = Arc::new(MockPool::with(vec![create_test_tx_from(1, 0, 10)]));
+        let registry = create_registry().await;
+        let state_cache = StateCache::new();
+        let sender = Address::from_low_u64_be(1);
+        let recipient = Address::zero();
+        state_cache.update(AccountState { address: sender, balance: U256::from(1_000_000), nonce: 1, ..Default::default() }).await;
+        let pre_root = state_cache.state_root();
+        let orchestrator = create_orchestrator_with_state(
+            Arc::new(MockForced::default()),
+            pool,
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+            state_cache.clone(),
+        );
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let witness = registry.batch_witness(batch.batch_id).await.unwrap().unwrap();
+        assert_eq!(witness.pre_state.state_root, pre_root);
+        assert_eq!(witness.post_state.state_root, state_cache.state_root());
+        assert!(witness.pre_state.verify() && witness.post_state.verify());
+
+        // The recipient is proven absent before, and holds the transfer after
+        let proven: Vec<_> = witness.post_state.accounts.iter().map(|account| account.address).collect();
+        assert_eq!(proven, vec![recipient, sender]);
+        assert!(witness.pre_state.accounts[0].account.is_none());
+        assert_eq!(witness.post_state.accounts[0].account.as_ref().unwrap().balance, U256::from(1_000));
+
+        // A forged balance no longer matches its proof
+        let mut forged = witness.post_state.clone();
+        forged.accounts[1].account.as_mut().unwrap().balance = U256::from(1_000_000);
+        assert!(!forged.verify());
+    }
+
+    #[tokio::test]
+    async fn test_normal_transactions_follow_policy() {
+        // Distinct senders, as each sender's transactions stay in nonce order
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx_from(1, 1, 10),
+            create_test_tx_from(2, 2, 30),
+            create_test_tx_from(3, 3, 20),
+        ]));
+        let orchestrator = create_orchestrator(
+            Arc::new(MockForced::default()),
+            pool,
+            SchedulingPolicyType::FeePriority,
+        ).await;
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let nonces: Vec<u64> = batch.transactions
+            .iter()
+            .map(|tx| match tx {
+                Transaction::Normal(tx) => tx.nonce,
+                Transaction::Forced(tx) => tx.nonce,
+                Transaction::UserOp(op) => op.nonce,
+            })
+            .collect();
+        assert_eq!(nonces, vec![2, 3, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_sender_guard_diversifies_batch() {
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx_from(1, 0, 50),
+            create_test_tx_from(1, 1, 50),
+            create_test_tx_from(1, 2, 50),
+            create_test_tx_from(2, 0, 10),
+            create_test_tx_from(3, 0, 10),
+        ]));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::FeePriority,
+            registry.clone(),
+        ).with_min_distinct_senders(Some(3));
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let senders: HashSet<Address> = batch.transactions
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Normal(tx) => Some(tx.from),
+                Transaction::Forced(_) | Transaction::UserOp(_) => None,
+            })
+            .collect();
+        assert_eq!(senders.len(), 3);
+
+        // The dominant sender's later nonces go back to the front of the pool, in order
+        let remaining: Vec<u64> = pool.pending.lock().unwrap().iter().map(|tx| tx.nonce).collect();
+        assert_eq!(remaining, vec![1, 2]);
+
+        let adjustments = registry.sender_guard_adjustments().await.unwrap();
+        assert_eq!(adjustments, vec![SenderGuardAdjustment {
+            batch_id: batch.batch_id,
+            senders_before: 1,
+            senders_after: 3,
+            displaced: 2,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn test_sender_guard_leaves_single_sender_pool_alone() {
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx_from(1, 0, 50),
+            create_test_tx_from(1, 1, 50),
+        ]));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool,
+            SchedulingPolicyType::FeePriority,
+            registry.clone(),
+        ).with_min_distinct_senders(Some(3));
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(batch.transactions.len(), 2);
+        assert!(registry.sender_guard_adjustments().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reinject_restores_failed_batch_in_order() {
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![create_forced_tx(0)]),
+        });
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx(1, 10),
+            create_test_tx(2, 10),
+            create_test_tx(3, 10),
+        ]));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            forced.clone(),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        );
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let batch_id = batch.batch_id;
+        orchestrator.reinject(batch).await;
+
+        // Reinjected transactions sit ahead of the one left behind
+        let pending: Vec<u64> = pool.pending.lock().unwrap().iter().map(|tx| tx.nonce).collect();
+        assert_eq!(pending, vec![1, 2, 3]);
+        assert_eq!(forced.queued.lock().unwrap().len(), 1);
+
+        let history = registry.transactions_by_sender_nonce(Address::zero(), 1).await.unwrap();
+        assert_eq!(history.last().unwrap().outcome, TransactionOutcome::Reinjected { batch_id });
+
+        // The next batch picks them up again, forced first
+        let retry = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert!(matches!(retry.transactions[0], Transaction::Forced(_)));
+        assert_eq!(retry.transactions.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_submission_reinjects_later_batches_too() {
+        let pool = Arc::new(MockPool::with((1..=4).map(|nonce| create_test_tx(nonce, 10)).collect()));
+        let registry = create_registry().await;
+        let (submissions, mut pending) = mpsc::unbounded_channel();
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        )
+        .with_submissions(submissions);
+
+        let first = orchestrator.produce_batch().await.unwrap().unwrap();
+        let second = orchestrator.produce_batch().await.unwrap().unwrap();
+        let status = registry.batch_status(first.batch_id).await.unwrap().unwrap();
+        assert_eq!(status.status, BatchStatus::Sealed);
+
+        // Each batch is handed over with the state root it leads to
+        let submitted = pending.recv().await.unwrap();
+        assert_eq!(submitted.batch.batch_id, first.batch_id);
+        assert_eq!(submitted.state_root, second.prev_state_root);
+
+        // The first batch fails on L1: the second, built on it, goes back too
+        let failed = orchestrator.reinject_failed(submitted.batch, &mut pending).await;
+        assert_eq!(failed, vec![first.batch_id, second.batch_id]);
+        assert!(pending.try_recv().is_err());
+        let nonces: Vec<u64> = pool.pending.lock().unwrap().iter().map(|tx| tx.nonce).collect();
+        assert_eq!(nonces, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_compressed_batch_data_decodes_and_its_sizes_are_recorded() {
+        let pool = Arc::new(MockPool::with((1..=3).map(|nonce| create_test_tx(nonce, 10)).collect()));
+        let registry = create_registry().await;
+        let (submissions, mut pending) = mpsc::unbounded_channel();
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool,
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        )
+        .with_compression(BatchCompression::Zstd, None)
+        .with_submissions(submissions);
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let sealed = pending.recv().await.unwrap();
+        assert_eq!(decode_batch(&sealed.data).unwrap().hash(), batch.hash());
+
+        let metadata = registry.batch(batch.batch_id).await.unwrap().unwrap();
+        assert_eq!(metadata.compression, "zstd");
+        assert_eq!(metadata.raw_size, serde_json::to_vec(&batch).unwrap().len());
+        assert_eq!(metadata.compressed_size, sealed.data.len());
+        assert!(metadata.compressed_size < metadata.raw_size);
+
+        // Every codec round-trips, and uncompressed data is the bare JSON
+        let json = serde_json::to_vec(&batch).unwrap();
+        for codec in [BatchCompression::None, BatchCompression::Zlib, BatchCompression::Brotli, BatchCompression::Zstd] {
+            let data = compress_batch(&json, codec, None).unwrap();
+            assert_eq!(decode_batch(&data).unwrap().hash(), batch.hash(), "{:?}", codec);
+        }
+        assert_eq!(compress_batch(&json, BatchCompression::None, None).unwrap(), json);
+    }
+
+    #[tokio::test]
+    async fn test_sealed_batches_snapshot_sender_nonces() {
+        let pool = Arc::new(MockPool::with(vec![
+            create_test_tx_from(1, 0, 10),
+            create_test_tx_from(1, 1, 10),
+            create_test_tx_from(2, 4, 10),
+        ]));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool,
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        );
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let nonce_at = |sender| {
+            let registry = registry.clone();
+            async move {
+                registry.account_at_batch(Address::from_low_u64_be(sender), batch.batch_id)
+                    .await.unwrap().map(|account| account.nonce)
+            }
+        };
+        assert_eq!(nonce_at(1).await, Some(2));
+        assert_eq!(nonce_at(2).await, Some(5));
+        assert_eq!(nonce_at(3).await, None);
+    }
+
+    /// Clock replaying scripted readings (`None` = unavailable)
+    struct ScriptedClock {
+        readings: Mutex<VecDeque<Option<u64>>>,
+    }
+
+    #[async_trait]
+    impl BatchClock for ScriptedClock {
+        async fn now(&self) -> anyhow::Result<u64> {
+            self.readings.lock().unwrap().pop_front().flatten()
+                .ok_or_else(|| anyhow::anyhow!("clock unavailable"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_timestamps_never_decrease() {
+        let pool = Arc::new(MockPool::default());
+        let clock = Arc::new(ScriptedClock {
+            readings: Mutex::new(VecDeque::from([Some(100), Some(90), None, Some(120)])),
+        });
+        let orchestrator = create_orchestrator(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+        ).await.with_clock(clock);
+
+        let mut timestamps = Vec::new();
+        for nonce in 1..=4 {
+            pool.pending.lock().unwrap().push_back(create_test_tx(nonce, 10));
+            timestamps.push(orchestrator.produce_batch().await.unwrap().unwrap().timestamp);
+        }
+        // A backwards jump and an outage both keep the previous timestamp
+        assert_eq!(timestamps, vec![100, 100, 100, 120]);
+    }
+
+    #[tokio::test]
+    async fn test_proposed_batch_sealed_in_proposer_order() {
+        let mut deposit = create_forced_tx(0);
+        deposit.l1_tx_hash = H256::from_low_u64_be(7);
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![deposit.clone()]),
+        });
+        let first = create_test_tx_from(1, 0, 10);
+        let second = create_test_tx_from(2, 0, 20);
+        let pool = Arc::new(MockPool::with(vec![first.clone(), second.clone()]));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            forced.clone(),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        );
+
+        // Skipping the queued forced transaction is refused, and nothing is consumed
+        let err = orchestrator.seal_proposal(&[second.hash(), first.hash()]).await.unwrap_err();
+        assert!(matches!(err, ProposalError::ForcedInclusion { expected } if expected == vec![deposit.l1_tx_hash]));
+        let err = orchestrator.seal_proposal(&[deposit.l1_tx_hash, H256::random()]).await.unwrap_err();
+        assert!(matches!(err, ProposalError::NotPending { .. }));
+        assert_eq!(forced.queued.lock().unwrap().len(), 1);
+        assert_eq!(pool.pending.lock().unwrap().len(), 2);
+
+        // A valid proposal keeps its order, even against the FCFS policy
+        let batch = orchestrator
+            .seal_proposal(&[deposit.l1_tx_hash, second.hash(), first.hash()])
+            .await
+            .unwrap();
+        let senders: Vec<Address> = batch.transactions
+            .iter()
+            .map(|tx| match tx {
+                Transaction::Normal(tx) => tx.from,
+                Transaction::Forced(tx) => tx.from,
+                Transaction::UserOp(op) => op.sender,
+            })
+            .collect();
+        assert_eq!(senders, vec![Address::zero(), second.from, first.from]);
+        assert!(pool.pending.lock().unwrap().is_empty());
+
+        let metadata = registry.batch(batch.batch_id).await.unwrap().unwrap();
+        assert_eq!(metadata.scheduling_policy, "ExternalProposer");
+    }
+
+    /// Posting costs of a fixed price per batch, whatever its size
+    struct FlatCost(Option<u64>);
+
+    impl PostingCosts for FlatCost {
+        fn batch_cost(&self, _tx_count: usize) -> Option<U256> {
+            self.0.map(U256::from)
+        }
+    }
+
+    #[test]
+    fn test_economic_trigger_holds_expensive_batches_up_to_the_delay() {
+        let max_delay = Duration::from_secs(60);
+        let trigger = EconomicTrigger::new(Arc::new(FlatCost(Some(1000))), U256::from(100), max_delay);
+
+        // 200 wei per transaction: wait for more to share the cost
+        assert!(trigger.should_wait(5, Duration::ZERO));
+        // 100 wei per transaction is cheap enough
+        assert!(!trigger.should_wait(10, Duration::ZERO));
+        // Never past the maximum delay, nor for an empty batch
+        assert!(!trigger.should_wait(5, max_delay));
+        assert!(!trigger.should_wait(0, Duration::ZERO));
+
+        // Nor while the cost is unknown
+        let unknown = EconomicTrigger::new(Arc::new(FlatCost(None)), U256::from(100), max_delay);
+        assert!(!unknown.should_wait(5, Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_forced_transactions_left_out_for_gas_are_sealed_before_their_deadline() {
+        let large_forced_tx = |log_index| ForcedTransaction {
+            gas_limit: 20_000_000,
+            l1_log_index: log_index,
+            ..create_forced_tx(0)
+        };
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![large_forced_tx(0), large_forced_tx(1)]),
+        });
+        let orchestrator = create_orchestrator(forced.clone(), Arc::new(MockPool::default()), SchedulingPolicyType::Fcfs).await;
+        let deadlines = ForcedDeadlines::new(ForcedInclusionConfig {
+            deadline_blocks: Some(10),
+            margin_blocks: 2,
+            deadline_batches: Some(2),
+        });
+
+        // Only one fits the gas limit; the other goes back to the queue
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(batch.transactions.len(), 1);
+        let queued = forced.queued.lock().unwrap().clone();
+        assert_eq!(queued.iter().map(|tx| tx.l1_log_index).collect::<Vec<_>>(), vec![1]);
+
+        // Left out of one batch, the next is its last: seal it right away
+        deadlines.record_batch(&[large_forced_tx(0)], &queued, Some(5));
+        assert!(deadlines.due(Some(1), Some(5)));
+        assert!(!deadlines.due(None, Some(5)));
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(batch.transactions.len(), 1);
+        deadlines.record_batch(&queued, &[], Some(5));
+        assert!(!deadlines.due(Some(1), Some(5)));
+
+        // A forced transaction from block 1 is due 2 blocks before block 11
+        assert!(!deadlines.due(Some(1), Some(8)));
+        assert!(deadlines.due(Some(1), Some(9)));
+        assert!(!deadlines.due(Some(1), None));
+    }
+
+    #[tokio::test]
+    async fn test_transactions_left_out_for_gas_are_sealed_in_the_next_batch() {
+        let large_tx = |nonce| UserTransaction { gas_limit: 20_000_000, ..create_test_tx_from(1, nonce, 10) };
+        let txs: Vec<_> = (0..3).map(large_tx).collect();
+        let state_cache = StateCache::new();
+        for tx in &txs {
+            state_cache.reservations().reserve(tx.from, tx.nonce, tx.hash());
+        }
+        let pool = Arc::new(MockPool::with(txs));
+        let orchestrator = create_orchestrator_with_state(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+            create_registry().await,
+            state_cache.clone(),
+        );
+
+        // Only one fits the gas limit; the others go back to the pool in order, still reserved
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(batch.transactions.len(), 1);
+        let pending: Vec<u64> = pool.pending.lock().unwrap().iter().map(|tx| tx.nonce).collect();
+        assert_eq!(pending, vec![1, 2]);
+        let reserved: Vec<u64> = state_cache.reservations().reserved(&Address::from_low_u64_be(1))
+            .into_iter().map(|(nonce, _)| nonce).collect();
+        assert_eq!(reserved, vec![1, 2]);
+
+        // Each following batch seals the next one, committing its nonce
+        for nonce in 1..3 {
+            let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+            assert!(matches!(&batch.transactions[..], [Transaction::Normal(tx)] if tx.nonce == nonce));
+        }
+        assert!(pool.pending.lock().unwrap().is_empty());
+        assert!(state_cache.reservations().reserved(&Address::from_low_u64_be(1)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_seals_forced_transactions_only() {
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![create_forced_tx(0)]),
+        });
+        let pool = Arc::new(MockPool::with(vec![create_test_tx(1, 10), create_test_tx(2, 10)]));
+        let safe_mode = Arc::new(SafeMode::new(Some(Duration::ZERO)));
+        let orchestrator = create_orchestrator(forced.clone(), pool.clone(), SchedulingPolicyType::Fcfs)
+            .await
+            .with_safe_mode(safe_mode.clone());
+
+        // A failing commitment, over the (zero) limit, turns safe mode on
+        safe_mode.submission_failed();
+        assert!(safe_mode.status().active);
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(batch.transactions.len(), 1);
+        assert!(matches!(batch.transactions[0], Transaction::Forced(_)));
+        assert!(pool.requests.lock().unwrap().is_empty());
+
+        // The escape hatch keeps it on after commitments recover
+        safe_mode.set_escape_hatch(true);
+        safe_mode.submission_succeeded();
+        assert!(orchestrator.produce_batch().await.unwrap().is_none());
+
+        safe_mode.set_escape_hatch(false);
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(batch.transactions.len(), 2);
+        assert_eq!(*pool.requests.lock().unwrap(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_policy_switch_applies_from_next_batch() {
+        let pool = Arc::new(MockPool::with(
+            (1..=6).map(|sender| create_test_tx_from(sender, 0, [10, 30, 20][(sender as usize - 1) % 3])).collect(),
+        ));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool,
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        );
+        let senders = |batch: &crate::Batch| -> Vec<u64> {
+            batch.transactions
+                .iter()
+                .map(|tx| match tx {
+                    Transaction::Normal(tx) => tx.from.to_low_u64_be(),
+                    _ => panic!("Expected normal transactions only"),
+                })
+                .collect()
+        };
+
+        let first = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(senders(&first), vec![1, 2, 3]);
+
+        orchestrator.set_policy(SchedulingPolicyType::FeePriority);
+        assert!(matches!(orchestrator.policy(), SchedulingPolicyType::FeePriority));
+        let second = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(senders(&second), vec![5, 6, 4]);
+
+        // Each batch records the policy it was scheduled by
+        let policy = |batch_id| {
+            let registry = registry.clone();
+            async move { registry.batch(batch_id).await.unwrap().unwrap().scheduling_policy }
+        };
+        assert_eq!(policy(first.batch_id).await, "FCFS");
+        assert_eq!(policy(second.batch_id).await, "FeePriority");
+    }
+
+    #[tokio::test]
+    async fn test_auction_winners_lead_the_batch_and_are_recorded() {
+        let txs: Vec<_> = (1..=3).map(|sender| create_test_tx_from(sender, 0, [10, 30, 20][sender as usize - 1])).collect();
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            Arc::new(MockPool::with(txs.clone())),
+            SchedulingPolicyType::Auction { top_slots: 1 },
+            registry.clone(),
+        );
+        // The cheapest transaction outbids the others for the top slot
+        orchestrator.auction_book().place(txs[0].hash(), txs[0].from, U256::from(5));
+
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let senders: Vec<u64> = batch.transactions
+            .iter()
+            .map(|tx| match tx {
+                Transaction::Normal(tx) => tx.from.to_low_u64_be(),
+                _ => panic!("Expected normal transactions only"),
+            })
+            .collect();
+        assert_eq!(senders, vec![1, 2, 3]);
+
+        let wins = registry.auction_wins(batch.batch_id).await.unwrap();
+        assert_eq!(wins, vec![AuctionWin {
+            batch_id: batch.batch_id,
+            position: 0,
+            tx_hash: txs[0].hash(),
+            bidder: txs[0].from,
+            amount: U256::from(5),
+        }]);
+        assert!(orchestrator.auction_book().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_decrypted_transactions_follow_the_forced_ones() {
+        let forced = Arc::new(MockForced {
+            queued: Mutex::new(vec![create_forced_tx(0)]),
+        });
+        let pool = Arc::new(MockPool::with(vec![create_test_tx(1, 10), create_test_tx(2, 10)]));
+        let state_cache = StateCache::new();
+        let validator = Validator::new(state_cache.clone()).with_config(&ValidationConfig {
+            disabled_rules: vec![BuiltinRule::Format, BuiltinRule::Signature, BuiltinRule::Balance],
+            ..ValidationConfig::default()
+        });
+        let lane = Arc::new(EncryptedPool::new(EncryptedMempoolConfig {
+            enabled: true,
+            decryption_seed: Some(H256::repeat_byte(0x42)),
+            epoch_ms: 20,
+            ..EncryptedMempoolConfig::default()
+        }).unwrap());
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_state(forced, pool.clone(), SchedulingPolicyType::Fcfs, registry.clone(), state_cache.clone())
+            .with_encrypted_mempool(lane.clone(), Arc::new(ValidationWorkers::new(Arc::new(validator), 1)), 2);
+        
+        // The second claims the nonce the first takes, so fails validation once decrypted
+        let epoch = lane.current_epoch() + 1;
+        let tx = create_test_tx_from(9, 0, 10);
+        let replay = UserTransaction { timestamp: 1, ..tx.clone() };
+        for tx in [&tx, &replay] {
+            lane.add(encrypt_transaction(tx, epoch, lane.master_key()).unwrap()).unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert!(matches!(batch.transactions[0], Transaction::Forced(_)));
+        assert!(matches!(&batch.transactions[1], Transaction::Normal(decrypted) if decrypted.hash() == tx.hash()));
+        assert!(matches!(&batch.transactions[2], Transaction::Normal(normal) if normal.nonce == 1));
+        assert_eq!(*pool.requests.lock().unwrap(), vec![1]);
+        assert!(lane.is_empty());
+        
+        assert_eq!(state_cache.get_nonce(&tx.from).await, Some(1));
+        let records = registry.transactions_by_sender_nonce(tx.from, 0).await.unwrap();
+        let replayed = records.iter().find(|record| record.tx_hash == replay.hash()).unwrap();
+        assert!(matches!(replayed.outcome, TransactionOutcome::Rejected { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_bundles_are_sealed_whole_and_hold_back_their_senders() {
+        let bundle = |sender: u64, nonces: &[u64]| Bundle {
+            transactions: nonces.iter().map(|&nonce| create_test_tx_from(sender, nonce, 10)).collect(),
+        };
+        let first = bundle(7, &[0, 1]);
+        let second = bundle(8, &[0, 1]);
+        let lane = Arc::new(BundlePool::new(4));
+        lane.add(first.clone()).await.unwrap();
+        lane.add(second.clone()).await.unwrap();
+        let later = create_test_tx_from(8, 2, 10);
+        let pool = Arc::new(MockPool::with(vec![later.clone(), create_test_tx_from(1, 0, 10)]));
+        let registry = create_registry().await;
+        let orchestrator = create_orchestrator_with_registry(
+            Arc::new(MockForced::default()),
+            pool.clone(),
+            SchedulingPolicyType::Fcfs,
+            registry.clone(),
+        ).with_bundles(lane.clone(), 4);
+        let hashes = |batch: &Batch| -> Vec<H256> {
+            batch.transactions
+                .iter()
+                .map(|tx| match tx {
+                    Transaction::Normal(tx) => tx.hash(),
+                    _ => panic!("Expected normal transactions only"),
+                })
+                .collect()
+        };
+        
+        // The second bundle does not fit whole, and its sender's later nonce waits for it
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert_eq!(hashes(&batch), first.transactions.iter().map(UserTransaction::hash).collect::<Vec<_>>());
+        assert_eq!(pool.pending.lock().unwrap().front().unwrap().hash(), later.hash());
+        let inclusion = registry.bundle_inclusion(first.hash()).await.unwrap().unwrap();
+        assert_eq!((inclusion.batch_id, inclusion.position, inclusion.size), (batch.batch_id, 0, 2));
+        
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let mut expected: Vec<H256> = second.transactions.iter().map(UserTransaction::hash).collect();
+        expected.push(later.hash());
+        assert_eq!(hashes(&batch), expected);
+        assert!(lane.is_empty().await);
+        
+        // A failed batch returns its bundle to the lane whole
+        let batch_id = batch.batch_id;
+        orchestrator.reinject(batch).await;
+        assert!(lane.contains(&second.hash()).await);
+        assert_eq!(lane.len().await, 1);
+        assert_eq!(pool.pending.lock().unwrap().front().unwrap().hash(), later.hash());
+        assert!(registry.bundle_inclusions(batch_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transactions_nearing_their_deadline_go_ahead_of_the_policy() {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let due = UserTransaction { deadline: Some(now + 1), ..create_test_tx_from(4, 0, 1) };
+        let later = UserTransaction { deadline: Some(now + 3_600), ..create_test_tx_from(5, 0, 2) };
+        let pending = || vec![
+            create_test_tx_from(1, 0, 30),
+            create_test_tx_from(2, 0, 20),
+            create_test_tx_from(3, 0, 10),
+            due.clone(),
+            later.clone(),
+        ];
+        let senders = |batch: &Batch| -> Vec<u64> {
+            batch.transactions
+                .iter()
+                .map(|tx| match tx {
+                    Transaction::Normal(tx) => tx.from.to_low_u64_be(),
+                    _ => panic!("Expected normal transactions only"),
+                })
+                .collect()
+        };
+        
+        // Without deadlines, the cheapest transactions wait
+        let pool = Arc::new(MockPool::with(pending()));
+        let orchestrator = create_orchestrator(Arc::new(MockForced::default()), pool, SchedulingPolicyType::FeePriority).await;
+        assert_eq!(senders(&orchestrator.produce_batch().await.unwrap().unwrap()), vec![1, 2, 3]);
+        
+        // The transaction due before the next batch takes a slot, still ordered by fee
+        let pool = Arc::new(MockPool::with(pending()));
+        let orchestrator = create_orchestrator(Arc::new(MockForced::default()), pool.clone(), SchedulingPolicyType::FeePriority)
+            .await
+            .with_deadlines(1);
+        assert_eq!(senders(&orchestrator.produce_batch().await.unwrap().unwrap()), vec![1, 2, 4]);
+        assert_eq!(*pool.requests.lock().unwrap(), vec![2]);
+        assert!(pool.pending.lock().unwrap().iter().any(|tx| tx.hash() == later.hash()));
+    }
+
+    #[tokio::test]
+    async fn test_sealed_batches_carry_execution_lanes_if_enabled() {
+        let transfer = |from: u64, to: u64| UserTransaction { to: Address::from_low_u64_be(to), ..create_test_tx_from(from, 0, 10) };
+        let pending = || vec![transfer(1, 2), transfer(3, 4), transfer(2, 5)];
+        
+        let pool = Arc::new(MockPool::with(pending()));
+        let orchestrator = create_orchestrator(Arc::new(MockForced::default()), pool, SchedulingPolicyType::Fcfs).await;
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        assert!(batch.lanes.is_none());
+        
+        let pool = Arc::new(MockPool::with(pending()));
+        let orchestrator = create_orchestrator(Arc::new(MockForced::default()), pool, SchedulingPolicyType::Fcfs)
+            .await
+            .with_execution_lanes(Some(4));
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let lanes: Vec<Vec<usize>> = batch.lanes.unwrap().into_iter().map(|lane| lane.transactions).collect();
+        assert_eq!(lanes, vec![vec![0, 2], vec![1]]);
+        
+        // Sending to a contract runs its code, which may touch anything
+        let state_cache = StateCache::new();
+        state_cache.set_code_hash(&Address::from_low_u64_be(5), H256::repeat_byte(0xc0)).await;
+        let pool = Arc::new(MockPool::with(pending()));
+        let forced = Arc::new(MockForced::default());
+        let orchestrator = create_orchestrator_with_state(forced, pool, SchedulingPolicyType::Fcfs, create_registry().await, state_cache)
+            .with_execution_lanes(Some(4));
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        let lanes: Vec<Vec<usize>> = batch.lanes.unwrap().into_iter().map(|lane| lane.transactions).collect();
+        assert_eq!(lanes, vec![vec![0, 1, 2]]);
+    }
+    
+    #[tokio::test]
+    async fn test_sealed_batches_are_measured() {
+        // FCFS keeps the pool's order, here newest first: every pair is inverted
+        let pending = vec![create_test_tx_from(1, 3, 10), create_test_tx_from(2, 2, 10), create_test_tx_from(3, 1, 10)];
+        let pool = Arc::new(MockPool::with(pending));
+        let orchestrator = create_orchestrator(Arc::new(MockForced::default()), pool, SchedulingPolicyType::Fcfs).await;
+        let batch = orchestrator.produce_batch().await.unwrap().unwrap();
+        
+        let recent = orchestrator.fairness().recent();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].batch_id, batch.batch_id);
+        assert_eq!(recent[0].policy, "FCFS");
+        assert_eq!(recent[0].transactions, 3);
+        assert_eq!(recent[0].inversions, 3);
+        assert_eq!(recent[0].max_reorder_distance, 2);
+        assert!(orchestrator.fairness().to_prometheus().contains("sequencer_scheduling_batches_total{policy=\"FCFS\"} 1\n"));
+    }
+
+    /// Transaction signed by the test key `key` in `config`'s signing domain
+    fn signed_tx(config: &Config, key: u64, nonce: u64) -> UserTransaction {
+        let wallet = LocalWallet::from_bytes(H256::from_low_u64_be(key).as_bytes()).unwrap();
+        let mut tx = UserTransaction {
+            from: wallet.address(),
+            timestamp: 1_700_000_000 + nonce,
+            chain_id: config.signing.chain_id,
+            ..create_test_tx(nonce, 10)
+        };
+        tx.signature = wallet.sign_hash(tx.signing_hash(&config.signing.domain())).unwrap();
+        tx
+    }
+    
+    #[tokio::test]
+    async fn test_calldata_is_signed_and_sealed_with_the_transaction() {
+        let config = Config::load("config/default.toml").unwrap();
+        let wallet = LocalWallet::from_bytes(H256::from_low_u64_be(1).as_bytes()).unwrap();
+        let data = Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb, 0x00, 0x00]);
+        let mut tx = UserTransaction { data: data.clone(), gas_limit: 21_072, ..signed_tx(&config, 1, 0) };
+        tx.signature = wallet.sign_hash(tx.signing_hash(&config.signing.domain())).unwrap();
+        let without_data = UserTransaction { data: Bytes::new(), ..tx.clone() };
+        assert_ne!(tx.hash(), without_data.hash());
+        assert_ne!(tx.signing_hash(&config.signing.domain()), without_data.signing_hash(&config.signing.domain()));
+        
+        // Tampered calldata fails the signature check; the signed one is sealed as sent
+        let mut tampered = tx.clone();
+        tampered.data = Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbc, 0x00, 0x00]);
+        let accounts = vec![AccountState { address: tx.from, balance: U256::exp10(18), ..Default::default() }];
+        let artifact = offline::build_batches(&config, accounts, vec![(1, tampered), (2, tx.clone())]).await.unwrap();
+        assert_eq!(artifact.rejected.len(), 1);
+        assert!(artifact.rejected[0].reason.contains("signature"));
+        let batch = &artifact.batches[0];
+        match &batch.transactions[..] {
+            [Transaction::Normal(sealed)] => {
+                assert_eq!(sealed.data, data);
+                assert_eq!(sealed.hash(), tx.hash());
+            }
+            other => panic!("expected the one transaction, got {:?}", other),
+        }
+        
+        // The posted batch data carries it too
+        let json = serde_json::to_vec(batch).unwrap();
+        let decoded = decode_batch(&compress_batch(&json, BatchCompression::Zstd, None).unwrap()).unwrap();
+        assert!(matches!(&decoded.transactions[..], [Transaction::Normal(sealed)] if sealed.data == data));
+    }
+    
+    #[test]
+    fn test_offline_input_is_read_line_by_line() {
+        let config = Config::load("config/default.toml").unwrap();
+        let line = |tx: &UserTransaction| serde_json::to_string(tx).unwrap();
+        let (first, second) = (signed_tx(&config, 1, 0), signed_tx(&config, 1, 1));
+        
+        // Blank lines are skipped, but still counted
+        let input = format!("{}\n\n{}\n", line(&first), line(&second));
+        let read = offline::read_transactions(input.as_bytes()).unwrap();
+        let read: Vec<(usize, H256)> = read.iter().map(|(line, tx)| (*line, tx.hash())).collect();
+        assert_eq!(read, vec![(1, first.hash()), (3, second.hash())]);
+        
+        // A malformed line fails the whole input, naming the line
+        let input = format!("{}\n{{\"from\": 1}}\n", line(&first));
+        let error = offline::read_transactions(input.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+    
+    #[tokio::test]
+    async fn test_offline_build_rejects_invalid_transactions_and_seals_the_rest() {
+        let mut config = Config::load("config/default.toml").unwrap();
+        config.batch.max_batch_size = 2;
+        let funded = signed_tx(&config, 1, 0).from;
+        let accounts = vec![AccountState {
+            address: funded,
+            balance: U256::exp10(18),
+            nonce: 0,
+            code_hash: H256::zero(),
+            storage_root: H256::zero(),
+        }];
+        let mut forged = signed_tx(&config, 1, 3);
+        forged.value += U256::one();
+        let transactions = vec![
+            (1, signed_tx(&config, 1, 0)),
+            (2, signed_tx(&config, 2, 0)),
+            (3, signed_tx(&config, 1, 1)),
+            (4, forged),
+            (5, signed_tx(&config, 1, 2)),
+        ];
+        
+        let artifact = offline::build_batches(&config, accounts, transactions).await.unwrap();
+        assert_eq!(artifact.scheduling_policy, "FCFS");
+        // Unfunded sender, and a transaction changed after signing
+        let rejected: Vec<usize> = artifact.rejected.iter().map(|rejected| rejected.line).collect();
+        assert_eq!(rejected, vec![2, 4]);
+        assert!(artifact.rejected[0].reason.contains("balance"));
+        assert!(artifact.rejected[1].reason.contains("signature"));
+        // Three admitted transactions, two per batch, in nonce order
+        let batches: Vec<Vec<u64>> = artifact.batches
+            .iter()
+            .map(|batch| batch.transactions.iter().map(|tx| match tx {
+                Transaction::Normal(tx) => tx.nonce,
+                _ => panic!("only normal transactions were submitted"),
+            }).collect())
+            .collect();
+        assert_eq!(batches, vec![vec![0, 1], vec![2]]);
+        assert_eq!(artifact.fairness.len(), 2);
+    }
+}
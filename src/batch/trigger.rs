@@ -1,12 +1,122 @@
 //! Batch Trigger Module
-//! 
-//! This module is a placeholder for batch trigger logic.
-//! 
-//! # Planned Functionality
-//! The batch trigger will determine when batches should be sealed based on:
-//! - Time-based triggers (seal batch after timeout, even if not full)
-//! - Size-based triggers (seal batch when it reaches max size)
-//! - Event-based triggers (seal immediately if forced transactions arrive)
-//! - Economic triggers (seal when gas savings threshold is met)
-
-// Placeholder for batch trigger logic
\ No newline at end of file
+//!
+//! The orchestrator seals a batch after the timeout, when a full batch is
+//! waiting, or right away for an express-lane exit. The economic trigger can
+//! hold a partial batch past the timeout while posting it to L1 would cost too
+//! much per transaction, so more transactions (or cheaper L1 gas) can share
+//! the cost. It never holds a batch for longer than its maximum delay.
+//!
+//! Forced-inclusion deadlines seal a batch right away when a queued forced
+//! transaction nears its deadline: a number of L1 blocks after its event, or
+//! a number of batches it may be left out of for lack of gas.
+
+use crate::config::ForcedInclusionConfig;
+use crate::ForcedTransaction;
+use ethers::types::{H256, U256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+/// Source of L1 posting cost estimates
+pub trait PostingCosts: Send + Sync {
+    /// Estimated L1 cost, in wei, of posting a batch of `tx_count` transactions
+    ///
+    /// `None` while the cost is unknown, e.g. before L1 gas prices were read.
+    fn batch_cost(&self, tx_count: usize) -> Option<U256>;
+}
+
+/// Holds partial batches while their L1 cost per transaction is too high
+pub struct EconomicTrigger {
+    /// Where posting costs are estimated
+    costs: Arc<dyn PostingCosts>,
+    /// Highest L1 cost per transaction worth sealing a partial batch for
+    max_cost_per_tx: U256,
+    /// Longest a partial batch is held past the timeout
+    max_delay: Duration,
+}
+
+impl EconomicTrigger {
+    /// Creates an economic trigger
+    ///
+    /// # Arguments
+    /// * `costs` - Source of posting cost estimates
+    /// * `max_cost_per_tx` - Highest L1 cost per transaction, in wei
+    /// * `max_delay` - Longest a batch is held past the timeout
+    pub fn new(costs: Arc<dyn PostingCosts>, max_cost_per_tx: U256, max_delay: Duration) -> Self {
+        Self { costs, max_cost_per_tx, max_delay }
+    }
+
+    /// Whether a partial batch of `pending` transactions, `overdue` past the
+    /// timeout, should keep waiting
+    ///
+    /// Batches are never held once the maximum delay is reached, nor while
+    /// the posting cost is unknown.
+    pub fn should_wait(&self, pending: usize, overdue: Duration) -> bool {
+        if pending == 0 || overdue >= self.max_delay {
+            return false;
+        }
+        self.costs
+            .batch_cost(pending)
+            .is_some_and(|cost| cost / U256::from(pending) > self.max_cost_per_tx)
+    }
+}
+
+/// Tracks forced transactions against their inclusion deadlines
+pub struct ForcedDeadlines {
+    /// Deadlines and margin
+    config: ForcedInclusionConfig,
+    /// Batches each forced transaction was left out of, by L1 event
+    left_out: Mutex<HashMap<(H256, u64), u64>>,
+}
+
+impl ForcedDeadlines {
+    /// Creates a tracker for the deadlines in `config`
+    pub fn new(config: ForcedInclusionConfig) -> Self {
+        Self { config, left_out: Mutex::default() }
+    }
+
+    /// Whether a batch must be sealed now for queued forced transactions to make their deadlines
+    ///
+    /// # Arguments
+    /// * `oldest_block` - L1 block of the oldest queued forced transaction (`None` if none is queued)
+    /// * `head` - Latest L1 head
+    pub fn due(&self, oldest_block: Option<u64>, head: Option<u64>) -> bool {
+        if oldest_block.is_none() {
+            return false;
+        }
+        let block_due = match (self.config.deadline_blocks, oldest_block, head) {
+            (Some(deadline), Some(oldest), Some(head)) => head + self.config.margin_blocks >= oldest + deadline,
+            _ => false,
+        };
+        let batch_due = self.config.deadline_batches.is_some_and(|deadline| {
+            self.left_out.lock().unwrap().values().any(|&batches| batches + 1 >= deadline)
+        });
+        block_due || batch_due
+    }
+
+    /// Record a sealed batch that `included` made it into and `left_out` did not
+    ///
+    /// Warns about forced transactions past a deadline.
+    pub fn record_batch(&self, included: &[ForcedTransaction], left_out: &[ForcedTransaction], head: Option<u64>) {
+        let mut counts = self.left_out.lock().unwrap();
+        for tx in included {
+            counts.remove(&tx.l1_event_key());
+            if let (Some(deadline), Some(head)) = (self.config.deadline_blocks, head)
+                && head > tx.l1_block_number + deadline
+            {
+                warn!(
+                    "Forced transaction {:?} from L1 block {} batched at block {}, past its deadline",
+                    tx.l1_tx_hash, tx.l1_block_number, head
+                );
+            }
+        }
+        for tx in left_out {
+            let batches = counts.entry(tx.l1_event_key()).or_default();
+            *batches += 1;
+            if self.config.deadline_batches.is_some_and(|deadline| *batches >= deadline) {
+                warn!("Forced transaction {:?} left out of {} batches, past its deadline", tx.l1_tx_hash, batches);
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Batch Withdrawals Module
+//!
+//! Every forced exit debited in a batch pays out on L1. The batch lists these
+//! payouts as withdrawals, in batch order, and commits to them in a Merkle
+//! root posted with the batch, against which each recipient claims theirs.
+//!
+//! The root is a binary Merkle tree over the withdrawals' leaf hashes
+//! ([`Withdrawal::leaf_hash`]), in order:
+//! - Branch: `keccak256(left || right)`
+//! - A level with an odd number of nodes pairs its last node with zero
+//! - A single withdrawal is its own root, and no withdrawal gives zero
+//!
+//! A [`WithdrawalProof`] holds the sibling hashes from the leaf up, so the
+//! bits of the withdrawal's index, least significant first, tell on which
+//! side each sibling goes.
+
+use crate::{Batch, ForcedEventType, ForcedTransaction, Transaction, Withdrawal};
+use ethers::types::{H256, U256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Withdrawal root of a batch without withdrawals
+pub const EMPTY_WITHDRAWAL_ROOT: H256 = H256::zero();
+
+/// Merkle proof of a withdrawal against its batch's withdrawal root
+///
+/// # Fields
+/// - `withdrawal`: The withdrawal
+/// - `withdrawal_root`: Root of its batch's withdrawals
+/// - `siblings`: Hashes beside the withdrawal's path, from the leaf up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalProof {
+    pub withdrawal: Withdrawal,
+    pub withdrawal_root: H256,
+    pub siblings: Vec<H256>,
+}
+
+impl WithdrawalProof {
+    /// Prove the withdrawal at `index` among `withdrawals`, a batch's withdrawals in order
+    ///
+    /// `None` if there is no withdrawal at `index`.
+    pub fn new(withdrawals: &[Withdrawal], index: usize) -> Option<Self> {
+        let withdrawal = withdrawals.get(index)?.clone();
+        let mut level: Vec<H256> = withdrawals.iter().map(Withdrawal::leaf_hash).collect();
+        let mut position = index;
+        let mut siblings = Vec::new();
+        while level.len() > 1 {
+            siblings.push(level.get(position ^ 1).copied().unwrap_or_else(H256::zero));
+            level = parent_level(&level);
+            position /= 2;
+        }
+        Some(Self { withdrawal, withdrawal_root: level[0], siblings })
+    }
+
+    /// Whether the siblings lead from the withdrawal to `withdrawal_root`
+    pub fn verify(&self) -> bool {
+        let mut hash = self.withdrawal.leaf_hash();
+        let mut position = self.withdrawal.index;
+        for sibling in &self.siblings {
+            hash = if position.is_multiple_of(2) {
+                branch_hash(&hash, sibling)
+            } else {
+                branch_hash(sibling, &hash)
+            };
+            position /= 2;
+        }
+        position == 0 && hash == self.withdrawal_root
+    }
+}
+
+/// Withdrawals of a sealed batch, in batch order
+///
+/// One per forced exit, except those in `unpaid`, whose sender could not
+/// cover them and was not debited.
+pub fn batch_withdrawals(batch: &Batch, unpaid: &HashMap<(H256, u64), U256>) -> Vec<Withdrawal> {
+    batch.transactions
+        .iter()
+        .filter_map(|tx| match tx {
+            Transaction::Forced(tx @ ForcedTransaction { event_type: ForcedEventType::ForcedExit, .. }) => Some(tx),
+            _ => None,
+        })
+        .filter(|tx| !unpaid.contains_key(&tx.l1_event_key()))
+        .enumerate()
+        .map(|(index, tx)| Withdrawal {
+            batch_id: batch.batch_id,
+            index: index as u64,
+            l1_tx_hash: tx.l1_tx_hash,
+            l1_log_index: tx.l1_log_index,
+            account: tx.from,
+            recipient: tx.to,
+            amount: tx.value,
+        })
+        .collect()
+}
+
+/// Merkle root of a batch's withdrawals, in order
+pub fn withdrawal_root(withdrawals: &[Withdrawal]) -> H256 {
+    let mut level: Vec<H256> = withdrawals.iter().map(Withdrawal::leaf_hash).collect();
+    while level.len() > 1 {
+        level = parent_level(&level);
+    }
+    level.first().copied().unwrap_or(EMPTY_WITHDRAWAL_ROOT)
+}
+
+/// Hashes of the level above `level`, pairing a last odd node with zero
+fn parent_level(level: &[H256]) -> Vec<H256> {
+    level
+        .chunks(2)
+        .map(|pair| branch_hash(&pair[0], pair.get(1).unwrap_or(&H256::zero())))
+        .collect()
+}
+
+/// Hash of a branch over two subtrees
+fn branch_hash(left: &H256, right: &H256) -> H256 {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(left.as_bytes());
+    preimage[32..].copy_from_slice(right.as_bytes());
+    H256::from(keccak256(preimage))
+}
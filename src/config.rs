@@ -3,6 +3,8 @@
 //! This module defines all configuration structures for the sequencer.
 //! Configuration is loaded from TOML files and parsed using serde.
 
+use crate::scheduler::{PolicyKind, SchedulingPolicyType};
+use ethers::types::{transaction::eip712::EIP712Domain, Address, Bytes, H256};
 use serde::Deserialize;
 use std::fs;
 
@@ -24,6 +26,9 @@ use std::fs;
 /// [api]
 /// host = "127.0.0.1"
 /// port = 8545
+/// 
+/// [pool]
+/// max_size = 10000
 /// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -32,6 +37,26 @@ pub struct Config {
     pub api: ApiConfig,
     pub l1: L1Config,
     pub database: DatabaseConfig,
+    #[serde(default)]
+    pub pool: PoolConfig,
+    #[serde(default)]
+    pub state: StateConfig,
+    #[serde(default)]
+    pub proposer: ProposerConfig,
+    #[serde(default)]
+    pub signing: SigningConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub account_abstraction: AccountAbstractionConfig,
+    #[serde(default)]
+    pub attestation: AttestationConfig,
+    #[serde(default)]
+    pub encrypted_mempool: EncryptedMempoolConfig,
+    #[serde(default)]
+    pub bundles: BundleConfig,
+    #[serde(default)]
+    pub deadlines: DeadlineConfig,
 }
 
 /// Batch creation configuration
@@ -43,12 +68,43 @@ pub struct Config {
 /// - `timeout_interval_ms`: How long to wait before sealing a partial batch (in milliseconds)
 /// - `min_batch_size`: Minimum transactions before considering a timeout seal
 /// - `max_gas_limit`: Maximum cumulative gas consumption per batch (prevents expensive L1 verification)
+/// - `timestamp_source`: Where batch timestamps come from (`"WallClock"` or `"L1Head"`).
+///   Whatever the source, batch timestamps never decrease.
+/// - `base_fee_wei`: Current L2 base fee per gas. EIP-1559 transactions must
+///   allow at least this much, and fee ordering ranks by the tip above it.
+/// - `max_l1_cost_per_tx_wei`: Economic trigger. Once the timeout expires, a
+///   partial batch is held while its estimated L1 posting cost per transaction
+///   is above this (needs `[l1.gas_oracle]`; disabled if unset)
+/// - `max_economic_delay_ms`: Longest the economic trigger holds a batch past
+///   the timeout (default 60000)
 #[derive(Debug, Clone, Deserialize)]
 pub struct BatchConfig {
     pub max_batch_size: usize,
     pub timeout_interval_ms: u64,
     pub min_batch_size: usize,
     pub max_gas_limit: u64,
+    #[serde(default)]
+    pub timestamp_source: TimestampSource,
+    #[serde(default)]
+    pub base_fee_wei: u64,
+    #[serde(default)]
+    pub max_l1_cost_per_tx_wei: Option<u64>,
+    #[serde(default = "default_max_economic_delay_ms")]
+    pub max_economic_delay_ms: u64,
+}
+
+fn default_max_economic_delay_ms() -> u64 {
+    60_000
+}
+
+/// Source of batch timestamps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum TimestampSource {
+    /// The local system clock
+    #[default]
+    WallClock,
+    /// The timestamp of the latest L1 block
+    L1Head,
 }
 
 /// Transaction scheduling configuration
@@ -57,9 +113,11 @@ pub struct BatchConfig {
 /// 
 /// # Supported Policies
 /// - `"FCFS"`: First-Come-First-Served (transactions ordered by arrival time)
-/// - `"FeePriority"`: Fee-based priority (highest gas price first)
+/// - `"FeePriority"`: Fee-based priority (highest effective tip first)
 /// - `"TimeBoost"`: Time-windowed ordering with premium bids
 /// - `"FairBFT"`: Fair Byzantine Fault Tolerant ordering (timestamp-based)
+/// - `"Auction"`: Sealed-bid auction for the top slots, then fee priority
+/// - `"WeightedFair"`: Round-robin across senders, optionally weighted by fee
 /// 
 /// # TimeBoost Configuration
 /// For TimeBoost policy, you can specify the time window:
@@ -68,36 +126,283 @@ pub struct BatchConfig {
 /// policy_type = "TimeBoost"
 /// time_window_ms = 5000  # 5-second time windows
 /// ```
+/// 
+/// # Auction Configuration
+/// For the Auction policy, you can specify how many positions at the top of
+/// each batch are auctioned:
+/// ```toml
+/// [scheduling]
+/// policy_type = "Auction"
+/// auction_slots = 3
+/// ```
+/// 
+/// # WeightedFair Configuration
+/// For the WeightedFair policy, senders can be weighted by the tip of their
+/// first pending transaction instead of served equally:
+/// ```toml
+/// [scheduling]
+/// policy_type = "WeightedFair"
+/// fee_weighted = true
+/// ```
+/// 
+/// # Custom Policies
+/// A policy registered with `scheduler::register_policy` before the
+/// configuration is loaded is selected by the name it was registered under:
+/// ```toml
+/// [scheduling]
+/// policy_type = "MyPolicy"
+/// ```
+/// 
+/// # Execution Lanes
+/// Whatever the policy, each batch can carry lanes grouping its transactions
+/// by the accounts they touch, so a parallel executor can run them concurrently:
+/// ```toml
+/// [scheduling]
+/// execution_lanes = 8
+/// ```
+/// 
+/// # Sender Guard
+/// For the fee-ordered policies (FeePriority and Auction), batches can be required to contain a minimum number
+/// of distinct senders (when the pool has them available):
+/// ```toml
+/// [scheduling]
+/// policy_type = "FeePriority"
+/// min_distinct_senders = 4
+/// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct SchedulingConfig {
-    /// Policy type: "FCFS", "FeePriority", "TimeBoost", or "FairBFT"
-    policy_type: String,
+    /// Policy type: "FCFS", "FeePriority", "TimeBoost", "FairBFT", "Auction", "WeightedFair",
+    /// or the name of a registered custom policy
+    policy_type: PolicyKind,
     /// Time window in milliseconds (only used for TimeBoost policy)
     #[serde(default = "default_time_window")]
     time_window_ms: u64,
+    /// Auctioned positions at the top of each batch (only used for Auction policy)
+    #[serde(default = "default_auction_slots")]
+    auction_slots: usize,
+    /// Weight senders by fee (only used for WeightedFair policy)
+    #[serde(default)]
+    fee_weighted: bool,
+    /// Minimum distinct senders per batch (only used for fee-ordered policies)
+    #[serde(default)]
+    min_distinct_senders: Option<usize>,
+    /// Most execution lanes per batch (batches carry no lanes if unset)
+    #[serde(default)]
+    execution_lanes: Option<usize>,
 }
 
 fn default_time_window() -> u64 {
     5000 // Default to 5-second windows
 }
 
+fn default_auction_slots() -> usize {
+    3
+}
+
 impl SchedulingConfig {
     /// Parse the configuration into a SchedulingPolicyType enum
-    pub fn to_policy_type(&self) -> crate::scheduler::SchedulingPolicyType {
-        use crate::scheduler::SchedulingPolicyType;
-        
-        match self.policy_type.as_str() {
-            "FCFS" => SchedulingPolicyType::Fcfs,
-            "FeePriority" => SchedulingPolicyType::FeePriority,
-            "TimeBoost" => SchedulingPolicyType::TimeBoost {
-                time_window_ms: self.time_window_ms,
-            },
-            "FairBFT" => SchedulingPolicyType::FairBft,
-            _ => panic!("Invalid scheduling policy type: {}. Must be one of: FCFS, FeePriority, TimeBoost, FairBFT", self.policy_type),
+    pub fn to_policy_type(&self) -> SchedulingPolicyType {
+        self.policy_type.to_policy_type(self.time_window_ms, self.auction_slots, self.fee_weighted)
+    }
+    
+    /// Minimum distinct senders a batch must contain under fee ordering
+    /// 
+    /// The guard only makes sense under fee ordering, where a single
+    /// high-paying actor could otherwise fill whole batches during a burst.
+    /// It is configured whatever the starting policy, and applies whenever
+    /// the policy (possibly switched at runtime) is `FeePriority` or `Auction`.
+    pub fn min_distinct_senders(&self) -> Option<usize> {
+        self.min_distinct_senders
+    }
+    
+    /// Most lanes each batch's transactions are grouped into for parallel execution
+    /// 
+    /// Lanes touch no common account, so they can execute concurrently.
+    /// `None` seals batches without lanes.
+    pub fn execution_lanes(&self) -> Option<usize> {
+        self.execution_lanes
+    }
+}
+
+/// Transaction pool configuration
+/// 
+/// Bounds the memory used by pending user transactions.
+/// 
+/// # Fields
+/// - `max_size`: Maximum number of pending transactions held in the pool.
+///   When full, the lowest-fee (then oldest) transaction is evicted to make room
+///   for a better-paying one; otherwise the new transaction is rejected.
+/// - `tx_ttl_secs`: How long a transaction may wait in the pool before it expires
+/// - `wal_path`: Write-ahead log file persisting pending transactions across
+///   restarts (in-memory only if unset)
+/// - `remote`: When set, this node runs as an RPC ingress node that forwards
+///   transactions to a central sequencer's pool instead of batching them itself
+/// - `denylist`: Senders refused at pool admission (updatable at runtime via
+///   `admin_denySender` / `admin_allowSender`)
+/// - `fee_floor`: Minimum gas price for admission (advertised via `estimateFee`)
+/// - `locals`: Senders exempt from the fee floor, capacity limit and eviction,
+///   whose transactions are batched ahead of all others
+/// - `forced_queue_capacity`: Forced queue length at which the L1 listener
+///   pauses ingesting events until batches drain the queue
+/// - `shards`: Number of independently locked pool shards (senders are
+///   spread across them), so concurrent submissions don't contend on one lock
+/// - `nonce_lookahead`: How many nonces past its earliest pending transaction
+///   a sender may queue
+/// - `allowlist`: Permissioned mode: when set, only these senders (plus any
+///   approved at runtime or by the L1 allowlist registry) are admitted
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolConfig {
+    #[serde(default = "default_pool_max_size")]
+    pub max_size: usize,
+    #[serde(default = "default_tx_ttl")]
+    pub tx_ttl_secs: u64,
+    #[serde(default)]
+    pub wal_path: Option<String>,
+    #[serde(default)]
+    pub remote: Option<RemotePoolConfig>,
+    #[serde(default)]
+    pub denylist: Vec<Address>,
+    #[serde(default)]
+    pub fee_floor: FeeFloorConfig,
+    #[serde(default)]
+    pub locals: Vec<Address>,
+    #[serde(default = "default_forced_queue_capacity")]
+    pub forced_queue_capacity: usize,
+    #[serde(default = "default_pool_shards")]
+    pub shards: usize,
+    #[serde(default = "default_nonce_lookahead")]
+    pub nonce_lookahead: u64,
+    #[serde(default)]
+    pub allowlist: Option<Vec<Address>>,
+}
+
+fn default_pool_max_size() -> usize {
+    10_000
+}
+
+fn default_tx_ttl() -> u64 {
+    600 // 10 minutes
+}
+
+fn default_forced_queue_capacity() -> usize {
+    10_000
+}
+
+fn default_pool_shards() -> usize {
+    16
+}
+
+fn default_nonce_lookahead() -> u64 {
+    64
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: default_pool_max_size(),
+            tx_ttl_secs: default_tx_ttl(),
+            wal_path: None,
+            remote: None,
+            denylist: Vec::new(),
+            fee_floor: FeeFloorConfig::default(),
+            locals: Vec::new(),
+            forced_queue_capacity: default_forced_queue_capacity(),
+            shards: default_pool_shards(),
+            nonce_lookahead: default_nonce_lookahead(),
+            allowlist: None,
         }
     }
 }
 
+/// Minimum gas price enforced at pool admission
+/// 
+/// # Fields
+/// - `min_gas_price`: Absolute floor in wei (0 disables it)
+/// - `dynamic`: Optional floor that rises with pool occupancy
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FeeFloorConfig {
+    #[serde(default)]
+    pub min_gas_price: u64,
+    #[serde(default)]
+    pub dynamic: Option<DynamicFeeFloorConfig>,
+}
+
+/// Fee floor that tracks pool congestion
+/// 
+/// Once the pool holds at least `start_fill_ratio * max_size` transactions,
+/// new transactions must pay at least the `percentile` gas price of the
+/// pending ones (and never less than the absolute floor).
+/// 
+/// The floor also follows recent batches. A batch that leaves transactions
+/// behind is congested, and its cutoff is the lowest fee cap it included.
+/// New transactions must pay at least the lowest cutoff of the last
+/// `inclusion_window` batches, where a batch that took everything counts as 0.
+/// 
+/// # Fields
+/// - `start_fill_ratio`: Pool occupancy (0.0-1.0) at which the floor kicks in
+/// - `percentile`: Pending gas price percentile (0.0-1.0) used as the floor
+/// - `inclusion_window`: Recent batches whose inclusion cutoffs raise the floor (0 disables it)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DynamicFeeFloorConfig {
+    #[serde(default = "default_start_fill_ratio")]
+    pub start_fill_ratio: f64,
+    #[serde(default = "default_floor_percentile")]
+    pub percentile: f64,
+    #[serde(default = "default_inclusion_window")]
+    pub inclusion_window: usize,
+}
+
+fn default_start_fill_ratio() -> f64 {
+    0.5
+}
+
+fn default_floor_percentile() -> f64 {
+    0.25
+}
+
+fn default_inclusion_window() -> usize {
+    10
+}
+
+/// Remote pool configuration for RPC ingress nodes
+/// 
+/// Ingress nodes check signatures locally and forward transactions over gRPC
+/// to the central sequencer, which does full validation and ordering.
+/// 
+/// # Fields
+/// - `upstream_url`: API endpoint of the central sequencer, which serves gRPC
+///   alongside JSON-RPC
+/// - `api_key`: API key sent upstream (if the sequencer requires one)
+/// - `max_in_flight`: Forwards allowed at once; beyond this, submissions are
+///   refused with `POOL_FULL` so clients back off
+/// - `timeout_ms`: Per-request timeout for the upstream call
+/// - `dedup_capacity`: How many recently forwarded hashes are remembered to
+///   reject duplicates without a round trip
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemotePoolConfig {
+    pub upstream_url: String,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default = "default_max_in_flight")]
+    pub max_in_flight: usize,
+    #[serde(default = "default_remote_timeout")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_dedup_capacity")]
+    pub dedup_capacity: usize,
+}
+
+fn default_max_in_flight() -> usize {
+    256
+}
+
+fn default_remote_timeout() -> u64 {
+    5_000
+}
+
+fn default_dedup_capacity() -> usize {
+    10_000
+}
+
 /// API server configuration
 /// 
 /// Controls the JSON-RPC API endpoint settings.
@@ -105,10 +410,88 @@ impl SchedulingConfig {
 /// # Fields
 /// - `host`: IP address to bind to (e.g., "127.0.0.1" or "0.0.0.0")
 /// - `port`: TCP port to listen on (e.g., 8545)
+/// - `admin_token`: Bearer token required for `admin_*` methods (admin API disabled if unset)
+/// - `require_api_key`: Require an `X-Api-Key` header on every request (metered mode)
+/// - `quota_window_secs`: Length of the window after which API key usage counters reset
+/// - `sender_bans`: Temporary bans for senders with repeated validation failures (disabled if unset)
+/// - `validation_workers`: Transactions whose signatures are checked in parallel
+/// - `max_bundle_size`: Most transactions a `validateBundle` or `sendBundle` call may hold
+/// - `max_accounts_per_read`: Most accounts a `getAccounts` call may read
 #[derive(Debug, Clone, Deserialize)]
 pub struct ApiConfig {
     pub host: String,
     pub port: u16,
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    #[serde(default)]
+    pub require_api_key: bool,
+    #[serde(default = "default_quota_window")]
+    pub quota_window_secs: u64,
+    #[serde(default)]
+    pub sender_bans: Option<SenderBanConfig>,
+    #[serde(default = "default_validation_workers")]
+    pub validation_workers: usize,
+    #[serde(default = "default_max_bundle_size")]
+    pub max_bundle_size: usize,
+    #[serde(default = "default_max_accounts_per_read")]
+    pub max_accounts_per_read: usize,
+}
+
+fn default_quota_window() -> u64 {
+    86_400 // Daily quotas
+}
+
+fn default_max_bundle_size() -> usize {
+    32
+}
+
+fn default_max_accounts_per_read() -> usize {
+    1_000
+}
+
+fn default_validation_workers() -> usize {
+    // One signature check per CPU
+    std::thread::available_parallelism().map_or(4, |cpus| cpus.get())
+}
+
+/// Temporary sender bans after repeated validation failures
+/// 
+/// A sender with `max_failures` counted failures within `window_secs` is
+/// refused for `ban_secs`. Only nonces more than `nonce_tolerance` away from
+/// the expected one count, on transactions whose signature checked out: bad
+/// signatures prove nothing about who sent them.
+/// 
+/// # Fields
+/// - `max_failures`: Counted failures that trigger a ban
+/// - `window_secs`: Window over which failures are counted
+/// - `ban_secs`: How long a ban lasts
+/// - `nonce_tolerance`: Nonce distance still treated as an honest mistake
+#[derive(Debug, Clone, Deserialize)]
+pub struct SenderBanConfig {
+    #[serde(default = "default_ban_max_failures")]
+    pub max_failures: u32,
+    #[serde(default = "default_ban_window")]
+    pub window_secs: u64,
+    #[serde(default = "default_ban_duration")]
+    pub ban_secs: u64,
+    #[serde(default = "default_ban_nonce_tolerance")]
+    pub nonce_tolerance: u64,
+}
+
+fn default_ban_max_failures() -> u32 {
+    10
+}
+
+fn default_ban_window() -> u64 {
+    60
+}
+
+fn default_ban_duration() -> u64 {
+    600
+}
+
+fn default_ban_nonce_tolerance() -> u64 {
+    16
 }
 
 /// Layer 1 connection configuration
@@ -116,14 +499,1425 @@ pub struct ApiConfig {
 /// Settings for monitoring the L1 blockchain for forced transactions.
 /// 
 /// # Fields
-/// - `rpc_url`: Ethereum L1 RPC endpoint (e.g., "https://eth-mainnet.g.alchemy.com/v2/...")
-/// - `bridge_address`: Address of the L1 bridge contract to monitor
-/// - `start_block`: L1 block number to start monitoring from
+/// - `rpc_url`: Ethereum L1 WebSocket RPC endpoint (e.g., "wss://eth-mainnet.g.alchemy.com/v2/...")
+/// - `bridge_address`: Address of the native L1 bridge contract to monitor
+/// - `bridges`: Further bridge contracts to monitor (e.g. an ERC-20 gateway),
+///   each with its own events
+/// - `start_block`: L1 block number to start monitoring from on the first start;
+///   later starts resume from the scan cursor persisted in the registry
+/// - `express_lane`: Bonded forced-exit fast path (disabled by default)
+/// - `allowlist_registry`: L1 contract whose `SenderApproved` / `SenderRevoked`
+///   events maintain the pool allowlist (turns on permissioned mode)
+/// - `stake_registry`: L1 contract whose `Staked` / `Unstaked` events record
+///   sender stakes (see `validation.min_sender_stake_wei`)
+/// - `confirmations`: Blocks a bridge event must be buried under before it is
+///   queued; until then it is provisional, and dropped if reorged out
+///   (queued immediately if 0, the default)
+/// - `max_reorg_depth`: Recent L1 block hashes remembered to detect reorgs
+///   (default 64)
+/// - `http_url`: HTTP endpoint of the same L1 node, polled with `eth_getLogs`
+///   while the WebSocket at `rpc_url` is down (no fallback if unset)
+/// - `poll_interval_ms`: Interval between polls of `http_url` (default 2000)
+/// - `fallback_rpc_urls`: WebSocket endpoints failed over to, in order, when
+///   `rpc_url` is unhealthy
+/// - `retry_initial_ms` / `retry_max_ms`: Backoff between rounds of failed
+///   connections, doubling from the initial delay up to the maximum
+///   (defaults 1000 and 60000)
+/// - `health_check_interval_ms`: How often a listener on a fallback checks
+///   whether `rpc_url` is healthy again (default 30000)
+/// - `submitter`: Commits sealed batches to the L1 rollup contract (disabled
+///   unless set)
+/// - `gas_oracle`: Tracks L1 gas prices to estimate batch posting costs
+///   (disabled unless set)
+/// - `posting`: Holds sealed batches until L1 gas gets cheaper, within a
+///   maximum delay (disabled unless set; needs `gas_oracle` and `submitter`)
+/// - `finality`: Tracks the L1 `safe` and `finalized` tags to report how
+///   final deposits and batches are (disabled unless set)
+/// - `verification`: Monitors the rollup contract for batch verifications
+///   (disabled unless set; needs `submitter`)
+/// - `safe_mode`: Falls back to forced-transaction-only batches when the
+///   rollup contract's escape hatch opens or commitments keep failing
+///   (disabled unless set; needs `submitter`)
+/// - `wallet`: The sequencer's L1 account: its key, nonces and fee escalation
+/// - `forced_inclusion`: Deadlines by which forced transactions must be batched
+/// - `backfill`: How past blocks are scanned for bridge events, from
+///   `start_block` on a fresh start or from the scan cursor after downtime
+/// - `devnet`: Local L1 test node used when started with `--devnet`
 #[derive(Debug, Clone, Deserialize)]
 pub struct L1Config {
     pub rpc_url: String,
     pub bridge_address: String,
+    #[serde(default)]
+    pub bridges: Vec<BridgeConfig>,
     pub start_block: u64,
+    #[serde(default)]
+    pub express_lane: ExpressLaneConfig,
+    #[serde(default)]
+    pub allowlist_registry: Option<String>,
+    #[serde(default)]
+    pub stake_registry: Option<String>,
+    #[serde(default)]
+    pub confirmations: u64,
+    #[serde(default = "default_max_reorg_depth")]
+    pub max_reorg_depth: usize,
+    #[serde(default)]
+    pub http_url: Option<String>,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default = "default_retry_initial_ms")]
+    pub retry_initial_ms: u64,
+    #[serde(default = "default_retry_max_ms")]
+    pub retry_max_ms: u64,
+    #[serde(default = "default_health_check_interval_ms")]
+    pub health_check_interval_ms: u64,
+    #[serde(default)]
+    pub submitter: Option<SubmitterConfig>,
+    #[serde(default)]
+    pub gas_oracle: Option<GasOracleConfig>,
+    #[serde(default)]
+    pub posting: Option<PostingConfig>,
+    #[serde(default)]
+    pub finality: Option<FinalityConfig>,
+    #[serde(default)]
+    pub verification: Option<VerificationConfig>,
+    #[serde(default)]
+    pub safe_mode: Option<SafeModeConfig>,
+    #[serde(default)]
+    pub wallet: WalletConfig,
+    #[serde(default)]
+    pub forced_inclusion: ForcedInclusionConfig,
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+    #[serde(default)]
+    pub devnet: DevnetConfig,
+}
+
+impl L1Config {
+    /// WebSocket endpoints in order of preference: `rpc_url`, then the fallbacks
+    pub fn rpc_urls(&self) -> Vec<&str> {
+        std::iter::once(self.rpc_url.as_str())
+            .chain(self.fallback_rpc_urls.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// L1 bridge contract watched besides the native bridge
+/// 
+/// Each of its events is declared in human-readable ABI form and mapped onto
+/// a forced transaction: which parameters hold its sender, recipient and
+/// amount. L2 balances are in a single asset, so a bridge's amounts must
+/// already be in it (e.g. a gateway converting tokens as they are deposited).
+/// 
+/// # Fields
+/// - `name`: Name of the bridge in logs
+/// - `address`: Address of the contract
+/// - `events`: Events of the contract that make forced transactions
+/// 
+/// # Example TOML
+/// ```toml
+/// [[l1.bridges]]
+/// name = "erc20_gateway"
+/// address = "0x5FbDB2315678afecb367f032d93F642f64180aa3"
+/// 
+/// [[l1.bridges.events]]
+/// signature = "event TokenDeposited(address indexed token, address indexed sender, address indexed recipient, uint256 amount)"
+/// kind = "deposit"
+/// from = "sender"
+/// to = "recipient"
+/// value = "amount"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct BridgeConfig {
+    pub name: String,
+    pub address: String,
+    pub events: Vec<BridgeEventConfig>,
+}
+
+/// Bridge event making a forced transaction
+/// 
+/// # Fields
+/// - `signature`: Human-readable event declaration, with parameter names
+/// - `kind`: Forced transaction it makes
+/// - `from`: Parameter holding the sender (default "from")
+/// - `to`: Parameter holding the recipient: the L2 account of a deposit, the
+///   L1 address of an exit (default "to")
+/// - `value`: Parameter holding the amount (default "value")
+/// - `bond`: Parameter holding an express exit's bond (default "bond")
+/// - `gas_limit`: Parameter holding the gas limit of a message (default "gasLimit")
+/// - `data`: Parameter holding the calldata of a message (default "data")
+#[derive(Debug, Clone, Deserialize)]
+pub struct BridgeEventConfig {
+    pub signature: String,
+    pub kind: BridgeEventKind,
+    #[serde(default = "default_from_param")]
+    pub from: String,
+    #[serde(default = "default_to_param")]
+    pub to: String,
+    #[serde(default = "default_value_param")]
+    pub value: String,
+    #[serde(default = "default_bond_param")]
+    pub bond: String,
+    #[serde(default = "default_gas_limit_param")]
+    pub gas_limit: String,
+    #[serde(default = "default_data_param")]
+    pub data: String,
+}
+
+fn default_from_param() -> String {
+    "from".to_string()
+}
+
+fn default_to_param() -> String {
+    "to".to_string()
+}
+
+fn default_value_param() -> String {
+    "value".to_string()
+}
+
+fn default_bond_param() -> String {
+    "bond".to_string()
+}
+
+fn default_gas_limit_param() -> String {
+    "gasLimit".to_string()
+}
+
+fn default_data_param() -> String {
+    "data".to_string()
+}
+
+/// Forced transaction a bridge event makes
+/// 
+/// Express exits are only watched while `l1.express_lane` is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeEventKind {
+    /// Credit the recipient's L2 account
+    Deposit,
+    /// Debit the sender's L2 account, paying the L1 recipient
+    ForcedExit,
+    /// Forced exit bonded for the express lane
+    ExpressExit,
+    /// Call of an L2 contract, with calldata and a gas limit
+    Message,
+}
+
+fn default_max_reorg_depth() -> usize {
+    64
+}
+
+fn default_poll_interval_ms() -> u64 {
+    2000
+}
+
+fn default_retry_initial_ms() -> u64 {
+    1000
+}
+
+fn default_retry_max_ms() -> u64 {
+    60_000
+}
+
+fn default_health_check_interval_ms() -> u64 {
+    30_000
+}
+
+/// L1 batch submission configuration
+/// 
+/// Each sealed batch is posted to the rollup contract's `commitBatch`, in a
+/// transaction sent through the L1 endpoints of `l1.rpc_url` and
+/// `l1.fallback_rpc_urls`.
+/// 
+/// # Fields
+/// - `rollup_address`: Address of the L1 rollup contract
+/// - `signing_key`: Private key of the sequencer's L1 account, which signs and
+///   pays for the commitments (or set a key source under `[l1.wallet]`, which
+///   takes precedence)
+/// - `chain_id`: Chain ID of L1, for replay-protected signatures
+/// - `confirmations`: Blocks a commitment must be buried under before the
+///   batch counts as committed (default 1)
+/// - `max_attempts`: Attempts to get a commitment mined before the batch is
+///   marked failed and its transactions reinjected (default 3)
+/// - `data_availability`: Where the batch data goes: `"calldata"` (default)
+///   or `"blobs"` (EIP-4844 blob transactions)
+/// - `max_blobs_per_tx`: Most blobs one commitment may carry (default 6);
+///   larger batches are posted as calldata
+/// - `blob_base_fee_update_fraction`: L1's blob base fee update fraction
+///   (default 5007716, from Prague; 3338477 under Cancun)
+/// - `max_fee_per_blob_gas_wei`: Highest blob gas price offered. While the
+///   blob base fee is above it, batches are posted as calldata. Unlimited if unset.
+/// - `blob_commit_gas_limit`: Gas limit of blob commitments (default 200000)
+/// - `compression`: Codec compressing the batch data posted, in calldata or
+///   blobs: `"none"` (default), `"zlib"`, `"brotli"` or `"zstd"`
+/// - `compression_level`: Level of that codec (its own default if unset:
+///   zlib 6, brotli 11, zstd 3)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.submitter]
+/// rollup_address = "0x0000000000000000000000000000000000000000"
+/// signing_key = "0x0000000000000000000000000000000000000000000000000000000000000001"
+/// chain_id = 1
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmitterConfig {
+    pub rollup_address: String,
+    #[serde(default)]
+    pub signing_key: Option<H256>,
+    pub chain_id: u64,
+    #[serde(default = "default_submitter_confirmations")]
+    pub confirmations: usize,
+    #[serde(default = "default_submitter_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default)]
+    pub data_availability: DataAvailability,
+    #[serde(default = "default_max_blobs_per_tx")]
+    pub max_blobs_per_tx: usize,
+    #[serde(default = "default_blob_base_fee_update_fraction")]
+    pub blob_base_fee_update_fraction: u64,
+    #[serde(default)]
+    pub max_fee_per_blob_gas_wei: Option<u64>,
+    #[serde(default = "default_blob_commit_gas_limit")]
+    pub blob_commit_gas_limit: u64,
+    #[serde(default)]
+    pub compression: BatchCompression,
+    #[serde(default)]
+    pub compression_level: Option<u32>,
+}
+
+/// Where committed batches publish their data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataAvailability {
+    /// In the calldata of the commitment
+    #[default]
+    Calldata,
+    /// In EIP-4844 blobs attached to the commitment
+    Blobs,
+}
+
+/// Codec compressing batch data before it is posted to L1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchCompression {
+    /// Posted as plain JSON
+    #[default]
+    None,
+    Zlib,
+    Brotli,
+    Zstd,
+}
+
+impl BatchCompression {
+    /// Name recorded in batch metadata
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchCompression::None => "none",
+            BatchCompression::Zlib => "zlib",
+            BatchCompression::Brotli => "brotli",
+            BatchCompression::Zstd => "zstd",
+        }
+    }
+}
+
+fn default_submitter_confirmations() -> usize {
+    1
+}
+
+fn default_submitter_max_attempts() -> u32 {
+    3
+}
+
+fn default_max_blobs_per_tx() -> usize {
+    6
+}
+
+pub(crate) fn default_blob_base_fee_update_fraction() -> u64 {
+    5_007_716
+}
+
+fn default_blob_commit_gas_limit() -> u64 {
+    200_000
+}
+
+/// L1 gas price oracle configuration
+/// 
+/// The oracle reads the base fee and blob base fee of the L1 head and
+/// estimates what posting a batch costs, in calldata or blobs as the
+/// submitter would post it. The estimates feed the economic batch trigger
+/// and `estimateFee`.
+/// 
+/// # Fields
+/// - `poll_interval_ms`: Interval between reads of the L1 head (default 12000)
+/// - `commit_gas`: Execution gas of a commitment, besides its calldata
+///   (default 100000)
+/// - `priority_fee_wei`: Priority fee per gas assumed on top of the base fee
+///   (default 1 gwei)
+/// - `bytes_per_tx`: Posted bytes per transaction, after compression, used to
+///   size batches that are not sealed yet (default 200)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.gas_oracle]
+/// poll_interval_ms = 12000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct GasOracleConfig {
+    #[serde(default = "default_gas_oracle_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default = "default_commit_gas")]
+    pub commit_gas: u64,
+    #[serde(default = "default_priority_fee_wei")]
+    pub priority_fee_wei: u64,
+    #[serde(default = "default_bytes_per_tx")]
+    pub bytes_per_tx: usize,
+}
+
+impl Default for GasOracleConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: default_gas_oracle_poll_interval_ms(),
+            commit_gas: default_commit_gas(),
+            priority_fee_wei: default_priority_fee_wei(),
+            bytes_per_tx: default_bytes_per_tx(),
+        }
+    }
+}
+
+fn default_gas_oracle_poll_interval_ms() -> u64 {
+    12_000
+}
+
+fn default_commit_gas() -> u64 {
+    100_000
+}
+
+fn default_priority_fee_wei() -> u64 {
+    1_000_000_000
+}
+
+fn default_bytes_per_tx() -> usize {
+    200
+}
+
+/// Forced-inclusion deadline configuration
+/// 
+/// Forced transactions are normally sealed into the next batch. These
+/// deadlines bound how long one may wait when batches are far apart or too
+/// full of forced transactions, sealing a batch right away as one nears.
+/// 
+/// # Fields
+/// - `deadline_blocks`: L1 blocks after its event within which a forced
+///   transaction must be batched (no block deadline if unset)
+/// - `margin_blocks`: How many blocks before the block deadline a batch is
+///   sealed, leaving time to post it (default 2)
+/// - `deadline_batches`: Batches within which a forced transaction must be
+///   batched. Once one left out for lack of gas is down to its last batch,
+///   that batch is sealed right away (no batch deadline if unset)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.forced_inclusion]
+/// deadline_blocks = 50
+/// deadline_batches = 3
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ForcedInclusionConfig {
+    #[serde(default)]
+    pub deadline_blocks: Option<u64>,
+    #[serde(default = "default_margin_blocks")]
+    pub margin_blocks: u64,
+    #[serde(default)]
+    pub deadline_batches: Option<u64>,
+}
+
+impl Default for ForcedInclusionConfig {
+    fn default() -> Self {
+        Self {
+            deadline_blocks: None,
+            margin_blocks: default_margin_blocks(),
+            deadline_batches: None,
+        }
+    }
+}
+
+impl ForcedInclusionConfig {
+    /// Whether any deadline is set
+    pub fn enabled(&self) -> bool {
+        self.deadline_blocks.is_some() || self.deadline_batches.is_some()
+    }
+}
+
+fn default_margin_blocks() -> u64 {
+    2
+}
+
+/// Cost-aware batch posting configuration
+/// 
+/// The submitter holds each sealed batch while the L1 base fee read by the
+/// gas oracle is above `max_base_fee_wei` (or the blob base fee above
+/// `max_blob_base_fee_wei`, when set and the batch would go in blobs), for
+/// at most `max_delay_ms`. A batch carrying an express exit is never held,
+/// nor one whose forced transactions near their `l1.forced_inclusion`
+/// block deadline.
+/// 
+/// # Fields
+/// - `max_base_fee_wei`: Highest L1 base fee to post at
+/// - `max_blob_base_fee_wei`: Highest blob base fee to post blobs at (no
+///   limit if unset)
+/// - `max_delay_ms`: Longest a batch is held (default 300000)
+/// - `check_interval_ms`: Interval between checks of the fees while a
+///   batch is held (default 12000)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.posting]
+/// max_base_fee_wei = 20000000000
+/// max_delay_ms = 600000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostingConfig {
+    pub max_base_fee_wei: u64,
+    #[serde(default)]
+    pub max_blob_base_fee_wei: Option<u64>,
+    #[serde(default = "default_max_posting_delay_ms")]
+    pub max_delay_ms: u64,
+    #[serde(default = "default_posting_check_interval_ms")]
+    pub check_interval_ms: u64,
+}
+
+fn default_max_posting_delay_ms() -> u64 {
+    300_000
+}
+
+fn default_posting_check_interval_ms() -> u64 {
+    12_000
+}
+
+/// L1 finality tracking configuration
+/// 
+/// The tracker reads the L1 blocks tagged `safe` and `finalized` and records
+/// them in the registry. Committed batches become finalized once their
+/// commitment is in a finalized block, and deposits report how final the
+/// block of their event is.
+/// 
+/// # Fields
+/// - `poll_interval_ms`: Interval between reads of the tags (default 12000)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.finality]
+/// poll_interval_ms = 12000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct FinalityConfig {
+    #[serde(default = "default_finality_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+impl Default for FinalityConfig {
+    fn default() -> Self {
+        Self { poll_interval_ms: default_finality_poll_interval_ms() }
+    }
+}
+
+fn default_finality_poll_interval_ms() -> u64 {
+    12_000
+}
+
+/// Batch verification monitoring configuration
+/// 
+/// The monitor scans the rollup contract at `l1.submitter.rollup_address`
+/// for `BatchVerified` and `BatchFinalized` events, once they are
+/// `l1.confirmations` deep, and marks their batches verified. Batches are
+/// then only finalized once their verification is (see `l1.finality`).
+/// 
+/// # Fields
+/// - `poll_interval_ms`: Interval between scans of new L1 blocks
+///   (default 12000)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.verification]
+/// poll_interval_ms = 12000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerificationConfig {
+    #[serde(default = "default_verification_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self { poll_interval_ms: default_verification_poll_interval_ms() }
+    }
+}
+
+fn default_verification_poll_interval_ms() -> u64 {
+    12_000
+}
+
+/// Safe mode configuration
+/// 
+/// The sequencer enters safe mode, sealing forced transactions only, while
+/// the rollup contract at `l1.submitter.rollup_address` reports its escape
+/// hatch open (`escapeHatchActive()`), or while batch commitments have been
+/// failing for longer than `max_submission_failure_ms`.
+/// 
+/// # Fields
+/// - `poll_interval_ms`: Interval between reads of the escape hatch
+///   (default 12000)
+/// - `max_submission_failure_ms`: Longest commitments may keep failing
+///   before safe mode (default 600000)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.safe_mode]
+/// max_submission_failure_ms = 300000
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct SafeModeConfig {
+    #[serde(default = "default_escape_hatch_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default = "default_max_submission_failure_ms")]
+    pub max_submission_failure_ms: u64,
+}
+
+impl Default for SafeModeConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: default_escape_hatch_poll_interval_ms(),
+            max_submission_failure_ms: default_max_submission_failure_ms(),
+        }
+    }
+}
+
+fn default_escape_hatch_poll_interval_ms() -> u64 {
+    12_000
+}
+
+fn default_max_submission_failure_ms() -> u64 {
+    600_000
+}
+
+/// Historical event scan configuration
+/// 
+/// Past blocks are scanned with `eth_getLogs`, a chunk of blocks per call.
+/// A call the node refuses (e.g. for returning too many logs) is retried on
+/// half the chunk, which is kept for the rest of the scan.
+/// 
+/// # Fields
+/// - `chunk_blocks`: Blocks per `eth_getLogs` call (default 1000)
+/// - `request_interval_ms`: Shortest time between two calls, to stay within
+///   the provider's rate limits (default 0, unlimited)
+/// - `progress_interval_ms`: How often progress is logged during a scan
+///   (default 10000)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.backfill]
+/// chunk_blocks = 2000
+/// request_interval_ms = 100
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackfillConfig {
+    #[serde(default = "default_chunk_blocks")]
+    pub chunk_blocks: u64,
+    #[serde(default)]
+    pub request_interval_ms: u64,
+    #[serde(default = "default_progress_interval_ms")]
+    pub progress_interval_ms: u64,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            chunk_blocks: default_chunk_blocks(),
+            request_interval_ms: 0,
+            progress_interval_ms: default_progress_interval_ms(),
+        }
+    }
+}
+
+fn default_chunk_blocks() -> u64 {
+    1_000
+}
+
+fn default_progress_interval_ms() -> u64 {
+    10_000
+}
+
+/// Local devnet configuration
+/// 
+/// Only read when the sequencer is started with `--devnet`. It then spawns
+/// an Anvil node (or attaches to a running one), deploys a mock bridge to it,
+/// and points the L1 settings at them.
+/// 
+/// # Fields
+/// - `anvil_path`: Anvil executable spawned (default "anvil", from `PATH`)
+/// - `port`: Port the spawned node listens on (default 8545)
+/// - `block_time_secs`: Interval between blocks of the spawned node (a block
+///   per transaction if unset)
+/// - `attach_url`: WebSocket endpoint of a running test node to use instead
+///   of spawning one
+/// - `deployer_key`: Key deploying the mock bridge on an attached node
+///   (default: the first account of Anvil's default mnemonic)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.devnet]
+/// port = 8546
+/// block_time_secs = 2
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct DevnetConfig {
+    #[serde(default = "default_anvil_path")]
+    pub anvil_path: String,
+    #[serde(default = "default_devnet_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub block_time_secs: Option<u64>,
+    #[serde(default)]
+    pub attach_url: Option<String>,
+    #[serde(default)]
+    pub deployer_key: Option<H256>,
+}
+
+impl Default for DevnetConfig {
+    fn default() -> Self {
+        Self {
+            anvil_path: default_anvil_path(),
+            port: default_devnet_port(),
+            block_time_secs: None,
+            attach_url: None,
+            deployer_key: None,
+        }
+    }
+}
+
+fn default_anvil_path() -> String {
+    "anvil".to_string()
+}
+
+fn default_devnet_port() -> u16 {
+    8545
+}
+
+/// Sequencer L1 wallet configuration
+/// 
+/// The key is taken from the first source set: `keystore_path`, then
+/// `signing_key_env`, then `signing_key` (then `l1.submitter.signing_key`).
+/// 
+/// # Fields
+/// - `keystore_path`: Encrypted JSON keystore holding the key
+/// - `keystore_password_env`: Environment variable holding the keystore's
+///   password (required with `keystore_path`)
+/// - `signing_key_env`: Environment variable holding the private key (hex)
+/// - `signing_key`: Private key, in the configuration file itself
+/// - `resubmit_after_blocks`: L1 blocks a transaction may stay unmined before
+///   it is replaced with higher fees (default 3)
+/// - `fee_bump_percent`: How much each replacement raises the fees, in
+///   percent (default 12; at least 10, the minimum nodes accept)
+/// - `max_fee_per_gas_wei`: Highest fee cap offered; a stuck transaction is no
+///   longer replaced once its fees would go above it. Unlimited if unset.
+/// - `poll_interval_ms`: Interval between checks for receipts (default 3000)
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.wallet]
+/// keystore_path = "keys/sequencer.json"
+/// keystore_password_env = "SEQUENCER_KEYSTORE_PASSWORD"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletConfig {
+    #[serde(default)]
+    pub keystore_path: Option<String>,
+    #[serde(default)]
+    pub keystore_password_env: Option<String>,
+    #[serde(default)]
+    pub signing_key_env: Option<String>,
+    #[serde(default)]
+    pub signing_key: Option<H256>,
+    #[serde(default = "default_resubmit_after_blocks")]
+    pub resubmit_after_blocks: u64,
+    #[serde(default = "default_fee_bump_percent")]
+    pub fee_bump_percent: u64,
+    #[serde(default)]
+    pub max_fee_per_gas_wei: Option<u64>,
+    #[serde(default = "default_wallet_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+impl Default for WalletConfig {
+    fn default() -> Self {
+        Self {
+            keystore_path: None,
+            keystore_password_env: None,
+            signing_key_env: None,
+            signing_key: None,
+            resubmit_after_blocks: default_resubmit_after_blocks(),
+            fee_bump_percent: default_fee_bump_percent(),
+            max_fee_per_gas_wei: None,
+            poll_interval_ms: default_wallet_poll_interval_ms(),
+        }
+    }
+}
+
+fn default_resubmit_after_blocks() -> u64 {
+    3
+}
+
+fn default_fee_bump_percent() -> u64 {
+    12
+}
+
+fn default_wallet_poll_interval_ms() -> u64 {
+    3000
+}
+
+/// Forced-exit express lane configuration
+/// 
+/// When enabled, `ExpressExit` events from the bridge whose bond is at least
+/// `min_bond_wei` are sealed into the very next batch, bypassing the normal
+/// batch timeout. Bonds of exits that turn out to be spam are forfeited.
+/// 
+/// # Example TOML
+/// ```toml
+/// [l1.express_lane]
+/// enabled = true
+/// min_bond_wei = 100000000000000000  # 0.1 ETH
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExpressLaneConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub min_bond_wei: u64,
+}
+
+/// Account state configuration
+/// 
+/// # Fields
+/// - `archive_after_batches`: Accounts not modified for this many batches are
+///   moved from the hot state cache into the registry, and resurrected
+///   transparently when referenced again. Archival is disabled if unset.
+/// - `store`: Persistent store the state cache writes through to, so balances,
+///   nonces and stakes survive restarts. State is kept in memory only if unset.
+/// - `snapshot_batches`: How many recent batches keep a snapshot of the state
+///   changes made while sealing them, so a batch failing downstream can be
+///   rolled back. Rollback is disabled if 0.
+/// - `journal_capacity`: How many recent state changes are journaled with the
+///   transaction and batch that made them, for auditing. Journaling is
+///   disabled if 0.
+/// - `genesis`: Genesis file (JSON) a new chain's state is loaded from. Once
+///   loaded, its hash is recorded, and later starts refuse a different file.
+/// - `checkpoint`: State checkpoint (JSON, see `admin_exportCheckpoint`) a new
+///   node's empty state is loaded from, continuing after its batch instead of
+///   replaying from genesis. Ignored once the state holds accounts.
+/// - `devnet_prefund_wei`: Balance given to every account when first seen, so
+///   devnet senders can transact without a deposit. Never set it on a real
+///   chain: it mints funds. Disabled if unset.
+/// - `max_hot_accounts`: Most accounts held in memory over the store; the
+///   least recently used are evicted and read back from the store when
+///   needed. Needs `store`. Unbounded if unset. Does not cover the state
+///   trie, which bounds itself over a store, or contract storage.
+/// - `shards`: Number of independently locked state cache shards (accounts are
+///   spread across them), so concurrent validations don't contend on one lock
+/// - `reconciliation`: Periodic comparison of the cached state against the
+///   executor's canonical state, repairing drift. Disabled if unset.
+/// - `pruning`: How long the registry keeps per-batch history (account
+///   snapshots and execution witnesses). Everything is kept by default.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateConfig {
+    #[serde(default)]
+    pub archive_after_batches: Option<u64>,
+    #[serde(default)]
+    pub store: Option<StateStoreConfig>,
+    #[serde(default = "default_snapshot_batches")]
+    pub snapshot_batches: usize,
+    #[serde(default = "default_journal_capacity")]
+    pub journal_capacity: usize,
+    #[serde(default)]
+    pub genesis: Option<String>,
+    #[serde(default)]
+    pub checkpoint: Option<String>,
+    #[serde(default)]
+    pub devnet_prefund_wei: Option<u64>,
+    #[serde(default)]
+    pub max_hot_accounts: Option<usize>,
+    #[serde(default = "default_state_shards")]
+    pub shards: usize,
+    #[serde(default)]
+    pub reconciliation: Option<ReconciliationConfig>,
+    #[serde(default)]
+    pub pruning: PruningConfig,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            archive_after_batches: None,
+            store: None,
+            snapshot_batches: default_snapshot_batches(),
+            journal_capacity: default_journal_capacity(),
+            genesis: None,
+            checkpoint: None,
+            devnet_prefund_wei: None,
+            max_hot_accounts: None,
+            shards: default_state_shards(),
+            reconciliation: None,
+            pruning: PruningConfig::default(),
+        }
+    }
+}
+
+fn default_snapshot_batches() -> usize {
+    64
+}
+
+fn default_journal_capacity() -> usize {
+    100_000
+}
+
+fn default_state_shards() -> usize {
+    16
+}
+
+/// Persistent state store configuration
+/// 
+/// # Fields
+/// - `path`: Directory of the store
+/// - `flush`: When writes are flushed to disk
+/// - `flush_interval_ms`: How often writes are flushed with `flush = "interval"`
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateStoreConfig {
+    pub path: String,
+    #[serde(default)]
+    pub flush: FlushPolicy,
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+}
+
+fn default_flush_interval_ms() -> u64 {
+    500
+}
+
+/// State reconciliation configuration
+/// 
+/// The executor is reached over Ethereum JSON-RPC, where its block numbers
+/// are batch IDs: `eth_blockNumber` is the latest executed batch, and
+/// `eth_getBalance` / `eth_getTransactionCount` read the state after it.
+/// 
+/// # Fields
+/// - `executor_url`: JSON-RPC endpoint of the executor
+/// - `interval_secs`: Time between reconciliation rounds
+/// - `accounts_per_round`: Accounts compared per round; rounds walk through
+///   the cached accounts in address order
+/// - `repair`: Whether drifted accounts are overwritten with the canonical
+///   state, or only logged
+/// - `timeout_ms`: Per-request timeout for executor calls
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReconciliationConfig {
+    pub executor_url: String,
+    #[serde(default = "default_reconciliation_interval")]
+    pub interval_secs: u64,
+    #[serde(default = "default_accounts_per_round")]
+    pub accounts_per_round: usize,
+    #[serde(default = "default_repair")]
+    pub repair: bool,
+    #[serde(default = "default_remote_timeout")]
+    pub timeout_ms: u64,
+}
+
+fn default_reconciliation_interval() -> u64 {
+    60
+}
+
+fn default_accounts_per_round() -> usize {
+    1_000
+}
+
+fn default_repair() -> bool {
+    true
+}
+
+/// History retention configuration
+/// 
+/// # Fields
+/// - `mode`: `archive` keeps the history of every batch; `prune` drops it
+///   once older than `keep_batches`
+/// - `keep_batches`: Most recent batches whose history is kept when pruning.
+///   Historical reads and witnesses of older batches are no longer available.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PruningConfig {
+    #[serde(default)]
+    pub mode: PruningMode,
+    #[serde(default = "default_keep_batches")]
+    pub keep_batches: u64,
+}
+
+impl PruningConfig {
+    /// Number of recent batches whose history is kept (`None` keeps all)
+    pub fn keep_batches(&self) -> Option<u64> {
+        match self.mode {
+            PruningMode::Archive => None,
+            PruningMode::Prune => Some(self.keep_batches),
+        }
+    }
+}
+
+impl Default for PruningConfig {
+    fn default() -> Self {
+        Self {
+            mode: PruningMode::default(),
+            keep_batches: default_keep_batches(),
+        }
+    }
+}
+
+fn default_keep_batches() -> u64 {
+    100_000
+}
+
+/// Whether old history is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruningMode {
+    /// Keep the history of every batch
+    #[default]
+    Archive,
+    /// Drop history older than `keep_batches`
+    Prune,
+}
+
+/// When the state store flushes writes to disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlushPolicy {
+    /// After every write: nothing acknowledged is lost in a crash, at the cost of an fsync per write
+    EveryWrite,
+    /// Every `flush_interval_ms`: a crash loses at most that much
+    #[default]
+    Interval,
+}
+
+/// External batch proposer configuration
+/// 
+/// # Fields
+/// - `token`: Bearer token required for `proposer_*` methods
+/// - `signing_key`: Private key the sequencer signs accepted proposals with
+/// 
+/// The proposer API is disabled unless both are set.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProposerConfig {
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub signing_key: Option<H256>,
+}
+
+impl ProposerConfig {
+    /// Whether the proposer API is enabled
+    pub fn enabled(&self) -> bool {
+        self.token.is_some() && self.signing_key.is_some()
+    }
+}
+
+/// EIP-712 domain that user transactions are signed in
+/// 
+/// Wallets must sign with the same domain, so changing any field invalidates
+/// every signature made for the old one.
+/// 
+/// # Fields
+/// - `name`: Signing domain name shown by wallets
+/// - `version`: Domain version, bumped when the transaction type changes
+/// - `chain_id`: Chain ID of this rollup deployment
+/// - `verifying_contract`: Contract that verifies the signatures on L1 (the bridge)
+#[derive(Debug, Clone, Deserialize)]
+pub struct SigningConfig {
+    #[serde(default = "default_signing_name")]
+    pub name: String,
+    #[serde(default = "default_signing_version")]
+    pub version: String,
+    #[serde(default = "default_chain_id")]
+    pub chain_id: u64,
+    #[serde(default)]
+    pub verifying_contract: Address,
+}
+
+fn default_signing_name() -> String {
+    "RollupX".to_string()
+}
+
+fn default_signing_version() -> String {
+    "1".to_string()
+}
+
+fn default_chain_id() -> u64 {
+    31_337 // Local development chain
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self {
+        Self {
+            name: default_signing_name(),
+            version: default_signing_version(),
+            chain_id: default_chain_id(),
+            verifying_contract: Address::zero(),
+        }
+    }
+}
+
+impl SigningConfig {
+    /// The EIP-712 domain described by this config
+    pub fn domain(&self) -> EIP712Domain {
+        EIP712Domain {
+            name: Some(self.name.clone()),
+            version: Some(self.version.clone()),
+            chain_id: Some(self.chain_id.into()),
+            verifying_contract: Some(self.verifying_contract),
+            salt: None,
+        }
+    }
+}
+
+/// Account abstraction configuration
+/// 
+/// User operations from smart-contract wallets are accepted through
+/// `sendUserOperation` and wait in their own lane until batched.
+/// 
+/// # Fields
+/// - `enabled`: Accept user operations
+/// - `lane_capacity`: Most user operations pending at once
+/// - `max_per_batch`: Most user operations per batch; the rest of the batch
+///   is left to normal transactions
+/// - `wallets`: Wallets the built-in owner-signature hook knows, with their owner keys
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountAbstractionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_user_op_lane_capacity")]
+    pub lane_capacity: usize,
+    #[serde(default = "default_user_ops_per_batch")]
+    pub max_per_batch: usize,
+    #[serde(default)]
+    pub wallets: Vec<WalletOwner>,
+}
+
+impl Default for AccountAbstractionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lane_capacity: default_user_op_lane_capacity(),
+            max_per_batch: default_user_ops_per_batch(),
+            wallets: Vec::new(),
+        }
+    }
+}
+
+fn default_user_op_lane_capacity() -> usize {
+    1_024
+}
+
+fn default_user_ops_per_batch() -> usize {
+    16
+}
+
+/// BLS attestation configuration for multi-sequencer deployments
+/// 
+/// Sequencers attest orderings (sealed proposals) and soft confirmations with
+/// BLS signatures, which anyone can aggregate into a single signature for the
+/// whole committee instead of checking one ECDSA signature per node.
+/// 
+/// # Fields
+/// - `bls_secret_key`: Seed this node's BLS key is derived from; nothing is
+///   attested unless set
+/// - `committee`: Compressed BLS public keys (48 bytes) of every sequencer in
+///   the set. Operators must only list keys whose owners proved possession of
+///   the secret key, or one member could forge the others' signatures.
+/// - `threshold`: Committee members an aggregated attestation needs (all of them if unset)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AttestationConfig {
+    #[serde(default)]
+    pub bls_secret_key: Option<H256>,
+    #[serde(default)]
+    pub committee: Vec<Bytes>,
+    #[serde(default)]
+    pub threshold: Option<usize>,
+}
+
+/// Atomic bundle configuration
+/// 
+/// Bundles submitted with `sendBundle` are sealed whole, their transactions
+/// contiguous and in order, or wait for a later batch.
+/// 
+/// # Fields
+/// - `enabled`: Accept bundles
+/// - `lane_capacity`: Most bundles pending at once
+/// - `max_per_batch`: Most bundled transactions per batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_bundle_lane_capacity")]
+    pub lane_capacity: usize,
+    #[serde(default = "default_bundled_per_batch")]
+    pub max_per_batch: usize,
+}
+
+impl Default for BundleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lane_capacity: default_bundle_lane_capacity(),
+            max_per_batch: default_bundled_per_batch(),
+        }
+    }
+}
+
+fn default_bundle_lane_capacity() -> usize {
+    256
+}
+
+fn default_bundled_per_batch() -> usize {
+    64
+}
+
+/// Encrypted mempool configuration
+/// 
+/// Transactions encrypted to an epoch are accepted through
+/// `sendEncryptedTransaction` and wait in their own lane, in arrival order,
+/// until the epoch is over and its decryption key is released.
+/// 
+/// # Fields
+/// - `enabled`: Accept encrypted transactions
+/// - `master_public_key`: Compressed BLS public key (48 bytes) of the keypers'
+///   master secret, which transactions are encrypted to
+/// - `decryption_seed`: Seed of the master secret, for a sequencer releasing
+///   the epoch keys itself once each epoch is over (timelock mode, without
+///   keypers); must match `master_public_key` if both are set
+/// - `epoch_ms`: Length of an epoch (default 5000)
+/// - `max_future_epochs`: How many epochs ahead a transaction may be encrypted to (default 12)
+/// - `key_timeout_epochs`: Epochs to wait for a key before dropping its
+///   transactions (default 12)
+/// - `lane_capacity`: Most encrypted transactions pending at once
+/// - `max_per_batch`: Most decrypted transactions per batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct EncryptedMempoolConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub master_public_key: Option<Bytes>,
+    #[serde(default)]
+    pub decryption_seed: Option<H256>,
+    #[serde(default = "default_epoch_ms")]
+    pub epoch_ms: u64,
+    #[serde(default = "default_max_future_epochs")]
+    pub max_future_epochs: u64,
+    #[serde(default = "default_key_timeout_epochs")]
+    pub key_timeout_epochs: u64,
+    #[serde(default = "default_encrypted_lane_capacity")]
+    pub lane_capacity: usize,
+    #[serde(default = "default_encrypted_per_batch")]
+    pub max_per_batch: usize,
+}
+
+impl Default for EncryptedMempoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            master_public_key: None,
+            decryption_seed: None,
+            epoch_ms: default_epoch_ms(),
+            max_future_epochs: default_max_future_epochs(),
+            key_timeout_epochs: default_key_timeout_epochs(),
+            lane_capacity: default_encrypted_lane_capacity(),
+            max_per_batch: default_encrypted_per_batch(),
+        }
+    }
+}
+
+fn default_epoch_ms() -> u64 {
+    5_000
+}
+
+fn default_max_future_epochs() -> u64 {
+    12
+}
+
+fn default_key_timeout_epochs() -> u64 {
+    12
+}
+
+fn default_encrypted_lane_capacity() -> usize {
+    1_024
+}
+
+fn default_encrypted_per_batch() -> usize {
+    16
+}
+
+/// Inclusion deadline configuration
+/// 
+/// Transactions may ask to be sealed by a `deadline`, or name a priority
+/// class that sets one. Pending transactions nearing their deadline are
+/// taken ahead of the scheduling policy, and a batch is sealed early for
+/// them if the next one would come too late.
+/// 
+/// # Fields
+/// - `enabled`: Honor deadlines; transactions carrying one are rejected otherwise
+/// - `min_tip_wei`: Lowest tip per gas, above the base fee, a transaction
+///   with an explicit deadline must pay
+/// - `min_lead_secs`: Shortest time between submission and deadline
+/// - `margin_secs`: How long before a deadline its transaction must be sealed
+/// - `classes`: Priority classes, each setting a deadline `max_delay_secs`
+///   after submission for transactions paying its `min_tip_wei`
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeadlineConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub min_tip_wei: u64,
+    #[serde(default = "default_deadline_min_lead")]
+    pub min_lead_secs: u64,
+    #[serde(default = "default_deadline_margin")]
+    pub margin_secs: u64,
+    #[serde(default)]
+    pub classes: Vec<PriorityClass>,
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_tip_wei: 0,
+            min_lead_secs: default_deadline_min_lead(),
+            margin_secs: default_deadline_margin(),
+            classes: Vec::new(),
+        }
+    }
+}
+
+impl DeadlineConfig {
+    /// Priority class of this name, if configured
+    pub fn class(&self, name: &str) -> Option<&PriorityClass> {
+        self.classes.iter().find(|class| class.name == name)
+    }
+}
+
+fn default_deadline_min_lead() -> u64 {
+    5
+}
+
+fn default_deadline_margin() -> u64 {
+    1
+}
+
+/// A named priority class: sealed within `max_delay_secs` for a tip of at least `min_tip_wei`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriorityClass {
+    pub name: String,
+    pub max_delay_secs: u64,
+    #[serde(default)]
+    pub min_tip_wei: u64,
+}
+
+/// A smart-contract wallet and the key that controls it
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletOwner {
+    pub wallet: Address,
+    pub owner: Address,
+}
+
+/// Transaction validation configuration
+/// 
+/// # Fields
+/// - `disabled_rules`: Built-in validation rules to skip on this deployment,
+///   e.g. `["balance"]` for a rollup without native balances
+/// - `max_value_wei`: Largest value a single transaction may transfer (no limit if unset)
+/// - `max_calldata_bytes`: Largest calldata a transaction may carry (no limit if unset)
+/// - `max_timestamp_skew_secs`: How far past the local clock a transaction's
+///   timestamp may be (no limit if unset)
+/// - `min_sender_balance_wei`: L2 balance a sender must hold before its
+///   transactions are accepted (no minimum if unset)
+/// - `min_sender_stake_wei`: Stake a sender must have in the L1 stake registry
+///   before its transactions are accepted (no minimum if unset; needs `l1.stake_registry`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ValidationConfig {
+    #[serde(default)]
+    pub disabled_rules: Vec<BuiltinRule>,
+    #[serde(default)]
+    pub max_value_wei: Option<u64>,
+    #[serde(default)]
+    pub max_calldata_bytes: Option<usize>,
+    #[serde(default)]
+    pub max_timestamp_skew_secs: Option<u64>,
+    #[serde(default)]
+    pub min_sender_balance_wei: Option<u64>,
+    #[serde(default)]
+    pub min_sender_stake_wei: Option<u64>,
+}
+
+/// Built-in validation rules, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinRule {
+    /// Signature is well-formed (stateless)
+    Format,
+    /// Value, calldata size, timestamp and deadline are within the configured limits (stateless)
+    Limits,
+    /// Transaction is meant for this chain (stateless)
+    ChainId,
+    /// Fee caps are consistent and cover the base fee (stateless), and the
+    /// pool's fee floor (stateful)
+    Fees,
+    /// Gas limit covers the intrinsic gas (stateless)
+    IntrinsicGas,
+    /// Transaction is signed by its sender (stateless)
+    Signature,
+    /// Sender holds the configured minimum L2 balance and L1 stake (stateful)
+    Stake,
+    /// Sender is not a contract account (stateful)
+    ExternallyOwned,
+    /// Nonce is the sender's next one (stateful)
+    Nonce,
+    /// Sender can pay for the transaction (stateful)
+    Balance,
+}
+
+impl BuiltinRule {
+    /// Every built-in rule, in order
+    pub const ALL: [BuiltinRule; 10] = [
+        BuiltinRule::Format,
+        BuiltinRule::Limits,
+        BuiltinRule::ChainId,
+        BuiltinRule::Fees,
+        BuiltinRule::IntrinsicGas,
+        BuiltinRule::Signature,
+        BuiltinRule::Stake,
+        BuiltinRule::ExternallyOwned,
+        BuiltinRule::Nonce,
+        BuiltinRule::Balance,
+    ];
+    
+    /// Name of the rule as written in the config
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinRule::Format => "format",
+            BuiltinRule::Limits => "limits",
+            BuiltinRule::ChainId => "chain_id",
+            BuiltinRule::Fees => "fees",
+            BuiltinRule::IntrinsicGas => "intrinsic_gas",
+            BuiltinRule::Signature => "signature",
+            BuiltinRule::Stake => "stake",
+            BuiltinRule::ExternallyOwned => "externally_owned",
+            BuiltinRule::Nonce => "nonce",
+            BuiltinRule::Balance => "balance",
+        }
+    }
 }
 
 /// Database configuration
@@ -149,7 +1943,11 @@ impl Config {
     /// 
     /// # Example
     /// ```no_run
+    /// # use sequencer::Config;
+    /// # fn main() -> anyhow::Result<()> {
     /// let config = Config::load("config/default.toml")?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn load(path: &str) -> anyhow::Result<Self> {
         // Read the file contents as a string
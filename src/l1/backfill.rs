@@ -0,0 +1,105 @@
+//! Historical Scan Module
+//!
+//! Pacing and progress of scans over past L1 blocks: the backfill from
+//! `start_block` on a fresh start, the catch-up after downtime, and the
+//! re-scan after a reorg. Calls are spaced out to stay within the provider's
+//! rate limits, and progress is logged periodically with an estimate of the
+//! time left.
+
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::info;
+
+/// Spaces out calls to the L1 node
+pub struct RequestPacer {
+    /// Shortest time between two calls
+    interval: Duration,
+    /// When the last call was let through
+    last: Option<Instant>,
+}
+
+impl RequestPacer {
+    /// Creates a pacer letting a call through every `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last: None }
+    }
+
+    /// Wait until the next call may be made
+    pub async fn wait(&mut self) {
+        if let Some(last) = self.last {
+            tokio::time::sleep_until(last + self.interval).await;
+        }
+        self.last = Some(Instant::now());
+    }
+}
+
+/// Progress of a scan over blocks `from..=to`
+pub struct ScanProgress {
+    /// First block of the scan
+    from: u64,
+    /// Last block of the scan
+    to: u64,
+    /// Last block scanned so far (`None` before the first chunk)
+    scanned: Option<u64>,
+    /// Bridge events found so far
+    events: usize,
+    /// When the scan started
+    started: Instant,
+    /// When progress was last logged
+    reported: Instant,
+    /// How often progress is logged
+    interval: Duration,
+}
+
+impl ScanProgress {
+    /// Starts tracking a scan of `from..=to`, logging progress every `interval`
+    pub fn new(from: u64, to: u64, interval: Duration) -> Self {
+        let now = Instant::now();
+        Self { from, to, scanned: None, events: 0, started: now, reported: now, interval }
+    }
+
+    /// Record a scanned chunk ending at block `through`, in which `events` bridge events were found
+    ///
+    /// Logs progress if it was last logged over an interval ago, or the scan is done.
+    pub fn advance(&mut self, through: u64, events: usize) {
+        self.scanned = Some(through);
+        self.events += events;
+        if self.done() || self.reported.elapsed() >= self.interval {
+            self.reported = Instant::now();
+            let remaining = self.remaining(self.started.elapsed()).unwrap_or_default();
+            info!(
+                "Scanned L1 blocks {}..={} of {}..={} ({:.1}%): {} bridge events, about {}s left",
+                self.from,
+                through,
+                self.from,
+                self.to,
+                self.fraction() * 100.0,
+                self.events,
+                remaining.as_secs()
+            );
+        }
+    }
+
+    /// Fraction of the blocks scanned, from 0 to 1
+    pub fn fraction(&self) -> f64 {
+        let total = self.to.saturating_sub(self.from) + 1;
+        let scanned = self.scanned.map_or(0, |scanned| scanned.saturating_sub(self.from) + 1);
+        scanned.min(total) as f64 / total as f64
+    }
+
+    /// Whether every block was scanned
+    pub fn done(&self) -> bool {
+        self.scanned.is_some_and(|scanned| scanned >= self.to)
+    }
+
+    /// Time left at the pace so far, `elapsed` into the scan
+    ///
+    /// `None` before the first chunk.
+    pub fn remaining(&self, elapsed: Duration) -> Option<Duration> {
+        let fraction = self.fraction();
+        if fraction == 0.0 {
+            return None;
+        }
+        Some(elapsed.mul_f64((1.0 - fraction) / fraction))
+    }
+}
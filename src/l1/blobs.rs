@@ -0,0 +1,274 @@
+//! EIP-4844 Blob Module
+//!
+//! Packs batch data into blobs and builds the blob transactions (type 0x03)
+//! carrying them to L1.
+//!
+//! # Encoding
+//! The data is prefixed with its length (4 bytes, big-endian) and split into
+//! 31-byte chunks. Each chunk fills a 32-byte field element after a zero byte,
+//! so every element is below the BLS12-381 modulus. A blob holds 4096
+//! elements, or 126,976 bytes of data; the last blob is zero-padded.
+//!
+//! # Commitments
+//! Every blob gets a KZG commitment and proof, computed against the Ethereum
+//! trusted setup. The transaction references each blob by its versioned
+//! hash (`0x01` followed by the last 31 bytes of the commitment's SHA-256);
+//! the blobs, commitments and proofs travel alongside it as a sidecar and are
+//! never part of its hash.
+//!
+//! # Pricing
+//! The blob base fee follows its own fee market, set by the head block's
+//! excess blob gas (see [`blob_base_fee`]).
+
+use c_kzg::{ethereum_kzg_settings, Blob, Bytes48, BYTES_PER_BLOB, FIELD_ELEMENTS_PER_BLOB};
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::{keccak256, rlp::RlpStream};
+use sha2::{Digest, Sha256};
+
+/// Type byte of blob transactions
+const BLOB_TX_TYPE: u8 = 0x03;
+
+/// Version byte of KZG versioned hashes
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Data bytes per field element (the first byte stays zero)
+const BYTES_PER_CHUNK: usize = 31;
+
+/// Length prefix of the encoded data
+const LENGTH_PREFIX: usize = 4;
+
+/// Data bytes a single blob holds
+pub const BLOB_DATA_CAPACITY: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_CHUNK;
+
+/// Blob gas consumed by each blob
+pub const GAS_PER_BLOB: u64 = 1 << 17;
+
+/// Lowest blob base fee, in wei
+const MIN_BLOB_BASE_FEE: u64 = 1;
+
+/// Stack size for KZG computations, which keep whole blobs and polynomials on the stack
+const KZG_STACK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Blobs carrying a batch, with their KZG commitments and proofs
+pub struct BlobSidecar {
+    /// Blob contents, only turned into `Blob`s on the KZG thread
+    blobs: Vec<Vec<u8>>,
+    commitments: Vec<Bytes48>,
+    proofs: Vec<Bytes48>,
+}
+
+impl BlobSidecar {
+    /// Pack `data` into blobs and commit to them
+    ///
+    /// # Returns
+    /// An error if `data` needs more than `max_blobs` blobs, or the KZG
+    /// computation fails
+    pub fn new(data: &[u8], max_blobs: usize) -> anyhow::Result<Self> {
+        let blobs = encode_blobs(data)?;
+        if blobs.len() > max_blobs {
+            anyhow::bail!("{} bytes need {} blobs, more than the {} allowed", data.len(), blobs.len(), max_blobs);
+        }
+        with_kzg_stack(|| {
+            let settings = ethereum_kzg_settings(0);
+            let mut commitments = Vec::with_capacity(blobs.len());
+            let mut proofs = Vec::with_capacity(blobs.len());
+            for blob in &blobs {
+                let blob = Blob::from_bytes(blob)?;
+                let commitment = settings.blob_to_kzg_commitment(&blob)?.to_bytes();
+                proofs.push(settings.compute_blob_kzg_proof(&blob, &commitment)?.to_bytes());
+                commitments.push(commitment);
+            }
+            Ok((commitments, proofs))
+        })
+        .map(|(commitments, proofs)| Self { blobs, commitments, proofs })
+    }
+
+    /// Number of blobs
+    pub fn len(&self) -> usize {
+        self.blobs.len()
+    }
+
+    /// Whether there are no blobs (never, as even empty data takes one)
+    pub fn is_empty(&self) -> bool {
+        self.blobs.is_empty()
+    }
+
+    /// Versioned hashes of the blobs, as referenced by the transaction
+    pub fn versioned_hashes(&self) -> Vec<H256> {
+        self.commitments.iter().map(versioned_hash).collect()
+    }
+
+    /// Check every blob against its commitment and proof
+    pub fn verify(&self) -> anyhow::Result<bool> {
+        with_kzg_stack(|| {
+            let blobs = self.blobs.iter().map(|blob| Blob::from_bytes(blob)).collect::<Result<Vec<_>, _>>()?;
+            Ok(ethereum_kzg_settings(0).verify_blob_kzg_proof_batch(&blobs, &self.commitments, &self.proofs)?)
+        })
+    }
+
+    /// Decode the data packed into the blobs
+    pub fn data(&self) -> anyhow::Result<Vec<u8>> {
+        decode_blobs(&self.blobs)
+    }
+}
+
+/// Run a KZG computation on a thread with a large enough stack
+fn with_kzg_stack<T: Send>(f: impl FnOnce() -> anyhow::Result<T> + Send) -> anyhow::Result<T> {
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(KZG_STACK_SIZE)
+            .spawn_scoped(scope, f)?
+            .join()
+            .map_err(|_| anyhow::anyhow!("KZG computation panicked"))?
+    })
+}
+
+/// Versioned hash of a KZG commitment
+fn versioned_hash(commitment: &Bytes48) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment.into_inner()).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    H256(hash)
+}
+
+/// Pack `data`, length-prefixed, into as few blobs as it fits in
+fn encode_blobs(data: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+    let length = u32::try_from(data.len())?.to_be_bytes();
+    let payload: Vec<u8> = length.iter().chain(data).copied().collect();
+    let blobs = payload
+        .chunks(BLOB_DATA_CAPACITY)
+        .map(|chunk| {
+            let mut bytes = vec![0u8; BYTES_PER_BLOB];
+            for (element, piece) in bytes.chunks_mut(32).zip(chunk.chunks(BYTES_PER_CHUNK)) {
+                element[1..=piece.len()].copy_from_slice(piece);
+            }
+            bytes
+        })
+        .collect();
+    Ok(blobs)
+}
+
+/// Unpack the data [`encode_blobs`] packed
+fn decode_blobs(blobs: &[Vec<u8>]) -> anyhow::Result<Vec<u8>> {
+    let payload: Vec<u8> = blobs
+        .iter()
+        .flat_map(|blob| blob.chunks(32).flat_map(|element| element[1..].to_vec()))
+        .collect();
+    anyhow::ensure!(payload.len() >= LENGTH_PREFIX, "no blobs");
+    let length = u32::from_be_bytes(payload[..LENGTH_PREFIX].try_into()?) as usize;
+    payload
+        .get(LENGTH_PREFIX..LENGTH_PREFIX + length)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| anyhow::anyhow!("blobs hold fewer than the {} bytes announced", length))
+}
+
+/// Blob base fee (wei per blob gas) for a block's excess blob gas
+///
+/// `update_fraction` sets how fast the fee reacts to demand: 3,338,477 from
+/// Cancun, 5,007,716 from Prague.
+pub fn blob_base_fee(excess_blob_gas: U256, update_fraction: u64) -> U256 {
+    fake_exponential(U256::from(MIN_BLOB_BASE_FEE), excess_blob_gas, U256::from(update_fraction))
+}
+
+/// Integer approximation of `factor * e ** (numerator / denominator)`, as specified by EIP-4844
+///
+/// Saturates at `U256::MAX`.
+fn fake_exponential(factor: U256, numerator: U256, denominator: U256) -> U256 {
+    let mut output = U256::zero();
+    let mut accumulator = factor.saturating_mul(denominator);
+    let mut i = U256::one();
+    while !accumulator.is_zero() {
+        output = output.saturating_add(accumulator);
+        let Some(product) = accumulator.checked_mul(numerator) else {
+            return U256::MAX;
+        };
+        accumulator = product / (denominator * i);
+        i += U256::one();
+    }
+    output / denominator
+}
+
+/// Unsigned blob transaction (EIP-4844, type 0x03)
+#[derive(Debug, Clone)]
+pub struct BlobTransaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: u64,
+    pub to: Address,
+    pub data: Bytes,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<H256>,
+}
+
+impl BlobTransaction {
+    /// Digest the sender signs
+    pub fn sighash(&self) -> H256 {
+        let mut stream = RlpStream::new_list(11);
+        self.append_fields(&mut stream);
+        typed_hash(&stream.out())
+    }
+
+    /// Hash of the transaction signed with `signature`, as L1 reports it
+    pub fn hash(&self, signature: &Signature) -> H256 {
+        typed_hash(&self.rlp_signed(signature))
+    }
+
+    /// Signed transaction with its sidecar, as sent with `eth_sendRawTransaction`
+    ///
+    /// `0x03 || rlp([tx_payload_body, blobs, commitments, proofs])`
+    pub fn network_encoding(&self, signature: &Signature, sidecar: &BlobSidecar) -> Bytes {
+        let mut stream = RlpStream::new_list(4);
+        stream.append_raw(&self.rlp_signed(signature), 1);
+        stream.begin_list(sidecar.blobs.len());
+        for blob in &sidecar.blobs {
+            stream.append(blob);
+        }
+        stream.begin_list(sidecar.commitments.len());
+        for commitment in &sidecar.commitments {
+            stream.append(&commitment.into_inner().as_slice());
+        }
+        stream.begin_list(sidecar.proofs.len());
+        for proof in &sidecar.proofs {
+            stream.append(&proof.into_inner().as_slice());
+        }
+        typed_bytes(&stream.out())
+    }
+
+    /// RLP list of the fields and the signature
+    fn rlp_signed(&self, signature: &Signature) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(14);
+        self.append_fields(&mut stream);
+        // Typed transactions carry the y parity rather than a legacy `v`
+        stream.append(&(signature.v.saturating_sub(27) % 2));
+        stream.append(&signature.r);
+        stream.append(&signature.s);
+        stream.out().to_vec()
+    }
+
+    /// Append the fields covered by the signature
+    fn append_fields(&self, stream: &mut RlpStream) {
+        stream.append(&self.chain_id);
+        stream.append(&self.nonce);
+        stream.append(&self.max_priority_fee_per_gas);
+        stream.append(&self.max_fee_per_gas);
+        stream.append(&self.gas_limit);
+        stream.append(&self.to);
+        stream.append(&U256::zero());
+        stream.append(&self.data.as_ref());
+        // Empty access list
+        stream.begin_list(0);
+        stream.append(&self.max_fee_per_blob_gas);
+        stream.append_list(&self.blob_versioned_hashes);
+    }
+}
+
+/// Type byte followed by `payload`
+fn typed_bytes(payload: &[u8]) -> Bytes {
+    std::iter::once(BLOB_TX_TYPE).chain(payload.iter().copied()).collect::<Vec<_>>().into()
+}
+
+/// Keccak256 of the type byte followed by `payload`
+fn typed_hash(payload: &[u8]) -> H256 {
+    H256(keccak256(typed_bytes(payload)))
+}
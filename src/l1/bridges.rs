@@ -0,0 +1,226 @@
+//! Bridge Contracts Module
+//!
+//! The bridge contracts the L1 listener watches, and how their events decode
+//! into forced transactions. The native bridge at `l1.bridge_address` emits
+//! the built-in events:
+//! - `Deposit(address indexed from, address indexed to, uint256 value)`
+//! - `ForcedExit(address indexed from, address indexed to, uint256 value)`
+//! - `ExpressExit(address indexed from, address indexed to, uint256 value, uint256 bond)`
+//! - `Message(address indexed from, address indexed to, uint256 value, uint256 gasLimit, bytes data)`
+//!
+//! Every contract in `l1.bridges` declares its own events, and which of their
+//! parameters hold the sender, recipient, amount, bond, and a message's gas
+//! limit and calldata. All of them are
+//! watched with a single log filter, and each log is decoded by the event
+//! declared for its contract and first topic.
+
+use crate::config::{BridgeConfig, BridgeEventConfig, BridgeEventKind, L1Config};
+use ethers::abi::{Event, HumanReadableParser, RawLog, Token};
+use ethers::types::{Address, Bytes, Filter, Log, H256, U256};
+
+/// Name of the native bridge in logs
+const NATIVE_BRIDGE: &str = "native";
+
+/// Built-in events of the native bridge
+const NATIVE_EVENTS: [(&str, BridgeEventKind); 4] = [
+    ("event Deposit(address indexed from, address indexed to, uint256 value)", BridgeEventKind::Deposit),
+    ("event ForcedExit(address indexed from, address indexed to, uint256 value)", BridgeEventKind::ForcedExit),
+    (
+        "event ExpressExit(address indexed from, address indexed to, uint256 value, uint256 bond)",
+        BridgeEventKind::ExpressExit,
+    ),
+    (
+        "event Message(address indexed from, address indexed to, uint256 value, uint256 gasLimit, bytes data)",
+        BridgeEventKind::Message,
+    ),
+];
+
+/// A watched bridge event
+struct WatchedEvent {
+    /// Name of the bridge emitting it
+    bridge: String,
+    /// Address of the bridge
+    address: Address,
+    /// The event's ABI
+    event: Event,
+    /// Its topic (the hash of its signature)
+    topic: H256,
+    /// How it maps onto a forced transaction
+    mapping: BridgeEventConfig,
+}
+
+/// Bridge event decoded into the parts of a forced transaction
+///
+/// # Fields
+/// - `bridge`: Name of the bridge that emitted it
+/// - `event`: Name of the event
+/// - `kind`: Forced transaction it makes
+/// - `from`: Sender
+/// - `to`: Recipient
+/// - `value`: Amount
+/// - `bond`: Bond of an express exit (`None` otherwise)
+/// - `gas_limit`: Gas limit of a message (`None` otherwise)
+/// - `data`: Calldata of a message (empty otherwise)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeEvent {
+    pub bridge: String,
+    pub event: String,
+    pub kind: BridgeEventKind,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub bond: Option<U256>,
+    pub gas_limit: Option<u64>,
+    pub data: Bytes,
+}
+
+/// The bridge contracts watched for forced transactions
+pub struct Bridges {
+    /// Every watched event, of every bridge
+    events: Vec<WatchedEvent>,
+}
+
+impl Bridges {
+    /// Bridges of `config`: the native bridge and those of `l1.bridges`
+    ///
+    /// Express exits are left out unless the express lane is enabled.
+    ///
+    /// # Returns
+    /// An error if an address or event declaration is invalid, or an event
+    /// lacks a parameter it is mapped from
+    pub fn new(config: &L1Config) -> anyhow::Result<Self> {
+        let native = BridgeConfig {
+            name: NATIVE_BRIDGE.to_string(),
+            address: config.bridge_address.clone(),
+            events: NATIVE_EVENTS
+                .iter()
+                .map(|&(signature, kind)| BridgeEventConfig {
+                    signature: signature.to_string(),
+                    kind,
+                    from: "from".to_string(),
+                    to: "to".to_string(),
+                    value: "value".to_string(),
+                    bond: "bond".to_string(),
+                    gas_limit: "gasLimit".to_string(),
+                    data: "data".to_string(),
+                })
+                .collect(),
+        };
+
+        let mut events = Vec::new();
+        for bridge in std::iter::once(&native).chain(&config.bridges) {
+            let address: Address = bridge.address
+                .parse()
+                .map_err(|e| anyhow::anyhow!("invalid address of bridge {}: {}", bridge.name, e))?;
+            for mapping in &bridge.events {
+                if mapping.kind == BridgeEventKind::ExpressExit && !config.express_lane.enabled {
+                    continue;
+                }
+                let event = HumanReadableParser::parse_event(&mapping.signature)
+                    .map_err(|e| anyhow::anyhow!("invalid event of bridge {}: {}", bridge.name, e))?;
+                let mut params = vec![&mapping.from, &mapping.to, &mapping.value];
+                match mapping.kind {
+                    BridgeEventKind::ExpressExit => params.push(&mapping.bond),
+                    BridgeEventKind::Message => params.extend([&mapping.gas_limit, &mapping.data]),
+                    _ => {}
+                }
+                if let Some(missing) = params.iter().find(|name| !event.inputs.iter().any(|input| &input.name == **name)) {
+                    anyhow::bail!("event {} of bridge {} has no parameter {}", event.name, bridge.name, missing);
+                }
+                events.push(WatchedEvent {
+                    bridge: bridge.name.clone(),
+                    address,
+                    topic: event.signature(),
+                    event,
+                    mapping: mapping.clone(),
+                });
+            }
+        }
+        Ok(Self { events })
+    }
+
+    /// Log filter matching every watched event (and possibly others, which
+    /// [`Bridges::decode`] skips)
+    pub fn filter(&self) -> Filter {
+        let mut addresses: Vec<Address> = self.events.iter().map(|watched| watched.address).collect();
+        let mut topics: Vec<H256> = self.events.iter().map(|watched| watched.topic).collect();
+        addresses.sort();
+        addresses.dedup();
+        topics.sort();
+        topics.dedup();
+        Filter::new().address(addresses).topic0(topics)
+    }
+
+    /// Names of the watched bridges and their addresses, for logs
+    pub fn describe(&self) -> Vec<String> {
+        let mut bridges: Vec<String> = self.events
+            .iter()
+            .map(|watched| format!("{} at {:?}", watched.bridge, watched.address))
+            .collect();
+        bridges.dedup();
+        bridges
+    }
+
+    /// Decode a log into the forced transaction its event makes
+    ///
+    /// # Returns
+    /// `None` if the log is not a watched event, and an error if it does not
+    /// decode as one
+    pub fn decode(&self, log: &Log) -> anyhow::Result<Option<BridgeEvent>> {
+        let Some(&topic) = log.topics.first() else {
+            return Ok(None);
+        };
+        let Some(watched) = self.events.iter().find(|watched| watched.address == log.address && watched.topic == topic) else {
+            return Ok(None);
+        };
+        let raw = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+        let decoded = watched.event.parse_log(raw)?;
+        let param = |name: &str| {
+            decoded.params
+                .iter()
+                .find(|param| param.name == name)
+                .map(|param| param.value.clone())
+                .ok_or_else(|| anyhow::anyhow!("{} has no parameter {}", watched.event.name, name))
+        };
+        let address = |name: &str| match param(name)? {
+            Token::Address(address) => Ok(address),
+            other => Err(anyhow::anyhow!("parameter {} of {} is not an address: {:?}", name, watched.event.name, other)),
+        };
+        let amount = |name: &str| match param(name)? {
+            Token::Uint(amount) => Ok(amount),
+            other => Err(anyhow::anyhow!("parameter {} of {} is not an amount: {:?}", name, watched.event.name, other)),
+        };
+        let bytes = |name: &str| match param(name)? {
+            Token::Bytes(bytes) => Ok(Bytes::from(bytes)),
+            other => Err(anyhow::anyhow!("parameter {} of {} is not bytes: {:?}", name, watched.event.name, other)),
+        };
+
+        let mapping = &watched.mapping;
+        Ok(Some(BridgeEvent {
+            bridge: watched.bridge.clone(),
+            event: watched.event.name.clone(),
+            kind: mapping.kind,
+            from: address(&mapping.from)?,
+            to: address(&mapping.to)?,
+            value: amount(&mapping.value)?,
+            bond: match mapping.kind {
+                BridgeEventKind::ExpressExit => Some(amount(&mapping.bond)?),
+                _ => None,
+            },
+            gas_limit: match mapping.kind {
+                BridgeEventKind::Message => {
+                    let gas_limit = amount(&mapping.gas_limit)?;
+                    if gas_limit > U256::from(u64::MAX) {
+                        anyhow::bail!("gas limit {} of {} is out of range", gas_limit, watched.event.name);
+                    }
+                    Some(gas_limit.as_u64())
+                }
+                _ => None,
+            },
+            data: match mapping.kind {
+                BridgeEventKind::Message => bytes(&mapping.data)?,
+                _ => Bytes::new(),
+            },
+        }))
+    }
+}
@@ -0,0 +1,99 @@
+//! Confirmation Depth Module
+//!
+//! Holds forced transactions from L1 events until they are buried under
+//! enough blocks that a shallow reorg can no longer remove them. An event in
+//! block `b` is confirmed once the L1 head reaches `b + depth`, and only then
+//! handed to the forced queue. Until then it is provisional: a reorg removing
+//! it just drops it, and the sequencer never sees it.
+//!
+//! Provisional events are held in memory. The listener's scan cursor never
+//! moves past the oldest one, so after a restart their blocks are re-scanned.
+
+use crate::types::ForcedTransaction;
+use ethers::types::H256;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Forced transactions waiting for their L1 block to be deep enough
+pub struct ProvisionalEvents {
+    /// By L1 block, transaction hash and log index, so they confirm in L1 order
+    events: Mutex<BTreeMap<(u64, H256, u64), ForcedTransaction>>,
+    /// Blocks an event must be buried under before it is confirmed
+    depth: u64,
+}
+
+impl ProvisionalEvents {
+    /// Creates an empty set, confirming events `depth` blocks deep
+    pub fn new(depth: u64) -> Self {
+        Self {
+            events: Mutex::new(BTreeMap::new()),
+            depth,
+        }
+    }
+
+    /// Blocks an event must be buried under before it is confirmed
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+
+    /// Hold `tx` until its block is deep enough
+    ///
+    /// # Returns
+    /// `false` if the event is already held (e.g. a re-scan)
+    pub fn add(&self, tx: ForcedTransaction) -> bool {
+        let key = (tx.l1_block_number, tx.l1_tx_hash, tx.l1_log_index);
+        let mut events = self.events.lock().unwrap();
+        if events.contains_key(&key) {
+            return false;
+        }
+        events.insert(key, tx);
+        true
+    }
+
+    /// Drop the events of an L1 transaction reorged out of `l1_block_number`
+    ///
+    /// # Returns
+    /// The dropped events (empty if none were held)
+    pub fn remove_reorged(&self, l1_block_number: u64, l1_tx_hash: H256) -> Vec<ForcedTransaction> {
+        let mut events = self.events.lock().unwrap();
+        let keys: Vec<_> = events
+            .range((l1_block_number, l1_tx_hash, 0)..=(l1_block_number, l1_tx_hash, u64::MAX))
+            .map(|(key, _)| *key)
+            .collect();
+        keys.iter().filter_map(|key| events.remove(key)).collect()
+    }
+
+    /// Drop the events from blocks above `l1_block_number`, which were reorged out
+    ///
+    /// # Returns
+    /// The dropped events, in L1 order
+    pub fn remove_after(&self, l1_block_number: u64) -> Vec<ForcedTransaction> {
+        let mut events = self.events.lock().unwrap();
+        events.split_off(&(l1_block_number.saturating_add(1), H256::zero(), 0)).into_values().collect()
+    }
+
+    /// Take the events confirmed with the L1 head at block `head`, in L1 order
+    pub fn confirm(&self, head: u64) -> Vec<ForcedTransaction> {
+        let Some(deepest) = head.checked_sub(self.depth) else {
+            return Vec::new();
+        };
+        let mut events = self.events.lock().unwrap();
+        let pending = events.split_off(&(deepest.saturating_add(1), H256::zero(), 0));
+        std::mem::replace(&mut *events, pending).into_values().collect()
+    }
+
+    /// L1 block of the oldest event held
+    pub fn oldest_block(&self) -> Option<u64> {
+        self.events.lock().unwrap().keys().next().map(|(block, _, _)| *block)
+    }
+
+    /// Number of events held
+    pub fn len(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    /// Whether no event is held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
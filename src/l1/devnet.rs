@@ -0,0 +1,166 @@
+//! Local Devnet Module
+//!
+//! Runs the sequencer against a local L1 with no external infrastructure.
+//! Started with `--devnet`, the sequencer spawns an Anvil node (or attaches
+//! to a running test node), deploys a mock bridge to it, and points the
+//! listener at both, so deposits can be made and batched end to end.
+//!
+//! The mock bridge is a few hand-assembled opcodes. Any call to it emits the
+//! native bridge's `Deposit(address indexed from, address indexed to, uint256 value)`
+//! event for the value sent: to the `to` argument of `deposit(address to)`,
+//! or to the caller of a plain transfer.
+
+use crate::config::{DevnetConfig, L1Config};
+use ethers::prelude::*;
+use ethers::utils::{hex, keccak256, Anvil, AnvilInstance};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Key of the first account of Anvil's default mnemonic, funded on every
+/// Anvil node (never use it outside a devnet)
+const ANVIL_DEV_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+/// Length of the mock bridge's init code, which prefixes its runtime code
+const INIT_CODE_LEN: u8 = 11;
+
+/// Runtime code of the mock bridge
+pub fn mock_bridge_runtime() -> Vec<u8> {
+    let mut code = vec![
+        0x34, // CALLVALUE
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE: the value is the event's data
+        0x36, // CALLDATASIZE
+        0x15, // ISZERO
+        0x33, // CALLER
+        0x02, // MUL: the caller for a plain transfer, 0 otherwise
+        0x60, 0x04, // PUSH1 4
+        0x35, // CALLDATALOAD: the `to` argument (0 for a plain transfer)
+        0x17, // OR: topic 2, the recipient
+        0x33, // CALLER: topic 1, the sender
+        0x7f, // PUSH32: topic 0, the event signature
+    ];
+    code.extend_from_slice(&keccak256("Deposit(address,address,uint256)"));
+    code.extend_from_slice(&[
+        0x60, 0x20, // PUSH1 32: data size
+        0x60, 0x00, // PUSH1 0: data offset
+        0xa3, // LOG3
+        0x00, // STOP
+    ]);
+    code
+}
+
+/// Creation code deploying the mock bridge: copies its runtime code into
+/// memory and returns it
+pub fn mock_bridge_init_code() -> Bytes {
+    let runtime = mock_bridge_runtime();
+    let mut code = vec![
+        0x60, runtime.len() as u8, // PUSH1 runtime length
+        0x80, // DUP1
+        0x60, INIT_CODE_LEN, // PUSH1 runtime offset
+        0x60, 0x00, // PUSH1 0
+        0x39, // CODECOPY
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+    ];
+    code.extend(runtime);
+    code.into()
+}
+
+/// A local L1 with the mock bridge deployed
+pub struct Devnet {
+    /// The spawned node, killed when dropped (`None` when attached)
+    _anvil: Option<AnvilInstance>,
+    /// WebSocket endpoint of the node
+    ws_url: String,
+    /// Address of the mock bridge
+    bridge_address: Address,
+    /// Block the mock bridge was deployed in
+    deploy_block: u64,
+    /// Funded account that deployed the bridge
+    deployer: LocalWallet,
+}
+
+impl Devnet {
+    /// Spawn (or attach to) the test node and deploy the mock bridge
+    ///
+    /// # Returns
+    /// An error if the node cannot be started or reached, or the deployment fails
+    pub async fn start(config: &DevnetConfig) -> anyhow::Result<Self> {
+        let (anvil, ws_url, deployer) = match &config.attach_url {
+            Some(url) => {
+                let key = config.deployer_key.map(|key| hex::encode(key.as_bytes())).unwrap_or(ANVIL_DEV_KEY.to_string());
+                info!("Attaching to the devnet node at {}", url);
+                (None, url.clone(), key.parse::<LocalWallet>()?)
+            }
+            None => {
+                let mut anvil = Anvil::at(&config.anvil_path).port(config.port);
+                if let Some(block_time) = config.block_time_secs {
+                    anvil = anvil.block_time(block_time);
+                }
+                // Anvil panics if the node does not start; make that an error
+                let anvil = tokio::task::spawn_blocking(move || anvil.spawn())
+                    .await
+                    .map_err(|_| anyhow::anyhow!("failed to start {} (is Foundry installed?)", config.anvil_path))?;
+                let deployer = LocalWallet::from(anvil.keys()[0].clone());
+                let ws_url = anvil.ws_endpoint();
+                info!("Spawned an Anvil devnet at {}", ws_url);
+                (Some(anvil), ws_url, deployer)
+            }
+        };
+
+        let provider = Provider::<Ws>::connect(&ws_url)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to connect to the devnet node at {}: {}", ws_url, e))?;
+        let chain_id = provider.get_chainid().await?.as_u64();
+        let client = Arc::new(SignerMiddleware::new(provider, deployer.clone().with_chain_id(chain_id)));
+        let receipt = client
+            .send_transaction(TransactionRequest::new().data(mock_bridge_init_code()), None)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to deploy the mock bridge: {}", e))?
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("mock bridge deployment was dropped"))?;
+        let bridge_address = receipt.contract_address
+            .ok_or_else(|| anyhow::anyhow!("mock bridge deployment created no contract"))?;
+        let deploy_block = receipt.block_number.unwrap_or_default().as_u64();
+        info!("Deployed the mock bridge at {:?} in block {}", bridge_address, deploy_block);
+
+        Ok(Self { _anvil: anvil, ws_url, bridge_address, deploy_block, deployer })
+    }
+
+    /// Point the L1 settings at the devnet
+    ///
+    /// The native bridge becomes the mock bridge, scanned from its deployment
+    /// block. Contracts the devnet lacks (further bridges, registries, the
+    /// rollup contract) are left out, with a warning.
+    pub fn configure(&self, l1: &mut L1Config) {
+        l1.rpc_url = self.ws_url.clone();
+        l1.fallback_rpc_urls.clear();
+        l1.http_url = None;
+        l1.bridge_address = format!("{:?}", self.bridge_address);
+        l1.start_block = self.deploy_block;
+        if !l1.bridges.is_empty() {
+            warn!("Devnet: not watching the {} bridges of [[l1.bridges]]", l1.bridges.len());
+            l1.bridges.clear();
+        }
+        let allowlist = l1.allowlist_registry.take();
+        let stakes = l1.stake_registry.take();
+        if allowlist.is_some() || stakes.is_some() {
+            warn!("Devnet: the allowlist and stake registries are not deployed, ignoring them");
+        }
+        if l1.submitter.take().is_some() {
+            warn!("Devnet: no rollup contract is deployed, sealed batches are not committed");
+        }
+        l1.verification = None;
+        l1.safe_mode = None;
+    }
+
+    /// Log how to make a deposit on the devnet
+    pub fn log_usage(&self) {
+        info!(
+            "Devnet ready: deposit with `cast send {:?} 'deposit(address)' <l2 account> --value 1ether --private-key 0x{} --rpc-url {}`",
+            self.bridge_address,
+            hex::encode(self.deployer.signer().to_bytes()),
+            self.ws_url,
+        );
+    }
+}
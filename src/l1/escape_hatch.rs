@@ -0,0 +1,77 @@
+//! Escape Hatch Module
+//!
+//! Rollup contracts let users bypass a sequencer that stopped posting: after
+//! long enough without a batch, the contract opens its escape hatch and only
+//! accepts forced transactions. The monitor reads `escapeHatchActive()` from
+//! the rollup contract and switches the sequencer's safe mode accordingly
+//! (see the batch safe mode module). It also rechecks how long commitments
+//! have been failing, so safe mode is entered even while no batch is sealed.
+
+use super::failover::connect_first_healthy;
+use crate::batch::SafeMode;
+use crate::config::{L1Config, SafeModeConfig};
+use ethers::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// Rollup contract view reporting the escape hatch
+abigen!(
+    EscapeHatch,
+    r#"[
+        function escapeHatchActive() external view returns (bool)
+    ]"#,
+);
+
+/// Monitor of the rollup contract's escape hatch
+pub struct EscapeHatchMonitor {
+    /// L1 connection configuration
+    l1: L1Config,
+    /// Safe mode configuration
+    config: SafeModeConfig,
+    /// Address of the rollup contract
+    rollup_address: Address,
+    /// Safe mode switched by the escape hatch
+    safe_mode: Arc<SafeMode>,
+}
+
+impl EscapeHatchMonitor {
+    /// Creates a monitor of the rollup contract at `rollup_address`, switching `safe_mode`
+    ///
+    /// # Returns
+    /// An error if the rollup address is invalid
+    pub fn new(l1: L1Config, config: SafeModeConfig, rollup_address: &str, safe_mode: Arc<SafeMode>) -> anyhow::Result<Self> {
+        let rollup_address = rollup_address.parse()?;
+        Ok(Self { l1, config, rollup_address, safe_mode })
+    }
+
+    /// Read the escape hatch every poll interval, until the process exits
+    ///
+    /// Connects to the first healthy endpoint, and reconnects after a failed
+    /// read. While L1 is unreachable, the escape hatch keeps its last state.
+    pub async fn start(self) {
+        info!("Watching the escape hatch of rollup contract {:?}", self.rollup_address);
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut provider = None;
+        loop {
+            if provider.is_none() {
+                match connect_first_healthy(&self.l1.rpc_urls()).await {
+                    Ok((connected, _)) => provider = Some(Arc::new(connected)),
+                    Err(e) => warn!("Escape hatch monitor cannot reach L1: {:?}", e),
+                }
+            }
+            if let Some(connected) = &provider {
+                let rollup = EscapeHatch::new(self.rollup_address, connected.clone());
+                match rollup.escape_hatch_active().call().await {
+                    Ok(open) => self.safe_mode.set_escape_hatch(open),
+                    Err(e) => {
+                        warn!("Failed to read the escape hatch: {:?}", e);
+                        provider = None;
+                    }
+                }
+            }
+            self.safe_mode.is_active();
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! L1 Endpoint Failover Module
+//!
+//! The listener connects to the first healthy endpoint among `l1.rpc_url`
+//! and `l1.fallback_rpc_urls`, in that order. An endpoint is healthy if it
+//! accepts a WebSocket connection and answers `eth_blockNumber` within
+//! [`HEALTH_CHECK_TIMEOUT`]. When every endpoint fails, the listener waits
+//! with exponential backoff before trying them all again. While connected
+//! to a fallback, it checks the primary endpoint periodically and moves back
+//! to it once it is healthy again. The batch submitter sends each commitment
+//! through the first healthy endpoint the same way.
+
+use ethers::prelude::*;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Longest an endpoint may take to connect and answer a health check
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connect to `url` and check it answers, within [`HEALTH_CHECK_TIMEOUT`]
+pub async fn connect_healthy(url: &str) -> anyhow::Result<Provider<Ws>> {
+    tokio::time::timeout(HEALTH_CHECK_TIMEOUT, async {
+        let provider = Provider::<Ws>::connect(url).await?;
+        provider.get_block_number().await?;
+        Ok(provider)
+    })
+    .await
+    .map_err(|_| anyhow::anyhow!("L1 endpoint {} did not answer within {:?}", url, HEALTH_CHECK_TIMEOUT))?
+}
+
+/// Connect to the first healthy endpoint of `urls`, in order
+/// 
+/// # Returns
+/// The provider, and the index in `urls` of the endpoint it is connected to
+pub async fn connect_first_healthy(urls: &[&str]) -> anyhow::Result<(Provider<Ws>, usize)> {
+    for (index, url) in urls.iter().enumerate() {
+        info!("Connecting to L1 at {}", url);
+        match connect_healthy(url).await {
+            Ok(provider) => {
+                if index > 0 {
+                    warn!("Failed over to L1 endpoint {}", url);
+                }
+                return Ok((provider, index));
+            }
+            Err(e) => warn!("L1 endpoint {} is unhealthy: {:?}", url, e),
+        }
+    }
+    anyhow::bail!("no healthy L1 endpoint")
+}
+
+/// Exponential backoff between failed connection rounds
+pub struct Backoff {
+    /// Delay after the first failure
+    initial: Duration,
+    /// Longest delay
+    max: Duration,
+    /// Delay after the next failure
+    next: Duration,
+}
+
+impl Backoff {
+    /// Creates a backoff starting at `initial` and doubling up to `max`
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max, next: initial }
+    }
+
+    /// Delay to wait after a failure, doubling the one after it
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+
+    /// Start over from the initial delay, after a success
+    pub fn reset(&mut self) {
+        self.next = self.initial;
+    }
+}
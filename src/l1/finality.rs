@@ -0,0 +1,116 @@
+//! L1 Finality Module
+//!
+//! Follows the L1 blocks tagged `safe` and `finalized`. A block at or below
+//! the `safe` tag is unlikely to be reorged out, and one at or below the
+//! `finalized` tag cannot be without slashing a third of the validators.
+//!
+//! The tags are recorded in the registry, which finalizes every committed
+//! batch whose commitment is in a finalized block and reports how final the
+//! block of each deposit is. Batches are only ever finalized by the tag, not
+//! by a number of confirmations. While the rollup contract's verifications
+//! are monitored (`l1.verification`), a batch is only finalized once the
+//! block verifying it is finalized.
+
+use super::failover::connect_first_healthy;
+use crate::config::{FinalityConfig, L1Config};
+use crate::registry::Registry;
+use crate::FinalityTags;
+use ethers::prelude::*;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Tracker of the L1 finality tags
+pub struct FinalityTracker {
+    /// L1 connection configuration
+    l1: L1Config,
+    /// Tracker configuration
+    config: FinalityConfig,
+    /// Where the tags are recorded
+    registry: Arc<Registry>,
+    /// Tags last read
+    tags: RwLock<FinalityTags>,
+}
+
+impl FinalityTracker {
+    /// Creates a tracker for the L1 of `l1`, recording the tags in `registry`
+    pub fn new(l1: L1Config, config: FinalityConfig, registry: Arc<Registry>) -> Self {
+        Self { l1, config, registry, tags: RwLock::default() }
+    }
+
+    /// Tags last read
+    pub fn tags(&self) -> FinalityTags {
+        *self.tags.read().unwrap()
+    }
+
+    /// Record newly read tags, finalizing the batches they reach
+    ///
+    /// A tag the node did not return keeps its previous value.
+    pub async fn observe(&self, safe_block: Option<u64>, finalized_block: Option<u64>) -> anyhow::Result<()> {
+        let previous = self.tags();
+        let tags = FinalityTags {
+            safe_block: safe_block.or(previous.safe_block),
+            finalized_block: finalized_block.or(previous.finalized_block),
+        };
+        if tags == previous {
+            return Ok(());
+        }
+        debug!("L1 safe block {:?}, finalized block {:?}", tags.safe_block, tags.finalized_block);
+        let finalized = self.registry.set_l1_finality(&tags, self.l1.verification.is_some()).await?;
+        *self.tags.write().unwrap() = tags;
+        for batch_id in finalized {
+            info!("Batch #{} finalized on L1", batch_id);
+        }
+        Ok(())
+    }
+
+    /// Read the tags every poll interval, until the process exits
+    ///
+    /// Connects to the first healthy endpoint, and reconnects after a failed read.
+    pub async fn start(self: Arc<Self>) {
+        match self.registry.l1_finality().await {
+            Ok(tags) => *self.tags.write().unwrap() = tags,
+            Err(e) => warn!("Failed to load the recorded L1 finality tags: {:?}", e),
+        }
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut provider = None;
+        loop {
+            if provider.is_none() {
+                match connect_first_healthy(&self.l1.rpc_urls()).await {
+                    Ok((connected, _)) => provider = Some(connected),
+                    Err(e) => warn!("Finality tracker cannot reach L1: {:?}", e),
+                }
+            }
+            if let Some(connected) = &provider {
+                match read_tags(connected).await {
+                    Ok((safe_block, finalized_block)) => {
+                        if let Err(e) = self.observe(safe_block, finalized_block).await {
+                            warn!("Failed to record the L1 finality tags: {:?}", e);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Failed to read the L1 finality tags: {:?}", e);
+                        provider = None;
+                    }
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+/// Numbers of the blocks tagged `safe` and `finalized`
+///
+/// A tag is `None` if the node has no block for it yet.
+async fn read_tags<M: Middleware>(client: &M) -> anyhow::Result<(Option<u64>, Option<u64>)> {
+    let number = |block: Option<Block<H256>>| block.and_then(|block| block.number).map(|number| number.as_u64());
+    let safe = client
+        .get_block(BlockNumber::Safe)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to read the safe block: {}", e))?;
+    let finalized = client
+        .get_block(BlockNumber::Finalized)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to read the finalized block: {}", e))?;
+    Ok((number(safe), number(finalized)))
+}
@@ -0,0 +1,181 @@
+//! L1 Gas Price Oracle Module
+//!
+//! Follows the L1 head's base fee and blob base fee, and estimates what
+//! posting a batch costs at those prices.
+//!
+//! # Estimates
+//! A batch is posted the way the submitter would post it. In calldata, each
+//! byte costs 16 gas on top of the commitment's execution gas, and the
+//! transaction pays at least the EIP-7623 floor of 40 gas per byte. In blobs,
+//! the commitment only pays its execution gas, plus 131,072 blob gas per blob
+//! at the blob base fee. Blobs are only assumed while the submitter would use
+//! them: below its blob fee cap, and within its blobs per transaction.
+//! Compressed data is mostly non-zero bytes, so every byte is priced as one.
+
+use super::blobs::{blob_base_fee, BLOB_DATA_CAPACITY, GAS_PER_BLOB};
+use super::failover::connect_first_healthy;
+use crate::batch::PostingCosts;
+use crate::config::{default_blob_base_fee_update_fraction, DataAvailability, GasOracleConfig, L1Config};
+use crate::types::TX_BASE_GAS;
+use ethers::prelude::*;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Gas per non-zero calldata byte
+const CALLDATA_GAS_PER_BYTE: u64 = 16;
+
+/// Floor gas per non-zero calldata byte (EIP-7623)
+const CALLDATA_FLOOR_GAS_PER_BYTE: u64 = 40;
+
+/// Length prefix of data packed into blobs
+const BLOB_LENGTH_PREFIX: usize = 4;
+
+/// L1 fees at a block
+///
+/// # Fields
+/// - `block_number`: L1 block the fees were read at
+/// - `base_fee`: Base fee per gas
+/// - `blob_base_fee`: Blob base fee per blob gas (`None` before Cancun)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct L1Fees {
+    pub block_number: u64,
+    pub base_fee: U256,
+    pub blob_base_fee: Option<U256>,
+}
+
+/// Estimated cost of posting batch data to L1
+///
+/// # Fields
+/// - `gas`: Gas of the commitment
+/// - `blobs`: Blobs carrying the data (0 if posted as calldata)
+/// - `blob_gas`: Blob gas of those blobs
+/// - `cost_wei`: Total cost at the current fees
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostingCost {
+    pub gas: u64,
+    pub blobs: usize,
+    pub blob_gas: u64,
+    pub cost_wei: U256,
+}
+
+/// L1 gas price oracle
+pub struct L1GasOracle {
+    /// L1 connection configuration, and the submitter's data availability settings
+    l1: L1Config,
+    /// Oracle configuration
+    config: GasOracleConfig,
+    /// Fees at the latest head read (`None` until the first read)
+    fees: RwLock<Option<L1Fees>>,
+}
+
+impl L1GasOracle {
+    /// Creates an oracle for the L1 of `l1`, knowing no fees yet
+    pub fn new(l1: L1Config, config: GasOracleConfig) -> Self {
+        Self { l1, config, fees: RwLock::new(None) }
+    }
+
+    /// Fees at the latest head read
+    pub fn fees(&self) -> Option<L1Fees> {
+        *self.fees.read().unwrap()
+    }
+
+    /// Record the fees of an L1 block
+    ///
+    /// # Arguments
+    /// * `block_number` - The block's number
+    /// * `base_fee` - Its base fee per gas
+    /// * `excess_blob_gas` - Its excess blob gas (`None` before Cancun)
+    pub fn observe(&self, block_number: u64, base_fee: U256, excess_blob_gas: Option<U256>) {
+        let fraction = self.l1.submitter
+            .as_ref()
+            .map_or_else(default_blob_base_fee_update_fraction, |submitter| submitter.blob_base_fee_update_fraction);
+        let fees = L1Fees {
+            block_number,
+            base_fee,
+            blob_base_fee: excess_blob_gas.map(|excess| blob_base_fee(excess, fraction)),
+        };
+        debug!("L1 fees at block {}: base fee {}, blob base fee {:?}", block_number, base_fee, fees.blob_base_fee);
+        *self.fees.write().unwrap() = Some(fees);
+    }
+
+    /// Estimated cost of posting `data_bytes` bytes of batch data
+    ///
+    /// `None` until the fees were read.
+    pub fn estimate(&self, data_bytes: usize) -> Option<PostingCost> {
+        let fees = self.fees()?;
+        let gas_price = fees.base_fee.saturating_add(U256::from(self.config.priority_fee_wei));
+        let gas_cost = |gas: u64| gas_price.saturating_mul(U256::from(gas));
+
+        if let Some((blobs, blob_base_fee)) = self.blobs_for(data_bytes, &fees) {
+            let gas = TX_BASE_GAS + self.config.commit_gas;
+            let blob_gas = blobs as u64 * GAS_PER_BLOB;
+            let cost_wei = gas_cost(gas).saturating_add(blob_base_fee.saturating_mul(U256::from(blob_gas)));
+            return Some(PostingCost { gas, blobs, blob_gas, cost_wei });
+        }
+
+        let bytes = data_bytes as u64;
+        let execution = self.config.commit_gas + bytes * CALLDATA_GAS_PER_BYTE;
+        let gas = TX_BASE_GAS + execution.max(bytes * CALLDATA_FLOOR_GAS_PER_BYTE);
+        Some(PostingCost { gas, blobs: 0, blob_gas: 0, cost_wei: gas_cost(gas) })
+    }
+
+    /// Blobs the submitter would post `data_bytes` bytes in, and their fee
+    ///
+    /// `None` if it would post them as calldata.
+    fn blobs_for(&self, data_bytes: usize, fees: &L1Fees) -> Option<(usize, U256)> {
+        let submitter = self.l1.submitter.as_ref()?;
+        if submitter.data_availability != DataAvailability::Blobs {
+            return None;
+        }
+        let blob_base_fee = fees.blob_base_fee?;
+        if submitter.max_fee_per_blob_gas_wei.is_some_and(|cap| blob_base_fee > U256::from(cap)) {
+            return None;
+        }
+        let blobs = (data_bytes + BLOB_LENGTH_PREFIX).div_ceil(BLOB_DATA_CAPACITY);
+        (blobs <= submitter.max_blobs_per_tx).then_some((blobs, blob_base_fee))
+    }
+
+    /// Estimated L1 cost per transaction of a batch of `tx_count` transactions
+    pub fn cost_per_tx(&self, tx_count: usize) -> Option<U256> {
+        let tx_count = tx_count.max(1);
+        Some(self.batch_cost(tx_count)? / U256::from(tx_count))
+    }
+
+    /// Read the fees of every new L1 head, until the process exits
+    ///
+    /// Connects to the first healthy endpoint, and reconnects after a failed read.
+    pub async fn start(self: Arc<Self>) {
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut provider = None;
+        loop {
+            if provider.is_none() {
+                match connect_first_healthy(&self.l1.rpc_urls()).await {
+                    Ok((connected, _)) => provider = Some(connected),
+                    Err(e) => warn!("Gas oracle cannot reach L1: {:?}", e),
+                }
+            }
+            if let Some(connected) = &provider {
+                match connected.get_block(BlockNumber::Latest).await {
+                    Ok(Some(head)) => match (head.number, head.base_fee_per_gas) {
+                        (Some(number), Some(base_fee)) => self.observe(number.as_u64(), base_fee, head.excess_blob_gas),
+                        _ => warn!("L1 head has no number or base fee"),
+                    },
+                    Ok(None) => warn!("L1 node returned no latest block"),
+                    Err(e) => {
+                        warn!("Gas oracle failed to read the L1 head: {:?}", e);
+                        provider = None;
+                    }
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+impl PostingCosts for L1GasOracle {
+    fn batch_cost(&self, tx_count: usize) -> Option<U256> {
+        self.estimate(tx_count * self.config.bytes_per_tx).map(|cost| cost.cost_wei)
+    }
+}
@@ -0,0 +1,55 @@
+//! L1 Head Clock Module
+//!
+//! Derives batch timestamps from the latest L1 block, so batches never claim
+//! a time ahead of the chain they are posted to.
+
+use crate::batch::BatchClock;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Batch clock reading the timestamp of the latest L1 block
+///
+/// Connects lazily and reconnects after any failed query, so a dropped
+/// WebSocket only affects the batches sealed while L1 is unreachable.
+pub struct L1HeadClock {
+    /// L1 RPC endpoint (WebSocket)
+    rpc_url: String,
+    /// Current connection, if any
+    provider: Mutex<Option<Provider<Ws>>>,
+}
+
+impl L1HeadClock {
+    /// Creates a clock reading from the L1 node at `rpc_url`
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc_url,
+            provider: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchClock for L1HeadClock {
+    async fn now(&self) -> anyhow::Result<u64> {
+        let mut provider = self.provider.lock().await;
+        let connected = match provider.take() {
+            Some(connected) => connected,
+            None => Provider::<Ws>::connect(&self.rpc_url).await?,
+        };
+
+        match connected.get_block(BlockNumber::Latest).await {
+            Ok(head) => {
+                *provider = Some(connected);
+                let block = head.ok_or_else(|| anyhow::anyhow!("L1 node returned no latest block"))?;
+                Ok(block.timestamp.as_u64())
+            }
+            Err(e) => {
+                // The connection is dropped, so the next batch reconnects
+                warn!("L1 head query failed: {:?}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
@@ -7,33 +7,124 @@
 //! # Events Monitored
 //! - **Deposit events**: Users depositing funds from L1 to L2
 //! - **ForcedExit events**: Users forcing withdrawals (censorship resistance)
+//! - **ExpressExit events**: Bonded forced exits for the express lane (if enabled)
+//! 
+//! These come from the native bridge and from every bridge configured in
+//! `l1.bridges`, each decoded by its own event mapping (see the bridges module).
+//! - **SenderApproved / SenderRevoked events**: Allowlist changes from the L1
+//!   allowlist registry (permissioned mode, if configured)
+//! - **Staked / Unstaked events**: Sender stakes from the L1 stake registry
+//!   (stake-gated submission, if configured)
+//! 
+//! Logs the node marks as removed (chain reorganizations) take the matching
+//! transaction back out of the forced queue if it has not been batched yet.
+//!
+//! # Confirmation Depth
+//! With `l1.confirmations` set, bridge events are held as provisional until
+//! their block is that many blocks deep, following the L1 head, and only
+//! then queued. A shallow reorg then removes provisional events only, before
+//! the sequencer could include them.
+//!
+//! # Reorg Recovery
+//! The listener also follows the L1 head and remembers recent block hashes
+//! ([`BlockTracker`]). A head whose parent hash doesn't match reveals a reorg,
+//! even one whose removed logs were never delivered: the listener finds the
+//! last block the canonical chain still agrees with, drops the queued and
+//! provisional events from every block above it, and re-scans those blocks
+//! for bridge events. Events already taken for a batch cannot be recalled.
+//!
+//! # Scan Cursor
+//! After each head, the listener moves its scan cursor (block number and
+//! hash) to the head's parent, or to just before the oldest provisional event,
+//! and persists it in the registry if one is attached. On a restart or
+//! reconnection it checks the cursor block is still canonical, rolling back
+//! as above if not, then re-scans every block since for bridge events, so
+//! none emitted while it was down are missed. Events queued before are not
+//! queued twice.
+//!
+//! # Backfill
+//! On a fresh start, the same scan covers every block from `l1.start_block`
+//! to the head before the listener subscribes, so deposits made before the
+//! sequencer was deployed are not missed. Scans fetch `eth_getLogs` in chunks
+//! of `l1.backfill.chunk_blocks`, halving the chunk when the node refuses one
+//! (e.g. for returning too many logs), no faster than one call every
+//! `l1.backfill.request_interval_ms`, and log their progress every
+//! `l1.backfill.progress_interval_ms` (see the backfill module). The scan
+//! cursor moves after each chunk, so a restart mid-scan resumes from there.
+//!
+//! # Deposit Stages
+//! With a registry attached, deposits held as provisional are recorded as
+//! `Detected`, and as `Reorged` if they are dropped before they are deep
+//! enough. The registry records the later stages as the forced queue and
+//! the orchestrator handle them.
+//!
+//! # HTTP Polling Fallback
+//! Events are streamed with `eth_subscribe` over the WebSocket endpoint. With
+//! `l1.http_url` set, a dropped WebSocket degrades to polling that endpoint
+//! every `l1.poll_interval_ms`: each poll catches up to the head as after a
+//! reconnection, re-scanning bridge events with `eth_getLogs`. The listener
+//! switches back to streaming as soon as the WebSocket connects again.
+//!
+//! # Endpoint Failover
+//! The WebSocket endpoint is the first healthy one of `l1.rpc_url` and
+//! `l1.fallback_rpc_urls` (see the failover module). Failed connection rounds
+//! are retried with exponential backoff, and a listener on a fallback moves
+//! back to the primary endpoint once it is healthy again.
 
-use crate::config::L1Config;
-use crate::pool::ForcedQueue;
-use crate::types::{ForcedEventType, ForcedTransaction};
+use super::failover::{connect_first_healthy, connect_healthy, Backoff};
+use super::backfill::{RequestPacer, ScanProgress};
+use super::{BlockTracker, Bridges, ProvisionalEvents};
+use crate::config::{BridgeEventKind, L1Config};
+use crate::pool::{ForcedQueue, TransactionPool};
+use crate::registry::Registry;
+use crate::state::StateCache;
+use crate::types::{DepositStage, ForcedEventType, ForcedTransaction};
 use ethers::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
-// Bridge contract event signatures
-// These should match the actual RollupBridge contract events
+// Allowlist registry contract events (permissioned mode)
+abigen!(
+    AllowlistRegistry,
+    r#"[
+        event SenderApproved(address indexed sender)
+        event SenderRevoked(address indexed sender)
+    ]"#,
+);
+
+// Stake registry contract events (stake-gated submission)
 abigen!(
-    RollupBridge,
+    StakeRegistry,
     r#"[
-        event Deposit(address indexed from, address indexed to, uint256 value)
-        event ForcedExit(address indexed from, address indexed to, uint256 value)
+        event Staked(address indexed sender, uint256 amount)
+        event Unstaked(address indexed sender, uint256 amount)
     ]"#,
 );
 
 /// L1 event listener
 /// 
-/// Monitors the L1 bridge contract for forced transaction events.
+/// Monitors the L1 bridge contracts for forced transaction events.
 /// Runs continuously in the background, streaming events via WebSocket.
 pub struct L1Listener {
     /// L1 connection configuration (RPC URL, bridge address, etc.)
     config: L1Config,
     /// Reference to the forced transaction queue
     forced_queue: Arc<ForcedQueue>,
+    /// Pool whose allowlist follows the L1 allowlist registry, if configured
+    tx_pool: Option<Arc<TransactionPool>>,
+    /// State cache recording stakes from the L1 stake registry, if configured
+    state_cache: Option<StateCache>,
+    /// Bridge events not yet deep enough to be queued
+    provisional: ProvisionalEvents,
+    /// Hashes of recent L1 heads, to detect reorgs
+    blocks: BlockTracker,
+    /// Where the scan cursor and deposit stages are persisted (the cursor is
+    /// kept in memory only if `None`)
+    registry: Option<Arc<Registry>>,
+    /// Block to scan from on the next (re)connection
+    next_block: AtomicU64,
 }
 
 impl L1Listener {
@@ -44,101 +135,221 @@ impl L1Listener {
     /// * `forced_queue` - Shared reference to the forced transaction queue
     pub fn new(config: L1Config, forced_queue: Arc<ForcedQueue>) -> Self {
         Self { 
+            provisional: ProvisionalEvents::new(config.confirmations),
+            blocks: BlockTracker::new(config.max_reorg_depth),
+            registry: None,
+            next_block: AtomicU64::new(config.start_block),
             config,
             forced_queue,
+            tx_pool: None,
+            state_cache: None,
         }
     }
     
+    /// Persist the scan cursor in `registry`, and resume from it on restart
+    /// 
+    /// Also records there the deposits held until they are deep enough.
+    pub fn with_cursor(mut self, registry: Arc<Registry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+    
+    /// Keep `tx_pool`'s allowlist in sync with the configured allowlist registry
+    pub fn with_allowlist(mut self, tx_pool: Arc<TransactionPool>) -> Self {
+        self.tx_pool = Some(tx_pool);
+        self
+    }
+    
+    /// Record stakes from the configured stake registry in `state_cache`
+    pub fn with_stakes(mut self, state_cache: StateCache) -> Self {
+        self.state_cache = Some(state_cache);
+        self
+    }
+    
     /// Start listening for L1 events
     /// 
-    /// Connects to L1 via WebSocket and continuously monitors the bridge contract
-    /// for Deposit and ForcedExit events. When events are detected:
-    /// 1. Parse the event data (from, to, value)
+    /// Connects to L1 via WebSocket and continuously monitors the bridge contracts
+    /// for their deposit and forced exit events. When events are detected:
+    /// 1. Decode the event data (from, to, value) by its bridge's mapping
     /// 2. Create a ForcedTransaction
     /// 3. Add to the forced queue for priority processing
     /// 
     /// # Error Handling
-    /// - Automatically reconnects on WebSocket failures
+    /// - Automatically reconnects on WebSocket failures, failing over to the
+    ///   next healthy endpoint and backing off exponentially when none is
     /// - Logs errors but continues running
-    /// - Tracks last processed block to avoid duplicates after reconnection
+    /// - Resumes from the scan cursor after reconnection or restart, re-scanning
+    ///   the blocks missed in between
     /// 
     /// # Returns
     /// Runs indefinitely, or returns an error on unrecoverable failures
     pub async fn start(&self) -> anyhow::Result<()> {
         info!("Starting L1 event listener");
-        info!("RPC URL: {}", self.config.rpc_url);
-        info!("Bridge address: {}", self.config.bridge_address);
-        info!("Starting from block: {}", self.config.start_block);
+        info!("RPC URLs: {}", self.config.rpc_urls().join(", "));
+        let bridges = Bridges::new(&self.config)?;
+        info!("Bridges: {}", bridges.describe().join(", "));
+        info!("Confirmation depth: {} blocks", self.config.confirmations);
         
-        // Track the last processed block
-        let mut current_block = self.config.start_block;
+        // Resume after the persisted scan cursor, if any
+        let cursor = match &self.registry {
+            Some(registry) => registry.l1_cursor().await?,
+            None => None,
+        };
+        match cursor {
+            Some((number, hash)) => {
+                info!("Resuming after L1 block {} ({:?})", number, hash);
+                self.blocks.observe(number, hash, H256::zero());
+                self.next_block.store(number + 1, Ordering::Relaxed);
+            }
+            None => info!("Starting from block: {}", self.config.start_block),
+        }
         
         // Main event loop with automatic reconnection
+        let mut backoff = Backoff::new(
+            Duration::from_millis(self.config.retry_initial_ms),
+            Duration::from_millis(self.config.retry_max_ms),
+        );
         loop {
-            match self.listen_for_events(current_block).await {
-                Ok(last_block) => {
-                    // Update the last processed block
-                    current_block = last_block + 1;
-                    warn!("Event stream ended, reconnecting from block {}", current_block);
+            match self.listen_for_events(&bridges, self.next_block.load(Ordering::Relaxed), &mut backoff).await {
+                Ok(()) => {
+                    warn!("Event stream ended, reconnecting from block {}", self.next_block.load(Ordering::Relaxed));
                 }
                 Err(e) => {
                     error!("Error in event listener: {:?}", e);
-                    warn!("Reconnecting in 5 seconds...");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    if let Some(http_url) = &self.config.http_url {
+                        match self.poll_until_reconnected(&bridges, http_url).await {
+                            Ok(()) => continue,
+                            Err(e) => error!("HTTP polling fallback failed: {:?}", e),
+                        }
+                    }
+                    let delay = backoff.next_delay();
+                    warn!("Reconnecting in {:?}...", delay);
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
     }
     
+    /// Connect to the first healthy L1 endpoint, in order of preference
+    /// 
+    /// # Returns
+    /// The connection, and whether it is to the primary endpoint
+    async fn connect(&self) -> anyhow::Result<(Provider<Ws>, bool)> {
+        let (provider, index) = connect_first_healthy(&self.config.rpc_urls()).await?;
+        Ok((provider, index == 0))
+    }
+    
     /// Internal method to listen for events starting from a specific block
     /// 
     /// # Arguments
+    /// * `bridges` - Bridge contracts to watch
     /// * `from_block` - Block number to start listening from
+    /// * `backoff` - Reconnection backoff, reset once caught up
     /// 
     /// # Returns
-    /// The last successfully processed block number
-    async fn listen_for_events(&self, from_block: u64) -> anyhow::Result<u64> {
+    /// Once the event streams end, or a fallback endpoint should give way to
+    /// the primary; the scan cursor records how far they got
+    async fn listen_for_events(&self, bridges: &Bridges, from_block: u64, backoff: &mut Backoff) -> anyhow::Result<()> {
         // Connect to L1 via WebSocket
-        info!("Connecting to L1 at {}", self.config.rpc_url);
-        let provider = Provider::<Ws>::connect(&self.config.rpc_url).await?;
+        let (provider, on_primary) = self.connect().await?;
         let provider = Arc::new(provider);
         
-        // Parse bridge address
-        let bridge_address: Address = self.config.bridge_address.parse()?;
-        info!("Monitoring bridge contract at {}", bridge_address);
+        // Subscribe to the events of every bridge (express exits only if the
+        // express lane is enabled)
+        let mut bridge_stream = provider.subscribe_logs(&bridges.filter().from_block(from_block)).await?;
+        info!("Subscribed to bridge events from block {}", from_block);
         
-        // Create event filters for Deposit and ForcedExit events
-        let deposit_filter = Filter::new()
-            .address(bridge_address)
-            .event("Deposit(address,address,uint256)")
-            .from_block(from_block);
-            
-        let forced_exit_filter = Filter::new()
-            .address(bridge_address)
-            .event("ForcedExit(address,address,uint256)")
-            .from_block(from_block);
+        // Subscribe to allowlist changes (only in permissioned mode with a registry)
+        let registry_address: Option<Address> = match (&self.config.allowlist_registry, &self.tx_pool) {
+            (Some(registry), Some(_)) => Some(registry.parse()?),
+            _ => None,
+        };
+        let mut allowlist_stream = match registry_address {
+            Some(registry_address) => {
+                let allowlist_filter = Filter::new()
+                    .address(registry_address)
+                    .events(["SenderApproved(address)", "SenderRevoked(address)"])
+                    .from_block(from_block);
+                info!("Subscribed to allowlist registry {} from block {}", registry_address, from_block);
+                Some(provider.subscribe_logs(&allowlist_filter).await?)
+            }
+            None => None,
+        };
         
-        // Subscribe to deposit events
-        let mut deposit_stream = provider.subscribe_logs(&deposit_filter).await?;
-        info!("Subscribed to Deposit events from block {}", from_block);
+        // Subscribe to stake changes (only with a stake registry)
+        let stake_registry_address: Option<Address> = match (&self.config.stake_registry, &self.state_cache) {
+            (Some(registry), Some(_)) => Some(registry.parse()?),
+            _ => None,
+        };
+        let mut stake_stream = match stake_registry_address {
+            Some(registry_address) => {
+                let stake_filter = Filter::new()
+                    .address(registry_address)
+                    .events(["Staked(address,uint256)", "Unstaked(address,uint256)"])
+                    .from_block(from_block);
+                info!("Subscribed to stake registry {} from block {}", registry_address, from_block);
+                Some(provider.subscribe_logs(&stake_filter).await?)
+            }
+            None => None,
+        };
+        
+        // Follow the L1 head, to detect reorgs and confirm provisional events
+        let mut head_stream = provider.subscribe_blocks().await?;
+        info!("Subscribed to L1 heads, confirming events {} blocks deep", self.provisional.depth());
         
-        // Subscribe to forced exit events
-        let mut forced_exit_stream = provider.subscribe_logs(&forced_exit_filter).await?;
-        info!("Subscribed to ForcedExit events from block {}", from_block);
+        // Catch up on the blocks passed while down or disconnected
+        self.sync_to_head(&provider, bridges).await?;
+        backoff.reset();
         
-        let mut last_processed_block = from_block;
+        // On a fallback, check the primary endpoint now and then to move back
+        let health_check_period = Duration::from_millis(self.config.health_check_interval_ms);
+        let mut health_check =
+            tokio::time::interval_at(tokio::time::Instant::now() + health_check_period, health_check_period);
         
         // Process events as they arrive
         loop {
+            // Backpressure: stop pulling events while batching is behind
+            if self.forced_queue.is_full().await {
+                warn!("Forced queue full, pausing L1 event ingestion");
+                self.forced_queue.wait_for_space().await;
+                info!("Forced queue drained, resuming L1 event ingestion");
+            }
+            
             tokio::select! {
-                Some(log) = deposit_stream.next() => {
-                    if let Err(e) = self.handle_deposit_event(log).await {
-                        error!("Failed to handle deposit event: {:?}", e);
+                Some(log) = bridge_stream.next() => {
+                    if let Err(e) = self.handle_bridge_event(bridges, log).await {
+                        error!("Failed to handle bridge event: {:?}", e);
                     }
                 }
-                Some(log) = forced_exit_stream.next() => {
-                    if let Err(e) = self.handle_forced_exit_event(log).await {
-                        error!("Failed to handle forced exit event: {:?}", e);
+                Some(log) = async {
+                    match allowlist_stream.as_mut() {
+                        Some(stream) => stream.next().await,
+                        None => std::future::pending().await,
+                    }
+                }, if allowlist_stream.is_some() => {
+                    if let Err(e) = self.handle_allowlist_event(log).await {
+                        error!("Failed to handle allowlist event: {:?}", e);
+                    }
+                }
+                Some(log) = async {
+                    match stake_stream.as_mut() {
+                        Some(stream) => stream.next().await,
+                        None => std::future::pending().await,
+                    }
+                }, if stake_stream.is_some() => {
+                    if let Err(e) = self.handle_stake_event(log).await {
+                        error!("Failed to handle stake event: {:?}", e);
+                    }
+                }
+                Some(head) = head_stream.next() => {
+                    if let Err(e) = self.handle_new_head(&provider, bridges, head).await {
+                        error!("Failed to handle new L1 head: {:?}", e);
+                    }
+                }
+                _ = health_check.tick(), if !on_primary => {
+                    if connect_healthy(&self.config.rpc_url).await.is_ok() {
+                        info!("Primary L1 endpoint {} is healthy again, moving back", self.config.rpc_url);
+                        break;
                     }
                 }
                 else => {
@@ -146,69 +357,339 @@ impl L1Listener {
                     break;
                 }
             }
+        }
+        
+        Ok(())
+    }
+    
+    /// Poll `http_url` for bridge events until the WebSocket endpoint is back
+    async fn poll_until_reconnected(&self, bridges: &Bridges, http_url: &str) -> anyhow::Result<()> {
+        let provider = Provider::<Http>::try_from(http_url)?;
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        warn!("Polling L1 at {} every {:?} until the WebSocket reconnects", http_url, interval);
+        
+        loop {
+            // Backpressure: stop pulling events while batching is behind
+            if self.forced_queue.is_full().await {
+                self.forced_queue.wait_for_space().await;
+            }
+            if let Err(e) = self.sync_to_head(&provider, bridges).await {
+                error!("L1 poll failed: {:?}", e);
+            }
+            tokio::time::sleep(interval).await;
             
-            // Update last processed block (this is approximate, actual implementation
-            // would need more sophisticated tracking)
-            if let Some(block_num) = last_processed_block.checked_add(1) {
-                last_processed_block = block_num;
+            if self.connect().await.is_ok() {
+                info!("WebSocket to L1 is back, resuming event streaming");
+                return Ok(());
             }
         }
-        
-        Ok(last_processed_block)
     }
     
-    /// Handle a Deposit event
-    /// 
-    /// Parses the event and creates a ForcedTransaction for deposit
-    async fn handle_deposit_event(&self, log: Log) -> anyhow::Result<()> {
-        debug!("Received Deposit event: {:?}", log);
+    /// Catch up to the current L1 head: handle the bridge events since the
+    /// scan cursor, then the head itself
+    async fn sync_to_head<P: JsonRpcClient>(&self, provider: &Provider<P>, bridges: &Bridges) -> anyhow::Result<()> {
+        let head = provider
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("L1 node returned no latest block"))?;
+        let from_block = self.next_block.load(Ordering::Relaxed);
+        self.catch_up(provider, bridges, from_block, head.number.unwrap_or_default().as_u64()).await?;
+        self.handle_new_head(provider, bridges, head).await
+    }
+    
+    /// Handle the bridge events of blocks `from_block..=head` missed while
+    /// down or disconnected, first rolling back if the newest block
+    /// remembered is no longer canonical
+    async fn catch_up<P: JsonRpcClient>(&self, provider: &Provider<P>, bridges: &Bridges, from_block: u64, head: u64) -> anyhow::Result<()> {
+        if let Some(newest) = self.blocks.newest() {
+            let canonical = provider.get_block(newest).await?.and_then(|block| block.hash);
+            if canonical != self.blocks.hash(newest) {
+                return self.recover_from_reorg(provider, bridges, head).await;
+            }
+        }
+        if from_block <= head {
+            self.rescan(provider, bridges, from_block, head).await?;
+        }
+        Ok(())
+    }
+    
+    /// Queue a forced transaction, or hold it as provisional until its block
+    /// is deep enough
+    async fn enqueue(&self, forced_tx: ForcedTransaction, kind: &str) {
+        let l1_tx_hash = forced_tx.l1_tx_hash;
+        if self.provisional.depth() > 0 {
+            let l1_block_number = forced_tx.l1_block_number;
+            self.record_deposits(std::slice::from_ref(&forced_tx), DepositStage::Detected).await;
+            if self.provisional.add(forced_tx) {
+                info!(
+                    "Holding {} from block {} until it is {} blocks deep",
+                    kind, l1_block_number, self.provisional.depth()
+                );
+            } else {
+                debug!("Ignoring already held {} {:?}", kind, l1_tx_hash);
+            }
+            return;
+        }
         
-        // Parse the event
-        let event = parse_log::<DepositFilter>(log.clone())?;
+        // Add to forced queue (wakes the orchestrator if bonded), unless this
+        // event was queued before (e.g. a re-scan)
+        if self.forced_queue.add(forced_tx).await {
+            info!("Added {} to forced queue", kind);
+        } else {
+            debug!("Ignoring already queued {} {:?}", kind, l1_tx_hash);
+        }
+    }
+    
+    /// Handle a new L1 head: recover from a reorg if it reveals one, then
+    /// confirm the provisional events it buries deep enough
+    async fn handle_new_head<P: JsonRpcClient>(
+        &self,
+        provider: &Provider<P>,
+        bridges: &Bridges,
+        head: Block<H256>,
+    ) -> anyhow::Result<()> {
+        let (Some(number), Some(hash)) = (head.number, head.hash) else {
+            return Ok(());
+        };
+        let number = number.as_u64();
+        self.forced_queue.set_l1_head(number);
+        if !self.blocks.observe(number, hash, head.parent_hash) {
+            self.recover_from_reorg(provider, bridges, number).await?;
+            self.blocks.observe(number, hash, head.parent_hash);
+        }
+        self.confirm_events(number).await;
+        self.advance_cursor(number, head.parent_hash).await;
+        Ok(())
+    }
+    
+    /// Move the scan cursor to the parent of the head at block `head`, whose
+    /// hash is `parent_hash`, but never past a provisional event, which is
+    /// only held in memory
+    async fn advance_cursor(&self, head: u64, parent_hash: H256) {
+        let cursor = match self.provisional.oldest_block() {
+            Some(oldest) if oldest < head => oldest
+                .checked_sub(1)
+                .and_then(|number| self.blocks.hash(number).map(|hash| (number, hash))),
+            _ => head.checked_sub(1).map(|number| (number, parent_hash)),
+        };
+        let Some((number, hash)) = cursor else {
+            return;
+        };
+        self.next_block.store(number + 1, Ordering::Relaxed);
+        if let Some(registry) = &self.registry
+            && let Err(e) = registry.set_l1_cursor(number, hash).await
+        {
+            warn!("Failed to persist the L1 scan cursor: {:?}", e);
+        }
+    }
+    
+    /// Roll back the events from blocks replaced by a reorg, up to the head
+    /// at block `head`, and re-scan the canonical ones
+    async fn recover_from_reorg<P: JsonRpcClient>(&self, provider: &Provider<P>, bridges: &Bridges, head: u64) -> anyhow::Result<()> {
+        // The last remembered block the canonical chain still agrees with
+        let mut fork_point = None;
+        for number in self.blocks.below(head) {
+            let canonical = provider.get_block(number).await?.and_then(|block| block.hash);
+            if canonical.is_some() && canonical == self.blocks.hash(number) {
+                fork_point = Some(number);
+                break;
+            }
+        }
+        let fork_point = match fork_point {
+            Some(fork_point) => fork_point,
+            None => {
+                // Even the oldest block remembered was replaced
+                let fork_point = self.blocks.oldest().unwrap_or(head).saturating_sub(self.config.max_reorg_depth as u64);
+                error!("L1 reorg deeper than the blocks remembered, rolling back to block {}", fork_point);
+                fork_point
+            }
+        };
+        warn!("L1 reorg detected at head {}, replacing blocks after {}", head, fork_point);
         
+        self.blocks.rewind(fork_point);
+        let provisional = self.provisional.remove_after(fork_point);
+        self.record_deposits(&provisional, DepositStage::Reorged).await;
+        let queued = self.forced_queue.remove_after(fork_point).await;
         info!(
-            "Deposit detected: from={:?}, to={:?}, value={}",
-            event.from, event.to, event.value
+            "Rolled back {} provisional and {} queued forced transactions from reorged blocks",
+            provisional.len(), queued.len()
         );
         
-        // Create a ForcedTransaction
-        let forced_tx = ForcedTransaction {
-            tx_hash: log.transaction_hash.unwrap_or_default(),
-            from: event.from,
-            to: event.to,
-            value: event.value,
-            nonce: 0, // Nonce will be assigned during batch creation based on current state
-            gas_limit: 21000, // Standard gas limit for L1 transfers (deposits)
-            l1_tx_hash: log.transaction_hash.unwrap_or_default(),
-            l1_block_number: log.block_number.unwrap_or_default().as_u64(),
-            event_type: ForcedEventType::Deposit,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+        self.rescan(provider, bridges, fork_point + 1, head).await
+    }
+    
+    /// Handle the bridge events of the canonical blocks `from..=to` again,
+    /// `to` being the head
+    /// 
+    /// Blocks are fetched in chunks of `l1.backfill.chunk_blocks`, halving
+    /// the chunk whenever the node refuses one, and no faster than
+    /// `l1.backfill.request_interval_ms`. After each chunk, the events deep
+    /// enough are confirmed and the scan cursor moves to the chunk's end
+    /// (never past a provisional event), so a scan interrupted by a restart
+    /// resumes where it was.
+    async fn rescan<P: JsonRpcClient>(&self, provider: &Provider<P>, bridges: &Bridges, from: u64, to: u64) -> anyhow::Result<()> {
+        info!("Scanning L1 blocks {}..={} for bridge events", from, to);
+        let backfill = &self.config.backfill;
+        let mut pacer = RequestPacer::new(Duration::from_millis(backfill.request_interval_ms));
+        let mut progress = ScanProgress::new(from, to, Duration::from_millis(backfill.progress_interval_ms));
+        let mut chunk_blocks = backfill.chunk_blocks.max(1);
+        
+        let mut chunk_start = from;
+        while chunk_start <= to {
+            let chunk_end = to.min(chunk_start.saturating_add(chunk_blocks - 1));
+            let filter = bridges.filter().from_block(chunk_start).to_block(chunk_end);
+            pacer.wait().await;
+            let logs = match provider.get_logs(&filter).await {
+                Ok(logs) => logs,
+                Err(e) if chunk_blocks > 1 => {
+                    chunk_blocks = (chunk_blocks / 2).max(1);
+                    warn!(
+                        "Failed to fetch logs of blocks {}..={} ({}), retrying {} blocks at a time",
+                        chunk_start, chunk_end, e, chunk_blocks
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let events = logs.len();
+            for log in logs {
+                if self.forced_queue.is_full().await {
+                    self.forced_queue.wait_for_space().await;
+                }
+                if let Err(e) = self.handle_bridge_event(bridges, log).await {
+                    error!("Failed to handle re-scanned bridge event: {:?}", e);
+                }
+            }
+            progress.advance(chunk_end, events);
+            if chunk_end < to {
+                self.checkpoint(provider, chunk_end, to).await;
+            }
+            chunk_start = chunk_end + 1;
+        }
+        Ok(())
+    }
+    
+    /// Record that a scan handled every block up to `block_number`, with the
+    /// head at block `head`
+    /// 
+    /// Confirms the events deep enough, then moves the scan cursor to
+    /// `block_number`, unless a provisional event at or before it is still held.
+    async fn checkpoint<P: JsonRpcClient>(&self, provider: &Provider<P>, block_number: u64, head: u64) {
+        self.confirm_events(head).await;
+        if self.provisional.oldest_block().is_some_and(|oldest| oldest <= block_number) {
+            return;
+        }
+        let hash = match provider.get_block(block_number).await {
+            Ok(Some(Block { hash: Some(hash), .. })) => hash,
+            Ok(_) => return,
+            Err(e) => {
+                warn!("Failed to read L1 block {} for the scan cursor: {:?}", block_number, e);
+                return;
+            }
+        };
+        self.next_block.store(block_number + 1, Ordering::Relaxed);
+        if let Some(registry) = &self.registry
+            && let Err(e) = registry.set_l1_cursor(block_number, hash).await
+        {
+            warn!("Failed to persist the L1 scan cursor: {:?}", e);
+        }
+    }
+    
+    /// Queue the provisional events confirmed by a new L1 head
+    async fn confirm_events(&self, head: u64) {
+        for forced_tx in self.provisional.confirm(head) {
+            let (l1_tx_hash, l1_block_number) = (forced_tx.l1_tx_hash, forced_tx.l1_block_number);
+            if self.forced_queue.add(forced_tx).await {
+                info!("Confirmed forced transaction {:?} from block {} at head {}", l1_tx_hash, l1_block_number, head);
+            } else {
+                debug!("Ignoring already queued forced transaction {:?}", l1_tx_hash);
+            }
+        }
+    }
+    
+    /// Record the stage of provisional deposits in the registry, if attached
+    /// 
+    /// Only `Detected` and `Reorged` are recorded here. Failures are logged.
+    async fn record_deposits(&self, txs: &[ForcedTransaction], stage: DepositStage) {
+        let Some(registry) = &self.registry else {
+            return;
         };
+        let result = match stage {
+            DepositStage::Detected => registry.record_detected_deposits(txs).await,
+            DepositStage::Reorged => registry.record_reorged_deposits(txs).await,
+            _ => unreachable!("later deposit stages are recorded by the registry"),
+        };
+        if let Err(e) = result {
+            warn!("Failed to record {:?} deposits: {:?}", stage, e);
+        }
+    }
+    
+    /// Handle an event log removed by an L1 reorg
+    /// 
+    /// Drops the matching forced transaction if it is still provisional or
+    /// queued. One that was already batched cannot be recalled, so that is
+    /// only logged.
+    async fn handle_reorged_event(&self, log: &Log, kind: &str) -> anyhow::Result<()> {
+        let l1_block_number = log.block_number.unwrap_or_default().as_u64();
+        let l1_tx_hash = log.transaction_hash.unwrap_or_default();
         
-        // Add to forced queue
-        self.forced_queue.add(forced_tx).await;
-        info!("Added Deposit to forced queue");
+        let provisional = self.provisional.remove_reorged(l1_block_number, l1_tx_hash);
+        if !provisional.is_empty() {
+            self.record_deposits(&provisional, DepositStage::Reorged).await;
+            info!("Dropped provisional {} {:?} reorged out of block {}", kind, l1_tx_hash, l1_block_number);
+            return Ok(());
+        }
         
+        let removed = self.forced_queue.remove_reorged(l1_block_number, l1_tx_hash).await;
+        if removed.is_empty() {
+            warn!(
+                "{} {:?} in block {} was reorged out after leaving the forced queue",
+                kind, l1_tx_hash, l1_block_number
+            );
+        } else {
+            info!("Dropped reorged {} {:?} from the forced queue", kind, l1_tx_hash);
+        }
         Ok(())
     }
     
-    /// Handle a ForcedExit event
+    /// Handle an event of a watched bridge
     /// 
-    /// Parses the event and creates a ForcedTransaction for forced exit
-    async fn handle_forced_exit_event(&self, log: Log) -> anyhow::Result<()> {
-        debug!("Received ForcedExit event: {:?}", log);
+    /// Decodes the event by its bridge's mapping and creates the forced
+    /// transaction it makes. Express exits whose bond is below the configured
+    /// minimum fall back to the normal forced-exit lane (still included, but
+    /// without fast-path sealing). Logs of events not watched are ignored.
+    async fn handle_bridge_event(&self, bridges: &Bridges, log: Log) -> anyhow::Result<()> {
+        debug!("Received bridge event: {:?}", log);
+        let Some(event) = bridges.decode(&log)? else {
+            return Ok(());
+        };
+        let kind = format!("{} from the {} bridge", event.event, event.bridge);
         
-        // Parse the event
-        let event = parse_log::<ForcedExitFilter>(log.clone())?;
+        if log.removed == Some(true) {
+            return self.handle_reorged_event(&log, &kind).await;
+        }
         
         info!(
-            "ForcedExit detected: from={:?}, to={:?}, value={}",
-            event.from, event.to, event.value
+            "{} detected: from={:?}, to={:?}, value={}, bond={:?}",
+            kind, event.from, event.to, event.value, event.bond
         );
         
+        let (event_type, bond) = match event.kind {
+            BridgeEventKind::Deposit => (ForcedEventType::Deposit, None),
+            BridgeEventKind::ForcedExit => (ForcedEventType::ForcedExit, None),
+            BridgeEventKind::Message => (ForcedEventType::Message, None),
+            BridgeEventKind::ExpressExit => {
+                let min_bond = U256::from(self.config.express_lane.min_bond_wei);
+                let bond = event.bond.unwrap_or_default();
+                if bond >= min_bond {
+                    (ForcedEventType::ForcedExit, Some(bond))
+                } else {
+                    warn!("{} bond {} below minimum {}, using normal lane", kind, bond, min_bond);
+                    (ForcedEventType::ForcedExit, None)
+                }
+            }
+        };
+        
         // Create a ForcedTransaction
         let forced_tx = ForcedTransaction {
             tx_hash: log.transaction_hash.unwrap_or_default(),
@@ -216,20 +697,74 @@ impl L1Listener {
             to: event.to,
             value: event.value,
             nonce: 0, // Nonce will be assigned during batch creation based on current state
-            gas_limit: 21000, // Standard gas limit for L1 transfers (forced exits)
+            // Messages carry their own; deposits and forced exits are plain transfers
+            gas_limit: event.gas_limit.unwrap_or(21000),
             l1_tx_hash: log.transaction_hash.unwrap_or_default(),
             l1_block_number: log.block_number.unwrap_or_default().as_u64(),
-            event_type: ForcedEventType::ForcedExit,
+            l1_log_index: log.log_index.unwrap_or_default().as_u64(),
+            event_type,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            bond,
+            data: event.data,
         };
         
-        // Add to forced queue
-        self.forced_queue.add(forced_tx).await;
-        info!("Added ForcedExit to forced queue");
+        self.enqueue(forced_tx, &kind).await;
         
         Ok(())
     }
-}
\ No newline at end of file
+    
+    /// Handle a SenderApproved or SenderRevoked event from the allowlist registry
+    /// 
+    /// A log removed by a reorg is undone: its approval is revoked, or its
+    /// revocation approved again.
+    async fn handle_allowlist_event(&self, log: Log) -> anyhow::Result<()> {
+        debug!("Received allowlist event: {:?}", log);
+        let Some(tx_pool) = &self.tx_pool else {
+            return Ok(());
+        };
+        
+        let reorged = log.removed == Some(true);
+        let (sender, approve) = match parse_log::<AllowlistRegistryEvents>(log)? {
+            AllowlistRegistryEvents::SenderApprovedFilter(event) => (event.sender, !reorged),
+            AllowlistRegistryEvents::SenderRevokedFilter(event) => (event.sender, reorged),
+        };
+        
+        if approve {
+            tx_pool.approve(sender).await;
+            info!("Allowlisted sender {:?} (L1 registry)", sender);
+        } else {
+            tx_pool.revoke(&sender).await;
+            info!("Removed sender {:?} from the allowlist (L1 registry)", sender);
+        }
+        Ok(())
+    }
+    
+    /// Handle a Staked or Unstaked event from the stake registry
+    /// 
+    /// A log removed by a reorg is undone: its stake is withdrawn again, or
+    /// its withdrawal restaked.
+    async fn handle_stake_event(&self, log: Log) -> anyhow::Result<()> {
+        debug!("Received stake event: {:?}", log);
+        let Some(state_cache) = &self.state_cache else {
+            return Ok(());
+        };
+        
+        let reorged = log.removed == Some(true);
+        let (sender, amount, staked) = match parse_log::<StakeRegistryEvents>(log)? {
+            StakeRegistryEvents::StakedFilter(event) => (event.sender, event.amount, !reorged),
+            StakeRegistryEvents::UnstakedFilter(event) => (event.sender, event.amount, reorged),
+        };
+        
+        if staked {
+            state_cache.add_stake(sender, amount).await;
+            info!("Sender {:?} staked {} (L1 registry)", sender, amount);
+        } else {
+            state_cache.remove_stake(&sender, amount).await;
+            info!("Sender {:?} unstaked {} (L1 registry)", sender, amount);
+        }
+        Ok(())
+    }
+}
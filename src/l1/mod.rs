@@ -1,9 +1,64 @@
 //! Layer 1 Integration Module
 //! 
 //! This module handles integration with the Ethereum L1 blockchain:
-//! - Monitors the bridge contract for forced transaction events
+//! - Monitors the bridge contracts for forced transaction events, decoding
+//!   each contract's events by its configured mapping
 //! - Detects deposits and forced exits from L1
 //! - Ensures censorship resistance
+//! - Reads the L1 head timestamp for batch timestamps (optional)
+//! - Holds events until they are deep enough to survive shallow reorgs (optional)
+//! - Detects L1 reorgs from block hashes, rolling back and re-scanning events
+//! - Backfills bridge events from the start block in paced, paginated scans,
+//!   reporting progress
+//! - Fails over between L1 endpoints, with exponential backoff
+//! - Commits sealed batches to the L1 rollup contract (optional), with their
+//!   data in calldata or EIP-4844 blobs
+//! - Tracks L1 gas prices to estimate batch posting costs (optional)
+//! - Holds sealed batches until L1 gas gets cheaper, within a maximum delay
+//!   and the forced-inclusion deadlines (optional)
+//! - Follows the L1 `safe` and `finalized` tags, finalizing committed batches
+//!   (optional)
+//! - Monitors the rollup contract for batch verifications (optional)
+//! - Watches the rollup contract's escape hatch, switching to safe mode while
+//!   it is open or commitments keep failing (optional)
+//! - Spawns a local Anvil devnet with a mock bridge (`--devnet`)
+//! - Manages the sequencer's L1 account: its key, nonces, and replacement of
+//!   stuck transactions
 
+mod backfill;
+mod blobs;
+mod bridges;
+mod confirmations;
+mod devnet;
+mod escape_hatch;
+mod failover;
+mod finality;
+mod gas_oracle;
 mod listener;
-pub use listener::L1Listener;
\ No newline at end of file
+mod posting;
+mod reorg;
+mod submitter;
+mod verification;
+mod wallet;
+mod head;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub use backfill::{RequestPacer, ScanProgress};
+pub use blobs::{blob_base_fee, BlobSidecar, BlobTransaction, BLOB_DATA_CAPACITY, GAS_PER_BLOB};
+pub use bridges::{BridgeEvent, Bridges};
+pub use confirmations::ProvisionalEvents;
+pub use devnet::{mock_bridge_init_code, mock_bridge_runtime, Devnet};
+pub use escape_hatch::EscapeHatchMonitor;
+pub use failover::Backoff;
+pub use finality::FinalityTracker;
+pub use gas_oracle::{L1Fees, L1GasOracle, PostingCost};
+pub use listener::L1Listener;
+pub use posting::PostingScheduler;
+pub use reorg::BlockTracker;
+pub use submitter::BatchSubmitter;
+pub use verification::VerificationMonitor;
+pub use wallet::{L1Transaction, L1Wallet, TxFees};
+pub use head::L1HeadClock;
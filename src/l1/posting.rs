@@ -0,0 +1,81 @@
+//! Batch Posting Scheduler Module
+//!
+//! Sealing a batch and posting it to L1 need not happen at once. The posting
+//! scheduler lets the submitter hold a sealed batch while L1 gas is
+//! expensive, so its data availability cost falls with the fees, up to a
+//! maximum delay. Later batches wait behind it, as batches are committed in
+//! order.
+//!
+//! Holding a batch never costs a forced transaction its inclusion: batches
+//! carrying an express exit are posted right away, and so are batches whose
+//! forced transactions near their `l1.forced_inclusion` block deadline. While
+//! the fees are unknown, batches are posted as usual.
+
+use super::gas_oracle::L1GasOracle;
+use crate::batch::SealedBatch;
+use crate::config::{ForcedInclusionConfig, PostingConfig};
+use crate::Transaction;
+use ethers::types::U256;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Decides when sealed batches are posted
+pub struct PostingScheduler {
+    /// Fee thresholds and delays
+    config: PostingConfig,
+    /// Deadlines of the forced transactions in the batches
+    deadlines: ForcedInclusionConfig,
+    /// Source of the current L1 fees
+    oracle: Arc<L1GasOracle>,
+}
+
+impl PostingScheduler {
+    /// Creates a scheduler reading the fees from `oracle`
+    ///
+    /// # Arguments
+    /// * `config` - Fee thresholds and delays
+    /// * `deadlines` - Forced-inclusion deadlines the scheduler must keep
+    /// * `oracle` - Source of the current L1 fees
+    pub fn new(config: PostingConfig, deadlines: ForcedInclusionConfig, oracle: Arc<L1GasOracle>) -> Self {
+        Self { config, deadlines, oracle }
+    }
+
+    /// Interval between checks of a held batch
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_millis(self.config.check_interval_ms)
+    }
+
+    /// Whether a sealed batch, already `held_for` a while, should keep waiting
+    pub fn should_wait(&self, sealed: &SealedBatch, held_for: Duration) -> bool {
+        if held_for >= Duration::from_millis(self.config.max_delay_ms) {
+            return false;
+        }
+        let forced: Vec<_> = sealed.batch.transactions
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Forced(tx) => Some(tx),
+                _ => None,
+            })
+            .collect();
+        if forced.iter().any(|tx| tx.is_express()) {
+            return false;
+        }
+        let Some(fees) = self.oracle.fees() else {
+            return false;
+        };
+        if let Some(deadline) = self.deadlines.deadline_blocks
+            && forced.iter().any(|tx| fees.block_number + self.deadlines.margin_blocks >= tx.l1_block_number + deadline)
+        {
+            return false;
+        }
+
+        if fees.base_fee > U256::from(self.config.max_base_fee_wei) {
+            return true;
+        }
+        let in_blobs = self.oracle.estimate(sealed.data.len()).is_some_and(|cost| cost.blobs > 0);
+        match (in_blobs, fees.blob_base_fee, self.config.max_blob_base_fee_wei) {
+            (true, Some(blob_base_fee), Some(max)) => blob_base_fee > U256::from(max),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Reorg Detection Module
+//!
+//! Remembers the hashes of the most recent L1 heads, so a head that does not
+//! build on them reveals a reorg even when the node never reports the removed
+//! logs (e.g. while the listener was reconnecting). The listener then walks
+//! back to the last block whose hash the canonical chain still agrees with,
+//! drops the events queued from the blocks above it, and re-scans them.
+//!
+//! Only the last `l1.max_reorg_depth` blocks are remembered. If even the
+//! oldest one was replaced, the listener rolls back `l1.max_reorg_depth`
+//! blocks further. After a restart, only the block of the persisted scan
+//! cursor is remembered.
+
+use ethers::types::H256;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Hashes of recent L1 heads, by block number
+pub struct BlockTracker {
+    blocks: Mutex<BTreeMap<u64, H256>>,
+    /// Most blocks remembered
+    capacity: usize,
+}
+
+impl BlockTracker {
+    /// Creates a tracker remembering the last `capacity` blocks (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            blocks: Mutex::new(BTreeMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record a new head, unless it contradicts the blocks remembered
+    ///
+    /// A head contradicts them if its parent hash differs from the hash
+    /// remembered for its parent, or another hash was remembered at its height.
+    ///
+    /// # Returns
+    /// `false` if the head contradicts them (it is not recorded then)
+    pub fn observe(&self, number: u64, hash: H256, parent_hash: H256) -> bool {
+        let mut blocks = self.blocks.lock().unwrap();
+        let parent_differs = number
+            .checked_sub(1)
+            .and_then(|parent| blocks.get(&parent))
+            .is_some_and(|&known| known != parent_hash);
+        let replaced = blocks.get(&number).is_some_and(|&known| known != hash);
+        if parent_differs || replaced {
+            return false;
+        }
+        blocks.insert(number, hash);
+        while blocks.len() > self.capacity {
+            blocks.pop_first();
+        }
+        true
+    }
+
+    /// Hash remembered for block `number`
+    pub fn hash(&self, number: u64) -> Option<H256> {
+        self.blocks.lock().unwrap().get(&number).copied()
+    }
+
+    /// Remembered block numbers below `number`, highest first
+    pub fn below(&self, number: u64) -> Vec<u64> {
+        self.blocks.lock().unwrap().range(..number).rev().map(|(&number, _)| number).collect()
+    }
+
+    /// Newest block remembered
+    pub fn newest(&self) -> Option<u64> {
+        self.blocks.lock().unwrap().keys().next_back().copied()
+    }
+
+    /// Oldest block remembered
+    pub fn oldest(&self) -> Option<u64> {
+        self.blocks.lock().unwrap().keys().next().copied()
+    }
+
+    /// Forget the blocks above `number`, which were reorged out
+    pub fn rewind(&self, number: u64) {
+        self.blocks.lock().unwrap().split_off(&number.saturating_add(1));
+    }
+}
@@ -0,0 +1,327 @@
+//! L1 Batch Submitter Module
+//!
+//! Commits every sealed batch to the L1 rollup contract. Batches arrive from
+//! the orchestrator in the order they were sealed and are submitted one at a
+//! time, each in a `commitBatch` call carrying the batch's ID, the state roots
+//! before and after it, the Merkle root of its withdrawals (which forced exits
+//! are claimed against), its hash and its data: its JSON encoding, compressed
+//! with `l1.submitter.compression` if set. The call is sent from the
+//! sequencer's L1 wallet (see the wallet module), which replaces it with
+//! higher fees while it is stuck, through the first healthy endpoint of
+//! `l1.rpc_url` and `l1.fallback_rpc_urls`.
+//!
+//! # Lifecycle
+//! The registry records where each batch is: `Sealed` by the orchestrator,
+//! `Submitted` once the commitment is sent (with its L1 transaction hash,
+//! updated by each replacement),
+//! then `Committed` once it is mined and `l1.submitter.confirmations` deep
+//! (with its L1 block), and `Finalized` once the finality tracker sees that
+//! block finalized (see the finality module). A commitment that could not be sent or confirmed is
+//! retried with backoff, up to `l1.submitter.max_attempts` times. If it still
+//! fails, or the contract reverts it, the batch is `Failed`: its transactions
+//! are reinjected, along with those of every later batch awaiting submission,
+//! as those build on its state.
+//!
+//! A retry after a commitment was sent but not confirmed replaces it, as the
+//! wallet reuses its nonce. Only if another transaction took that nonce is
+//! the commitment sent afresh; the rollup contract is expected to reject a
+//! batch ID it has already committed.
+//!
+//! With a posting scheduler attached, each batch may first be held while L1
+//! gas is expensive (see the posting module).
+//!
+//! Every attempt's outcome is reported to the safe mode, if attached, which
+//! takes over once commitments have failed for too long.
+//!
+//! # Blob Data
+//! With `l1.submitter.data_availability = "blobs"`, the batch data goes into
+//! EIP-4844 blobs instead (see the blobs module), attached to a
+//! `commitBlobBatch` call that takes no data: the contract reads the blobs'
+//! versioned hashes with `BLOBHASH`. The blob gas price offered is twice the
+//! blob base fee at the head, so the commitment stays includable if the fee
+//! rises for a few blocks, capped at `max_fee_per_blob_gas_wei`. While the
+//! blob base fee is above that cap, or a batch needs more than
+//! `max_blobs_per_tx` blobs, the batch is posted as calldata.
+
+use super::blobs::{blob_base_fee, BlobSidecar};
+use super::failover::{connect_first_healthy, Backoff};
+use super::posting::PostingScheduler;
+use super::wallet::{L1Transaction, L1Wallet};
+use crate::batch::{BatchOrchestrator, SafeMode, SealedBatch};
+use crate::config::{DataAvailability, L1Config, SubmitterConfig};
+use crate::registry::Registry;
+use crate::types::{BatchStatus, BatchSubmission};
+use ethers::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+// Rollup contract entry point for batch commitments
+abigen!(
+    RollupContract,
+    r#"[
+        function commitBatch(uint256 batchId, bytes32 prevStateRoot, bytes32 stateRoot, bytes32 withdrawalRoot, bytes32 batchHash, bytes data)
+        function commitBlobBatch(uint256 batchId, bytes32 prevStateRoot, bytes32 stateRoot, bytes32 withdrawalRoot, bytes32 batchHash)
+    ]"#,
+);
+
+/// L1 batch submitter
+///
+/// Posts sealed batches to the rollup contract and tracks their commitments.
+pub struct BatchSubmitter {
+    /// L1 connection configuration (endpoints and retry backoff)
+    l1: L1Config,
+    /// Submission configuration (contract, confirmations, attempts)
+    config: SubmitterConfig,
+    /// Address of the rollup contract
+    rollup_address: Address,
+    /// Sequencer's L1 account, signing the commitments
+    wallet: L1Wallet,
+    /// Where batch statuses are recorded
+    registry: Arc<Registry>,
+    /// Orchestrator taking back the transactions of failed batches
+    orchestrator: Arc<BatchOrchestrator>,
+    /// Safe mode told of failing commitments (none if `None`)
+    safe_mode: Option<Arc<SafeMode>>,
+    /// Holds batches while L1 gas is expensive (posted right away if `None`)
+    posting: Option<PostingScheduler>,
+}
+
+/// Outcome of one attempt to commit a batch
+enum Attempt {
+    /// Mined and confirmed in the given L1 block
+    Committed(H256, u64),
+    /// Mined, but the contract reverted it
+    Reverted(H256),
+}
+
+impl BatchSubmitter {
+    /// Creates a new batch submitter
+    ///
+    /// # Arguments
+    /// * `l1` - L1 connection configuration
+    /// * `config` - Submission configuration
+    /// * `registry` - Registry recording batch statuses
+    /// * `orchestrator` - Orchestrator the batches come from
+    ///
+    /// # Returns
+    /// An error if the rollup address is invalid, or the L1 key cannot be loaded
+    pub fn new(
+        l1: L1Config,
+        config: SubmitterConfig,
+        registry: Arc<Registry>,
+        orchestrator: Arc<BatchOrchestrator>,
+    ) -> anyhow::Result<Self> {
+        let rollup_address = config.rollup_address.parse()?;
+        let wallet = L1Wallet::load(&l1.wallet, config.signing_key, config.chain_id)?;
+        Ok(Self { l1, config, rollup_address, wallet, registry, orchestrator, safe_mode: None, posting: None })
+    }
+    
+    /// Tell `safe_mode` whether commitments succeed
+    pub fn with_safe_mode(mut self, safe_mode: Arc<SafeMode>) -> Self {
+        self.safe_mode = Some(safe_mode);
+        self
+    }
+
+    /// Hold each batch until `posting` lets it through
+    pub fn with_posting_scheduler(mut self, posting: PostingScheduler) -> Self {
+        self.posting = Some(posting);
+        self
+    }
+    
+    /// Address of the sequencer's L1 account
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    /// Submit the batches sealed into `batches`, in order, until the orchestrator stops
+    pub async fn start(self, mut batches: mpsc::UnboundedReceiver<SealedBatch>) {
+        info!("Submitting batches to rollup contract {:?} from {:?}", self.rollup_address, self.address());
+        while let Some(sealed) = batches.recv().await {
+            self.hold(&sealed).await;
+            if self.submit(&sealed).await {
+                continue;
+            }
+            let failed = self.orchestrator.reinject_failed(sealed.batch, &mut batches).await;
+            for batch_id in failed {
+                self.record(batch_id, BatchStatus::Failed, None, None).await;
+            }
+        }
+        warn!("Batch orchestrator stopped, no more batches to submit");
+    }
+
+    /// Wait until the posting scheduler lets a batch through
+    async fn hold(&self, sealed: &SealedBatch) {
+        let Some(posting) = &self.posting else {
+            return;
+        };
+        let held_since = tokio::time::Instant::now();
+        if !posting.should_wait(sealed, Duration::ZERO) {
+            return;
+        }
+        info!("Holding batch #{} until L1 gas gets cheaper", sealed.batch.batch_id);
+        while posting.should_wait(sealed, held_since.elapsed()) {
+            tokio::time::sleep(posting.check_interval()).await;
+        }
+        info!("Posting batch #{} after holding it for {:?}", sealed.batch.batch_id, held_since.elapsed());
+    }
+    
+    /// Commit a batch, retrying failed attempts with backoff
+    ///
+    /// # Returns
+    /// `true` once the batch is committed, `false` if it failed
+    async fn submit(&self, sealed: &SealedBatch) -> bool {
+        let batch_id = sealed.batch.batch_id;
+        let mut backoff = Backoff::new(
+            Duration::from_millis(self.l1.retry_initial_ms),
+            Duration::from_millis(self.l1.retry_max_ms),
+        );
+        for attempt in 1..=self.config.max_attempts.max(1) {
+            let outcome = self.commit(sealed).await;
+            if let Some(safe_mode) = &self.safe_mode {
+                match outcome {
+                    Ok(Attempt::Committed(..)) => safe_mode.submission_succeeded(),
+                    _ => safe_mode.submission_failed(),
+                }
+            }
+            match outcome {
+                Ok(Attempt::Committed(tx_hash, block_number)) => {
+                    info!("Batch #{} committed to L1 in block {} ({:?})", batch_id, block_number, tx_hash);
+                    self.record(batch_id, BatchStatus::Committed, Some(tx_hash), Some(block_number)).await;
+                    return true;
+                }
+                Ok(Attempt::Reverted(tx_hash)) => {
+                    error!("Commitment of batch #{} was reverted ({:?})", batch_id, tx_hash);
+                    return false;
+                }
+                Err(e) => {
+                    warn!("Attempt {} to commit batch #{} failed: {:?}", attempt, batch_id, e);
+                    if attempt < self.config.max_attempts {
+                        tokio::time::sleep(backoff.next_delay()).await;
+                    }
+                }
+            }
+        }
+        error!("Giving up on batch #{} after {} attempts", batch_id, self.config.max_attempts.max(1));
+        false
+    }
+
+    /// Send a batch's commitment and wait until it is confirmed
+    async fn commit(&self, sealed: &SealedBatch) -> anyhow::Result<Attempt> {
+        let batch = &sealed.batch;
+        let (provider, _) = connect_first_healthy(&self.l1.rpc_urls()).await?;
+        let rollup = RollupContract::new(self.rollup_address, Arc::new(provider.clone()));
+        let data = sealed.data.clone();
+
+        let blobs = match self.config.data_availability {
+            DataAvailability::Calldata => None,
+            DataAvailability::Blobs => self.blob_commitment(&provider, &data).await?,
+        };
+        let tx = match blobs {
+            Some((sidecar, max_fee_per_blob_gas)) => {
+                let call = rollup.commit_blob_batch(
+                    U256::from(batch.batch_id),
+                    batch.prev_state_root.0,
+                    sealed.state_root.0,
+                    sealed.withdrawal_root.0,
+                    batch.hash().0,
+                );
+                info!("Posting batch #{} in {} blobs", batch.batch_id, sidecar.len());
+                L1Transaction::Blob {
+                    to: self.rollup_address,
+                    data: call.calldata().ok_or_else(|| anyhow::anyhow!("commitBlobBatch has no calldata"))?,
+                    gas_limit: self.config.blob_commit_gas_limit,
+                    sidecar,
+                    max_fee_per_blob_gas,
+                    blob_fee_cap: self.config.max_fee_per_blob_gas_wei.map(U256::from),
+                }
+            }
+            None => {
+                let call = rollup.commit_batch(
+                    U256::from(batch.batch_id),
+                    batch.prev_state_root.0,
+                    sealed.state_root.0,
+                    sealed.withdrawal_root.0,
+                    batch.hash().0,
+                    Bytes::from(data),
+                );
+                L1Transaction::Call {
+                    to: self.rollup_address,
+                    data: call.calldata().ok_or_else(|| anyhow::anyhow!("commitBatch has no calldata"))?,
+                }
+            }
+        };
+
+        let on_sent = |tx_hash| {
+            info!("Submitted batch #{} to L1 ({:?})", batch.batch_id, tx_hash);
+            self.record(batch.batch_id, BatchStatus::Submitted, Some(tx_hash), None)
+        };
+        let receipt = self.wallet.send(&provider, &tx, self.config.confirmations, on_sent).await?;
+        let tx_hash = receipt.transaction_hash;
+        if receipt.status != Some(1.into()) {
+            return Ok(Attempt::Reverted(tx_hash));
+        }
+        let block_number = receipt
+            .block_number
+            .ok_or_else(|| anyhow::anyhow!("receipt of {:?} has no block", tx_hash))?;
+        Ok(Attempt::Committed(tx_hash, block_number.as_u64()))
+    }
+
+    /// Blobs carrying `data`, and the blob gas price to offer for them
+    ///
+    /// # Returns
+    /// `None` if the data should go into calldata instead: the blob base fee
+    /// is above the configured cap, or the data needs too many blobs
+    async fn blob_commitment<M: Middleware + 'static>(
+        &self,
+        client: &M,
+        data: &[u8],
+    ) -> anyhow::Result<Option<(BlobSidecar, U256)>> {
+        let head = client
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to read the L1 head: {}", e))?
+            .ok_or_else(|| anyhow::anyhow!("L1 has no head block"))?;
+        let excess_blob_gas = head
+            .excess_blob_gas
+            .ok_or_else(|| anyhow::anyhow!("L1 has no blob fee market"))?;
+        let base_fee = blob_base_fee(excess_blob_gas, self.config.blob_base_fee_update_fraction);
+        let mut max_fee = base_fee.saturating_mul(U256::from(2));
+        if let Some(cap) = self.config.max_fee_per_blob_gas_wei.map(U256::from) {
+            if base_fee > cap {
+                warn!("Blob base fee {} wei is above the {} wei cap, posting as calldata", base_fee, cap);
+                return Ok(None);
+            }
+            max_fee = max_fee.min(cap);
+        }
+
+        let (data, max_blobs) = (data.to_vec(), self.config.max_blobs_per_tx);
+        match tokio::task::spawn_blocking(move || BlobSidecar::new(&data, max_blobs)).await? {
+            Ok(sidecar) => Ok(Some((sidecar, max_fee))),
+            Err(e) => {
+                warn!("Cannot post batch data in blobs ({}), posting as calldata", e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record the status of a batch, logging failures
+    async fn record(&self, batch_id: u64, status: BatchStatus, l1_tx_hash: Option<H256>, l1_block_number: Option<u64>) {
+        let submission = BatchSubmission {
+            batch_id,
+            status,
+            l1_tx_hash,
+            l1_block_number,
+            verified_tx_hash: None,
+            verified_block_number: None,
+            updated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        if let Err(e) = self.registry.set_batch_status(&submission).await {
+            warn!("Failed to record the status of batch #{}: {:?}", batch_id, e);
+        }
+    }
+}
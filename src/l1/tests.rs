@@ -0,0 +1,619 @@
+//! Tests for L1 integration
+//!
+//! Verifies:
+//! - Provisional events are confirmed once deep enough, in L1 order, and
+//!   dropped if reorged out first
+//! - Heads not building on the remembered blocks are detected as reorgs
+//! - Reconnections back off exponentially
+//! - A dropped subscription falls back to HTTP polling until it reconnects
+//! - Batch data round-trips through blobs priced by the blob fee market
+//! - Posting costs are estimated in calldata or blobs, as the submitter posts
+//! - The L1 wallet loads its key from each source, and raises the fees of
+//!   stuck transactions enough for replacement, within the caps
+//! - Each configured bridge's events decode by their own mappings, messages
+//!   with their calldata and gas limit
+//! - The devnet's mock bridge deploys code emitting the native deposit event
+//! - Scans of past blocks estimate the time they have left
+//! - Sealed batches are held while L1 gas is expensive, but not past the
+//!   maximum delay or a forced transaction's deadline
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        batch::{PostingCosts, SealedBatch},
+        config::{BridgeEventKind, ForcedInclusionConfig, GasOracleConfig, L1Config, PostingConfig, WalletConfig},
+        l1::{
+            blob_base_fee, mock_bridge_init_code, mock_bridge_runtime, Backoff, BlobSidecar, BlobTransaction,
+            BlockTracker, Bridges, L1GasOracle, L1Listener, L1Wallet, PostingScheduler, ProvisionalEvents, ScanProgress, TxFees,
+            BLOB_DATA_CAPACITY, GAS_PER_BLOB,
+        },
+        pool::ForcedQueue,
+        Batch, ForcedEventType, ForcedTransaction, Transaction,
+    };
+    use axum::{
+        extract::{ws::Message, State, WebSocketUpgrade},
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::get,
+        Json, Router,
+    };
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Address, Block, Bytes, Log, H256, U256, U64};
+    use ethers::utils::rlp::Rlp;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::watch;
+
+    fn deposit(l1_tx: u64, block: u64) -> ForcedTransaction {
+        ForcedTransaction {
+            tx_hash: H256::from_low_u64_be(l1_tx),
+            from: Address::zero(),
+            to: Address::zero(),
+            value: U256::from(1000),
+            nonce: 0,
+            gas_limit: 21000,
+            l1_tx_hash: H256::from_low_u64_be(l1_tx),
+            l1_block_number: block,
+            l1_log_index: 0,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        }
+    }
+
+    #[test]
+    fn test_provisional_events_are_confirmed_once_deep_enough() {
+        let provisional = ProvisionalEvents::new(3);
+        assert!(provisional.add(deposit(2, 11)));
+        assert!(provisional.add(deposit(1, 10)));
+        assert!(provisional.add(deposit(3, 12)));
+        assert!(!provisional.add(deposit(1, 10)));
+
+        // Shallower than 3 blocks: nothing is confirmed yet
+        assert!(provisional.confirm(12).is_empty());
+
+        // Reorged out while provisional: dropped before ever being queued
+        assert!(provisional.remove_reorged(11, H256::from_low_u64_be(1)).is_empty());
+        assert_eq!(provisional.remove_reorged(11, H256::from_low_u64_be(2)).len(), 1);
+
+        // Confirmed in L1 order, once their block is 3 deep
+        let confirmed = provisional.confirm(14);
+        assert_eq!(confirmed.iter().map(|tx| tx.l1_block_number).collect::<Vec<_>>(), vec![10]);
+        assert_eq!(provisional.len(), 1);
+        assert_eq!(provisional.confirm(20).len(), 1);
+        assert!(provisional.is_empty());
+
+        // A reorg replacing every block above 10 drops what they held
+        provisional.add(deposit(4, 10));
+        provisional.add(deposit(5, 11));
+        assert_eq!(provisional.remove_after(10).len(), 1);
+        assert_eq!(provisional.len(), 1);
+    }
+
+    #[test]
+    fn test_heads_off_the_remembered_chain_reveal_reorgs() {
+        let hash = |n: u64| H256::from_low_u64_be(n);
+        let tracker = BlockTracker::new(3);
+        for number in 1..=5 {
+            assert!(tracker.observe(number, hash(number), hash(number - 1)));
+        }
+        // Only the last 3 blocks are remembered
+        assert_eq!(tracker.oldest(), Some(3));
+        assert_eq!(tracker.below(5), vec![4, 3]);
+
+        // A head on another parent, or replacing a known block, is a reorg
+        assert!(!tracker.observe(6, hash(60), hash(50)));
+        assert!(!tracker.observe(5, hash(50), hash(4)));
+        assert_eq!(tracker.hash(5), Some(hash(5)));
+
+        // Once rewound to the fork point, the new chain is followed
+        tracker.rewind(4);
+        assert!(tracker.observe(5, hash(50), hash(4)));
+        assert!(tracker.observe(6, hash(60), hash(50)));
+        assert_eq!(tracker.hash(5), Some(hash(50)));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_maximum_and_resets() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<_> = (0..4).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5]);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_blobs_carry_the_data_under_valid_commitments() {
+        // Just over one blob's worth, so it spills into a second blob
+        let data: Vec<u8> = (0..BLOB_DATA_CAPACITY).map(|i| (i % 251) as u8).collect();
+        let sidecar = BlobSidecar::new(&data, 6).unwrap();
+        assert_eq!(sidecar.len(), 2);
+        assert_eq!(sidecar.data().unwrap(), data);
+        assert!(sidecar.verify().unwrap());
+        assert!(sidecar.versioned_hashes().iter().all(|hash| hash[0] == 0x01));
+
+        // More blobs than allowed
+        assert!(BlobSidecar::new(&data, 1).is_err());
+    }
+
+    #[test]
+    fn test_blob_transactions_are_signed_over_their_fields_only() {
+        let wallet: LocalWallet = "0x0000000000000000000000000000000000000000000000000000000000000001".parse().unwrap();
+        let sidecar = BlobSidecar::new(b"batch", 6).unwrap();
+        let tx = BlobTransaction {
+            chain_id: 1,
+            nonce: U256::from(7),
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(30_000_000_000u64),
+            gas_limit: 200_000,
+            to: Address::from_low_u64_be(0xabc),
+            data: Bytes::from(vec![1, 2, 3]),
+            max_fee_per_blob_gas: U256::from(2),
+            blob_versioned_hashes: sidecar.versioned_hashes(),
+        };
+        let signature = wallet.sign_hash(tx.sighash()).unwrap();
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), wallet.address());
+
+        // The network form wraps the signed transaction with the sidecar,
+        // and the transaction hash covers the former only
+        let encoded = tx.network_encoding(&signature, &sidecar);
+        assert_eq!(encoded[0], 0x03);
+        let wrapper = Rlp::new(&encoded[1..]);
+        assert_eq!(wrapper.item_count().unwrap(), 4);
+        let signed = wrapper.at(0).unwrap();
+        assert_eq!(signed.item_count().unwrap(), 14);
+        let mut typed = vec![0x03];
+        typed.extend_from_slice(signed.as_raw());
+        assert_eq!(tx.hash(&signature), H256(ethers::utils::keccak256(typed)));
+    }
+
+    #[test]
+    fn test_blob_base_fee_grows_exponentially_with_excess_blob_gas() {
+        let fraction = 5_007_716;
+        assert_eq!(blob_base_fee(U256::zero(), fraction), U256::one());
+        assert_eq!(blob_base_fee(U256::from(fraction), fraction), U256::from(2));
+        assert_eq!(blob_base_fee(U256::from(fraction * 10), fraction), U256::from(22_026));
+        assert_eq!(blob_base_fee(U256::MAX, fraction), U256::MAX);
+    }
+
+    /// L1 configuration with the native bridge at 0x..01, and the given TOML appended
+    fn l1_config(extra: &str) -> L1Config {
+        toml::from_str(&format!(
+            r#"
+            rpc_url = "ws://localhost:8546"
+            bridge_address = "0x0000000000000000000000000000000000000001"
+            start_block = 0
+            {}
+            "#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    /// Log emitted by `address`, with `topics` after the signature of `event` and `data` as amounts
+    fn bridge_log(address: u64, event: &str, topics: &[Address], data: &[u64]) -> ethers::types::Log {
+        ethers::types::Log {
+            address: Address::from_low_u64_be(address),
+            topics: std::iter::once(H256::from(ethers::utils::keccak256(event)))
+                .chain(topics.iter().map(|&topic| H256::from(topic)))
+                .collect(),
+            data: ethers::abi::encode(&data.iter().map(|&amount| ethers::abi::Token::Uint(amount.into())).collect::<Vec<_>>()).into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bridge_events_decode_by_their_bridge_mapping() {
+        let config = l1_config(
+            r#"
+            [[bridges]]
+            name = "erc20_gateway"
+            address = "0x0000000000000000000000000000000000000002"
+            [[bridges.events]]
+            signature = "event TokenDeposited(address indexed token, address indexed sender, address indexed recipient, uint256 amount)"
+            kind = "deposit"
+            from = "sender"
+            to = "recipient"
+            value = "amount"
+            "#,
+        );
+        let bridges = Bridges::new(&config).unwrap();
+        let (token, alice, bob) = (Address::from_low_u64_be(7), Address::from_low_u64_be(8), Address::from_low_u64_be(9));
+
+        let native = bridges
+            .decode(&bridge_log(1, "ForcedExit(address,address,uint256)", &[alice, bob], &[500]))
+            .unwrap()
+            .unwrap();
+        assert_eq!((native.bridge.as_str(), native.kind), ("native", BridgeEventKind::ForcedExit));
+        assert_eq!((native.from, native.to, native.value), (alice, bob, U256::from(500)));
+
+        let gateway = bridges
+            .decode(&bridge_log(2, "TokenDeposited(address,address,address,uint256)", &[token, alice, bob], &[42]))
+            .unwrap()
+            .unwrap();
+        assert_eq!((gateway.bridge.as_str(), gateway.kind), ("erc20_gateway", BridgeEventKind::Deposit));
+        assert_eq!((gateway.from, gateway.to, gateway.value, gateway.bond), (alice, bob, U256::from(42), None));
+
+        // Events are only decoded from the bridge declaring them, and express
+        // exits only with the express lane enabled
+        let misplaced = bridge_log(2, "ForcedExit(address,address,uint256)", &[alice, bob], &[500]);
+        assert!(bridges.decode(&misplaced).unwrap().is_none());
+        let express = bridge_log(1, "ExpressExit(address,address,uint256,uint256)", &[alice, bob], &[500, 10]);
+        assert!(bridges.decode(&express).unwrap().is_none());
+
+        let config = l1_config("[express_lane]\nenabled = true");
+        let exit = Bridges::new(&config).unwrap().decode(&express).unwrap().unwrap();
+        assert_eq!((exit.kind, exit.bond), (BridgeEventKind::ExpressExit, Some(U256::from(10))));
+    }
+
+    #[test]
+    fn test_native_bridge_messages_carry_calldata() {
+        let bridges = Bridges::new(&l1_config("")).unwrap();
+        let (sender, target) = (Address::from_low_u64_be(8), Address::from_low_u64_be(9));
+        let calldata = vec![0xa9, 0x05, 0x9c, 0xbb, 0x01];
+        let mut log = bridge_log(1, "Message(address,address,uint256,uint256,bytes)", &[sender, target], &[]);
+        log.data = ethers::abi::encode(&[
+            ethers::abi::Token::Uint(7.into()),
+            ethers::abi::Token::Uint(100_000.into()),
+            ethers::abi::Token::Bytes(calldata.clone()),
+        ])
+        .into();
+
+        let message = bridges.decode(&log).unwrap().unwrap();
+        assert_eq!((message.kind, message.from, message.to), (BridgeEventKind::Message, sender, target));
+        assert_eq!((message.value, message.gas_limit), (U256::from(7), Some(100_000)));
+        assert_eq!(message.data, Bytes::from(calldata));
+
+        // Other events carry no calldata
+        let deposit = bridges
+            .decode(&bridge_log(1, "Deposit(address,address,uint256)", &[sender, target], &[5]))
+            .unwrap()
+            .unwrap();
+        assert_eq!((deposit.gas_limit, deposit.data), (None, Bytes::new()));
+    }
+
+    #[test]
+    fn test_devnet_mock_bridge_emits_native_deposits() {
+        let runtime = mock_bridge_runtime();
+        let init = mock_bridge_init_code();
+        // PUSH1 <runtime length> ... CODECOPY from the end of the init code ... RETURN
+        assert_eq!((init[0], init[1] as usize, init[4] as usize), (0x60, runtime.len(), init.len() - runtime.len()));
+        assert_eq!(&init[init.len() - runtime.len()..], runtime.as_slice());
+
+        // LOG3 with the topic of the native bridge's deposits, then STOP
+        let topic = H256::from(ethers::utils::keccak256("Deposit(address,address,uint256)"));
+        let push32 = runtime.iter().position(|&op| op == 0x7f).unwrap();
+        assert_eq!(H256::from_slice(&runtime[push32 + 1..push32 + 33]), topic);
+        assert_eq!(&runtime[push32 + 33..], &[0x60, 0x20, 0x60, 0x00, 0xa3, 0x00]);
+    }
+
+    #[test]
+    fn test_bridge_mapping_must_name_event_parameters() {
+        let config = l1_config(
+            r#"
+            [[bridges]]
+            name = "gateway"
+            address = "0x0000000000000000000000000000000000000002"
+            [[bridges.events]]
+            signature = "event TokenDeposited(address indexed sender, address indexed recipient, uint256 amount)"
+            kind = "deposit"
+            "#,
+        );
+        let error = Bridges::new(&config).err().unwrap().to_string();
+        assert!(error.contains("has no parameter from"), "{}", error);
+    }
+
+    /// Gas oracle at 10 gwei, for an L1 configured by `submitter` TOML
+    fn gas_oracle(submitter: &str) -> L1GasOracle {
+        let l1: L1Config = toml::from_str(&format!(
+            r#"
+            rpc_url = "ws://localhost:8546"
+            bridge_address = "0x0000000000000000000000000000000000000000"
+            start_block = 0
+            [gas_oracle]
+            priority_fee_wei = 0
+            bytes_per_tx = 200
+            {}
+            "#,
+            submitter
+        ))
+        .unwrap();
+        let oracle = L1GasOracle::new(l1.clone(), l1.gas_oracle.unwrap());
+        assert!(oracle.estimate(1000).is_none());
+        oracle.observe(100, U256::from(10_000_000_000u64), Some(U256::from(5_007_716)));
+        oracle
+    }
+
+    #[test]
+    fn test_posting_costs_follow_the_submitter() {
+        let gwei = |gas: u64| U256::from(gas) * U256::from(10_000_000_000u64);
+
+        // Calldata: 16 gas per byte on top of the commitment, or the 40 gas floor
+        let calldata = gas_oracle("");
+        assert_eq!(calldata.estimate(1000).unwrap().gas, 21_000 + 100_000 + 16_000);
+        let large = calldata.estimate(10_000).unwrap();
+        assert_eq!(large.gas, 21_000 + 400_000);
+        assert_eq!(large.cost_wei, gwei(421_000));
+        // Each transaction's share of a batch of 10 (2000 bytes)
+        assert_eq!(calldata.cost_per_tx(10).unwrap(), gwei(153_000) / 10);
+        assert_eq!(calldata.batch_cost(10).unwrap(), gwei(153_000));
+
+        // Blobs: the commitment plus blob gas at the blob base fee (2 wei)
+        let submitter = r#"
+            [submitter]
+            rollup_address = "0x0000000000000000000000000000000000000000"
+            signing_key = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            chain_id = 1
+            data_availability = "blobs"
+            max_fee_per_blob_gas_wei = 100
+        "#;
+        let blobs = gas_oracle(submitter);
+        let cost = blobs.estimate(200_000).unwrap();
+        assert_eq!((cost.gas, cost.blobs, cost.blob_gas), (121_000, 2, 2 * GAS_PER_BLOB));
+        assert_eq!(cost.cost_wei, gwei(121_000) + U256::from(2 * 2 * GAS_PER_BLOB));
+        // Too many blobs for one commitment: calldata
+        assert_eq!(blobs.estimate(7 * BLOB_DATA_CAPACITY).unwrap().blobs, 0);
+
+        // Above the blob fee cap (22026 wei), the submitter posts calldata
+        blobs.observe(101, U256::from(10_000_000_000u64), Some(U256::from(5_007_716 * 10)));
+        assert_eq!(blobs.fees().unwrap().blob_base_fee, Some(U256::from(22_026)));
+        assert_eq!(blobs.estimate(200_000).unwrap().blobs, 0);
+    }
+
+    #[test]
+    fn test_wallet_loads_its_key_from_the_first_source_set() {
+        let key = |n: u8| H256::from_low_u64_be(n as u64);
+        let address = |n: u8| LocalWallet::from_bytes(key(n).as_bytes()).unwrap().address();
+
+        // The configured key, then the submitter's as a fallback
+        let mut config = WalletConfig::default();
+        assert!(L1Wallet::load(&config, None, 1).is_err());
+        assert_eq!(L1Wallet::load(&config, Some(key(1)), 1).unwrap().address(), address(1));
+        config.signing_key = Some(key(2));
+        assert_eq!(L1Wallet::load(&config, Some(key(1)), 1).unwrap().address(), address(2));
+
+        // An environment variable, which must be set
+        config.signing_key_env = Some("SEQUENCER_TEST_WALLET_KEY".into());
+        assert!(L1Wallet::load(&config, None, 1).is_err());
+        // SAFETY: no other test reads or writes this variable
+        unsafe { std::env::set_var("SEQUENCER_TEST_WALLET_KEY", format!("{:?}\n", key(3))) };
+        assert_eq!(L1Wallet::load(&config, None, 1).unwrap().address(), address(3));
+
+        // An encrypted keystore, whose password comes from the environment
+        let dir = std::env::temp_dir().join(format!("sequencer-wallet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut rng = ethers::core::rand::thread_rng();
+        LocalWallet::encrypt_keystore(&dir, &mut rng, key(4).as_bytes(), "secret", Some("key.json")).unwrap();
+        config.keystore_path = Some(dir.join("key.json").to_string_lossy().into_owned());
+        assert!(L1Wallet::load(&config, None, 1).is_err());
+        config.keystore_password_env = Some("SEQUENCER_TEST_KEYSTORE_PASSWORD".into());
+        // SAFETY: no other test reads or writes this variable
+        unsafe { std::env::set_var("SEQUENCER_TEST_KEYSTORE_PASSWORD", "secret") };
+        assert_eq!(L1Wallet::load(&config, None, 1).unwrap().address(), address(4));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_replacement_fees_outbid_the_stuck_transaction_within_the_caps() {
+        let fees = |max_fee: u64, tip: u64, blob: Option<u64>| TxFees {
+            max_fee_per_gas: U256::from(max_fee),
+            max_priority_fee_per_gas: U256::from(tip),
+            max_fee_per_blob_gas: blob.map(U256::from),
+        };
+        let stuck = fees(100, 10, None);
+
+        // Raised by the bump, or to the current fees if higher
+        assert_eq!(stuck.bumped(&fees(50, 5, None), 12, None, None), Some(fees(112, 12, None)));
+        assert_eq!(stuck.bumped(&fees(300, 20, None), 12, None, None), Some(fees(300, 20, None)));
+        // Never by less than nodes accept
+        assert_eq!(stuck.bumped(&fees(50, 5, None), 1, None, None), Some(fees(110, 11, None)));
+
+        // The fee cap limits the raise, and stops it short of a valid replacement
+        assert_eq!(stuck.bumped(&fees(300, 20, None), 12, Some(U256::from(150)), None), Some(fees(150, 20, None)));
+        assert_eq!(stuck.bumped(&fees(50, 5, None), 12, Some(U256::from(105)), None), None);
+
+        // Blob fees double, within their own cap
+        let blob = fees(100, 10, Some(7));
+        assert_eq!(blob.bumped(&fees(50, 5, Some(1)), 12, None, None), Some(fees(112, 12, Some(14))));
+        assert_eq!(blob.bumped(&fees(50, 5, Some(1)), 12, None, Some(U256::from(10))), None);
+    }
+
+    #[test]
+    fn test_scan_progress_estimates_time_left() {
+        let mut progress = ScanProgress::new(100, 199, Duration::from_secs(3600));
+        assert_eq!(progress.fraction(), 0.0);
+        assert_eq!(progress.remaining(Duration::from_secs(10)), None);
+
+        progress.advance(124, 3);
+        assert_eq!(progress.fraction(), 0.25);
+        assert_eq!(progress.remaining(Duration::from_secs(10)), Some(Duration::from_secs(30)));
+        assert!(!progress.done());
+
+        progress.advance(199, 0);
+        assert_eq!(progress.fraction(), 1.0);
+        assert_eq!(progress.remaining(Duration::from_secs(40)), Some(Duration::ZERO));
+        assert!(progress.done());
+    }
+
+    #[test]
+    fn test_posting_waits_for_cheap_gas_within_bounds() {
+        let oracle = Arc::new(L1GasOracle::new(l1_config(""), GasOracleConfig::default()));
+        let posting = PostingConfig {
+            max_base_fee_wei: 20_000_000_000,
+            max_blob_base_fee_wei: None,
+            max_delay_ms: 60_000,
+            check_interval_ms: 1_000,
+        };
+        let deadlines = ForcedInclusionConfig { deadline_blocks: Some(50), margin_blocks: 2, deadline_batches: None };
+        let scheduler = PostingScheduler::new(posting, deadlines, oracle.clone());
+        let sealed = |forced: Vec<ForcedTransaction>| SealedBatch {
+            batch: Batch {
+                batch_id: 1,
+                transactions: forced.into_iter().map(Transaction::Forced).collect(),
+                prev_state_root: H256::zero(),
+                timestamp: 0,
+                lanes: None,
+            },
+            state_root: H256::zero(),
+            withdrawal_root: H256::zero(),
+            data: vec![1; 1_000],
+        };
+
+        // Posted right away while the fees are unknown
+        assert!(!scheduler.should_wait(&sealed(vec![]), Duration::ZERO));
+
+        // Held while the base fee is above the threshold, up to the maximum delay
+        oracle.observe(100, U256::from(30_000_000_000u64), None);
+        assert!(scheduler.should_wait(&sealed(vec![]), Duration::from_secs(59)));
+        assert!(!scheduler.should_wait(&sealed(vec![]), Duration::from_secs(60)));
+
+        // Never past a forced transaction's deadline (less the margin)
+        assert!(scheduler.should_wait(&sealed(vec![deposit(1, 53)]), Duration::ZERO));
+        assert!(!scheduler.should_wait(&sealed(vec![deposit(1, 52)]), Duration::ZERO));
+        let mut express = deposit(2, 99);
+        express.bond = Some(U256::from(10));
+        express.event_type = ForcedEventType::ForcedExit;
+        assert!(!scheduler.should_wait(&sealed(vec![express]), Duration::ZERO));
+
+        oracle.observe(101, U256::from(10_000_000_000u64), None);
+        assert!(!scheduler.should_wait(&sealed(vec![]), Duration::ZERO));
+    }
+
+    /// L1 node with a scripted chain, answering over HTTP and over a WebSocket
+    /// that can be taken down and brought back
+    struct FakeL1 {
+        head: AtomicU64,
+        logs: Mutex<Vec<Log>>,
+        ws_up: watch::Sender<bool>,
+        subscriptions: AtomicU64,
+        http_calls: AtomicU64,
+    }
+
+    impl FakeL1 {
+        fn new(head: u64) -> Self {
+            Self {
+                head: AtomicU64::new(head),
+                logs: Mutex::default(),
+                ws_up: watch::channel(true).0,
+                subscriptions: AtomicU64::new(0),
+                http_calls: AtomicU64::new(0),
+            }
+        }
+
+        /// Add a native deposit at a new head
+        fn deposit(&self, block: u64) {
+            let mut log = bridge_log(1, "Deposit(address,address,uint256)", &[Address::zero(), Address::zero()], &[500]);
+            log.block_number = Some(block.into());
+            log.transaction_hash = Some(H256::from_low_u64_be(block));
+            log.log_index = Some(U256::zero());
+            self.logs.lock().unwrap().push(log);
+            self.head.store(block, Ordering::SeqCst);
+        }
+
+        /// Result of a JSON-RPC call
+        fn answer(&self, request: &Value) -> Value {
+            let head = self.head.load(Ordering::SeqCst);
+            let result = match request["method"].as_str().unwrap_or_default() {
+                "eth_blockNumber" => json!(U64::from(head)),
+                "eth_getBlockByNumber" => {
+                    let number = match request["params"][0].as_str() {
+                        Some(hex) if hex.starts_with("0x") => u64::from_str_radix(&hex[2..], 16).unwrap(),
+                        _ => head,
+                    };
+                    let hash = |number: u64| H256::from_low_u64_be(number + 1);
+                    json!(Block::<H256> {
+                        number: Some(number.into()),
+                        hash: Some(hash(number)),
+                        parent_hash: number.checked_sub(1).map(hash).unwrap_or_default(),
+                        ..Default::default()
+                    })
+                }
+                "eth_getLogs" => json!(*self.logs.lock().unwrap()),
+                "eth_subscribe" => json!(U64::from(self.subscriptions.fetch_add(1, Ordering::SeqCst) + 1)),
+                _ => Value::Null,
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        }
+    }
+
+    async fn fake_l1_http(State(node): State<Arc<FakeL1>>, Json(request): Json<Value>) -> Json<Value> {
+        node.http_calls.fetch_add(1, Ordering::SeqCst);
+        Json(node.answer(&request))
+    }
+
+    async fn fake_l1_ws(State(node): State<Arc<FakeL1>>, upgrade: WebSocketUpgrade) -> Response {
+        if !*node.ws_up.borrow() {
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+        upgrade.on_upgrade(|mut socket| async move {
+            let mut ws_up = node.ws_up.subscribe();
+            loop {
+                let message = tokio::select! {
+                    message = socket.recv() => message,
+                    _ = ws_up.wait_for(|up| !*up) => return,
+                };
+                // Nothing is answered once the WebSocket is down
+                let text = match message {
+                    Some(Ok(Message::Text(text))) if *ws_up.borrow() => text,
+                    Some(Ok(_)) if *ws_up.borrow() => continue,
+                    _ => return,
+                };
+                let request: Value = serde_json::from_str(&text).unwrap();
+                if socket.send(Message::Text(node.answer(&request).to_string())).await.is_err() {
+                    return;
+                }
+            }
+        })
+    }
+
+    /// Wait up to five seconds for `condition` to hold
+    async fn eventually(condition: impl AsyncFn() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !condition().await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("condition never held");
+    }
+
+    #[tokio::test]
+    async fn test_dropped_subscriptions_fall_back_to_polling_until_reconnected() {
+        let node = Arc::new(FakeL1::new(10));
+        let app = Router::new().route("/", get(fake_l1_ws).post(fake_l1_http)).with_state(node.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let mut config = l1_config("poll_interval_ms = 20\nretry_initial_ms = 20");
+        config.rpc_url = format!("ws://{}", addr);
+        config.http_url = Some(format!("http://{}", addr));
+        let forced_queue = Arc::new(ForcedQueue::new());
+        let l1 = Arc::new(L1Listener::new(config, forced_queue.clone()));
+        let running = l1.clone();
+        let task = tokio::spawn(async move { running.start().await });
+        
+        // Streaming bridge events and heads
+        eventually(async || node.subscriptions.load(Ordering::SeqCst) == 2).await;
+        assert_eq!(node.http_calls.load(Ordering::SeqCst), 0);
+        
+        // Events keep arriving while the WebSocket is down, by polling
+        node.ws_up.send_replace(false);
+        node.deposit(11);
+        eventually(async || forced_queue.len().await == 1).await;
+        node.deposit(12);
+        eventually(async || forced_queue.len().await == 2).await;
+        assert!(node.http_calls.load(Ordering::SeqCst) > 0);
+        assert_eq!(node.subscriptions.load(Ordering::SeqCst), 2);
+        
+        // Polling stops once the WebSocket is back and subscribed to again
+        node.ws_up.send_replace(true);
+        eventually(async || node.subscriptions.load(Ordering::SeqCst) == 4).await;
+        let http_calls = node.http_calls.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(node.http_calls.load(Ordering::SeqCst), http_calls);
+        task.abort();
+    }
+}
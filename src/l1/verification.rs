@@ -0,0 +1,137 @@
+//! Batch Verification Module
+//!
+//! Follows what the rollup contract does with committed batches once the
+//! submitter is done with them. Its `BatchVerified(uint256 indexed batchId)`
+//! and `BatchFinalized(uint256 indexed batchId)` events mark a batch
+//! verified in the registry, so its lifecycle reads sealed, submitted,
+//! committed, verified, then finalized once the L1 block verifying it is.
+//!
+//! New blocks are scanned with `eth_getLogs` every poll interval, in chunks
+//! of `l1.backfill.chunk_blocks`, up to `l1.confirmations` below the head so
+//! a shallow reorg cannot verify a batch that never was. After a restart the
+//! scan starts over from the oldest commitment not yet verified.
+
+use super::backfill::RequestPacer;
+use super::failover::connect_first_healthy;
+use crate::config::{L1Config, VerificationConfig};
+use crate::registry::Registry;
+use ethers::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Rollup contract events that verify a batch
+const VERIFICATION_EVENTS: [&str; 2] = ["BatchVerified(uint256)", "BatchFinalized(uint256)"];
+
+/// Monitor of the rollup contract's batch verifications
+pub struct VerificationMonitor {
+    /// L1 connection configuration
+    l1: L1Config,
+    /// Monitor configuration
+    config: VerificationConfig,
+    /// Address of the rollup contract
+    rollup_address: Address,
+    /// Where verifications are recorded
+    registry: Arc<Registry>,
+}
+
+impl VerificationMonitor {
+    /// Creates a monitor of the rollup contract at `rollup_address`
+    ///
+    /// # Returns
+    /// An error if the rollup address is invalid
+    pub fn new(l1: L1Config, config: VerificationConfig, rollup_address: &str, registry: Arc<Registry>) -> anyhow::Result<Self> {
+        let rollup_address = rollup_address.parse()?;
+        Ok(Self { l1, config, rollup_address, registry })
+    }
+
+    /// Log filter matching the rollup contract's verification events
+    pub fn filter(&self) -> Filter {
+        Filter::new().address(self.rollup_address).events(VERIFICATION_EVENTS)
+    }
+
+    /// Record the verification a log reports
+    ///
+    /// # Returns
+    /// The batch verified, or `None` if the log is not a verification event
+    pub async fn handle_log(&self, log: &Log) -> anyhow::Result<Option<u64>> {
+        let (Some(batch_topic), Some(tx_hash), Some(block_number)) = (log.topics.get(1), log.transaction_hash, log.block_number)
+        else {
+            return Ok(None);
+        };
+        let batch_id = U256::from_big_endian(batch_topic.as_bytes());
+        if batch_id > U256::from(u64::MAX) {
+            return Ok(None);
+        }
+        let batch_id = batch_id.as_u64();
+        if self.registry.record_batch_verified(batch_id, tx_hash, block_number.as_u64()).await? {
+            info!("Batch #{} verified on L1 in block {} ({:?})", batch_id, block_number, tx_hash);
+        } else {
+            debug!("Rollup contract verified unknown batch #{}", batch_id);
+        }
+        Ok(Some(batch_id))
+    }
+
+    /// Scan new L1 blocks for verifications every poll interval, until the
+    /// process exits
+    ///
+    /// Connects to the first healthy endpoint, and reconnects after a failed scan.
+    pub async fn start(self) {
+        info!("Monitoring rollup contract {:?} for batch verifications", self.rollup_address);
+        let interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut pacer = RequestPacer::new(Duration::from_millis(self.l1.backfill.request_interval_ms));
+        let mut next_block = None;
+        let mut provider = None;
+        loop {
+            if provider.is_none() {
+                match connect_first_healthy(&self.l1.rpc_urls()).await {
+                    Ok((connected, _)) => provider = Some(connected),
+                    Err(e) => warn!("Verification monitor cannot reach L1: {:?}", e),
+                }
+            }
+            if let Some(connected) = &provider
+                && let Err(e) = self.scan(connected, &mut next_block, &mut pacer).await
+            {
+                warn!("Failed to scan L1 for batch verifications: {:?}", e);
+                provider = None;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Handle the verifications of the blocks from `next_block` to the
+    /// deepest confirmed one, moving `next_block` past them
+    ///
+    /// Without a `next_block`, starts from the oldest unverified commitment,
+    /// or the confirmed head if there is none.
+    async fn scan<M: Middleware>(&self, client: &M, next_block: &mut Option<u64>, pacer: &mut RequestPacer) -> anyhow::Result<()> {
+        let head = client
+            .get_block_number()
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to read the L1 head: {}", e))?
+            .as_u64();
+        let to = head.saturating_sub(self.l1.confirmations);
+        let from = match *next_block {
+            Some(from) => from,
+            None => self.registry.oldest_unverified_commitment().await?.unwrap_or(to + 1).min(to + 1),
+        };
+
+        let chunk_blocks = self.l1.backfill.chunk_blocks.max(1);
+        let mut chunk_start = from;
+        while chunk_start <= to {
+            let chunk_end = to.min(chunk_start.saturating_add(chunk_blocks - 1));
+            pacer.wait().await;
+            let logs = client
+                .get_logs(&self.filter().from_block(chunk_start).to_block(chunk_end))
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to fetch logs of blocks {}..={}: {}", chunk_start, chunk_end, e))?;
+            for log in &logs {
+                self.handle_log(log).await?;
+            }
+            chunk_start = chunk_end + 1;
+            *next_block = Some(chunk_start);
+        }
+        next_block.get_or_insert(from);
+        Ok(())
+    }
+}
@@ -0,0 +1,445 @@
+//! L1 Wallet Module
+//!
+//! The sequencer's L1 account, which signs and pays for its L1 transactions.
+//! Its key comes from an encrypted keystore, an environment variable or the
+//! configuration (see `WalletConfig`).
+//!
+//! # Nonces
+//! The wallet assigns nonces itself rather than asking the node for each
+//! transaction, and sends one transaction at a time. The first nonce is the
+//! account's count of mined transactions, so a transaction left pending by an
+//! earlier run is replaced rather than queued behind.
+//!
+//! # Stuck Transactions
+//! A transaction still unmined `resubmit_after_blocks` blocks after it was
+//! sent is replaced: the same nonce, with fees raised by `fee_bump_percent`
+//! (and the blob fee doubled), or to the current fees if those are higher.
+//! Every version stays watched, so whichever is mined counts. Fees never go
+//! above `max_fee_per_gas_wei`; a transaction stuck there is left waiting.
+//!
+//! A send that fails before its transaction is mined leaves it in flight: the
+//! next send of the same call replaces it instead of taking a new nonce, so
+//! retries never post the same call twice.
+
+use super::blobs::{BlobSidecar, BlobTransaction};
+use crate::config::WalletConfig;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::keccak256;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Lowest fee increase nodes accept for a replacement, in percent
+const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+/// Factor nodes require a replacement blob transaction to raise the blob fee by
+const BLOB_FEE_BUMP_FACTOR: u64 = 2;
+
+/// Most fee raises tried when the node refuses a transaction as underpriced
+const MAX_UNDERPRICED_RAISES: usize = 32;
+
+/// Transaction for the wallet to send
+pub enum L1Transaction {
+    /// EIP-1559 transaction (type 0x02) calling `to` with `data`
+    Call { to: Address, data: Bytes },
+    /// Blob transaction (type 0x03) calling `to` with `data`, carrying `sidecar`
+    Blob {
+        to: Address,
+        data: Bytes,
+        gas_limit: u64,
+        sidecar: BlobSidecar,
+        /// Blob gas price offered at first
+        max_fee_per_blob_gas: U256,
+        /// Highest blob gas price replacements may offer (unlimited if `None`)
+        blob_fee_cap: Option<U256>,
+    },
+}
+
+impl L1Transaction {
+    /// Target and calldata, which identify the call across retries
+    fn payload(&self) -> (Address, Bytes) {
+        match self {
+            L1Transaction::Call { to, data } | L1Transaction::Blob { to, data, .. } => (*to, data.clone()),
+        }
+    }
+}
+
+/// Fees offered by a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxFees {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    /// Blob gas price (blob transactions only)
+    pub max_fee_per_blob_gas: Option<U256>,
+}
+
+impl TxFees {
+    /// Fees for a replacement of a transaction offering these
+    ///
+    /// Each fee is raised by `bump_percent` (at least 10), and the blob fee
+    /// doubled, or set to `current` if that is higher. Fees are capped at
+    /// `cap` and `blob_cap`.
+    ///
+    /// # Returns
+    /// `None` if the caps leave too little room for nodes to accept a replacement
+    pub fn bumped(&self, current: &TxFees, bump_percent: u64, cap: Option<U256>, blob_cap: Option<U256>) -> Option<TxFees> {
+        let raise = |fee: U256, percent: u64| (fee.saturating_mul(U256::from(percent)) + 99) / 100;
+        let percent = 100 + bump_percent.max(MIN_FEE_BUMP_PERCENT);
+        let min_percent = 100 + MIN_FEE_BUMP_PERCENT;
+
+        let mut max_fee_per_gas = raise(self.max_fee_per_gas, percent).max(current.max_fee_per_gas);
+        if let Some(cap) = cap {
+            max_fee_per_gas = max_fee_per_gas.min(cap);
+        }
+        let max_priority_fee_per_gas = raise(self.max_priority_fee_per_gas, percent)
+            .max(current.max_priority_fee_per_gas)
+            .min(max_fee_per_gas);
+        if max_fee_per_gas < raise(self.max_fee_per_gas, min_percent)
+            || max_priority_fee_per_gas < raise(self.max_priority_fee_per_gas, min_percent)
+        {
+            return None;
+        }
+
+        let max_fee_per_blob_gas = match self.max_fee_per_blob_gas {
+            Some(previous) => {
+                let required = previous.saturating_mul(U256::from(BLOB_FEE_BUMP_FACTOR));
+                let fee = required.max(current.max_fee_per_blob_gas.unwrap_or_default());
+                let fee = blob_cap.map_or(fee, |cap| fee.min(cap));
+                if fee < required {
+                    return None;
+                }
+                Some(fee)
+            }
+            None => current.max_fee_per_blob_gas,
+        };
+        Some(TxFees { max_fee_per_gas, max_priority_fee_per_gas, max_fee_per_blob_gas })
+    }
+}
+
+/// Why a node refused a transaction
+#[derive(Debug, PartialEq, Eq)]
+enum Refusal {
+    /// The node already has this exact transaction
+    AlreadyKnown,
+    /// The fees are too low, or too low to replace the pending transaction
+    Underpriced,
+    /// The nonce was already used by a mined transaction
+    NonceTooLow,
+    Other,
+}
+
+impl Refusal {
+    fn of(error: &ProviderError) -> Self {
+        let message = error.to_string().to_lowercase();
+        if message.contains("already known") {
+            Refusal::AlreadyKnown
+        } else if message.contains("underpriced") {
+            Refusal::Underpriced
+        } else if message.contains("nonce too low") {
+            Refusal::NonceTooLow
+        } else {
+            Refusal::Other
+        }
+    }
+}
+
+/// Nonces of the wallet's account
+#[derive(Default)]
+struct NonceState {
+    /// Next nonce to use (read from L1 if `None`)
+    next_nonce: Option<U256>,
+    /// Latest transaction sent but not mined
+    in_flight: Option<InFlight>,
+}
+
+/// Transaction sent but not mined
+struct InFlight {
+    nonce: U256,
+    /// Fees of the latest version sent
+    fees: TxFees,
+    /// Target and calldata of the call
+    payload: (Address, Bytes),
+    /// Hashes of every version sent
+    hashes: Vec<H256>,
+}
+
+/// The sequencer's L1 account
+pub struct L1Wallet {
+    /// Signing key, bound to the L1 chain ID
+    signer: LocalWallet,
+    /// Key sources and fee escalation settings
+    config: WalletConfig,
+    /// Held for the whole of a send, so transactions go out one at a time
+    state: Mutex<NonceState>,
+}
+
+impl L1Wallet {
+    /// Load the wallet's key from the first source configured
+    ///
+    /// # Arguments
+    /// * `config` - Wallet configuration
+    /// * `fallback_key` - Key used if `config` sets no source
+    /// * `chain_id` - Chain ID of L1, for replay-protected signatures
+    ///
+    /// # Returns
+    /// An error if no key is configured, or it cannot be read
+    pub fn load(config: &WalletConfig, fallback_key: Option<H256>, chain_id: u64) -> anyhow::Result<Self> {
+        let env = |name: &str| std::env::var(name).map_err(|_| anyhow::anyhow!("environment variable {} is not set", name));
+        let signer = if let Some(path) = &config.keystore_path {
+            let password_env = config
+                .keystore_password_env
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("l1.wallet.keystore_path needs keystore_password_env"))?;
+            LocalWallet::decrypt_keystore(path, env(password_env)?)?
+        } else if let Some(key_env) = &config.signing_key_env {
+            env(key_env)?.trim().parse::<LocalWallet>()?
+        } else {
+            let key = config
+                .signing_key
+                .or(fallback_key)
+                .ok_or_else(|| anyhow::anyhow!("no L1 signing key configured"))?;
+            LocalWallet::from_bytes(key.as_bytes())?
+        };
+        Ok(Self { signer: signer.with_chain_id(chain_id), config: config.clone(), state: Mutex::default() })
+    }
+
+    /// Address of the account
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// Send `tx` and wait until it is mined and `confirmations` deep
+    ///
+    /// Replaces the transaction while it is stuck. `on_sent` is called with
+    /// the hash of every version broadcast.
+    ///
+    /// # Returns
+    /// The receipt of the version that was mined, whether or not it reverted
+    pub async fn send<F: Future<Output = ()>>(
+        &self,
+        provider: &Provider<Ws>,
+        tx: &L1Transaction,
+        confirmations: usize,
+        on_sent: impl Fn(H256) -> F,
+    ) -> anyhow::Result<TransactionReceipt> {
+        let mut state = self.state.lock().await;
+        let payload = tx.payload();
+        let gas_limit = match tx {
+            L1Transaction::Call { to, data } => {
+                let request: TypedTransaction = Eip1559TransactionRequest::new()
+                    .from(self.address())
+                    .to(*to)
+                    .data(data.clone())
+                    .into();
+                provider.estimate_gas(&request, None).await?
+            }
+            L1Transaction::Blob { gas_limit, .. } => U256::from(*gas_limit),
+        };
+        let current = self.current_fees(provider, tx).await?;
+
+        // A retry replaces the transaction in flight, still watching the
+        // versions sent before if it is the same call
+        let (nonce, fees, mut hashes) = match &state.in_flight {
+            Some(in_flight) => {
+                let fees = in_flight
+                    .fees
+                    .bumped(&current, self.config.fee_bump_percent, self.fee_cap(), blob_fee_cap(tx))
+                    .unwrap_or(in_flight.fees);
+                let hashes = if in_flight.payload == payload { in_flight.hashes.clone() } else { Vec::new() };
+                (in_flight.nonce, fees, hashes)
+            }
+            None => {
+                let nonce = match state.next_nonce {
+                    Some(nonce) => nonce,
+                    None => provider.get_transaction_count(self.address(), Some(BlockNumber::Latest.into())).await?,
+                };
+                (nonce, current, Vec::new())
+            }
+        };
+
+        let (hash, mut fees) = match self.broadcast_raising(provider, tx, gas_limit, nonce, fees).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                if matches!(e.downcast_ref::<ProviderError>().map(Refusal::of), Some(Refusal::NonceTooLow)) {
+                    // The nonce is used: start again from the account's count
+                    state.next_nonce = None;
+                    state.in_flight = None;
+                }
+                return Err(e);
+            }
+        };
+        hashes.push(hash);
+        state.next_nonce = Some(nonce + 1);
+        state.in_flight = Some(InFlight { nonce, fees, payload: payload.clone(), hashes: hashes.clone() });
+        on_sent(hash).await;
+
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut sent_at = provider.get_block_number().await?.as_u64();
+        loop {
+            tokio::time::sleep(poll_interval).await;
+            let head = provider.get_block_number().await?.as_u64();
+
+            if let Some(receipt) = find_receipt(provider, &hashes).await? {
+                let mined_in = receipt.block_number.map_or(head, |number| number.as_u64());
+                if head + 1 >= mined_in + confirmations.max(1) as u64 {
+                    state.in_flight = None;
+                    return Ok(receipt);
+                }
+                continue;
+            }
+
+            // Mined by a transaction this wallet did not send (checking our
+            // versions once more, as one may have been mined meanwhile)
+            let mined = provider.get_transaction_count(self.address(), Some(BlockNumber::Latest.into())).await?;
+            if mined > nonce && find_receipt(provider, &hashes).await?.is_none() {
+                state.next_nonce = None;
+                state.in_flight = None;
+                anyhow::bail!("nonce {} was used by another transaction", nonce);
+            }
+
+            if head < sent_at + self.config.resubmit_after_blocks {
+                continue;
+            }
+            sent_at = head;
+            let current = self.current_fees(provider, tx).await?;
+            let Some(bumped) = fees.bumped(&current, self.config.fee_bump_percent, self.fee_cap(), blob_fee_cap(tx)) else {
+                warn!("L1 transaction {:?} is stuck at the fee cap", hashes.last());
+                continue;
+            };
+            match self.broadcast(provider, tx, gas_limit, nonce, bumped).await {
+                Ok(hash) => {
+                    info!(
+                        "Replaced stuck L1 transaction {:?} with {:?} (max fee {} wei)",
+                        hashes.last(),
+                        hash,
+                        bumped.max_fee_per_gas
+                    );
+                    fees = bumped;
+                    hashes.push(hash);
+                    state.in_flight = Some(InFlight { nonce, fees, payload: payload.clone(), hashes: hashes.clone() });
+                    on_sent(hash).await;
+                }
+                Err(e) => warn!("Failed to replace stuck L1 transaction {:?}: {:?}", hashes.last(), e),
+            }
+        }
+    }
+
+    /// Broadcast a version of `tx`, raising its fees while it is refused as underpriced
+    ///
+    /// A transaction left pending by an earlier run can only be replaced by
+    /// outbidding it, though its fees are unknown.
+    async fn broadcast_raising(
+        &self,
+        provider: &Provider<Ws>,
+        tx: &L1Transaction,
+        gas_limit: U256,
+        nonce: U256,
+        mut fees: TxFees,
+    ) -> anyhow::Result<(H256, TxFees)> {
+        for _ in 0..MAX_UNDERPRICED_RAISES {
+            match self.broadcast(provider, tx, gas_limit, nonce, fees).await {
+                Ok(hash) => return Ok((hash, fees)),
+                Err(e) if e.downcast_ref::<ProviderError>().map(Refusal::of) == Some(Refusal::Underpriced) => {
+                    fees = fees
+                        .bumped(&fees, self.config.fee_bump_percent, self.fee_cap(), blob_fee_cap(tx))
+                        .ok_or_else(|| anyhow::anyhow!("nonce {} is taken by a transaction paying more than the fee cap", nonce))?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        anyhow::bail!("nonce {} is still taken after {} fee raises", nonce, MAX_UNDERPRICED_RAISES)
+    }
+
+    /// Sign and broadcast a version of `tx`
+    ///
+    /// # Returns
+    /// Its hash, also if the node already had it
+    async fn broadcast(
+        &self,
+        provider: &Provider<Ws>,
+        tx: &L1Transaction,
+        gas_limit: U256,
+        nonce: U256,
+        fees: TxFees,
+    ) -> anyhow::Result<H256> {
+        let (raw, hash) = match tx {
+            L1Transaction::Call { to, data } => {
+                let request: TypedTransaction = Eip1559TransactionRequest::new()
+                    .from(self.address())
+                    .to(*to)
+                    .data(data.clone())
+                    .nonce(nonce)
+                    .gas(gas_limit)
+                    .max_fee_per_gas(fees.max_fee_per_gas)
+                    .max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+                    .chain_id(self.signer.chain_id())
+                    .into();
+                let signature = self.signer.sign_transaction_sync(&request)?;
+                let raw = request.rlp_signed(&signature);
+                let hash = H256(keccak256(&raw));
+                (raw, hash)
+            }
+            L1Transaction::Blob { to, data, gas_limit, sidecar, max_fee_per_blob_gas, .. } => {
+                let blob_tx = BlobTransaction {
+                    chain_id: self.signer.chain_id(),
+                    nonce,
+                    max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+                    max_fee_per_gas: fees.max_fee_per_gas,
+                    gas_limit: *gas_limit,
+                    to: *to,
+                    data: data.clone(),
+                    max_fee_per_blob_gas: fees.max_fee_per_blob_gas.unwrap_or(*max_fee_per_blob_gas),
+                    blob_versioned_hashes: sidecar.versioned_hashes(),
+                };
+                let signature = self.signer.sign_hash(blob_tx.sighash())?;
+                (blob_tx.network_encoding(&signature, sidecar), blob_tx.hash(&signature))
+            }
+        };
+        match provider.send_raw_transaction(raw).await {
+            Ok(_) => Ok(hash),
+            Err(e) if Refusal::of(&e) == Refusal::AlreadyKnown => Ok(hash),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Fees L1 currently asks for `tx`, within the fee cap
+    async fn current_fees(&self, provider: &Provider<Ws>, tx: &L1Transaction) -> anyhow::Result<TxFees> {
+        let (mut max_fee_per_gas, max_priority_fee_per_gas) = provider.estimate_eip1559_fees(None).await?;
+        if let Some(cap) = self.fee_cap() {
+            max_fee_per_gas = max_fee_per_gas.min(cap);
+        }
+        let max_fee_per_blob_gas = match tx {
+            L1Transaction::Call { .. } => None,
+            L1Transaction::Blob { max_fee_per_blob_gas, .. } => Some(*max_fee_per_blob_gas),
+        };
+        Ok(TxFees {
+            max_fee_per_gas,
+            max_priority_fee_per_gas: max_priority_fee_per_gas.min(max_fee_per_gas),
+            max_fee_per_blob_gas,
+        })
+    }
+
+    /// Highest fee cap offered
+    fn fee_cap(&self) -> Option<U256> {
+        self.config.max_fee_per_gas_wei.map(U256::from)
+    }
+}
+
+/// Highest blob gas price replacements of `tx` may offer
+fn blob_fee_cap(tx: &L1Transaction) -> Option<U256> {
+    match tx {
+        L1Transaction::Call { .. } => None,
+        L1Transaction::Blob { blob_fee_cap, .. } => *blob_fee_cap,
+    }
+}
+
+/// Receipt of whichever of `hashes` was mined
+async fn find_receipt(provider: &Provider<Ws>, hashes: &[H256]) -> anyhow::Result<Option<TransactionReceipt>> {
+    for hash in hashes {
+        if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+            return Ok(Some(receipt));
+        }
+    }
+    Ok(None)
+}
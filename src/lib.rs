@@ -12,6 +12,10 @@ pub mod scheduler; // Manages task scheduling and execution.
 pub mod batch; // Handles batch processing of transactions or operations.
 pub mod registry; // Manages registration and lookup of components or entities.
 pub mod config; // Defines and loads system configuration.
+pub mod logging; // Sets up tracing with a runtime-reloadable filter.
+pub mod vectors; // Generates and verifies cross-client test vectors.
+#[cfg(test)]
+mod test_utils; // Fixtures shared by the test modules.
 
 // Re-export commonly used types and configurations for easier access.
 pub use types::*;
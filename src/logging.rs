@@ -0,0 +1,63 @@
+//! Logging Module
+//!
+//! Sets up tracing output with a level filter that can be swapped at runtime
+//! (see the `admin_setLogFilter` RPC method), so operators can turn on targeted
+//! debug logging during an incident without restarting and losing the pool.
+//!
+//! Filters are comma-separated directives: an optional default level plus
+//! `target=level` pairs, e.g. `info,sequencer::scheduler=debug`.
+
+use tracing_subscriber::{filter::Targets, layer::SubscriberExt, reload, util::SubscriberInitExt, Registry};
+
+/// Filter applied to all log output until changed
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Handle to the active log filter
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: reload::Handle<Targets, Registry>,
+}
+
+impl LogFilter {
+    /// Current filter directives
+    pub fn current(&self) -> String {
+        self.handle
+            .with_current(|targets| targets.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Replace the filter with `directives`
+    ///
+    /// # Returns
+    /// An error if the directives don't parse; the previous filter stays active
+    pub fn set(&self, directives: &str) -> anyhow::Result<()> {
+        let targets: Targets = directives.parse()?;
+        self.handle.reload(targets)?;
+        Ok(())
+    }
+}
+
+/// Install the global tracing subscriber, writing to stdout with [`DEFAULT_FILTER`]
+///
+/// # Returns
+/// A handle for changing the filter later
+pub fn init() -> anyhow::Result<LogFilter> {
+    let (subscriber, log_filter) = subscriber()?;
+    subscriber.try_init()?;
+    Ok(log_filter)
+}
+
+/// Build the subscriber [`init`] installs, without installing it
+///
+/// The filter can only be changed while the subscriber is alive.
+///
+/// # Returns
+/// The subscriber and a handle for changing its filter
+pub fn subscriber() -> anyhow::Result<(impl tracing::Subscriber + Send + Sync + 'static, LogFilter)> {
+    let targets: Targets = DEFAULT_FILTER.parse()?;
+    let (filter, handle) = reload::Layer::new(targets);
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+    Ok((subscriber, LogFilter { handle }))
+}
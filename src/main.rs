@@ -1,11 +1,27 @@
 use sequencer::{
     api::Server,
-    config::Config,
-    state::StateCache,
-    pool::{ForcedQueue, TransactionPool},
-    l1::L1Listener,
+    config::{Config, RemotePoolConfig, SigningConfig, TimestampSource},
+    state::{Genesis, StateCache, StateCheckpoint, StateReconciler, StateStore, EMPTY_STATE_ROOT},
+    pool::{BundlePool, EncryptedPool, ForcedQueue, PoolEvent, RecoveredTransaction, RemotePool, TransactionPool, UserOpPool},
+    validation::{BlsCommittee, BlsSigner, OwnerSignatureValidator, Validator},
+    l1::{
+        BatchSubmitter, Bridges, Devnet, EscapeHatchMonitor, FinalityTracker, L1GasOracle, L1HeadClock, L1Listener,
+        PostingScheduler, VerificationMonitor,
+    },
+    logging::{self, LogFilter},
+    registry::Registry,
+    batch::{offline, EconomicTrigger, ForcedDeadlines, SafeMode},
+    vectors,
+    AccountState,
+    GenesisRecord,
+    TransactionOutcome,
+    TransactionRecord,
 };
+use ethers::signers::LocalWallet;
+use ethers::types::U256;
+use std::io::{BufReader, Write};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::info;
 
 /// The main entry point for the sequencer application.
@@ -13,32 +29,176 @@ use tracing::info;
 /// This function initializes logging, loads the application configuration,
 /// sets up shared resources (state cache, transaction pools), starts the L1
 /// event listener in the background, and starts the API server.
+/// 
+/// # Subcommands
+/// - `build-batch --input <txs.jsonl|-> [--state <accounts.json>] [--output <batch.json>] [--config <path>]`:
+///   builds sealed batches offline from presigned transactions (see [`offline`])
+/// - `export-vectors [--output <vectors.json>]`: writes the canonical test vectors (see [`vectors`])
+/// - `verify-vectors --input <vectors.json>`: checks a test vector set against this implementation
+/// 
+/// # Flags
+/// - `--devnet`: runs against a local Anvil node with a mock bridge (see `l1.devnet`)
 #[tokio::main] // Marks the async main function to be run by the Tokio runtime.
 async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("build-batch") {
+        // Logs go to stderr so the artifact can be piped from stdout
+        tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+        return build_batch(&args[1..]).await;
+    }
+    match args.first().map(String::as_str) {
+        Some("export-vectors") => return export_vectors(&args[1..]),
+        Some("verify-vectors") => {
+            tracing_subscriber::fmt().init();
+            return verify_vectors(&args[1..]);
+        }
+        _ => {}
+    }
+    
     // Initialize logging using tracing_subscriber.
-    // This sets up a default formatter that prints logs to stdout.
-    tracing_subscriber::fmt::init();
+    // Logs go to stdout; the filter can be changed at runtime via the admin API.
+    let log_filter = logging::init()?;
     
     // Load the application configuration from the specified TOML file.
     // The `?` operator propagates any errors that occur during loading.
-    let config = Config::load("config/default.toml")?;
+    let mut config = Config::load("config/default.toml")?;
+    
+    // Devnet mode: a local L1 node with a mock bridge, kept alive until exit
+    let devnet = if args.iter().any(|arg| arg == "--devnet") {
+        let devnet = Devnet::start(&config.l1.devnet).await?;
+        devnet.configure(&mut config.l1);
+        Some(devnet)
+    } else {
+        None
+    };
     // Log the loaded configuration for debugging and informational purposes.
     info!("Sequencer starting with config: {:?}", config);
     
+    // With a remote pool configured, this node only takes in transactions
+    if let Some(remote) = config.pool.remote.clone() {
+        return run_ingress(config, remote, log_filter).await;
+    }
+    
     // Initialize shared resources
     // All shared state is created here and passed to components that need it
     
+    // Registry: persists batch metadata and transaction history
+    let registry = Arc::new(Registry::new(&config.database).await?);
+    
     // State cache: stores account balances and nonces for validation
-    let state_cache = StateCache::new();
+    // Inactive accounts are archived into the registry when configured
+    // With a store configured, state written before a restart is picked up again
+    // Recent batches can be rolled back if they fail downstream
+    let mut state_cache = StateCache::new()
+        .with_shards(config.state.shards)
+        .with_snapshots(config.state.snapshot_batches)
+        .with_journal(config.state.journal_capacity);
+    if let Some(store) = &config.state.store {
+        state_cache = state_cache.with_store(StateStore::open(store)?);
+        info!("Persisting account state to {}", store.path);
+    }
+    if config.state.archive_after_batches.is_some() {
+        state_cache = state_cache.with_archive(registry.clone());
+    }
+    if let Some(max) = config.state.max_hot_accounts {
+        if config.state.store.is_none() {
+            tracing::warn!("state.max_hot_accounts is set without state.store; memory stays unbounded");
+        }
+        state_cache = state_cache.with_max_hot_accounts(max);
+    }
+    if let Some(prefund) = config.state.devnet_prefund_wei {
+        tracing::warn!("Prefunding every new account with {} wei (devnet only)", prefund);
+        state_cache = state_cache.with_prefund(prefund.into());
+    }
+    let restored = state_cache.restore().await?;
+    if restored > 0 {
+        info!("Restored {} accounts into the state trie", restored);
+    }
+    init_checkpoint(&config, &registry, &state_cache).await?;
+    init_genesis(&config, &registry, &state_cache).await?;
     
     // Transaction pool: stores normal pending transactions from users
-    let tx_pool = Arc::new(TransactionPool::new());
+    // Pending transactions from before a restart are recovered from the WAL
+    // An L1 allowlist registry implies permissioned mode, starting from the configured allowlist
+    let mut pool_config = config.pool.clone();
+    if config.l1.allowlist_registry.is_some() && pool_config.allowlist.is_none() {
+        pool_config.allowlist = Some(Vec::new());
+    }
+    let (tx_pool, recovered) = TransactionPool::open(pool_config)?;
+    let tx_pool = Arc::new(tx_pool);
+    restore_pending(&tx_pool, &state_cache, &config.signing, recovered).await;
     
     // Forced queue: stores priority transactions from L1 (deposits, forced exits)
-    let forced_queue = Arc::new(ForcedQueue::new());
+    // Persisted in the registry, so queued deposits survive restarts and re-scanned
+    // L1 events are not queued twice
+    let forced_queue = Arc::new(
+        ForcedQueue::new()
+            .with_registry(registry.clone())
+            .with_capacity(config.pool.forced_queue_capacity),
+    );
+    let restored = forced_queue.restore().await?;
+    if restored > 0 {
+        info!("Restored {} queued forced transactions", restored);
+    }
+    
+    // Start the pool expiry task so stale transactions are dropped
+    let expiry_pool = tx_pool.clone();
+    tokio::spawn(async move { expiry_pool.start_expiry().await });
+    
+    // Record pool events (e.g., expirations) in the registry's transaction history,
+    // giving back the nonces of dropped transactions
+    // Removals come on a lossless channel: a missed one would hold its nonce forever
+    let mut pool_events = tx_pool.subscribe_removals();
+    let event_registry = registry.clone();
+    let event_state_cache = state_cache.clone();
+    let event_pool = tx_pool.clone();
+    tokio::spawn(async move {
+        while let Some(event) = pool_events.recv().await {
+            let (tx, expired) = match event {
+                PoolEvent::Expired(tx) => (tx, true),
+                PoolEvent::Evicted(tx) | PoolEvent::Dropped(tx) => (tx, false),
+                _ => continue,
+            };
+            // A removed transaction gives its nonce back, and the sender's later ones can't execute without it
+            // Dropped ones are given back too: one admitted at the released nonce before the drop still holds it
+            let dropped = event_state_cache.give_back(&event_pool, &tx).await;
+            // Admission, eviction and batching outcomes are recorded where they happen
+            let outcomes = expired
+                .then_some((tx, TransactionOutcome::Expired))
+                .into_iter()
+                .chain(dropped.into_iter().map(|tx| (tx, TransactionOutcome::Dropped)));
+            for (tx, outcome) in outcomes {
+                let record = TransactionRecord {
+                    tx_hash: tx.hash(),
+                    sender: tx.from,
+                    nonce: tx.nonce,
+                    outcome,
+                    timestamp: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs(),
+                };
+                if let Err(e) = event_registry.record_transaction(record).await {
+                    tracing::error!("Failed to record dropped transaction: {:?}", e);
+                }
+            }
+        }
+    });
+    
+    // Without a stake registry no stakes are recorded, so every sender would be refused
+    if config.validation.min_sender_stake_wei.is_some() && config.l1.stake_registry.is_none() {
+        tracing::warn!("validation.min_sender_stake_wei is set without l1.stake_registry; all senders will be refused");
+    }
+    
+    // Refuse to start with a bridge the listener could not decode events of
+    Bridges::new(&config.l1)?;
     
     // Create the L1 event listener
-    let l1_listener = L1Listener::new(config.l1.clone(), forced_queue.clone());
+    // It resumes from the scan cursor persisted in the registry
+    let l1_listener = L1Listener::new(config.l1.clone(), forced_queue.clone())
+        .with_cursor(registry.clone())
+        .with_allowlist(tx_pool.clone())
+        .with_stakes(state_cache.clone());
     
     // Start the L1 listener in the background
     // This spawns a new async task that monitors L1 for forced transactions
@@ -55,21 +215,230 @@ async fn main() -> anyhow::Result<()> {
     let orchestrator = sequencer::BatchOrchestrator::new(
         forced_queue.clone(),
         tx_pool.clone(),
+        state_cache.clone(),
+        registry.clone(),
         config.batch.clone(),
         config.scheduling.to_policy_type(),
-    );
+    )
+    .with_min_distinct_senders(config.scheduling.min_distinct_senders())
+    .with_execution_lanes(config.scheduling.execution_lanes())
+    .with_state_archival(config.state.archive_after_batches)
+    .with_history_pruning(config.state.pruning.keep_batches());
+    
+    // Seal batches early when forced transactions near their inclusion deadline
+    let forced_inclusion = &config.l1.forced_inclusion;
+    let orchestrator = if forced_inclusion.enabled() {
+        // Events only reach the queue once confirmed, eating into the block deadline
+        if let Some(deadline) = forced_inclusion.deadline_blocks
+            && deadline <= config.l1.confirmations + forced_inclusion.margin_blocks
+        {
+            tracing::warn!("l1.forced_inclusion.deadline_blocks leaves no time after l1.confirmations and margin_blocks");
+        }
+        orchestrator.with_forced_deadlines(ForcedDeadlines::new(forced_inclusion.clone()))
+    } else {
+        orchestrator
+    };
+    
+    // Sealed batches are committed to the L1 rollup contract, if configured
+    let (submissions, sealed_batches) = tokio::sync::mpsc::unbounded_channel();
+    let orchestrator = match &config.l1.submitter {
+        Some(submitter) => orchestrator
+            .with_compression(submitter.compression, submitter.compression_level)
+            .with_submissions(submissions),
+        None => orchestrator,
+    };
+    
+    // User operations from smart-contract wallets get their own lane
+    let user_op_lane = config.account_abstraction.enabled
+        .then(|| Arc::new(UserOpPool::new(config.account_abstraction.lane_capacity)));
+    let orchestrator = match &user_op_lane {
+        Some(lane) => orchestrator.with_user_ops(lane.clone(), config.account_abstraction.max_per_batch),
+        None => orchestrator,
+    };
+    // Encrypted transactions wait in their own lane until their epoch key is out,
+    // and are admitted in turn with the API's submissions
+    let bundle_lane = config.bundles.enabled
+        .then(|| Arc::new(BundlePool::new(config.bundles.lane_capacity)));
+    let validation = Server::validation_workers(&config, &state_cache, &tx_pool, bundle_lane.as_ref());
+    let encrypted_lane = if config.encrypted_mempool.enabled {
+        Some(Arc::new(EncryptedPool::new(config.encrypted_mempool.clone())?))
+    } else {
+        None
+    };
+    let orchestrator = match &encrypted_lane {
+        Some(lane) => orchestrator.with_encrypted_mempool(lane.clone(), validation.clone(), config.encrypted_mempool.max_per_batch),
+        None => orchestrator,
+    };
+    // Bundles are sealed whole, right after the decrypted transactions
+    let orchestrator = match &bundle_lane {
+        Some(lane) => orchestrator.with_bundles(lane.clone(), config.bundles.max_per_batch),
+        None => orchestrator,
+    };
+    // Transactions nearing their deadline are sealed ahead of the scheduling policy
+    let orchestrator = if config.deadlines.enabled {
+        orchestrator.with_deadlines(config.deadlines.margin_secs)
+    } else {
+        orchestrator
+    };
+    // L1 gas prices feed posting cost estimates, for the economic trigger and estimateFee
+    let gas_oracle = config.l1.gas_oracle.clone()
+        .map(|oracle| Arc::new(L1GasOracle::new(config.l1.clone(), oracle)));
+    if let Some(oracle) = &gas_oracle {
+        tokio::spawn(oracle.clone().start());
+        info!("L1 gas price oracle started");
+    }
+    let orchestrator = match (&gas_oracle, config.batch.max_l1_cost_per_tx_wei) {
+        (Some(oracle), Some(max_cost)) => orchestrator.with_economic_trigger(EconomicTrigger::new(
+            oracle.clone(),
+            U256::from(max_cost),
+            Duration::from_millis(config.batch.max_economic_delay_ms),
+        )),
+        (None, Some(_)) => {
+            tracing::warn!("batch.max_l1_cost_per_tx_wei is set without [l1.gas_oracle]; the economic trigger is disabled");
+            orchestrator
+        }
+        (_, None) => orchestrator,
+    };
+    let orchestrator = match config.batch.timestamp_source {
+        TimestampSource::WallClock => orchestrator,
+        TimestampSource::L1Head => orchestrator.with_clock(Arc::new(L1HeadClock::new(config.l1.rpc_url.clone()))),
+    };
+    
+    // Safe mode seals forced transactions only while L1 won't take our batches
+    let safe_mode = match (config.l1.safe_mode.clone(), &config.l1.submitter) {
+        (Some(safe_mode_config), Some(submitter)) => {
+            let max_failure = Duration::from_millis(safe_mode_config.max_submission_failure_ms);
+            let safe_mode = Arc::new(SafeMode::new(Some(max_failure)));
+            let monitor = EscapeHatchMonitor::new(
+                config.l1.clone(),
+                safe_mode_config,
+                &submitter.rollup_address,
+                safe_mode.clone(),
+            )?;
+            tokio::spawn(monitor.start());
+            Some(safe_mode)
+        }
+        (Some(_), None) => {
+            tracing::warn!("[l1.safe_mode] is set without [l1.submitter]; safe mode is disabled");
+            None
+        }
+        (None, _) => None,
+    };
+    let orchestrator = match &safe_mode {
+        Some(safe_mode) => orchestrator.with_safe_mode(safe_mode.clone()),
+        None => orchestrator,
+    };
+    
+    let orchestrator = Arc::new(orchestrator);
+    
+    if let Some(submitter_config) = config.l1.submitter.clone() {
+        if config.state.snapshot_batches == 0 {
+            tracing::warn!("state.snapshot_batches is 0: batches failing on L1 cannot be rolled back");
+        }
+        let mut submitter = BatchSubmitter::new(config.l1.clone(), submitter_config, registry.clone(), orchestrator.clone())?;
+        if let Some(safe_mode) = &safe_mode {
+            submitter = submitter.with_safe_mode(safe_mode.clone());
+        }
+        match (config.l1.posting.clone(), &gas_oracle) {
+            (Some(posting), Some(oracle)) => {
+                let scheduler = PostingScheduler::new(posting, config.l1.forced_inclusion.clone(), oracle.clone());
+                submitter = submitter.with_posting_scheduler(scheduler);
+            }
+            (Some(_), None) => tracing::warn!("[l1.posting] is set without [l1.gas_oracle]; batches are posted right away"),
+            (None, _) => {}
+        }
+        tokio::spawn(submitter.start(sealed_batches));
+        info!("L1 batch submitter started");
+    }
+    
+    // The rollup contract's verifications of committed batches
+    match (config.l1.verification.clone(), &config.l1.submitter) {
+        (Some(verification), Some(submitter)) => {
+            let monitor = VerificationMonitor::new(config.l1.clone(), verification, &submitter.rollup_address, registry.clone())?;
+            tokio::spawn(monitor.start());
+        }
+        (Some(_), None) => tracing::warn!("[l1.verification] is set without [l1.submitter]; batch verifications are not monitored"),
+        (None, _) => {}
+    }
+    
+    // The L1 finality tags finalize committed batches
+    if let Some(finality) = config.l1.finality.clone() {
+        let tracker = Arc::new(FinalityTracker::new(config.l1.clone(), finality, registry.clone()));
+        tokio::spawn(tracker.start());
+        info!("L1 finality tracker started");
+    }
+    
+    // Compare the cached state with the executor's and repair drift
+    if let Some(reconciliation) = config.state.reconciliation.clone() {
+        let mut reconciler = StateReconciler::new(state_cache.clone(), tx_pool.clone(), reconciliation)?;
+        if let Some(lane) = &user_op_lane {
+            reconciler = reconciler.with_user_ops(lane.clone());
+        }
+        if let Some(lane) = &bundle_lane {
+            reconciler = reconciler.with_bundles(lane.clone());
+        }
+        tokio::spawn(reconciler.start());
+    }
     
     // Start the orchestrator in the background
+    let batch_loop = orchestrator.clone();
     tokio::spawn(async move {
-        if let Err(e) = orchestrator.start().await {
+        if let Err(e) = batch_loop.start().await {
             tracing::error!("Batch orchestrator error: {:?}", e);
         }
     });
     info!("Batch orchestrator started");
+    if let Some(devnet) = &devnet {
+        devnet.log_usage();
+    }
     
     // Create a new API server instance.
     // Pass shared resources needed for handling user transactions.
-    let server = Server::new(config, state_cache, tx_pool);
+    let proposer_key = config.proposer.enabled().then_some(config.proposer.signing_key).flatten();
+    let wallet_owners: Vec<_> = config.account_abstraction.wallets
+        .iter()
+        .map(|wallet| (wallet.wallet, wallet.owner))
+        .collect();
+    let attestation = config.attestation.clone();
+    let mut server = Server::new(config, state_cache, tx_pool, registry)
+        .with_validation(validation)
+        .with_log_filter(log_filter)
+        .with_checkpoints(orchestrator.clone())
+        .with_policy_switching(orchestrator.clone())
+        .with_auction(orchestrator.clone());
+    if let Some(oracle) = gas_oracle {
+        server = server.with_gas_oracle(oracle);
+    }
+    if let Some(safe_mode) = safe_mode {
+        server = server.with_safe_mode(safe_mode);
+    }
+    if let Some(lane) = user_op_lane {
+        server = server.with_user_ops(lane, Arc::new(OwnerSignatureValidator::new(wallet_owners)));
+        info!("Account abstraction enabled");
+    }
+    if let Some(lane) = encrypted_lane {
+        info!("Encrypted mempool enabled, epochs of {}ms", lane.epoch_ms());
+        server = server.with_encrypted_mempool(lane);
+    }
+    if let Some(lane) = bundle_lane {
+        info!("Atomic bundles enabled");
+        server = server.with_bundles(lane);
+    }
+    // External proposers can hand in ordered batches once a token and key are configured
+    if let Some(key) = proposer_key {
+        server = server.with_proposer(orchestrator, LocalWallet::from_bytes(key.as_bytes())?);
+        info!("External batch proposer API enabled");
+    }
+    // Multi-sequencer mode: attest with this node's BLS key, and check the committee's
+    if let Some(seed) = attestation.bls_secret_key {
+        let signer = BlsSigner::from_seed(&seed)?;
+        info!("Attesting with BLS key {}", signer.public_key());
+        server = server.with_attestor(signer);
+    }
+    if !attestation.committee.is_empty() {
+        server = server.with_committee(BlsCommittee::new(&attestation.committee, attestation.threshold)?);
+        info!("BLS committee of {} sequencers", attestation.committee.len());
+    }
     // Start the API server. This will typically bind to a port and begin
     // listening for incoming requests. The `?` operator propagates any
     // errors that occur during server startup.
@@ -77,4 +446,224 @@ async fn main() -> anyhow::Result<()> {
     
     // Return `Ok(())` to indicate successful execution of the main function.
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Runs the node as an RPC ingress node
+/// 
+/// Ingress nodes serve the JSON-RPC API but forward transactions to the central
+/// sequencer's pool, so no L1 listener, local batching, or pool WAL runs here.
+async fn run_ingress(config: Config, remote: RemotePoolConfig, log_filter: LogFilter) -> anyhow::Result<()> {
+    info!("Running as ingress node forwarding to {}", remote.upstream_url);
+    let remote_pool = Arc::new(RemotePool::new(remote)?);
+    
+    // The local registry still backs API keys and quotas for this node's clients
+    let registry = Arc::new(Registry::new(&config.database).await?);
+    let state_cache = StateCache::new();
+    let tx_pool = Arc::new(TransactionPool::default());
+    
+    Server::new(config, state_cache, tx_pool, registry)
+        .with_remote_pool(remote_pool)
+        .with_log_filter(log_filter)
+        .start()
+        .await
+}
+
+/// Load the configured checkpoint into a new node's empty state
+/// 
+/// The checkpoint must be for this chain, and started from the recorded
+/// genesis, if any. Its genesis is recorded otherwise, so a genesis file can
+/// still be configured alongside. A node whose state already holds accounts
+/// keeps it and ignores the checkpoint.
+async fn init_checkpoint(config: &Config, registry: &Registry, state_cache: &StateCache) -> anyhow::Result<()> {
+    let Some(path) = &config.state.checkpoint else {
+        return Ok(());
+    };
+    if state_cache.state_root() != EMPTY_STATE_ROOT {
+        info!("State already present, ignoring checkpoint {}", path);
+        return Ok(());
+    }
+
+    let checkpoint = StateCheckpoint::read(path)?;
+    if checkpoint.chain_id != config.signing.chain_id {
+        anyhow::bail!("checkpoint {} is for chain {}, but signing.chain_id is {}",
+                      path, checkpoint.chain_id, config.signing.chain_id);
+    }
+    match (registry.genesis().await?, &checkpoint.genesis) {
+        (Some(recorded), Some(genesis)) if recorded.genesis_hash != genesis.genesis_hash => {
+            anyhow::bail!("checkpoint {} (genesis {:?}) is not from this chain ({:?})",
+                          path, genesis.genesis_hash, recorded.genesis_hash);
+        }
+        (None, Some(genesis)) => registry.record_genesis(genesis).await?,
+        _ => {}
+    }
+    let state_root = state_cache.import_checkpoint(&checkpoint).await?;
+    info!("Imported checkpoint at batch #{} with {} accounts (state root {:?})",
+          checkpoint.batch_id, checkpoint.accounts.len(), state_root);
+    Ok(())
+}
+
+/// Load the configured genesis into a new chain, and check it against the recorded one
+/// 
+/// The genesis hash is recorded in the registry the first time. Later starts
+/// must use the same genesis file, or none. A state kept in memory only comes
+/// back empty after a restart, so the genesis is loaded into it again.
+async fn init_genesis(config: &Config, registry: &Registry, state_cache: &StateCache) -> anyhow::Result<()> {
+    let recorded = registry.genesis().await?;
+    if let Some(record) = &recorded
+        && record.chain_id != config.signing.chain_id
+    {
+        anyhow::bail!("the database is for chain {}, but signing.chain_id is {}",
+                      record.chain_id, config.signing.chain_id);
+    }
+    let Some(path) = &config.state.genesis else {
+        return Ok(());
+    };
+
+    let genesis = Genesis::load(path)?;
+    if genesis.chain_id != config.signing.chain_id {
+        anyhow::bail!("genesis file {} is for chain {}, but signing.chain_id is {}",
+                      path, genesis.chain_id, config.signing.chain_id);
+    }
+    let genesis_hash = genesis.hash();
+    match recorded {
+        Some(record) if record.genesis_hash != genesis_hash => {
+            anyhow::bail!("genesis file {} (hash {:?}) is not the genesis of this chain ({:?})",
+                          path, genesis_hash, record.genesis_hash);
+        }
+        Some(_) if state_cache.state_root() != EMPTY_STATE_ROOT => return Ok(()),
+        Some(_) => {
+            state_cache.load_genesis(&genesis).await?;
+            info!("Reloaded genesis {:?} into the in-memory state", genesis_hash);
+        }
+        None => {
+            let state_root = state_cache.load_genesis(&genesis).await?;
+            registry.record_genesis(&GenesisRecord {
+                chain_id: genesis.chain_id,
+                genesis_hash,
+                state_root,
+                accounts: genesis.accounts.len() as u64,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            }).await?;
+            info!("Loaded genesis {:?} with {} accounts (state root {:?})",
+                  genesis_hash, genesis.accounts.len(), state_root);
+        }
+    }
+    Ok(())
+}
+
+/// Revalidate recovered pool transactions and put them back in the pool
+/// 
+/// Signatures are re-verified (the log is just a file on disk), and each
+/// sender's nonce is restored past its pending transactions, mirroring the
+/// nonce bump done at original admission.
+async fn restore_pending(
+    tx_pool: &TransactionPool,
+    state_cache: &StateCache,
+    signing: &SigningConfig,
+    recovered: Vec<RecoveredTransaction>,
+) {
+    let validator = Validator::new(state_cache.clone()).with_signing(signing);
+    let mut restored = 0;
+    for entry in recovered {
+        let tx = entry.tx.clone();
+        if let Err(e) = validator.verify_signature(&tx) {
+            tracing::warn!("Dropping recovered transaction {:?}: {}", tx.hash(), e);
+            tx_pool.discard(tx.hash());
+            continue;
+        }
+        if tx_pool.readmit(entry).await {
+            let mut account = state_cache.get_or_init_account(&tx.from).await;
+            account.nonce = account.nonce.max(tx.nonce + 1);
+            state_cache.update(account).await;
+            state_cache.reservations().reserve(tx.from, tx.nonce, tx.hash());
+            restored += 1;
+        }
+    }
+    if restored > 0 {
+        info!("Restored {} pending transactions into the pool", restored);
+    }
+}
+
+/// Look up the value following a `--flag` in the argument list
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|idx| args.get(idx + 1))
+        .map(String::as_str)
+}
+
+/// Runs the `build-batch` subcommand
+/// 
+/// Reads presigned transactions from `--input` (a JSONL file, or `-` for stdin),
+/// validates and schedules them offline, and writes the sealed batch artifact
+/// as JSON to `--output` (or stdout).
+async fn build_batch(args: &[String]) -> anyhow::Result<()> {
+    let config = Config::load(flag_value(args, "--config").unwrap_or("config/default.toml"))?;
+    
+    let input = flag_value(args, "--input")
+        .ok_or_else(|| anyhow::anyhow!("build-batch requires --input <file|->"))?;
+    let transactions = if input == "-" {
+        offline::read_transactions(std::io::stdin().lock())?
+    } else {
+        offline::read_transactions(BufReader::new(std::fs::File::open(input)?))?
+    };
+    info!("Loaded {} transactions from {}", transactions.len(), input);
+    
+    // Optional initial account state (JSON array of AccountState)
+    let accounts: Vec<AccountState> = match flag_value(args, "--state") {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+    
+    let artifact = offline::build_batches(&config, accounts, transactions).await?;
+    info!("Built {} batches ({} transactions rejected)",
+          artifact.batches.len(),
+          artifact.rejected.len());
+    
+    let json = serde_json::to_string_pretty(&artifact)?;
+    match flag_value(args, "--output") {
+        Some(path) => std::fs::write(path, json)?,
+        None => writeln!(std::io::stdout(), "{}", json)?,
+    }
+    
+    Ok(())
+}
+
+/// Runs the `export-vectors` subcommand
+/// 
+/// Writes the canonical test vector set as JSON to `--output` (or stdout).
+fn export_vectors(args: &[String]) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(&vectors::generate()?)?;
+    match flag_value(args, "--output") {
+        Some(path) => std::fs::write(path, json)?,
+        None => writeln!(std::io::stdout(), "{}", json)?,
+    }
+    Ok(())
+}
+
+/// Runs the `verify-vectors` subcommand
+/// 
+/// Checks the vector set in `--input` and logs every mismatch. Exits with
+/// status 1 if any vector does not verify.
+fn verify_vectors(args: &[String]) -> anyhow::Result<()> {
+    let input = flag_value(args, "--input")
+        .ok_or_else(|| anyhow::anyhow!("verify-vectors requires --input <file>"))?;
+    let set: vectors::TestVectors = serde_json::from_str(&std::fs::read_to_string(input)?)?;
+    
+    let failures = vectors::verify(&set);
+    for failure in &failures {
+        tracing::error!("Test vector failed: {}", failure);
+    }
+    if !failures.is_empty() {
+        // A failed check, not an error of the tool: exit non-zero without a backtrace
+        tracing::error!("{} test vectors failed", failures.len());
+        std::process::exit(1);
+    }
+    
+    info!("{} transaction, {} account and {} batch vectors verified",
+          set.transactions.len(), set.accounts.len(), set.batches.len());
+    Ok(())
+}
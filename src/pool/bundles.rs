@@ -0,0 +1,118 @@
+//! Bundle Lane Module
+//!
+//! Bundles are ordered lists of transactions that must be sealed together,
+//! contiguously and in order, or not at all. They wait in their own lane
+//! rather than the transaction pool, whose fee ranking, replacement and
+//! eviction work on single transactions. The lane is a plain FIFO queue of
+//! whole bundles, so a sender's bundles leave it in nonce order.
+//!
+//! The lane is held in memory only: pending bundles are lost on restart.
+
+use crate::{Bundle, PoolError};
+use ethers::types::{Address, H256, U256};
+use std::collections::VecDeque;
+use tokio::sync::RwLock;
+
+/// Pending bundles, in arrival order
+pub struct BundlePool {
+    bundles: RwLock<VecDeque<Bundle>>,
+    /// Most bundles the lane holds
+    capacity: usize,
+}
+
+impl BundlePool {
+    /// Creates an empty lane holding up to `capacity` bundles
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            bundles: RwLock::new(VecDeque::new()),
+            capacity,
+        }
+    }
+
+    /// Add a validated bundle to the back of the lane
+    ///
+    /// # Returns
+    /// * `Ok(())` if the bundle was queued
+    /// * `Err(PoolError::AlreadyKnown)` if an identical bundle is pending
+    /// * `Err(PoolError::BundleLaneFull)` if the lane is at capacity
+    pub async fn add(&self, bundle: Bundle) -> Result<(), PoolError> {
+        let mut bundles = self.bundles.write().await;
+        let bundle_hash = bundle.hash();
+        if bundles.iter().any(|pending| pending.hash() == bundle_hash) {
+            return Err(PoolError::AlreadyKnown { tx_hash: bundle_hash });
+        }
+        if bundles.len() >= self.capacity {
+            return Err(PoolError::BundleLaneFull { capacity: self.capacity });
+        }
+        bundles.push_back(bundle);
+        Ok(())
+    }
+
+    /// Check whether a bundle with this hash is pending
+    pub async fn contains(&self, bundle_hash: &H256) -> bool {
+        self.bundles.read().await.iter().any(|bundle| bundle.hash() == *bundle_hash)
+    }
+
+    /// Check whether a pending bundle holds the transaction with this hash
+    pub async fn contains_transaction(&self, tx_hash: &H256) -> bool {
+        self.bundles
+            .read()
+            .await
+            .iter()
+            .any(|bundle| bundle.transactions.iter().any(|tx| tx.hash() == *tx_hash))
+    }
+
+    /// Number of pending bundles
+    pub async fn len(&self) -> usize {
+        self.bundles.read().await.len()
+    }
+
+    /// Check whether the lane is empty
+    pub async fn is_empty(&self) -> bool {
+        self.bundles.read().await.is_empty()
+    }
+
+    /// Remove and return whole bundles from the front of the lane, holding at
+    /// most `max` transactions together
+    ///
+    /// Stops at the first bundle that would go over, so bundles leave in order.
+    pub async fn take(&self, max: usize) -> Vec<Bundle> {
+        let mut bundles = self.bundles.write().await;
+        let mut room = max;
+        let mut count = 0;
+        while let Some(bundle) = bundles.get(count)
+            && bundle.len() <= room
+        {
+            room -= bundle.len();
+            count += 1;
+        }
+        bundles.drain(..count).collect()
+    }
+
+    /// Return bundles to the front of the lane, keeping their order
+    ///
+    /// Used when a batch could not fit them, or failed after sealing. The
+    /// capacity is not enforced, so nothing already validated is dropped.
+    pub async fn requeue(&self, requeued: Vec<Bundle>) {
+        let mut bundles = self.bundles.write().await;
+        for bundle in requeued.into_iter().rev() {
+            bundles.push_front(bundle);
+        }
+    }
+
+    /// Check whether `sender` has transactions in pending bundles
+    pub async fn has_pending(&self, sender: &Address) -> bool {
+        self.bundles.read().await.iter().any(|bundle| bundle.involves(sender))
+    }
+
+    /// Maximum cost of `sender`'s transactions in pending bundles
+    pub async fn pending_spend(&self, sender: &Address) -> U256 {
+        self.bundles
+            .read()
+            .await
+            .iter()
+            .flat_map(|bundle| &bundle.transactions)
+            .filter(|tx| tx.from == *sender)
+            .fold(U256::zero(), |spend, tx| spend.saturating_add(tx.max_cost()))
+    }
+}
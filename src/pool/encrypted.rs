@@ -0,0 +1,206 @@
+//! Encrypted Lane Module
+//!
+//! Encrypted transactions wait in their own lane, unreadable by the
+//! sequencer, so their contents cannot be front-run. Time is split into
+//! epochs of `epoch_ms`, and each transaction is encrypted to an epoch that
+//! has not ended yet (see the threshold encryption module). Its place in the
+//! lane is fixed on arrival and committed to in the receipt, a hash chain
+//! over every encrypted transaction before it.
+//!
+//! Once its epoch is over and the epoch's decryption key is known, a
+//! transaction is ready: the orchestrator takes the ready ones in lane order
+//! and decrypts them as it seals the next batch, placing them right after
+//! the forced transactions. Keys are released by the keypers through
+//! `submitDecryptionKey`, or by the sequencer itself in timelock mode.
+//! Transactions whose key does not come within `key_timeout_epochs` are
+//! dropped.
+//!
+//! The lane is held in memory only: pending transactions are lost on restart.
+
+use super::threshold::{derive_epoch_key, master_public_key, validate_master_key, verify_epoch_key};
+use crate::config::EncryptedMempoolConfig;
+use crate::{EncryptedTransaction, EncryptionReceipt, PoolError, ValidationError};
+use ethers::types::{Bytes, H256};
+use ethers::utils::keccak256;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Encrypted transactions in arrival order, with the commitment to that order
+#[derive(Default)]
+struct Lane {
+    /// Pending transactions and their hashes
+    pending: VecDeque<(H256, EncryptedTransaction)>,
+    /// Position of the next arrival
+    next_position: u64,
+    /// Hash chain over every arrival so far
+    commitment: H256,
+}
+
+/// Pending encrypted transactions and the epoch keys that open them
+pub struct EncryptedPool {
+    /// Epochs and lane limits
+    config: EncryptedMempoolConfig,
+    /// Master public key transactions are encrypted to
+    master_key: Bytes,
+    lane: Mutex<Lane>,
+    /// Decryption keys released so far, by epoch
+    keys: Mutex<BTreeMap<u64, Bytes>>,
+}
+
+impl EncryptedPool {
+    /// Creates an empty lane
+    ///
+    /// # Returns
+    /// An error if the master public key is missing or malformed, or does
+    /// not match the configured decryption seed
+    pub fn new(config: EncryptedMempoolConfig) -> Result<Self, ValidationError> {
+        let master_key = match (&config.master_public_key, &config.decryption_seed) {
+            (Some(key), Some(seed)) if *key != master_public_key(seed)? => {
+                return Err(ValidationError::InvalidEncryption {
+                    reason: "master_public_key does not match decryption_seed".to_string(),
+                });
+            }
+            (Some(key), _) => key.clone(),
+            (None, Some(seed)) => master_public_key(seed)?,
+            (None, None) => {
+                return Err(ValidationError::InvalidEncryption {
+                    reason: "master_public_key or decryption_seed is required".to_string(),
+                });
+            }
+        };
+        validate_master_key(&master_key)?;
+        Ok(Self {
+            config,
+            master_key,
+            lane: Mutex::new(Lane::default()),
+            keys: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Master public key transactions are encrypted to (48 bytes compressed)
+    pub fn master_key(&self) -> &Bytes {
+        &self.master_key
+    }
+
+    /// Length of an epoch, in milliseconds
+    pub fn epoch_ms(&self) -> u64 {
+        self.config.epoch_ms
+    }
+
+    /// Epoch the local clock is in
+    pub fn current_epoch(&self) -> u64 {
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        now_ms / self.config.epoch_ms.max(1)
+    }
+
+    /// Add an encrypted transaction to the back of the lane
+    ///
+    /// # Returns
+    /// * `Ok(EncryptionReceipt)` with its position and the commitment to it
+    /// * `Err(PoolError::EpochOutOfRange)` if its epoch is over, or more than
+    ///   `max_future_epochs` ahead
+    /// * `Err(PoolError::AlreadyKnown)` if it is already pending
+    /// * `Err(PoolError::EncryptedLaneFull)` if the lane is at capacity
+    pub fn add(&self, encrypted: EncryptedTransaction) -> Result<EncryptionReceipt, PoolError> {
+        let min_epoch = self.current_epoch();
+        let max_epoch = min_epoch.saturating_add(self.config.max_future_epochs);
+        if encrypted.epoch < min_epoch || encrypted.epoch > max_epoch {
+            return Err(PoolError::EpochOutOfRange { epoch: encrypted.epoch, min_epoch, max_epoch });
+        }
+
+        let tx_hash = encrypted.hash();
+        let mut lane = self.lane.lock().unwrap();
+        if lane.pending.iter().any(|(pending, _)| *pending == tx_hash) {
+            return Err(PoolError::AlreadyKnown { tx_hash });
+        }
+        if lane.pending.len() >= self.config.lane_capacity {
+            return Err(PoolError::EncryptedLaneFull { capacity: self.config.lane_capacity });
+        }
+        let receipt = EncryptionReceipt {
+            tx_hash,
+            epoch: encrypted.epoch,
+            position: lane.next_position,
+            commitment: H256::from(keccak256([lane.commitment.as_bytes(), tx_hash.as_bytes()].concat())),
+        };
+        lane.next_position += 1;
+        lane.commitment = receipt.commitment;
+        lane.pending.push_back((tx_hash, encrypted));
+        Ok(receipt)
+    }
+
+    /// Record the decryption key of an epoch
+    ///
+    /// # Returns
+    /// * `Ok(())` if it is the epoch's key under the master key
+    /// * `Err(ValidationError::InvalidEncryption)` otherwise
+    pub fn submit_key(&self, epoch: u64, key: Bytes) -> Result<(), ValidationError> {
+        verify_epoch_key(&self.master_key, epoch, &key)?;
+        self.keys.lock().unwrap().insert(epoch, key);
+        Ok(())
+    }
+
+    /// Decryption key of an epoch, if released
+    ///
+    /// In timelock mode, the sequencer derives the key of any epoch that is over.
+    pub fn epoch_key(&self, epoch: u64) -> Option<Bytes> {
+        if let Some(key) = self.keys.lock().unwrap().get(&epoch) {
+            return Some(key.clone());
+        }
+        let seed = self.config.decryption_seed.filter(|_| epoch < self.current_epoch())?;
+        let key = derive_epoch_key(&seed, epoch).ok()?;
+        self.keys.lock().unwrap().insert(epoch, key.clone());
+        Some(key)
+    }
+
+    /// Number of pending encrypted transactions
+    pub fn len(&self) -> usize {
+        self.lane.lock().unwrap().pending.len()
+    }
+
+    /// Check whether the lane is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove and return up to `max` ready transactions, in lane order, with their epoch keys
+    ///
+    /// A transaction is ready once its epoch is over and the epoch's key is
+    /// known. `fits` is asked, in order, whether each one's `gas_limit` fits
+    /// the batch; once one doesn't, later ones wait too. Transactions still
+    /// without a key `key_timeout_epochs` after their epoch are dropped.
+    pub fn take_ready(&self, max: usize, mut fits: impl FnMut(u64) -> bool) -> Vec<(EncryptedTransaction, Bytes)> {
+        let current = self.current_epoch();
+        let mut lane = self.lane.lock().unwrap();
+        let mut ready = Vec::new();
+        let mut waiting = VecDeque::with_capacity(lane.pending.len());
+        let mut expired = 0;
+        let mut full = false;
+        for (tx_hash, encrypted) in lane.pending.drain(..) {
+            if encrypted.epoch >= current {
+                waiting.push_back((tx_hash, encrypted));
+                continue;
+            }
+            match self.epoch_key(encrypted.epoch) {
+                Some(key) if !full && ready.len() < max && fits(encrypted.gas_limit) => ready.push((encrypted, key)),
+                Some(_) => {
+                    full = true;
+                    waiting.push_back((tx_hash, encrypted));
+                }
+                None if encrypted.epoch.saturating_add(self.config.key_timeout_epochs) < current => expired += 1,
+                None => waiting.push_back((tx_hash, encrypted)),
+            }
+        }
+        lane.pending = waiting;
+        drop(lane);
+
+        if expired > 0 {
+            warn!("Dropped {} encrypted transactions whose epoch key never came", expired);
+        }
+        // Keys of epochs whose transactions are all sealed or dropped
+        let oldest = current.saturating_sub(self.config.key_timeout_epochs.saturating_add(1));
+        self.keys.lock().unwrap().retain(|&epoch, _| epoch >= oldest);
+        ready
+    }
+}
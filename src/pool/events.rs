@@ -0,0 +1,35 @@
+//! Pool Events Module
+//! 
+//! Typed notifications emitted by the transaction pool on a broadcast channel,
+//! so other components can react to pool changes without polling.
+
+use crate::UserTransaction;
+use ethers::types::H256;
+
+/// Capacity of the pool event broadcast channel
+/// 
+/// Slow subscribers that fall further behind than this miss events
+/// (they observe a `RecvError::Lagged`), which never blocks the pool.
+/// Subscribers that must see every removal use
+/// `TransactionPool::subscribe_removals` instead.
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Event emitted by the transaction pool
+#[derive(Debug, Clone)]
+pub enum PoolEvent {
+    /// Transaction admitted to the pool, or returned to it after being taken
+    Added(UserTransaction),
+    /// Pending transaction superseded by one with the same sender and nonce
+    /// 
+    /// The pool does not replace transactions yet; reserved for replace-by-fee.
+    Replaced { old_hash: H256, new: UserTransaction },
+    /// Transaction dropped to make room for a better-paying one
+    Evicted(UserTransaction),
+    /// Transaction taken from the pool for a batch
+    Batched(UserTransaction),
+    /// Transaction waited longer than the configured TTL and was dropped
+    Expired(UserTransaction),
+    /// Transaction dropped because an earlier nonce of its sender was given
+    /// back, leaving it unable to execute
+    Dropped(UserTransaction),
+}
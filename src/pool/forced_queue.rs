@@ -3,10 +3,32 @@
 //! This module implements a queue for forced transactions from Layer 1.
 //! Forced transactions (deposits and forced exits) must be included in batches
 //! to maintain censorship resistance.
+//! 
+//! Each L1 event is queued at most once, identified by its L1 transaction hash
+//! and log index, so a listener restart or block re-scan cannot enqueue the
+//! same deposit twice. With a registry attached, the queue and the set of seen
+//! events survive restarts.
+//! 
+//! The queue also follows the L1 head, as reported by the listener, so the
+//! orchestrator can tell how close queued transactions are to their
+//! forced-inclusion deadline.
+//! 
+//! Events reorged out of L1 before being batched are removed again
+//! (`remove_reorged`, or `remove_after` for every block above a fork point)
+//! and forgotten, so they are queued anew if they reappear.
+//! 
+//! # Backpressure
+//! Forced transactions can never be dropped, so the capacity limit is enforced
+//! on the producer side: once the queue is full the L1 listener stops pulling
+//! events (`wait_for_space`) until a batch drains the queue.
 
-use crate::ForcedTransaction;
-use std::collections::VecDeque;
-use tokio::sync::RwLock;
+use crate::{registry::Registry, ForcedTransaction};
+use ethers::types::H256;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, RwLock};
+use tracing::warn;
 
 /// Queue for forced transactions from L1
 /// 
@@ -16,6 +38,18 @@ use tokio::sync::RwLock;
 pub struct ForcedQueue {
     /// Queue of forced transactions, protected by a read-write lock
     transactions: RwLock<VecDeque<ForcedTransaction>>,
+    /// Signals the orchestrator that an express-lane transaction is waiting
+    express: Notify,
+    /// L1 events seen so far (without a registry only)
+    seen: Mutex<HashSet<(H256, u64)>>,
+    /// Persistent record of queued and included forced transactions
+    registry: Option<Arc<Registry>>,
+    /// Queue length at which ingestion should pause
+    capacity: usize,
+    /// Signals a paused producer that the queue was drained
+    space: Notify,
+    /// Latest L1 head seen by the listener (0 until the first)
+    l1_head: AtomicU64,
 }
 
 impl ForcedQueue {
@@ -23,20 +57,218 @@ impl ForcedQueue {
     pub fn new() -> Self {
         Self {
             transactions: RwLock::new(VecDeque::new()),
+            express: Notify::new(),
+            seen: Mutex::new(HashSet::new()),
+            registry: None,
+            capacity: usize::MAX,
+            space: Notify::new(),
+            l1_head: AtomicU64::new(0),
+        }
+    }
+    
+    /// Report the queue as full once it holds `capacity` transactions
+    /// 
+    /// The queue is unbounded by default.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+    
+    /// Number of queued forced transactions
+    pub async fn len(&self) -> usize {
+        self.transactions.read().await.len()
+    }
+    
+    /// Check whether the queue has no forced transactions
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+    
+    /// L1 block of the oldest queued forced transaction
+    pub async fn oldest_l1_block(&self) -> Option<u64> {
+        self.transactions.read().await.iter().map(|tx| tx.l1_block_number).min()
+    }
+    
+    /// Record the latest L1 head, called by the L1 listener
+    pub fn set_l1_head(&self, number: u64) {
+        self.l1_head.store(number, Ordering::Relaxed);
+    }
+    
+    /// Latest L1 head, if the listener has seen one
+    pub fn l1_head(&self) -> Option<u64> {
+        Some(self.l1_head.load(Ordering::Relaxed)).filter(|&number| number > 0)
+    }
+    
+    /// Check whether the queue has reached its capacity
+    pub async fn is_full(&self) -> bool {
+        self.len().await >= self.capacity
+    }
+    
+    /// Wait until the queue is below its capacity
+    /// 
+    /// Producers call this before ingesting more events. Returns immediately
+    /// if there is room.
+    pub async fn wait_for_space(&self) {
+        loop {
+            // Registered before the check, so a drain in between is not missed
+            let drained = self.space.notified();
+            if !self.is_full().await {
+                return;
+            }
+            drained.await;
         }
     }
     
+    /// Persist queued transactions and seen L1 events in `registry`
+    /// 
+    /// Call [`Self::restore`] afterwards to reload what was queued before a restart.
+    pub fn with_registry(mut self, registry: Arc<Registry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+    
+    /// Reload forced transactions that were queued but not batched before a restart
+    /// 
+    /// # Returns
+    /// The number of restored transactions
+    pub async fn restore(&self) -> anyhow::Result<usize> {
+        let Some(registry) = &self.registry else {
+            return Ok(0);
+        };
+        let restored = registry.queued_forced_transactions().await?;
+        let count = restored.len();
+        let express = restored.iter().any(ForcedTransaction::is_express);
+        
+        self.transactions.write().await.extend(restored);
+        if express {
+            self.express.notify_one();
+        }
+        Ok(count)
+    }
+    
     /// Add a forced transaction from L1
     /// 
     /// Called by the L1 listener when it detects a deposit or forced exit event.
     /// These transactions are added to the queue to be included in the next batch.
+    /// Express-lane transactions additionally wake the orchestrator so a batch is
+    /// sealed immediately. The capacity is not enforced here (see `wait_for_space`).
     /// 
     /// # Arguments
     /// * `tx` - The forced transaction to add
-    pub async fn add(&self, tx: ForcedTransaction) {
+    /// 
+    /// # Returns
+    /// `false` if the same L1 event was queued before (the transaction is dropped)
+    pub async fn add(&self, tx: ForcedTransaction) -> bool {
+        if !self.first_sighting(&tx).await {
+            return false;
+        }
+        let express = tx.is_express();
+        
         // Acquire write lock to add transaction
         let mut txs = self.transactions.write().await;
         txs.push_back(tx);
+        drop(txs);
+        
+        if express {
+            // Stores a permit if the orchestrator isn't waiting right now
+            self.express.notify_one();
+        }
+        true
+    }
+    
+    /// Remember an L1 event, returning whether it was new
+    /// 
+    /// If the registry is unreachable the event is queued anyway: a possible
+    /// duplicate is safer than dropping a deposit.
+    async fn first_sighting(&self, tx: &ForcedTransaction) -> bool {
+        match &self.registry {
+            Some(registry) => registry.record_forced_transaction(tx).await.unwrap_or_else(|e| {
+                warn!("Failed to persist forced transaction {:?}: {:?}", tx.l1_tx_hash, e);
+                true
+            }),
+            None => self.seen.lock().unwrap().insert(tx.l1_event_key()),
+        }
+    }
+    
+    /// Remove queued forced transactions whose L1 event was reorged out
+    /// 
+    /// Called by the L1 listener when it sees a removed log. Matches every event
+    /// of L1 transaction `l1_tx_hash` in block `l1_block_number`. Events that
+    /// were already taken for a batch are not affected.
+    /// 
+    /// # Returns
+    /// The removed transactions, in queue order
+    pub async fn remove_reorged(&self, l1_block_number: u64, l1_tx_hash: H256) -> Vec<ForcedTransaction> {
+        self.remove_where(|tx| tx.l1_block_number == l1_block_number && tx.l1_tx_hash == l1_tx_hash).await
+    }
+    
+    /// Remove queued forced transactions from L1 blocks above `l1_block_number`
+    /// 
+    /// Called by the L1 listener when a reorg replaced every block above that
+    /// one. Events that were already taken for a batch are not affected.
+    /// 
+    /// # Returns
+    /// The removed transactions, in queue order
+    pub async fn remove_after(&self, l1_block_number: u64) -> Vec<ForcedTransaction> {
+        self.remove_where(|tx| tx.l1_block_number > l1_block_number).await
+    }
+    
+    /// Remove and forget the queued forced transactions matching `reorged`
+    async fn remove_where(&self, reorged: impl Fn(&ForcedTransaction) -> bool) -> Vec<ForcedTransaction> {
+        let mut queued = self.transactions.write().await;
+        let (removed, kept): (VecDeque<_>, VecDeque<_>) = queued.drain(..).partition(|tx| reorged(tx));
+        *queued = kept;
+        drop(queued);
+        
+        if removed.is_empty() {
+            return Vec::new();
+        }
+        let removed: Vec<ForcedTransaction> = removed.into();
+        self.forget(&removed).await;
+        self.space.notify_waiters();
+        removed
+    }
+    
+    /// Forget L1 events, so they are queued again if seen later
+    async fn forget(&self, txs: &[ForcedTransaction]) {
+        match &self.registry {
+            Some(registry) => {
+                if let Err(e) = registry.forget_forced_transactions(txs).await {
+                    warn!("Failed to delete reorged forced transactions: {:?}", e);
+                }
+            }
+            None => {
+                let mut seen = self.seen.lock().unwrap();
+                for tx in txs {
+                    seen.remove(&tx.l1_event_key());
+                }
+            }
+        }
+    }
+    
+    /// Wait until an express-lane transaction is queued
+    /// 
+    /// Used by the orchestrator to seal a batch without waiting for the timeout.
+    pub async fn express_ready(&self) {
+        self.express.notified().await;
+    }
+    
+    /// Return forced transactions to the front of the queue, keeping their order
+    /// 
+    /// Used when a batch containing them failed to post or was reverted:
+    /// forced transactions must still be included, ahead of anything queued since.
+    pub async fn requeue(&self, txs: Vec<ForcedTransaction>) {
+        let express = txs.iter().any(ForcedTransaction::is_express);
+        
+        let mut queued = self.transactions.write().await;
+        for tx in txs.into_iter().rev() {
+            queued.push_front(tx);
+        }
+        drop(queued);
+        
+        if express {
+            self.express.notify_one();
+        }
     }
     
     /// Get all forced transactions and clear the queue
@@ -51,6 +283,17 @@ impl ForcedQueue {
         // Acquire write lock to drain all transactions
         let mut txs = self.transactions.write().await;
         // Drain all transactions (clear the queue)
-        txs.drain(..).collect()
+        let drained = txs.drain(..).collect();
+        drop(txs);
+        
+        // Resume a producer paused on a full queue
+        self.space.notify_waiters();
+        drained
     }
-}
\ No newline at end of file
+}
+
+impl Default for ForcedQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,144 @@
+//! Forwarding Protocol Module
+//!
+//! The gRPC service ingress nodes forward transactions to the central
+//! sequencer over. The sequencer serves it on its API port, alongside
+//! JSON-RPC; [`super::RemotePool`] is the ingress side.
+//!
+//! # Schema
+//! ```text
+//! package sequencer;
+//!
+//! service Ingress {
+//!   rpc SendTransaction(ForwardRequest) returns (ForwardReply);
+//! }
+//!
+//! message ForwardRequest {
+//!   bytes transaction = 1;   // JSON, as `sendTransaction` takes it
+//! }
+//!
+//! message ForwardReply {
+//!   bytes confirmation = 1;  // JSON soft confirmation, empty if refused
+//!   int32 error_code = 2;    // JSON-RPC error code of a refusal, 0 if none
+//!   string error_message = 3;
+//! }
+//! ```
+//!
+//! The transaction and confirmation keep their JSON form, so both ends agree
+//! on them wherever the JSON-RPC API does. Refusals carry the JSON-RPC error
+//! code the sequencer would have answered, for the ingress node to pass on
+//! unchanged. The API key goes in the `x-api-key` metadata entry.
+
+use async_trait::async_trait;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{http, Body, BoxFuture, Service, StdError};
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::transport::Channel;
+
+/// Name of the forwarding service
+const SERVICE_NAME: &str = "sequencer.Ingress";
+
+/// Path of its `SendTransaction` method
+const SEND_TRANSACTION_PATH: &str = "/sequencer.Ingress/SendTransaction";
+
+/// A transaction forwarded by an ingress node
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ForwardRequest {
+    /// The transaction, JSON-encoded
+    #[prost(bytes = "vec", tag = "1")]
+    pub transaction: Vec<u8>,
+}
+
+/// The sequencer's answer to a forwarded transaction
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ForwardReply {
+    /// The soft confirmation, JSON-encoded (empty if refused)
+    #[prost(bytes = "vec", tag = "1")]
+    pub confirmation: Vec<u8>,
+    /// JSON-RPC error code of a refusal (0 if none)
+    #[prost(int32, tag = "2")]
+    pub error_code: i32,
+    /// Reason for a refusal
+    #[prost(string, tag = "3")]
+    pub error_message: String,
+}
+
+/// Answers forwarded transactions on the sequencer
+#[async_trait]
+pub trait ForwardHandler: Send + Sync + 'static {
+    /// Admit a forwarded transaction as `sendTransaction` would
+    async fn send_transaction(&self, request: tonic::Request<ForwardRequest>) -> ForwardReply;
+}
+
+/// gRPC service dispatching forwarded transactions to a [`ForwardHandler`]
+pub struct ForwardServer<T> {
+    handler: Arc<T>,
+}
+
+impl<T> ForwardServer<T> {
+    /// Serves forwarded transactions to `handler`
+    pub fn new(handler: T) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+}
+
+impl<T> Clone for ForwardServer<T> {
+    fn clone(&self) -> Self {
+        Self { handler: self.handler.clone() }
+    }
+}
+
+impl<T> NamedService for ForwardServer<T> {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+/// The `SendTransaction` method of a handler
+struct SendTransaction<T>(Arc<T>);
+
+impl<T: ForwardHandler> UnaryService<ForwardRequest> for SendTransaction<T> {
+    type Response = ForwardReply;
+    type Future = BoxFuture<tonic::Response<ForwardReply>, tonic::Status>;
+
+    fn call(&mut self, request: tonic::Request<ForwardRequest>) -> Self::Future {
+        let handler = self.0.clone();
+        Box::pin(async move { Ok(tonic::Response::new(handler.send_transaction(request).await)) })
+    }
+}
+
+impl<T, B> Service<http::Request<B>> for ForwardServer<T>
+where
+    T: ForwardHandler,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if request.uri().path() != SEND_TRANSACTION_PATH {
+            return Box::pin(async { Ok(tonic::Status::unimplemented("unknown method").into_http()) });
+        }
+        let method = SendTransaction(self.handler.clone());
+        Box::pin(async move { Ok(Grpc::new(ProstCodec::default()).unary(method, request).await) })
+    }
+}
+
+/// Call `SendTransaction` on the sequencer at the other end of `channel`
+pub(super) async fn send_transaction(
+    channel: Channel,
+    request: tonic::Request<ForwardRequest>,
+) -> Result<ForwardReply, tonic::Status> {
+    let mut client = tonic::client::Grpc::new(channel);
+    client.ready().await.map_err(|e| tonic::Status::unavailable(e.to_string()))?;
+    let path = http::uri::PathAndQuery::from_static(SEND_TRANSACTION_PATH);
+    let response = client.unary(request, path, ProstCodec::default()).await?;
+    Ok(response.into_inner())
+}
@@ -0,0 +1,84 @@
+//! Pool Metrics Module
+//!
+//! Point-in-time gauges describing the transaction pool backlog, rendered in
+//! the Prometheus text exposition format for the `/metrics` endpoint.
+
+use ethers::types::U256;
+use std::fmt::Write;
+
+/// Quantiles reported for age and fee distributions
+pub const QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Snapshot of the pool backlog
+///
+/// Distributions are `(quantile, value)` pairs for each of [`QUANTILES`],
+/// and are empty when the pool is empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolMetrics {
+    /// Number of pending transactions
+    pub depth: usize,
+    /// Age of the oldest pending transaction, in milliseconds (0 if empty)
+    pub oldest_age_ms: u64,
+    /// Pending transaction ages, in milliseconds
+    pub age_percentiles_ms: Vec<(f64, u64)>,
+    /// Pending transaction gas prices, in wei
+    pub fee_percentiles: Vec<(f64, U256)>,
+}
+
+impl PoolMetrics {
+    /// Build a snapshot from ages and gas prices, both sorted ascending
+    pub(crate) fn from_sorted(ages_ms: &[u64], fees: &[U256]) -> Self {
+        Self {
+            depth: fees.len(),
+            oldest_age_ms: ages_ms.last().copied().unwrap_or(0),
+            age_percentiles_ms: percentiles(ages_ms),
+            fee_percentiles: percentiles(fees),
+        }
+    }
+
+    /// Render the snapshot as Prometheus gauges
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# HELP sequencer_pool_depth Pending transactions in the pool");
+        let _ = writeln!(out, "# TYPE sequencer_pool_depth gauge");
+        let _ = writeln!(out, "sequencer_pool_depth {}", self.depth);
+
+        let _ = writeln!(out, "# HELP sequencer_pool_oldest_age_seconds Age of the oldest pending transaction");
+        let _ = writeln!(out, "# TYPE sequencer_pool_oldest_age_seconds gauge");
+        let _ = writeln!(out, "sequencer_pool_oldest_age_seconds {}", seconds(self.oldest_age_ms));
+
+        let _ = writeln!(out, "# HELP sequencer_pool_age_seconds Age distribution of pending transactions");
+        let _ = writeln!(out, "# TYPE sequencer_pool_age_seconds gauge");
+        for (quantile, age_ms) in &self.age_percentiles_ms {
+            let _ = writeln!(out, "sequencer_pool_age_seconds{{quantile=\"{}\"}} {}", quantile, seconds(*age_ms));
+        }
+
+        let _ = writeln!(out, "# HELP sequencer_pool_gas_price_wei Gas price distribution of pending transactions");
+        let _ = writeln!(out, "# TYPE sequencer_pool_gas_price_wei gauge");
+        for (quantile, fee) in &self.fee_percentiles {
+            let _ = writeln!(out, "sequencer_pool_gas_price_wei{{quantile=\"{}\"}} {}", quantile, fee);
+        }
+
+        out
+    }
+}
+
+/// Nearest-rank percentiles of an ascending slice
+fn percentiles<T: Copy>(sorted: &[T]) -> Vec<(f64, T)> {
+    if sorted.is_empty() {
+        return Vec::new();
+    }
+    QUANTILES
+        .iter()
+        .map(|&q| {
+            let rank = (q * sorted.len() as f64).ceil() as usize;
+            (q, sorted[rank.clamp(1, sorted.len()) - 1])
+        })
+        .collect()
+}
+
+/// Milliseconds as fractional seconds
+fn seconds(ms: u64) -> f64 {
+    ms as f64 / 1000.0
+}
@@ -3,9 +3,45 @@
 //! This module manages pools for pending transactions:
 //! - Normal user transactions waiting to be batched
 //! - Forced transactions from L1 (deposits and forced exits)
+//! - Pool events (expiry notifications) for other components to react to
+//! - A write-ahead log that persists pending transactions across restarts
+//! - Source traits that decouple the batch orchestrator from concrete pools
+//! - A remote pool client for RPC ingress nodes forwarding to a central sequencer,
+//!   and the gRPC service the sequencer takes forwarded transactions on
+//! - Backlog metrics (depth, age and fee distributions)
+//! - Snapshots of the pending pool for migration and post-mortems
+//! - A separate lane for user operations from smart-contract wallets
+//! - An encrypted lane, decrypted by epoch once its ordering is fixed
+//! - A lane for atomic bundles, sealed whole or not at all
 
 mod tx_pool;
 mod forced_queue;
+mod events;
+mod wal;
+mod source;
+mod remote;
+mod forwarding;
+mod metrics;
+mod snapshot;
+mod user_ops;
+mod threshold;
+mod encrypted;
+mod bundles;
 
-pub use tx_pool::TransactionPool;
-pub use forced_queue::ForcedQueue;
\ No newline at end of file
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub use tx_pool::{TransactionPool, PoolView};
+pub use forced_queue::ForcedQueue;
+pub use events::{PoolEvent, EVENT_CHANNEL_CAPACITY};
+pub use wal::RecoveredTransaction;
+pub use source::{PoolSource, ForcedSource};
+pub use remote::RemotePool;
+pub use forwarding::{ForwardHandler, ForwardReply, ForwardRequest, ForwardServer};
+pub use metrics::PoolMetrics;
+pub use snapshot::{PoolSnapshot, PoolImportReport, RejectedImport};
+pub use user_ops::UserOpPool;
+pub use threshold::{encrypt_transaction, decrypt_transaction, derive_epoch_key, master_public_key, verify_epoch_key};
+pub use encrypted::EncryptedPool;
+pub use bundles::BundlePool;
@@ -0,0 +1,164 @@
+//! Remote Pool Module
+//!
+//! This module lets an RPC ingress node act as a front for a central
+//! sequencer's transaction pool. Intake scales horizontally across ingress
+//! nodes while ordering stays centralized.
+//!
+//! # Forwarding
+//! Transactions are sent to the central sequencer's gRPC `SendTransaction`
+//! method (see [`super::forwarding`]), which performs full validation (nonce,
+//! balance) and pool admission like JSON-RPC `sendTransaction`. The upstream
+//! soft confirmation is returned to the client unchanged.
+//!
+//! # Dedup and Backpressure
+//! - Recently forwarded hashes are remembered, so client retries are answered
+//!   locally instead of costing an upstream round trip
+//! - At most `max_in_flight` forwards run at once; further submissions are
+//!   refused immediately so clients back off rather than queueing on the node
+
+use super::forwarding::{self, ForwardRequest};
+use crate::{config::RemotePoolConfig, ConfirmationStatus, RemoteError, SoftConfirmation, UserTransaction};
+use ethers::types::H256;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use tokio::sync::Semaphore;
+use tokio::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+use tracing::debug;
+
+/// JSON-RPC error code the sequencer returns for duplicate submissions
+const UPSTREAM_ALREADY_KNOWN: i32 = -32004;
+
+/// Bounded set of recently forwarded transaction hashes (oldest forgotten first)
+#[derive(Default)]
+struct RecentHashes {
+    /// Hashes in insertion order, for eviction
+    order: VecDeque<H256>,
+    /// Hashes for lookup
+    set: HashSet<H256>,
+}
+
+/// Client for a central sequencer's transaction pool
+pub struct RemotePool {
+    /// gRPC channel to the upstream sequencer (connects lazily, per-request timeout)
+    channel: Channel,
+    /// Remote pool configuration
+    config: RemotePoolConfig,
+    /// Limits concurrent forwards
+    in_flight: Semaphore,
+    /// Recently forwarded (or in-flight) transaction hashes
+    recent: Mutex<RecentHashes>,
+}
+
+impl RemotePool {
+    /// Creates a client for the configured upstream sequencer
+    ///
+    /// # Arguments
+    /// * `config` - Upstream URL, concurrency limit, timeout, and dedup capacity
+    pub fn new(config: RemotePoolConfig) -> anyhow::Result<Self> {
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let channel = Endpoint::from_shared(config.upstream_url.clone())?
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .connect_lazy();
+        Ok(Self {
+            channel,
+            in_flight: Semaphore::new(config.max_in_flight),
+            recent: Mutex::default(),
+            config,
+        })
+    }
+
+    /// Upstream gRPC endpoint
+    pub fn upstream_url(&self) -> &str {
+        &self.config.upstream_url
+    }
+
+    /// Forward a transaction to the upstream pool
+    ///
+    /// The caller is expected to have checked the signature already, so
+    /// upstream capacity isn't spent on forgeries.
+    ///
+    /// # Returns
+    /// * `Ok(SoftConfirmation)` - the upstream verdict (accepted or rejected)
+    /// * `Err(RemoteError::AlreadyKnown)` - forwarded recently or pending upstream
+    /// * `Err(RemoteError::Busy)` - too many forwards in flight
+    /// * `Err(RemoteError::Rpc)` - upstream refused it (e.g., its pool is full)
+    /// * `Err(RemoteError::Unavailable)` - upstream unreachable or unintelligible
+    pub async fn forward(&self, tx: &UserTransaction) -> Result<SoftConfirmation, RemoteError> {
+        let tx_hash = tx.hash();
+
+        // Fail fast instead of queueing: a saturated node should push back on clients
+        let Ok(_permit) = self.in_flight.try_acquire() else {
+            return Err(RemoteError::Busy { max_in_flight: self.config.max_in_flight });
+        };
+
+        // Claim the hash before forwarding so concurrent duplicates are caught too
+        if !self.remember(tx_hash) {
+            return Err(RemoteError::AlreadyKnown { tx_hash });
+        }
+
+        let result = self.send(tx).await;
+        // Only accepted transactions stay remembered; anything else may be retried
+        let accepted = matches!(&result, Ok(confirmation) if matches!(confirmation.status, ConfirmationStatus::Accepted))
+            || matches!(result, Err(RemoteError::AlreadyKnown { .. }));
+        if !accepted {
+            self.forget(&tx_hash);
+        }
+        result
+    }
+
+    /// Send one `SendTransaction` call upstream and decode the reply
+    async fn send(&self, tx: &UserTransaction) -> Result<SoftConfirmation, RemoteError> {
+        let transaction = serde_json::to_vec(tx).expect("transactions always serialize");
+        let mut request = tonic::Request::new(ForwardRequest { transaction });
+        if let Some(key) = &self.config.api_key {
+            let key = key.parse().map_err(|_| RemoteError::Unavailable {
+                reason: "API key is not a valid metadata value".to_string(),
+            })?;
+            request.metadata_mut().insert("x-api-key", key);
+        }
+
+        let reply = forwarding::send_transaction(self.channel.clone(), request)
+            .await
+            .map_err(|status| RemoteError::Unavailable { reason: status.to_string() })?;
+
+        if reply.error_code != 0 {
+            debug!("Upstream refused {:?}: {} {}", tx.hash(), reply.error_code, reply.error_message);
+            if reply.error_code == UPSTREAM_ALREADY_KNOWN {
+                return Err(RemoteError::AlreadyKnown { tx_hash: tx.hash() });
+            }
+            return Err(RemoteError::Rpc { code: reply.error_code, message: reply.error_message });
+        }
+
+        serde_json::from_slice(&reply.confirmation).map_err(|e| RemoteError::Unavailable {
+            reason: format!("malformed upstream response: {}", e),
+        })
+    }
+
+    /// Remember a forwarded hash, forgetting the oldest beyond capacity
+    ///
+    /// # Returns
+    /// `false` if the hash was already remembered
+    fn remember(&self, tx_hash: H256) -> bool {
+        let mut recent = self.recent.lock().unwrap();
+        if !recent.set.insert(tx_hash) {
+            return false;
+        }
+        recent.order.push_back(tx_hash);
+        while recent.order.len() > self.config.dedup_capacity {
+            if let Some(oldest) = recent.order.pop_front() {
+                recent.set.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Forget a hash whose forward did not stick
+    fn forget(&self, tx_hash: &H256) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.set.remove(tx_hash) {
+            recent.order.retain(|hash| hash != tx_hash);
+        }
+    }
+}
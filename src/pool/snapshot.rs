@@ -0,0 +1,53 @@
+//! Pool Snapshot Module
+//!
+//! A JSON dump of every pending transaction, in queue order, used to move a
+//! pool between sequencer instances and to inspect it after an incident.
+//!
+//! Importing is not a restore: each transaction goes through full admission
+//! (validation, denylist, capacity) on the receiving instance, because that
+//! instance's account state may differ from the exporter's.
+
+use super::wal::RecoveredTransaction;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Pending pool contents at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    /// When the snapshot was taken (unix milliseconds)
+    pub exported_at_ms: u64,
+    /// Pending transactions with their admission times, front of the queue first
+    pub transactions: Vec<RecoveredTransaction>,
+}
+
+impl PoolSnapshot {
+    /// Write the snapshot to `path` as JSON
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Read a snapshot written by [`PoolSnapshot::write`]
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+/// Outcome of importing a snapshot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PoolImportReport {
+    /// Transactions admitted into the pool
+    pub imported: usize,
+    /// Transactions refused by admission, with the reason
+    pub rejected: Vec<RejectedImport>,
+}
+
+/// A snapshot transaction refused on import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedImport {
+    /// Hash of the refused transaction
+    pub tx_hash: H256,
+    /// Human-readable rejection reason
+    pub reason: String,
+}
@@ -0,0 +1,170 @@
+//! Pool Source Module
+//!
+//! This module defines the boundary between the batch orchestrator and the
+//! pools it drains. The orchestrator only depends on these traits, so
+//! alternative pool implementations (persistent, sharded, remote) can be
+//! swapped in, and tests can drive the orchestrator with scripted mock pools.
+
+use super::{ForcedQueue, TransactionPool};
+use crate::{ForcedTransaction, UserTransaction};
+use async_trait::async_trait;
+use ethers::types::{Address, H256, U256};
+use std::collections::HashSet;
+
+/// Source of normal user transactions for batching
+#[async_trait]
+pub trait PoolSource: Send + Sync {
+    /// Number of pending transactions, without consuming any
+    async fn len(&self) -> usize;
+
+    /// Check whether the source has no pending transactions
+    async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Remove and return up to `max` pending transactions, in pool order
+    async fn take(&self, max: usize) -> Vec<UserTransaction>;
+
+    /// Remove and return up to `max` pending transactions, best fee first
+    ///
+    /// Each sender's transactions must still come out in nonce order.
+    async fn take_by_priority(&self, max: usize) -> Vec<UserTransaction>;
+
+    /// Remove and return up to `max` pending transactions, round-robin across senders
+    ///
+    /// Weighted by each sender's tip at `base_fee` if given (see
+    /// `scheduler::fair_order`). Each sender's transactions must still come
+    /// out in nonce order.
+    async fn take_fair(&self, max: usize, base_fee: Option<U256>) -> Vec<UserTransaction>;
+
+    /// Remove and return up to `max` pending transactions due by `before`
+    /// (unix seconds), earliest deadline first, with their senders' earlier ones
+    ///
+    /// Sources without deadlines return nothing.
+    async fn take_due(&self, _before: u64, _max: usize) -> Vec<UserTransaction> {
+        Vec::new()
+    }
+
+    /// Earliest deadline among pending transactions
+    ///
+    /// Sources without deadlines return `None`.
+    async fn earliest_deadline(&self) -> Option<u64> {
+        None
+    }
+
+    /// Remove the earliest pending transaction of up to `n` senders outside `exclude`
+    async fn take_from_new_senders(&self, exclude: &HashSet<Address>, n: usize) -> Vec<UserTransaction>;
+
+    /// Remove exactly the given pending transactions, in the given order
+    ///
+    /// All-or-nothing; fails with the first hash that is not pending or would
+    /// skip one of its sender's earlier pending nonces.
+    async fn take_exact(&self, hashes: &[H256]) -> Result<Vec<UserTransaction>, H256>;
+
+    /// Put taken transactions back at the front of the pool, keeping their order
+    async fn requeue(&self, txs: Vec<UserTransaction>);
+}
+
+/// Source of forced transactions from L1
+#[async_trait]
+pub trait ForcedSource: Send + Sync {
+    /// Remove and return all queued forced transactions
+    async fn get_all(&self) -> Vec<ForcedTransaction>;
+
+    /// Number of queued forced transactions
+    async fn len(&self) -> usize;
+
+    /// Check whether no forced transactions are queued
+    async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Put taken forced transactions back at the front of the queue, keeping their order
+    async fn requeue(&self, txs: Vec<ForcedTransaction>);
+
+    /// Wait until an express-lane transaction is queued
+    ///
+    /// Sources without an express lane never resolve.
+    async fn express_ready(&self) {
+        std::future::pending::<()>().await
+    }
+
+    /// L1 block of the oldest queued forced transaction
+    ///
+    /// Sources that don't follow L1 return `None`, which disables the
+    /// block deadline.
+    async fn oldest_l1_block(&self) -> Option<u64> {
+        None
+    }
+
+    /// Latest L1 head, if known
+    fn l1_head(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[async_trait]
+impl PoolSource for TransactionPool {
+    async fn len(&self) -> usize {
+        TransactionPool::len(self).await
+    }
+
+    async fn take(&self, max: usize) -> Vec<UserTransaction> {
+        TransactionPool::take(self, max).await
+    }
+
+    async fn take_by_priority(&self, max: usize) -> Vec<UserTransaction> {
+        TransactionPool::take_by_priority(self, max).await
+    }
+
+    async fn take_fair(&self, max: usize, base_fee: Option<U256>) -> Vec<UserTransaction> {
+        TransactionPool::take_fair(self, max, base_fee).await
+    }
+
+    async fn take_due(&self, before: u64, max: usize) -> Vec<UserTransaction> {
+        TransactionPool::take_due(self, before, max).await
+    }
+
+    async fn earliest_deadline(&self) -> Option<u64> {
+        TransactionPool::earliest_deadline(self).await
+    }
+
+    async fn take_from_new_senders(&self, exclude: &HashSet<Address>, n: usize) -> Vec<UserTransaction> {
+        TransactionPool::take_from_new_senders(self, exclude, n).await
+    }
+
+    async fn take_exact(&self, hashes: &[H256]) -> Result<Vec<UserTransaction>, H256> {
+        TransactionPool::take_exact(self, hashes).await
+    }
+
+    async fn requeue(&self, txs: Vec<UserTransaction>) {
+        TransactionPool::requeue(self, txs).await
+    }
+}
+
+#[async_trait]
+impl ForcedSource for ForcedQueue {
+    async fn get_all(&self) -> Vec<ForcedTransaction> {
+        ForcedQueue::get_all(self).await
+    }
+
+    async fn len(&self) -> usize {
+        ForcedQueue::len(self).await
+    }
+
+    async fn requeue(&self, txs: Vec<ForcedTransaction>) {
+        ForcedQueue::requeue(self, txs).await
+    }
+
+    async fn express_ready(&self) {
+        ForcedQueue::express_ready(self).await
+    }
+
+    async fn oldest_l1_block(&self) -> Option<u64> {
+        ForcedQueue::oldest_l1_block(self).await
+    }
+
+    fn l1_head(&self) -> Option<u64> {
+        ForcedQueue::l1_head(self)
+    }
+}
@@ -0,0 +1,901 @@
+//! Tests for the transaction pools
+//! 
+//! Verifies:
+//! - Admission, capacity limits, eviction and fair selection across senders
+//! - Removal subscribers miss no removal
+//! - Ingress nodes forward over gRPC with dedup and backpressure
+//! - Encrypted transactions open only with their epoch's key, and leave the
+//!   encrypted lane in the order committed to on arrival
+//! - Bundles leave their lane whole and in order
+//! - Transactions falling due leave the pool earliest deadline first, after
+//!   their senders' earlier nonces
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::create_test_tx,
+        pool::{
+            decrypt_transaction, derive_epoch_key, encrypt_transaction, master_public_key, verify_epoch_key,
+            BundlePool, EncryptedPool, ForcedQueue, ForwardHandler, ForwardReply, ForwardRequest, ForwardServer, PoolEvent,
+            EVENT_CHANNEL_CAPACITY, PoolSnapshot, RemotePool, TransactionPool,
+        },
+        config::{DatabaseConfig, DynamicFeeFloorConfig, EncryptedMempoolConfig, FeeFloorConfig, PoolConfig, RemotePoolConfig},
+        registry::Registry,
+        state::StateCache,
+        validation::Validator,
+        Bundle, ConfirmationStatus, ForcedEventType, ForcedTransaction, PoolError, RemoteError, UserTransaction,
+        ValidationError,
+    };
+    use ethers::types::{Address, Bytes, H256, U256};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_pool_full_rejects_low_fee() {
+        let pool = TransactionPool::new(PoolConfig { max_size: 2, ..PoolConfig::default() });
+        pool.add(create_test_tx(1, 100)).await.unwrap();
+        pool.add(create_test_tx(2, 200)).await.unwrap();
+        
+        // Equal to the cheapest fee is not enough to evict
+        let result = pool.add(create_test_tx(3, 100)).await;
+        assert!(matches!(result, Err(PoolError::PoolFull { max_size: 2, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_pool_full_evicts_lowest_fee_then_oldest() {
+        let pool = TransactionPool::new(PoolConfig { max_size: 3, ..PoolConfig::default() });
+        pool.add(create_test_tx(1, 200)).await.unwrap();
+        pool.add(create_test_tx(2, 100)).await.unwrap(); // Oldest of the cheapest
+        pool.add(create_test_tx(3, 100)).await.unwrap();
+        
+        let evicted = pool.add(create_test_tx(4, 300)).await.unwrap();
+        assert_eq!(evicted.map(|tx| tx.nonce), Some(2));
+        
+        // Remaining transactions keep their FIFO order
+        let pending = pool.take(10).await;
+        let nonces: Vec<u64> = pending.iter().map(|tx| tx.nonce).collect();
+        assert_eq!(nonces, vec![1, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_introspection_does_not_consume() {
+        let pool = TransactionPool::default();
+        assert!(pool.is_empty().await);
+        for nonce in 1..=3 {
+            pool.add(create_test_tx(nonce, 100)).await.unwrap();
+        }
+        
+        let peeked: Vec<u64> = pool.peek(2).await.iter().map(|tx| tx.nonce).collect();
+        assert_eq!(peeked, vec![1, 2]);
+        {
+            let view = pool.view().await;
+            assert_eq!(view.len(), 3);
+            let nonces: Vec<u64> = view.iter().map(|tx| tx.nonce).collect();
+            assert_eq!(nonces, vec![1, 2, 3]);
+        }
+        assert_eq!(pool.len().await, 3);
+        
+        // Only take removes transactions
+        assert_eq!(pool.take(2).await.len(), 2);
+        assert_eq!(pool.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_take_from_new_senders_and_requeue() {
+        let pool = TransactionPool::default();
+        let mut txs = Vec::new();
+        for (sender, nonce) in [(1, 0), (2, 0), (2, 1), (3, 0)] {
+            let mut tx = create_test_tx(nonce, 100);
+            tx.from = Address::from_low_u64_be(sender);
+            txs.push(tx.clone());
+            pool.add(tx).await.unwrap();
+        }
+        
+        // Only each sender's first transaction is eligible
+        let exclude = [Address::from_low_u64_be(1)].into_iter().collect();
+        let taken = pool.take_from_new_senders(&exclude, 5).await;
+        let taken_hashes: Vec<_> = taken.iter().map(|tx| tx.hash()).collect();
+        assert_eq!(taken_hashes, vec![txs[1].hash(), txs[3].hash()]);
+        
+        // Requeued transactions go back to the front in their original order
+        pool.requeue(taken).await;
+        let order: Vec<(Address, u64)> = pool.take(10).await.iter().map(|tx| (tx.from, tx.nonce)).collect();
+        assert_eq!(order, vec![
+            (txs[1].from, 0),
+            (txs[3].from, 0),
+            (txs[0].from, 0),
+            (txs[2].from, 1),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_take_exact_is_all_or_nothing_in_nonce_order() {
+        let pool = TransactionPool::default();
+        let mut txs = Vec::new();
+        for (sender, nonce) in [(1, 0), (1, 1), (2, 0)] {
+            let mut tx = create_test_tx(nonce, 100);
+            tx.from = Address::from_low_u64_be(sender);
+            txs.push(tx.clone());
+            pool.add(tx).await.unwrap();
+        }
+        
+        // Sender 1's nonce 1 cannot be taken ahead of its nonce 0
+        assert_eq!(pool.take_exact(&[txs[2].hash(), txs[1].hash()]).await.unwrap_err(), txs[1].hash());
+        assert_eq!(pool.len().await, 3);
+        
+        let taken = pool.take_exact(&[txs[2].hash(), txs[0].hash(), txs[1].hash()]).await.unwrap();
+        let taken_hashes: Vec<_> = taken.iter().map(|tx| tx.hash()).collect();
+        assert_eq!(taken_hashes, vec![txs[2].hash(), txs[0].hash(), txs[1].hash()]);
+        assert!(pool.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_take_by_priority_respects_sender_nonce_order() {
+        let pool = TransactionPool::default();
+        for (sender, nonce, gas_price) in [(1, 0, 10), (1, 1, 90), (2, 0, 50), (3, 0, 50), (4, 0, 20)] {
+            let mut tx = create_test_tx(nonce, gas_price);
+            tx.from = Address::from_low_u64_be(sender);
+            pool.add(tx).await.unwrap();
+        }
+        
+        // Sender 1's expensive nonce 1 is stuck behind its cheap nonce 0;
+        // equal fees are taken oldest first
+        let taken: Vec<(u64, u64)> = pool.take_by_priority(3).await
+            .iter()
+            .map(|tx| (tx.from.to_low_u64_be(), tx.nonce))
+            .collect();
+        assert_eq!(taken, vec![(2, 0), (3, 0), (4, 0)]);
+        
+        // Taking nonce 0 unlocks nonce 1
+        let taken: Vec<(u64, u64)> = pool.take_by_priority(10).await
+            .iter()
+            .map(|tx| (tx.from.to_low_u64_be(), tx.nonce))
+            .collect();
+        assert_eq!(taken, vec![(1, 0), (1, 1)]);
+        assert!(pool.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_take_fair_serves_senders_in_rounds() {
+        let pool = TransactionPool::default();
+        let add = |sender: u64, nonce: u64, gas_price: u64| {
+            let tx = UserTransaction { from: Address::from_low_u64_be(sender), ..create_test_tx(nonce, gas_price) };
+            pool.add(tx)
+        };
+        for nonce in 0..4 {
+            add(1, nonce, 10).await.unwrap();
+        }
+        add(2, 0, 10).await.unwrap();
+        add(3, 0, 40).await.unwrap();
+        let pool = &pool;
+        let take = |max: usize, base_fee: Option<U256>| async move {
+            pool.take_fair(max, base_fee).await
+                .iter()
+                .map(|tx| (tx.from.to_low_u64_be(), tx.nonce))
+                .collect::<Vec<(u64, u64)>>()
+        };
+        
+        // Sender 1's backlog does not keep the others waiting
+        assert_eq!(take(3, None).await, vec![(1, 0), (2, 0), (3, 0)]);
+        
+        // Weighted by tip, a sender paying three times as much is served three times as often
+        add(4, 0, 30).await.unwrap();
+        add(4, 1, 30).await.unwrap();
+        assert_eq!(take(3, Some(U256::zero())).await, vec![(4, 0), (4, 1), (1, 1)]);
+        assert_eq!(take(10, None).await, vec![(1, 2), (1, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_take_due_takes_earliest_deadlines_with_earlier_nonces() {
+        let pool = TransactionPool::default();
+        let add = |sender: u64, nonce: u64, deadline: Option<u64>| {
+            let tx = UserTransaction { from: Address::from_low_u64_be(sender), deadline, ..create_test_tx(nonce, 10) };
+            pool.add(tx)
+        };
+        add(1, 0, None).await.unwrap();
+        add(1, 1, Some(100)).await.unwrap();
+        add(2, 0, Some(50)).await.unwrap();
+        add(3, 0, Some(500)).await.unwrap();
+        assert_eq!(pool.earliest_deadline().await, Some(50));
+        let pool = &pool;
+        let take_due = |before: u64, max: usize| async move {
+            pool.take_due(before, max).await
+                .iter()
+                .map(|tx| (tx.from.to_low_u64_be(), tx.nonce))
+                .collect::<Vec<(u64, u64)>>()
+        };
+        
+        // Sender 1's deadline brings its earlier nonce along, unless there is no room for both
+        assert_eq!(take_due(200, 2).await, vec![(2, 0)]);
+        assert_eq!(take_due(200, 2).await, vec![(1, 0), (1, 1)]);
+        assert_eq!(pool.earliest_deadline().await, Some(500));
+        
+        // Transactions not yet due stay
+        assert!(take_due(499, 10).await.is_empty());
+        assert_eq!(take_due(500, 10).await, vec![(3, 0)]);
+        assert_eq!(pool.earliest_deadline().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_expire_stale_drops_old_transactions_and_emits_events() {
+        let pool = TransactionPool::new(PoolConfig { tx_ttl_secs: 0, ..PoolConfig::default() });
+        let mut events = pool.subscribe();
+        pool.add(create_test_tx(1, 100)).await.unwrap();
+        
+        // A zero TTL expires everything immediately
+        let expired = pool.expire_stale().await;
+        assert_eq!(expired.len(), 1);
+        assert!(pool.take(10).await.is_empty());
+        
+        assert!(matches!(events.recv().await.unwrap(), PoolEvent::Added(tx) if tx.nonce == 1));
+        match events.recv().await.unwrap() {
+            PoolEvent::Expired(tx) => assert_eq!(tx.nonce, 1),
+            other => panic!("expected an expiry, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_removal_subscribers_miss_no_removal() {
+        let pool = TransactionPool::new(PoolConfig { tx_ttl_secs: 0, ..PoolConfig::default() });
+        let mut events = pool.subscribe();
+        let mut removals = pool.subscribe_removals();
+        let count = EVENT_CHANNEL_CAPACITY as u64 + 10;
+        for nonce in 0..count {
+            pool.add(create_test_tx(nonce, 100)).await.unwrap();
+        }
+        pool.expire_stale().await;
+        
+        // The broadcast subscriber fell behind and lost events
+        assert!(matches!(events.recv().await, Err(broadcast::error::RecvError::Lagged(_))));
+        // The removal subscriber gets every expiry, and nothing else
+        let mut expired = 0;
+        while let Ok(event) = removals.try_recv() {
+            assert!(matches!(event, PoolEvent::Expired(tx) if tx.nonce == expired));
+            expired += 1;
+        }
+        assert_eq!(expired, count);
+    }
+
+    #[tokio::test]
+    async fn test_pool_events_follow_transaction_lifecycle() {
+        let pool = TransactionPool::new(PoolConfig { max_size: 1, ..PoolConfig::default() });
+        let mut events = pool.subscribe();
+        pool.add(create_test_tx(1, 10)).await.unwrap();
+        pool.add(create_test_tx(2, 20)).await.unwrap();
+        let taken = pool.take(1).await;
+        pool.requeue(taken).await;
+        
+        let mut lifecycle = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            lifecycle.push(match event {
+                PoolEvent::Added(tx) => ("added", tx.nonce),
+                PoolEvent::Evicted(tx) => ("evicted", tx.nonce),
+                PoolEvent::Batched(tx) => ("batched", tx.nonce),
+                other => panic!("unexpected event {:?}", other),
+            });
+        }
+        assert_eq!(lifecycle, vec![
+            ("added", 1),
+            ("evicted", 1),
+            ("added", 2),
+            ("batched", 2),
+            ("added", 2),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_from_removes_later_nonces_of_the_sender() {
+        let pool = TransactionPool::default();
+        for nonce in 0..4 {
+            pool.add(create_test_tx(nonce, 100)).await.unwrap();
+        }
+        let mut events = pool.subscribe();
+
+        let dropped = pool.drop_from(&Address::zero(), 2).await;
+        assert_eq!(dropped.iter().map(|tx| tx.nonce).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(pool.pending_nonces(&Address::zero()).await, vec![0, 1]);
+        assert!(matches!(events.recv().await.unwrap(), PoolEvent::Dropped(tx) if tx.nonce == 2));
+        // Dropped transactions can be submitted again
+        pool.add(create_test_tx(2, 100)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_report_depth_and_fee_percentiles() {
+        let pool = TransactionPool::default();
+        let empty = pool.metrics().await;
+        assert_eq!(empty.depth, 0);
+        assert!(empty.fee_percentiles.is_empty());
+        
+        for nonce in 1..=10 {
+            pool.add(create_test_tx(nonce, nonce * 10)).await.unwrap();
+        }
+        let metrics = pool.metrics().await;
+        assert_eq!(metrics.depth, 10);
+        let fees: Vec<U256> = metrics.fee_percentiles.iter().map(|(_, fee)| *fee).collect();
+        assert_eq!(fees, vec![U256::from(50), U256::from(90), U256::from(100)]);
+        
+        let text = metrics.to_prometheus();
+        assert!(text.contains("sequencer_pool_depth 10\n"));
+        assert!(text.contains("sequencer_pool_gas_price_wei{quantile=\"0.9\"} 90\n"));
+    }
+
+    #[tokio::test]
+    async fn test_denylisted_senders_are_refused_until_allowed() {
+        let denied = Address::from_low_u64_be(0xbad);
+        let pool = TransactionPool::new(PoolConfig { denylist: vec![denied], ..PoolConfig::default() });
+        let mut tx = create_test_tx(1, 100);
+        tx.from = denied;
+        
+        let result = pool.add(tx.clone()).await;
+        assert!(matches!(result, Err(PoolError::Denied { sender }) if sender == denied));
+        
+        assert!(pool.allow(&denied).await);
+        pool.add(tx.clone()).await.unwrap();
+        
+        // Denying again only affects new admissions
+        assert!(pool.deny(denied).await);
+        assert_eq!(pool.denylist().await, vec![denied]);
+        assert_eq!(pool.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_permissioned_pool_admits_only_allowlisted_senders() {
+        let approved = Address::from_low_u64_be(0xa1);
+        let stranger = Address::from_low_u64_be(0xa2);
+        let pool = TransactionPool::new(PoolConfig { allowlist: Some(vec![approved]), ..PoolConfig::default() });
+        assert!(pool.is_permissioned());
+        
+        let mut tx = create_test_tx(1, 100);
+        tx.from = approved;
+        pool.add(tx).await.unwrap();
+        
+        let mut tx = create_test_tx(1, 100);
+        tx.from = stranger;
+        let result = pool.add(tx.clone()).await;
+        assert!(matches!(result, Err(PoolError::NotAllowlisted { sender }) if sender == stranger));
+        
+        assert_eq!(pool.approve(stranger).await, Some(true));
+        pool.add(tx).await.unwrap();
+        assert_eq!(pool.allowlist().await, Some(vec![approved, stranger]));
+        
+        // Revoking only affects new admissions
+        assert_eq!(pool.revoke(&stranger).await, Some(true));
+        assert!(!pool.is_allowlisted(&stranger).await);
+        assert_eq!(pool.len().await, 2);
+        
+        // Without an allowlist everyone is admitted and there is nothing to approve
+        let open = TransactionPool::default();
+        assert!(open.is_allowlisted(&stranger).await);
+        assert_eq!(open.approve(stranger).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_fee_floor_rises_as_pool_fills() {
+        let pool = TransactionPool::new(PoolConfig {
+            max_size: 4,
+            fee_floor: FeeFloorConfig {
+                min_gas_price: 20,
+                dynamic: Some(DynamicFeeFloorConfig { start_fill_ratio: 0.5, percentile: 0.5, inclusion_window: 4 }),
+            },
+            ..PoolConfig::default()
+        });
+        
+        let result = pool.add(create_test_tx(1, 10)).await;
+        assert!(matches!(result, Err(PoolError::Underpriced { min_gas_price, .. }) if min_gas_price == U256::from(20)));
+        
+        pool.add(create_test_tx(2, 30)).await.unwrap();
+        assert_eq!(pool.fee_floor().await, U256::from(20));
+        
+        // Half full: the floor becomes the median pending gas price
+        pool.add(create_test_tx(3, 50)).await.unwrap();
+        assert_eq!(pool.fee_floor().await, U256::from(30));
+        assert!(matches!(pool.add(create_test_tx(4, 25)).await, Err(PoolError::Underpriced { .. })));
+        pool.add(create_test_tx(5, 30)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fee_floor_follows_congested_batch_cutoffs() {
+        let pool = Arc::new(TransactionPool::new(PoolConfig {
+            fee_floor: FeeFloorConfig {
+                min_gas_price: 0,
+                // Occupancy never kicks in; only inclusion cutoffs raise the floor
+                dynamic: Some(DynamicFeeFloorConfig { start_fill_ratio: 2.0, percentile: 0.5, inclusion_window: 2 }),
+            },
+            ..PoolConfig::default()
+        }));
+        let tx_from = |sender: u64, gas_price: u64| UserTransaction {
+            from: Address::from_low_u64_be(sender),
+            ..create_test_tx(0, gas_price)
+        };
+        for (sender, gas_price) in [(1, 40), (2, 30), (3, 20), (4, 10)] {
+            pool.add(tx_from(sender, gas_price)).await.unwrap();
+        }
+        
+        // Two batches leave transactions behind, cutting off at 30 and then 35
+        pool.take_by_priority(2).await;
+        assert_eq!(pool.fee_floor().await, U256::from(30));
+        pool.add(tx_from(5, 35)).await.unwrap();
+        pool.take_by_priority(1).await;
+        assert_eq!(pool.fee_floor().await, U256::from(30));
+        
+        // Enforced in validation as well as at admission
+        let validator = Validator::new(StateCache::new()).with_pool(pool.clone(), u64::MAX);
+        let result = validator.check_fee_floor(&tx_from(6, 25)).await;
+        assert!(matches!(result, Err(ValidationError::BelowFeeFloor { fee_floor, .. }) if fee_floor == U256::from(30)));
+        validator.check_fee_floor(&tx_from(6, 30)).await.unwrap();
+        
+        // Once a batch takes everything, the window's lowest cutoff drops to 0
+        pool.take_by_priority(10).await;
+        assert_eq!(pool.fee_floor().await, U256::zero());
+        validator.check_fee_floor(&tx_from(6, 1)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_local_senders_bypass_limits_and_go_first() {
+        let local = Address::from_low_u64_be(0xaa);
+        let pool = TransactionPool::new(PoolConfig {
+            max_size: 1,
+            fee_floor: FeeFloorConfig { min_gas_price: 20, dynamic: None },
+            locals: vec![local],
+            ..PoolConfig::default()
+        });
+        pool.add(create_test_tx(1, 50)).await.unwrap();
+        
+        // Below the floor and past capacity, without evicting anything
+        let mut maintenance = create_test_tx(1, 1);
+        maintenance.from = local;
+        assert!(pool.add(maintenance.clone()).await.unwrap().is_none());
+        assert_eq!(pool.len().await, 2);
+        
+        // A better-paying remote transaction can only evict other remote ones
+        let evicted = pool.add(create_test_tx(2, 60)).await.unwrap().unwrap();
+        assert_eq!(evicted.nonce, 1);
+        assert_ne!(evicted.from, local);
+        
+        assert_eq!(pool.take(1).await[0].hash(), maintenance.hash());
+    }
+
+    #[tokio::test]
+    async fn test_forced_queue_deduplicates_and_survives_restart() {
+        let registry = Arc::new(Registry::new(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+        }).await.unwrap());
+        let deposit = |log_index| ForcedTransaction {
+            tx_hash: H256::from_low_u64_be(1),
+            from: Address::zero(),
+            to: Address::zero(),
+            value: U256::from(1000),
+            nonce: 0,
+            gas_limit: 21000,
+            l1_tx_hash: H256::from_low_u64_be(1),
+            l1_block_number: 1,
+            l1_log_index: log_index,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        };
+        
+        // Two events of one L1 transaction are distinct; a re-scan of either is not
+        let queue = ForcedQueue::new().with_registry(registry.clone());
+        assert!(queue.add(deposit(0)).await);
+        assert!(queue.add(deposit(1)).await);
+        assert!(!queue.add(deposit(0)).await);
+        
+        // A restarted queue reloads what was not batched, and still refuses re-scans
+        let restarted = ForcedQueue::new().with_registry(registry.clone());
+        assert_eq!(restarted.restore().await.unwrap(), 2);
+        assert!(!restarted.add(deposit(1)).await);
+        let queued = restarted.get_all().await;
+        assert_eq!(queued.iter().map(|tx| tx.l1_log_index).collect::<Vec<_>>(), vec![0, 1]);
+        
+        registry.mark_forced_included(&queued, Some(1)).await.unwrap();
+        let restarted = ForcedQueue::new().with_registry(registry);
+        assert_eq!(restarted.restore().await.unwrap(), 0);
+        
+        // Without a registry, duplicates are caught in memory
+        let in_memory = ForcedQueue::new();
+        assert!(in_memory.add(deposit(0)).await);
+        assert!(!in_memory.add(deposit(0)).await);
+    }
+
+    #[tokio::test]
+    async fn test_reorged_forced_transactions_are_dropped_and_forgotten() {
+        let registry = Arc::new(Registry::new(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+        }).await.unwrap());
+        let deposit = |l1_tx, block| ForcedTransaction {
+            tx_hash: H256::from_low_u64_be(l1_tx),
+            from: Address::zero(),
+            to: Address::zero(),
+            value: U256::from(1000),
+            nonce: 0,
+            gas_limit: 21000,
+            l1_tx_hash: H256::from_low_u64_be(l1_tx),
+            l1_block_number: block,
+            l1_log_index: 0,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        };
+        
+        let queue = ForcedQueue::new().with_registry(registry.clone());
+        queue.add(deposit(1, 10)).await;
+        queue.add(deposit(2, 10)).await;
+        
+        // Only an exact block and transaction match is removed
+        assert!(queue.remove_reorged(11, H256::from_low_u64_be(1)).await.is_empty());
+        let removed = queue.remove_reorged(10, H256::from_low_u64_be(1)).await;
+        assert_eq!(removed.len(), 1);
+        assert_eq!(queue.len().await, 1);
+        
+        // Gone after a restart, and queued again when re-mined in another block
+        let restarted = ForcedQueue::new().with_registry(registry);
+        assert_eq!(restarted.restore().await.unwrap(), 1);
+        assert!(restarted.add(deposit(1, 12)).await);
+    }
+
+    #[tokio::test]
+    async fn test_forced_transactions_above_a_fork_point_are_rolled_back() {
+        let deposit = |l1_tx, block| ForcedTransaction {
+            tx_hash: H256::from_low_u64_be(l1_tx),
+            from: Address::zero(),
+            to: Address::zero(),
+            value: U256::from(1000),
+            nonce: 0,
+            gas_limit: 21000,
+            l1_tx_hash: H256::from_low_u64_be(l1_tx),
+            l1_block_number: block,
+            l1_log_index: 0,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        };
+        
+        let queue = ForcedQueue::new();
+        for (l1_tx, block) in [(1, 10), (2, 11), (3, 12)] {
+            queue.add(deposit(l1_tx, block)).await;
+        }
+        let removed = queue.remove_after(10).await;
+        assert_eq!(removed.iter().map(|tx| tx.l1_block_number).collect::<Vec<_>>(), vec![11, 12]);
+        assert_eq!(queue.len().await, 1);
+        
+        // Forgotten, so the canonical chain's events are queued again on re-scan
+        assert!(queue.add(deposit(2, 11)).await);
+        assert!(!queue.add(deposit(1, 10)).await);
+    }
+
+    #[tokio::test]
+    async fn test_full_forced_queue_holds_producers_until_drained() {
+        let queue = Arc::new(ForcedQueue::new().with_capacity(1));
+        let mut deposit = ForcedTransaction {
+            tx_hash: H256::zero(),
+            from: Address::zero(),
+            to: Address::zero(),
+            value: U256::from(1000),
+            nonce: 0,
+            gas_limit: 21000,
+            l1_tx_hash: H256::from_low_u64_be(1),
+            l1_block_number: 1,
+            l1_log_index: 0,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        };
+        queue.wait_for_space().await;
+        assert!(queue.add(deposit.clone()).await);
+        assert!(queue.is_full().await);
+        
+        // Capacity is a producer-side limit: forced transactions are never refused
+        deposit.l1_log_index = 1;
+        assert!(queue.add(deposit).await);
+        
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.wait_for_space().await })
+        };
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        
+        assert_eq!(queue.get_all().await.len(), 2);
+        tokio::time::timeout(std::time::Duration::from_secs(1), waiter).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sharded_pool_admits_concurrently_and_keeps_arrival_order() {
+        let pool = Arc::new(TransactionPool::new(PoolConfig { max_size: 64, shards: 4, ..PoolConfig::default() }));
+        let submitters: Vec<_> = (1..=32u64)
+            .map(|i| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let tx = UserTransaction { from: Address::from_low_u64_be(i), ..create_test_tx(0, 100) };
+                    pool.add(tx).await.unwrap();
+                })
+            })
+            .collect();
+        for submitter in submitters {
+            submitter.await.unwrap();
+        }
+        assert_eq!(pool.len().await, 32);
+
+        // Senders spread over every shard, but the queue stays globally ordered
+        pool.add(UserTransaction { from: Address::from_low_u64_be(1), ..create_test_tx(1, 100) }).await.unwrap();
+        pool.add(UserTransaction { from: Address::from_low_u64_be(2), ..create_test_tx(1, 100) }).await.unwrap();
+        let taken = pool.take(100).await;
+        assert_eq!(taken.len(), 34);
+        let tail: Vec<(u64, u64)> = taken[32..].iter().map(|tx| (tx.from.to_low_u64_be(), tx.nonce)).collect();
+        assert_eq!(tail, vec![(1, 1), (2, 1)]);
+        assert!(pool.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_hash_rejected_until_removed() {
+        let pool = TransactionPool::default();
+        let tx = create_test_tx(1, 100);
+        pool.add(tx.clone()).await.unwrap();
+        
+        assert!(pool.contains(&tx.hash()).await);
+        let result = pool.add(tx.clone()).await;
+        assert!(matches!(result, Err(PoolError::AlreadyKnown { .. })));
+        
+        // Once batched, the hash is released from the index
+        pool.take(10).await;
+        assert!(!pool.contains(&tx.hash()).await);
+    }
+
+    #[tokio::test]
+    async fn test_wal_recovers_pending_transactions_after_restart() {
+        let path = std::env::temp_dir().join(format!("pool-wal-test-{}.wal", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = PoolConfig {
+            wal_path: Some(path.display().to_string()),
+            ..PoolConfig::default()
+        };
+        
+        {
+            let (pool, recovered) = TransactionPool::open(config.clone()).unwrap();
+            assert!(recovered.is_empty());
+            pool.add(create_test_tx(1, 100)).await.unwrap();
+            pool.add(create_test_tx(2, 100)).await.unwrap();
+            pool.add(create_test_tx(3, 100)).await.unwrap();
+            // Batched transactions must not come back
+            pool.take(1).await;
+        }
+        
+        let (pool, recovered) = TransactionPool::open(config).unwrap();
+        let nonces: Vec<u64> = recovered.iter().map(|entry| entry.tx.nonce).collect();
+        assert_eq!(nonces, vec![2, 3]);
+        
+        for entry in recovered {
+            assert!(pool.readmit(entry).await);
+        }
+        let pending = pool.take(10).await;
+        assert_eq!(pending.len(), 2);
+        
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_roundtrips_queue_without_consuming() {
+        let pool = TransactionPool::default();
+        for nonce in 1..=3 {
+            pool.add(create_test_tx(nonce, 100)).await.unwrap();
+        }
+        
+        let path = std::env::temp_dir().join(format!("pool-snapshot-test-{}.json", std::process::id()));
+        pool.snapshot().await.write(&path).unwrap();
+        assert_eq!(pool.len().await, 3);
+        
+        let snapshot = PoolSnapshot::read(&path).unwrap();
+        let nonces: Vec<u64> = snapshot.transactions.iter().map(|entry| entry.tx.nonce).collect();
+        assert_eq!(nonces, vec![1, 2, 3]);
+        assert!(snapshot.transactions.iter().all(|entry| entry.added_at_ms <= snapshot.exported_at_ms));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Fake upstream sequencer answering every forwarded transaction with `reply`
+    struct FakeUpstream {
+        reply: ForwardReply,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl ForwardHandler for FakeUpstream {
+        async fn send_transaction(&self, _request: tonic::Request<ForwardRequest>) -> ForwardReply {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.reply.clone()
+        }
+    }
+
+    /// Start a fake upstream sequencer that answers every call with `reply`
+    /// 
+    /// # Returns
+    /// The upstream URL and a counter of calls received
+    async fn spawn_upstream(reply: ForwardReply) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let upstream = FakeUpstream { reply, calls: calls.clone() };
+        let app = tonic::service::Routes::new(ForwardServer::new(upstream)).into_axum_router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, calls)
+    }
+
+    fn remote_config(upstream_url: String) -> RemotePoolConfig {
+        RemotePoolConfig {
+            upstream_url,
+            api_key: None,
+            max_in_flight: 4,
+            timeout_ms: 5_000,
+            dedup_capacity: 16,
+        }
+    }
+
+    fn confirmation(status: Value) -> ForwardReply {
+        let confirmation = json!({ "tx_hash": format!("{:?}", ethers::types::H256::zero()), "status": status, "timestamp": 0 });
+        ForwardReply { confirmation: serde_json::to_vec(&confirmation).unwrap(), ..ForwardReply::default() }
+    }
+
+    #[tokio::test]
+    async fn test_remote_pool_dedups_accepted_transactions() {
+        let (url, calls) = spawn_upstream(confirmation(json!("Accepted"))).await;
+        let remote = RemotePool::new(remote_config(url)).unwrap();
+        
+        let forwarded = remote.forward(&create_test_tx(1, 100)).await.unwrap();
+        assert!(matches!(forwarded.status, ConfirmationStatus::Accepted));
+        
+        // A retry is answered locally without another upstream call
+        let retry = remote.forward(&create_test_tx(1, 100)).await;
+        assert!(matches!(retry, Err(RemoteError::AlreadyKnown { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remote_pool_forgets_rejected_transactions() {
+        let (url, calls) = spawn_upstream(confirmation(json!({ "Rejected": { "reason": "bad nonce" } }))).await;
+        let remote = RemotePool::new(remote_config(url)).unwrap();
+        
+        for _ in 0..2 {
+            let forwarded = remote.forward(&create_test_tx(1, 100)).await.unwrap();
+            assert!(matches!(forwarded.status, ConfirmationStatus::Rejected { .. }));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remote_pool_passes_through_upstream_errors() {
+        let (url, _) = spawn_upstream(ForwardReply {
+            error_code: -32003,
+            error_message: "pool full".to_string(),
+            ..ForwardReply::default()
+        }).await;
+        let remote = RemotePool::new(remote_config(url)).unwrap();
+        
+        let result = remote.forward(&create_test_tx(1, 100)).await;
+        assert!(matches!(result, Err(RemoteError::Rpc { code: -32003, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_remote_pool_applies_backpressure() {
+        let (url, calls) = spawn_upstream(confirmation(json!("Accepted"))).await;
+        let remote = RemotePool::new(RemotePoolConfig {
+            max_in_flight: 0,
+            ..remote_config(url)
+        }).unwrap();
+        
+        let result = remote.forward(&create_test_tx(1, 100)).await;
+        assert!(matches!(result, Err(RemoteError::Busy { max_in_flight: 0 })));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_encrypted_transactions_open_with_their_epoch_key() {
+        let seed = H256::repeat_byte(0x42);
+        let master_key = master_public_key(&seed).unwrap();
+        let tx = create_test_tx(5, 20);
+        
+        let encrypted = encrypt_transaction(&tx, 7, &master_key).unwrap();
+        assert_eq!((encrypted.epoch, encrypted.gas_limit), (7, tx.gas_limit));
+        assert!(!encrypted.ciphertext.windows(4).any(|window| window == b"from"));
+        
+        let key = derive_epoch_key(&seed, 7).unwrap();
+        verify_epoch_key(&master_key, 7, &key).unwrap();
+        assert_eq!(decrypt_transaction(&encrypted, &key).unwrap().hash(), tx.hash());
+        
+        // Another epoch's key neither verifies nor decrypts
+        let other = derive_epoch_key(&seed, 8).unwrap();
+        assert!(matches!(verify_epoch_key(&master_key, 7, &other), Err(ValidationError::InvalidEncryption { .. })));
+        assert!(matches!(decrypt_transaction(&encrypted, &other), Err(ValidationError::InvalidEncryption { .. })));
+        
+        // Nor does a tampered ciphertext
+        let mut tampered = encrypted.clone();
+        let mut ciphertext = tampered.ciphertext.to_vec();
+        ciphertext[0] ^= 1;
+        tampered.ciphertext = Bytes::from(ciphertext);
+        assert!(matches!(decrypt_transaction(&tampered, &key), Err(ValidationError::InvalidEncryption { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_lane_releases_in_committed_order() {
+        let seed = H256::repeat_byte(0x42);
+        let master_key = master_public_key(&seed).unwrap();
+        let lane = EncryptedPool::new(EncryptedMempoolConfig {
+            enabled: true,
+            master_public_key: Some(master_key.clone()),
+            epoch_ms: 20,
+            lane_capacity: 2,
+            ..EncryptedMempoolConfig::default()
+        }).unwrap();
+        let epoch = lane.current_epoch() + 1;
+        let txs = [create_test_tx(1, 10), create_test_tx(2, 10)];
+        let encrypted: Vec<_> = txs.iter().map(|tx| encrypt_transaction(tx, epoch, &master_key).unwrap()).collect();
+        
+        let first = lane.add(encrypted[0].clone()).unwrap();
+        let second = lane.add(encrypted[1].clone()).unwrap();
+        assert_eq!((first.position, second.position), (0, 1));
+        let chain = |prev: H256, hash: H256| H256::from(ethers::utils::keccak256([prev.as_bytes(), hash.as_bytes()].concat()));
+        assert_eq!(first.commitment, chain(H256::zero(), first.tx_hash));
+        assert_eq!(second.commitment, chain(first.commitment, second.tx_hash));
+        
+        assert!(matches!(lane.add(encrypted[0].clone()), Err(PoolError::AlreadyKnown { .. })));
+        let late = encrypt_transaction(&txs[0], epoch - 2, &master_key).unwrap();
+        assert!(matches!(lane.add(late), Err(PoolError::EpochOutOfRange { .. })));
+        let extra = encrypt_transaction(&txs[0], epoch, &master_key).unwrap();
+        assert!(matches!(lane.add(extra), Err(PoolError::EncryptedLaneFull { capacity: 2 })));
+        
+        // Nothing opens before the epoch is over and its key is out
+        assert!(lane.take_ready(10, |_| true).is_empty());
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        assert!(lane.take_ready(10, |_| true).is_empty());
+        assert!(lane.submit_key(epoch, derive_epoch_key(&H256::repeat_byte(0x43), epoch).unwrap()).is_err());
+        lane.submit_key(epoch, derive_epoch_key(&seed, epoch).unwrap()).unwrap();
+        
+        let ready = lane.take_ready(10, |_| true);
+        let opened: Vec<H256> = ready
+            .iter()
+            .map(|(encrypted, key)| decrypt_transaction(encrypted, key).unwrap().hash())
+            .collect();
+        assert_eq!(opened, vec![txs[0].hash(), txs[1].hash()]);
+        assert!(lane.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bundle_lane_releases_whole_bundles_in_order() {
+        let sender = Address::repeat_byte(0x11);
+        let bundle = |nonces: &[u64]| Bundle {
+            transactions: nonces.iter().map(|&nonce| UserTransaction { from: sender, ..create_test_tx(nonce, 10) }).collect(),
+        };
+        let lane = BundlePool::new(2);
+        lane.add(bundle(&[0, 1])).await.unwrap();
+        assert!(matches!(lane.add(bundle(&[0, 1])).await, Err(PoolError::AlreadyKnown { .. })));
+        lane.add(bundle(&[2, 3, 4])).await.unwrap();
+        assert!(matches!(lane.add(bundle(&[5])).await, Err(PoolError::BundleLaneFull { capacity: 2 })));
+        
+        assert!(lane.has_pending(&sender).await);
+        assert!(!lane.has_pending(&Address::zero()).await);
+        assert_eq!(lane.pending_spend(&sender).await, create_test_tx(0, 10).max_cost() * 5);
+        assert!(lane.contains_transaction(&bundle(&[2]).transactions[0].hash()).await);
+        
+        // The second bundle does not fit whole, so it waits
+        let taken = lane.take(4).await;
+        assert_eq!(taken.len(), 1);
+        assert_eq!(taken[0].hash(), bundle(&[0, 1]).hash());
+        assert!(lane.take(2).await.is_empty());
+        
+        // Requeued bundles go back in front, in order
+        lane.requeue(taken).await;
+        let taken = lane.take(10).await;
+        assert_eq!(
+            taken.iter().map(Bundle::hash).collect::<Vec<_>>(),
+            vec![bundle(&[0, 1]).hash(), bundle(&[2, 3, 4]).hash()],
+        );
+        assert!(lane.is_empty().await);
+        assert!(!lane.has_pending(&sender).await);
+    }
+}
@@ -0,0 +1,134 @@
+//! Threshold Encryption Module
+//!
+//! Transactions are encrypted to an epoch rather than to a key anyone holds:
+//! identity-based encryption (Boneh-Franklin) over BLS12-381, as used by
+//! threshold-encrypted mempools. The keypers hold shares of a master secret
+//! `s`, and publish the master public key `s·G1` (48 bytes compressed). Once
+//! an epoch is over, a threshold of them release its decryption key: the BLS
+//! signature `s·H(epoch)` of the epoch's identity, which anyone can check
+//! against the master public key like any BLS signature.
+//!
+//! A sender encrypts with a one-time scalar `r`: the ephemeral key `r·G1`
+//! goes with the ciphertext, and the shared secret is the pairing
+//! `e(r·H(epoch), s·G1)`. The decryption key gives the same pairing as
+//! `e(s·H(epoch), r·G1)`. The secret seeds a Keccak-256 keystream, XORed
+//! with the transaction's JSON encoding, and an authentication tag.
+
+use crate::{EncryptedTransaction, UserTransaction, ValidationError};
+use blst::min_pk::{PublicKey, SecretKey, Signature};
+use blst::{blst_fp12, blst_p1_affine, blst_p2_affine, BLST_ERROR};
+use ethers::core::rand::{thread_rng, RngCore};
+use ethers::types::{Bytes, H256};
+use ethers::utils::keccak256;
+
+/// Domain separation tag hashing epoch identities to G2
+pub const EPOCH_KEY_DST: &[u8] = b"SEQUENCER_EPOCH_KEY_BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+/// Identity an epoch's decryption key signs: the epoch as 8 big-endian bytes
+fn identity(epoch: u64) -> [u8; 8] {
+    epoch.to_be_bytes()
+}
+
+/// Derive the decryption key of `epoch` from the master secret's seed
+///
+/// For a sequencer releasing the epoch keys itself (timelock mode), or tests;
+/// keypers sharing the secret combine their shares into the same key.
+pub fn derive_epoch_key(seed: &H256, epoch: u64) -> Result<Bytes, ValidationError> {
+    let secret = SecretKey::key_gen(seed.as_bytes(), &[]).map_err(|e| invalid(e, "bad key seed"))?;
+    Ok(Bytes::from(secret.sign(&identity(epoch), EPOCH_KEY_DST, &[]).compress().to_vec()))
+}
+
+/// Master public key of the secret derived from `seed`
+pub fn master_public_key(seed: &H256) -> Result<Bytes, ValidationError> {
+    let secret = SecretKey::key_gen(seed.as_bytes(), &[]).map_err(|e| invalid(e, "bad key seed"))?;
+    Ok(Bytes::from(secret.sk_to_pk().compress().to_vec()))
+}
+
+/// Check that `master_key` is a valid master public key
+pub fn validate_master_key(master_key: &[u8]) -> Result<(), ValidationError> {
+    PublicKey::key_validate(master_key).map(|_| ()).map_err(|e| invalid(e, "malformed master key"))
+}
+
+/// Check that `key` is the decryption key of `epoch` under `master_key`
+pub fn verify_epoch_key(master_key: &[u8], epoch: u64, key: &[u8]) -> Result<(), ValidationError> {
+    let master_key = PublicKey::key_validate(master_key).map_err(|e| invalid(e, "malformed master key"))?;
+    let key = Signature::sig_validate(key, true).map_err(|e| invalid(e, "malformed epoch key"))?;
+    match key.verify(false, &identity(epoch), EPOCH_KEY_DST, &[], &master_key, false) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        e => Err(invalid(e, "not the key of this epoch")),
+    }
+}
+
+/// Encrypt `tx` to `epoch` under `master_key`
+///
+/// # Returns
+/// An error if the master key is malformed
+pub fn encrypt_transaction(tx: &UserTransaction, epoch: u64, master_key: &[u8]) -> Result<EncryptedTransaction, ValidationError> {
+    let master_key = PublicKey::key_validate(master_key).map_err(|e| invalid(e, "malformed master key"))?;
+    let mut ikm = [0u8; 32];
+    thread_rng().fill_bytes(&mut ikm);
+    let ephemeral = SecretKey::key_gen(&ikm, &[]).map_err(|e| invalid(e, "bad ephemeral key"))?;
+    let ephemeral_key = ephemeral.sk_to_pk().compress();
+
+    // e(r·H(epoch), s·G1)
+    let shared = ephemeral.sign(&identity(epoch), EPOCH_KEY_DST, &[]);
+    let secret = shared_secret(&shared, &master_key, &ephemeral_key);
+
+    let mut ciphertext = serde_json::to_vec(tx).expect("transaction serialization cannot fail");
+    apply_keystream(&secret, &mut ciphertext);
+    Ok(EncryptedTransaction {
+        epoch,
+        gas_limit: tx.gas_limit,
+        ephemeral_key: Bytes::from(ephemeral_key.to_vec()),
+        tag: tag(&secret, &ciphertext),
+        ciphertext: Bytes::from(ciphertext),
+    })
+}
+
+/// Decrypt `encrypted` with its epoch's decryption key
+///
+/// The key is not checked against the master key (see [`verify_epoch_key`]);
+/// a wrong one fails the authentication tag.
+///
+/// # Returns
+/// * `Ok(UserTransaction)` inside
+/// * `Err(ValidationError::InvalidEncryption)` if the ephemeral key or epoch
+///   key is malformed, the tag does not match, or the plaintext is not a transaction
+pub fn decrypt_transaction(encrypted: &EncryptedTransaction, epoch_key: &[u8]) -> Result<UserTransaction, ValidationError> {
+    let ephemeral_key = PublicKey::key_validate(&encrypted.ephemeral_key).map_err(|e| invalid(e, "malformed ephemeral key"))?;
+    let epoch_key = Signature::sig_validate(epoch_key, true).map_err(|e| invalid(e, "malformed epoch key"))?;
+
+    // e(s·H(epoch), r·G1)
+    let secret = shared_secret(&epoch_key, &ephemeral_key, &encrypted.ephemeral_key);
+    if tag(&secret, &encrypted.ciphertext) != encrypted.tag {
+        return Err(ValidationError::InvalidEncryption { reason: "authentication tag mismatch".to_string() });
+    }
+    let mut plaintext = encrypted.ciphertext.to_vec();
+    apply_keystream(&secret, &mut plaintext);
+    serde_json::from_slice(&plaintext)
+        .map_err(|e| ValidationError::InvalidEncryption { reason: format!("not a transaction: {}", e) })
+}
+
+/// Hash of the pairing of a G2 and a G1 point, bound to the ephemeral key
+fn shared_secret(g2: &Signature, g1: &PublicKey, ephemeral_key: &[u8]) -> H256 {
+    let pairing = blst_fp12::miller_loop(&blst_p2_affine::from(*g2), &blst_p1_affine::from(*g1)).final_exp();
+    H256::from(keccak256([pairing.to_bendian().as_slice(), ephemeral_key].concat()))
+}
+
+/// XOR `data` with the keystream `keccak256(secret || counter)`
+fn apply_keystream(secret: &H256, data: &mut [u8]) {
+    for (counter, chunk) in data.chunks_mut(32).enumerate() {
+        let block = keccak256([secret.as_bytes(), &(counter as u64).to_be_bytes()].concat());
+        chunk.iter_mut().zip(block).for_each(|(byte, key)| *byte ^= key);
+    }
+}
+
+/// Authentication tag `keccak256("tag" || secret || ciphertext)`
+fn tag(secret: &H256, ciphertext: &[u8]) -> H256 {
+    H256::from(keccak256([b"tag".as_slice(), secret.as_bytes(), ciphertext].concat()))
+}
+
+/// Map a blst error to `InvalidEncryption`
+fn invalid(error: BLST_ERROR, reason: &str) -> ValidationError {
+    ValidationError::InvalidEncryption { reason: format!("{} ({:?})", reason, error) }
+}
@@ -1,28 +1,612 @@
 //! Transaction Pool Module
 //! 
 //! This module implements a pool for pending user transactions.
-//! Transactions are stored in a FIFO queue and retrieved by the batch engine.
+//! Transactions are stored in a FIFO queue and retrieved by the batch engine,
+//! either in arrival order (`take`), best-fee first (`take_by_priority`) or
+//! round-robin across senders (`take_fair`).
+//! The queue can be inspected without consuming it (`len`, `peek`, `view`).
+//! The pool is bounded: once full, the lowest-fee transaction is evicted to
+//! make room for a better-paying one. Transactions that wait longer than the
+//! configured TTL are expired by a background task. Pending transactions are
+//! indexed by hash so identical re-submissions are rejected.
+//!
+//! Fees are compared by fee cap: `gas_price`, or `max_fee_per_gas` for
+//! EIP-1559 transactions. The pool does not know the base fee, so the fee
+//! floor, eviction and `take_by_priority` all rank by what a sender may pay.
+//! A dynamic fee floor rises with occupancy and with the fee caps recent
+//! congested batches cut off at, pricing senders out while the pool is busy.
+//!
+//! Transactions from configured local senders (e.g. the operator's own
+//! maintenance accounts) skip the fee floor and capacity limit, are never
+//! evicted, and are taken ahead of everyone else's.
+//!
+//! When a write-ahead log is configured, every change is logged so pending
+//! transactions survive restarts (see [`super::wal`]).
+//!
+//! # Sharding
+//! Storage is split into shards by sender address, each behind its own lock,
+//! so concurrent submissions from different senders don't contend. Admission
+//! only locks the sender's shard unless the pool is full and something must
+//! be evicted. Queue positions are global sequence numbers, so operations that
+//! span the pool (batching, eviction, expiry) lock every shard, always in
+//! shard order, and merge their contents.
+//!
+//! # Indexes
+//! Besides the arrival-ordered queue, each shard keeps:
+//! - a fee index over its transactions (cheapest first), for eviction
+//! - per-sender lists in nonce order, and a priority index over each sender's
+//!   next transaction, so the top N can be pulled without sorting the pool
+//! - each sender's pending spend (the sum of its transactions' maximum cost),
+//!   so balance checks can count what is already committed
+//! - the transactions with an inclusion deadline, earliest first, so those
+//!   falling due can be taken ahead of the scheduling policy (`take_due`)
 
-use crate::UserTransaction;
-use std::collections::VecDeque;
-use tokio::sync::RwLock;
+use super::events::{PoolEvent, EVENT_CHANNEL_CAPACITY};
+use super::metrics::PoolMetrics;
+use super::snapshot::PoolSnapshot;
+use super::wal::{PoolWal, RecoveredTransaction};
+use crate::{UserTransaction, PoolError, config::PoolConfig, scheduler::fair_order};
+use ethers::types::{Address, H256, U256};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::iter::Peekable;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info};
+
+/// How often the expiry task scans the pool for stale transactions
+const EXPIRY_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A pending transaction together with its pool bookkeeping
+struct PoolEntry {
+    /// The validated user transaction
+    tx: UserTransaction,
+    /// Cached transaction hash
+    hash: H256,
+    /// When the transaction entered the pool, in unix milliseconds (used for TTL expiry)
+    added_at_ms: u64,
+}
+
+/// Ordering key for fee-priority selection: local sender, then fee cap, then boost bid
+type Priority = (bool, U256, U256);
+
+/// Sequence number assigned to the first transaction (leaves room for `push_front`)
+const FIRST_SEQ: u64 = 1 << 63;
+
+/// Pending transactions of the senders mapped to one shard
+///
+/// Every transaction has a sequence number giving its position in the
+/// pool-wide queue. The shard's queue and all its indexes must always be
+/// updated together, through `insert` and `remove`.
+struct PoolInner {
+    /// Pending transactions keyed by sequence number (queue order)
+    entries: BTreeMap<u64, PoolEntry>,
+    /// Sequence number of every pending transaction, by hash
+    hashes: HashMap<H256, u64>,
+    /// All transactions, cheapest fee cap first (oldest first among equals)
+    by_fee: BTreeSet<(U256, u64)>,
+    /// Each sender's transactions in queue (and therefore nonce) order
+    senders: HashMap<Address, VecDeque<u64>>,
+    /// Each sender's next transaction, best priority first
+    ready: BTreeSet<(Reverse<Priority>, u64)>,
+    /// Sum of each sender's pending transactions' maximum cost
+    spend: HashMap<Address, U256>,
+    /// Transactions with a deadline, earliest first (oldest first among equals)
+    deadlines: BTreeSet<(u64, u64)>,
+    /// Local senders, whose transactions always rank first
+    locals: Arc<HashSet<Address>>,
+}
+
+impl PoolInner {
+    /// Creates an empty shard
+    fn new(locals: Arc<HashSet<Address>>) -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            hashes: HashMap::new(),
+            by_fee: BTreeSet::new(),
+            senders: HashMap::new(),
+            ready: BTreeSet::new(),
+            spend: HashMap::new(),
+            deadlines: BTreeSet::new(),
+            locals,
+        }
+    }
+
+    /// Add an entry at queue position `seq`, at the back (arrivals) or front
+    /// (requeued) of its sender's transactions
+    fn insert(&mut self, seq: u64, entry: PoolEntry, front: bool) {
+        let queue = self.senders.entry(entry.tx.from).or_default();
+        if front {
+            // Requeued transactions precede the sender's remaining ones
+            if let Some(&old_head) = queue.front() {
+                let old_priority = priority(&self.entries[&old_head].tx, &self.locals);
+                self.ready.remove(&(Reverse(old_priority), old_head));
+            }
+            queue.push_front(seq);
+            self.ready.insert((Reverse(priority(&entry.tx, &self.locals)), seq));
+        } else {
+            if queue.is_empty() {
+                self.ready.insert((Reverse(priority(&entry.tx, &self.locals)), seq));
+            }
+            queue.push_back(seq);
+        }
+
+        let spend = self.spend.entry(entry.tx.from).or_default();
+        *spend = spend.saturating_add(entry.tx.max_cost());
+        self.hashes.insert(entry.hash, seq);
+        self.by_fee.insert((entry.tx.fee_cap(), seq));
+        if let Some(deadline) = entry.tx.deadline {
+            self.deadlines.insert((deadline, seq));
+        }
+        self.entries.insert(seq, entry);
+    }
+
+    /// Remove an entry from the queue and every index
+    fn remove(&mut self, seq: u64) -> Option<PoolEntry> {
+        let entry = self.entries.remove(&seq)?;
+        self.hashes.remove(&entry.hash);
+        self.by_fee.remove(&(entry.tx.fee_cap(), seq));
+        if let Some(deadline) = entry.tx.deadline {
+            self.deadlines.remove(&(deadline, seq));
+        }
+
+        if let Some(queue) = self.senders.get_mut(&entry.tx.from) {
+            if queue.front() == Some(&seq) {
+                // The sender's next transaction becomes selectable
+                queue.pop_front();
+                self.ready.remove(&(Reverse(priority(&entry.tx, &self.locals)), seq));
+                if let Some(&next) = queue.front() {
+                    self.ready.insert((Reverse(priority(&self.entries[&next].tx, &self.locals)), next));
+                }
+            } else {
+                queue.retain(|other| *other != seq);
+            }
+            if queue.is_empty() {
+                self.senders.remove(&entry.tx.from);
+                self.spend.remove(&entry.tx.from);
+            } else if let Some(spend) = self.spend.get_mut(&entry.tx.from) {
+                *spend = spend.saturating_sub(entry.tx.max_cost());
+            }
+        }
+        Some(entry)
+    }
+
+    /// Cheapest transaction that may be evicted (oldest first on ties; never local)
+    fn cheapest_evictable(&self) -> Option<(U256, u64)> {
+        self.by_fee
+            .iter()
+            .find(|(_, seq)| !self.locals.contains(&self.entries[seq].tx.from))
+            .copied()
+    }
+}
+
+/// Fee-priority key of a transaction
+fn priority(tx: &UserTransaction, locals: &HashSet<Address>) -> Priority {
+    (locals.contains(&tx.from), tx.fee_cap(), tx.boost_bid.unwrap_or_default())
+}
+
+/// Merges per-shard iterators, each ascending by key, into one ascending sequence
+struct MergeSorted<I: Iterator> {
+    iters: Vec<Peekable<I>>,
+}
+
+impl<I: Iterator> MergeSorted<I> {
+    fn new(iters: impl IntoIterator<Item = I>) -> Self {
+        Self {
+            iters: iters.into_iter().map(Iterator::peekable).collect(),
+        }
+    }
+}
+
+impl<K: Ord, V, I: Iterator<Item = (K, V)>> Iterator for MergeSorted<I> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut best: Option<(usize, &K)> = None;
+        for (idx, iter) in self.iters.iter_mut().enumerate() {
+            if let Some((key, _)) = iter.peek()
+                && best.is_none_or(|(_, best_key)| key < best_key)
+            {
+                best = Some((idx, key));
+            }
+        }
+        let idx = best?.0;
+        self.iters[idx].next()
+    }
+}
+
+/// Every shard's entries, in pool-wide queue order, tagged with their shard
+fn queue_order<S: std::ops::Deref<Target = PoolInner>>(
+    shards: &[S],
+) -> impl Iterator<Item = (u64, (usize, &PoolEntry))> {
+    MergeSorted::new(shards.iter().enumerate().map(|(shard, inner)| {
+        inner.entries.iter().map(move |(&seq, entry)| (seq, (shard, entry)))
+    }))
+}
 
 /// Pool for pending user transactions
 /// 
 /// Stores validated transactions in a FIFO queue waiting to be batched.
-/// Uses VecDeque for efficient insertion at the back and removal from the front.
-/// Protected by RwLock for concurrent access.
+/// Each shard's queue is a BTreeMap keyed by sequence number, so transactions
+/// can be removed from anywhere (eviction, priority selection) in O(log n).
+/// Each shard is protected by its own RwLock.
 pub struct TransactionPool {
-    /// Queue of pending transactions, protected by a read-write lock
-    transactions: RwLock<VecDeque<UserTransaction>>,
+    /// Pending transactions and their indexes, sharded by sender
+    shards: Vec<RwLock<PoolInner>>,
+    /// Number of pending transactions across all shards
+    len: AtomicUsize,
+    /// Next sequence number at the back of the queue
+    next_back: AtomicU64,
+    /// Next sequence number at the front of the queue
+    next_front: AtomicU64,
+    /// Local senders, exempt from the fee floor, capacity limit and eviction
+    locals: Arc<HashSet<Address>>,
+    /// Pool configuration (capacity limit, TTL)
+    config: PoolConfig,
+    /// Broadcast channel for pool events
+    events: broadcast::Sender<PoolEvent>,
+    /// Lossless channels for removals (see `subscribe_removals`)
+    removals: Mutex<Vec<mpsc::UnboundedSender<PoolEvent>>>,
+    /// Optional write-ahead log for persistence across restarts
+    wal: Option<PoolWal>,
+    /// Senders refused at admission, seeded from the config
+    denylist: RwLock<HashSet<Address>>,
+    /// In permissioned mode, the only senders admitted (seeded from the config)
+    allowlist: Option<RwLock<HashSet<Address>>>,
+    /// Inclusion cutoffs of the most recent batches, oldest first (0 if not congested)
+    inclusion_cutoffs: Mutex<VecDeque<U256>>,
 }
 
 impl TransactionPool {
-    /// Creates a new empty transaction pool
-    pub fn new() -> Self {
+    /// Creates a new empty, in-memory transaction pool
+    ///
+    /// The pool is not persisted, even if `wal_path` is configured; use
+    /// [`TransactionPool::open`] for a persistent pool.
+    ///
+    /// # Arguments
+    /// * `config` - Pool configuration (capacity limit, TTL, shard count)
+    pub fn new(config: PoolConfig) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let locals: Arc<HashSet<Address>> = Arc::new(config.locals.iter().copied().collect());
         Self {
-            transactions: RwLock::new(VecDeque::new()),
+            shards: (0..config.shards.max(1))
+                .map(|_| RwLock::new(PoolInner::new(locals.clone())))
+                .collect(),
+            len: AtomicUsize::new(0),
+            next_back: AtomicU64::new(FIRST_SEQ),
+            next_front: AtomicU64::new(FIRST_SEQ - 1),
+            locals,
+            denylist: RwLock::new(config.denylist.iter().copied().collect()),
+            allowlist: config.allowlist.as_ref().map(|senders| RwLock::new(senders.iter().copied().collect())),
+            config,
+            events,
+            removals: Mutex::new(Vec::new()),
+            wal: None,
+            inclusion_cutoffs: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Opens a transaction pool, persisted to `wal_path` if configured
+    ///
+    /// The pool starts empty. Transactions recovered from the log are returned
+    /// so the caller can revalidate them and put them back with [`Self::readmit`]
+    /// (or drop them with [`Self::discard`]).
+    ///
+    /// # Returns
+    /// The pool and the recovered pending transactions, in admission order
+    pub fn open(config: PoolConfig) -> anyhow::Result<(Self, Vec<RecoveredTransaction>)> {
+        let Some(path) = config.wal_path.clone() else {
+            return Ok((Self::new(config), Vec::new()));
+        };
+
+        let (wal, recovered) = PoolWal::open(&path)?;
+        info!("Recovered {} pending transactions from {}", recovered.len(), path);
+
+        let mut pool = Self::new(config);
+        pool.wal = Some(wal);
+        Ok((pool, recovered))
+    }
+
+    /// Put a recovered transaction back into the pool
+    ///
+    /// The original admission time is kept, so transactions past their TTL are
+    /// dropped instead, as are transactions from senders denylisted (or no
+    /// longer allowlisted) since.
+    /// Capacity limits are not applied: these transactions were already
+    /// admitted before the restart.
+    ///
+    /// # Returns
+    /// `true` if the transaction was readmitted
+    pub async fn readmit(&self, recovered: RecoveredTransaction) -> bool {
+        let hash = recovered.tx.hash();
+        let ttl_ms = self.config.tx_ttl_secs.saturating_mul(1000);
+        if now_ms().saturating_sub(recovered.added_at_ms) >= ttl_ms
+            || self.is_denied(&recovered.tx.from).await
+            || !self.is_allowlisted(&recovered.tx.from).await
+        {
+            self.discard(hash);
+            return false;
         }
+
+        let mut inner = self.shard(&recovered.tx.from).write().await;
+        if inner.hashes.contains_key(&hash) {
+            return false;
+        }
+        // Already in the log from before the restart, so not re-logged
+        self.len.fetch_add(1, Ordering::SeqCst);
+        self.insert(&mut inner, PoolEntry {
+            tx: recovered.tx,
+            hash,
+            added_at_ms: recovered.added_at_ms,
+        }, false);
+        true
+    }
+
+    /// Record that a recovered transaction will not be readmitted
+    pub fn discard(&self, tx_hash: H256) {
+        if let Some(wal) = &self.wal {
+            wal.append_removed(tx_hash);
+        }
+    }
+
+    /// Check whether a transaction with this hash is pending
+    pub async fn contains(&self, tx_hash: &H256) -> bool {
+        for shard in &self.shards {
+            if shard.read().await.hashes.contains_key(tx_hash) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Get a pending transaction by its hash
+    pub async fn get(&self, tx_hash: &H256) -> Option<UserTransaction> {
+        for shard in &self.shards {
+            let inner = shard.read().await;
+            if let Some(seq) = inner.hashes.get(tx_hash) {
+                return Some(inner.entries[seq].tx.clone());
+            }
+        }
+        None
+    }
+
+    /// Nonces of `sender`'s pending transactions, lowest first
+    pub async fn pending_nonces(&self, sender: &Address) -> Vec<u64> {
+        let inner = self.shards[self.shard_index(sender)].read().await;
+        inner
+            .senders
+            .get(sender)
+            .map(|queue| queue.iter().map(|seq| inner.entries[seq].tx.nonce).collect())
+            .unwrap_or_default()
+    }
+
+    /// Total maximum cost of `sender`'s pending transactions
+    pub async fn pending_spend(&self, sender: &Address) -> U256 {
+        let inner = self.shards[self.shard_index(sender)].read().await;
+        inner.spend.get(sender).copied().unwrap_or_default()
+    }
+
+    /// Number of pending transactions
+    pub async fn len(&self) -> usize {
+        self.len.load(Ordering::SeqCst)
+    }
+
+    /// Check whether the pool has no pending transactions
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Copy up to `n` transactions from the front of the queue without removing them
+    ///
+    /// # Returns
+    /// The next transactions `take` would return, in the same order
+    pub async fn peek(&self, n: usize) -> Vec<UserTransaction> {
+        self.view().await.iter().take(n).cloned().collect()
+    }
+
+    /// Borrow a read-only view of the pending transactions
+    ///
+    /// The view holds every shard's read lock, so writers (new submissions,
+    /// batching, expiry) wait until it is dropped. Keep it short-lived.
+    pub async fn view(&self) -> PoolView<'_> {
+        PoolView {
+            shards: self.read_all().await,
+        }
+    }
+
+    /// Copy every pending transaction, front of the queue first, with its admission time
+    pub async fn snapshot(&self) -> PoolSnapshot {
+        let shards = self.read_all().await;
+        PoolSnapshot {
+            exported_at_ms: now_ms(),
+            transactions: queue_order(&shards)
+                .map(|(_, (_, entry))| RecoveredTransaction {
+                    tx: entry.tx.clone(),
+                    added_at_ms: entry.added_at_ms,
+                })
+                .collect(),
+        }
+    }
+
+    /// Lowest gas price the pool currently admits
+    ///
+    /// The absolute floor. With a dynamic floor configured, raised to a
+    /// percentile of pending gas prices once the pool is full enough, and to
+    /// the lowest inclusion cutoff of the recent batches.
+    pub async fn fee_floor(&self) -> U256 {
+        self.floor(&self.read_all().await)
+    }
+
+    /// Fee floor for the given pool contents
+    fn floor<S: std::ops::Deref<Target = PoolInner>>(&self, shards: &[S]) -> U256 {
+        let config = &self.config.fee_floor;
+        let absolute = U256::from(config.min_gas_price);
+        let Some(dynamic) = &config.dynamic else {
+            return absolute;
+        };
+
+        let absolute = absolute.max(self.inclusion_floor(dynamic.inclusion_window));
+        let depth: usize = shards.iter().map(|inner| inner.by_fee.len()).sum();
+        if depth == 0 || (depth as f64) < dynamic.start_fill_ratio * self.config.max_size as f64 {
+            return absolute;
+        }
+        // Nearest-rank percentile over the merged fee indexes (cheapest first)
+        let rank = (dynamic.percentile * depth as f64).ceil() as usize;
+        let pending = MergeSorted::new(shards.iter().map(|inner| inner.by_fee.iter().map(|key| (*key, ()))))
+            .nth(rank.clamp(1, depth) - 1)
+            .map(|((gas_price, _), ())| gas_price)
+            .unwrap_or_default();
+        absolute.max(pending)
+    }
+
+    /// Lowest inclusion cutoff of the last `window` batches (0 if none were recorded)
+    fn inclusion_floor(&self, window: usize) -> U256 {
+        let cutoffs = self.inclusion_cutoffs.lock().unwrap();
+        cutoffs.iter().rev().take(window).min().copied().unwrap_or_default()
+    }
+
+    /// Record the inclusion cutoff of a batch that took `taken`
+    ///
+    /// If transactions were left behind, the cutoff is the lowest fee cap among
+    /// the taken ones from non-local senders; otherwise the batch was not
+    /// congested and the cutoff is 0. Batches of local senders' transactions
+    /// only are not recorded.
+    fn record_cutoff(&self, shards: &[RwLockWriteGuard<'_, PoolInner>], taken: &[UserTransaction]) {
+        let Some(window) = self.config.fee_floor.dynamic.as_ref().map(|dynamic| dynamic.inclusion_window) else {
+            return;
+        };
+        let left_behind = shards.iter().any(|inner| !inner.entries.is_empty());
+        let cutoff = if left_behind {
+            match taken.iter().filter(|tx| !self.locals.contains(&tx.from)).map(|tx| tx.fee_cap()).min() {
+                Some(cutoff) => cutoff,
+                None => return,
+            }
+        } else {
+            U256::zero()
+        };
+        let mut cutoffs = self.inclusion_cutoffs.lock().unwrap();
+        cutoffs.push_back(cutoff);
+        while cutoffs.len() > window {
+            cutoffs.pop_front();
+        }
+    }
+
+    /// Check whether a sender is local (exempt from the fee floor and capacity limit)
+    pub fn is_local(&self, sender: &Address) -> bool {
+        self.locals.contains(sender)
+    }
+
+    /// Check whether a sender is denylisted
+    pub async fn is_denied(&self, sender: &Address) -> bool {
+        self.denylist.read().await.contains(sender)
+    }
+
+    /// Refuse further transactions from `sender`
+    ///
+    /// Transactions already pending are not removed.
+    ///
+    /// # Returns
+    /// `true` if the sender was not denylisted before
+    pub async fn deny(&self, sender: Address) -> bool {
+        self.denylist.write().await.insert(sender)
+    }
+
+    /// Remove `sender` from the denylist
+    ///
+    /// # Returns
+    /// `true` if the sender was denylisted
+    pub async fn allow(&self, sender: &Address) -> bool {
+        self.denylist.write().await.remove(sender)
+    }
+
+    /// Current denylist, sorted by address
+    pub async fn denylist(&self) -> Vec<Address> {
+        let mut senders: Vec<Address> = self.denylist.read().await.iter().copied().collect();
+        senders.sort();
+        senders
+    }
+
+    /// Whether the pool runs in permissioned mode (only allowlisted senders)
+    pub fn is_permissioned(&self) -> bool {
+        self.allowlist.is_some()
+    }
+
+    /// Check whether a sender may submit (always, unless permissioned)
+    pub async fn is_allowlisted(&self, sender: &Address) -> bool {
+        match &self.allowlist {
+            Some(allowlist) => allowlist.read().await.contains(sender),
+            None => true,
+        }
+    }
+
+    /// Add `sender` to the allowlist
+    ///
+    /// # Returns
+    /// * `Some(true)` if the sender was not allowlisted before
+    /// * `None` if the pool is not permissioned
+    pub async fn approve(&self, sender: Address) -> Option<bool> {
+        Some(self.allowlist.as_ref()?.write().await.insert(sender))
+    }
+
+    /// Remove `sender` from the allowlist
+    ///
+    /// Transactions already pending are not removed.
+    ///
+    /// # Returns
+    /// * `Some(true)` if the sender was allowlisted
+    /// * `None` if the pool is not permissioned
+    pub async fn revoke(&self, sender: &Address) -> Option<bool> {
+        Some(self.allowlist.as_ref()?.write().await.remove(sender))
+    }
+
+    /// Current allowlist, sorted by address (`None` if not permissioned)
+    pub async fn allowlist(&self) -> Option<Vec<Address>> {
+        let mut senders: Vec<Address> = self.allowlist.as_ref()?.read().await.iter().copied().collect();
+        senders.sort();
+        Some(senders)
+    }
+
+    /// Snapshot depth, age and fee distribution gauges
+    pub async fn metrics(&self) -> PoolMetrics {
+        let now = now_ms();
+        let shards = self.read_all().await;
+
+        let mut ages: Vec<u64> = shards
+            .iter()
+            .flat_map(|inner| inner.entries.values())
+            .map(|entry| now.saturating_sub(entry.added_at_ms))
+            .collect();
+        ages.sort_unstable();
+        let mut fees: Vec<U256> = shards
+            .iter()
+            .flat_map(|inner| inner.by_fee.iter().map(|(fee, _)| *fee))
+            .collect();
+        fees.sort_unstable();
+
+        PoolMetrics::from_sorted(&ages, &fees)
+    }
+
+    /// Subscribe to pool events
+    ///
+    /// Each subscriber receives every event emitted after it subscribed:
+    /// admissions and requeues (`Added`), evictions, transactions taken for a
+    /// batch (`Batched`), and expirations.
+    pub fn subscribe(&self) -> broadcast::Receiver<PoolEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribe to removals from the pool, without ever missing one
+    ///
+    /// Receives the `Evicted`, `Expired` and `Dropped` events emitted after
+    /// subscribing. Unlike [`Self::subscribe`], the channel is unbounded: a
+    /// slow subscriber delays nothing and lags behind nothing, so it can be
+    /// trusted to give back every removed transaction's nonce.
+    pub fn subscribe_removals(&self) -> mpsc::UnboundedReceiver<PoolEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.removals.lock().unwrap().push(sender);
+        receiver
     }
     
     /// Add a validated transaction to the pool
@@ -30,29 +614,560 @@ impl TransactionPool {
     /// Transactions are added to the back of the queue (FIFO ordering).
     /// Called by the API server after a transaction passes validation.
     /// 
+    /// # Eviction
+    /// If the pool is at `max_size`, the transaction with the lowest fee cap
+    /// (oldest first among equals) is evicted, provided the new transaction pays
+    /// strictly more. Otherwise the new transaction is rejected. Local senders'
+    /// transactions are never evicted, and are admitted even when the pool is
+    /// full or they pay less than the fee floor.
+    ///
+    /// Emits `PoolEvent::Added`, preceded by `PoolEvent::Evicted` if another
+    /// transaction was evicted.
+    ///
     /// # Arguments
     /// * `tx` - The validated user transaction to add
-    pub async fn add(&self, tx: UserTransaction) {
-        // Acquire write lock to add transaction
-        let mut txs = self.transactions.write().await;
-        txs.push_back(tx);
+    ///
+    /// # Returns
+    /// * `Ok(None)` if the transaction was added without eviction
+    /// * `Ok(Some(evicted))` if another transaction was evicted to make room
+    /// * `Err(PoolError::AlreadyKnown)` if an identical transaction is pending
+    /// * `Err(PoolError::PoolFull)` if the pool is full and the fee is too low
+    /// * `Err(PoolError::Denied)` if the sender is denylisted
+    /// * `Err(PoolError::Underpriced)` if the fee cap is below the fee floor
+    /// * `Err(PoolError::NotAllowlisted)` if the pool is permissioned and the
+    ///   sender is not allowlisted
+    pub async fn add(&self, tx: UserTransaction) -> Result<Option<UserTransaction>, PoolError> {
+        let hash = tx.hash();
+
+        if self.is_denied(&tx.from).await {
+            return Err(PoolError::Denied { sender: tx.from });
+        }
+        if !self.is_allowlisted(&tx.from).await {
+            return Err(PoolError::NotAllowlisted { sender: tx.from });
+        }
+
+        // Local senders bypass the fee floor and the capacity limit
+        let local = self.locals.contains(&tx.from);
+
+        // The floor reads every shard, so it is computed before locking the sender's
+        let min_gas_price = if local { U256::zero() } else { self.fee_floor().await };
+
+        // Fast path: only the sender's shard is locked
+        let shard = self.shard_index(&tx.from);
+        {
+            let mut inner = self.shards[shard].write().await;
+
+            // Reject exact duplicates: a second copy would double-execute
+            if inner.hashes.contains_key(&hash) {
+                return Err(PoolError::AlreadyKnown { tx_hash: hash });
+            }
+
+            // Keep dust out before it can take a slot (or evict anything)
+            if tx.fee_cap() < min_gas_price {
+                return Err(PoolError::Underpriced { gas_price: tx.fee_cap(), min_gas_price });
+            }
+
+            if local {
+                self.len.fetch_add(1, Ordering::SeqCst);
+                self.admit(&mut inner, tx, hash, None);
+                return Ok(None);
+            }
+            if self.reserve_slot() {
+                self.admit(&mut inner, tx, hash, None);
+                return Ok(None);
+            }
+        }
+
+        // Slow path: the pool is full, so lock every shard to find the cheapest transaction
+        let mut shards = self.write_all().await;
+        if shards[shard].hashes.contains_key(&hash) {
+            return Err(PoolError::AlreadyKnown { tx_hash: hash });
+        }
+        if self.reserve_slot() {
+            // Room was freed while the shards were being locked
+            self.admit(&mut shards[shard], tx, hash, None);
+            return Ok(None);
+        }
+
+        let cheapest = shards
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, inner)| inner.cheapest_evictable().map(|(gas_price, seq)| ((gas_price, seq), idx)))
+            .min();
+        match cheapest {
+            Some(((min_gas_price, seq), idx)) if tx.fee_cap() > min_gas_price => {
+                let evicted = self.remove(&mut shards[idx], seq).map(|entry| {
+                    self.discard(entry.hash);
+                    entry.tx
+                });
+                debug!("Pool full, evicted transaction with gas price {}", min_gas_price);
+
+                // The evicted transaction's slot goes to the new one
+                self.len.fetch_add(1, Ordering::SeqCst);
+                self.admit(&mut shards[shard], tx, hash, evicted.as_ref());
+                Ok(evicted)
+            }
+            cheapest => Err(PoolError::PoolFull {
+                max_size: self.config.max_size,
+                min_gas_price: cheapest.map(|((price, _), _)| price).unwrap_or_default(),
+            }),
+        }
     }
     
-    /// Retrieve pending transactions for batching
+    /// Remove pending transactions for batching
     /// 
-    /// Removes and returns up to `max` transactions from the front of the queue.
-    /// Called by the batch engine when creating a new batch.
+    /// Removes and returns up to `max` transactions from the front of the queue,
+    /// local senders' transactions first. Called by the batch engine when
+    /// creating a new batch. Use `peek` or `view` to inspect the queue without
+    /// consuming it.
     /// 
     /// # Arguments
     /// * `max` - Maximum number of transactions to retrieve
     /// 
     /// # Returns
     /// A vector of up to `max` transactions (may be fewer if pool has less)
-    pub async fn get_pending(&self, max: usize) -> Vec<UserTransaction> {
-        // Acquire write lock to drain transactions
-        let mut txs = self.transactions.write().await;
-        let len = txs.len();
-        // Drain up to `max` transactions from the front
-        txs.drain(..max.min(len)).collect()
-    }
-}
\ No newline at end of file
+    pub async fn take(&self, max: usize) -> Vec<UserTransaction> {
+        // Acquire every shard's write lock to remove transactions
+        let mut shards = self.write_all().await;
+        let mut picks: Vec<(usize, u64)> = Vec::new();
+        if !self.locals.is_empty() {
+            // A sender is either local or not, so nonce order is kept
+            picks.extend(queue_order(&shards)
+                .filter(|(_, (_, entry))| self.locals.contains(&entry.tx.from))
+                .map(|(seq, (shard, _))| (shard, seq))
+                .take(max));
+        }
+        let remaining = max - picks.len();
+        picks.extend(queue_order(&shards)
+            .filter(|(_, (_, entry))| !self.locals.contains(&entry.tx.from))
+            .map(|(seq, (shard, _))| (shard, seq))
+            .take(remaining));
+        let taken = self.remove_all(&mut shards, picks);
+        self.record_cutoff(&shards, &taken);
+        self.emit_batched(&taken);
+        taken
+    }
+
+    /// Remove the best-paying pending transactions for batching
+    ///
+    /// Only each sender's next transaction is a candidate, so a sender's
+    /// transactions still come out in nonce order. Selection walks the shards'
+    /// priority indexes (local senders, then fee cap, then boost bid; oldest
+    /// first among equals), costing O(max · (shards + log n)) rather than a
+    /// sort of the whole pool.
+    ///
+    /// # Arguments
+    /// * `max` - Maximum number of transactions to retrieve
+    ///
+    /// # Returns
+    /// Up to `max` transactions, best priority first
+    pub async fn take_by_priority(&self, max: usize) -> Vec<UserTransaction> {
+        let mut shards = self.write_all().await;
+        let mut taken = Vec::new();
+        while taken.len() < max {
+            let best = shards
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, inner)| inner.ready.first().map(|key| (*key, idx)))
+                .min();
+            let Some(((_, seq), idx)) = best else {
+                break;
+            };
+            if let Some(entry) = self.remove(&mut shards[idx], seq) {
+                self.discard(entry.hash);
+                taken.push(entry.tx);
+            }
+        }
+        self.record_cutoff(&shards, &taken);
+        self.emit_batched(&taken);
+        taken
+    }
+
+    /// Remove pending transactions for batching, round-robin across senders
+    ///
+    /// Senders are served in rounds, one transaction each per round, in
+    /// virtual finish-time order (see [`fair_order`]); with a `base_fee`, each
+    /// sender is weighted by the tip of its first transaction at it. Local
+    /// senders still go first. A sender's transactions come out in nonce
+    /// order. The whole queue is ordered, costing O(n log n).
+    ///
+    /// # Returns
+    /// Up to `max` transactions, in the order they were served
+    pub async fn take_fair(&self, max: usize, base_fee: Option<U256>) -> Vec<UserTransaction> {
+        let mut shards = self.write_all().await;
+        let mut picks: Vec<(usize, u64)> = Vec::new();
+        if !self.locals.is_empty() {
+            picks.extend(queue_order(&shards)
+                .filter(|(_, (_, entry))| self.locals.contains(&entry.tx.from))
+                .map(|(seq, (shard, _))| (shard, seq))
+                .take(max));
+        }
+        let remaining = max - picks.len();
+        let queue: Vec<_> = queue_order(&shards)
+            .filter(|(_, (_, entry))| !self.locals.contains(&entry.tx.from))
+            .collect();
+        picks.extend(fair_order(queue, |(_, (_, entry))| &entry.tx, base_fee)
+            .into_iter()
+            .map(|(seq, (shard, _))| (shard, seq))
+            .take(remaining));
+        let taken = self.remove_all(&mut shards, picks);
+        self.record_cutoff(&shards, &taken);
+        self.emit_batched(&taken);
+        taken
+    }
+
+    /// Earliest deadline among pending transactions, if any has one
+    pub async fn earliest_deadline(&self) -> Option<u64> {
+        self.read_all()
+            .await
+            .iter()
+            .filter_map(|inner| inner.deadlines.first().map(|(deadline, _)| *deadline))
+            .min()
+    }
+
+    /// Remove pending transactions whose deadline is at or before `before`
+    ///
+    /// Earliest deadline first, each with its sender's earlier pending
+    /// transactions, which must be sealed first. A transaction that would
+    /// take more than `max` transactions in all is left for later.
+    ///
+    /// # Returns
+    /// Up to `max` transactions, each sender's in nonce order
+    pub async fn take_due(&self, before: u64, max: usize) -> Vec<UserTransaction> {
+        let mut shards = self.write_all().await;
+        let due = MergeSorted::new(shards.iter().enumerate().map(|(shard, inner)| {
+            inner.deadlines
+                .iter()
+                .take_while(move |(deadline, _)| *deadline <= before)
+                .map(move |&(deadline, seq)| ((deadline, seq), shard))
+        }));
+        let mut picked = HashSet::new();
+        let mut picks: Vec<(usize, u64)> = Vec::new();
+        for ((_, seq), shard) in due {
+            if picked.contains(&seq) {
+                continue;
+            }
+            let inner = &shards[shard];
+            let queue = &inner.senders[&inner.entries[&seq].tx.from];
+            let Some(position) = queue.iter().position(|other| *other == seq) else {
+                continue;
+            };
+            let needed: Vec<u64> = queue
+                .iter()
+                .take(position + 1)
+                .filter(|other| !picked.contains(*other))
+                .copied()
+                .collect();
+            if picks.len() + needed.len() > max {
+                continue;
+            }
+            picked.extend(needed.iter().copied());
+            picks.extend(needed.into_iter().map(|other| (shard, other)));
+        }
+        let taken = self.remove_all(&mut shards, picks);
+        self.emit_batched(&taken);
+        taken
+    }
+
+    /// Remove the earliest pending transaction of senders not yet represented
+    ///
+    /// Scans the queue in order and takes the first transaction of each sender
+    /// outside `exclude`, up to `n` senders. Only a sender's first transaction
+    /// is eligible, so its nonces stay in order.
+    ///
+    /// # Returns
+    /// At most `n` transactions, one per new sender, in queue order
+    pub async fn take_from_new_senders(&self, exclude: &HashSet<Address>, n: usize) -> Vec<UserTransaction> {
+        let mut shards = self.write_all().await;
+
+        let mut seen = HashSet::new();
+        let mut picks = Vec::new();
+        for (seq, (shard, entry)) in queue_order(&shards) {
+            if picks.len() >= n {
+                break;
+            }
+            let sender = entry.tx.from;
+            if !exclude.contains(&sender) && seen.insert(sender) {
+                picks.push((shard, seq));
+            }
+        }
+        let taken = self.remove_all(&mut shards, picks);
+        self.emit_batched(&taken);
+        taken
+    }
+
+    /// Remove exactly the given pending transactions, in the given order
+    ///
+    /// Used for externally proposed batches. All-or-nothing: every hash must be
+    /// pending, and each sender's transactions must appear in nonce order
+    /// starting from its earliest pending one, so no nonce gap is left behind.
+    ///
+    /// # Returns
+    /// * `Ok(txs)` in the order of `hashes`
+    /// * `Err(hash)` with the first offending hash (nothing is removed)
+    pub async fn take_exact(&self, hashes: &[H256]) -> Result<Vec<UserTransaction>, H256> {
+        let mut shards = self.write_all().await;
+
+        let mut taken_per_sender: HashMap<Address, usize> = HashMap::new();
+        let mut picks = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let Some((shard, seq)) = shards
+                .iter()
+                .enumerate()
+                .find_map(|(idx, inner)| inner.hashes.get(hash).map(|&seq| (idx, seq)))
+            else {
+                return Err(*hash);
+            };
+            let inner = &shards[shard];
+            let sender = inner.entries[&seq].tx.from;
+            let position = taken_per_sender.entry(sender).or_default();
+            if inner.senders.get(&sender).and_then(|queue| queue.get(*position)) != Some(&seq) {
+                return Err(*hash);
+            }
+            *position += 1;
+            picks.push((shard, seq));
+        }
+        let taken = self.remove_all(&mut shards, picks);
+        self.emit_batched(&taken);
+        Ok(taken)
+    }
+
+    /// Return transactions to the front of the queue, keeping their order
+    ///
+    /// Used when transactions were taken for a batch but not included after all.
+    /// Capacity limits are not applied (they were already admitted), and
+    /// transactions that are somehow already pending are skipped. The TTL
+    /// restarts from now, and each returned transaction is announced as `Added`.
+    pub async fn requeue(&self, txs: Vec<UserTransaction>) {
+        let added_at_ms = now_ms();
+        let mut shards = self.write_all().await;
+        for tx in txs.into_iter().rev() {
+            let hash = tx.hash();
+            let inner = &mut shards[self.shard_index(&tx.from)];
+            if inner.hashes.contains_key(&hash) {
+                continue;
+            }
+            if let Some(wal) = &self.wal {
+                wal.append_added(&tx, added_at_ms);
+            }
+            self.emit(PoolEvent::Added(tx.clone()));
+            self.len.fetch_add(1, Ordering::SeqCst);
+            self.insert(inner, PoolEntry {
+                tx,
+                hash,
+                added_at_ms,
+            }, true);
+        }
+    }
+
+    /// Remove every transaction that has waited longer than the TTL
+    ///
+    /// Emits a `PoolEvent::Expired` for each removed transaction.
+    ///
+    /// # Returns
+    /// The expired transactions
+    pub async fn expire_stale(&self) -> Vec<UserTransaction> {
+        let ttl_ms = self.config.tx_ttl_secs.saturating_mul(1000);
+        let now = now_ms();
+        let mut shards = self.write_all().await;
+        let stale: Vec<(usize, u64)> = queue_order(&shards)
+            .filter(|(_, (_, entry))| now.saturating_sub(entry.added_at_ms) >= ttl_ms)
+            .map(|(seq, (shard, _))| (shard, seq))
+            .collect();
+        let expired = self.remove_all(&mut shards, stale);
+        drop(shards);
+
+        for tx in &expired {
+            self.emit(PoolEvent::Expired(tx.clone()));
+        }
+        expired
+    }
+
+    /// Drop `sender`'s pending transactions with a nonce of `nonce` or above
+    ///
+    /// Used once an earlier nonce of the sender was given back, since these
+    /// can no longer execute. Emits a `PoolEvent::Dropped` for each.
+    ///
+    /// # Returns
+    /// The dropped transactions, in the order they were admitted
+    pub async fn drop_from(&self, sender: &Address, nonce: u64) -> Vec<UserTransaction> {
+        let mut inner = self.shard(sender).write().await;
+        let seqs: Vec<u64> = match inner.senders.get(sender) {
+            Some(queue) => queue.iter().copied().filter(|seq| inner.entries[seq].tx.nonce >= nonce).collect(),
+            None => Vec::new(),
+        };
+        let dropped: Vec<UserTransaction> = seqs
+            .into_iter()
+            .filter_map(|seq| self.remove(&mut inner, seq))
+            .map(|entry| {
+                self.discard(entry.hash);
+                entry.tx
+            })
+            .collect();
+        drop(inner);
+
+        for tx in &dropped {
+            self.emit(PoolEvent::Dropped(tx.clone()));
+        }
+        dropped
+    }
+
+    /// Broadcast a pool event to current subscribers
+    fn emit(&self, event: PoolEvent) {
+        if matches!(event, PoolEvent::Evicted(_) | PoolEvent::Expired(_) | PoolEvent::Dropped(_)) {
+            // Subscribers that went away are forgotten
+            self.removals.lock().unwrap().retain(|removals| removals.send(event.clone()).is_ok());
+        }
+        // A send error only means nobody is subscribed
+        let _ = self.events.send(event);
+    }
+
+    /// Announce transactions taken for a batch
+    fn emit_batched(&self, taken: &[UserTransaction]) {
+        for tx in taken {
+            self.emit(PoolEvent::Batched(tx.clone()));
+        }
+    }
+
+    /// Shard index holding `sender`'s transactions
+    fn shard_index(&self, sender: &Address) -> usize {
+        // Addresses are hash-derived, so their low bytes spread senders evenly
+        (sender.to_low_u64_be() % self.shards.len() as u64) as usize
+    }
+
+    /// Shard holding `sender`'s transactions
+    fn shard(&self, sender: &Address) -> &RwLock<PoolInner> {
+        &self.shards[self.shard_index(sender)]
+    }
+
+    /// Read-lock every shard, in shard order
+    async fn read_all(&self) -> Vec<RwLockReadGuard<'_, PoolInner>> {
+        let mut guards = Vec::with_capacity(self.shards.len());
+        for shard in &self.shards {
+            guards.push(shard.read().await);
+        }
+        guards
+    }
+
+    /// Write-lock every shard, in shard order
+    ///
+    /// Locks are only ever taken in shard order, and a task holding a single
+    /// shard never waits on another, so this cannot deadlock.
+    async fn write_all(&self) -> Vec<RwLockWriteGuard<'_, PoolInner>> {
+        let mut guards = Vec::with_capacity(self.shards.len());
+        for shard in &self.shards {
+            guards.push(shard.write().await);
+        }
+        guards
+    }
+
+    /// Claim a slot below `max_size`, returning whether one was free
+    fn reserve_slot(&self) -> bool {
+        self.len
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| {
+                (len < self.config.max_size).then_some(len + 1)
+            })
+            .is_ok()
+    }
+
+    /// Log, announce and insert a newly admitted transaction whose slot is already counted
+    fn admit(&self, inner: &mut PoolInner, tx: UserTransaction, hash: H256, evicted: Option<&UserTransaction>) {
+        let added_at_ms = now_ms();
+        if let Some(wal) = &self.wal {
+            wal.append_added(&tx, added_at_ms);
+        }
+        if let Some(evicted) = evicted {
+            self.emit(PoolEvent::Evicted(evicted.clone()));
+        }
+        self.emit(PoolEvent::Added(tx.clone()));
+        self.insert(inner, PoolEntry {
+            tx,
+            hash,
+            added_at_ms,
+        }, false);
+    }
+
+    /// Insert an entry with the next sequence number at the back or front of the queue
+    ///
+    /// The caller accounts for the entry in `len`.
+    fn insert(&self, inner: &mut PoolInner, entry: PoolEntry, front: bool) {
+        let seq = if front {
+            self.next_front.fetch_sub(1, Ordering::SeqCst)
+        } else {
+            self.next_back.fetch_add(1, Ordering::SeqCst)
+        };
+        inner.insert(seq, entry, front);
+    }
+
+    /// Remove an entry from its shard and the pool-wide count
+    fn remove(&self, inner: &mut PoolInner, seq: u64) -> Option<PoolEntry> {
+        let entry = inner.remove(seq)?;
+        self.len.fetch_sub(1, Ordering::SeqCst);
+        Some(entry)
+    }
+
+    /// Remove the given `(shard, seq)` entries, logging each removal
+    fn remove_all(&self, shards: &mut [RwLockWriteGuard<'_, PoolInner>], picks: Vec<(usize, u64)>) -> Vec<UserTransaction> {
+        picks.into_iter()
+            .filter_map(|(shard, seq)| self.remove(&mut shards[shard], seq))
+            .map(|entry| {
+                self.discard(entry.hash);
+                entry.tx
+            })
+            .collect()
+    }
+
+    /// Start the background expiry loop
+    ///
+    /// Periodically drops transactions older than `tx_ttl_secs`, so stale
+    /// transactions never land in a batch long after submission.
+    /// Runs indefinitely.
+    pub async fn start_expiry(&self) {
+        info!("Pool expiry task started (ttl={}s)", self.config.tx_ttl_secs);
+        loop {
+            sleep(EXPIRY_SCAN_INTERVAL).await;
+            let expired = self.expire_stale().await;
+            if !expired.is_empty() {
+                info!("Expired {} stale transactions from the pool", expired.len());
+            }
+        }
+    }
+}
+
+/// Read-only view of the pending transactions, in queue order
+///
+/// Returned by [`TransactionPool::view`]; holds every shard's read lock.
+pub struct PoolView<'a> {
+    shards: Vec<RwLockReadGuard<'a, PoolInner>>,
+}
+
+impl PoolView<'_> {
+    /// Number of pending transactions
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|inner| inner.entries.len()).sum()
+    }
+
+    /// Check whether the pool has no pending transactions
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|inner| inner.entries.is_empty())
+    }
+
+    /// Iterate over pending transactions from the front of the queue
+    pub fn iter(&self) -> impl Iterator<Item = &UserTransaction> {
+        queue_order(&self.shards).map(|(_, (_, entry))| &entry.tx)
+    }
+}
+
+/// Current wall-clock time in unix milliseconds
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+impl Default for TransactionPool {
+    fn default() -> Self {
+        Self::new(PoolConfig::default())
+    }
+}
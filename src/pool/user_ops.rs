@@ -0,0 +1,97 @@
+//! User Operation Lane Module
+//!
+//! User operations from smart-contract wallets wait in their own lane instead
+//! of the transaction pool. The pool's fee ranking, replacement and eviction
+//! rules are built around `UserTransaction`s; the lane is a plain FIFO queue,
+//! so each wallet's operations leave it in nonce order.
+//!
+//! The lane is held in memory only: pending operations are lost on restart.
+
+use crate::{PoolError, UserOperation};
+use ethers::types::{Address, H256, U256};
+use std::collections::VecDeque;
+use tokio::sync::RwLock;
+
+/// Pending user operations, in arrival order
+pub struct UserOpPool {
+    operations: RwLock<VecDeque<UserOperation>>,
+    /// Most operations the lane holds
+    capacity: usize,
+}
+
+impl UserOpPool {
+    /// Creates an empty lane holding up to `capacity` operations
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            operations: RwLock::new(VecDeque::new()),
+            capacity,
+        }
+    }
+
+    /// Add a validated operation to the back of the lane
+    ///
+    /// # Returns
+    /// * `Ok(())` if the operation was queued
+    /// * `Err(PoolError::AlreadyKnown)` if an identical operation is pending
+    /// * `Err(PoolError::UserOpLaneFull)` if the lane is at capacity
+    pub async fn add(&self, op: UserOperation) -> Result<(), PoolError> {
+        let mut operations = self.operations.write().await;
+        let tx_hash = op.hash();
+        if operations.iter().any(|pending| pending.hash() == tx_hash) {
+            return Err(PoolError::AlreadyKnown { tx_hash });
+        }
+        if operations.len() >= self.capacity {
+            return Err(PoolError::UserOpLaneFull { capacity: self.capacity });
+        }
+        operations.push_back(op);
+        Ok(())
+    }
+
+    /// Check whether an operation with this hash is pending
+    pub async fn contains(&self, tx_hash: &H256) -> bool {
+        self.operations.read().await.iter().any(|op| op.hash() == *tx_hash)
+    }
+
+    /// Number of pending operations
+    pub async fn len(&self) -> usize {
+        self.operations.read().await.len()
+    }
+
+    /// Check whether the lane is empty
+    pub async fn is_empty(&self) -> bool {
+        self.operations.read().await.is_empty()
+    }
+
+    /// Remove and return up to `max` operations from the front of the lane
+    pub async fn take(&self, max: usize) -> Vec<UserOperation> {
+        let mut operations = self.operations.write().await;
+        let count = max.min(operations.len());
+        operations.drain(..count).collect()
+    }
+
+    /// Return operations to the front of the lane, keeping their order
+    ///
+    /// Used when a batch could not fit them, or failed after sealing. The
+    /// capacity is not enforced, so nothing already validated is dropped.
+    pub async fn requeue(&self, ops: Vec<UserOperation>) {
+        let mut operations = self.operations.write().await;
+        for op in ops.into_iter().rev() {
+            operations.push_front(op);
+        }
+    }
+
+    /// Check whether `sender` has pending operations
+    pub async fn has_pending(&self, sender: &Address) -> bool {
+        self.operations.read().await.iter().any(|op| op.sender == *sender)
+    }
+
+    /// Maximum cost of `sender`'s pending operations
+    pub async fn pending_spend(&self, sender: &Address) -> U256 {
+        self.operations
+            .read()
+            .await
+            .iter()
+            .filter(|op| op.sender == *sender)
+            .fold(U256::zero(), |spend, op| spend.saturating_add(op.max_cost()))
+    }
+}
@@ -0,0 +1,137 @@
+//! Pool Write-Ahead Log Module
+//!
+//! This module persists the pending transaction pool as an append-only log so
+//! that pending user transactions survive a sequencer restart.
+//!
+//! # Format
+//! One JSON-encoded `WalRecord` per line:
+//! - `Added`: a transaction entered the pool (with its wall-clock admission time)
+//! - `Removed`: a transaction left the pool (batched, evicted, or expired)
+//!
+//! Replaying the log in order yields exactly the pending set. The log is
+//! compacted (rewritten with only the pending transactions) every time it is opened.
+
+use crate::UserTransaction;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// A pending transaction recovered from the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveredTransaction {
+    /// The pending transaction
+    pub tx: UserTransaction,
+    /// When the transaction was originally admitted (unix milliseconds)
+    pub added_at_ms: u64,
+}
+
+/// A single log record
+#[derive(Debug, Serialize, Deserialize)]
+enum WalRecord {
+    /// Transaction entered the pool
+    Added(Box<RecoveredTransaction>),
+    /// Transaction left the pool
+    Removed(H256),
+}
+
+/// Append-only write-ahead log for the transaction pool
+pub struct PoolWal {
+    /// Buffered append handle (flushed after every record)
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl PoolWal {
+    /// Open a log, recovering and compacting its contents
+    ///
+    /// # Arguments
+    /// * `path` - Location of the log file (created if missing)
+    ///
+    /// # Returns
+    /// The opened log and the pending transactions it contained, in admission order
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<(Self, Vec<RecoveredTransaction>)> {
+        let path = path.as_ref();
+        let recovered = if path.exists() {
+            replay(path)?
+        } else {
+            Vec::new()
+        };
+
+        // Rewrite the log with only the pending transactions, atomically via rename
+        let tmp_path = PathBuf::from(format!("{}.tmp", path.display()));
+        {
+            let mut tmp = BufWriter::new(File::create(&tmp_path)?);
+            for entry in &recovered {
+                serde_json::to_writer(&mut tmp, &WalRecord::Added(Box::new(entry.clone())))?;
+                tmp.write_all(b"\n")?;
+            }
+            tmp.flush()?;
+            tmp.get_ref().sync_all()?;
+        }
+        std::fs::rename(&tmp_path, path)?;
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        let wal = Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        };
+        Ok((wal, recovered))
+    }
+
+    /// Log that a transaction entered the pool
+    pub fn append_added(&self, tx: &UserTransaction, added_at_ms: u64) {
+        self.append(&WalRecord::Added(Box::new(RecoveredTransaction {
+            tx: tx.clone(),
+            added_at_ms,
+        })));
+    }
+
+    /// Log that a transaction left the pool
+    pub fn append_removed(&self, tx_hash: H256) {
+        self.append(&WalRecord::Removed(tx_hash));
+    }
+
+    /// Append one record and flush it to the OS
+    ///
+    /// Write failures are logged rather than propagated: the in-memory pool
+    /// stays authoritative and a lost record only affects crash recovery.
+    fn append(&self, record: &WalRecord) {
+        let mut writer = self.writer.lock().unwrap();
+        let result = serde_json::to_writer(&mut *writer, record)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writer.write_all(b"\n"))
+            .and_then(|_| writer.flush());
+        if let Err(e) = result {
+            warn!("Failed to append to pool WAL: {:?}", e);
+        }
+    }
+}
+
+/// Replay a log file into the set of still-pending transactions
+///
+/// A truncated final line (e.g., from a crash mid-write) is ignored.
+fn replay(path: &Path) -> anyhow::Result<Vec<RecoveredTransaction>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    // Pending transactions keyed by hash, plus a sequence number to restore order
+    let mut pending: HashMap<H256, (usize, RecoveredTransaction)> = HashMap::new();
+    for (seq, line) in reader.lines().enumerate() {
+        let line = line?;
+        match serde_json::from_str::<WalRecord>(&line) {
+            Ok(WalRecord::Added(entry)) => {
+                pending.entry(entry.tx.hash()).or_insert((seq, *entry));
+            }
+            Ok(WalRecord::Removed(hash)) => {
+                pending.remove(&hash);
+            }
+            Err(e) => warn!("Skipping corrupt pool WAL record on line {}: {}", seq + 1, e),
+        }
+    }
+
+    let mut entries: Vec<_> = pending.into_values().collect();
+    entries.sort_by_key(|(seq, _)| *seq);
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
@@ -4,28 +4,389 @@
 //! The registry allows querying batch information without loading full transaction data.
 //! 
 //! # Storage
-//! Stores lightweight metadata for each batch:
-//! - Batch ID, transaction counts, timestamp
-//! - Scheduling policy used
-//! - Links to full batch data (if needed)
+//! Backed by SQLite (via sqlx). Stores:
+//! - Batch metadata: batch ID, transaction counts, timestamp, scheduling policy
+//! - Transaction observations: every outcome recorded for a transaction
+//!   (accepted, rejected, evicted, batched), used for forensic queries
+//! - API keys: quotas and usage counters for metered RPC access
+//! - Bond forfeitures: express-lane exits found to be spam
+//! - Sender guard adjustments: batches whose selection was diversified
+//! - Archived accounts: inactive state moved out of the hot cache, plus
+//!   witnesses recorded when such accounts are resurrected
+//! - Account snapshots: the state of every account a batch changed, as of
+//!   that batch, used to answer historical reads
+//! - Forced transactions: every L1 event ever queued, keyed by L1 transaction
+//!   hash and log index, with the batch that included it (if any)
+//! - Genesis: the chain ID and genesis hash the chain was started from
+//! - Batch witnesses: the execution witness of every sealed batch, as JSON
+//! - Batch submissions: the L1 lifecycle status of every sealed batch, with
+//!   the L1 transaction committing it
+//! - Deposits: the stage of every L1 deposit seen, from detection on L1 to
+//!   the L1 commitment of the batch crediting it
+//! - Withdrawals: the L1 payouts of the forced exits debited in each batch,
+//!   kept for good so they can be proven when claimed
+//! - L1 finality: the latest L1 blocks tagged `safe` and `finalized`, which
+//!   finalize committed batches and tell how final each deposit is
+//!
+//! # Deposit Stages
+//! Deposit stages follow the forced transaction records: a deposit is
+//! `Confirmed` when it is first queued, `Included` when its batch is sealed
+//! (`Confirmed` again if that batch is reinjected), and `Reorged` when its
+//! event is forgotten. Committing a batch credits its included deposits.
+//! Deposits held until they are deep enough are recorded by the L1 listener.
+//!
+//! # Pruning
+//! Account snapshots and batch witnesses grow with every batch. In archival
+//! mode they are all kept. Otherwise [`Registry::prune_history`] drops those
+//! of old batches, keeping each account's latest snapshot so reads from the
+//! retained batches still resolve.
 
-use crate::BatchMetadata;
+use crate::{
+    AccountState, ApiKeyUsage, ArchivedAccount, AuctionWin, BatchMetadata, BundleInclusion, BatchStatus, BatchSubmission, BondForfeiture,
+    DepositStage, DepositStatus, FinalityTags, ForcedEventType, ForcedTransaction, GenesisRecord, ResurrectionWitness,
+    SenderGuardAdjustment, TransactionOutcome, TransactionRecord, Withdrawal,
+    config::DatabaseConfig,
+    state::BatchWitness,
+};
+use anyhow::Context;
+use ethers::types::{Address, H256, U256};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use std::str::FromStr;
+
+/// Result of charging a request against an API key's quota
+#[derive(Debug, Clone)]
+pub enum QuotaCheck {
+    /// Request is within quota; usage has been incremented
+    Allowed(ApiKeyUsage),
+    /// Request would exceed the quota; usage was not incremented
+    Exceeded(ApiKeyUsage),
+    /// The key does not exist (or was revoked)
+    UnknownKey,
+}
 
 /// Batch metadata registry
 /// 
-/// Stores batch metadata in a persistent database for querying and auditing.
-/// Planned to use SQLite or PostgreSQL for storage.
+/// Stores batch metadata and transaction history in a persistent database
+/// for querying and auditing.
 pub struct Registry {
-    // TODO: Add database connection (e.g., sqlx::Pool<Postgres>)
+    /// SQLite connection pool
+    pool: SqlitePool,
 }
 
 impl Registry {
     /// Creates a new registry instance
     /// 
-    /// # Planned Implementation
-    /// Will establish a database connection pool
-    pub fn new() -> Self {
-        Self {}
+    /// Connects to the configured database (creating the file if missing)
+    /// and ensures all tables exist.
+    ///
+    /// # Arguments
+    /// * `config` - Database configuration (connection URL)
+    ///
+    /// # Returns
+    /// * `Ok(Registry)` if the connection and schema setup succeeded
+    /// * `Err` if the database is unreachable or the URL is invalid
+    pub async fn new(config: &DatabaseConfig) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::from_str(&config.url)
+            .with_context(|| format!("invalid database URL {}", config.url))?
+            .create_if_missing(true);
+
+        // Each in-memory connection is a separate database, so pin those to one connection
+        let max_connections = if config.url.contains(":memory:") { 1 } else { 5 };
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect_with(options)
+            .await?;
+
+        let registry = Self { pool };
+        registry.migrate().await?;
+        Ok(registry)
+    }
+
+    /// Creates the registry tables if they don't exist
+    async fn migrate(&self) -> anyhow::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS batches (
+                batch_id INTEGER PRIMARY KEY,
+                tx_count INTEGER NOT NULL,
+                forced_tx_count INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                scheduling_policy TEXT NOT NULL,
+                compression TEXT NOT NULL DEFAULT 'none',
+                raw_size INTEGER NOT NULL DEFAULT 0,
+                compressed_size INTEGER NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        // Databases created before batch data was compressed lack the size columns
+        self.add_column_if_missing("batches", "compression", "TEXT NOT NULL DEFAULT 'none'").await?;
+        self.add_column_if_missing("batches", "raw_size", "INTEGER NOT NULL DEFAULT 0").await?;
+        self.add_column_if_missing("batches", "compressed_size", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS tx_observations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                tx_hash TEXT NOT NULL,
+                sender TEXT NOT NULL,
+                nonce INTEGER NOT NULL,
+                outcome TEXT NOT NULL,
+                timestamp INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_tx_observations_sender_nonce
+                ON tx_observations (sender, nonce)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS api_keys (
+                key TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                max_requests INTEGER,
+                max_transactions INTEGER,
+                requests INTEGER NOT NULL,
+                transactions INTEGER NOT NULL,
+                window_start INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS bond_forfeitures (
+                l1_tx_hash TEXT PRIMARY KEY,
+                sender TEXT NOT NULL,
+                bond TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                batch_id INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS archived_accounts (
+                address TEXT PRIMARY KEY,
+                balance TEXT NOT NULL,
+                nonce INTEGER NOT NULL,
+                last_active_batch INTEGER NOT NULL,
+                archived_at_batch INTEGER NOT NULL,
+                state_hash TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS resurrection_witnesses (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                address TEXT NOT NULL,
+                balance TEXT NOT NULL,
+                nonce INTEGER NOT NULL,
+                archived_at_batch INTEGER NOT NULL,
+                resurrected_at_batch INTEGER NOT NULL,
+                state_hash TEXT NOT NULL,
+                verified INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS account_snapshots (
+                address TEXT NOT NULL,
+                batch_id INTEGER NOT NULL,
+                balance TEXT NOT NULL,
+                nonce INTEGER NOT NULL,
+                PRIMARY KEY (address, batch_id)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS forced_transactions (
+                l1_tx_hash TEXT NOT NULL,
+                l1_log_index INTEGER NOT NULL,
+                tx TEXT NOT NULL,
+                batch_id INTEGER,
+                PRIMARY KEY (l1_tx_hash, l1_log_index)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sender_guard_adjustments (
+                batch_id INTEGER PRIMARY KEY,
+                senders_before INTEGER NOT NULL,
+                senders_after INTEGER NOT NULL,
+                displaced INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS auction_wins (
+                batch_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                tx_hash TEXT NOT NULL,
+                bidder TEXT NOT NULL,
+                amount TEXT NOT NULL,
+                PRIMARY KEY (batch_id, position)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS bundle_inclusions (
+                batch_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                bundle_hash TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                PRIMARY KEY (batch_id, position)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS genesis (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                chain_id INTEGER NOT NULL,
+                genesis_hash TEXT NOT NULL,
+                state_root TEXT NOT NULL,
+                accounts INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_account_snapshots_batch
+                ON account_snapshots (batch_id)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS history_pruning (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                pruned_before INTEGER NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS l1_cursor (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                block_number INTEGER NOT NULL,
+                block_hash TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS l1_finality (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                safe_block INTEGER,
+                finalized_block INTEGER
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS batch_witnesses (
+                batch_id INTEGER PRIMARY KEY,
+                witness TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS batch_submissions (
+                batch_id INTEGER PRIMARY KEY,
+                status TEXT NOT NULL,
+                l1_tx_hash TEXT,
+                l1_block_number INTEGER,
+                updated_at INTEGER NOT NULL,
+                verified_tx_hash TEXT,
+                verified_block_number INTEGER
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        self.add_column_if_missing("batch_submissions", "verified_tx_hash", "TEXT").await?;
+        self.add_column_if_missing("batch_submissions", "verified_block_number", "INTEGER").await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS deposits (
+                l1_tx_hash TEXT NOT NULL,
+                l1_log_index INTEGER NOT NULL,
+                l1_block_number INTEGER NOT NULL,
+                recipient TEXT NOT NULL,
+                value TEXT NOT NULL,
+                stage TEXT NOT NULL,
+                batch_id INTEGER,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (l1_tx_hash, l1_log_index)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_deposits_batch ON deposits(batch_id)")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS withdrawals (
+                batch_id INTEGER NOT NULL,
+                withdrawal_index INTEGER NOT NULL,
+                l1_tx_hash TEXT NOT NULL,
+                l1_log_index INTEGER NOT NULL,
+                account TEXT NOT NULL,
+                recipient TEXT NOT NULL,
+                amount TEXT NOT NULL,
+                PRIMARY KEY (batch_id, withdrawal_index)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_withdrawals_l1_tx ON withdrawals(l1_tx_hash)")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Add a column to a table created by an earlier version
+    async fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> anyhow::Result<()> {
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(&self.pool)
+            .await?;
+        if exists == 0 {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
     }
     
     /// Store batch metadata to the database
@@ -33,18 +394,1265 @@ impl Registry {
     /// # Arguments
     /// * `metadata` - Batch metadata to persist
     /// 
-    /// # Planned Implementation
-    /// Will insert the metadata into a database table:
-    /// - CREATE TABLE batches (batch_id, tx_count, forced_tx_count, timestamp, policy)
-    /// - INSERT INTO batches VALUES (...)
-    /// 
     /// # Returns
     /// `Ok(())` if the metadata was successfully stored
     pub async fn store(&self, metadata: BatchMetadata) -> anyhow::Result<()> {
-        // TODO: Store to database
-        // - Connect to database
-        // - Execute INSERT query with metadata fields
-        // - Return result
+        sqlx::query(
+            "INSERT OR REPLACE INTO batches
+                (batch_id, tx_count, forced_tx_count, timestamp, scheduling_policy,
+                    compression, raw_size, compressed_size)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(metadata.batch_id as i64)
+        .bind(metadata.tx_count as i64)
+        .bind(metadata.forced_tx_count as i64)
+        .bind(metadata.timestamp as i64)
+        .bind(metadata.scheduling_policy)
+        .bind(metadata.compression)
+        .bind(metadata.raw_size as i64)
+        .bind(metadata.compressed_size as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Get the metadata of a sealed batch
+    ///
+    /// # Returns
+    /// `None` if no batch with this ID was recorded
+    pub async fn batch(&self, batch_id: u64) -> anyhow::Result<Option<BatchMetadata>> {
+        let row = sqlx::query(
+            "SELECT batch_id, tx_count, forced_tx_count, timestamp, scheduling_policy,
+                    compression, raw_size, compressed_size
+                FROM batches WHERE batch_id = ?",
+        )
+        .bind(batch_id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(BatchMetadata {
+                batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+                tx_count: row.try_get::<i64, _>("tx_count")? as usize,
+                forced_tx_count: row.try_get::<i64, _>("forced_tx_count")? as usize,
+                timestamp: row.try_get::<i64, _>("timestamp")? as u64,
+                scheduling_policy: row.try_get("scheduling_policy")?,
+                compression: row.try_get("compression")?,
+                raw_size: row.try_get::<i64, _>("raw_size")? as usize,
+                compressed_size: row.try_get::<i64, _>("compressed_size")? as usize,
+            })
+        })
+        .transpose()
+    }
+
+    /// Timestamp of the most recently timestamped batch
+    ///
+    /// # Returns
+    /// `None` if no batch was ever recorded
+    pub async fn latest_batch_timestamp(&self) -> anyhow::Result<Option<u64>> {
+        let timestamp: Option<i64> = sqlx::query_scalar("SELECT MAX(timestamp) FROM batches")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(timestamp.map(|t| t as u64))
+    }
+
+    /// Record an observed transaction outcome
+    ///
+    /// Every decision about a transaction is appended (never overwritten), so the
+    /// complete history of a (sender, nonce) pair can be reconstructed.
+    ///
+    /// # Arguments
+    /// * `record` - The observation to persist
+    pub async fn record_transaction(&self, record: TransactionRecord) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO tx_observations (tx_hash, sender, nonce, outcome, timestamp)
+                VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(format!("{:?}", record.tx_hash))
+        .bind(format!("{:?}", record.sender))
+        .bind(record.nonce as i64)
+        .bind(serde_json::to_string(&record.outcome)?)
+        .bind(record.timestamp as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Get every observation recorded for a (sender, nonce) pair
+    ///
+    /// Includes transactions that were rejected, evicted, or superseded, in the
+    /// order they were observed. This is the primary tool for investigating
+    /// "my transaction disappeared" reports.
+    ///
+    /// # Arguments
+    /// * `sender` - Sender address
+    /// * `nonce` - Sender nonce
+    pub async fn transactions_by_sender_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> anyhow::Result<Vec<TransactionRecord>> {
+        let rows = sqlx::query(
+            "SELECT tx_hash, sender, nonce, outcome, timestamp FROM tx_observations
+                WHERE sender = ? AND nonce = ? ORDER BY id",
+        )
+        .bind(format!("{:?}", sender))
+        .bind(nonce as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_record).collect()
+    }
+
+    /// Find nonces of a sender that were claimed by more than one distinct transaction
+    ///
+    /// A duplicate nonce usually means the sender (or a compromised key) signed
+    /// conflicting transactions; only one of them can ever be batched.
+    ///
+    /// # Arguments
+    /// * `sender` - Sender address
+    ///
+    /// # Returns
+    /// The affected nonces in ascending order
+    pub async fn duplicate_nonces(&self, sender: Address) -> anyhow::Result<Vec<u64>> {
+        let rows = sqlx::query(
+            "SELECT nonce FROM tx_observations WHERE sender = ?
+                GROUP BY nonce HAVING COUNT(DISTINCT tx_hash) > 1 ORDER BY nonce",
+        )
+        .bind(format!("{:?}", sender))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok(row.try_get::<i64, _>("nonce")? as u64))
+            .collect()
+    }
+
+    /// Store a newly issued API key
+    ///
+    /// # Arguments
+    /// * `usage` - The key with its quotas (counters normally start at zero)
+    pub async fn create_api_key(&self, usage: &ApiKeyUsage) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO api_keys
+                (key, label, max_requests, max_transactions, requests, transactions, window_start)
+                VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&usage.key)
+        .bind(&usage.label)
+        .bind(usage.max_requests.map(|v| v as i64))
+        .bind(usage.max_transactions.map(|v| v as i64))
+        .bind(usage.requests as i64)
+        .bind(usage.transactions as i64)
+        .bind(usage.window_start as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete an API key
+    ///
+    /// # Returns
+    /// `true` if the key existed
+    pub async fn revoke_api_key(&self, key: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM api_keys WHERE key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get the quotas and current usage of an API key
+    pub async fn api_key_usage(&self, key: &str) -> anyhow::Result<Option<ApiKeyUsage>> {
+        let row = sqlx::query("SELECT * FROM api_keys WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(row_to_api_key).transpose()
+    }
+
+    /// Charge one request (and `transactions` submissions) against an API key
+    ///
+    /// Resets the counters first if the quota window has elapsed. The check and
+    /// the increment happen in one database transaction so concurrent requests
+    /// can't overshoot the quota.
+    ///
+    /// # Arguments
+    /// * `key` - The API key presented by the client
+    /// * `now` - Current unix time in seconds
+    /// * `window_secs` - Quota window length
+    /// * `transactions` - Number of transactions this request submits
+    pub async fn consume_api_quota(
+        &self,
+        key: &str,
+        now: u64,
+        window_secs: u64,
+        transactions: u64,
+    ) -> anyhow::Result<QuotaCheck> {
+        let mut db_tx = self.pool.begin().await?;
+
+        let row = sqlx::query("SELECT * FROM api_keys WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&mut *db_tx)
+            .await?;
+        let Some(row) = row else {
+            return Ok(QuotaCheck::UnknownKey);
+        };
+        let mut usage = row_to_api_key(&row)?;
+
+        // Start a fresh window if the current one has elapsed
+        if now.saturating_sub(usage.window_start) >= window_secs {
+            usage.requests = 0;
+            usage.transactions = 0;
+            usage.window_start = now;
+        }
+
+        let over_requests = usage.max_requests.is_some_and(|max| usage.requests + 1 > max);
+        let over_transactions = usage
+            .max_transactions
+            .is_some_and(|max| usage.transactions + transactions > max);
+        if over_requests || over_transactions {
+            return Ok(QuotaCheck::Exceeded(usage));
+        }
+
+        usage.requests += 1;
+        usage.transactions += transactions;
+        sqlx::query(
+            "UPDATE api_keys SET requests = ?, transactions = ?, window_start = ? WHERE key = ?",
+        )
+        .bind(usage.requests as i64)
+        .bind(usage.transactions as i64)
+        .bind(usage.window_start as i64)
+        .bind(key)
+        .execute(&mut *db_tx)
+        .await?;
+        db_tx.commit().await?;
+
+        Ok(QuotaCheck::Allowed(usage))
+    }
+
+    /// Record a forfeited express-lane bond
+    ///
+    /// Idempotent per L1 transaction: re-recording the same exit is a no-op.
+    pub async fn record_bond_forfeiture(&self, forfeiture: &BondForfeiture) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO bond_forfeitures (l1_tx_hash, sender, bond, reason, batch_id)
+                VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(format!("{:?}", forfeiture.l1_tx_hash))
+        .bind(format!("{:?}", forfeiture.sender))
+        .bind(forfeiture.bond.to_string())
+        .bind(&forfeiture.reason)
+        .bind(forfeiture.batch_id as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// List all forfeited express-lane bonds, oldest batch first
+    pub async fn bond_forfeitures(&self) -> anyhow::Result<Vec<BondForfeiture>> {
+        let rows = sqlx::query(
+            "SELECT l1_tx_hash, sender, bond, reason, batch_id FROM bond_forfeitures
+                ORDER BY batch_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(BondForfeiture {
+                    l1_tx_hash: row.try_get::<&str, _>("l1_tx_hash")?.parse()?,
+                    sender: row.try_get::<&str, _>("sender")?.parse()?,
+                    bond: U256::from_dec_str(row.try_get("bond")?)?,
+                    reason: row.try_get("reason")?,
+                    batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Persist a newly queued forced transaction
+    ///
+    /// # Returns
+    /// `false` if the same L1 event (transaction hash and log index) was
+    /// recorded before, in which case nothing is written
+    pub async fn record_forced_transaction(&self, tx: &ForcedTransaction) -> anyhow::Result<bool> {
+        let mut db_tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO forced_transactions (l1_tx_hash, l1_log_index, tx, batch_id)
+                VALUES (?, ?, ?, NULL)",
+        )
+        .bind(format!("{:?}", tx.l1_tx_hash))
+        .bind(tx.l1_log_index as i64)
+        .bind(serde_json::to_string(tx)?)
+        .execute(&mut *db_tx)
+        .await?;
+        let recorded = result.rows_affected() > 0;
+        if recorded {
+            set_deposit_stage(&mut db_tx, tx, DepositStage::Confirmed, None).await?;
+        }
+        db_tx.commit().await?;
+        Ok(recorded)
+    }
+
+    /// Set (or with `None`, clear) the batch that included forced transactions
+    ///
+    /// Cleared when a batch is reinjected, so the transactions are queued
+    /// again after a restart.
+    pub async fn mark_forced_included(&self, txs: &[ForcedTransaction], batch_id: Option<u64>) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for tx in txs {
+            sqlx::query(
+                "UPDATE forced_transactions SET batch_id = ? WHERE l1_tx_hash = ? AND l1_log_index = ?",
+            )
+            .bind(batch_id.map(|id| id as i64))
+            .bind(format!("{:?}", tx.l1_tx_hash))
+            .bind(tx.l1_log_index as i64)
+            .execute(&mut *db_tx)
+            .await?;
+            let stage = if batch_id.is_some() { DepositStage::Included } else { DepositStage::Confirmed };
+            set_deposit_stage(&mut db_tx, tx, stage, batch_id).await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete forced transactions whose L1 events were reorged out
+    ///
+    /// Only rows not yet included in a batch are deleted, and the events are
+    /// forgotten, so they are queued again if they reappear on the new chain.
+    pub async fn forget_forced_transactions(&self, txs: &[ForcedTransaction]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for tx in txs {
+            let result = sqlx::query(
+                "DELETE FROM forced_transactions
+                    WHERE l1_tx_hash = ? AND l1_log_index = ? AND batch_id IS NULL",
+            )
+            .bind(format!("{:?}", tx.l1_tx_hash))
+            .bind(tx.l1_log_index as i64)
+            .execute(&mut *db_tx)
+            .await?;
+            if result.rows_affected() > 0 {
+                set_deposit_stage(&mut db_tx, tx, DepositStage::Reorged, None).await?;
+            }
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record deposits seen on L1 but held until they are deep enough
+    ///
+    /// A deposit already recorded keeps its stage, unless it was reorged out
+    /// and has now reappeared. Other forced transactions are ignored.
+    pub async fn record_detected_deposits(&self, txs: &[ForcedTransaction]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for tx in txs.iter().filter(|tx| is_deposit(tx)) {
+            sqlx::query(
+                "INSERT INTO deposits
+                    (l1_tx_hash, l1_log_index, l1_block_number, recipient, value, stage, batch_id, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, NULL, ?)
+                    ON CONFLICT (l1_tx_hash, l1_log_index) DO UPDATE SET
+                        l1_block_number = excluded.l1_block_number,
+                        stage = excluded.stage,
+                        updated_at = excluded.updated_at
+                    WHERE stage = ?",
+            )
+            .bind(format!("{:?}", tx.l1_tx_hash))
+            .bind(tx.l1_log_index as i64)
+            .bind(tx.l1_block_number as i64)
+            .bind(format!("{:?}", tx.to))
+            .bind(tx.value.to_string())
+            .bind(DepositStage::Detected.as_str())
+            .bind(unix_now() as i64)
+            .bind(DepositStage::Reorged.as_str())
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record held deposits dropped because their L1 events were reorged out
+    ///
+    /// Only deposits still `Detected` are changed.
+    pub async fn record_reorged_deposits(&self, txs: &[ForcedTransaction]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for tx in txs.iter().filter(|tx| is_deposit(tx)) {
+            sqlx::query(
+                "UPDATE deposits SET stage = ?, updated_at = ?
+                    WHERE l1_tx_hash = ? AND l1_log_index = ? AND stage = ?",
+            )
+            .bind(DepositStage::Reorged.as_str())
+            .bind(unix_now() as i64)
+            .bind(format!("{:?}", tx.l1_tx_hash))
+            .bind(tx.l1_log_index as i64)
+            .bind(DepositStage::Detected.as_str())
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Credit the deposits included in a batch, once it is final
+    ///
+    /// # Returns
+    /// The number of deposits credited
+    pub async fn credit_deposits(&self, batch_id: u64) -> anyhow::Result<u64> {
+        let mut conn = self.pool.acquire().await?;
+        credit_deposits(&mut conn, batch_id).await
+    }
+
+    /// Get the deposits emitted by an L1 transaction, in log order, with how
+    /// final their L1 blocks are
+    ///
+    /// Empty if the transaction emitted no deposit seen by the sequencer.
+    pub async fn deposit_status(&self, l1_tx_hash: H256) -> anyhow::Result<Vec<DepositStatus>> {
+        let tags = self.l1_finality().await?;
+        let rows = sqlx::query(
+            "SELECT l1_tx_hash, l1_log_index, l1_block_number, recipient, value, stage, batch_id, updated_at
+                FROM deposits WHERE l1_tx_hash = ? ORDER BY l1_log_index",
+        )
+        .bind(format!("{:?}", l1_tx_hash))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let l1_block_number = row.try_get::<i64, _>("l1_block_number")? as u64;
+                Ok(DepositStatus {
+                    l1_tx_hash: row.try_get::<&str, _>("l1_tx_hash")?.parse()?,
+                    l1_log_index: row.try_get::<i64, _>("l1_log_index")? as u64,
+                    l1_block_number,
+                    recipient: row.try_get::<&str, _>("recipient")?.parse()?,
+                    value: U256::from_dec_str(row.try_get("value")?)?,
+                    stage: serde_json::from_value(serde_json::Value::String(row.try_get("stage")?))?,
+                    batch_id: row.try_get::<Option<i64>, _>("batch_id")?.map(|id| id as u64),
+                    updated_at: row.try_get::<i64, _>("updated_at")? as u64,
+                    l1_finality: tags.finality(l1_block_number),
+                })
+            })
+            .collect()
+    }
+
+    /// Forced transactions not yet included in any batch, in the order they were queued
+    pub async fn queued_forced_transactions(&self) -> anyhow::Result<Vec<ForcedTransaction>> {
+        let rows = sqlx::query(
+            "SELECT tx FROM forced_transactions WHERE batch_id IS NULL ORDER BY rowid",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok(serde_json::from_str(row.try_get("tx")?)?))
+            .collect()
+    }
+
+    /// Record the L1 listener's scan cursor: every event up to block
+    /// `block_number`, whose hash is `block_hash`, was handled
+    pub async fn set_l1_cursor(&self, block_number: u64, block_hash: H256) -> anyhow::Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO l1_cursor (id, block_number, block_hash) VALUES (0, ?, ?)")
+            .bind(block_number as i64)
+            .bind(format!("{:?}", block_hash))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The L1 listener's scan cursor, as `(block number, block hash)`, if one was recorded
+    pub async fn l1_cursor(&self) -> anyhow::Result<Option<(u64, H256)>> {
+        let row = sqlx::query("SELECT block_number, block_hash FROM l1_cursor")
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| {
+            Ok((
+                row.try_get::<i64, _>("block_number")? as u64,
+                row.try_get::<&str, _>("block_hash")?.parse()?,
+            ))
+        })
+        .transpose()
+    }
+
+    /// Record the latest L1 blocks tagged `safe` and `finalized`
+    ///
+    /// Every `Committed` or `Verified` batch whose commitment is in a
+    /// finalized block becomes `Finalized`. With `require_verification`, only
+    /// `Verified` batches do, once their verification is in a finalized block.
+    ///
+    /// # Returns
+    /// The batches finalized, in order
+    pub async fn set_l1_finality(&self, tags: &FinalityTags, require_verification: bool) -> anyhow::Result<Vec<u64>> {
+        let mut db_tx = self.pool.begin().await?;
+        sqlx::query("INSERT OR REPLACE INTO l1_finality (id, safe_block, finalized_block) VALUES (0, ?, ?)")
+            .bind(tags.safe_block.map(|number| number as i64))
+            .bind(tags.finalized_block.map(|number| number as i64))
+            .execute(&mut *db_tx)
+            .await?;
+
+        let mut finalized = Vec::new();
+        if let Some(finalized_block) = tags.finalized_block {
+            let (final_block, eligible) = match require_verification {
+                true => ("verified_block_number", BatchStatus::Verified),
+                false => ("l1_block_number", BatchStatus::Committed),
+            };
+            let rows = sqlx::query(&format!(
+                "UPDATE batch_submissions SET status = ?, updated_at = ?
+                    WHERE status IN (?, ?) AND {} <= ?
+                    RETURNING batch_id",
+                final_block
+            ))
+            .bind(serde_json::to_string(&BatchStatus::Finalized)?)
+            .bind(unix_now() as i64)
+            .bind(serde_json::to_string(&eligible)?)
+            .bind(serde_json::to_string(&BatchStatus::Verified)?)
+            .bind(finalized_block as i64)
+            .fetch_all(&mut *db_tx)
+            .await?;
+            for row in rows {
+                finalized.push(row.try_get::<i64, _>("batch_id")? as u64);
+            }
+            finalized.sort_unstable();
+        }
+        db_tx.commit().await?;
+        Ok(finalized)
+    }
+
+    /// The latest L1 blocks tagged `safe` and `finalized`, as last recorded
+    pub async fn l1_finality(&self) -> anyhow::Result<FinalityTags> {
+        let row = sqlx::query("SELECT safe_block, finalized_block FROM l1_finality")
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(row) = row else {
+            return Ok(FinalityTags::default());
+        };
+        Ok(FinalityTags {
+            safe_block: row.try_get::<Option<i64>, _>("safe_block")?.map(|number| number as u64),
+            finalized_block: row.try_get::<Option<i64>, _>("finalized_block")?.map(|number| number as u64),
+        })
+    }
+
+    /// Record that the sender guard changed a batch's selection
+    pub async fn record_sender_guard(&self, adjustment: &SenderGuardAdjustment) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO sender_guard_adjustments
+                (batch_id, senders_before, senders_after, displaced)
+                VALUES (?, ?, ?, ?)",
+        )
+        .bind(adjustment.batch_id as i64)
+        .bind(adjustment.senders_before as i64)
+        .bind(adjustment.senders_after as i64)
+        .bind(adjustment.displaced as i64)
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// List all sender guard adjustments, oldest batch first
+    pub async fn sender_guard_adjustments(&self) -> anyhow::Result<Vec<SenderGuardAdjustment>> {
+        let rows = sqlx::query(
+            "SELECT batch_id, senders_before, senders_after, displaced FROM sender_guard_adjustments
+                ORDER BY batch_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(SenderGuardAdjustment {
+                    batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+                    senders_before: row.try_get::<i64, _>("senders_before")? as usize,
+                    senders_after: row.try_get::<i64, _>("senders_after")? as usize,
+                    displaced: row.try_get::<i64, _>("displaced")? as usize,
+                })
+            })
+            .collect()
+    }
+
+    /// Record the bids that won a batch's top slots
+    ///
+    /// Their amounts are the auction proceeds owed to the sequencer.
+    pub async fn record_auction_wins(&self, wins: &[AuctionWin]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for win in wins {
+            sqlx::query(
+                "INSERT OR REPLACE INTO auction_wins (batch_id, position, tx_hash, bidder, amount)
+                    VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(win.batch_id as i64)
+            .bind(win.position as i64)
+            .bind(format!("{:?}", win.tx_hash))
+            .bind(format!("{:?}", win.bidder))
+            .bind(win.amount.to_string())
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get the winning bids of a batch's auction, top slot first
+    pub async fn auction_wins(&self, batch_id: u64) -> anyhow::Result<Vec<AuctionWin>> {
+        let rows = sqlx::query(
+            "SELECT batch_id, position, tx_hash, bidder, amount FROM auction_wins
+                WHERE batch_id = ? ORDER BY position",
+        )
+        .bind(batch_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(AuctionWin {
+                    batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+                    position: row.try_get::<i64, _>("position")? as usize,
+                    tx_hash: row.try_get::<&str, _>("tx_hash")?.parse()?,
+                    bidder: row.try_get::<&str, _>("bidder")?.parse()?,
+                    amount: U256::from_dec_str(row.try_get("amount")?)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete the winning bids of a reinjected batch
+    ///
+    /// Its transactions go back to the pool, and the bids are not owed.
+    pub async fn forget_auction_wins(&self, batch_id: u64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM auction_wins WHERE batch_id = ?")
+            .bind(batch_id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record where bundles were sealed
+    pub async fn record_bundle_inclusions(&self, inclusions: &[BundleInclusion]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for inclusion in inclusions {
+            sqlx::query(
+                "INSERT OR REPLACE INTO bundle_inclusions (batch_id, position, bundle_hash, size)
+                    VALUES (?, ?, ?, ?)",
+            )
+            .bind(inclusion.batch_id as i64)
+            .bind(inclusion.position as i64)
+            .bind(format!("{:?}", inclusion.bundle_hash))
+            .bind(inclusion.size as i64)
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get the bundles sealed in a batch, in batch order
+    pub async fn bundle_inclusions(&self, batch_id: u64) -> anyhow::Result<Vec<BundleInclusion>> {
+        let rows = sqlx::query(
+            "SELECT batch_id, position, bundle_hash, size FROM bundle_inclusions
+                WHERE batch_id = ? ORDER BY position",
+        )
+        .bind(batch_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_bundle_inclusion).collect()
+    }
+
+    /// Get where a bundle was sealed
+    ///
+    /// # Returns
+    /// `None` if the bundle is not in a sealed batch
+    pub async fn bundle_inclusion(&self, bundle_hash: H256) -> anyhow::Result<Option<BundleInclusion>> {
+        let row = sqlx::query(
+            "SELECT batch_id, position, bundle_hash, size FROM bundle_inclusions
+                WHERE bundle_hash = ? ORDER BY batch_id DESC LIMIT 1",
+        )
+        .bind(format!("{:?}", bundle_hash))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(row_to_bundle_inclusion).transpose()
+    }
+
+    /// Delete the bundle inclusions of a reinjected batch
+    ///
+    /// Its bundles go back to their lane.
+    pub async fn forget_bundle_inclusions(&self, batch_id: u64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM bundle_inclusions WHERE batch_id = ?")
+            .bind(batch_id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record the state of the accounts a batch changed, as of that batch
+    ///
+    /// Only changed accounts are stored, so each batch adds a diff rather than
+    /// a full copy of the state.
+    pub async fn record_account_snapshots(&self, batch_id: u64, accounts: &[AccountState]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for account in accounts {
+            sqlx::query(
+                "INSERT OR REPLACE INTO account_snapshots (address, batch_id, balance, nonce)
+                    VALUES (?, ?, ?, ?)",
+            )
+            .bind(format!("{:?}", account.address))
+            .bind(batch_id as i64)
+            .bind(account.balance.to_string())
+            .bind(account.nonce as i64)
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get the state of an account as of a batch
+    ///
+    /// Resolves to the latest snapshot taken at or before `batch_id`.
+    ///
+    /// # Returns
+    /// `None` if no batch up to `batch_id` changed the account
+    pub async fn account_at_batch(&self, address: Address, batch_id: u64) -> anyhow::Result<Option<AccountState>> {
+        let row = sqlx::query(
+            "SELECT address, balance, nonce FROM account_snapshots
+                WHERE address = ? AND batch_id <= ? ORDER BY batch_id DESC LIMIT 1",
+        )
+        .bind(format!("{:?}", address))
+        .bind(batch_id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| row_to_account(&row)).transpose()
+    }
+
+    /// Move accounts into the archive
+    ///
+    /// Written in a single database transaction, so either every account is
+    /// archived or none is.
+    pub async fn archive_accounts(&self, accounts: &[ArchivedAccount]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for archived in accounts {
+            sqlx::query(
+                "INSERT OR REPLACE INTO archived_accounts
+                    (address, balance, nonce, last_active_batch, archived_at_batch, state_hash)
+                    VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(format!("{:?}", archived.account.address))
+            .bind(archived.account.balance.to_string())
+            .bind(archived.account.nonce as i64)
+            .bind(archived.last_active_batch as i64)
+            .bind(archived.archived_at_batch as i64)
+            .bind(format!("{:?}", archived.state_hash))
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remove an account from the archive and return it
+    ///
+    /// # Returns
+    /// `None` if the account is not archived
+    pub async fn take_archived_account(&self, address: Address) -> anyhow::Result<Option<ArchivedAccount>> {
+        let row = sqlx::query(
+            "DELETE FROM archived_accounts WHERE address = ?
+                RETURNING address, balance, nonce, last_active_batch, archived_at_batch, state_hash",
+        )
+        .bind(format!("{:?}", address))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(ArchivedAccount {
+                account: row_to_account(&row)?,
+                last_active_batch: row.try_get::<i64, _>("last_active_batch")? as u64,
+                archived_at_batch: row.try_get::<i64, _>("archived_at_batch")? as u64,
+                state_hash: row.try_get::<&str, _>("state_hash")?.parse()?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Look up an archived account without resurrecting it
+    ///
+    /// # Returns
+    /// `None` if the account is not archived
+    pub async fn archived_account(&self, address: Address) -> anyhow::Result<Option<AccountState>> {
+        let row = sqlx::query("SELECT address, balance, nonce FROM archived_accounts WHERE address = ?")
+            .bind(format!("{:?}", address))
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(row_to_account).transpose()
+    }
+
+    /// List every archived account
+    pub async fn archived_accounts(&self) -> anyhow::Result<Vec<AccountState>> {
+        let rows = sqlx::query("SELECT address, balance, nonce FROM archived_accounts")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(row_to_account).collect()
+    }
+
+    /// Record the genesis the chain was started from
+    ///
+    /// # Returns
+    /// `Err` if a genesis was already recorded
+    pub async fn record_genesis(&self, genesis: &GenesisRecord) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO genesis (id, chain_id, genesis_hash, state_root, accounts, timestamp)
+                VALUES (0, ?, ?, ?, ?, ?)",
+        )
+        .bind(genesis.chain_id as i64)
+        .bind(format!("{:?}", genesis.genesis_hash))
+        .bind(format!("{:?}", genesis.state_root))
+        .bind(genesis.accounts as i64)
+        .bind(genesis.timestamp as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Get the genesis the chain was started from
+    ///
+    /// # Returns
+    /// `None` if no genesis was recorded
+    pub async fn genesis(&self) -> anyhow::Result<Option<GenesisRecord>> {
+        let row = sqlx::query("SELECT chain_id, genesis_hash, state_root, accounts, timestamp FROM genesis")
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| {
+            Ok(GenesisRecord {
+                chain_id: row.try_get::<i64, _>("chain_id")? as u64,
+                genesis_hash: row.try_get::<&str, _>("genesis_hash")?.parse()?,
+                state_root: row.try_get::<&str, _>("state_root")?.parse()?,
+                accounts: row.try_get::<i64, _>("accounts")? as u64,
+                timestamp: row.try_get::<i64, _>("timestamp")? as u64,
+            })
+        })
+        .transpose()
+    }
+
+    /// Record the execution witness of a sealed batch, replacing any earlier one
+    pub async fn record_batch_witness(&self, witness: &BatchWitness) -> anyhow::Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO batch_witnesses (batch_id, witness) VALUES (?, ?)")
+            .bind(witness.batch_id as i64)
+            .bind(serde_json::to_string(witness)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the execution witness of a batch
+    ///
+    /// # Returns
+    /// `None` if no witness was recorded for the batch
+    pub async fn batch_witness(&self, batch_id: u64) -> anyhow::Result<Option<BatchWitness>> {
+        let row = sqlx::query("SELECT witness FROM batch_witnesses WHERE batch_id = ?")
+            .bind(batch_id as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| Ok(serde_json::from_str(row.try_get("witness")?)?))
+            .transpose()
+    }
+
+    /// Record the withdrawals of a sealed batch
+    pub async fn record_withdrawals(&self, withdrawals: &[Withdrawal]) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        for withdrawal in withdrawals {
+            sqlx::query(
+                "INSERT OR REPLACE INTO withdrawals
+                    (batch_id, withdrawal_index, l1_tx_hash, l1_log_index, account, recipient, amount)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(withdrawal.batch_id as i64)
+            .bind(withdrawal.index as i64)
+            .bind(format!("{:?}", withdrawal.l1_tx_hash))
+            .bind(withdrawal.l1_log_index as i64)
+            .bind(format!("{:?}", withdrawal.account))
+            .bind(format!("{:?}", withdrawal.recipient))
+            .bind(withdrawal.amount.to_string())
+            .execute(&mut *db_tx)
+            .await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete the withdrawals of a reinjected batch
+    ///
+    /// Its forced exits are queued again, and make new withdrawals in the
+    /// batch they are sealed into next.
+    pub async fn forget_withdrawals(&self, batch_id: u64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM withdrawals WHERE batch_id = ?")
+            .bind(batch_id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the withdrawals of a batch, in order
+    pub async fn batch_withdrawals(&self, batch_id: u64) -> anyhow::Result<Vec<Withdrawal>> {
+        let rows = sqlx::query(
+            "SELECT batch_id, withdrawal_index, l1_tx_hash, l1_log_index, account, recipient, amount
+                FROM withdrawals WHERE batch_id = ? ORDER BY withdrawal_index",
+        )
+        .bind(batch_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_withdrawal).collect()
+    }
+
+    /// Get the withdrawal made for a forced exit, by its L1 event
+    ///
+    /// Without a log index, the first forced exit of the L1 transaction is used.
+    ///
+    /// # Returns
+    /// `None` if the forced exit has not been debited (yet)
+    pub async fn withdrawal(&self, l1_tx_hash: H256, l1_log_index: Option<u64>) -> anyhow::Result<Option<Withdrawal>> {
+        let row = sqlx::query(
+            "SELECT batch_id, withdrawal_index, l1_tx_hash, l1_log_index, account, recipient, amount
+                FROM withdrawals WHERE l1_tx_hash = ? AND (? IS NULL OR l1_log_index = ?)
+                ORDER BY l1_log_index LIMIT 1",
+        )
+        .bind(format!("{:?}", l1_tx_hash))
+        .bind(l1_log_index.map(|index| index as i64))
+        .bind(l1_log_index.map(|index| index as i64))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(row_to_withdrawal).transpose()
+    }
+
+    /// Record the L1 lifecycle status of a batch, replacing the previous one
+    ///
+    /// A `Committed` batch credits the deposits it included. The verification
+    /// of a batch is kept, and a verified batch is not moved back to
+    /// `Submitted` or `Committed` (its commitment may be confirmed after the
+    /// contract verified it).
+    pub async fn set_batch_status(&self, submission: &BatchSubmission) -> anyhow::Result<()> {
+        let mut db_tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO batch_submissions
+                (batch_id, status, l1_tx_hash, l1_block_number, updated_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT (batch_id) DO UPDATE SET
+                    status = CASE WHEN status IN (?, ?) AND excluded.status IN (?, ?)
+                        THEN status ELSE excluded.status END,
+                    l1_tx_hash = excluded.l1_tx_hash,
+                    l1_block_number = excluded.l1_block_number,
+                    updated_at = excluded.updated_at",
+        )
+        .bind(submission.batch_id as i64)
+        .bind(serde_json::to_string(&submission.status)?)
+        .bind(submission.l1_tx_hash.map(|hash| format!("{:?}", hash)))
+        .bind(submission.l1_block_number.map(|number| number as i64))
+        .bind(submission.updated_at as i64)
+        .bind(serde_json::to_string(&BatchStatus::Verified)?)
+        .bind(serde_json::to_string(&BatchStatus::Finalized)?)
+        .bind(serde_json::to_string(&BatchStatus::Submitted)?)
+        .bind(serde_json::to_string(&BatchStatus::Committed)?)
+        .execute(&mut *db_tx)
+        .await?;
+        if submission.status == BatchStatus::Committed {
+            credit_deposits(&mut db_tx, submission.batch_id).await?;
+        }
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    /// Get the L1 lifecycle status of a batch
+    ///
+    /// # Returns
+    /// `None` if no status was recorded for the batch
+    pub async fn batch_status(&self, batch_id: u64) -> anyhow::Result<Option<BatchSubmission>> {
+        let row = sqlx::query(
+            "SELECT batch_id, status, l1_tx_hash, l1_block_number, verified_tx_hash, verified_block_number, updated_at
+                FROM batch_submissions WHERE batch_id = ?",
+        )
+        .bind(batch_id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(BatchSubmission {
+                batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+                status: serde_json::from_str(row.try_get("status")?)?,
+                l1_tx_hash: row.try_get::<Option<&str>, _>("l1_tx_hash")?.map(str::parse).transpose()?,
+                l1_block_number: row.try_get::<Option<i64>, _>("l1_block_number")?.map(|number| number as u64),
+                verified_tx_hash: row.try_get::<Option<&str>, _>("verified_tx_hash")?.map(str::parse).transpose()?,
+                verified_block_number: row
+                    .try_get::<Option<i64>, _>("verified_block_number")?
+                    .map(|number| number as u64),
+                updated_at: row.try_get::<i64, _>("updated_at")? as u64,
+            })
+        })
+        .transpose()
+    }
+
+    /// Record that the rollup contract verified a batch in L1 transaction
+    /// `l1_tx_hash`, mined in block `l1_block_number`
+    ///
+    /// A `Submitted` or `Committed` batch becomes `Verified`, crediting its
+    /// deposits. Only the first verification of a batch is kept.
+    ///
+    /// # Returns
+    /// `false` if no status was recorded for the batch
+    pub async fn record_batch_verified(&self, batch_id: u64, l1_tx_hash: H256, l1_block_number: u64) -> anyhow::Result<bool> {
+        let mut db_tx = self.pool.begin().await?;
+        let updated = sqlx::query(
+            "UPDATE batch_submissions SET
+                status = CASE WHEN status IN (?, ?) THEN ? ELSE status END,
+                verified_tx_hash = COALESCE(verified_tx_hash, ?),
+                verified_block_number = COALESCE(verified_block_number, ?),
+                updated_at = ?
+                WHERE batch_id = ?",
+        )
+        .bind(serde_json::to_string(&BatchStatus::Submitted)?)
+        .bind(serde_json::to_string(&BatchStatus::Committed)?)
+        .bind(serde_json::to_string(&BatchStatus::Verified)?)
+        .bind(format!("{:?}", l1_tx_hash))
+        .bind(l1_block_number as i64)
+        .bind(unix_now() as i64)
+        .bind(batch_id as i64)
+        .execute(&mut *db_tx)
+        .await?
+        .rows_affected();
+        if updated > 0 {
+            credit_deposits(&mut db_tx, batch_id).await?;
+        }
+        db_tx.commit().await?;
+        Ok(updated > 0)
+    }
+
+    /// L1 block of the oldest commitment the rollup contract has not verified yet
+    ///
+    /// `None` if every committed batch is verified.
+    pub async fn oldest_unverified_commitment(&self) -> anyhow::Result<Option<u64>> {
+        let oldest: Option<i64> = sqlx::query_scalar(
+            "SELECT MIN(l1_block_number) FROM batch_submissions
+                WHERE status IN (?, ?) AND verified_block_number IS NULL",
+        )
+        .bind(serde_json::to_string(&BatchStatus::Committed)?)
+        .bind(serde_json::to_string(&BatchStatus::Finalized)?)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(oldest.map(|block| block as u64))
+    }
+
+    /// Drop the history of batches before `before`
+    ///
+    /// Removes their witnesses, and every account snapshot superseded by a
+    /// later one at or before `before`. The latest snapshot of each account
+    /// stays, so reads as of `before` or later resolve as they did. Only the
+    /// batches since the last call are scanned.
+    ///
+    /// # Returns
+    /// The number of records removed
+    pub async fn prune_history(&self, before: u64) -> anyhow::Result<u64> {
+        let pruned_before = self.pruned_before().await?;
+        if before <= pruned_before {
+            return Ok(0);
+        }
+
+        let mut db_tx = self.pool.begin().await?;
+        let snapshots = sqlx::query(
+            "DELETE FROM account_snapshots WHERE rowid IN (
+                SELECT older.rowid FROM account_snapshots newer
+                JOIN account_snapshots older
+                    ON older.address = newer.address AND older.batch_id < newer.batch_id
+                WHERE newer.batch_id > ? AND newer.batch_id <= ?
+            )",
+        )
+        .bind(pruned_before as i64)
+        .bind(before as i64)
+        .execute(&mut *db_tx)
+        .await?;
+        let witnesses = sqlx::query("DELETE FROM batch_witnesses WHERE batch_id < ?")
+            .bind(before as i64)
+            .execute(&mut *db_tx)
+            .await?;
+        sqlx::query("INSERT OR REPLACE INTO history_pruning (id, pruned_before) VALUES (0, ?)")
+            .bind(before as i64)
+            .execute(&mut *db_tx)
+            .await?;
+        db_tx.commit().await?;
+        Ok(snapshots.rows_affected() + witnesses.rows_affected())
+    }
+
+    /// First batch whose history is complete (0 if nothing was pruned)
+    pub async fn pruned_before(&self) -> anyhow::Result<u64> {
+        let row = sqlx::query("SELECT pruned_before FROM history_pruning")
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(match row {
+            Some(row) => row.try_get::<i64, _>("pruned_before")? as u64,
+            None => 0,
+        })
+    }
+
+    /// Record the witness for a resurrected account
+    pub async fn record_resurrection(&self, witness: &ResurrectionWitness) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO resurrection_witnesses
+                (address, balance, nonce, archived_at_batch, resurrected_at_batch, state_hash, verified)
+                VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(format!("{:?}", witness.account.address))
+        .bind(witness.account.balance.to_string())
+        .bind(witness.account.nonce as i64)
+        .bind(witness.archived_at_batch as i64)
+        .bind(witness.resurrected_at_batch as i64)
+        .bind(format!("{:?}", witness.state_hash))
+        .bind(witness.verified)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// List the resurrection witnesses of an account, oldest first
+    pub async fn resurrection_witnesses(&self, address: Address) -> anyhow::Result<Vec<ResurrectionWitness>> {
+        let rows = sqlx::query(
+            "SELECT address, balance, nonce, archived_at_batch, resurrected_at_batch, state_hash, verified
+                FROM resurrection_witnesses WHERE address = ? ORDER BY id",
+        )
+        .bind(format!("{:?}", address))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ResurrectionWitness {
+                    account: row_to_account(row)?,
+                    archived_at_batch: row.try_get::<i64, _>("archived_at_batch")? as u64,
+                    resurrected_at_batch: row.try_get::<i64, _>("resurrected_at_batch")? as u64,
+                    state_hash: row.try_get::<&str, _>("state_hash")?.parse()?,
+                    verified: row.try_get("verified")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Decode an `api_keys` row into an `ApiKeyUsage`
+fn row_to_api_key(row: &SqliteRow) -> anyhow::Result<ApiKeyUsage> {
+    Ok(ApiKeyUsage {
+        key: row.try_get("key")?,
+        label: row.try_get("label")?,
+        max_requests: row.try_get::<Option<i64>, _>("max_requests")?.map(|v| v as u64),
+        max_transactions: row.try_get::<Option<i64>, _>("max_transactions")?.map(|v| v as u64),
+        requests: row.try_get::<i64, _>("requests")? as u64,
+        transactions: row.try_get::<i64, _>("transactions")? as u64,
+        window_start: row.try_get::<i64, _>("window_start")? as u64,
+    })
+}
+
+/// Decode a `tx_observations` row into a `TransactionRecord`
+fn row_to_record(row: &SqliteRow) -> anyhow::Result<TransactionRecord> {
+    let outcome: TransactionOutcome = serde_json::from_str(row.try_get("outcome")?)?;
+    Ok(TransactionRecord {
+        tx_hash: row.try_get::<&str, _>("tx_hash")?.parse()?,
+        sender: row.try_get::<&str, _>("sender")?.parse()?,
+        nonce: row.try_get::<i64, _>("nonce")? as u64,
+        outcome,
+        timestamp: row.try_get::<i64, _>("timestamp")? as u64,
+    })
+
+}
+
+/// Decode a `withdrawals` row into a `Withdrawal`
+fn row_to_withdrawal(row: &SqliteRow) -> anyhow::Result<Withdrawal> {
+    Ok(Withdrawal {
+        batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+        index: row.try_get::<i64, _>("withdrawal_index")? as u64,
+        l1_tx_hash: row.try_get::<&str, _>("l1_tx_hash")?.parse()?,
+        l1_log_index: row.try_get::<i64, _>("l1_log_index")? as u64,
+        account: row.try_get::<&str, _>("account")?.parse()?,
+        recipient: row.try_get::<&str, _>("recipient")?.parse()?,
+        amount: U256::from_dec_str(row.try_get("amount")?)?,
+    })
+}
+
+/// Decode a `bundle_inclusions` row into a `BundleInclusion`
+fn row_to_bundle_inclusion(row: &SqliteRow) -> anyhow::Result<BundleInclusion> {
+    Ok(BundleInclusion {
+        batch_id: row.try_get::<i64, _>("batch_id")? as u64,
+        position: row.try_get::<i64, _>("position")? as usize,
+        bundle_hash: row.try_get::<&str, _>("bundle_hash")?.parse()?,
+        size: row.try_get::<i64, _>("size")? as usize,
+    })
+}
+
+/// Decode the `address`, `balance` and `nonce` columns into an `AccountState`
+///
+/// Only accounts without code or storage are archived and snapshotted, so
+/// there are no other columns to read.
+fn row_to_account(row: &SqliteRow) -> anyhow::Result<AccountState> {
+    Ok(AccountState {
+        address: row.try_get::<&str, _>("address")?.parse()?,
+        balance: U256::from_dec_str(row.try_get("balance")?)?,
+        nonce: row.try_get::<i64, _>("nonce")? as u64,
+        ..Default::default()
+    })
+}
+
+/// Whether a forced transaction is a deposit
+fn is_deposit(tx: &ForcedTransaction) -> bool {
+    matches!(tx.event_type, ForcedEventType::Deposit)
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Move a deposit to `stage`, creating its record if it has none
+///
+/// Other forced transactions are ignored.
+async fn set_deposit_stage(
+    conn: &mut SqliteConnection,
+    tx: &ForcedTransaction,
+    stage: DepositStage,
+    batch_id: Option<u64>,
+) -> anyhow::Result<()> {
+    if !is_deposit(tx) {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO deposits
+            (l1_tx_hash, l1_log_index, l1_block_number, recipient, value, stage, batch_id, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (l1_tx_hash, l1_log_index) DO UPDATE SET
+                stage = excluded.stage,
+                batch_id = excluded.batch_id,
+                updated_at = excluded.updated_at",
+    )
+    .bind(format!("{:?}", tx.l1_tx_hash))
+    .bind(tx.l1_log_index as i64)
+    .bind(tx.l1_block_number as i64)
+    .bind(format!("{:?}", tx.to))
+    .bind(tx.value.to_string())
+    .bind(stage.as_str())
+    .bind(batch_id.map(|id| id as i64))
+    .bind(unix_now() as i64)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Move the deposits included in a batch to `Credited`
+async fn credit_deposits(conn: &mut SqliteConnection, batch_id: u64) -> anyhow::Result<u64> {
+    let result = sqlx::query("UPDATE deposits SET stage = ?, updated_at = ? WHERE batch_id = ? AND stage = ?")
+        .bind(DepositStage::Credited.as_str())
+        .bind(unix_now() as i64)
+        .bind(batch_id as i64)
+        .bind(DepositStage::Included.as_str())
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected())
+}
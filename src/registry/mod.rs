@@ -1,7 +1,12 @@
 //! Batch Registry Module
 //! 
 //! This module provides a database registry for storing batch metadata.
-//! Allows querying batch information without loading full transaction data.
+//! Allows querying batch information without loading full transaction data,
+//! and keeps the history of every transaction outcome for forensic queries.
 
 mod database;
-pub use database::Registry;
\ No newline at end of file
+pub use database::{Registry, QuotaCheck};
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
@@ -0,0 +1,388 @@
+//! Tests for the batch registry
+//! 
+//! Runs against an in-memory SQLite database
+//! 
+//! Verifies:
+//! - Nonce histories, duplicate nonces and API key quotas
+//! - Historical account reads and pruning
+//! - Genesis, L1 cursor and batch status records
+//! - Deposit stages, through batching, reinjection, reorgs and L1 commitment
+//! - L1 finality tags, finalizing committed batches and grading deposits
+//! - Batch verifications by the rollup contract, ahead of finality
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        registry::{QuotaCheck, Registry},
+        config::DatabaseConfig,
+        state::{BatchWitness, StateWitness},
+        AccountState, ApiKeyUsage, BatchMetadata, BatchStatus, BatchSubmission, DepositStage, FinalityTags,
+        ForcedEventType, ForcedTransaction, L1Finality, GenesisRecord, TransactionOutcome, TransactionRecord,
+    };
+    use ethers::types::{Address, Bytes, H256, U256};
+
+    /// Helper function to open a fresh in-memory registry
+    async fn memory_registry() -> Registry {
+        Registry::new(&DatabaseConfig { url: "sqlite::memory:".to_string() })
+            .await
+            .unwrap()
+    }
+
+    /// Helper function to create a transaction observation
+    fn record(hash: u64, nonce: u64, outcome: TransactionOutcome) -> TransactionRecord {
+        TransactionRecord {
+            tx_hash: H256::from_low_u64_be(hash),
+            sender: Address::from_low_u64_be(1),
+            nonce,
+            outcome,
+            timestamp: 1000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nonce_history_includes_all_outcomes_in_order() {
+        let registry = memory_registry().await;
+        registry.record_transaction(record(1, 5, TransactionOutcome::Accepted)).await.unwrap();
+        registry.record_transaction(record(1, 5, TransactionOutcome::Evicted)).await.unwrap();
+        registry.record_transaction(record(2, 5, TransactionOutcome::Accepted)).await.unwrap();
+        registry.record_transaction(record(3, 6, TransactionOutcome::Accepted)).await.unwrap();
+        registry.record_transaction(record(3, 6, TransactionOutcome::Dropped)).await.unwrap();
+        
+        let history = registry
+            .transactions_by_sender_nonce(Address::from_low_u64_be(1), 5)
+            .await
+            .unwrap();
+        
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].outcome, TransactionOutcome::Accepted);
+        assert_eq!(history[1].outcome, TransactionOutcome::Evicted);
+        assert_eq!(history[2].tx_hash, H256::from_low_u64_be(2));
+        
+        let history = registry
+            .transactions_by_sender_nonce(Address::from_low_u64_be(1), 6)
+            .await
+            .unwrap();
+        assert_eq!(history[1].outcome, TransactionOutcome::Dropped);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_nonces_requires_distinct_hashes() {
+        let registry = memory_registry().await;
+        // Same transaction observed twice is not a duplicate
+        registry.record_transaction(record(1, 5, TransactionOutcome::Accepted)).await.unwrap();
+        registry.record_transaction(record(1, 5, TransactionOutcome::Batched { batch_id: 1 })).await.unwrap();
+        // Two different transactions claiming nonce 6 is
+        registry.record_transaction(record(2, 6, TransactionOutcome::Accepted)).await.unwrap();
+        registry.record_transaction(record(3, 6, TransactionOutcome::Rejected {
+            reason: "Invalid nonce".to_string(),
+        })).await.unwrap();
+        
+        let duplicates = registry.duplicate_nonces(Address::from_low_u64_be(1)).await.unwrap();
+        assert_eq!(duplicates, vec![6]);
+    }
+
+    #[tokio::test]
+    async fn test_api_quota_enforced_and_reset_per_window() {
+        let registry = memory_registry().await;
+        registry.create_api_key(&ApiKeyUsage {
+            key: "k".to_string(),
+            label: "customer".to_string(),
+            max_requests: Some(10),
+            max_transactions: Some(1),
+            requests: 0,
+            transactions: 0,
+            window_start: 0,
+        }).await.unwrap();
+        
+        // First transaction fits, the second exceeds the transaction quota
+        assert!(matches!(registry.consume_api_quota("k", 10, 100, 1).await.unwrap(), QuotaCheck::Allowed(_)));
+        assert!(matches!(registry.consume_api_quota("k", 20, 100, 1).await.unwrap(), QuotaCheck::Exceeded(_)));
+        // Read-only requests still count against the request quota only
+        assert!(matches!(registry.consume_api_quota("k", 30, 100, 0).await.unwrap(), QuotaCheck::Allowed(_)));
+        
+        // A new window resets the counters
+        match registry.consume_api_quota("k", 150, 100, 1).await.unwrap() {
+            QuotaCheck::Allowed(usage) => {
+                assert_eq!(usage.requests, 1);
+                assert_eq!(usage.transactions, 1);
+                assert_eq!(usage.window_start, 150);
+            }
+            other => panic!("Expected allowed, got {:?}", other),
+        }
+        
+        assert!(matches!(registry.consume_api_quota("missing", 0, 100, 0).await.unwrap(), QuotaCheck::UnknownKey));
+    }
+
+    #[tokio::test]
+    async fn test_account_reads_resolve_to_latest_snapshot_at_batch() {
+        let registry = memory_registry().await;
+        let address = Address::from_low_u64_be(1);
+        let account = |nonce| AccountState { address, balance: U256::from(100), nonce, ..Default::default() };
+        registry.record_account_snapshots(2, &[account(3)]).await.unwrap();
+        registry.record_account_snapshots(5, &[account(7)]).await.unwrap();
+        
+        assert!(registry.account_at_batch(address, 1).await.unwrap().is_none());
+        assert_eq!(registry.account_at_batch(address, 2).await.unwrap().unwrap().nonce, 3);
+        assert_eq!(registry.account_at_batch(address, 4).await.unwrap().unwrap().nonce, 3);
+        assert_eq!(registry.account_at_batch(address, 9).await.unwrap().unwrap().nonce, 7);
+        
+        registry.store(BatchMetadata {
+            batch_id: 5,
+            tx_count: 1,
+            forced_tx_count: 0,
+            timestamp: 1000,
+            scheduling_policy: "FCFS".to_string(),
+            compression: "zstd".to_string(),
+            raw_size: 900,
+            compressed_size: 300,
+        }).await.unwrap();
+        let metadata = registry.batch(5).await.unwrap().unwrap();
+        assert_eq!(metadata.timestamp, 1000);
+        assert_eq!((metadata.compression.as_str(), metadata.raw_size, metadata.compressed_size), ("zstd", 900, 300));
+        assert!(registry.batch(6).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pruning_keeps_reads_of_retained_batches() {
+        let registry = memory_registry().await;
+        let (busy, idle) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let account = |address, nonce| AccountState { address, balance: U256::from(100), nonce, ..Default::default() };
+        registry.record_account_snapshots(1, &[account(busy, 1), account(idle, 9)]).await.unwrap();
+        for batch_id in 2..=6 {
+            registry.record_account_snapshots(batch_id, &[account(busy, batch_id)]).await.unwrap();
+            registry.record_batch_witness(&BatchWitness {
+                batch_id,
+                pre_state: StateWitness::default(),
+                post_state: StateWitness::default(),
+            }).await.unwrap();
+        }
+
+        // Keeping batches 4 to 6: snapshots 1 to 3 of the busy account go, the idle one's stays
+        assert_eq!(registry.prune_history(4).await.unwrap(), 3 + 2);
+        assert_eq!(registry.pruned_before().await.unwrap(), 4);
+        assert_eq!(registry.account_at_batch(busy, 4).await.unwrap().unwrap().nonce, 4);
+        assert_eq!(registry.account_at_batch(idle, 6).await.unwrap().unwrap().nonce, 9);
+        assert!(registry.batch_witness(3).await.unwrap().is_none());
+        assert!(registry.batch_witness(4).await.unwrap().is_some());
+
+        // Pruning again scans only the newly pruned batches
+        assert_eq!(registry.prune_history(4).await.unwrap(), 0);
+        assert_eq!(registry.prune_history(6).await.unwrap(), 2 + 2);
+        assert_eq!(registry.account_at_batch(idle, 6).await.unwrap().unwrap().nonce, 9);
+    }
+
+    #[tokio::test]
+    async fn test_genesis_is_recorded_once() {
+        let registry = memory_registry().await;
+        assert!(registry.genesis().await.unwrap().is_none());
+        
+        let genesis = GenesisRecord {
+            chain_id: 31_337,
+            genesis_hash: H256::from_low_u64_be(1),
+            state_root: H256::from_low_u64_be(2),
+            accounts: 3,
+            timestamp: 1000,
+        };
+        registry.record_genesis(&genesis).await.unwrap();
+        let recorded = registry.genesis().await.unwrap().unwrap();
+        assert_eq!((recorded.chain_id, recorded.genesis_hash, recorded.accounts), (31_337, genesis.genesis_hash, 3));
+        
+        let other = GenesisRecord { genesis_hash: H256::from_low_u64_be(9), ..genesis };
+        assert!(registry.record_genesis(&other).await.is_err());
+        assert_eq!(registry.genesis().await.unwrap().unwrap().genesis_hash, recorded.genesis_hash);
+    }
+
+    #[tokio::test]
+    async fn test_l1_cursor_keeps_the_latest_block() {
+        let registry = memory_registry().await;
+        assert!(registry.l1_cursor().await.unwrap().is_none());
+        
+        registry.set_l1_cursor(100, H256::from_low_u64_be(1)).await.unwrap();
+        registry.set_l1_cursor(101, H256::from_low_u64_be(2)).await.unwrap();
+        assert_eq!(registry.l1_cursor().await.unwrap(), Some((101, H256::from_low_u64_be(2))));
+    }
+
+    #[tokio::test]
+    async fn test_batch_status_follows_the_lifecycle() {
+        let registry = memory_registry().await;
+        assert!(registry.batch_status(7).await.unwrap().is_none());
+
+        let mut submission = BatchSubmission {
+            batch_id: 7,
+            status: BatchStatus::Sealed,
+            l1_tx_hash: None,
+            l1_block_number: None,
+            verified_tx_hash: None,
+            verified_block_number: None,
+            updated_at: 100,
+        };
+        registry.set_batch_status(&submission).await.unwrap();
+        assert_eq!(registry.batch_status(7).await.unwrap().unwrap(), submission);
+
+        submission.status = BatchStatus::Committed;
+        submission.l1_tx_hash = Some(H256::from_low_u64_be(1));
+        submission.l1_block_number = Some(18_500_000);
+        submission.updated_at = 112;
+        registry.set_batch_status(&submission).await.unwrap();
+        assert_eq!(registry.batch_status(7).await.unwrap().unwrap(), submission);
+    }
+
+    /// Helper function to create a deposit emitted by L1 transaction `l1_tx`
+    fn deposit(l1_tx: u64, log_index: u64) -> ForcedTransaction {
+        ForcedTransaction {
+            tx_hash: H256::from_low_u64_be(100 + log_index),
+            from: Address::from_low_u64_be(1),
+            to: Address::from_low_u64_be(2),
+            value: U256::from(1_000),
+            nonce: 0,
+            gas_limit: 21_000,
+            l1_tx_hash: H256::from_low_u64_be(l1_tx),
+            l1_block_number: 50,
+            l1_log_index: log_index,
+            event_type: ForcedEventType::Deposit,
+            timestamp: 1000,
+            bond: None,
+            data: Bytes::new(),
+        }
+    }
+
+    /// Helper function to read the stage of a deposit
+    async fn stage(registry: &Registry, tx: &ForcedTransaction) -> Option<(DepositStage, Option<u64>)> {
+        registry.deposit_status(tx.l1_tx_hash).await.unwrap()
+            .into_iter()
+            .find(|status| status.l1_log_index == tx.l1_log_index)
+            .map(|status| (status.stage, status.batch_id))
+    }
+
+    #[tokio::test]
+    async fn test_deposit_moves_through_its_stages() {
+        let registry = memory_registry().await;
+        let tx = deposit(1, 0);
+        assert!(registry.deposit_status(tx.l1_tx_hash).await.unwrap().is_empty());
+
+        registry.record_detected_deposits(std::slice::from_ref(&tx)).await.unwrap();
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Detected, None)));
+        assert!(registry.record_forced_transaction(&tx).await.unwrap());
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Confirmed, None)));
+
+        // A re-scan sighting does not move the deposit back
+        registry.record_detected_deposits(std::slice::from_ref(&tx)).await.unwrap();
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Confirmed, None)));
+
+        registry.mark_forced_included(std::slice::from_ref(&tx), Some(4)).await.unwrap();
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Included, Some(4))));
+
+        // Committing another batch credits nothing
+        let mut submission = BatchSubmission {
+            batch_id: 3,
+            status: BatchStatus::Committed,
+            l1_tx_hash: Some(H256::from_low_u64_be(9)),
+            l1_block_number: Some(60),
+            verified_tx_hash: None,
+            verified_block_number: None,
+            updated_at: 100,
+        };
+        registry.set_batch_status(&submission).await.unwrap();
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Included, Some(4))));
+
+        submission.batch_id = 4;
+        registry.set_batch_status(&submission).await.unwrap();
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Credited, Some(4))));
+    }
+
+    #[tokio::test]
+    async fn test_deposit_stages_follow_reinjection_and_reorgs() {
+        let registry = memory_registry().await;
+        let (batched, queued, held) = (deposit(1, 0), deposit(1, 1), deposit(2, 0));
+        registry.record_forced_transaction(&batched).await.unwrap();
+        registry.record_forced_transaction(&queued).await.unwrap();
+        registry.record_detected_deposits(std::slice::from_ref(&held)).await.unwrap();
+
+        registry.mark_forced_included(std::slice::from_ref(&batched), Some(4)).await.unwrap();
+        registry.mark_forced_included(std::slice::from_ref(&batched), None).await.unwrap();
+        assert_eq!(stage(&registry, &batched).await, Some((DepositStage::Confirmed, None)));
+
+        registry.forget_forced_transactions(std::slice::from_ref(&queued)).await.unwrap();
+        registry.record_reorged_deposits(std::slice::from_ref(&held)).await.unwrap();
+        assert_eq!(stage(&registry, &queued).await, Some((DepositStage::Reorged, None)));
+        assert_eq!(stage(&registry, &held).await, Some((DepositStage::Reorged, None)));
+        assert_eq!(registry.deposit_status(batched.l1_tx_hash).await.unwrap().len(), 2);
+
+        // A reorged deposit reappearing on the new chain is detected again
+        registry.record_detected_deposits(std::slice::from_ref(&held)).await.unwrap();
+        assert_eq!(stage(&registry, &held).await, Some((DepositStage::Detected, None)));
+    }
+
+    #[tokio::test]
+    async fn test_finality_tags_finalize_committed_batches() {
+        let registry = memory_registry().await;
+        let tx = deposit(1, 0);
+        registry.record_detected_deposits(std::slice::from_ref(&tx)).await.unwrap();
+        assert_eq!(registry.deposit_status(tx.l1_tx_hash).await.unwrap()[0].l1_finality, None);
+
+        let committed = |batch_id: u64, block: u64| BatchSubmission {
+            batch_id,
+            status: BatchStatus::Committed,
+            l1_tx_hash: Some(H256::from_low_u64_be(batch_id)),
+            l1_block_number: Some(block),
+            verified_tx_hash: None,
+            verified_block_number: None,
+            updated_at: 100,
+        };
+        registry.set_batch_status(&committed(1, 60)).await.unwrap();
+        registry.set_batch_status(&committed(2, 70)).await.unwrap();
+
+        // The deposit's block is safe, no commitment is final yet
+        let tags = FinalityTags { safe_block: Some(65), finalized_block: Some(55) };
+        assert!(registry.set_l1_finality(&tags, false).await.unwrap().is_empty());
+        assert_eq!(registry.l1_finality().await.unwrap(), tags);
+        assert_eq!(registry.deposit_status(tx.l1_tx_hash).await.unwrap()[0].l1_finality, Some(L1Finality::Finalized));
+        assert_eq!(tags.finality(60), Some(L1Finality::Safe));
+        assert_eq!(tags.finality(66), Some(L1Finality::Unsafe));
+
+        // Only commitments at or below the finalized tag are finalized
+        let tags = FinalityTags { safe_block: Some(75), finalized_block: Some(65) };
+        assert_eq!(registry.set_l1_finality(&tags, false).await.unwrap(), vec![1]);
+        assert_eq!(registry.batch_status(1).await.unwrap().unwrap().status, BatchStatus::Finalized);
+        assert_eq!(registry.batch_status(2).await.unwrap().unwrap().status, BatchStatus::Committed);
+        assert!(registry.set_l1_finality(&tags, false).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verified_batches_wait_for_final_verification() {
+        let registry = memory_registry().await;
+        let tx = deposit(1, 0);
+        registry.record_forced_transaction(&tx).await.unwrap();
+        registry.mark_forced_included(std::slice::from_ref(&tx), Some(3)).await.unwrap();
+        assert!(!registry.record_batch_verified(3, H256::from_low_u64_be(9), 80).await.unwrap());
+
+        let mut submission = BatchSubmission {
+            batch_id: 3,
+            status: BatchStatus::Submitted,
+            l1_tx_hash: Some(H256::from_low_u64_be(3)),
+            l1_block_number: None,
+            verified_tx_hash: None,
+            verified_block_number: None,
+            updated_at: 100,
+        };
+        registry.set_batch_status(&submission).await.unwrap();
+        assert_eq!(registry.oldest_unverified_commitment().await.unwrap(), None);
+
+        // Verified before the submitter saw its commitment confirmed
+        assert!(registry.record_batch_verified(3, H256::from_low_u64_be(9), 80).await.unwrap());
+        assert_eq!(stage(&registry, &tx).await, Some((DepositStage::Credited, Some(3))));
+        submission.status = BatchStatus::Committed;
+        submission.l1_block_number = Some(70);
+        registry.set_batch_status(&submission).await.unwrap();
+        let status = registry.batch_status(3).await.unwrap().unwrap();
+        assert_eq!(status.status, BatchStatus::Verified);
+        assert_eq!(status.l1_block_number, Some(70));
+        assert_eq!(status.verified_block_number, Some(80));
+
+        // Final only once the verification is
+        let tags = |finalized| FinalityTags { safe_block: Some(90), finalized_block: Some(finalized) };
+        assert!(registry.set_l1_finality(&tags(75), true).await.unwrap().is_empty());
+        assert_eq!(registry.set_l1_finality(&tags(80), true).await.unwrap(), vec![3]);
+        assert_eq!(registry.batch_status(3).await.unwrap().unwrap().status, BatchStatus::Finalized);
+    }
+}
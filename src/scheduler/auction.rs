@@ -0,0 +1,163 @@
+//! Sealed-Bid Auction Module
+//!
+//! Searchers bid for the top positions of the next batch with the `submitBid`
+//! RPC. Bids are sealed: the book keeps them private (no RPC lists them)
+//! until the batch is sealed, when the `Auction` policy opens them. The
+//! highest bids for transactions in the batch win its top slots, in bid
+//! order, equal bids going to the earlier one; the rest of the batch is
+//! ordered by fee priority.
+//!
+//! Each opening closes the auction window: bids for transactions left out
+//! of the batch are discarded, and searchers bid again for the next one. The
+//! winning bids are recorded in the registry as proceeds owed to the
+//! sequencer (see `getAuctionResults`).
+
+use super::policies::{FeePriorityPolicy, SchedulingPolicy};
+use crate::UserTransaction;
+use ethers::types::{Address, H256, U256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Bid that won a top slot, in a batch not yet sealed
+///
+/// # Fields
+/// - `tx_hash`: Transaction placed in the slot
+/// - `bidder`: Sender of the transaction
+/// - `amount`: Amount bid (in wei)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenedBid {
+    pub tx_hash: H256,
+    pub bidder: Address,
+    pub amount: U256,
+}
+
+/// Bid kept sealed in the book
+struct SealedBid {
+    bidder: Address,
+    amount: U256,
+    /// Order the bid was placed in, breaking ties
+    seq: u64,
+}
+
+/// Bids of the current auction window, and the winners of the last one
+#[derive(Default)]
+struct BookInner {
+    /// Sealed bids by the transaction they are for
+    bids: HashMap<H256, SealedBid>,
+    /// Sequence number of the next bid
+    next_seq: u64,
+    /// Winners of the last opening, until the orchestrator takes them
+    winners: Vec<OpenedBid>,
+}
+
+/// Sealed bids for the top positions of the next batch
+#[derive(Default)]
+pub struct AuctionBook {
+    inner: Mutex<BookInner>,
+}
+
+impl AuctionBook {
+    /// Creates an empty book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place a bid of `bidder` for `tx_hash`
+    ///
+    /// The bidder must be the transaction's sender (the RPC checks the bid's
+    /// signature). A new bid for the same transaction replaces the previous
+    /// one, and counts as placed now when breaking ties.
+    ///
+    /// # Returns
+    /// `true` if it replaced an earlier bid
+    pub fn place(&self, tx_hash: H256, bidder: Address, amount: U256) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        inner.bids.insert(tx_hash, SealedBid { bidder, amount, seq }).is_some()
+    }
+
+    /// Number of sealed bids
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().bids.len()
+    }
+
+    /// Check whether no bid is sealed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Discard every sealed bid, when auctions stop
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().bids.clear();
+    }
+
+    /// Open the bids for a batch's transactions, closing the window
+    ///
+    /// Bids whose bidder is not the transaction's sender are ignored.
+    ///
+    /// # Returns
+    /// The `slots` highest bids, highest first (also kept for [`AuctionBook::take_winners`])
+    fn open(&self, transactions: &[UserTransaction], slots: usize) -> Vec<OpenedBid> {
+        let mut inner = self.inner.lock().unwrap();
+        let bids = std::mem::take(&mut inner.bids);
+        let mut opened: Vec<_> = transactions
+            .iter()
+            .filter_map(|tx| {
+                let tx_hash = tx.hash();
+                let bid = bids.get(&tx_hash).filter(|bid| bid.bidder == tx.from)?;
+                Some((bid.seq, OpenedBid { tx_hash, bidder: bid.bidder, amount: bid.amount }))
+            })
+            .collect();
+        opened.sort_by(|(seq_a, a), (seq_b, b)| b.amount.cmp(&a.amount).then(seq_a.cmp(seq_b)));
+        let winners: Vec<_> = opened.into_iter().take(slots).map(|(_, bid)| bid).collect();
+        inner.winners = winners.clone();
+        winners
+    }
+
+    /// Take the winners of the last opening
+    pub fn take_winners(&self) -> Vec<OpenedBid> {
+        std::mem::take(&mut self.inner.lock().unwrap().winners)
+    }
+}
+
+/// Sealed-Bid Auction Policy
+///
+/// Opens the bids of `book` for the batch's transactions: the winners take
+/// the first `top_slots` positions, highest bid first, and the other
+/// transactions follow by fee priority.
+pub struct AuctionPolicy {
+    /// Positions at the top of each batch that are auctioned
+    pub top_slots: usize,
+    /// Where bids are placed
+    book: Arc<AuctionBook>,
+}
+
+impl AuctionPolicy {
+    /// Creates a policy opening the bids of `book`
+    pub fn new(top_slots: usize, book: Arc<AuctionBook>) -> Self {
+        Self { top_slots, book }
+    }
+}
+
+impl SchedulingPolicy for AuctionPolicy {
+    fn order_transactions(&self, transactions: Vec<UserTransaction>, base_fee: U256) -> Vec<UserTransaction> {
+        let mut ordered = FeePriorityPolicy.order_transactions(transactions, base_fee);
+        let ranks: HashMap<H256, usize> = self.book
+            .open(&ordered, self.top_slots)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, bid)| (bid.tx_hash, rank))
+            .collect();
+        if ranks.is_empty() {
+            return ordered;
+        }
+        // Stable, so the transactions without a winning bid keep their fee order
+        ordered.sort_by_cached_key(|tx| ranks.get(&tx.hash()).copied().unwrap_or(usize::MAX));
+        ordered
+    }
+
+    fn name(&self) -> &str {
+        "Auction"
+    }
+}
@@ -0,0 +1,87 @@
+//! Weighted Fair Queuing Module
+//!
+//! Under fee priority, one address sending many well-paying transactions can
+//! fill every batch, and small senders wait until it stops. Weighted fair
+//! queuing serves senders in rounds instead: each sender's first transaction,
+//! then each one's second, and so on, so a sender's share of a batch does not
+//! grow with how many transactions it queued.
+//!
+//! Optionally, a sender's share is weighted by the tip of its earliest
+//! transaction: a sender paying twice the tip is served twice as often.
+//! Each transaction gets the virtual finish time `n / weight`, `n` being its
+//! rank among its sender's transactions, and transactions are served in
+//! finish-time order, earlier arrivals first among equals. The pool selects
+//! batch candidates the same way (see `TransactionPool::take_fair`).
+
+use super::policies::SchedulingPolicy;
+use crate::UserTransaction;
+use ethers::types::{Address, U256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Virtual finish time of a sender's `served`-th transaction: `served / weight`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FinishTime {
+    served: u64,
+    weight: U256,
+}
+
+impl Ord for FinishTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // a/b < c/d iff a·d < c·b, in 512 bits so nothing overflows
+        U256::from(self.served)
+            .full_mul(other.weight)
+            .cmp(&U256::from(other.served).full_mul(self.weight))
+            .then(self.served.cmp(&other.served))
+    }
+}
+
+impl PartialOrd for FinishTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Order `items` round-robin across senders, in virtual finish-time order
+///
+/// `items` are in arrival order, each sender's in nonce order; `tx` gives
+/// the transaction of an item. With a `base_fee`, each sender is weighted by
+/// the tip of its first transaction at that base fee (at least 1 wei), and
+/// otherwise all senders weigh the same.
+pub fn fair_order<T>(items: Vec<T>, tx: impl Fn(&T) -> &UserTransaction, base_fee: Option<U256>) -> Vec<T> {
+    let mut senders: HashMap<Address, FinishTime> = HashMap::new();
+    let mut keyed: Vec<(FinishTime, T)> = Vec::with_capacity(items.len());
+    for item in items {
+        let tx = tx(&item);
+        let sender = senders.entry(tx.from).or_insert_with(|| FinishTime {
+            served: 0,
+            weight: base_fee.map_or(U256::one(), |base_fee| tx.effective_tip(base_fee).max(U256::one())),
+        });
+        sender.served += 1;
+        let finish = *sender;
+        keyed.push((finish, item));
+    }
+    // Stable, so earlier arrivals go first among equal finish times
+    keyed.sort_by_key(|(finish, _)| *finish);
+    keyed.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Weighted Fair Queuing Policy
+///
+/// Serves senders in rounds, one transaction each per round, so no sender
+/// can monopolize a batch by volume. With `fee_weighted`, senders paying
+/// higher tips are served proportionally more often.
+pub struct WeightedFairPolicy {
+    /// Weight each sender by the tip of its first transaction
+    pub fee_weighted: bool,
+}
+
+impl SchedulingPolicy for WeightedFairPolicy {
+    fn order_transactions(&self, transactions: Vec<UserTransaction>, base_fee: U256) -> Vec<UserTransaction> {
+        fair_order(transactions, |tx| tx, self.fee_weighted.then_some(base_fee))
+    }
+
+    fn name(&self) -> &str {
+        "WeightedFair"
+    }
+}
@@ -0,0 +1,102 @@
+//! Execution Lanes Module
+//!
+//! A parallel executor can run transactions that touch no common account at
+//! the same time. Lanes make that explicit: each batch's transactions are
+//! grouped so that no account is touched from two lanes, and every lane keeps
+//! its transactions in batch order. Running the lanes concurrently, each one
+//! in order, gives the same state as running the batch in order.
+//!
+//! A transaction touches the accounts its execution reads or changes (see
+//! `Transaction::touched_accounts`): for a transfer, its sender and recipient.
+//! Transactions sharing an account, directly or through others, form one
+//! group. When there are more groups than lanes, groups are packed into the
+//! lanes heaviest first, each into the lane with the least gas so far.
+//!
+//! Contract code can read or change any account, so its touch set is unknown
+//! until it runs. A batch with a transaction that runs contract code (one with
+//! calldata, a user operation, or one touching a contract account) is a single
+//! lane, run serially.
+//!
+//! Lanes only group transactions: the batch keeps the order the scheduling
+//! policy gave it, forced transactions first.
+
+use crate::{ExecutionLane, Transaction};
+use ethers::types::Address;
+use std::collections::{HashMap, HashSet};
+
+/// Group `transactions` into at most `max_lanes` lanes touching no common account
+///
+/// `contracts` are the accounts among those touched that hold contract code.
+/// Lanes are in the order of their first transaction. `max_lanes` of 0 is
+/// taken as 1.
+pub fn execution_lanes(transactions: &[Transaction], max_lanes: usize, contracts: &HashSet<Address>) -> Vec<ExecutionLane> {
+    // Contract code may touch any account, so nothing can run beside it
+    let runs_code = |tx: &Transaction| {
+        tx.calls_code() || tx.touched_accounts().iter().any(|account| contracts.contains(account))
+    };
+    if transactions.iter().any(runs_code) {
+        return vec![ExecutionLane {
+            transactions: (0..transactions.len()).collect(),
+            gas_limit: transactions.iter().fold(0u64, |gas, tx| gas.saturating_add(tx.gas_limit())),
+        }];
+    }
+
+    // Union-find over transactions, joined through the accounts they touch
+    let mut parent: Vec<usize> = (0..transactions.len()).collect();
+    let mut last_toucher: HashMap<Address, usize> = HashMap::new();
+    for (idx, tx) in transactions.iter().enumerate() {
+        for account in tx.touched_accounts() {
+            if let Some(&other) = last_toucher.get(&account) {
+                let (root, other_root) = (find(&mut parent, idx), find(&mut parent, other));
+                parent[root.max(other_root)] = root.min(other_root);
+            }
+            last_toucher.insert(account, idx);
+        }
+    }
+
+    // Groups in the order of their first transaction, which is their root
+    let mut groups: Vec<ExecutionLane> = Vec::new();
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    for (idx, tx) in transactions.iter().enumerate() {
+        let root = find(&mut parent, idx);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push(ExecutionLane { transactions: Vec::new(), gas_limit: 0 });
+            groups.len() - 1
+        });
+        groups[group].transactions.push(idx);
+        groups[group].gas_limit = groups[group].gas_limit.saturating_add(tx.gas_limit());
+    }
+    let max_lanes = max_lanes.max(1);
+    if groups.len() <= max_lanes {
+        return groups;
+    }
+
+    // Heaviest group first, into the lightest lane (the earlier among equals)
+    groups.sort_by_key(|group| std::cmp::Reverse(group.gas_limit));
+    let mut lanes: Vec<ExecutionLane> = (0..max_lanes)
+        .map(|_| ExecutionLane { transactions: Vec::new(), gas_limit: 0 })
+        .collect();
+    for group in groups {
+        let lane = lanes
+            .iter_mut()
+            .min_by_key(|lane| lane.gas_limit)
+            .expect("at least one lane");
+        lane.transactions.extend(group.transactions);
+        lane.gas_limit = lane.gas_limit.saturating_add(group.gas_limit);
+    }
+    lanes.retain(|lane| !lane.transactions.is_empty());
+    for lane in &mut lanes {
+        lane.transactions.sort_unstable();
+    }
+    lanes.sort_by_key(|lane| lane.transactions.first().copied());
+    lanes
+}
+
+/// Root of `idx`'s set, halving paths along the way
+fn find(parent: &mut [usize], mut idx: usize) -> usize {
+    while parent[idx] != idx {
+        parent[idx] = parent[parent[idx]];
+        idx = parent[idx];
+    }
+    idx
+}
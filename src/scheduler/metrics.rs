@@ -0,0 +1,205 @@
+//! Scheduling Metrics Module
+//!
+//! Measures how each sealed batch was ordered, so scheduling policies can be
+//! compared on real traffic rather than argued about:
+//! - reorder distance: how many positions each transaction moved away from
+//!   arrival order
+//! - inversions: pairs placed in the opposite order of their arrival, also
+//!   weighted by what the overtaken transaction paid, so overtaking a
+//!   well-paying transaction costs more than overtaking a cheap one
+//! - inclusion latency: time from arrival to sealing, overall and per sender
+//!
+//! A transaction's arrival time is its own `timestamp`, which the `limits`
+//! rule can bound ahead of the sequencer's clock; senders can still backdate
+//! it, so the metrics evaluate policies, not adversarial senders. Only normal
+//! transactions are measured: forced transactions and user operations keep
+//! their own order whatever the policy.
+//!
+//! Per-policy totals are served on `/metrics`, and the recent batches' metrics
+//! by the `getSchedulingMetrics` RPC. Offline batch builds report them for
+//! each batch (see `OfflineBatchArtifact`).
+
+use crate::{Batch, BatchFairness, SenderLatency, Transaction, UserTransaction};
+use ethers::types::{Address, U256};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Recent batches whose metrics are kept by default
+pub const DEFAULT_RECENT_BATCHES: usize = 256;
+
+/// Measure the ordering of `batch`, sealed under `policy`, at `base_fee`
+pub fn batch_fairness(batch: &Batch, policy: &str, base_fee: U256) -> BatchFairness {
+    let txs: Vec<&UserTransaction> = batch.transactions
+        .iter()
+        .filter_map(|tx| match tx {
+            Transaction::Normal(tx) => Some(tx),
+            _ => None,
+        })
+        .collect();
+    let count = txs.len();
+
+    // Arrival rank of each position, earlier timestamps first (stable, so ties keep batch order)
+    let mut arrival: Vec<usize> = (0..count).collect();
+    arrival.sort_by_key(|&idx| txs[idx].timestamp);
+    let mut rank = vec![0; count];
+    for (arrival_rank, &idx) in arrival.iter().enumerate() {
+        rank[idx] = arrival_rank;
+    }
+    let distances: Vec<usize> = rank.iter().enumerate().map(|(idx, &rank)| idx.abs_diff(rank)).collect();
+
+    // Tips relative to the batch's mean, so weighted inversions equal plain ones when all pay alike
+    let tips: Vec<f64> = txs.iter().map(|tx| wei_to_f64(tx.effective_tip(base_fee))).collect();
+    let mean_tip = tips.iter().sum::<f64>() / count.max(1) as f64;
+    let weight = |idx: usize| if mean_tip > 0.0 { tips[idx] / mean_tip } else { 1.0 };
+    let mut inversions = 0;
+    let mut fee_weighted_inversions = 0.0;
+    for later in 0..count {
+        for earlier in 0..later {
+            if txs[later].timestamp < txs[earlier].timestamp {
+                inversions += 1;
+                fee_weighted_inversions += weight(later);
+            }
+        }
+    }
+
+    // Inclusion latency, overall and per sender
+    let latency = |tx: &UserTransaction| batch.timestamp.saturating_sub(tx.timestamp);
+    let mut per_sender: HashMap<Address, (usize, u64, u64)> = HashMap::new();
+    for tx in &txs {
+        let (transactions, total, max) = per_sender.entry(tx.from).or_default();
+        *transactions += 1;
+        *total += latency(tx);
+        *max = (*max).max(latency(tx));
+    }
+    let mut senders: Vec<SenderLatency> = per_sender
+        .into_iter()
+        .map(|(sender, (transactions, total, max))| SenderLatency {
+            sender,
+            transactions,
+            mean_latency_secs: total as f64 / transactions as f64,
+            max_latency_secs: max,
+        })
+        .collect();
+    senders.sort_by(|a, b| b.mean_latency_secs.total_cmp(&a.mean_latency_secs).then(a.sender.cmp(&b.sender)));
+
+    BatchFairness {
+        batch_id: batch.batch_id,
+        policy: policy.to_string(),
+        transactions: count,
+        mean_reorder_distance: distances.iter().sum::<usize>() as f64 / count.max(1) as f64,
+        max_reorder_distance: distances.iter().copied().max().unwrap_or(0),
+        inversions,
+        fee_weighted_inversions,
+        mean_inclusion_latency_secs: txs.iter().map(|tx| latency(tx)).sum::<u64>() as f64 / count.max(1) as f64,
+        senders,
+    }
+}
+
+/// Amount in wei as a float, saturating beyond `u128::MAX`
+fn wei_to_f64(amount: U256) -> f64 {
+    amount.min(U256::from(u128::MAX)).as_u128() as f64
+}
+
+/// Scheduling metrics of recent batches, and totals by policy
+pub struct SchedulingTelemetry {
+    inner: Mutex<Inner>,
+    /// Most recent batches kept
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Totals since startup, by policy
+    totals: BTreeMap<String, PolicyTotals>,
+    /// Most recent batches, oldest first
+    recent: VecDeque<BatchFairness>,
+}
+
+/// Prometheus counter: name, help text, and its value in a policy's totals
+type Counter = (&'static str, &'static str, fn(&PolicyTotals) -> f64);
+
+/// Sums over the batches a policy ordered
+#[derive(Default)]
+struct PolicyTotals {
+    batches: u64,
+    transactions: u64,
+    reorder_distance: f64,
+    inversions: u64,
+    fee_weighted_inversions: f64,
+    inclusion_latency_secs: f64,
+}
+
+impl SchedulingTelemetry {
+    /// Creates empty telemetry keeping the last `capacity` batches
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            capacity,
+        }
+    }
+
+    /// Record the metrics of a sealed batch
+    pub fn record(&self, fairness: BatchFairness) {
+        let mut inner = self.inner.lock().unwrap();
+        let totals = inner.totals.entry(fairness.policy.clone()).or_default();
+        let count = fairness.transactions as f64;
+        totals.batches += 1;
+        totals.transactions += fairness.transactions as u64;
+        totals.reorder_distance += fairness.mean_reorder_distance * count;
+        totals.inversions += fairness.inversions;
+        totals.fee_weighted_inversions += fairness.fee_weighted_inversions;
+        totals.inclusion_latency_secs += fairness.mean_inclusion_latency_secs * count;
+        if self.capacity == 0 {
+            return;
+        }
+        if inner.recent.len() >= self.capacity {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(fairness);
+    }
+
+    /// Metrics of the recent batches, newest first
+    pub fn recent(&self) -> Vec<BatchFairness> {
+        self.inner.lock().unwrap().recent.iter().rev().cloned().collect()
+    }
+
+    /// Metrics of batch `batch_id`, if it is among the recent ones
+    pub fn batch(&self, batch_id: u64) -> Option<BatchFairness> {
+        self.inner.lock().unwrap().recent.iter().rev().find(|fairness| fairness.batch_id == batch_id).cloned()
+    }
+
+    /// Render the totals by policy as Prometheus counters, and the latest
+    /// batch's worst sender latency as a gauge
+    ///
+    /// Dividing a total by `sequencer_scheduling_transactions_total` gives its
+    /// mean per transaction under that policy.
+    pub fn to_prometheus(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+        let counters: [Counter; 6] = [
+            ("sequencer_scheduling_batches_total", "Batches sealed, by scheduling policy", |t| t.batches as f64),
+            ("sequencer_scheduling_transactions_total", "Normal transactions sealed, by scheduling policy", |t| t.transactions as f64),
+            ("sequencer_scheduling_reorder_distance_total", "Positions transactions moved from arrival order", |t| t.reorder_distance),
+            ("sequencer_scheduling_inversions_total", "Transaction pairs sealed against their arrival order", |t| t.inversions as f64),
+            ("sequencer_scheduling_fee_weighted_inversions_total", "Inversions weighted by the overtaken transaction's relative tip", |t| t.fee_weighted_inversions),
+            ("sequencer_inclusion_latency_seconds_total", "Time from arrival to sealing, summed over transactions", |t| t.inclusion_latency_secs),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (policy, totals) in &inner.totals {
+                let _ = writeln!(out, "{}{{policy=\"{}\"}} {}", name, policy, value(totals));
+            }
+        }
+
+        let worst = inner.recent
+            .back()
+            .and_then(|fairness| fairness.senders.first())
+            .map_or(0.0, |sender| sender.mean_latency_secs);
+        let _ = writeln!(out, "# HELP sequencer_scheduling_max_sender_latency_seconds Worst mean inclusion latency of a sender in the latest batch");
+        let _ = writeln!(out, "# TYPE sequencer_scheduling_max_sender_latency_seconds gauge");
+        let _ = writeln!(out, "sequencer_scheduling_max_sender_latency_seconds {}", worst);
+        out
+    }
+}
@@ -6,22 +6,42 @@
 //! - FeePriority: Transactions ordered by gas price (highest first)
 //! - TimeBoost: Time-windowed ordering with premium bids for faster confirmation
 //! - FairBFT: Timestamp-based fair ordering (Byzantine Fault Tolerant)
+//! - Auction: Sealed bids for the top of the batch, then fee priority
+//! - WeightedFair: Round-robin across senders, optionally weighted by fee
+//! - Custom policies registered by the operator under their own name
 //! 
 //! Forced transactions from L1 always have priority regardless of policy.
+//! 
+//! Whatever the policy, batches can also be grouped into execution lanes that
+//! touch no common account, for a parallel executor (see the lanes module).
+//! The metrics module measures how each batch was reordered, to compare policies.
 
+#[allow(clippy::module_inception)]
 mod scheduler;
 mod policies;
+mod auction;
+mod fair;
+mod registry;
+mod lanes;
+mod metrics;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
 
 pub use scheduler::Scheduler;
 pub use policies::{
     SchedulingPolicy,
     SchedulingPolicyType,
+    PolicyKind,
     FcfsPolicy,
     FeePriorityPolicy,
     TimeBoostPolicy,
     FairBftPolicy,
     create_policy,
-};
\ No newline at end of file
+};
+pub use auction::{AuctionBook, AuctionPolicy, OpenedBid};
+pub use fair::{fair_order, WeightedFairPolicy};
+pub use lanes::execution_lanes;
+pub use metrics::{batch_fairness, SchedulingTelemetry, DEFAULT_RECENT_BATCHES};
+pub use registry::{is_registered, register_policy, registered_policies, PolicyFactory};
\ No newline at end of file
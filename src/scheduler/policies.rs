@@ -13,7 +13,7 @@
 //! - **Best for**: Systems prioritizing simplicity and time-based fairness
 //! 
 //! ## 2. Fee Priority
-//! - Orders transactions by gas price (highest first)
+//! - Orders transactions by effective tip at the current base fee (highest first)
 //! - Incentivizes users to pay higher fees
 //! - **Advantage**: Revenue maximization, faster confirmation for willing payers
 //! - **Disadvantage**: Unfair to low-fee transactions, prone to fee wars
@@ -22,7 +22,7 @@
 //! ## 3. Time-Boost
 //! - Divides time into discrete windows (e.g., 5-second slots)
 //! - Users bid for priority within their submission window via `boost_bid`
-//! - Within each window: sorts by boost_bid, then gas_price, then FCFS
+//! - Within each window: sorts by boost_bid, then effective tip, then FCFS
 //! - **Advantage**: Predictable latency guarantees, granular fairness
 //! - **Disadvantage**: Complex, still favors wealthy users, strategic gaming
 //! - **Best for**: Systems needing SLA guarantees with balanced fairness
@@ -35,18 +35,46 @@
 //! - **Disadvantage**: Higher overhead, increased latency (in multi-node setup)
 //! - **Best for**: Decentralized sequencers prioritizing censorship resistance
 //! 
+//! ## 5. Sealed-Bid Auction
+//! - Searchers submit sealed bids for the top positions of the next batch
+//! - Bids are opened when the batch is sealed; the highest take the top slots
+//! - The rest of the batch is ordered by fee priority
+//! - **Advantage**: MEV is captured by the sequencer instead of fee wars
+//! - **Disadvantage**: Favors searchers, bids are trusted to the sequencer until opened
+//! - **Best for**: Sequencers auctioning MEV openly (see the auction module)
+//! 
+//! ## 6. Weighted Fair Queuing
+//! - Serves senders in rounds, one transaction each per round
+//! - Optionally weights each sender by its tip, serving higher payers more often
+//! - **Advantage**: No sender monopolizes batches by volume, low latency for small users
+//! - **Disadvantage**: Less revenue than fee priority, splitting across addresses games it
+//! - **Best for**: Public sequencers with a few high-volume senders (see the fair module)
+//! 
+//! ## Custom Policies
+//! - Operators can register their own policies under a name (see the registry module)
+//! - Selected by that name like a built-in policy
+//! 
 //! # Important Rule
 //! All policies only affect **normal user transactions**. Forced transactions
 //! from L1 ALWAYS come first, regardless of the selected policy.
 
+use super::auction::{AuctionBook, AuctionPolicy};
+use super::fair::WeightedFairPolicy;
+use super::registry::{custom_policy, is_registered};
 use crate::UserTransaction;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Scheduling policy trait (Strategy pattern)
 /// Defines the interface for all transaction ordering policies.
 /// Each policy implements its own `order_transactions()` logic.
 pub trait SchedulingPolicy: Send + Sync {
     /// Order transactions according to this policy's rules
-    fn order_transactions(&self, transactions: Vec<UserTransaction>) -> Vec<UserTransaction>;
+    /// 
+    /// `base_fee` is the current base fee per gas, for policies that rank by
+    /// the tip a transaction pays on top of it.
+    fn order_transactions(&self, transactions: Vec<UserTransaction>, base_fee: U256) -> Vec<UserTransaction>;
     
     /// Get the policy name for logging and metadata
     fn name(&self) -> &str;
@@ -59,7 +87,7 @@ pub trait SchedulingPolicy: Send + Sync {
 pub struct FcfsPolicy;
 
 impl SchedulingPolicy for FcfsPolicy {
-    fn order_transactions(&self, transactions: Vec<UserTransaction>) -> Vec<UserTransaction> {
+    fn order_transactions(&self, transactions: Vec<UserTransaction>, _base_fee: U256) -> Vec<UserTransaction> {
         // FCFS: maintain original order, no sorting needed
         transactions
     }
@@ -71,14 +99,17 @@ impl SchedulingPolicy for FcfsPolicy {
 
 /// Fee Priority Policy
 /// 
-/// Orders transactions by gas price in descending order (highest fee first).
+/// Orders transactions by effective tip in descending order (highest tip first).
+/// The tip is what a transaction pays per gas above the base fee: `gas_price`
+/// minus the base fee for legacy transactions, and for EIP-1559 ones the
+/// priority fee, capped by `max_fee_per_gas` minus the base fee.
 /// This maximizes sequencer revenue and gives priority to users willing to pay more.
 pub struct FeePriorityPolicy;
 
 impl SchedulingPolicy for FeePriorityPolicy {
-    fn order_transactions(&self, mut transactions: Vec<UserTransaction>) -> Vec<UserTransaction> {
-        // Sort by gas_price in descending order (highest fee first)
-        transactions.sort_by(|a, b| b.gas_price.cmp(&a.gas_price));
+    fn order_transactions(&self, mut transactions: Vec<UserTransaction>, base_fee: U256) -> Vec<UserTransaction> {
+        // Sort by effective tip in descending order (highest tip first)
+        transactions.sort_by_key(|tx| std::cmp::Reverse(tx.effective_tip(base_fee)));
         transactions
     }
     
@@ -95,7 +126,7 @@ impl SchedulingPolicy for FeePriorityPolicy {
 /// 
 /// # Ordering Rules (within each time window)
 /// 1. Sort by `boost_bid` (if present) - descending
-/// 2. If no boost_bid or tied, sort by `gas_price` - descending  
+/// 2. If no boost_bid or tied, sort by effective tip - descending
 /// 3. If tied on both, maintain FCFS order
 pub struct TimeBoostPolicy {
     /// Time window size in milliseconds (e.g., 5000 for 5-second windows)
@@ -103,14 +134,14 @@ pub struct TimeBoostPolicy {
 }
 
 impl SchedulingPolicy for TimeBoostPolicy {
-    fn order_transactions(&self, mut transactions: Vec<UserTransaction>) -> Vec<UserTransaction> {
+    fn order_transactions(&self, mut transactions: Vec<UserTransaction>, base_fee: U256) -> Vec<UserTransaction> {
         // Group transactions by time window
         // Time window = floor(timestamp / window_size)
         
         // Sort by multiple criteria:
         // 1. Time window (ascending - earlier windows first)
         // 2. Within same window: boost_bid (descending)
-        // 3. Within same boost_bid: gas_price (descending)
+        // 3. Within same boost_bid: effective tip (descending)
         // 4. Maintain stable sort for FCFS tie-breaking
         
         transactions.sort_by(|a, b| {
@@ -127,8 +158,8 @@ impl SchedulingPolicy for TimeBoostPolicy {
                     
                     match boost_b.cmp(&boost_a) { // Descending (b vs a)
                         std::cmp::Ordering::Equal => {
-                            // Same boost: compare by gas_price
-                            b.gas_price.cmp(&a.gas_price) // Descending
+                            // Same boost: compare by effective tip
+                            b.effective_tip(base_fee).cmp(&a.effective_tip(base_fee)) // Descending
                         }
                         other => other,
                     }
@@ -178,10 +209,10 @@ impl SchedulingPolicy for TimeBoostPolicy {
 pub struct FairBftPolicy;
 
 impl SchedulingPolicy for FairBftPolicy {
-    fn order_transactions(&self, mut transactions: Vec<UserTransaction>) -> Vec<UserTransaction> {
+    fn order_transactions(&self, mut transactions: Vec<UserTransaction>, _base_fee: U256) -> Vec<UserTransaction> {
         // Sort strictly by timestamp (ascending - earliest first)
         // This provides time-based fairness
-        transactions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        transactions.sort_by_key(|tx| tx.timestamp);
         transactions
     }
     
@@ -190,15 +221,82 @@ impl SchedulingPolicy for FairBftPolicy {
     }
 }
 
+/// Name of a policy in configuration files
+/// 
+/// Each name is the one its policy reports (see [`SchedulingPolicy::name`]),
+/// or one a custom policy was registered under (see [`super::register_policy`]).
+/// An unknown name fails to parse, so a misspelled policy stops the
+/// configuration from loading.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PolicyKind {
+    Fcfs,
+    FeePriority,
+    TimeBoost,
+    FairBft,
+    Auction,
+    WeightedFair,
+    /// A registered custom policy
+    Custom(String),
+}
+
+impl PolicyKind {
+    /// Policy type of this name, with the TimeBoost window, the number of
+    /// auctioned slots and whether fair queuing is weighted by fee (each
+    /// ignored by the other policies)
+    pub fn to_policy_type(&self, time_window_ms: u64, auction_slots: usize, fee_weighted: bool) -> SchedulingPolicyType {
+        match self {
+            PolicyKind::Fcfs => SchedulingPolicyType::Fcfs,
+            PolicyKind::FeePriority => SchedulingPolicyType::FeePriority,
+            PolicyKind::TimeBoost => SchedulingPolicyType::TimeBoost { time_window_ms },
+            PolicyKind::FairBft => SchedulingPolicyType::FairBft,
+            PolicyKind::Auction => SchedulingPolicyType::Auction { top_slots: auction_slots },
+            PolicyKind::WeightedFair => SchedulingPolicyType::WeightedFair { fee_weighted },
+            PolicyKind::Custom(name) => SchedulingPolicyType::Custom { name: name.clone() },
+        }
+    }
+}
+
+impl TryFrom<String> for PolicyKind {
+    type Error = String;
+    
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.as_str() {
+            "FCFS" => Ok(PolicyKind::Fcfs),
+            "FeePriority" => Ok(PolicyKind::FeePriority),
+            "TimeBoost" => Ok(PolicyKind::TimeBoost),
+            "FairBFT" => Ok(PolicyKind::FairBft),
+            "Auction" => Ok(PolicyKind::Auction),
+            "WeightedFair" => Ok(PolicyKind::WeightedFair),
+            _ if is_registered(&name) => Ok(PolicyKind::Custom(name)),
+            _ => Err(format!("unknown scheduling policy {}", name)),
+        }
+    }
+}
+
+impl From<PolicyKind> for String {
+    fn from(kind: PolicyKind) -> Self {
+        match kind {
+            PolicyKind::Fcfs => "FCFS".to_string(),
+            PolicyKind::FeePriority => "FeePriority".to_string(),
+            PolicyKind::TimeBoost => "TimeBoost".to_string(),
+            PolicyKind::FairBft => "FairBFT".to_string(),
+            PolicyKind::Auction => "Auction".to_string(),
+            PolicyKind::WeightedFair => "WeightedFair".to_string(),
+            PolicyKind::Custom(name) => name,
+        }
+    }
+}
+
 /// Policy type enum for configuration
 /// 
 /// Allows easy policy selection via configuration files or API.
 /// Used by the factory function to create policy instances.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SchedulingPolicyType {
     /// First-Come-First-Served (maintain submission order)
     Fcfs,
-    /// Fee Priority (highest gas price first)
+    /// Fee Priority (highest effective tip first)
     FeePriority,
     /// Time-Boost with configurable time window
     TimeBoost { 
@@ -207,6 +305,21 @@ pub enum SchedulingPolicyType {
     },
     /// Fair BFT Ordering (timestamp-based)
     FairBft,
+    /// Sealed-bid auction for the top slots, then fee priority
+    Auction {
+        /// Positions at the top of each batch that are auctioned
+        top_slots: usize,
+    },
+    /// Weighted fair queuing across senders
+    WeightedFair {
+        /// Weight each sender by the tip of its first transaction
+        fee_weighted: bool,
+    },
+    /// A custom policy registered with [`super::register_policy`]
+    Custom {
+        /// Name it was registered under
+        name: String,
+    },
 }
 
 /// Factory function to create policy instances
@@ -217,12 +330,20 @@ pub enum SchedulingPolicyType {
 /// # Returns
 /// A boxed trait object implementing `SchedulingPolicy`
 /// 
+/// An auction policy made here has an empty book of its own; the
+/// orchestrator builds its own, sharing the book bids are placed in.
+/// 
+/// # Panics
+/// If a custom policy's name was never registered. Names parsed into a
+/// [`PolicyKind`] always are.
+/// 
 /// # Example
 /// ```
 /// use sequencer::scheduler::{create_policy, SchedulingPolicyType};
 /// 
 /// let policy = create_policy(SchedulingPolicyType::FeePriority);
-/// let ordered = policy.order_transactions(transactions);
+/// let ordered = policy.order_transactions(Vec::new(), 0.into());
+/// assert!(ordered.is_empty());
 /// ```
 pub fn create_policy(policy_type: SchedulingPolicyType) -> Box<dyn SchedulingPolicy> {
     match policy_type {
@@ -232,5 +353,12 @@ pub fn create_policy(policy_type: SchedulingPolicyType) -> Box<dyn SchedulingPol
             Box::new(TimeBoostPolicy { time_window_ms })
         }
         SchedulingPolicyType::FairBft => Box::new(FairBftPolicy),
+        SchedulingPolicyType::Auction { top_slots } => {
+            Box::new(AuctionPolicy::new(top_slots, Arc::new(AuctionBook::new())))
+        }
+        SchedulingPolicyType::WeightedFair { fee_weighted } => Box::new(WeightedFairPolicy { fee_weighted }),
+        SchedulingPolicyType::Custom { name } => {
+            custom_policy(&name).unwrap_or_else(|| panic!("Scheduling policy {} is not registered", name))
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+//! Custom Policy Registry Module
+//!
+//! Operators embedding the sequencer can order batches with their own
+//! `SchedulingPolicy` without patching the crate: they register a factory
+//! under a name, and that name is then accepted wherever a built-in policy
+//! name is (`scheduling.policy_type`, `admin_setPolicy`).
+//!
+//! The registry is process-wide, so policies must be registered before the
+//! configuration is loaded: an unregistered name fails to parse like a
+//! misspelled built-in one. Registrations cannot be undone, so a name that
+//! parsed once always resolves.
+//!
+//! Custom policies order the candidates the pool hands out in arrival order,
+//! as under FCFS; the scheduler then puts each sender's transactions back in
+//! nonce order. Policies are native Rust: no plugin loader (WASM or dynamic
+//! library) is provided.
+
+use super::policies::SchedulingPolicy;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Names of the built-in policies, which cannot be registered over
+const BUILTIN_POLICIES: [&str; 6] = ["FCFS", "FeePriority", "TimeBoost", "FairBFT", "Auction", "WeightedFair"];
+
+/// Builds a fresh instance of a custom policy
+pub type PolicyFactory = Arc<dyn Fn() -> Box<dyn SchedulingPolicy> + Send + Sync>;
+
+/// Custom policies by name
+static CUSTOM_POLICIES: LazyLock<RwLock<HashMap<String, PolicyFactory>>> = LazyLock::new(RwLock::default);
+
+/// Register a custom scheduling policy under `name`
+///
+/// `factory` is called each time the policy is selected. The name should be
+/// the one the policy reports (see [`SchedulingPolicy::name`]), which batches
+/// record in their metadata.
+///
+/// # Returns
+/// An error if `name` is empty, a built-in policy's, or already registered
+///
+/// # Example
+/// ```
+/// use sequencer::scheduler::{register_policy, Scheduler, SchedulingPolicy, SchedulingPolicyType};
+/// use sequencer::UserTransaction;
+/// use ethers::types::U256;
+///
+/// struct NewestFirst;
+///
+/// impl SchedulingPolicy for NewestFirst {
+///     fn order_transactions(&self, mut transactions: Vec<UserTransaction>, _base_fee: U256) -> Vec<UserTransaction> {
+///         transactions.reverse();
+///         transactions
+///     }
+///
+///     fn name(&self) -> &str {
+///         "NewestFirst"
+///     }
+/// }
+///
+/// register_policy("NewestFirst", || Box::new(NewestFirst)).unwrap();
+/// let scheduler = Scheduler::from_type(SchedulingPolicyType::Custom { name: "NewestFirst".to_string() });
+/// assert_eq!(scheduler.policy_name(), "NewestFirst");
+/// ```
+pub fn register_policy(
+    name: &str,
+    factory: impl Fn() -> Box<dyn SchedulingPolicy> + Send + Sync + 'static,
+) -> anyhow::Result<()> {
+    if name.is_empty() {
+        anyhow::bail!("Policy name must not be empty");
+    }
+    if BUILTIN_POLICIES.contains(&name) {
+        anyhow::bail!("{} is a built-in policy", name);
+    }
+    let mut policies = CUSTOM_POLICIES.write().unwrap();
+    if policies.contains_key(name) {
+        anyhow::bail!("Policy {} is already registered", name);
+    }
+    policies.insert(name.to_string(), Arc::new(factory));
+    Ok(())
+}
+
+/// Check whether a custom policy is registered under `name`
+pub fn is_registered(name: &str) -> bool {
+    CUSTOM_POLICIES.read().unwrap().contains_key(name)
+}
+
+/// Names of the registered custom policies, sorted
+pub fn registered_policies() -> Vec<String> {
+    let mut names: Vec<String> = CUSTOM_POLICIES.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// New instance of the custom policy registered under `name`, if any
+pub(crate) fn custom_policy(name: &str) -> Option<Box<dyn SchedulingPolicy>> {
+    let factory = CUSTOM_POLICIES.read().unwrap().get(name).cloned()?;
+    Some(factory())
+}
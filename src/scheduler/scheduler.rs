@@ -8,13 +8,21 @@
 //! - **FeePriority**: Transactions ordered by gas price (highest first)
 //! - **TimeBoost**: Time-windowed ordering with premium bids
 //! - **FairBFT**: Timestamp-based fair ordering (Byzantine Fault Tolerant)
+//! - **Auction**: Sealed bids for the top of the batch, then fee priority
+//! - **WeightedFair**: Round-robin across senders, optionally weighted by fee
+//! - Custom policies registered under their own name
 //! 
 //! # Important Rule
 //! Forced transactions from L1 ALWAYS come first, regardless of policy.
 //! Only normal transactions are reordered based on the selected policy.
+//! User operations from smart-contract wallets come last, in arrival order.
+//! Whatever the policy, each sender's transactions stay in nonce order: a
+//! nonce placed ahead of its predecessor could only fail.
 
-use crate::{UserTransaction, ForcedTransaction, Transaction};
-use super::policies::SchedulingPolicy;
+use crate::{UserTransaction, ForcedTransaction, Transaction, UserOperation};
+use super::policies::{create_policy, SchedulingPolicy, SchedulingPolicyType};
+use ethers::types::{Address, U256};
+use std::collections::HashMap;
 
 /// Transaction scheduler
 /// 
@@ -47,17 +55,32 @@ impl Scheduler {
         Self { policy }
     }
     
+    /// Creates a scheduler with the policy of the given type
+    /// 
+    /// # Example
+    /// ```
+    /// use sequencer::scheduler::{Scheduler, SchedulingPolicyType};
+    /// 
+    /// let scheduler = Scheduler::from_type(SchedulingPolicyType::FairBft);
+    /// assert_eq!(scheduler.policy_name(), "FairBFT");
+    /// ```
+    pub fn from_type(policy_type: SchedulingPolicyType) -> Self {
+        Self::new(create_policy(policy_type))
+    }
+    
     /// Schedule transactions for a batch
     /// 
     /// Combines forced and normal transactions into a single ordered list.
     /// 
     /// # Ordering Rules
     /// 1. ALL forced transactions come first (maintain L1 order)
-    /// 2. Normal transactions follow, ordered by the selected policy
+    /// 2. Normal transactions follow, ordered by the selected policy, then
+    ///    put back in nonce order within each sender
     /// 
     /// # Arguments
     /// * `forced` - Forced transactions from L1
     /// * `normal` - Normal user transactions from the pool
+    /// * `base_fee` - Current base fee per gas (for policies ranking by tip)
     /// 
     /// # Returns
     /// An ordered list of transactions ready for batching
@@ -65,6 +88,23 @@ impl Scheduler {
         &self,
         forced: Vec<ForcedTransaction>,
         normal: Vec<UserTransaction>,
+        base_fee: U256,
+    ) -> Vec<Transaction> {
+        self.schedule_with_user_ops(forced, normal, Vec::new(), base_fee)
+    }
+    
+    /// Schedule transactions and user operations for a batch
+    /// 
+    /// Like [`Scheduler::schedule`], with `user_ops` appended after the normal
+    /// transactions. User operations keep their arrival order: the policies
+    /// rank signed transactions, and arrival order keeps each wallet's
+    /// operations in nonce order.
+    pub fn schedule_with_user_ops(
+        &self,
+        forced: Vec<ForcedTransaction>,
+        normal: Vec<UserTransaction>,
+        user_ops: Vec<UserOperation>,
+        base_fee: U256,
     ) -> Vec<Transaction> {
         let mut result = Vec::new();
         
@@ -75,13 +115,16 @@ impl Scheduler {
         }
         
         // Step 2: Delegate normal transaction ordering to the policy
-        let ordered_normal = self.policy.order_transactions(normal);
+        let ordered_normal = in_nonce_order(self.policy.order_transactions(normal, base_fee));
         
         // Add all ordered normal transactions to the result
         for tx in ordered_normal {
             result.push(Transaction::Normal(tx));
         }
         
+        // Step 3: User operations last, as they arrived
+        result.extend(user_ops.into_iter().map(Transaction::UserOp));
+        
         result
     }
     
@@ -92,4 +135,25 @@ impl Scheduler {
     pub fn policy_name(&self) -> &str {
         self.policy.name()
     }
-}
\ No newline at end of file
+}
+
+/// Put each sender's transactions back in nonce order
+/// 
+/// A sender keeps the positions the policy gave its transactions, which are
+/// handed out to them in nonce order: a sender whose nonce 5 outbid its
+/// nonce 4 gets nonce 4 in the earlier slot and nonce 5 in the later one.
+fn in_nonce_order(ordered: Vec<UserTransaction>) -> Vec<UserTransaction> {
+    let mut by_sender: HashMap<Address, Vec<UserTransaction>> = HashMap::new();
+    let senders: Vec<Address> = ordered.iter().map(|tx| tx.from).collect();
+    for tx in ordered {
+        by_sender.entry(tx.from).or_default().push(tx);
+    }
+    // Sorted descending, so each slot pops the sender's lowest remaining nonce
+    for txs in by_sender.values_mut() {
+        txs.sort_by_key(|tx| std::cmp::Reverse(tx.nonce));
+    }
+    senders
+        .into_iter()
+        .filter_map(|sender| by_sender.get_mut(&sender).and_then(Vec::pop))
+        .collect()
+}
@@ -1,17 +1,31 @@
 //! Tests for scheduling policies
 //! 
 //! Comprehensive test suite verifying the behavior of all scheduling policies
+//! 
+//! Verifies:
+//! - Every configured policy name builds the scheduler of that name
+//! - Each sender's transactions stay in nonce order whatever the policy
+//! - The auction policy opens its sealed bids into the top slots
+//! - Fair queuing serves senders in rounds, weighted by tip if configured
+//! - Registered custom policies are selected by name
+//! - Execution lanes share no account, or are one lane once contract code runs
+//! - Batch metrics measure reordering and inclusion latency
 
 #[cfg(test)]
 mod tests {
     use crate::{
+        test_utils,
+        config::SchedulingConfig,
         scheduler::{
             SchedulingPolicy, FcfsPolicy, FeePriorityPolicy, TimeBoostPolicy, FairBftPolicy,
-            SchedulingPolicyType, create_policy, Scheduler,
+            SchedulingPolicyType, create_policy, Scheduler, AuctionBook, AuctionPolicy, register_policy,
+            registered_policies, execution_lanes, batch_fairness, SchedulingTelemetry,
         },
-        UserTransaction, ForcedTransaction, Transaction, ForcedEventType,
+        Batch, BatchFairness, UserTransaction, ForcedTransaction, Transaction, ForcedEventType,
     };
-    use ethers::types::{Address, U256, Signature, H256};
+    use ethers::types::{Address, Bytes, U256, H256};
+    use std::collections::HashSet;
+    use std::sync::Arc;
 
     /// Helper function to create a test user transaction
     fn create_test_tx(
@@ -22,15 +36,10 @@ mod tests {
         boost_bid: Option<u64>,
     ) -> UserTransaction {
         UserTransaction {
-            from: Address::zero(),
-            to: Address::zero(),
-            value: U256::from(1000),
-            nonce,
-            gas_price: U256::from(gas_price),
             gas_limit,
-            signature: Signature::default(),
             timestamp,
             boost_bid: boost_bid.map(U256::from),
+            ..test_utils::create_test_tx(nonce, gas_price)
         }
     }
 
@@ -45,8 +54,11 @@ mod tests {
             gas_limit,
             l1_tx_hash: H256::zero(),
             l1_block_number: 1,
+            l1_log_index: 0,
             event_type: ForcedEventType::Deposit,
             timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
         }
     }
 
@@ -61,7 +73,7 @@ mod tests {
             create_test_tx(3, 50, 21000, 3000, None),   // Lower gas price
         ];
         
-        let ordered = policy.order_transactions(txs.clone());
+        let ordered = policy.order_transactions(txs.clone(), U256::zero());
         
         // FCFS should maintain original order
         assert_eq!(ordered.len(), 3);
@@ -70,6 +82,32 @@ mod tests {
         assert_eq!(ordered[2].nonce, 3);
     }
 
+    #[test]
+    fn test_fee_priority_orders_by_effective_tip() {
+        let policy = FeePriorityPolicy;
+        let dynamic = |nonce, max_fee: u64, max_priority_fee: u64| UserTransaction {
+            max_fee_per_gas: Some(U256::from(max_fee)),
+            max_priority_fee_per_gas: Some(U256::from(max_priority_fee)),
+            data: Bytes::new(),
+            raw: None,
+            deadline: None,
+            priority_class: None,
+            ..create_test_tx(nonce, 0, 21000, 1000, None)
+        };
+        
+        // At a base fee of 100: tips are 30, 20 (capped by max fee), 10 and 50
+        let txs = vec![
+            dynamic(1, 1000, 30),
+            dynamic(2, 120, 90),
+            create_test_tx(3, 110, 21000, 1000, None),
+            create_test_tx(4, 150, 21000, 1000, None),
+        ];
+        
+        let ordered = policy.order_transactions(txs, U256::from(100));
+        let nonces: Vec<u64> = ordered.iter().map(|tx| tx.nonce).collect();
+        assert_eq!(nonces, vec![4, 1, 2, 3]);
+    }
+
     #[test]
     fn test_fee_priority_orders_by_gas_price() {
         let policy = FeePriorityPolicy;
@@ -82,7 +120,7 @@ mod tests {
             create_test_tx(4, 300, 21000, 4000, None),  // Medium gas price
         ];
         
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         
         // Should be ordered by gas price (highest first)
         assert_eq!(ordered.len(), 4);
@@ -106,7 +144,7 @@ mod tests {
             create_test_tx(4, 150, 21000, 2000, None),  // Window 0 (0-4999ms)
         ];
         
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         
         // Should process window 0 first, then window 1, then window 2
         assert_eq!(ordered.len(), 4);
@@ -130,7 +168,7 @@ mod tests {
             create_test_tx(4, 100, 21000, 4000, Some(800)),  // Highest boost
         ];
         
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         
         // Within same window, should order by boost_bid (highest first)
         assert_eq!(ordered.len(), 4);
@@ -154,7 +192,7 @@ mod tests {
             create_test_tx(3, 200, 21000, 3000, Some(500)), // Same boost, medium gas
         ];
         
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         
         // Should fall back to gas_price when boost_bid is equal
         assert_eq!(ordered.len(), 3);
@@ -174,7 +212,7 @@ mod tests {
             create_test_tx(3, 300, 21000, 3000, None),  // Middle
         ];
         
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         
         // Should be ordered by timestamp (earliest first)
         assert_eq!(ordered.len(), 3);
@@ -185,8 +223,7 @@ mod tests {
 
     #[test]
     fn test_scheduler_forced_transactions_always_first() {
-        let policy = create_policy(SchedulingPolicyType::FeePriority);
-        let scheduler = Scheduler::new(policy);
+        let scheduler = Scheduler::from_type(SchedulingPolicyType::FeePriority);
         
         // Create forced and normal transactions
         let forced = vec![
@@ -199,7 +236,7 @@ mod tests {
             create_test_tx(2, 500, 21000, 2000, None),
         ];
         
-        let ordered = scheduler.schedule(forced, normal);
+        let ordered = scheduler.schedule(forced, normal, U256::zero());
         
         // Verify forced transactions come first
         assert_eq!(ordered.len(), 4);
@@ -223,6 +260,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scheduler_keeps_each_sender_in_nonce_order() {
+        let from = |sender: u64, tx: UserTransaction| UserTransaction { from: Address::from_low_u64_be(sender), ..tx };
+        let normal = vec![
+            from(1, create_test_tx(4, 10, 21000, 1000, None)),
+            from(1, create_test_tx(5, 90, 21000, 1100, Some(50))),
+            from(2, create_test_tx(0, 50, 21000, 1200, None)),
+            from(1, create_test_tx(6, 70, 21000, 1300, None)),
+        ];
+        let order = |policy| {
+            Scheduler::from_type(policy)
+                .schedule(Vec::new(), normal.clone(), U256::zero())
+                .into_iter()
+                .map(|tx| match tx {
+                    Transaction::Normal(tx) => (tx.from.to_low_u64_be(), tx.nonce),
+                    _ => panic!("Expected normal transactions only"),
+                })
+                .collect::<Vec<_>>()
+        };
+        
+        // The fee order is 5, 6, then sender 2, then 4: sender 1 keeps its
+        // slots, filled in nonce order
+        assert_eq!(order(SchedulingPolicyType::FeePriority), vec![(1, 4), (1, 5), (2, 0), (1, 6)]);
+        // The boost bid puts nonce 5 first
+        assert_eq!(
+            order(SchedulingPolicyType::TimeBoost { time_window_ms: 5000 }),
+            vec![(1, 4), (1, 5), (2, 0), (1, 6)]
+        );
+        assert_eq!(order(SchedulingPolicyType::Fcfs), vec![(1, 4), (1, 5), (2, 0), (1, 6)]);
+    }
+
+    #[test]
+    fn test_weighted_fair_serves_senders_in_rounds() {
+        let from = |sender: u64, tx: UserTransaction| UserTransaction { from: Address::from_low_u64_be(sender), ..tx };
+        let normal = vec![
+            from(1, create_test_tx(0, 90, 21000, 1000, None)),
+            from(1, create_test_tx(1, 90, 21000, 1100, None)),
+            from(1, create_test_tx(2, 90, 21000, 1200, None)),
+            from(2, create_test_tx(0, 30, 21000, 1300, None)),
+            from(3, create_test_tx(0, 10, 21000, 1400, None)),
+            from(3, create_test_tx(1, 10, 21000, 1500, None)),
+        ];
+        let order = |fee_weighted| {
+            create_policy(SchedulingPolicyType::WeightedFair { fee_weighted })
+                .order_transactions(normal.clone(), U256::from(5))
+                .iter()
+                .map(|tx| (tx.from.to_low_u64_be(), tx.nonce))
+                .collect::<Vec<_>>()
+        };
+        
+        // One transaction per sender per round, in arrival order
+        assert_eq!(order(false), vec![(1, 0), (2, 0), (3, 0), (1, 1), (3, 1), (1, 2)]);
+        // Tips of 85, 25 and 5: sender 1's three finish before the others' first
+        assert_eq!(order(true), vec![(1, 0), (1, 1), (1, 2), (2, 0), (3, 0), (3, 1)]);
+    }
+
     #[test]
     fn test_policy_factory_creates_correct_instances() {
         // Test FCFS creation
@@ -242,6 +335,133 @@ mod tests {
         assert_eq!(fair_bft.name(), "FairBFT");
     }
 
+    #[test]
+    fn test_configured_policy_names_build_their_scheduler() {
+        for name in ["FCFS", "FeePriority", "TimeBoost", "FairBFT", "Auction", "WeightedFair"] {
+            let config: SchedulingConfig = toml::from_str(&format!("policy_type = \"{}\"", name)).unwrap();
+            assert_eq!(Scheduler::from_type(config.to_policy_type()).policy_name(), name);
+        }
+        
+        let config: SchedulingConfig = toml::from_str("policy_type = \"TimeBoost\"\ntime_window_ms = 250").unwrap();
+        assert!(matches!(config.to_policy_type(), SchedulingPolicyType::TimeBoost { time_window_ms: 250 }));
+        let config: SchedulingConfig = toml::from_str("policy_type = \"Auction\"\nauction_slots = 2").unwrap();
+        assert!(matches!(config.to_policy_type(), SchedulingPolicyType::Auction { top_slots: 2 }));
+        let config: SchedulingConfig = toml::from_str("policy_type = \"WeightedFair\"\nfee_weighted = true").unwrap();
+        assert!(matches!(config.to_policy_type(), SchedulingPolicyType::WeightedFair { fee_weighted: true }));
+        
+        // A misspelled policy is a configuration error
+        assert!(toml::from_str::<SchedulingConfig>("policy_type = \"Fifo\"").is_err());
+    }
+
+    #[test]
+    fn test_registered_custom_policy_is_selected_by_name() {
+        /// Orders the newest transactions first
+        struct NewestFirst;
+        
+        impl SchedulingPolicy for NewestFirst {
+            fn order_transactions(&self, mut transactions: Vec<UserTransaction>, _base_fee: U256) -> Vec<UserTransaction> {
+                transactions.sort_by_key(|tx| std::cmp::Reverse(tx.timestamp));
+                transactions
+            }
+            
+            fn name(&self) -> &str {
+                "TestNewestFirst"
+            }
+        }
+        
+        // Unknown until registered
+        assert!(toml::from_str::<SchedulingConfig>("policy_type = \"TestNewestFirst\"").is_err());
+        register_policy("TestNewestFirst", || Box::new(NewestFirst)).unwrap();
+        assert!(registered_policies().contains(&"TestNewestFirst".to_string()));
+        
+        let config: SchedulingConfig = toml::from_str("policy_type = \"TestNewestFirst\"").unwrap();
+        let scheduler = Scheduler::from_type(config.to_policy_type());
+        assert_eq!(scheduler.policy_name(), "TestNewestFirst");
+        let txs = vec![
+            UserTransaction { from: Address::from_low_u64_be(1), ..create_test_tx(0, 10, 21000, 1000, None) },
+            UserTransaction { from: Address::from_low_u64_be(2), ..create_test_tx(0, 10, 21000, 2000, None) },
+        ];
+        let ordered: Vec<u64> = scheduler.schedule(Vec::new(), txs, U256::zero())
+            .iter()
+            .map(|tx| match tx {
+                Transaction::Normal(tx) => tx.timestamp,
+                _ => panic!("Expected normal transactions only"),
+            })
+            .collect();
+        assert_eq!(ordered, vec![2000, 1000]);
+        
+        // Built-in and taken names cannot be registered
+        assert!(register_policy("FeePriority", || Box::new(NewestFirst)).is_err());
+        assert!(register_policy("TestNewestFirst", || Box::new(NewestFirst)).is_err());
+        assert!(register_policy("", || Box::new(NewestFirst)).is_err());
+    }
+
+    #[test]
+    fn test_execution_lanes_share_no_account() {
+        let transfer = |from: u64, to: u64, gas_limit: u64| Transaction::Normal(UserTransaction {
+            from: Address::from_low_u64_be(from),
+            to: Address::from_low_u64_be(to),
+            ..create_test_tx(0, 10, gas_limit, 1000, None)
+        });
+        let deposit = ForcedTransaction { to: Address::from_low_u64_be(5), ..create_forced_tx(0, 21000) };
+        let transactions = vec![
+            Transaction::Forced(deposit),
+            transfer(1, 2, 21000),
+            transfer(3, 4, 50000),
+            transfer(5, 6, 21000),
+            transfer(2, 7, 21000),
+            transfer(8, 9, 30000),
+        ];
+        let lanes = |max: usize| -> Vec<(Vec<usize>, u64)> {
+            execution_lanes(&transactions, max, &HashSet::new())
+                .into_iter()
+                .map(|lane| (lane.transactions, lane.gas_limit))
+                .collect()
+        };
+        
+        // The deposit credits sender 5, and sender 2 received before sending
+        assert_eq!(lanes(8), vec![
+            (vec![0, 3], 42000),
+            (vec![1, 4], 42000),
+            (vec![2], 50000),
+            (vec![5], 30000),
+        ]);
+        
+        // Fewer lanes: heaviest group first, each into the lightest lane
+        assert_eq!(lanes(2), vec![
+            (vec![0, 1, 3, 4], 84000),
+            (vec![2, 5], 80000),
+        ]);
+        assert_eq!(lanes(0), vec![(vec![0, 1, 2, 3, 4, 5], 164000)]);
+    }
+
+    #[test]
+    fn test_batches_running_contract_code_are_one_lane() {
+        let transfer = |from: u64, to: u64| UserTransaction {
+            from: Address::from_low_u64_be(from),
+            to: Address::from_low_u64_be(to),
+            ..create_test_tx(0, 10, 21000, 1000, None)
+        };
+        let serial = |transactions: &[Transaction], contracts: &HashSet<Address>| -> Vec<Vec<usize>> {
+            execution_lanes(transactions, 8, contracts).into_iter().map(|lane| lane.transactions).collect()
+        };
+        let transfers = vec![Transaction::Normal(transfer(1, 2)), Transaction::Normal(transfer(3, 4))];
+        assert_eq!(serial(&transfers, &HashSet::new()), vec![vec![0], vec![1]]);
+        
+        // A call may reach the other transfer's accounts
+        let call = UserTransaction { data: Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]), ..transfer(5, 6) };
+        let mut with_call = transfers.clone();
+        with_call.push(Transaction::Normal(call));
+        assert_eq!(serial(&with_call, &HashSet::new()), vec![vec![0, 1, 2]]);
+        
+        // So may a plain transfer to a contract, through its fallback
+        let contracts = HashSet::from([Address::from_low_u64_be(4)]);
+        let lanes = execution_lanes(&transfers, 8, &contracts);
+        assert_eq!(lanes.len(), 1);
+        assert_eq!((lanes[0].transactions.clone(), lanes[0].gas_limit), (vec![0, 1], 42000));
+        assert!(execution_lanes(&[], 8, &contracts).is_empty());
+    }
+
     #[test]
     fn test_policy_switching() {
         // Create transactions
@@ -253,17 +473,17 @@ mod tests {
         
         // Test with FCFS policy
         let fcfs_policy = create_policy(SchedulingPolicyType::Fcfs);
-        let fcfs_ordered = fcfs_policy.order_transactions(txs.clone());
+        let fcfs_ordered = fcfs_policy.order_transactions(txs.clone(), U256::zero());
         assert_eq!(fcfs_ordered[0].nonce, 1); // Original order
         
         // Test with FeePriority policy
         let fee_policy = create_policy(SchedulingPolicyType::FeePriority);
-        let fee_ordered = fee_policy.order_transactions(txs.clone());
+        let fee_ordered = fee_policy.order_transactions(txs.clone(), U256::zero());
         assert_eq!(fee_ordered[0].gas_price, U256::from(500)); // Highest fee first
         
         // Test with FairBFT policy
         let bft_policy = create_policy(SchedulingPolicyType::FairBft);
-        let bft_ordered = bft_policy.order_transactions(txs.clone());
+        let bft_ordered = bft_policy.order_transactions(txs.clone(), U256::zero());
         assert_eq!(bft_ordered[0].timestamp, 1000); // Earliest timestamp first
     }
 
@@ -271,7 +491,7 @@ mod tests {
     fn test_empty_transaction_list() {
         let policy = FeePriorityPolicy;
         let txs = vec![];
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         assert_eq!(ordered.len(), 0);
     }
 
@@ -279,8 +499,89 @@ mod tests {
     fn test_single_transaction() {
         let policy = TimeBoostPolicy { time_window_ms: 5000 };
         let txs = vec![create_test_tx(1, 100, 21000, 1000, None)];
-        let ordered = policy.order_transactions(txs);
+        let ordered = policy.order_transactions(txs, U256::zero());
         assert_eq!(ordered.len(), 1);
         assert_eq!(ordered[0].nonce, 1);
     }
+
+    #[test]
+    fn test_auction_opens_sealed_bids_into_top_slots() {
+        let txs = vec![
+            create_test_tx(1, 100, 21000, 1000, None),
+            create_test_tx(2, 500, 21000, 2000, None),
+            create_test_tx(3, 50, 21000, 3000, None),
+            create_test_tx(4, 300, 21000, 4000, None),
+        ];
+        let book = Arc::new(AuctionBook::new());
+        book.place(txs[2].hash(), Address::zero(), U256::from(10));
+        book.place(txs[0].hash(), Address::zero(), U256::from(20));
+        book.place(txs[3].hash(), Address::zero(), U256::from(10));
+        // Not the sender's bid: ignored
+        book.place(txs[1].hash(), Address::repeat_byte(0xb1), U256::from(99));
+        assert_eq!(book.len(), 4);
+        
+        let policy = AuctionPolicy::new(2, book.clone());
+        let ordered = policy.order_transactions(txs, U256::zero());
+        
+        // Highest bid first, equal bids to the earlier one, then by fee
+        let nonces: Vec<u64> = ordered.iter().map(|tx| tx.nonce).collect();
+        assert_eq!(nonces, vec![1, 3, 2, 4]);
+        let winners = book.take_winners();
+        assert_eq!(winners.len(), 2);
+        assert_eq!((winners[0].tx_hash, winners[0].amount), (ordered[0].hash(), U256::from(20)));
+        assert_eq!((winners[1].tx_hash, winners[1].amount), (ordered[1].hash(), U256::from(10)));
+        
+        // Opening closed the window
+        assert!(book.is_empty());
+        assert!(book.take_winners().is_empty());
+    }
+
+    #[test]
+    fn test_batch_fairness_measures_reordering_and_latency() {
+        let tx = |sender: u64, nonce: u64, gas_price: u64, timestamp: u64| Transaction::Normal(UserTransaction {
+            from: Address::from_low_u64_be(sender),
+            ..create_test_tx(nonce, gas_price, 21000, timestamp, None)
+        });
+        let batch = Batch {
+            batch_id: 1,
+            transactions: vec![
+                Transaction::Forced(create_forced_tx(0, 21000)),
+                tx(1, 0, 10, 30),
+                tx(2, 0, 30, 10),
+                tx(1, 1, 20, 20),
+            ],
+            prev_state_root: H256::zero(),
+            timestamp: 40,
+            lanes: None,
+        };
+        
+        // Arrival order is 2, 3, 1: the forced transaction is not measured
+        let fairness = batch_fairness(&batch, "FCFS", U256::zero());
+        assert_eq!(fairness.transactions, 3);
+        assert!((fairness.mean_reorder_distance - 4.0 / 3.0).abs() < 1e-9);
+        assert_eq!(fairness.max_reorder_distance, 2);
+        assert_eq!(fairness.inversions, 2);
+        // Overtaken tips of 30 and 20 against a mean tip of 20
+        assert!((fairness.fee_weighted_inversions - 2.5).abs() < 1e-9);
+        assert!((fairness.mean_inclusion_latency_secs - 20.0).abs() < 1e-9);
+        let senders: Vec<(u64, usize, f64, u64)> = fairness.senders
+            .iter()
+            .map(|sender| (sender.sender.to_low_u64_be(), sender.transactions, sender.mean_latency_secs, sender.max_latency_secs))
+            .collect();
+        assert_eq!(senders, vec![(2, 1, 30.0, 30), (1, 2, 15.0, 20)]);
+        
+        // Only the most recent batches are kept, but totals cover them all
+        let telemetry = SchedulingTelemetry::new(1);
+        telemetry.record(fairness.clone());
+        telemetry.record(BatchFairness { batch_id: 2, ..fairness });
+        let recent: Vec<u64> = telemetry.recent().iter().map(|fairness| fairness.batch_id).collect();
+        assert_eq!(recent, vec![2]);
+        assert!(telemetry.batch(1).is_none());
+        assert_eq!(telemetry.batch(2).unwrap().inversions, 2);
+        let metrics = telemetry.to_prometheus();
+        assert!(metrics.contains("sequencer_scheduling_batches_total{policy=\"FCFS\"} 2\n"));
+        assert!(metrics.contains("sequencer_scheduling_inversions_total{policy=\"FCFS\"} 4\n"));
+        assert!(metrics.contains("sequencer_inclusion_latency_seconds_total{policy=\"FCFS\"} 120\n"));
+        assert!(metrics.contains("sequencer_scheduling_max_sender_latency_seconds 30\n"));
+    }
 }
@@ -2,28 +2,180 @@
 //! 
 //! This module provides an in-memory cache for account state (balances and nonces).
 //! The cache is used for fast transaction validation without querying a database.
-//! It supports concurrent access through RwLock for thread safety.
+//! Accounts are spread over shards by address, each behind its own RwLock,
+//! so concurrent validations of different senders rarely wait on each other.
+//!
+//! # Archival
+//! When an archive is attached, accounts that have not been modified for a
+//! configured number of batches can be moved out of memory into the registry.
+//! Archived accounts are resurrected transparently the next time any method
+//! references them, and a `ResurrectionWitness` is recorded for each resurrection.
+//!
+//! # Account Creation
+//! An account is created the first time it is seen, by validation or
+//! admission, with a zero nonce. On a devnet, new accounts can be prefunded
+//! so senders can transact without a deposit.
+//!
+//! # Sealed Transactions
+//! Transactions change balances once they are sealed into a batch: deposits
+//! from L1 credit the recipient, forced exits debit the sender, and transfers
+//! move value and charge fees.
+//!
+//! # Snapshots
+//! With snapshots enabled, the changes made while sealing each recent batch
+//! are recorded under its batch ID. If the batch fails downstream (submission
+//! or proving), [`StateCache::rollback`] undoes them, and those of every batch
+//! sealed after it. Only what sealing changed is undone: nonces of transactions
+//! admitted since are kept. Snapshots are held in memory only.
+//!
+//! # Journal
+//! With a journal enabled, every change is also recorded in a [`StateJournal`]
+//! with the transaction and batch it was made for.
+//!
+//! # Nonce Reservations
+//! Admitting a transaction reserves the nonce it bumps past, in
+//! [`NonceReservations`]. Sealing the transaction commits the nonce, and
+//! dropping it from the pool releases it with [`StateCache::give_back`],
+//! along with the sender's later transactions, so the sender can reuse it.
+//!
+//! # Contract Accounts
+//! An account with a code hash or storage is a contract account. Its storage
+//! slots are held in an [`AccountStorage`] per account, whose trie root is
+//! the account's `storage_root`, so the state root commits to every slot.
+//! Contract accounts and their storage are never archived.
+//!
+//! # Stakes
+//! The cache also tracks each sender's stake on L1, as reported by the L1
+//! listener. Stakes are never archived.
+//!
+//! # Persistence
+//! With a [`StateStore`] attached, the cache is a write-through cache over it:
+//! every change is written to the store, and accounts missing from memory are
+//! looked up there before the archive. Stakes are loaded when it is attached.
+//! Memory can then be bounded: over [`StateCache::with_max_hot_accounts`],
+//! the least recently used accounts are dropped from memory, to be read back
+//! from the store on a miss. The state trie moves its deep subtrees out to the
+//! store too. Contract storage and stakes are still held in memory whole.
+//!
+//! # Checkpoints
+//! [`StateCache::export_checkpoint`] collects every account and storage slot
+//! into a [`StateCheckpoint`], with nonces as the last sealed batch left them.
+//! [`StateCache::import_checkpoint`] loads one into an empty state, so a new
+//! node starts from that batch.
+//!
+//! # State Root
+//! Every account, hot or archived, is also kept in a [`StateTrie`], whose root
+//! commits to the whole state. Archiving moves an account out of memory but
+//! not out of the trie. Without a store, the trie holds every account in
+//! memory, so neither archival nor eviction bounds it; with one, only the
+//! trie's nodes down to `TRIE_HOT_DEPTH` and those on paths changed since the
+//! last root stay in memory. After a restart, [`StateCache::restore`] puts the
+//! stored and archived accounts back into it. [`StateCache::witness`] proves
+//! accounts against the root.
+//!
+//! # Metrics
+//! The cache counts where each account lookup was served from and how long
+//! callers waited for shard locks. [`StateCache::metrics`] reads the counters,
+//! with how many accounts are held in memory and in the state.
 
-use crate::AccountState;
-use ethers::types::{Address, U256};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use super::metrics::{CacheCounters, Lookup};
+use super::{
+    AccountStorage, AccountWitness, ContractStorage, Genesis, NonceReservations, StateCheckpoint, StateJournal,
+    StateMetrics, StateStore, StateTrie, StateWitness,
+};
+use crate::{
+    AccountState, ArchivedAccount, ForcedEventType, ForcedTransaction, ResurrectionWitness, StateChangeKind,
+    StateSnapshot, UserTransaction, ValidationError, pool::TransactionPool, registry::Registry,
+};
+use ethers::types::{Address, H256, U256};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tracing::{debug, error, warn};
+
+/// An account held in memory, with its activity stamp
+struct HotAccount {
+    /// Current account state
+    state: AccountState,
+    /// Last batch during which the account was modified or resurrected
+    last_active_batch: u64,
+    /// Access clock reading when the account was last read or written
+    last_access: AtomicU64,
+}
+
+/// An account changed while sealing a batch
+struct AccountChange {
+    /// State before the batch (`None` if the account did not exist)
+    before: Option<AccountState>,
+    /// State the batch left
+    after: AccountState,
+}
+
+/// Changes made while sealing a batch, by account
+type BatchSnapshot = HashMap<Address, AccountChange>;
+
+/// Accounts whose addresses map to one shard
+type Shard = RwLock<HashMap<Address, HotAccount>>;
+
+/// Shards of a cache unless configured otherwise
+const DEFAULT_SHARDS: usize = 16;
+
+/// Depth from which state trie subtrees are moved out to the store at each
+/// root, leaving at most about 2^17 nodes in memory between roots
+const TRIE_HOT_DEPTH: usize = 16;
+
+/// What a change is journaled as
+struct Cause {
+    kind: StateChangeKind,
+    tx_hash: Option<H256>,
+    batch_id: Option<u64>,
+}
 
 /// In-memory state cache for account data
 /// 
 /// Stores account state (balance and nonce) in memory for fast access.
-/// Uses `Arc<RwLock<...>>` for thread-safe concurrent access:
-/// - Multiple readers can access simultaneously
-/// - Writes are exclusive
+/// Accounts are sharded by address, each shard behind its own `RwLock`:
+/// - Multiple readers can access a shard simultaneously
+/// - Writes are exclusive within a shard, and never block other shards
+/// - Operations spanning every account lock all shards, always in shard order
 /// 
 /// # Cloning
 /// This struct is cheaply cloneable because it uses Arc internally.
 /// All clones share the same underlying data.
 #[derive(Clone)]
 pub struct StateCache {
-    /// Map from address to account state, protected by a read-write lock
-    accounts: Arc<RwLock<HashMap<Address, AccountState>>>,
+    /// Accounts by address, split into independently locked shards
+    accounts: Arc<Vec<Shard>>,
+    /// Latest sealed batch, used to stamp account activity
+    current_batch: Arc<AtomicU64>,
+    /// Where inactive accounts are archived (archival disabled if `None`)
+    archive: Option<Arc<Registry>>,
+    /// Amount each sender has staked on L1
+    stakes: Arc<RwLock<HashMap<Address, U256>>>,
+    /// Storage slots of contract accounts
+    storage: Arc<RwLock<HashMap<Address, AccountStorage>>>,
+    /// Persistent store written through to (in memory only if `None`)
+    store: Option<StateStore>,
+    /// Merkle trie over every account, hot or archived
+    trie: Arc<Mutex<StateTrie>>,
+    /// Changes made while sealing recent batches, by batch ID
+    snapshots: Arc<Mutex<BTreeMap<u64, BatchSnapshot>>>,
+    /// Most recent batches kept snapshots of (snapshots disabled if 0)
+    max_snapshots: usize,
+    /// Record of recent changes
+    journal: Arc<StateJournal>,
+    /// Nonces reserved by pending transactions
+    reservations: Arc<NonceReservations>,
+    /// Balance given to accounts when they are created
+    prefund: U256,
+    /// Most accounts held in memory over a store (unbounded if `None`)
+    max_hot_accounts: Option<usize>,
+    /// Ticks on every access, ordering accounts by recency
+    access_clock: Arc<AtomicU64>,
+    /// Lookup and lock wait counters
+    counters: Arc<CacheCounters>,
 }
 
 impl StateCache {
@@ -33,8 +185,105 @@ impl StateCache {
     /// A new `StateCache` instance with no accounts
     pub fn new() -> Self {
         Self {
-            accounts: Arc::new(RwLock::new(HashMap::new())),
+            accounts: Arc::new(new_shards(DEFAULT_SHARDS)),
+            current_batch: Arc::new(AtomicU64::new(0)),
+            archive: None,
+            stakes: Arc::new(RwLock::new(HashMap::new())),
+            storage: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
+            trie: Arc::new(Mutex::new(StateTrie::new())),
+            snapshots: Arc::new(Mutex::new(BTreeMap::new())),
+            max_snapshots: 0,
+            journal: Arc::new(StateJournal::new(0)),
+            reservations: Arc::new(NonceReservations::new()),
+            prefund: U256::zero(),
+            max_hot_accounts: None,
+            access_clock: Arc::new(AtomicU64::new(0)),
+            counters: Arc::new(CacheCounters::default()),
+        }
+    }
+
+    /// Attach a registry to archive inactive accounts into
+    ///
+    /// Must be called before the cache is cloned: clones made earlier
+    /// don't see the archive.
+    pub fn with_archive(mut self, registry: Arc<Registry>) -> Self {
+        self.archive = Some(registry);
+        self
+    }
+
+    /// Persist state in `store`, picking up the state it already holds
+    ///
+    /// Must be called before the cache is cloned: clones made earlier
+    /// don't see the store.
+    pub fn with_store(mut self, store: StateStore) -> Self {
+        self.stakes = Arc::new(RwLock::new(store.stakes().into_iter().collect()));
+        let mut storage: HashMap<Address, AccountStorage> = HashMap::new();
+        for (address, slot, value) in store.storage() {
+            storage.entry(address).or_default().set(slot, value);
         }
+        self.storage = Arc::new(RwLock::new(storage));
+        self.current_batch = Arc::new(AtomicU64::new(store.current_batch()));
+        self.trie = Arc::new(Mutex::new(StateTrie::new().with_store(store.clone(), TRIE_HOT_DEPTH)));
+        self.store = Some(store);
+        self
+    }
+
+    /// Keep snapshots of the last `max_batches` sealed batches, so they can be rolled back
+    pub fn with_snapshots(mut self, max_batches: usize) -> Self {
+        self.max_snapshots = max_batches;
+        self
+    }
+
+    /// Spread accounts over `shards` independently locked shards (at least one)
+    ///
+    /// Must be called before any account is added and before the cache is
+    /// cloned: accounts added earlier are dropped, and clones made earlier
+    /// keep the previous shards.
+    pub fn with_shards(mut self, shards: usize) -> Self {
+        self.accounts = Arc::new(new_shards(shards.max(1)));
+        self
+    }
+
+    /// Hold at most `max` accounts in memory, evicting the least recently used
+    ///
+    /// Only takes effect with a store attached, which evicted accounts are
+    /// read back from when referenced again. Each shard holds its share of
+    /// `max`, so the bound is approximate across shards. Bounds the accounts
+    /// only: the state trie has its own bound (see `TRIE_HOT_DEPTH`), and
+    /// contract storage is not bounded. Must be called before the cache is
+    /// cloned: clones made earlier stay unbounded.
+    pub fn with_max_hot_accounts(mut self, max: usize) -> Self {
+        self.max_hot_accounts = Some(max);
+        self
+    }
+
+    /// Prefund accounts with `amount` when they are created (devnets only)
+    ///
+    /// Must be called before the cache is cloned: clones made earlier
+    /// don't see the prefund.
+    pub fn with_prefund(mut self, amount: U256) -> Self {
+        self.prefund = amount;
+        self
+    }
+
+    /// Journal the last `capacity` changes
+    ///
+    /// Must be called before the cache is cloned: clones made earlier
+    /// don't see the journal.
+    pub fn with_journal(mut self, capacity: usize) -> Self {
+        self.journal = Arc::new(StateJournal::new(capacity));
+        self
+    }
+
+    /// The journal of recent changes (empty if journaling is disabled)
+    pub fn journal(&self) -> &StateJournal {
+        &self.journal
+    }
+
+    /// Nonces reserved by pending transactions
+    pub fn reservations(&self) -> &NonceReservations {
+        &self.reservations
     }
     
     /// Get the balance of an account
@@ -43,12 +292,10 @@ impl StateCache {
     /// * `address` - The account address to query
     /// 
     /// # Returns
-    /// * `Some(balance)` if the account exists in the cache
-    /// * `None` if the account is not in the cache
+    /// * `Some(balance)` if the account exists in the cache (or archive)
+    /// * `None` if the account is unknown
     pub async fn get_balance(&self, address: &Address) -> Option<U256> {
-        // Acquire read lock (allows concurrent reads)
-        let accounts = self.accounts.read().await;
-        accounts.get(address).map(|acc| acc.balance)
+        self.load(address).await.map(|acc| acc.balance)
     }
     
     /// Get the nonce of an account
@@ -57,39 +304,68 @@ impl StateCache {
     /// * `address` - The account address to query
     /// 
     /// # Returns
-    /// * `Some(nonce)` if the account exists in the cache
-    /// * `None` if the account is not in the cache
+    /// * `Some(nonce)` if the account exists in the cache (or archive)
+    /// * `None` if the account is unknown
     pub async fn get_nonce(&self, address: &Address) -> Option<u64> {
-        // Acquire read lock (allows concurrent reads)
-        let accounts = self.accounts.read().await;
-        accounts.get(address).map(|acc| acc.nonce)
+        self.load(address).await.map(|acc| acc.nonce)
     }
     
-    /// Get account state or initialize with defaults if not found
+    /// Get account state, reading unknown accounts as they would be created
+    ///
+    /// Unlike [`StateCache::get_or_init_account`], nothing is added to the
+    /// cache. Used for reads and simulations that must not change the state.
+    ///
+    /// # Arguments
+    /// * `address` - The account address to query
+    pub async fn get_account(&self, address: &Address) -> AccountState {
+        self.load(address).await.unwrap_or_else(|| self.new_account(address))
+    }
+
+    /// Get account state, creating the account if not found
     /// 
-    /// This is the primary method used during transaction validation.
-    /// If the account doesn't exist, it returns default values (zero balance, zero nonce)
-    /// WITHOUT adding it to the cache. This allows validation to proceed for new accounts.
+    /// Used once a transaction is admitted or executed; validation reads with
+    /// [`StateCache::get_account`], so rejected transactions create nothing.
+    /// If the account doesn't exist, it is added to the cache with a zero
+    /// nonce and the configured prefund (zero by default), so every later
+    /// read and write sees the same account.
     /// 
     /// # Arguments
     /// * `address` - The account address to query
     /// 
     /// # Returns
-    /// Account state (either from cache or default values)
+    /// Account state (either existing or newly created)
     pub async fn get_or_init_account(&self, address: &Address) -> AccountState {
-        // First try to read from cache
-        let accounts = self.accounts.read().await;
-        if let Some(account) = accounts.get(address) {
-            // Account exists - return a clone
-            account.clone()
-        } else {
-            // Account doesn't exist - release read lock and return defaults
-            drop(accounts); // Explicitly drop to release lock early
-            AccountState {
-                address: *address,
-                balance: U256::zero(), // New accounts start with zero balance
-                nonce: 0,               // New accounts start with nonce 0
+        if let Some(account) = self.load(address).await {
+            return account;
+        }
+
+        // Created under the write lock, so concurrent callers agree on one account
+        let mut accounts = self.write_shard(address).await;
+        let state = match accounts.entry(*address) {
+            Entry::Occupied(entry) => return entry.get().state.clone(),
+            Entry::Vacant(entry) => {
+                let state = self.new_account(address);
+                self.persist(None, &state, Cause {
+                    kind: StateChangeKind::Creation,
+                    tx_hash: None,
+                    batch_id: None,
+                })
+                .await;
+                entry.insert(self.hot(state.clone(), self.current_batch()));
+                state
             }
+        };
+        self.evict(&mut accounts);
+        state
+    }
+
+    /// State of an account being created
+    fn new_account(&self, address: &Address) -> AccountState {
+        AccountState {
+            address: *address,
+            balance: self.prefund,
+            nonce: 0,
+            ..Default::default()
         }
     }
     
@@ -97,29 +373,33 @@ impl StateCache {
     /// 
     /// Called after a transaction is validated and accepted into the pool.
     /// This prevents the next transaction from the same account from having
-    /// a nonce conflict.
+    /// a nonce conflict. The nonce is reserved for the transaction until it is
+    /// sealed, and given back if it is dropped (see [`StateCache::release_nonce`]).
     /// 
     /// # Arguments
     /// * `address` - The account address to update
+    /// * `tx_hash` - The accepted transaction, journaled with the change
     /// 
     /// # Behavior
     /// - If account exists: increments its nonce by 1
-    /// - If account doesn't exist: creates it with nonce 1 and zero balance
-    pub async fn increment_nonce(&self, address: &Address) {
-        // Acquire write lock (exclusive access)
-        let mut accounts = self.accounts.write().await;
-        if let Some(account) = accounts.get_mut(address) {
-            // Account exists - increment nonce
-            account.nonce += 1;
-        } else {
-            // Account doesn't exist - initialize with nonce 1
-            // This handles the case where the first transaction from an account is processed
-            accounts.insert(*address, AccountState {
-                address: *address,
-                balance: U256::zero(),
-                nonce: 1, // First transaction processed, so nonce becomes 1
-            });
-        }
+    /// - If account doesn't exist: creates it as [`StateCache::get_or_init_account`]
+    ///   does, with nonce 1
+    pub async fn increment_nonce(&self, address: &Address, tx_hash: H256) {
+        // Bring the account back if it was archived, or create it
+        self.get_or_init_account(address).await;
+
+        // Acquire the shard's write lock (exclusive access)
+        let mut accounts = self.write_shard(address).await;
+        let before = accounts.get(address).map(|account| account.state.clone());
+        let mut state = before.clone().unwrap_or_else(|| self.new_account(address));
+        self.reservations.reserve(*address, state.nonce, tx_hash);
+        state.nonce += 1;
+        self.write(&mut accounts, before.as_ref(), state, Cause {
+            kind: StateChangeKind::Admission,
+            tx_hash: Some(tx_hash),
+            batch_id: None,
+        })
+        .await;
     }
     
     /// Update or insert account state
@@ -130,8 +410,904 @@ impl StateCache {
     /// # Arguments
     /// * `state` - The new account state to store
     pub async fn update(&self, state: AccountState) {
-        // Acquire write lock (exclusive access)
-        let mut accounts = self.accounts.write().await;
-        accounts.insert(state.address, state);
+        // Resurrect first so a stale archived copy can't come back later
+        self.load(&state.address).await;
+
+        // Acquire the shard's write lock (exclusive access)
+        let mut accounts = self.write_shard(&state.address).await;
+        let before = accounts.get(&state.address).map(|account| account.state.clone());
+        self.write(&mut accounts, before.as_ref(), state, Cause {
+            kind: StateChangeKind::Sync,
+            tx_hash: None,
+            batch_id: None,
+        })
+        .await;
+    }
+
+    /// Value of a storage slot of an account (zero if never set)
+    pub async fn storage_at(&self, address: &Address, slot: &H256) -> H256 {
+        match self.storage.read().await.get(address) {
+            Some(storage) => storage.get(slot),
+            None => H256::zero(),
+        }
+    }
+
+    /// Set storage slots of an account, clearing those set to zero
+    ///
+    /// The account's `storage_root` is updated to commit to the new slots,
+    /// and the account is created if it doesn't exist yet.
+    pub async fn set_storage(&self, address: &Address, slots: &[(H256, H256)]) {
+        self.load(address).await;
+
+        // Under the account's write lock, so the root and the slots agree
+        let mut accounts = self.write_shard(address).await;
+        let before = accounts.get(address).map(|account| account.state.clone());
+        let mut state = before.clone().unwrap_or_else(|| self.new_account(address));
+        {
+            let mut storage = self.storage.write().await;
+            let account_storage = storage.entry(*address).or_default();
+            for (slot, value) in slots {
+                account_storage.set(*slot, *value);
+            }
+            state.storage_root = account_storage.root();
+            if account_storage.is_empty() {
+                storage.remove(address);
+            }
+        }
+        if let Some(store) = &self.store {
+            store.put_storage(address, slots).await;
+        }
+        self.write(&mut accounts, before.as_ref(), state, Cause {
+            kind: StateChangeKind::Contract,
+            tx_hash: None,
+            batch_id: None,
+        })
+        .await;
+    }
+
+    /// Set the code hash of an account, making it a contract account
+    ///
+    /// The account is created if it doesn't exist yet.
+    pub async fn set_code_hash(&self, address: &Address, code_hash: H256) {
+        self.load(address).await;
+
+        let mut accounts = self.write_shard(address).await;
+        let before = accounts.get(address).map(|account| account.state.clone());
+        let mut state = before.clone().unwrap_or_else(|| self.new_account(address));
+        state.code_hash = code_hash;
+        self.write(&mut accounts, before.as_ref(), state, Cause {
+            kind: StateChangeKind::Contract,
+            tx_hash: None,
+            batch_id: None,
+        })
+        .await;
+    }
+
+    /// Apply a deposit, message or forced exit sealed into batch `batch_id`
+    ///
+    /// A deposit credits `tx.to`, creating the account if needed, and so does
+    /// the value of a message; its calldata is left to the executor. A forced
+    /// exit debits `tx.from`; an exit larger than the balance can't be honored
+    /// and leaves the account untouched. Nonces are not changed. The change is
+    /// recorded in the batch's snapshot, if one was taken.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the balance was changed
+    /// * `Err(ValidationError::InsufficientBalance)` if a forced exit exceeds the balance
+    pub async fn apply_forced(&self, batch_id: u64, tx: &ForcedTransaction) -> Result<(), ValidationError> {
+        match tx.event_type {
+            ForcedEventType::Deposit | ForcedEventType::Message => {
+                self.apply(batch_id, tx.tx_hash, &tx.to, |state| {
+                    state.balance = state.balance.saturating_add(tx.value);
+                    Ok(())
+                })
+                .await
+            }
+            ForcedEventType::ForcedExit => {
+                self.apply(batch_id, tx.tx_hash, &tx.from, |state| debit(state, tx.value)).await
+            }
+        }
+    }
+
+    /// Execute transfer `tx_hash` sealed into batch `batch_id`
+    ///
+    /// Debits `value + fee` from `from` and credits `value` to `to`. The fee
+    /// is not credited to any L2 account. A sender that can no longer cover
+    /// both is charged the fee (or its whole balance, if less) and the value
+    /// is not moved, like a reverted transaction. Nonces are not changed: they
+    /// were bumped when the transaction was admitted, and the nonce it reserved
+    /// then is committed. The changes are recorded in the batch's snapshot, if
+    /// one was taken.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the value was moved
+    /// * `Err(ValidationError::InsufficientBalance)` if the transfer was reverted
+    pub async fn apply_transfer(
+        &self,
+        batch_id: u64,
+        tx_hash: H256,
+        from: &Address,
+        to: &Address,
+        value: U256,
+        fee: U256,
+    ) -> Result<(), ValidationError> {
+        self.reservations.commit(from, tx_hash);
+        let cost = value.saturating_add(fee);
+        if let Err(e) = self.apply(batch_id, tx_hash, from, |state| debit(state, cost)).await {
+            self.apply(batch_id, tx_hash, from, |state| {
+                state.balance = state.balance.saturating_sub(fee);
+                Ok(())
+            })
+            .await?;
+            return Err(e);
+        }
+        if !value.is_zero() {
+            self.apply(batch_id, tx_hash, to, |state| {
+                state.balance = state.balance.saturating_add(value);
+                Ok(())
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Change an account for transaction `tx_hash` while sealing batch `batch_id`
+    ///
+    /// `change` edits a copy of the account (a zero account if unknown), which
+    /// is only written back, and recorded in the batch's snapshot, if it returns `Ok`.
+    async fn apply(
+        &self,
+        batch_id: u64,
+        tx_hash: H256,
+        address: &Address,
+        change: impl FnOnce(&mut AccountState) -> Result<(), ValidationError>,
+    ) -> Result<(), ValidationError> {
+        self.load(address).await;
+
+        let mut accounts = self.write_shard(address).await;
+        let before = accounts.get(address).map(|account| account.state.clone());
+        let mut state = before.clone().unwrap_or(AccountState {
+            address: *address,
+            balance: U256::zero(),
+            nonce: 0,
+            ..Default::default()
+        });
+        change(&mut state)?;
+        if let Some(snapshot) = self.snapshots.lock().unwrap().get_mut(&batch_id) {
+            snapshot
+                .entry(*address)
+                .or_insert_with(|| AccountChange { before: before.clone(), after: state.clone() })
+                .after = state.clone();
+        }
+        self.write(&mut accounts, before.as_ref(), state, Cause {
+            kind: StateChangeKind::Execution,
+            tx_hash: Some(tx_hash),
+            batch_id: Some(batch_id),
+        })
+        .await;
+        Ok(())
+    }
+
+    /// Write a changed account to memory, the store and the trie, and journal the change
+    ///
+    /// Called under the shard's write lock, so concurrent changes reach the
+    /// store and the journal in order. The account counts as active in the
+    /// current batch.
+    async fn write(
+        &self,
+        accounts: &mut HashMap<Address, HotAccount>,
+        before: Option<&AccountState>,
+        state: AccountState,
+        cause: Cause,
+    ) {
+        self.persist(before, &state, cause).await;
+        accounts.insert(state.address, self.hot(state, self.current_batch()));
+        self.evict(accounts);
+    }
+
+    /// Wrap an account going into memory, as just accessed
+    fn hot(&self, state: AccountState, last_active_batch: u64) -> HotAccount {
+        HotAccount {
+            state,
+            last_active_batch,
+            last_access: AtomicU64::new(self.access_clock.fetch_add(1, Ordering::Relaxed)),
+        }
+    }
+
+    /// Drop a shard's least recently used accounts from memory once over its limit
+    ///
+    /// They stay in the store and the trie. Evicts down to 90% of the limit,
+    /// so the scan this takes is paid once per many insertions.
+    fn evict(&self, accounts: &mut HashMap<Address, HotAccount>) {
+        let Some(max) = self.max_hot_accounts else {
+            return;
+        };
+        let max = max.div_ceil(self.accounts.len()).max(1);
+        if self.store.is_none() || accounts.len() <= max {
+            return;
+        }
+        let mut by_access: Vec<(u64, Address)> = accounts
+            .iter()
+            .map(|(address, account)| (account.last_access.load(Ordering::Relaxed), *address))
+            .collect();
+        let evicted = accounts.len() - (max - max / 10);
+        by_access.select_nth_unstable(evicted - 1);
+        for (_, address) in &by_access[..evicted] {
+            accounts.remove(address);
+        }
+        debug!("Evicted {} least recently used accounts from memory", evicted);
+    }
+
+    /// Write a changed account to the store and the trie, and journal the change
+    async fn persist(&self, before: Option<&AccountState>, state: &AccountState, cause: Cause) {
+        if let Some(store) = &self.store {
+            store.put_account(state).await;
+        }
+        self.trie.lock().unwrap().insert(state);
+        self.journal.record(cause.kind, cause.tx_hash, cause.batch_id, before, state);
+    }
+
+    /// Start recording the changes made while sealing batch `batch_id`
+    ///
+    /// Called before the batch changes any state. The oldest snapshot is
+    /// dropped once more than the configured number are kept. Does nothing
+    /// with snapshots disabled.
+    pub fn snapshot(&self, batch_id: u64) {
+        if self.max_snapshots == 0 {
+            return;
+        }
+        let mut snapshots = self.snapshots.lock().unwrap();
+        snapshots.insert(batch_id, BatchSnapshot::new());
+        while snapshots.len() > self.max_snapshots {
+            snapshots.pop_first();
+        }
     }
-}
\ No newline at end of file
+
+    /// Drop the snapshots of batches up to `batch_id`, once they can no longer fail
+    pub fn release(&self, batch_id: u64) {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        *snapshots = snapshots.split_off(&(batch_id + 1));
+    }
+
+    /// Undo the changes made while sealing batch `batch_id` and every later batch
+    ///
+    /// Newest batches are undone first. Accounts created by the undone batches
+    /// are removed again, so the state root returns to the one the batch was
+    /// sealed on, unless accounts changed by other means since.
+    ///
+    /// # Returns
+    /// * `Ok(count)` - The number of account changes undone
+    /// * `Err` if there is no snapshot of `batch_id` (never taken, or already dropped)
+    pub async fn rollback(&self, batch_id: u64) -> anyhow::Result<usize> {
+        let undone = {
+            let mut snapshots = self.snapshots.lock().unwrap();
+            if !snapshots.contains_key(&batch_id) {
+                anyhow::bail!("no snapshot of batch {}", batch_id);
+            }
+            snapshots.split_off(&batch_id)
+        };
+
+        let mut count = 0;
+        for (batch_id, snapshot) in undone.into_iter().rev() {
+            for (address, change) in snapshot {
+                self.undo(&address, change, Cause {
+                    kind: StateChangeKind::Undo,
+                    tx_hash: None,
+                    batch_id: Some(batch_id),
+                })
+                .await;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Give back the nonce of a transaction dropped from the pool before being sealed
+    ///
+    /// Releases the nonce the transaction reserved at admission, along with
+    /// every later nonce of the sender, and rewinds the sender's nonce to it.
+    /// The sender's later pending transactions can't execute past the gap, so
+    /// the caller drops them from the pool. Does nothing if the transaction
+    /// holds no reservation (already sealed or released).
+    ///
+    /// # Returns
+    /// The released nonce, from which the sender's pending transactions must
+    /// be dropped (`None` if nothing was released)
+    pub async fn release_nonce(&self, address: &Address, tx_hash: H256) -> Option<u64> {
+        self.load(address).await;
+
+        // Under the account's write lock, so no admission interleaves
+        let mut accounts = self.write_shard(address).await;
+        let nonce = self.reservations.release(address, tx_hash)?;
+        let before = match accounts.get(address) {
+            Some(account) => account.state.clone(),
+            None => self.read_cold(address).await.unwrap_or_else(|| self.new_account(address)),
+        };
+        if before.nonce > nonce {
+            let state = AccountState { nonce, ..before.clone() };
+            self.write(&mut accounts, Some(&before), state, Cause {
+                kind: StateChangeKind::Undo,
+                tx_hash: Some(tx_hash),
+                batch_id: None,
+            })
+            .await;
+        }
+        Some(nonce)
+    }
+
+    /// Give back the nonce of a transaction removed from the pool
+    ///
+    /// Releases the transaction's nonce with [`Self::release_nonce`], then
+    /// drops the sender's pending transactions from the released nonce on.
+    /// A transaction admitted at the released nonce in between is dropped
+    /// too, still holding a reservation: the caller gives back every
+    /// `PoolEvent::Dropped` transaction as well, which releases it.
+    ///
+    /// # Returns
+    /// The transactions dropped from the pool
+    pub async fn give_back(&self, pool: &TransactionPool, tx: &UserTransaction) -> Vec<UserTransaction> {
+        match self.release_nonce(&tx.from, tx.hash()).await {
+            Some(nonce) => pool.drop_from(&tx.from, nonce).await,
+            None => Vec::new(),
+        }
+    }
+
+    /// Undo a recorded change
+    ///
+    /// Reverses the difference between the change's before and after states,
+    /// so changes made on top of it since are kept.
+    async fn undo(&self, address: &Address, change: AccountChange, cause: Cause) {
+        self.load(address).await;
+
+        let mut accounts = self.write_shard(address).await;
+        let before = change.before.clone().unwrap_or(AccountState {
+            address: *address,
+            balance: U256::zero(),
+            nonce: 0,
+            ..Default::default()
+        });
+        let current = accounts.get(address).map(|account| account.state.clone());
+        let mut state = current.clone().unwrap_or(change.after.clone());
+        state.balance = if change.after.balance >= before.balance {
+            state.balance.saturating_sub(change.after.balance - before.balance)
+        } else {
+            state.balance.saturating_add(before.balance - change.after.balance)
+        };
+        state.nonce = if change.after.nonce >= before.nonce {
+            state.nonce.saturating_sub(change.after.nonce - before.nonce)
+        } else {
+            state.nonce.saturating_add(before.nonce - change.after.nonce)
+        };
+
+        // An account the change created, and that nothing else has touched since
+        if change.before.is_none() && state.balance.is_zero() && state.nonce == 0 {
+            accounts.remove(address);
+            if let Some(store) = &self.store {
+                store.remove_accounts([*address]).await;
+            }
+            self.trie.lock().unwrap().remove(address);
+            self.journal.record(cause.kind, cause.tx_hash, cause.batch_id, current.as_ref(), &state);
+            return;
+        }
+        self.write(&mut accounts, current.as_ref(), state, cause).await;
+    }
+
+    /// Get the amount an account has staked on L1 (zero if none)
+    pub async fn get_stake(&self, address: &Address) -> U256 {
+        self.stakes.read().await.get(address).copied().unwrap_or_default()
+    }
+
+    /// Record `amount` more staked on L1 by `address`
+    pub async fn add_stake(&self, address: Address, amount: U256) {
+        let mut stakes = self.stakes.write().await;
+        let stake = stakes.entry(address).or_default();
+        *stake = stake.saturating_add(amount);
+        if let Some(store) = &self.store {
+            store.put_stake(&address, *stake).await;
+        }
+    }
+
+    /// Record `amount` withdrawn from the L1 stake of `address`
+    ///
+    /// The stake never goes below zero.
+    pub async fn remove_stake(&self, address: &Address, amount: U256) {
+        let mut stakes = self.stakes.write().await;
+        if let Some(stake) = stakes.get_mut(address) {
+            *stake = stake.saturating_sub(amount);
+            if let Some(store) = &self.store {
+                store.put_stake(address, *stake).await;
+            }
+            if stake.is_zero() {
+                stakes.remove(address);
+            }
+        }
+    }
+
+    /// Latest sealed batch known to the cache
+    pub fn current_batch(&self) -> u64 {
+        self.current_batch.load(Ordering::Relaxed)
+    }
+
+    /// Record that a batch was sealed
+    ///
+    /// Account activity from now on is stamped with `batch_id`.
+    pub fn advance_batch(&self, batch_id: u64) {
+        let previous = self.current_batch.fetch_max(batch_id, Ordering::Relaxed);
+        if let Some(store) = &self.store
+            && batch_id > previous
+        {
+            store.set_current_batch(batch_id);
+        }
+    }
+
+    /// Read several accounts at one point in time, without changing anything
+    ///
+    /// Holds the read lock of every shard throughout, so no write lands
+    /// between two of the accounts or before the state root is taken, and
+    /// readers only wait for writers, never for each other. Accounts missing
+    /// from memory are read from the store or the archive without being
+    /// loaded or resurrected.
+    pub(super) async fn read_snapshot(&self, addresses: &[Address]) -> StateSnapshot {
+        let shards = self.read_all().await;
+        let mut read = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let account = match shards[self.shard_index(address)].get(address) {
+                Some(account) => {
+                    self.counters.lookup(Lookup::Hit);
+                    Some(account.state.clone())
+                }
+                None => self.read_cold(address).await,
+            };
+            read.push(account.unwrap_or_else(|| self.new_account(address)));
+        }
+        StateSnapshot {
+            batch_id: self.current_batch(),
+            state_root: self.state_root(),
+            accounts: read,
+        }
+    }
+
+    /// Prove the state of several accounts against the current state root
+    ///
+    /// Holds the read lock of every shard throughout, like
+    /// [`StateCache::read_snapshot`], so the accounts and proofs agree with
+    /// the root. Accounts missing from memory are read from the store or the
+    /// archive without being loaded or resurrected; unknown ones are proven
+    /// absent.
+    pub async fn witness(&self, addresses: &[Address]) -> StateWitness {
+        let shards = self.read_all().await;
+        let mut addresses = addresses.to_vec();
+        addresses.sort_unstable();
+        addresses.dedup();
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let account = match shards[self.shard_index(&address)].get(&address) {
+                Some(account) => {
+                    self.counters.lookup(Lookup::Hit);
+                    Some(account.state.clone())
+                }
+                None => self.read_cold(&address).await,
+            };
+            accounts.push((address, account));
+        }
+
+        let mut trie = self.trie.lock().unwrap();
+        StateWitness {
+            state_root: trie.root(),
+            accounts: accounts
+                .into_iter()
+                .map(|(address, account)| AccountWitness { address, proof: trie.prove(&address), account })
+                .collect(),
+        }
+    }
+
+    /// Read one account without changing anything, locking only its shard
+    pub(super) async fn read_account(&self, address: &Address) -> AccountState {
+        let account = self.read_shard(address).await.get(address).map(|account| account.state.clone());
+        let account = match account {
+            Some(account) => {
+                self.counters.lookup(Lookup::Hit);
+                Some(account)
+            }
+            None => self.read_cold(address).await,
+        };
+        account.unwrap_or_else(|| self.new_account(address))
+    }
+
+    /// Read an account missing from memory from the store or the archive
+    async fn read_cold(&self, address: &Address) -> Option<AccountState> {
+        if let Some(state) = self.store.as_ref().and_then(|store| store.account(address)) {
+            self.counters.lookup(Lookup::Store);
+            return Some(state);
+        }
+        let archived = match &self.archive {
+            Some(registry) => match registry.archived_account(*address).await {
+                Ok(account) => account,
+                Err(e) => {
+                    warn!("Failed to look up archived account {:?}: {:?}", address, e);
+                    None
+                }
+            },
+            None => None,
+        };
+        self.counters.lookup(if archived.is_some() { Lookup::Archive } else { Lookup::Absent });
+        archived
+    }
+
+    /// Accounts in memory last changed before batch `batch_id` was sealed
+    ///
+    /// Changes are stamped with the latest sealed batch when they are made,
+    /// so a change stamped below `batch_id` is part of batch `batch_id` at the
+    /// latest. Returns up to `limit` accounts with an address above `after`,
+    /// in address order.
+    pub async fn settled_accounts(&self, batch_id: u64, after: Option<Address>, limit: usize) -> Vec<AccountState> {
+        let mut settled = Vec::new();
+        for shard in self.accounts.iter() {
+            settled.extend(
+                shard
+                    .read()
+                    .await
+                    .values()
+                    .filter(|account| account.last_active_batch < batch_id)
+                    .filter(|account| after.is_none_or(|after| account.state.address > after))
+                    .map(|account| account.state.clone()),
+            );
+        }
+        settled.sort_unstable_by_key(|account| account.address);
+        settled.truncate(limit);
+        settled
+    }
+
+    /// Overwrite an account with its canonical state, unless it changed since `seen`
+    ///
+    /// Only the balance and nonce are overwritten; the code hash and storage
+    /// are kept.
+    ///
+    /// # Returns
+    /// `true` if the account was overwritten
+    pub async fn reconcile(&self, seen: &AccountState, canonical: AccountState) -> bool {
+        let mut accounts = self.write_shard(&seen.address).await;
+        let Some(current) = accounts.get(&seen.address).map(|account| account.state.clone()) else {
+            return false;
+        };
+        if current.balance != seen.balance || current.nonce != seen.nonce {
+            return false;
+        }
+        let canonical = AccountState {
+            code_hash: current.code_hash,
+            storage_root: current.storage_root,
+            ..canonical
+        };
+        self.write(&mut accounts, Some(&current), canonical, Cause {
+            kind: StateChangeKind::Reconciliation,
+            tx_hash: None,
+            batch_id: None,
+        })
+        .await;
+        true
+    }
+
+    /// Number of accounts held in memory
+    pub async fn hot_accounts(&self) -> usize {
+        let mut len = 0;
+        for shard in self.accounts.iter() {
+            len += shard.read().await.len();
+        }
+        len
+    }
+
+    /// Lookup and lock wait counters since startup, with account gauges
+    pub async fn metrics(&self) -> StateMetrics {
+        let hot_accounts = self.hot_accounts().await;
+        let contracts = self.storage.read().await.len();
+        let accounts = self.trie.lock().unwrap().len();
+        self.counters.snapshot(hot_accounts, accounts, contracts)
+    }
+
+    /// Root of the state trie, committing to every account
+    ///
+    /// Only the paths of accounts changed since the last call are rehashed.
+    pub fn state_root(&self) -> H256 {
+        self.trie.lock().unwrap().root()
+    }
+
+    /// Put the accounts held in the store and the archive back into the state trie
+    ///
+    /// Called once at startup, so the state root covers accounts written
+    /// before a restart that have not been referenced since.
+    ///
+    /// # Returns
+    /// The number of accounts restored
+    pub async fn restore(&self) -> anyhow::Result<usize> {
+        let mut restored = self.store.as_ref().map(|store| store.accounts()).unwrap_or_default();
+        if let Some(registry) = &self.archive {
+            restored.extend(registry.archived_accounts().await?);
+        }
+        let mut trie = self.trie.lock().unwrap();
+        for account in &restored {
+            trie.insert(account);
+        }
+        // Moves the restored subtrees out to the store, if there is one
+        trie.root();
+        Ok(restored.len())
+    }
+
+    /// Load a genesis allocation into an empty state
+    ///
+    /// # Returns
+    /// * `Ok(H256)` - The state root, which is the genesis' own
+    /// * `Err` if any account already exists
+    pub async fn load_genesis(&self, genesis: &Genesis) -> anyhow::Result<H256> {
+        if !self.trie.lock().unwrap().is_empty() {
+            anyhow::bail!("a genesis can only be loaded into an empty state");
+        }
+        for account in &genesis.accounts {
+            self.update(account.clone()).await;
+        }
+        Ok(self.state_root())
+    }
+
+    /// Collect the whole state after the latest sealed batch into a checkpoint
+    ///
+    /// Every account is included, hot, stored or archived, without loading or
+    /// resurrecting any. Nonces reserved by pending transactions are given
+    /// back in the checkpoint (not in the cache), so each is the nonce the
+    /// last sealed batch left. Holds the read lock of every shard throughout,
+    /// so no transaction is admitted meanwhile. The caller must make sure no
+    /// batch is being sealed (see `BatchOrchestrator::checkpoint`).
+    ///
+    /// The chain ID and genesis are left for the caller to fill in.
+    pub async fn export_checkpoint(&self) -> anyhow::Result<StateCheckpoint> {
+        let shards = self.read_all().await;
+        let mut accounts: BTreeMap<Address, AccountState> = BTreeMap::new();
+        if let Some(registry) = &self.archive {
+            accounts.extend(registry.archived_accounts().await?.into_iter().map(|account| (account.address, account)));
+        }
+        if let Some(store) = &self.store {
+            accounts.extend(store.accounts().into_iter().map(|account| (account.address, account)));
+        }
+        for shard in &shards {
+            accounts.extend(shard.values().map(|account| (account.state.address, account.state.clone())));
+        }
+
+        let mut trie = StateTrie::new();
+        for account in accounts.values_mut() {
+            if let Some(&(nonce, _)) = self.reservations.reserved(&account.address).first() {
+                account.nonce = account.nonce.min(nonce);
+            }
+            trie.insert(account);
+        }
+        let mut storage: Vec<ContractStorage> = self
+            .storage
+            .read()
+            .await
+            .iter()
+            .map(|(address, storage)| {
+                let mut slots: Vec<(H256, H256)> = storage.iter().collect();
+                slots.sort_unstable();
+                ContractStorage { address: *address, slots }
+            })
+            .collect();
+        storage.sort_unstable_by_key(|contract| contract.address);
+
+        Ok(StateCheckpoint {
+            batch_id: self.current_batch(),
+            state_root: trie.root(),
+            accounts: accounts.into_values().collect(),
+            storage,
+            ..Default::default()
+        })
+    }
+
+    /// Load a checkpoint into an empty state, continuing from its batch
+    ///
+    /// # Returns
+    /// * `Ok(H256)` - The state root, which is the checkpoint's own
+    /// * `Err` if any account already exists, or the checkpoint fails
+    ///   [`StateCheckpoint::verify`]
+    pub async fn import_checkpoint(&self, checkpoint: &StateCheckpoint) -> anyhow::Result<H256> {
+        if !self.trie.lock().unwrap().is_empty() {
+            anyhow::bail!("a checkpoint can only be imported into an empty state");
+        }
+        checkpoint.verify()?;
+
+        // Stamp the imported accounts as active at the checkpoint's batch
+        self.advance_batch(checkpoint.batch_id);
+        {
+            let mut storage = self.storage.write().await;
+            for contract in &checkpoint.storage {
+                let account_storage = storage.entry(contract.address).or_default();
+                for (slot, value) in &contract.slots {
+                    account_storage.set(*slot, *value);
+                }
+                if let Some(store) = &self.store {
+                    store.put_storage(&contract.address, &contract.slots).await;
+                }
+            }
+        }
+        for account in &checkpoint.accounts {
+            self.update(account.clone()).await;
+        }
+        Ok(self.state_root())
+    }
+
+    /// Move accounts idle for at least `inactive_batches` batches into the archive
+    ///
+    /// Contract accounts are never archived. Holds the write lock of every
+    /// shard while archiving, so no account can change between being archived
+    /// and being dropped from memory.
+    ///
+    /// # Returns
+    /// The number of accounts archived (always 0 without an archive)
+    pub async fn archive_inactive(&self, inactive_batches: u64) -> anyhow::Result<usize> {
+        let Some(registry) = &self.archive else {
+            return Ok(0);
+        };
+        let current = self.current_batch();
+
+        let mut shards = self.write_all().await;
+        let archived: Vec<ArchivedAccount> = shards
+            .iter()
+            .flat_map(|shard| shard.values())
+            .filter(|account| !account.state.is_contract())
+            .filter(|account| account.last_active_batch.saturating_add(inactive_batches) <= current)
+            .map(|account| ArchivedAccount {
+                state_hash: account.state.state_hash(),
+                account: account.state.clone(),
+                last_active_batch: account.last_active_batch,
+                archived_at_batch: current,
+            })
+            .collect();
+        if archived.is_empty() {
+            return Ok(0);
+        }
+
+        registry.archive_accounts(&archived).await?;
+        for entry in &archived {
+            let address = &entry.account.address;
+            shards[self.shard_index(address)].remove(address);
+        }
+        if let Some(store) = &self.store {
+            store.remove_accounts(archived.iter().map(|entry| entry.account.address)).await;
+        }
+        Ok(archived.len())
+    }
+
+    /// Look up an account, loading it from the store or resurrecting it from
+    /// the archive if needed
+    ///
+    /// # Returns
+    /// `None` if the account is neither in memory, stored nor archived
+    async fn load(&self, address: &Address) -> Option<AccountState> {
+        if let Some(account) = self.read_shard(address).await.get(address) {
+            account.last_access.store(self.access_clock.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+            self.counters.lookup(Lookup::Hit);
+            return Some(account.state.clone());
+        }
+        if self.store.is_none() && self.archive.is_none() {
+            self.counters.lookup(Lookup::Absent);
+            return None;
+        }
+
+        // Load under the write lock so concurrent lookups can't both miss
+        let mut accounts = self.write_shard(address).await;
+        if let Some(account) = accounts.get(address) {
+            self.counters.lookup(Lookup::Hit);
+            return Some(account.state.clone());
+        }
+        if let Some(state) = self.store.as_ref().and_then(|store| store.account(address)) {
+            self.counters.lookup(Lookup::Store);
+            accounts.insert(*address, self.hot(state.clone(), self.current_batch()));
+            self.evict(&mut accounts);
+            return Some(state);
+        }
+        let Some(registry) = self.archive.as_ref() else {
+            self.counters.lookup(Lookup::Absent);
+            return None;
+        };
+        let archived = match registry.take_archived_account(*address).await {
+            Ok(Some(archived)) => archived,
+            Ok(None) => {
+                self.counters.lookup(Lookup::Absent);
+                return None;
+            }
+            Err(e) => {
+                warn!("Failed to look up archived account {:?}: {:?}", address, e);
+                self.counters.lookup(Lookup::Absent);
+                return None;
+            }
+        };
+        self.counters.lookup(Lookup::Archive);
+
+        let current = self.current_batch();
+        let witness = ResurrectionWitness {
+            verified: archived.account.state_hash() == archived.state_hash,
+            account: archived.account.clone(),
+            archived_at_batch: archived.archived_at_batch,
+            resurrected_at_batch: current,
+            state_hash: archived.state_hash,
+        };
+        if !witness.verified {
+            error!("Archived state of {:?} does not match its commitment", address);
+        }
+        if let Err(e) = registry.record_resurrection(&witness).await {
+            error!("Failed to record resurrection witness for {:?}: {:?}", address, e);
+        }
+
+        if let Some(store) = &self.store {
+            store.put_account(&archived.account).await;
+        }
+        accounts.insert(*address, self.hot(archived.account.clone(), current));
+        self.evict(&mut accounts);
+        Some(archived.account)
+    }
+
+    /// Index of the shard holding `address`
+    fn shard_index(&self, address: &Address) -> usize {
+        // Addresses are hash-derived, so their low bytes spread accounts evenly
+        (address.to_low_u64_be() % self.accounts.len() as u64) as usize
+    }
+
+    /// Shard holding `address`
+    fn shard(&self, address: &Address) -> &Shard {
+        &self.accounts[self.shard_index(address)]
+    }
+
+    /// Read-lock the shard holding `address`, recording the wait
+    async fn read_shard(&self, address: &Address) -> RwLockReadGuard<'_, HashMap<Address, HotAccount>> {
+        let started = Instant::now();
+        let guard = self.shard(address).read().await;
+        self.counters.read_wait(started.elapsed());
+        guard
+    }
+
+    /// Write-lock the shard holding `address`, recording the wait
+    async fn write_shard(&self, address: &Address) -> RwLockWriteGuard<'_, HashMap<Address, HotAccount>> {
+        let started = Instant::now();
+        let guard = self.shard(address).write().await;
+        self.counters.write_wait(started.elapsed());
+        guard
+    }
+
+    /// Read-lock every shard, in shard order
+    async fn read_all(&self) -> Vec<RwLockReadGuard<'_, HashMap<Address, HotAccount>>> {
+        let started = Instant::now();
+        let mut guards = Vec::with_capacity(self.accounts.len());
+        for shard in self.accounts.iter() {
+            guards.push(shard.read().await);
+        }
+        self.counters.read_wait(started.elapsed());
+        guards
+    }
+
+    /// Write-lock every shard, in shard order
+    async fn write_all(&self) -> Vec<RwLockWriteGuard<'_, HashMap<Address, HotAccount>>> {
+        let started = Instant::now();
+        let mut guards = Vec::with_capacity(self.accounts.len());
+        for shard in self.accounts.iter() {
+            guards.push(shard.write().await);
+        }
+        self.counters.write_wait(started.elapsed());
+        guards
+    }
+}
+
+/// `count` empty shards
+fn new_shards(count: usize) -> Vec<Shard> {
+    (0..count).map(|_| RwLock::new(HashMap::new())).collect()
+}
+
+/// Take `amount` from an account, failing if the balance is short
+fn debit(state: &mut AccountState, amount: U256) -> Result<(), ValidationError> {
+    if state.balance < amount {
+        return Err(ValidationError::InsufficientBalance { required: amount, available: state.balance });
+    }
+    state.balance -= amount;
+    Ok(())
+}
+
+impl Default for StateCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,126 @@
+//! State Checkpoint Module
+//!
+//! A checkpoint is the full state at a batch boundary: every account, hot,
+//! stored or archived, with the storage slots of contract accounts and the
+//! state root they commit to. A new node imports one to start from that batch
+//! instead of replaying every batch from genesis.
+//!
+//! Nonces reserved by pending transactions are not part of the checkpoint:
+//! each sender's nonce is the one left by the last sealed batch. Stakes are
+//! not included either, as they are reported again by the L1 listener.
+//!
+//! Importing checks the checkpoint against its own state root, so a damaged
+//! or tampered file is refused.
+
+use super::{AccountStorage, StateTrie};
+use crate::{AccountState, GenesisRecord};
+use anyhow::Context;
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// The full state after a sealed batch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateCheckpoint {
+    /// Chain the state belongs to
+    pub chain_id: u64,
+    /// Genesis the chain started from, if one was recorded
+    #[serde(default)]
+    pub genesis: Option<GenesisRecord>,
+    /// Latest batch sealed into the state
+    pub batch_id: u64,
+    /// Root of the state trie over `accounts`
+    pub state_root: H256,
+    /// Every account, in address order
+    pub accounts: Vec<AccountState>,
+    /// Storage slots of contract accounts, in address order
+    #[serde(default)]
+    pub storage: Vec<ContractStorage>,
+}
+
+/// Non-zero storage slots of a contract account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractStorage {
+    pub address: Address,
+    /// Slots with their values, in slot order
+    pub slots: Vec<(H256, H256)>,
+}
+
+/// What an exported checkpoint holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointSummary {
+    pub batch_id: u64,
+    pub state_root: H256,
+    pub accounts: usize,
+}
+
+impl StateCheckpoint {
+    /// Write the checkpoint to `path` as JSON
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Read a checkpoint written by [`StateCheckpoint::write`]
+    ///
+    /// # Returns
+    /// * `Err` if the file can't be read or parsed, or fails [`StateCheckpoint::verify`]
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read(path).with_context(|| format!("reading checkpoint {}", path.display()))?;
+        let checkpoint: Self =
+            serde_json::from_slice(&content).with_context(|| format!("parsing checkpoint {}", path.display()))?;
+        checkpoint.verify().with_context(|| format!("verifying checkpoint {}", path.display()))?;
+        Ok(checkpoint)
+    }
+
+    /// Check that the accounts and storage commit to the checkpoint's state root
+    ///
+    /// # Returns
+    /// * `Err` if an account is listed twice, storage is given to an unknown
+    ///   account or doesn't match its storage root, or the state root differs
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let mut storage_roots: HashMap<Address, H256> = HashMap::new();
+        for contract in &self.storage {
+            let mut storage = AccountStorage::new();
+            for (slot, value) in &contract.slots {
+                storage.set(*slot, *value);
+            }
+            if storage_roots.insert(contract.address, storage.root()).is_some() {
+                anyhow::bail!("storage of {:?} is listed twice", contract.address);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut trie = StateTrie::new();
+        for account in &self.accounts {
+            if !seen.insert(account.address) {
+                anyhow::bail!("{:?} is listed twice", account.address);
+            }
+            let storage_root = storage_roots.remove(&account.address).unwrap_or_default();
+            if storage_root != account.storage_root {
+                anyhow::bail!("storage of {:?} does not match its storage root", account.address);
+            }
+            trie.insert(account);
+        }
+        if let Some(address) = storage_roots.keys().next() {
+            anyhow::bail!("storage is given to {:?}, which is not an account", address);
+        }
+
+        let state_root = trie.root();
+        if state_root != self.state_root {
+            anyhow::bail!("accounts commit to {:?}, not to the state root {:?}", state_root, self.state_root);
+        }
+        Ok(())
+    }
+
+    /// What the checkpoint holds
+    pub fn summary(&self) -> CheckpointSummary {
+        CheckpointSummary {
+            batch_id: self.batch_id,
+            state_root: self.state_root,
+            accounts: self.accounts.len(),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Genesis Module
+//!
+//! The allocation a chain starts from: a JSON file naming the chain ID and
+//! the accounts that exist before the first batch, with their balances and
+//! nonces. Balances are hex strings. Contract accounts can be given a
+//! `code_hash`, but not storage.
+//!
+//! ```json
+//! {
+//!   "chain_id": 31337,
+//!   "accounts": [
+//!     { "address": "0x...", "balance": "0xde0b6b3a7640000", "nonce": 0 }
+//!   ]
+//! }
+//! ```
+//!
+//! The genesis hash is `keccak256(chain_id || state_root)`, with the chain ID
+//! as 8 big-endian bytes and the state root of the allocation. It identifies
+//! the chain: two nodes agree on it exactly when they start from the same state.
+
+use super::StateTrie;
+use crate::AccountState;
+use anyhow::Context;
+use ethers::types::H256;
+use ethers::utils::keccak256;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// A chain's initial allocation
+#[derive(Debug, Clone, Deserialize)]
+pub struct Genesis {
+    pub chain_id: u64,
+    #[serde(default)]
+    pub accounts: Vec<AccountState>,
+}
+
+impl Genesis {
+    /// Reads a genesis file
+    ///
+    /// # Returns
+    /// * `Err` if the file can't be read or parsed, lists an account twice, or
+    ///   gives an account storage
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("reading genesis file {}", path))?;
+        let genesis: Genesis =
+            serde_json::from_str(&content).with_context(|| format!("parsing genesis file {}", path))?;
+        let mut seen = HashSet::new();
+        if let Some(account) = genesis.accounts.iter().find(|account| !seen.insert(account.address)) {
+            anyhow::bail!("genesis file {} lists {:?} twice", path, account.address);
+        }
+        if let Some(account) = genesis.accounts.iter().find(|account| !account.storage_root.is_zero()) {
+            anyhow::bail!("genesis file {} gives {:?} storage, which is not supported", path, account.address);
+        }
+        Ok(genesis)
+    }
+
+    /// Root of the state trie over the allocation
+    pub fn state_root(&self) -> H256 {
+        let mut trie = StateTrie::new();
+        for account in &self.accounts {
+            trie.insert(account);
+        }
+        trie.root()
+    }
+
+    /// Hash identifying the chain
+    pub fn hash(&self) -> H256 {
+        let mut data = Vec::with_capacity(40);
+        data.extend_from_slice(&self.chain_id.to_be_bytes());
+        data.extend_from_slice(self.state_root().as_bytes());
+        H256::from(keccak256(data))
+    }
+}
@@ -0,0 +1,92 @@
+//! State Journal Module
+//!
+//! Records every change the state cache makes to an account (nonce bumps and
+//! balance moves) with the transaction and batch it was made for. The journal
+//! shows how any balance was reached. Each change carries the account state
+//! before and after, so replaying changes in order on top of an earlier state
+//! reproduces a later one.
+//!
+//! The journal is held in memory and keeps the most recent changes only.
+
+use crate::{AccountState, StateChange, StateChangeKind};
+use ethers::types::{Address, H256};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// State changes recorded by the state cache, oldest first
+pub struct StateJournal {
+    inner: Mutex<Inner>,
+    /// Most recent changes kept (journaling disabled if 0)
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    changes: VecDeque<StateChange>,
+    /// Sequence number of the next change
+    next_seq: u64,
+}
+
+impl StateJournal {
+    /// Creates an empty journal keeping the last `capacity` changes
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            capacity,
+        }
+    }
+
+    /// Record a change from `before` (`None` if the account was created) to `after`
+    pub fn record(
+        &self,
+        kind: StateChangeKind,
+        tx_hash: Option<H256>,
+        batch_id: Option<u64>,
+        before: Option<&AccountState>,
+        after: &AccountState,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let change = StateChange {
+            seq: inner.next_seq,
+            address: after.address,
+            kind,
+            tx_hash,
+            batch_id,
+            created: before.is_none(),
+            balance_before: before.map(|before| before.balance).unwrap_or_default(),
+            balance_after: after.balance,
+            nonce_before: before.map(|before| before.nonce).unwrap_or_default(),
+            nonce_after: after.nonce,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        inner.next_seq += 1;
+        if inner.changes.len() >= self.capacity {
+            inner.changes.pop_front();
+        }
+        inner.changes.push_back(change);
+    }
+
+    /// Changes made for a transaction, oldest first
+    pub fn for_transaction(&self, tx_hash: H256) -> Vec<StateChange> {
+        self.filter(|change| change.tx_hash == Some(tx_hash))
+    }
+
+    /// Changes made while sealing or rolling back a batch, oldest first
+    pub fn for_batch(&self, batch_id: u64) -> Vec<StateChange> {
+        self.filter(|change| change.batch_id == Some(batch_id))
+    }
+
+    /// Changes made to an account, oldest first
+    pub fn for_account(&self, address: Address) -> Vec<StateChange> {
+        self.filter(|change| change.address == address)
+    }
+
+    fn filter(&self, keep: impl Fn(&StateChange) -> bool) -> Vec<StateChange> {
+        let inner = self.inner.lock().unwrap();
+        inner.changes.iter().filter(|change| keep(change)).cloned().collect()
+    }
+}
@@ -0,0 +1,185 @@
+//! State Cache Metrics Module
+//!
+//! Counts how account lookups are served (from memory, the store or the
+//! archive, or not at all) and how long callers wait for shard locks, and
+//! gauges how many accounts the cache holds. Rendered in the Prometheus text
+//! exposition format for the `/metrics` endpoint, so operators can size
+//! `state.max_hot_accounts` and `state.shards`.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the lock wait histogram buckets, in seconds
+pub const LOCK_WAIT_BUCKETS: [f64; 7] = [0.000_01, 0.000_1, 0.001, 0.01, 0.1, 1.0, 10.0];
+
+/// Where an account lookup was served from
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Lookup {
+    /// Held in memory
+    Hit,
+    /// Read back from the store
+    Store,
+    /// Read from (or resurrected out of) the archive
+    Archive,
+    /// Unknown account
+    Absent,
+}
+
+/// Counters updated by the state cache
+#[derive(Default)]
+pub(super) struct CacheCounters {
+    hits: AtomicU64,
+    store: AtomicU64,
+    archive: AtomicU64,
+    absent: AtomicU64,
+    read_waits: LockWaits,
+    write_waits: LockWaits,
+}
+
+impl CacheCounters {
+    /// Count a lookup
+    pub(super) fn lookup(&self, lookup: Lookup) {
+        let counter = match lookup {
+            Lookup::Hit => &self.hits,
+            Lookup::Store => &self.store,
+            Lookup::Archive => &self.archive,
+            Lookup::Absent => &self.absent,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record the time spent waiting for a shard's read lock
+    pub(super) fn read_wait(&self, wait: Duration) {
+        self.read_waits.observe(wait);
+    }
+
+    /// Record the time spent waiting for a shard's write lock
+    pub(super) fn write_wait(&self, wait: Duration) {
+        self.write_waits.observe(wait);
+    }
+
+    /// Read the counters, alongside the account gauges
+    pub(super) fn snapshot(&self, hot_accounts: usize, accounts: usize, contracts: usize) -> StateMetrics {
+        StateMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            store_loads: self.store.load(Ordering::Relaxed),
+            archive_loads: self.archive.load(Ordering::Relaxed),
+            absent: self.absent.load(Ordering::Relaxed),
+            read_lock_waits: self.read_waits.snapshot(),
+            write_lock_waits: self.write_waits.snapshot(),
+            hot_accounts,
+            accounts,
+            contracts,
+        }
+    }
+}
+
+/// Lock wait histogram, over [`LOCK_WAIT_BUCKETS`] and beyond
+#[derive(Default)]
+struct LockWaits {
+    /// Waits per bucket (not cumulative), the last one past every bound
+    buckets: [AtomicU64; LOCK_WAIT_BUCKETS.len() + 1],
+    /// Total wait, in nanoseconds
+    sum_ns: AtomicU64,
+}
+
+impl LockWaits {
+    fn observe(&self, wait: Duration) {
+        let secs = wait.as_secs_f64();
+        let bucket = LOCK_WAIT_BUCKETS.iter().position(|&bound| secs <= bound).unwrap_or(LOCK_WAIT_BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_ns.fetch_add(wait.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> LockWaitHistogram {
+        let mut cumulative = 0;
+        let mut buckets = Vec::with_capacity(LOCK_WAIT_BUCKETS.len());
+        for (bound, count) in LOCK_WAIT_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += count.load(Ordering::Relaxed);
+            buckets.push((*bound, cumulative));
+        }
+        LockWaitHistogram {
+            buckets,
+            count: cumulative + self.buckets[LOCK_WAIT_BUCKETS.len()].load(Ordering::Relaxed),
+            sum_secs: self.sum_ns.load(Ordering::Relaxed) as f64 / 1e9,
+        }
+    }
+}
+
+/// Lock waits observed since startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockWaitHistogram {
+    /// `(upper bound in seconds, waits at most that long)`, cumulative
+    pub buckets: Vec<(f64, u64)>,
+    /// Waits observed
+    pub count: u64,
+    /// Total time waited, in seconds
+    pub sum_secs: f64,
+}
+
+/// Snapshot of the state cache metrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMetrics {
+    /// Lookups served from memory
+    pub hits: u64,
+    /// Lookups read back from the store
+    pub store_loads: u64,
+    /// Lookups read from the archive
+    pub archive_loads: u64,
+    /// Lookups of unknown accounts
+    pub absent: u64,
+    /// Waits for shard read locks
+    pub read_lock_waits: LockWaitHistogram,
+    /// Waits for shard write locks
+    pub write_lock_waits: LockWaitHistogram,
+    /// Accounts held in memory
+    pub hot_accounts: usize,
+    /// Accounts in the state trie, hot or not
+    pub accounts: usize,
+    /// Contract accounts with storage
+    pub contracts: usize,
+}
+
+impl StateMetrics {
+    /// Render the snapshot as Prometheus counters, histograms and gauges
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# HELP sequencer_state_lookups_total Account lookups, by where they were served from");
+        let _ = writeln!(out, "# TYPE sequencer_state_lookups_total counter");
+        for (result, count) in [
+            ("hit", self.hits),
+            ("store", self.store_loads),
+            ("archive", self.archive_loads),
+            ("absent", self.absent),
+        ] {
+            let _ = writeln!(out, "sequencer_state_lookups_total{{result=\"{}\"}} {}", result, count);
+        }
+
+        let _ = writeln!(out, "# HELP sequencer_state_lock_wait_seconds Time spent waiting for state cache shard locks");
+        let _ = writeln!(out, "# TYPE sequencer_state_lock_wait_seconds histogram");
+        for (mode, waits) in [("read", &self.read_lock_waits), ("write", &self.write_lock_waits)] {
+            for (bound, count) in &waits.buckets {
+                let _ = writeln!(out, "sequencer_state_lock_wait_seconds_bucket{{mode=\"{}\",le=\"{}\"}} {}", mode, bound, count);
+            }
+            let _ = writeln!(out, "sequencer_state_lock_wait_seconds_bucket{{mode=\"{}\",le=\"+Inf\"}} {}", mode, waits.count);
+            let _ = writeln!(out, "sequencer_state_lock_wait_seconds_sum{{mode=\"{}\"}} {}", mode, waits.sum_secs);
+            let _ = writeln!(out, "sequencer_state_lock_wait_seconds_count{{mode=\"{}\"}} {}", mode, waits.count);
+        }
+
+        let _ = writeln!(out, "# HELP sequencer_state_hot_accounts Accounts held in memory");
+        let _ = writeln!(out, "# TYPE sequencer_state_hot_accounts gauge");
+        let _ = writeln!(out, "sequencer_state_hot_accounts {}", self.hot_accounts);
+
+        let _ = writeln!(out, "# HELP sequencer_state_accounts Accounts in the state, in memory or not");
+        let _ = writeln!(out, "# TYPE sequencer_state_accounts gauge");
+        let _ = writeln!(out, "sequencer_state_accounts {}", self.accounts);
+
+        let _ = writeln!(out, "# HELP sequencer_state_contract_accounts Contract accounts with storage");
+        let _ = writeln!(out, "# TYPE sequencer_state_contract_accounts gauge");
+        let _ = writeln!(out, "sequencer_state_contract_accounts {}", self.contracts);
+
+        out
+    }
+}
@@ -1,7 +1,47 @@
 //! State Management Module
 //! 
 //! This module provides in-memory caching of account state for fast transaction validation.
-//! The state cache stores account balances and nonces.
+//! The state cache stores account balances and nonces, and can archive
+//! long-inactive accounts into the registry. It can also write through to a
+//! persistent store, so state survives restarts and memory can be bounded. A
+//! sparse Merkle trie over every account provides the state root committed to by batches,
+//! and a journal records recent changes with the transaction and batch behind them.
+//! Nonces are reserved at admission and given back if the transaction is dropped.
+//! A new chain can start from a genesis allocation, and the cached state can
+//! be reconciled against the executor's canonical state. RPC reads go through a
+//! separate read-only view. Contract accounts also have a code hash and
+//! storage slots, committed to by a storage trie per account. The whole state
+//! at a batch boundary can be exported to a checkpoint, which a new node
+//! imports instead of replaying every batch. Each sealed batch gets an
+//! execution witness, proving the state of every account it touches. Cache
+//! hits and misses, shard lock waits and account counts are exported as metrics.
 
 mod cache;
-pub use cache::StateCache;
\ No newline at end of file
+mod checkpoint;
+mod genesis;
+mod journal;
+mod metrics;
+mod reader;
+mod reconcile;
+mod reservations;
+mod storage;
+mod store;
+mod trie;
+mod witness;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub use cache::StateCache;
+pub use checkpoint::{CheckpointSummary, ContractStorage, StateCheckpoint};
+pub use genesis::Genesis;
+pub use journal::StateJournal;
+pub use metrics::{LockWaitHistogram, StateMetrics, LOCK_WAIT_BUCKETS};
+pub use reader::StateReader;
+pub use reconcile::{ReconciliationReport, StateReconciler};
+pub use reservations::NonceReservations;
+pub use storage::AccountStorage;
+pub use store::StateStore;
+pub use trie::{ProofLeaf, StateProof, StateTrie, EMPTY_STATE_ROOT};
+pub use witness::{AccountWitness, BatchWitness, StateWitness};
@@ -0,0 +1,38 @@
+//! State Reader Module
+//!
+//! The read path for RPC handlers. Balance and nonce queries go through a
+//! [`StateReader`] instead of the validator's [`StateCache`] methods: reads
+//! never take the write lock, never load or resurrect accounts, and several
+//! accounts can be read in one consistent snapshot.
+
+use super::StateCache;
+use crate::{AccountState, StateSnapshot};
+use ethers::types::{Address, H256};
+
+/// Read-only view of the state, cheaply cloneable
+#[derive(Clone)]
+pub struct StateReader {
+    state_cache: StateCache,
+}
+
+impl StateReader {
+    /// Creates a reader over `state_cache`
+    pub fn new(state_cache: StateCache) -> Self {
+        Self { state_cache }
+    }
+
+    /// Read one account (unknown accounts read as they would be created)
+    pub async fn account(&self, address: &Address) -> AccountState {
+        self.state_cache.read_account(address).await
+    }
+
+    /// Read several accounts at one point in time, with the batch and state root they reflect
+    pub async fn snapshot(&self, addresses: &[Address]) -> StateSnapshot {
+        self.state_cache.read_snapshot(addresses).await
+    }
+
+    /// Read a storage slot of an account (zero if never set)
+    pub async fn storage_at(&self, address: &Address, slot: &H256) -> H256 {
+        self.state_cache.storage_at(address, slot).await
+    }
+}
@@ -0,0 +1,202 @@
+//! State Reconciliation Module
+//!
+//! Periodically compares the balances and nonces held by the state cache
+//! against the executor's canonical state after the latest executed batch,
+//! and repairs drift.
+//!
+//! Only settled accounts are compared: ones not changed since before that
+//! batch was sealed, with nothing pending in the pool or the user-operation
+//! lane. Their cached state must then equal the canonical one. A batch being
+//! sealed can still make an account look drifted for a moment, so a
+//! discrepancy is only repaired once seen again in the next round, with the
+//! cached state unchanged in between.
+
+use super::StateCache;
+use crate::{
+    config::ReconciliationConfig,
+    pool::{BundlePool, TransactionPool, UserOpPool},
+    AccountState,
+};
+use anyhow::Context;
+use ethers::types::{Address, U256, U64};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Outcome of a reconciliation round
+#[derive(Debug, Default)]
+pub struct ReconciliationReport {
+    /// Latest batch the executor had executed
+    pub executed_batch: u64,
+    /// Accounts compared
+    pub checked: usize,
+    /// Accounts found to differ from the canonical state
+    pub drifted: usize,
+    /// Drifted accounts overwritten with the canonical state
+    pub repaired: usize,
+}
+
+/// Periodic comparison of the cached state against the executor's
+pub struct StateReconciler {
+    state_cache: StateCache,
+    tx_pool: Arc<TransactionPool>,
+    user_ops: Option<Arc<UserOpPool>>,
+    bundles: Option<Arc<BundlePool>>,
+    client: reqwest::Client,
+    config: ReconciliationConfig,
+    /// Last address compared; the next round continues after it
+    cursor: Option<Address>,
+    /// Accounts found drifted last round, with the cached state seen then
+    suspects: HashMap<Address, AccountState>,
+}
+
+impl StateReconciler {
+    /// Creates a reconciler for the configured executor
+    pub fn new(
+        state_cache: StateCache,
+        tx_pool: Arc<TransactionPool>,
+        config: ReconciliationConfig,
+    ) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+        Ok(Self {
+            state_cache,
+            tx_pool,
+            user_ops: None,
+            bundles: None,
+            client,
+            config,
+            cursor: None,
+            suspects: HashMap::new(),
+        })
+    }
+
+    /// Also skip accounts with operations pending in the user-operation lane
+    pub fn with_user_ops(mut self, user_ops: Arc<UserOpPool>) -> Self {
+        self.user_ops = Some(user_ops);
+        self
+    }
+
+    /// Also skip accounts with transactions pending in the bundle lane
+    pub fn with_bundles(mut self, bundles: Arc<BundlePool>) -> Self {
+        self.bundles = Some(bundles);
+        self
+    }
+
+    /// Run reconciliation rounds forever, every `interval_secs`
+    pub async fn start(mut self) {
+        info!("Reconciling state against the executor at {}", self.config.executor_url);
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            match self.reconcile_once().await {
+                Ok(report) if report.drifted > 0 => warn!(
+                    "State reconciliation at batch #{}: {} of {} accounts drifted, {} repaired",
+                    report.executed_batch, report.drifted, report.checked, report.repaired
+                ),
+                Ok(report) => debug!("State reconciliation at batch #{}: {} accounts match",
+                                     report.executed_batch, report.checked),
+                Err(e) => warn!("State reconciliation failed: {:?}", e),
+            }
+        }
+    }
+
+    /// Compare the next `accounts_per_round` settled accounts, and last round's suspects
+    pub async fn reconcile_once(&mut self) -> anyhow::Result<ReconciliationReport> {
+        let executed: U64 = self.call("eth_blockNumber", json!([])).await?;
+        let executed_batch = executed.as_u64();
+        let mut report = ReconciliationReport { executed_batch, ..Default::default() };
+
+        let mut accounts = self
+            .state_cache
+            .settled_accounts(executed_batch, self.cursor, self.config.accounts_per_round)
+            .await;
+        // Wrap around once every account was visited
+        self.cursor = accounts.last().map(|account| account.address);
+        let suspects = std::mem::take(&mut self.suspects);
+        if !suspects.is_empty() {
+            accounts.retain(|account| !suspects.contains_key(&account.address));
+            let settled = self.state_cache.settled_accounts(executed_batch, None, usize::MAX).await;
+            accounts.extend(settled.into_iter().filter(|account| suspects.contains_key(&account.address)));
+        }
+
+        for cached in accounts {
+            if self.is_pending(&cached.address).await {
+                continue;
+            }
+            report.checked += 1;
+            let canonical = self.canonical_account(cached.address, executed_batch).await?;
+            if canonical.balance == cached.balance && canonical.nonce == cached.nonce {
+                continue;
+            }
+            report.drifted += 1;
+            warn!("State drift for {:?} at batch #{}: cached balance {} nonce {}, canonical balance {} nonce {}",
+                  cached.address, executed_batch, cached.balance, cached.nonce, canonical.balance, canonical.nonce);
+
+            // Repair only what was seen drifted, unchanged, in two rounds
+            let confirmed = suspects
+                .get(&cached.address)
+                .is_some_and(|seen| seen.balance == cached.balance && seen.nonce == cached.nonce);
+            if !confirmed {
+                self.suspects.insert(cached.address, cached);
+            } else if self.config.repair && self.state_cache.reconcile(&cached, canonical).await {
+                info!("Repaired the state of {:?} from the executor", cached.address);
+                report.repaired += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Whether the account has transactions or operations waiting for a batch
+    async fn is_pending(&self, address: &Address) -> bool {
+        if !self.tx_pool.pending_nonces(address).await.is_empty() {
+            return true;
+        }
+        if let Some(user_ops) = &self.user_ops
+            && user_ops.has_pending(address).await
+        {
+            return true;
+        }
+        match &self.bundles {
+            Some(bundles) => bundles.has_pending(address).await,
+            None => false,
+        }
+    }
+
+    /// The executor's state of an account after batch `batch_id`
+    async fn canonical_account(&self, address: Address, batch_id: u64) -> anyhow::Result<AccountState> {
+        let block = format!("{:#x}", batch_id);
+        let balance: U256 = self.call("eth_getBalance", json!([address, block])).await?;
+        let nonce: U64 = self.call("eth_getTransactionCount", json!([address, block])).await?;
+        Ok(AccountState { address, balance, nonce: nonce.as_u64(), ..Default::default() })
+    }
+
+    /// Make a JSON-RPC call to the executor and decode its result
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> anyhow::Result<T> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1,
+        });
+        let response: Value = self
+            .client
+            .post(&self.config.executor_url)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("calling {} on the executor", method))?
+            .json()
+            .await
+            .with_context(|| format!("decoding the executor's {} response", method))?;
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("executor refused {}: {}", method, error);
+        }
+        let result = response.get("result").cloned().unwrap_or_default();
+        serde_json::from_value(result).with_context(|| format!("decoding the executor's {} result", method))
+    }
+}
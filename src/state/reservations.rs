@@ -0,0 +1,79 @@
+//! Nonce Reservations Module
+//!
+//! A sender's nonce moves when a transaction is admitted, not when it is
+//! sealed, so the next transaction can be queued behind it. The nonce is
+//! only reserved for the transaction until then: it is committed once the
+//! transaction is sealed into a batch, and released if the transaction is
+//! dropped from the pool instead (expired or evicted), so the sender can
+//! reuse it.
+//!
+//! Releasing a nonce also releases every later one of the sender: those
+//! transactions can't execute past the gap, so they are dropped as well.
+//!
+//! Reservations are held in memory. Pending transactions recovered from the
+//! pool's write-ahead log are reserved again at startup.
+
+use ethers::types::{Address, H256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Nonces reserved by pending transactions, by sender
+#[derive(Default)]
+pub struct NonceReservations {
+    reserved: Mutex<HashMap<Address, BTreeMap<u64, H256>>>,
+}
+
+impl NonceReservations {
+    /// Creates an empty set of reservations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve `nonce` of `sender` for a pending transaction
+    pub fn reserve(&self, sender: Address, nonce: u64, tx_hash: H256) {
+        self.reserved.lock().unwrap().entry(sender).or_default().insert(nonce, tx_hash);
+    }
+
+    /// Commit the nonce reserved by a transaction sealed into a batch
+    ///
+    /// # Returns
+    /// `true` if the transaction held a reservation
+    pub fn commit(&self, sender: &Address, tx_hash: H256) -> bool {
+        let mut reserved = self.reserved.lock().unwrap();
+        let Some(nonces) = reserved.get_mut(sender) else {
+            return false;
+        };
+        let Some(nonce) = nonces.iter().find_map(|(&nonce, &hash)| (hash == tx_hash).then_some(nonce)) else {
+            return false;
+        };
+        nonces.remove(&nonce);
+        if nonces.is_empty() {
+            reserved.remove(sender);
+        }
+        true
+    }
+
+    /// Release the nonce reserved by a dropped transaction, and every later one
+    ///
+    /// # Returns
+    /// The released nonce (`None` if the transaction held no reservation)
+    pub(super) fn release(&self, sender: &Address, tx_hash: H256) -> Option<u64> {
+        let mut reserved = self.reserved.lock().unwrap();
+        let nonces = reserved.get_mut(sender)?;
+        let nonce = nonces.iter().find_map(|(&nonce, &hash)| (hash == tx_hash).then_some(nonce))?;
+        nonces.split_off(&nonce);
+        if nonces.is_empty() {
+            reserved.remove(sender);
+        }
+        Some(nonce)
+    }
+
+    /// Nonces reserved by `sender`'s pending transactions, lowest first
+    pub fn reserved(&self, sender: &Address) -> Vec<(u64, H256)> {
+        let reserved = self.reserved.lock().unwrap();
+        reserved
+            .get(sender)
+            .map(|nonces| nonces.iter().map(|(&nonce, &hash)| (nonce, hash)).collect())
+            .unwrap_or_default()
+    }
+}
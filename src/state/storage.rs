@@ -0,0 +1,64 @@
+//! Contract Storage Module
+//!
+//! The storage of a contract account: a map from 32-byte slots to 32-byte
+//! values, committed to by a storage trie whose root is the account's
+//! `storage_root`. The trie has the same shape as the state trie, with each
+//! slot at the path given by `keccak256(slot)` and its leaf committing to the
+//! value. Zero values are not stored, so an account without storage has a
+//! zero root.
+
+use super::StateTrie;
+use ethers::types::H256;
+use ethers::utils::keccak256;
+use std::collections::HashMap;
+
+/// Storage slots of one account, with their trie
+#[derive(Default)]
+pub struct AccountStorage {
+    slots: HashMap<H256, H256>,
+    trie: StateTrie,
+}
+
+impl AccountStorage {
+    /// Creates empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Value of a slot (zero if never set)
+    pub fn get(&self, slot: &H256) -> H256 {
+        self.slots.get(slot).copied().unwrap_or_default()
+    }
+
+    /// Set a slot, clearing it if `value` is zero
+    pub fn set(&mut self, slot: H256, value: H256) {
+        let key = H256::from(keccak256(slot.as_bytes()));
+        if value.is_zero() {
+            self.slots.remove(&slot);
+            self.trie.remove_key(key);
+        } else {
+            self.slots.insert(slot, value);
+            self.trie.insert_key(key, value);
+        }
+    }
+
+    /// Non-zero slots with their values, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (H256, H256)> + '_ {
+        self.slots.iter().map(|(&slot, &value)| (slot, value))
+    }
+
+    /// Root of the storage trie
+    pub fn root(&mut self) -> H256 {
+        self.trie.root()
+    }
+
+    /// Number of non-zero slots
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether every slot is zero
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
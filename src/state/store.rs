@@ -0,0 +1,279 @@
+//! Persistent State Store Module
+//!
+//! An embedded key-value store (sled) holding account balances, nonces and
+//! L1 stakes on disk, so they survive restarts. The `StateCache` writes
+//! through to it and falls back to it on a miss; the store is never read on
+//! the hot path for accounts already in memory.
+//!
+//! Values are fixed-size big-endian encodings, keyed by address:
+//! - `accounts`: balance (32 bytes) followed by nonce (8 bytes), then code
+//!   hash and storage root (32 bytes each) for contract accounts only
+//! - `stakes`: staked amount (32 bytes)
+//! - `storage`: slot value (32 bytes), keyed by address followed by slot
+//! - `trie_nodes`: state trie nodes moved out of memory, keyed by their hash
+//!   (see `StateTrie::with_store`); rebuilt from the accounts at startup
+//!
+//! Writes reach sled's log immediately; when they are flushed to disk is up
+//! to the configured [`FlushPolicy`].
+
+use crate::{config::{FlushPolicy, StateStoreConfig}, AccountState};
+use anyhow::{Context, Result};
+use ethers::types::{Address, H256, U256};
+use tracing::error;
+
+/// Key of the latest sealed batch in the metadata tree
+const CURRENT_BATCH_KEY: &[u8] = b"current_batch";
+
+/// Persistent account state, cheaply cloneable
+#[derive(Clone)]
+pub struct StateStore {
+    db: sled::Db,
+    accounts: sled::Tree,
+    stakes: sled::Tree,
+    storage: sled::Tree,
+    trie_nodes: sled::Tree,
+    meta: sled::Tree,
+    flush: FlushPolicy,
+}
+
+impl StateStore {
+    /// Opens (or creates) the store at `config.path`
+    pub fn open(config: &StateStoreConfig) -> Result<Self> {
+        let mut sled_config = sled::Config::new().path(&config.path);
+        sled_config = match config.flush {
+            FlushPolicy::EveryWrite => sled_config.flush_every_ms(None),
+            FlushPolicy::Interval => sled_config.flush_every_ms(Some(config.flush_interval_ms)),
+        };
+        let db = sled_config.open().with_context(|| format!("opening state store at {}", config.path))?;
+        Ok(Self {
+            accounts: db.open_tree("accounts")?,
+            stakes: db.open_tree("stakes")?,
+            storage: db.open_tree("storage")?,
+            trie_nodes: db.open_tree("trie_nodes")?,
+            meta: db.open_tree("meta")?,
+            db,
+            flush: config.flush,
+        })
+    }
+
+    /// Look up an account
+    pub fn account(&self, address: &Address) -> Option<AccountState> {
+        match self.accounts.get(address.as_bytes()) {
+            Ok(value) => value.and_then(|value| decode_account(*address, &value)),
+            Err(e) => {
+                error!("Failed to read account {:?} from the state store: {:?}", address, e);
+                None
+            }
+        }
+    }
+
+    /// Write an account
+    pub async fn put_account(&self, account: &AccountState) {
+        let mut value = [0u8; 104];
+        account.balance.to_big_endian(&mut value[..32]);
+        value[32..40].copy_from_slice(&account.nonce.to_be_bytes());
+        let len = if account.is_contract() {
+            value[40..72].copy_from_slice(account.code_hash.as_bytes());
+            value[72..].copy_from_slice(account.storage_root.as_bytes());
+            104
+        } else {
+            40
+        };
+        if let Err(e) = self.accounts.insert(account.address.as_bytes(), &value[..len]) {
+            error!("Failed to write account {:?} to the state store: {:?}", account.address, e);
+        }
+        self.flush_write().await;
+    }
+
+    /// Every stored account
+    pub fn accounts(&self) -> Vec<AccountState> {
+        self.accounts
+            .iter()
+            .filter_map(|entry| match entry {
+                Ok((key, value)) if key.len() == 20 => decode_account(Address::from_slice(&key), &value),
+                Ok(_) => None,
+                Err(e) => {
+                    error!("Failed to read accounts from the state store: {:?}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Remove accounts (moved to the archive)
+    pub async fn remove_accounts(&self, addresses: impl IntoIterator<Item = Address>) {
+        let mut batch = sled::Batch::default();
+        for address in addresses {
+            batch.remove(address.as_bytes());
+        }
+        if let Err(e) = self.accounts.apply_batch(batch) {
+            error!("Failed to remove archived accounts from the state store: {:?}", e);
+        }
+        self.flush_write().await;
+    }
+
+    /// Every stored storage slot, with the account it belongs to
+    pub fn storage(&self) -> Vec<(Address, H256, H256)> {
+        self.storage
+            .iter()
+            .filter_map(|entry| match entry {
+                Ok((key, value)) if key.len() == 52 && value.len() == 32 => {
+                    Some((Address::from_slice(&key[..20]), H256::from_slice(&key[20..]), H256::from_slice(&value)))
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    error!("Failed to read storage from the state store: {:?}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Write storage slots of an account (removed when zero)
+    pub async fn put_storage(&self, address: &Address, slots: &[(H256, H256)]) {
+        let mut batch = sled::Batch::default();
+        for (slot, value) in slots {
+            let key = [address.as_bytes(), slot.as_bytes()].concat();
+            if value.is_zero() {
+                batch.remove(key);
+            } else {
+                batch.insert(key, value.as_bytes());
+            }
+        }
+        if let Err(e) = self.storage.apply_batch(batch) {
+            error!("Failed to write the storage of {:?} to the state store: {:?}", address, e);
+        }
+        self.flush_write().await;
+    }
+
+    /// Every recorded L1 stake
+    pub fn stakes(&self) -> Vec<(Address, U256)> {
+        self.stakes
+            .iter()
+            .filter_map(|entry| match entry {
+                Ok((key, value)) if key.len() == 20 && value.len() == 32 => {
+                    Some((Address::from_slice(&key), U256::from_big_endian(&value)))
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    error!("Failed to read stakes from the state store: {:?}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Write a stake (removed when zero)
+    pub async fn put_stake(&self, address: &Address, stake: U256) {
+        let result = if stake.is_zero() {
+            self.stakes.remove(address.as_bytes()).map(|_| ())
+        } else {
+            let mut value = [0u8; 32];
+            stake.to_big_endian(&mut value);
+            self.stakes.insert(address.as_bytes(), &value[..]).map(|_| ())
+        };
+        if let Err(e) = result {
+            error!("Failed to write the stake of {:?} to the state store: {:?}", address, e);
+        }
+        self.flush_write().await;
+    }
+
+    /// Look up a state trie node by its hash
+    pub fn trie_node(&self, hash: &H256) -> Option<Vec<u8>> {
+        match self.trie_nodes.get(hash.as_bytes()) {
+            Ok(value) => value.map(|value| value.to_vec()),
+            Err(e) => {
+                error!("Failed to read trie node {:?} from the state store: {:?}", hash, e);
+                None
+            }
+        }
+    }
+
+    /// Write state trie nodes, keyed by their hash
+    ///
+    /// Not flushed: the nodes are rebuilt from the accounts after a restart.
+    pub fn put_trie_nodes(&self, nodes: impl IntoIterator<Item = (H256, Vec<u8>)>) {
+        let mut batch = sled::Batch::default();
+        for (hash, node) in nodes {
+            batch.insert(hash.as_bytes(), node);
+        }
+        if let Err(e) = self.trie_nodes.apply_batch(batch) {
+            error!("Failed to write trie nodes to the state store: {:?}", e);
+        }
+    }
+
+    /// Remove a state trie node that is being replaced
+    pub fn remove_trie_node(&self, hash: &H256) {
+        if let Err(e) = self.trie_nodes.remove(hash.as_bytes()) {
+            error!("Failed to remove trie node {:?} from the state store: {:?}", hash, e);
+        }
+    }
+
+    /// Remove every state trie node, before the trie is rebuilt
+    pub fn clear_trie_nodes(&self) {
+        if let Err(e) = self.trie_nodes.clear() {
+            error!("Failed to clear the trie nodes in the state store: {:?}", e);
+        }
+    }
+
+    /// Latest sealed batch recorded (0 if none)
+    pub fn current_batch(&self) -> u64 {
+        match self.meta.get(CURRENT_BATCH_KEY) {
+            Ok(Some(value)) if value.len() == 8 => u64::from_be_bytes(value.as_ref().try_into().unwrap()),
+            Ok(_) => 0,
+            Err(e) => {
+                error!("Failed to read the current batch from the state store: {:?}", e);
+                0
+            }
+        }
+    }
+
+    /// Record the latest sealed batch
+    ///
+    /// Not flushed on its own, even with [`FlushPolicy::EveryWrite`]: it only
+    /// stamps account activity, and the next account write flushes it.
+    pub fn set_current_batch(&self, batch_id: u64) {
+        if let Err(e) = self.meta.insert(CURRENT_BATCH_KEY, &batch_id.to_be_bytes()[..]) {
+            error!("Failed to write the current batch to the state store: {:?}", e);
+        }
+    }
+
+    /// Flush every write so far to disk
+    ///
+    /// Flushes on the blocking pool: sled's `flush_async` stalls once many
+    /// tasks flush at the same time, as writes to different state cache
+    /// shards do.
+    pub async fn flush(&self) -> Result<()> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || db.flush()).await??;
+        Ok(())
+    }
+
+    /// Flush after a write if the policy asks for it
+    async fn flush_write(&self) {
+        if self.flush == FlushPolicy::EveryWrite
+            && let Err(e) = self.flush().await
+        {
+            error!("Failed to flush the state store: {:?}", e);
+        }
+    }
+}
+
+/// Decode an `accounts` value
+fn decode_account(address: Address, value: &[u8]) -> Option<AccountState> {
+    if value.len() != 40 && value.len() != 104 {
+        error!("Corrupt state store entry for {:?}", address);
+        return None;
+    }
+    let mut account = AccountState {
+        address,
+        balance: U256::from_big_endian(&value[..32]),
+        nonce: u64::from_be_bytes(value[32..40].try_into().unwrap()),
+        ..Default::default()
+    };
+    if value.len() == 104 {
+        account.code_hash = H256::from_slice(&value[40..72]);
+        account.storage_root = H256::from_slice(&value[72..]);
+    }
+    Some(account)
+}
@@ -0,0 +1,690 @@
+//! Tests for the state cache
+//! 
+//! Verifies:
+//! - Archival of inactive accounts and their transparent resurrection
+//! - Persistence across restarts, and LRU eviction to the store
+//! - The state root and its proofs, and the state trie moving subtrees out
+//!   to the store
+//! - Batch rollback, the state journal and nonce reservations
+//! - Checkpoints, genesis loading and reconciliation against the executor
+//! - The read-only view and concurrent admission across shards
+//! - Contract storage and cache metrics
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{DatabaseConfig, FlushPolicy, ReconciliationConfig, StateStoreConfig},
+        pool::{PoolEvent, TransactionPool},
+        registry::Registry,
+        state::{Genesis, StateCache, StateReader, StateReconciler, StateStore, StateTrie, EMPTY_STATE_ROOT},
+        test_utils::create_test_tx,
+        AccountState, ForcedEventType, ForcedTransaction, StateChangeKind,
+    };
+    use ethers::types::{Address, Bytes, H256, U256};
+    use std::sync::Arc;
+
+    async fn create_archived_cache() -> (StateCache, Arc<Registry>) {
+        let registry = Arc::new(Registry::new(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+        }).await.unwrap());
+        (StateCache::new().with_archive(registry.clone()), registry)
+    }
+
+    fn account(id: u64, balance: u64, nonce: u64) -> AccountState {
+        AccountState {
+            address: Address::from_low_u64_be(id),
+            balance: U256::from(balance),
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    fn forced(event_type: ForcedEventType, id: u64, value: u64) -> ForcedTransaction {
+        ForcedTransaction {
+            tx_hash: H256::zero(),
+            from: Address::from_low_u64_be(id),
+            to: Address::from_low_u64_be(id),
+            value: U256::from(value),
+            nonce: 0,
+            gas_limit: 21000,
+            l1_tx_hash: H256::zero(),
+            l1_block_number: 1,
+            l1_log_index: 0,
+            event_type,
+            timestamp: 0,
+            bond: None,
+            data: Bytes::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_archives_only_inactive_accounts() {
+        let (cache, _) = create_archived_cache().await;
+        cache.update(account(1, 100, 3)).await;
+        cache.advance_batch(5);
+        cache.update(account(2, 200, 0)).await;
+
+        // Account 1 was last active in batch 0, account 2 in batch 5
+        cache.advance_batch(10);
+        assert_eq!(cache.archive_inactive(10).await.unwrap(), 1);
+        assert_eq!(cache.archive_inactive(10).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_archived_account_is_resurrected_with_witness() {
+        let (cache, registry) = create_archived_cache().await;
+        let original = account(1, 100, 3);
+        cache.update(original.clone()).await;
+        cache.advance_batch(10);
+        assert_eq!(cache.archive_inactive(10).await.unwrap(), 1);
+
+        // Referencing the account brings it back unchanged
+        cache.advance_batch(12);
+        assert_eq!(cache.get_nonce(&original.address).await, Some(3));
+        assert_eq!(cache.get_balance(&original.address).await, Some(U256::from(100)));
+
+        let witnesses = registry.resurrection_witnesses(original.address).await.unwrap();
+        assert_eq!(witnesses.len(), 1);
+        assert!(witnesses[0].verified);
+        assert_eq!(witnesses[0].state_hash, original.state_hash());
+        assert_eq!(witnesses[0].archived_at_batch, 10);
+        assert_eq!(witnesses[0].resurrected_at_batch, 12);
+
+        // Resurrection counts as activity, so the account is hot again
+        assert_eq!(cache.archive_inactive(10).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_lookups_by_where_they_were_served_from() {
+        let (cache, _registry) = create_archived_cache().await;
+        cache.update(account(1, 100, 0)).await;
+        cache.advance_batch(10);
+        assert_eq!(cache.archive_inactive(10).await.unwrap(), 1);
+
+        // Resurrected from the archive, then held in memory
+        cache.get_balance(&Address::from_low_u64_be(1)).await;
+        cache.get_balance(&Address::from_low_u64_be(1)).await;
+        cache.get_balance(&Address::from_low_u64_be(2)).await;
+
+        let metrics = cache.metrics().await;
+        assert_eq!((metrics.hits, metrics.store_loads, metrics.archive_loads, metrics.absent), (1, 0, 1, 2));
+        assert_eq!((metrics.hot_accounts, metrics.accounts, metrics.contracts), (1, 1, 0));
+        assert!(metrics.read_lock_waits.count >= 3);
+        assert!(metrics.write_lock_waits.count >= 1);
+        assert!(metrics.read_lock_waits.buckets.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("sequencer_state_lookups_total{result=\"archive\"} 1"));
+        assert!(text.contains("sequencer_state_lock_wait_seconds_bucket{mode=\"write\",le=\"+Inf\"}"));
+        assert!(text.contains("sequencer_state_hot_accounts 1"));
+    }
+
+    #[tokio::test]
+    async fn test_increment_nonce_continues_from_archived_state() {
+        let (cache, _) = create_archived_cache().await;
+        let original = account(1, 100, 3);
+        cache.update(original.clone()).await;
+        cache.advance_batch(1);
+        cache.archive_inactive(1).await.unwrap();
+
+        cache.increment_nonce(&original.address, H256::zero()).await;
+        assert_eq!(cache.get_nonce(&original.address).await, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_reader_snapshots_accounts_without_changing_state() {
+        let (cache, registry) = create_archived_cache().await;
+        cache.update(account(1, 100, 3)).await;
+        cache.update(account(2, 200, 0)).await;
+        cache.advance_batch(5);
+        cache.archive_inactive(0).await.unwrap();
+        cache.update(account(2, 250, 1)).await;
+        let root = cache.state_root();
+
+        let reader = StateReader::new(cache.clone());
+        let addresses: Vec<Address> = (1..=3).map(Address::from_low_u64_be).collect();
+        let snapshot = reader.snapshot(&addresses).await;
+        assert_eq!((snapshot.batch_id, snapshot.state_root), (5, root));
+        let read: Vec<(u64, u64)> = snapshot.accounts.iter().map(|account| (account.balance.as_u64(), account.nonce)).collect();
+        assert_eq!(read, vec![(100, 3), (250, 1), (0, 0)]);
+        assert_eq!(reader.account(&addresses[0]).await.nonce, 3);
+
+        // The archived account was read in place, and the unknown one not created
+        assert!(registry.resurrection_witnesses(addresses[0]).await.unwrap().is_empty());
+        assert_eq!(cache.hot_accounts().await, 1);
+        assert_eq!(cache.state_root(), root);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_account_gets_defaults() {
+        let (cache, _) = create_archived_cache().await;
+        let address = Address::from_low_u64_be(7);
+        assert_eq!(cache.get_nonce(&address).await, None);
+        assert_eq!(cache.get_account(&address).await.nonce, 0);
+        assert_eq!(cache.get_nonce(&address).await, None);
+        assert_eq!(cache.state_root(), EMPTY_STATE_ROOT);
+
+        // Initializing creates the account, so it counts towards the state root
+        assert_eq!(cache.get_or_init_account(&address).await.nonce, 0);
+        assert_eq!(cache.get_nonce(&address).await, Some(0));
+        assert_ne!(cache.state_root(), EMPTY_STATE_ROOT);
+    }
+
+    #[tokio::test]
+    async fn test_new_accounts_are_created_once_with_the_prefund() {
+        let cache = StateCache::new().with_prefund(U256::from(1_000)).with_journal(16);
+        let address = Address::from_low_u64_be(7);
+        let creators: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                tokio::spawn(async move { cache.get_or_init_account(&address).await })
+            })
+            .collect();
+        for creator in creators {
+            assert_eq!(creator.await.unwrap().balance, U256::from(1_000));
+        }
+        assert_eq!(cache.journal().for_account(address).len(), 1);
+
+        // Spending the prefund doesn't top it up again
+        cache.update(account(7, 0, 1)).await;
+        assert_eq!(cache.get_or_init_account(&address).await.balance, U256::zero());
+        cache.increment_nonce(&Address::from_low_u64_be(8), H256::zero()).await;
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(8)).await, Some(U256::from(1_000)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_admissions_across_shards() {
+        let cache = StateCache::new().with_shards(4).with_journal(1_000);
+        let admissions: Vec<_> = (0..64u64)
+            .map(|i| {
+                let cache = cache.clone();
+                // 16 senders, so every shard sees several senders and each sender several tasks
+                let address = Address::from_low_u64_be(i % 16 + 1);
+                tokio::spawn(async move { cache.increment_nonce(&address, H256::from_low_u64_be(i)).await })
+            })
+            .collect();
+        for admission in admissions {
+            admission.await.unwrap();
+        }
+
+        let mut trie = StateTrie::new();
+        for id in 1..=16 {
+            assert_eq!(cache.get_nonce(&Address::from_low_u64_be(id)).await, Some(4));
+            trie.insert(&account(id, 0, 4));
+        }
+        assert_eq!(cache.hot_accounts().await, 16);
+        assert_eq!(cache.state_root(), trie.root());
+        let reader = StateReader::new(cache.clone());
+        let snapshot = reader.snapshot(&[Address::from_low_u64_be(1), Address::from_low_u64_be(2)]).await;
+        assert_eq!(snapshot.state_root, trie.root());
+        assert!(snapshot.accounts.iter().all(|account| account.nonce == 4));
+    }
+
+    #[tokio::test]
+    async fn test_contract_storage_is_committed_to_by_the_state_root() {
+        let path = std::env::temp_dir().join(format!("sequencer-storage-test-{}", std::process::id()));
+        let config = StateStoreConfig {
+            path: path.to_string_lossy().into_owned(),
+            flush: FlushPolicy::EveryWrite,
+            flush_interval_ms: 500,
+        };
+        let contract = Address::from_low_u64_be(1);
+        let (slot, value) = (H256::from_low_u64_be(7), H256::from_low_u64_be(42));
+        let cache = StateCache::new().with_store(StateStore::open(&config).unwrap());
+        cache.update(account(1, 100, 0)).await;
+        let eoa_root = cache.state_root();
+
+        cache.set_code_hash(&contract, H256::repeat_byte(0xc0)).await;
+        cache.set_storage(&contract, &[(slot, value)]).await;
+        assert_eq!(cache.storage_at(&contract, &slot).await, value);
+        assert_eq!(cache.storage_at(&contract, &H256::zero()).await, H256::zero());
+        let state = cache.get_account(&contract).await;
+        assert!(state.is_contract());
+        assert_ne!(state.storage_root, EMPTY_STATE_ROOT);
+        let contract_root = cache.state_root();
+        assert_ne!(contract_root, eoa_root);
+
+        // Storage is read back from the store after a restart
+        drop(cache);
+        let cache = StateCache::new().with_store(StateStore::open(&config).unwrap());
+        cache.restore().await.unwrap();
+        assert_eq!(cache.storage_at(&contract, &slot).await, value);
+        assert_eq!(cache.state_root(), contract_root);
+
+        // Clearing every slot and the code makes it an externally owned account again
+        cache.set_storage(&contract, &[(slot, H256::zero())]).await;
+        cache.set_code_hash(&contract, H256::zero()).await;
+        assert!(!cache.get_account(&contract).await.is_contract());
+        assert_eq!(cache.state_root(), eoa_root);
+        drop(cache);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_store_keeps_state_across_restarts() {
+        let path = std::env::temp_dir().join(format!("sequencer-state-test-{}", std::process::id()));
+        let config = StateStoreConfig {
+            path: path.to_string_lossy().into_owned(),
+            flush: FlushPolicy::EveryWrite,
+            flush_interval_ms: 500,
+        };
+        let original = account(1, 100, 3);
+        let staker = Address::from_low_u64_be(2);
+        let root = {
+            let cache = StateCache::new().with_store(StateStore::open(&config).unwrap());
+            cache.update(original.clone()).await;
+            cache.increment_nonce(&original.address, H256::zero()).await;
+            cache.add_stake(staker, U256::from(50)).await;
+            cache.advance_batch(7);
+            cache.state_root()
+        };
+
+        // A fresh cache over the same store picks everything up
+        let cache = StateCache::new().with_store(StateStore::open(&config).unwrap());
+        assert_eq!(cache.get_nonce(&original.address).await, Some(4));
+        assert_eq!(cache.get_balance(&original.address).await, Some(U256::from(100)));
+        assert_eq!(cache.get_stake(&staker).await, U256::from(50));
+        assert_eq!(cache.current_batch(), 7);
+        assert_eq!(cache.restore().await.unwrap(), 1);
+        assert_eq!(cache.state_root(), root);
+        drop(cache);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_accounts_are_evicted_to_the_store() {
+        let path = std::env::temp_dir().join(format!("sequencer-lru-test-{}", std::process::id()));
+        let config = StateStoreConfig {
+            path: path.to_string_lossy().into_owned(),
+            flush: FlushPolicy::Interval,
+            flush_interval_ms: 500,
+        };
+        // A single shard, so the limit applies to all accounts at once
+        let cache = StateCache::new()
+            .with_shards(1)
+            .with_store(StateStore::open(&config).unwrap())
+            .with_max_hot_accounts(10);
+        for id in 1..=10 {
+            cache.update(account(id, id * 10, 0)).await;
+        }
+        let root = cache.state_root();
+
+        // Account 1 is used again, so the next ones are the least recently used
+        cache.get_balance(&Address::from_low_u64_be(1)).await;
+        cache.update(account(11, 110, 0)).await;
+        assert_eq!(cache.hot_accounts().await, 9);
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(1)).await, Some(U256::from(10)));
+        assert_eq!(cache.hot_accounts().await, 9);
+
+        // Evicted accounts are read back from the store, and stay in the state root
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(2)).await, Some(U256::from(20)));
+        assert_eq!(cache.hot_accounts().await, 10);
+        let mut trie = StateTrie::new();
+        for id in 1..=11 {
+            trie.insert(&account(id, id * 10, 0));
+        }
+        assert_ne!(root, trie.root());
+        assert_eq!(cache.state_root(), trie.root());
+        drop(cache);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_state_root_depends_only_on_account_states() {
+        let accounts: Vec<AccountState> = (1..=20).map(|id| account(id, id * 10, id)).collect();
+        let mut forward = StateTrie::new();
+        assert_eq!(forward.root(), EMPTY_STATE_ROOT);
+        for account in &accounts {
+            forward.insert(account);
+        }
+
+        // Reverse order, going through a stale state first
+        let mut backward = StateTrie::new();
+        backward.insert(&account(5, 0, 0));
+        backward.root();
+        for account in accounts.iter().rev() {
+            backward.insert(account);
+        }
+        assert_eq!(backward.len(), 20);
+        assert_eq!(forward.root(), backward.root());
+
+        // A single changed nonce changes the root
+        let before = forward.root();
+        forward.insert(&account(7, 70, 8));
+        assert_ne!(forward.root(), before);
+    }
+
+    #[test]
+    fn test_proofs_show_accounts_and_their_absence() {
+        let mut trie = StateTrie::new();
+        let missing = Address::from_low_u64_be(99);
+        assert!(trie.prove(&missing).verify(EMPTY_STATE_ROOT, &missing, None));
+        for id in 1..=20 {
+            trie.insert(&account(id, id * 10, id));
+        }
+        let root = trie.root();
+
+        for id in [1, 7, 20] {
+            let proof = trie.prove(&Address::from_low_u64_be(id));
+            assert!(proof.verify(root, &Address::from_low_u64_be(id), Some(&account(id, id * 10, id))));
+            // Not for another state, nor as absent
+            assert!(!proof.verify(root, &Address::from_low_u64_be(id), Some(&account(id, 0, id))));
+            assert!(!proof.verify(root, &Address::from_low_u64_be(id), None));
+        }
+        let proof = trie.prove(&missing);
+        assert!(proof.verify(root, &missing, None));
+        assert!(!proof.verify(root, &missing, Some(&account(99, 0, 0))));
+
+        // Proofs are against the root they were taken at
+        let proof = trie.prove(&Address::from_low_u64_be(1));
+        trie.insert(&account(21, 0, 0));
+        assert!(!proof.verify(trie.root(), &Address::from_low_u64_be(1), Some(&account(1, 10, 1))));
+    }
+
+    #[test]
+    fn test_store_backed_trie_moves_deep_subtrees_out_of_memory() {
+        let path = std::env::temp_dir().join(format!("sequencer-trie-test-{}", std::process::id()));
+        let config = StateStoreConfig {
+            path: path.to_string_lossy().into_owned(),
+            flush: FlushPolicy::Interval,
+            flush_interval_ms: 500,
+        };
+        let mut stored = StateTrie::new().with_store(StateStore::open(&config).unwrap(), 3);
+        let mut in_memory = StateTrie::new();
+        for id in 1..=200 {
+            stored.insert(&account(id, id * 10, id));
+            in_memory.insert(&account(id, id * 10, id));
+        }
+        assert_eq!(stored.root(), in_memory.root());
+        // Nodes down to depth 3, and a stand-in for each subtree below
+        assert!(stored.nodes_in_memory() <= 15);
+        assert!(in_memory.nodes_in_memory() >= 399);
+
+        // Changes and removals read paths back, and leave the same root
+        for id in (1..=200).step_by(3) {
+            stored.insert(&account(id, 0, id + 1));
+            in_memory.insert(&account(id, 0, id + 1));
+        }
+        for id in (2..=200).step_by(5) {
+            stored.remove(&Address::from_low_u64_be(id));
+            in_memory.remove(&Address::from_low_u64_be(id));
+        }
+        let root = stored.root();
+        assert_eq!(root, in_memory.root());
+        assert_eq!(stored.len(), in_memory.len());
+        assert!(stored.nodes_in_memory() <= 15);
+
+        // Proofs descend into moved subtrees
+        let proof = stored.prove(&Address::from_low_u64_be(4));
+        assert!(proof.verify(root, &Address::from_low_u64_be(4), Some(&account(4, 0, 5))));
+        let proof = stored.prove(&Address::from_low_u64_be(7));
+        assert!(proof.verify(root, &Address::from_low_u64_be(7), None));
+        assert_eq!(stored.root(), root);
+        drop(stored);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_archived_accounts_stay_in_state_root() {
+        let (cache, registry) = create_archived_cache().await;
+        cache.update(account(1, 100, 3)).await;
+        cache.update(account(2, 200, 0)).await;
+        let root = cache.state_root();
+        assert_ne!(root, EMPTY_STATE_ROOT);
+
+        cache.advance_batch(10);
+        assert_eq!(cache.archive_inactive(10).await.unwrap(), 2);
+        assert_eq!(cache.state_root(), root);
+
+        // After a restart, the archived accounts are restored into the trie
+        let restarted = StateCache::new().with_archive(registry);
+        assert_eq!(restarted.restore().await.unwrap(), 2);
+        assert_eq!(restarted.state_root(), root);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_undoes_sealed_batches_newest_first() {
+        let cache = StateCache::new().with_snapshots(2);
+        let user = Address::from_low_u64_be(1);
+        cache.snapshot(1);
+        cache.apply_forced(1, &forced(ForcedEventType::Deposit, 1, 1_000)).await.unwrap();
+        cache.snapshot(2);
+        cache.apply_forced(2, &forced(ForcedEventType::ForcedExit, 1, 400)).await.unwrap();
+        assert!(cache.apply_forced(2, &forced(ForcedEventType::ForcedExit, 1, 5_000)).await.is_err());
+        assert_eq!(cache.get_balance(&user).await, Some(U256::from(600)));
+
+        assert_eq!(cache.rollback(2).await.unwrap(), 1);
+        assert_eq!(cache.get_balance(&user).await, Some(U256::from(1_000)));
+
+        // Rolling back batch 1 keeps the nonce of a transaction admitted since
+        cache.increment_nonce(&user, H256::zero()).await;
+        assert_eq!(cache.rollback(1).await.unwrap(), 1);
+        assert_eq!(cache.get_balance(&user).await, Some(U256::zero()));
+        assert_eq!(cache.get_nonce(&user).await, Some(1));
+        assert!(cache.rollback(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_restores_state_root() {
+        let cache = StateCache::new().with_snapshots(2);
+        cache.update(account(1, 100, 0)).await;
+        let root = cache.state_root();
+        cache.snapshot(1);
+        cache.apply_forced(1, &forced(ForcedEventType::Deposit, 2, 50)).await.unwrap();
+        cache.apply_forced(1, &forced(ForcedEventType::Deposit, 1, 50)).await.unwrap();
+        assert_ne!(cache.state_root(), root);
+
+        // The account the deposit created is gone again
+        cache.rollback(1).await.unwrap();
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(2)).await, None);
+        assert_eq!(cache.state_root(), root);
+
+        // Only the most recent snapshots are kept, and released ones are gone
+        for batch_id in 2..=4 {
+            cache.snapshot(batch_id);
+        }
+        assert!(cache.rollback(2).await.is_err());
+        cache.release(3);
+        assert!(cache.rollback(3).await.is_err());
+        assert_eq!(cache.rollback(4).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_journal_records_changes_by_transaction_batch_and_account() {
+        let cache = StateCache::new().with_snapshots(1).with_journal(16);
+        let (alice, bob) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let admitted = H256::from_low_u64_be(7);
+        cache.update(account(1, 1_000, 0)).await;
+        cache.increment_nonce(&alice, admitted).await;
+        cache.snapshot(1);
+        cache.apply_transfer(1, admitted, &alice, &bob, U256::from(300), U256::from(10)).await.unwrap();
+
+        let changes = cache.journal().for_transaction(admitted);
+        let kinds: Vec<_> = changes.iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, vec![StateChangeKind::Admission, StateChangeKind::Execution, StateChangeKind::Execution]);
+        assert_eq!((changes[0].nonce_before, changes[0].nonce_after), (0, 1));
+        assert_eq!((changes[1].balance_before, changes[1].balance_after), (U256::from(1_000), U256::from(690)));
+        assert!(changes[2].created);
+        assert_eq!(changes[2].balance_after, U256::from(300));
+        assert_eq!(cache.journal().for_batch(1).len(), 2);
+
+        // Replaying an account's changes in order reproduces its balance
+        let history = cache.journal().for_account(alice);
+        assert_eq!(history.len(), 3);
+        assert!(history.windows(2).all(|pair| pair[0].balance_after == pair[1].balance_before));
+        assert_eq!(history.last().unwrap().balance_after, cache.get_balance(&alice).await.unwrap());
+
+        // Only the most recent changes are kept
+        for _ in 0..16 {
+            cache.update(account(3, 1, 0)).await;
+        }
+        assert!(cache.journal().for_transaction(admitted).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_released_nonces_are_given_back_with_later_ones() {
+        let cache = StateCache::new().with_journal(16);
+        let user = Address::from_low_u64_be(1);
+        let hashes: Vec<H256> = (0..4).map(H256::from_low_u64_be).collect();
+        for hash in &hashes {
+            cache.increment_nonce(&user, *hash).await;
+        }
+        cache.apply_transfer(1, hashes[0], &user, &user, U256::zero(), U256::zero()).await.unwrap();
+        // The state after admitting only the first two
+        let root = {
+            let check = StateCache::new();
+            check.increment_nonce(&user, hashes[0]).await;
+            check.increment_nonce(&user, hashes[1]).await;
+            check.state_root()
+        };
+
+        // Sealed into a batch: its nonce is committed
+        assert_eq!(cache.release_nonce(&user, hashes[0]).await, None);
+        assert_eq!(cache.get_nonce(&user).await, Some(4));
+
+        // Dropping the third gives back its nonce and the fourth's
+        assert_eq!(cache.release_nonce(&user, hashes[2]).await, Some(2));
+        assert_eq!(cache.get_nonce(&user).await, Some(2));
+        assert_eq!(cache.reservations().reserved(&user), vec![(1, hashes[1])]);
+        assert_eq!(cache.state_root(), root);
+        assert_eq!(cache.release_nonce(&user, hashes[3]).await, None);
+        assert_eq!(cache.journal().for_account(user).last().unwrap().kind, StateChangeKind::Undo);
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_between_release_and_drop_is_given_back() {
+        let cache = StateCache::new();
+        let pool = TransactionPool::default();
+        let mut removals = pool.subscribe_removals();
+        let user = Address::zero();
+        let (first, second) = (create_test_tx(0, 100), create_test_tx(1, 100));
+        for tx in [&first, &second] {
+            cache.increment_nonce(&user, tx.hash()).await;
+            pool.add(tx.clone()).await.unwrap();
+        }
+        // The first one leaves the pool without being sealed
+        pool.take_exact(&[first.hash()]).await.unwrap();
+
+        // The first one's nonce is released, then the sender resubmits at it before the drop
+        assert_eq!(cache.release_nonce(&user, first.hash()).await, Some(0));
+        let resubmitted = create_test_tx(0, 200);
+        cache.increment_nonce(&user, resubmitted.hash()).await;
+        pool.add(resubmitted.clone()).await.unwrap();
+        let dropped = pool.drop_from(&user, 0).await;
+        assert_eq!(dropped.len(), 2);
+        assert!(dropped.iter().any(|tx| tx.hash() == resubmitted.hash()));
+        assert_eq!(cache.reservations().reserved(&user), vec![(0, resubmitted.hash())]);
+
+        // Giving back the dropped transactions releases the resubmitted one's reservation
+        while let Ok(PoolEvent::Dropped(tx)) = removals.try_recv() {
+            assert!(cache.give_back(&pool, &tx).await.is_empty());
+        }
+        assert!(cache.reservations().reserved(&user).is_empty());
+        assert_eq!(cache.get_nonce(&user).await, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_round_trips_the_state_at_the_last_batch() {
+        let (cache, _registry) = create_archived_cache().await;
+        let (user, contract) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        cache.update(account(1, 500, 3)).await;
+        cache.update(account(3, 200, 0)).await;
+        cache.set_code_hash(&contract, H256::from_low_u64_be(7)).await;
+        cache.set_storage(&contract, &[(H256::from_low_u64_be(1), H256::from_low_u64_be(9))]).await;
+        cache.advance_batch(5);
+        let sealed_root = cache.state_root();
+        // Archived accounts are exported, and pending admissions are not
+        assert_eq!(cache.archive_inactive(0).await.unwrap(), 2);
+        cache.increment_nonce(&user, H256::from_low_u64_be(100)).await;
+
+        let checkpoint = cache.export_checkpoint().await.unwrap();
+        assert_eq!((checkpoint.batch_id, checkpoint.state_root), (5, sealed_root));
+        assert_eq!(checkpoint.accounts.len(), 3);
+
+        let imported = StateCache::new();
+        assert_eq!(imported.import_checkpoint(&checkpoint).await.unwrap(), sealed_root);
+        assert_eq!(imported.current_batch(), 5);
+        assert_eq!(imported.get_nonce(&user).await, Some(3));
+        assert_eq!(imported.storage_at(&contract, &H256::from_low_u64_be(1)).await, H256::from_low_u64_be(9));
+        assert!(imported.import_checkpoint(&checkpoint).await.is_err());
+
+        let mut tampered = checkpoint.clone();
+        tampered.accounts[0].balance = U256::from(1_000_000);
+        assert!(tampered.verify().is_err());
+        assert!(StateCache::new().import_checkpoint(&tampered).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_genesis_is_loaded_into_an_empty_state() {
+        let path = std::env::temp_dir().join(format!("sequencer-genesis-test-{}.json", std::process::id()));
+        let write = |accounts: &str| {
+            std::fs::write(&path, format!(r#"{{"chain_id": 31337, "accounts": [{}]}}"#, accounts)).unwrap();
+            Genesis::load(path.to_str().unwrap())
+        };
+        let alice = r#"{"address": "0x0000000000000000000000000000000000000001", "balance": "0x3e8", "nonce": 2}"#;
+        let bob = r#"{"address": "0x0000000000000000000000000000000000000002", "balance": "0x0", "nonce": 0}"#;
+
+        let genesis = write(&format!("{}, {}", alice, bob)).unwrap();
+        assert!(write(&format!("{}, {}", alice, alice)).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // The hash covers the chain ID as well as the allocation
+        let other_chain = Genesis { chain_id: 1, ..genesis.clone() };
+        assert_ne!(genesis.hash(), other_chain.hash());
+        assert_eq!(genesis.state_root(), other_chain.state_root());
+
+        let cache = StateCache::new();
+        assert_eq!(cache.load_genesis(&genesis).await.unwrap(), genesis.state_root());
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(1)).await, Some(U256::from(1_000)));
+        assert_eq!(cache.get_nonce(&Address::from_low_u64_be(1)).await, Some(2));
+        assert!(cache.load_genesis(&genesis).await.is_err());
+    }
+
+    /// Start a fake executor at batch 2 where every account has balance 100 and nonce 1
+    async fn spawn_executor() -> String {
+        let app = axum::Router::new().route("/", axum::routing::post(|axum::Json(request): axum::Json<serde_json::Value>| async move {
+            let result = match request["method"].as_str() {
+                Some("eth_blockNumber") => "0x2",
+                Some("eth_getBalance") => "0x64",
+                _ => "0x1",
+            };
+            axum::Json(serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 1 }))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_reconciliation_repairs_drift_seen_twice() {
+        let cache = StateCache::new();
+        cache.update(account(1, 100, 1)).await;
+        cache.update(account(2, 50, 1)).await;
+        cache.advance_batch(2);
+        // Changed after batch 2 was sealed: not settled yet
+        cache.update(account(3, 0, 0)).await;
+        assert_eq!(cache.settled_accounts(2, None, 10).await.len(), 2);
+        assert_eq!(cache.settled_accounts(2, Some(Address::from_low_u64_be(1)), 10).await.len(), 1);
+
+        let config = ReconciliationConfig {
+            executor_url: spawn_executor().await,
+            interval_secs: 60,
+            accounts_per_round: 10,
+            repair: true,
+            timeout_ms: 5_000,
+        };
+        let mut reconciler = StateReconciler::new(cache.clone(), Arc::new(TransactionPool::default()), config).unwrap();
+        let report = reconciler.reconcile_once().await.unwrap();
+        assert_eq!((report.executed_batch, report.checked, report.drifted, report.repaired), (2, 2, 1, 0));
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(2)).await, Some(U256::from(50)));
+
+        let report = reconciler.reconcile_once().await.unwrap();
+        assert_eq!((report.drifted, report.repaired), (1, 1));
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(2)).await, Some(U256::from(100)));
+
+        // A repair never overwrites a change made since the account was compared
+        assert!(!cache.reconcile(&account(1, 7, 0), account(1, 5, 5)).await);
+        assert_eq!(cache.get_balance(&Address::from_low_u64_be(1)).await, Some(U256::from(100)));
+    }
+}
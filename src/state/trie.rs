@@ -0,0 +1,427 @@
+//! State Trie Module
+//!
+//! A sparse binary Merkle trie over every account, committing to the whole
+//! state in a single root. Each account sits at the path given by the bits of
+//! `keccak256(address)`, most significant first, and its leaf commits to
+//! [`AccountState::state_hash`].
+//!
+//! Subtrees holding a single account are collapsed into its leaf, so the trie
+//! holds about two nodes per account whatever the key length. The shape only
+//! depends on the set of accounts, never on insertion order, so any
+//! implementation hashing the same accounts arrives at the same root:
+//! - Empty subtree: zero
+//! - Leaf: `keccak256(0x00 || key || state_hash)`
+//! - Branch: `keccak256(0x01 || left || right)`
+//!
+//! Branch hashes are cached and only recomputed along the paths of accounts
+//! changed since the last root.
+//!
+//! In memory, the trie holds about two nodes per account ever seen, archived
+//! or evicted ones included. Backed by a [`StateStore`] (see
+//! [`StateTrie::with_store`]), each root moves the subtrees below a fixed
+//! depth out to the store, keyed by their hash, and only the nodes above it
+//! and those on paths changed since stay in memory. Moved subtrees are read
+//! back one node at a time as paths through them are changed or proven.
+//!
+//! Contract storage is committed to by tries of the same shape, with leaves
+//! inserted by key (see `AccountStorage`).
+//!
+//! A [`StateProof`] shows an account's state, or its absence, against a root:
+//! the sibling hashes along the account's path, down to the leaf or empty
+//! subtree the path ends at. Absence is shown by an empty subtree, or by the
+//! leaf of another account sharing the path so far.
+
+use super::StateStore;
+use crate::AccountState;
+use ethers::types::{Address, H256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+
+/// Root of the trie without any account
+pub const EMPTY_STATE_ROOT: H256 = H256::zero();
+
+/// Domain prefix of leaf hashes
+const LEAF_PREFIX: u8 = 0x00;
+/// Domain prefix of branch hashes
+const BRANCH_PREFIX: u8 = 0x01;
+
+/// A trie node
+enum Node {
+    Empty,
+    Leaf {
+        /// `keccak256` of the account address
+        key: H256,
+        /// The account's `state_hash`
+        value: H256,
+    },
+    Branch {
+        /// Subtree whose next key bit is 0
+        left: Box<Node>,
+        /// Subtree whose next key bit is 1
+        right: Box<Node>,
+        /// Cached hash (`None` once something below changed)
+        hash: Option<H256>,
+    },
+    /// Non-empty subtree moved out to the store, known by its hash
+    Stored(H256),
+}
+
+/// Where a trie keeps the nodes it moves out of memory
+struct TrieNodes {
+    store: StateStore,
+    /// Depth from which subtrees are moved out at each root
+    hot_depth: usize,
+}
+
+/// Merkle proof of an account's state, or of its absence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateProof {
+    /// Hashes of the subtrees beside the path, from the root down
+    pub siblings: Vec<H256>,
+    /// Leaf the path ends at (`None` for an empty subtree)
+    pub leaf: Option<ProofLeaf>,
+}
+
+/// A leaf of the state trie
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofLeaf {
+    /// `keccak256` of the account address
+    pub key: H256,
+    /// The account's `state_hash`
+    pub value: H256,
+}
+
+impl StateProof {
+    /// Whether the proof shows `account` at `address` under `root`
+    ///
+    /// With `account` as `None`, whether it shows no account is at `address`.
+    pub fn verify(&self, root: H256, address: &Address, account: Option<&AccountState>) -> bool {
+        let key = trie_key(address);
+        let leaf_fits = match (account, &self.leaf) {
+            (Some(account), Some(leaf)) => leaf.key == key && leaf.value == account.state_hash(),
+            (None, Some(leaf)) => {
+                leaf.key != key && (0..self.siblings.len()).all(|depth| bit(&leaf.key, depth) == bit(&key, depth))
+            }
+            (None, None) => true,
+            (Some(_), None) => false,
+        };
+        leaf_fits && self.root(&key) == root
+    }
+
+    /// Root reached by hashing up the path of `key`
+    fn root(&self, key: &H256) -> H256 {
+        let mut hash = match &self.leaf {
+            Some(leaf) => leaf_hash(&leaf.key, &leaf.value),
+            None => EMPTY_STATE_ROOT,
+        };
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            hash = if bit(key, depth) {
+                branch_hash(sibling, &hash)
+            } else {
+                branch_hash(&hash, sibling)
+            };
+        }
+        hash
+    }
+}
+
+/// Sparse Merkle trie of account states
+pub struct StateTrie {
+    root: Node,
+    /// Accounts in the trie
+    len: usize,
+    /// Store subtrees are moved out to (all in memory if `None`)
+    nodes: Option<TrieNodes>,
+}
+
+impl StateTrie {
+    /// Creates an empty trie
+    pub fn new() -> Self {
+        Self { root: Node::Empty, len: 0, nodes: None }
+    }
+
+    /// Move subtrees from `hot_depth` down out to `store` at each root
+    ///
+    /// The store's previous trie nodes are cleared: they are rebuilt from the
+    /// accounts (see `StateCache::restore`). Must be called on an empty trie.
+    pub fn with_store(mut self, store: StateStore, hot_depth: usize) -> Self {
+        store.clear_trie_nodes();
+        self.nodes = Some(TrieNodes { store, hot_depth });
+        self
+    }
+
+    /// Insert an account, or replace its previous state
+    pub fn insert(&mut self, account: &AccountState) {
+        self.insert_key(trie_key(&account.address), account.state_hash());
+    }
+
+    /// Remove an account, collapsing subtrees left with a single account
+    pub fn remove(&mut self, address: &Address) {
+        self.remove_key(trie_key(address));
+    }
+
+    /// Insert a leaf committing to `value` at `key`, or replace its value
+    pub(super) fn insert_key(&mut self, key: H256, value: H256) {
+        if insert(&mut self.root, key, value, 0, self.nodes.as_ref()) {
+            self.len += 1;
+        }
+    }
+
+    /// Remove the leaf at `key`
+    pub(super) fn remove_key(&mut self, key: H256) {
+        if remove(&mut self.root, key, 0, self.nodes.as_ref()) {
+            self.len -= 1;
+        }
+    }
+
+    /// Number of accounts in the trie
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the trie holds no account
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Nodes held in memory, not counting empty subtrees
+    pub fn nodes_in_memory(&self) -> usize {
+        count(&self.root)
+    }
+
+    /// Root committing to every account ([`EMPTY_STATE_ROOT`] if there is none)
+    ///
+    /// With a store, subtrees below the hot depth are then moved out to it.
+    pub fn root(&mut self) -> H256 {
+        let root = hash(&mut self.root);
+        if let Some(nodes) = &self.nodes {
+            let mut moved = Vec::new();
+            move_out(&mut self.root, nodes.hot_depth, 0, &mut moved);
+            nodes.store.put_trie_nodes(moved);
+        }
+        root
+    }
+
+    /// Proof of the account at `address`, or of its absence, against [`StateTrie::root`]
+    pub fn prove(&mut self, address: &Address) -> StateProof {
+        let key = trie_key(address);
+        let mut siblings = Vec::new();
+        let mut node = &mut self.root;
+        loop {
+            // Read back, but left in the store: the path does not change
+            load(node, self.nodes.as_ref(), false);
+            match node {
+                Node::Empty => return StateProof { siblings, leaf: None },
+                Node::Leaf { key, value } => {
+                    return StateProof { siblings, leaf: Some(ProofLeaf { key: *key, value: *value }) };
+                }
+                Node::Branch { left, right, .. } => {
+                    let (next, sibling) = if bit(&key, siblings.len()) { (right, left) } else { (left, right) };
+                    siblings.push(hash(sibling));
+                    node = &mut **next;
+                }
+                Node::Stored(_) => unreachable!("loaded above"),
+            }
+        }
+    }
+}
+
+impl Default for StateTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Path of an account in the trie
+fn trie_key(address: &Address) -> H256 {
+    H256::from(keccak256(address.as_bytes()))
+}
+
+/// Bit of `key` deciding the branch taken at `depth`
+fn bit(key: &H256, depth: usize) -> bool {
+    key.as_bytes()[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+/// Insert `value` at `key` below `node`, which sits at `depth`
+///
+/// # Returns
+/// `true` if the key was not in the trie yet
+fn insert(node: &mut Node, key: H256, value: H256, depth: usize, nodes: Option<&TrieNodes>) -> bool {
+    load(node, nodes, true);
+    match node {
+        Node::Empty => {
+            *node = Node::Leaf { key, value };
+            true
+        }
+        Node::Leaf { key: existing, value: existing_value } if *existing == key => {
+            *existing_value = value;
+            false
+        }
+        Node::Leaf { .. } => {
+            // Two accounts now share this subtree: split it
+            let Node::Leaf { key: existing, value: existing_value } = std::mem::replace(node, Node::Branch {
+                left: Box::new(Node::Empty),
+                right: Box::new(Node::Empty),
+                hash: None,
+            }) else {
+                unreachable!()
+            };
+            insert(node, existing, existing_value, depth, nodes);
+            insert(node, key, value, depth, nodes)
+        }
+        Node::Branch { left, right, hash } => {
+            *hash = None;
+            let child = if bit(&key, depth) { right } else { left };
+            insert(child, key, value, depth + 1, nodes)
+        }
+        Node::Stored(_) => unreachable!("loaded above"),
+    }
+}
+
+/// Remove `key` from below `node`, which sits at `depth`
+///
+/// # Returns
+/// `true` if the key was in the trie
+fn remove(node: &mut Node, key: H256, depth: usize, nodes: Option<&TrieNodes>) -> bool {
+    load(node, nodes, true);
+    match node {
+        Node::Empty => false,
+        Node::Leaf { key: existing, .. } => {
+            if *existing != key {
+                return false;
+            }
+            *node = Node::Empty;
+            true
+        }
+        Node::Branch { left, right, hash } => {
+            let child = if bit(&key, depth) { &mut **right } else { &mut **left };
+            if !remove(child, key, depth + 1, nodes) {
+                return false;
+            }
+            *hash = None;
+            // A single account left below: the subtree collapses into its leaf
+            match (&**left, &**right) {
+                (Node::Empty, _) => load(right, nodes, true),
+                (_, Node::Empty) => load(left, nodes, true),
+                _ => {}
+            }
+            match (&mut **left, &mut **right) {
+                (Node::Empty, remaining @ Node::Leaf { .. }) | (remaining @ Node::Leaf { .. }, Node::Empty) => {
+                    *node = std::mem::replace(remaining, Node::Empty);
+                }
+                _ => {}
+            }
+            true
+        }
+        Node::Stored(_) => unreachable!("loaded above"),
+    }
+}
+
+/// Read `node` back from the store if it was moved out, one level deep
+///
+/// A node read back to be changed is removed from the store: it is written
+/// again, under its new hash, when next moved out.
+fn load(node: &mut Node, nodes: Option<&TrieNodes>, reclaim: bool) {
+    let Node::Stored(stored) = *node else {
+        return;
+    };
+    let nodes = nodes.expect("only tries with a store move nodes out");
+    let Some(encoded) = nodes.store.trie_node(&stored).filter(|encoded| encoded.len() == 65) else {
+        panic!("state trie node {:?} is missing from the store", stored);
+    };
+    let (first, second) = (H256::from_slice(&encoded[1..33]), H256::from_slice(&encoded[33..]));
+    let child = |hash: H256| if hash == EMPTY_STATE_ROOT { Node::Empty } else { Node::Stored(hash) };
+    *node = match encoded[0] {
+        LEAF_PREFIX => Node::Leaf { key: first, value: second },
+        _ => Node::Branch {
+            left: Box::new(child(first)),
+            right: Box::new(child(second)),
+            hash: Some(stored),
+        },
+    };
+    if reclaim {
+        nodes.store.remove_trie_node(&stored);
+    }
+}
+
+/// Replace the subtrees from `hot_depth` down with their hash, collecting
+/// their nodes in memory as `(hash, encoding)` into `moved`
+///
+/// Hashes must be up to date (see [`hash`]).
+fn move_out(node: &mut Node, hot_depth: usize, depth: usize, moved: &mut Vec<(H256, Vec<u8>)>) {
+    match node {
+        Node::Empty | Node::Stored(_) => {}
+        Node::Leaf { .. } if depth < hot_depth => {}
+        Node::Branch { left, right, .. } if depth < hot_depth => {
+            move_out(left, hot_depth, depth + 1, moved);
+            move_out(right, hot_depth, depth + 1, moved);
+        }
+        _ => {
+            let hash = collect(node, moved);
+            *node = Node::Stored(hash);
+        }
+    }
+}
+
+/// Collect every node of the subtree at `node` held in memory into `moved`
+///
+/// # Returns
+/// The subtree's hash
+fn collect(node: &Node, moved: &mut Vec<(H256, Vec<u8>)>) -> H256 {
+    let (prefix, first, second) = match node {
+        Node::Empty => return EMPTY_STATE_ROOT,
+        Node::Stored(hash) => return *hash,
+        Node::Leaf { key, value } => (LEAF_PREFIX, *key, *value),
+        Node::Branch { left, right, .. } => (BRANCH_PREFIX, collect(left, moved), collect(right, moved)),
+    };
+    let encoded = encode(prefix, &first, &second);
+    let hash = H256::from(keccak256(&encoded));
+    moved.push((hash, encoded));
+    hash
+}
+
+/// Nodes of the subtree at `node` held in memory
+fn count(node: &Node) -> usize {
+    match node {
+        Node::Empty => 0,
+        Node::Leaf { .. } | Node::Stored(_) => 1,
+        Node::Branch { left, right, .. } => 1 + count(left) + count(right),
+    }
+}
+
+/// Hash of the subtree at `node`, refreshing stale branch hashes
+fn hash(node: &mut Node) -> H256 {
+    match node {
+        Node::Empty => EMPTY_STATE_ROOT,
+        Node::Leaf { key, value } => leaf_hash(key, value),
+        Node::Branch { left, right, hash: cached } => {
+            if let Some(hash) = cached {
+                return *hash;
+            }
+            let hash = branch_hash(&hash(left), &hash(right));
+            *cached = Some(hash);
+            hash
+        }
+        Node::Stored(hash) => *hash,
+    }
+}
+
+/// Hash of a leaf committing to `value` at `key`
+fn leaf_hash(key: &H256, value: &H256) -> H256 {
+    H256::from(keccak256(encode(LEAF_PREFIX, key, value)))
+}
+
+/// Hash of a branch over two subtrees
+fn branch_hash(left: &H256, right: &H256) -> H256 {
+    H256::from(keccak256(encode(BRANCH_PREFIX, left, right)))
+}
+
+/// Encoding of a node, which its hash is taken over: the domain prefix and
+/// the leaf's key and value, or the branch's subtree hashes
+fn encode(prefix: u8, first: &H256, second: &H256) -> Vec<u8> {
+    let mut data = Vec::with_capacity(65);
+    data.push(prefix);
+    data.extend_from_slice(first.as_bytes());
+    data.extend_from_slice(second.as_bytes());
+    data
+}
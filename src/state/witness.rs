@@ -0,0 +1,53 @@
+//! Execution Witness Module
+//!
+//! What an external prover needs to re-execute a batch without holding the
+//! state: every account the batch touches, with a Merkle proof of its state
+//! against the state root right before the batch was executed, and again
+//! right after.
+//!
+//! The prover checks the pre-state proofs, applies the batch's transactions
+//! to those accounts, and checks the result against the post-state proofs.
+//! Nonces are advanced when transactions are admitted, not when they are
+//! executed, so they are taken from the post-state. Transactions admitted
+//! while the batch was executed also move nonces of their senders in between.
+
+use super::StateProof;
+use crate::AccountState;
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+
+/// Accounts with proofs of their state against one state root
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateWitness {
+    pub state_root: H256,
+    /// Proven accounts, in address order
+    pub accounts: Vec<AccountWitness>,
+}
+
+/// An account's state, or its absence, with the proof of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountWitness {
+    pub address: Address,
+    /// State of the account (`None` if it doesn't exist)
+    pub account: Option<AccountState>,
+    pub proof: StateProof,
+}
+
+/// Execution witness of a sealed batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchWitness {
+    pub batch_id: u64,
+    /// Touched accounts before the batch was executed
+    pub pre_state: StateWitness,
+    /// Touched accounts after the batch was executed
+    pub post_state: StateWitness,
+}
+
+impl StateWitness {
+    /// Whether every account's proof holds against the state root
+    pub fn verify(&self) -> bool {
+        self.accounts
+            .iter()
+            .all(|witness| witness.proof.verify(self.state_root, &witness.address, witness.account.as_ref()))
+    }
+}
@@ -0,0 +1,34 @@
+//! Shared test fixtures
+//! 
+//! Unsigned transactions for tests that do not check signatures; tests that
+//! need other fields override them with struct update syntax.
+
+use crate::UserTransaction;
+use ethers::types::{Address, Bytes, Signature, U256};
+
+/// Helper function to create a test user transaction
+pub(crate) fn create_test_tx(nonce: u64, gas_price: u64) -> UserTransaction {
+    create_test_tx_from(0, nonce, gas_price)
+}
+
+/// Helper function to create a test user transaction from a numbered sender
+pub(crate) fn create_test_tx_from(sender: u64, nonce: u64, gas_price: u64) -> UserTransaction {
+    UserTransaction {
+        from: Address::from_low_u64_be(sender),
+        to: Address::zero(),
+        value: U256::from(1000),
+        nonce,
+        gas_price: U256::from(gas_price),
+        gas_limit: 21000,
+        signature: Signature { r: U256::zero(), s: U256::zero(), v: 0 },
+        timestamp: nonce,
+        boost_bid: None,
+        chain_id: 31_337,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        data: Bytes::new(),
+        raw: None,
+        deadline: None,
+        priority_class: None,
+    }
+}
@@ -1,15 +1,18 @@
 //! Type Definitions Module
 //! 
 //! This module contains all the core data structures used throughout the sequencer:
-//! - Transaction types (normal user transactions and forced L1 transactions)
+//! - Transaction types (normal user transactions, user operations from
+//!   smart-contract wallets, and forced L1 transactions)
 //! - Account state representation
 //! - Batch structures for transaction batching
 //! - Validation error types
 //! - Soft confirmation responses
 
-use ethers::types::{Address, U256, Signature, H256};
+use ethers::abi::{encode, Token};
+use ethers::types::{transaction::eip712::EIP712Domain, Address, Bytes, U256, Signature, H256};
 use ethers::utils::keccak256;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// User transaction submitted to L2
 /// 
@@ -23,9 +26,21 @@ use serde::{Deserialize, Serialize};
 /// - `nonce`: Transaction sequence number (prevents replay attacks)
 /// - `gas_price`: Price per unit of gas (determines transaction fee)
 /// - `gas_limit`: Maximum gas units this transaction can consume
-/// - `signature`: ECDSA signature proving transaction authenticity
+/// - `signature`: ECDSA signature over the EIP-712 digest (see [`UserTransaction::signing_hash`])
 /// - `timestamp`: When the transaction was created
 /// - `boost_bid`: Optional premium bid for Time-Boost scheduling policy
+/// - `chain_id`: Chain the transaction is meant for (replay protection across deployments)
+/// - `max_fee_per_gas` / `max_priority_fee_per_gas`: EIP-1559 fee caps. When
+///   set (always together), they replace `gas_price`, which is then ignored.
+/// - `data`: Calldata for a contract call (empty for a plain transfer)
+/// - `raw`: Signed Ethereum transaction this one was decoded from, if it was
+///   submitted with `eth_sendRawTransaction`. `signature` then signs the raw
+///   transaction, not the EIP-712 digest.
+/// - `deadline`: Latest time (unix seconds) the sender wants it sealed by
+/// - `priority_class`: Named priority class, setting `deadline` on submission
+/// 
+/// `deadline` and `priority_class` are scheduling requests to this
+/// sequencer: they are not signed, and not part of the hash.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserTransaction {
     pub from: Address,
@@ -39,59 +54,187 @@ pub struct UserTransaction {
     /// Optional premium bid for Time-Boost policy (faster confirmation)
     #[serde(default)]
     pub boost_bid: Option<U256>,
+    /// Missing in transactions from before chain IDs, which are then rejected
+    #[serde(default)]
+    pub chain_id: u64,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub data: Bytes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_class: Option<String>,
 }
 
+/// Gas every transaction costs before execution (signature check, account updates)
+pub const TX_BASE_GAS: u64 = 21_000;
+
+/// Gas per zero byte of calldata
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+
+/// Gas per non-zero byte of calldata
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+
+/// EIP-712 type of [`UserTransaction`] (the signature is not part of it)
+///
+/// `boostBid` and the EIP-1559 fee fields are zero when not set.
+pub const USER_TRANSACTION_TYPE: &str = "UserTransaction(address from,address to,uint256 value,uint64 nonce,uint256 gasPrice,uint64 gasLimit,uint64 timestamp,uint256 boostBid,uint64 chainId,uint256 maxFeePerGas,uint256 maxPriorityFeePerGas,bytes data)";
+
 impl UserTransaction {
-    /// Compute the hash of the transaction for signature verification
+    /// Compute the hash identifying the transaction
     /// 
-    /// This hash is used to:
-    /// 1. Verify the ECDSA signature
-    /// 2. Uniquely identify the transaction
-    /// 
-    /// The hash is computed by concatenating all transaction fields and
-    /// applying Keccak256 (the same hash function used in Ethereum).
-    /// 
-    /// # Note
-    /// In production, this should follow EIP-712 or similar standard for
-    /// structured data hashing to improve security and user experience.
+    /// This is the EIP-712 `hashStruct` of the transaction (see
+    /// [`USER_TRANSACTION_TYPE`]). It does not depend on the signing domain,
+    /// so it identifies the transaction in the pool, batches and the registry.
     /// 
     /// # Returns
     /// A 32-byte hash (H256) uniquely identifying this transaction
     pub fn hash(&self) -> H256 {
-        // Encode all transaction fields into a byte array
-        let mut data = Vec::new();
-        
-        // Add sender address (20 bytes)
-        data.extend_from_slice(self.from.as_bytes());
-        
-        // Add recipient address (20 bytes)
-        data.extend_from_slice(self.to.as_bytes());
-        
-        // Convert value to big-endian bytes (32 bytes)
-        let mut value_bytes = [0u8; 32];
-        self.value.to_big_endian(&mut value_bytes);
-        data.extend_from_slice(&value_bytes);
-        
-        // Add nonce as big-endian bytes (8 bytes)
-        data.extend_from_slice(&self.nonce.to_be_bytes());
-        
-        // Convert gas_price to big-endian bytes (32 bytes)
-        let mut gas_price_bytes = [0u8; 32];
-        self.gas_price.to_big_endian(&mut gas_price_bytes);
-        data.extend_from_slice(&gas_price_bytes);
-        
-        // Add timestamp as big-endian bytes (8 bytes)
-        data.extend_from_slice(&self.timestamp.to_be_bytes());
-        
-        // Add boost_bid if present (32 bytes, or zeros if None)
-        let mut boost_bid_bytes = [0u8; 32];
-        if let Some(boost_bid) = self.boost_bid {
-            boost_bid.to_big_endian(&mut boost_bid_bytes);
+        let tokens = [
+            Token::FixedBytes(keccak256(USER_TRANSACTION_TYPE).to_vec()),
+            Token::Address(self.from),
+            Token::Address(self.to),
+            Token::Uint(self.value),
+            Token::Uint(self.nonce.into()),
+            Token::Uint(self.gas_price),
+            Token::Uint(self.gas_limit.into()),
+            Token::Uint(self.timestamp.into()),
+            Token::Uint(self.boost_bid.unwrap_or_default()),
+            Token::Uint(self.chain_id.into()),
+            Token::Uint(self.max_fee_per_gas.unwrap_or_default()),
+            Token::Uint(self.max_priority_fee_per_gas.unwrap_or_default()),
+            // EIP-712 encodes dynamic `bytes` as their keccak256
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+        ];
+        H256::from(keccak256(encode(&tokens)))
+    }
+    
+    /// Gas the transaction costs before any execution
+    /// 
+    /// [`TX_BASE_GAS`] plus the calldata cost, 4 gas per zero byte and 16 per
+    /// non-zero byte. A transaction whose `gas_limit` is below this can never
+    /// be included.
+    pub fn intrinsic_gas(&self) -> u64 {
+        let zero_bytes = self.data.iter().filter(|byte| **byte == 0).count() as u64;
+        let non_zero_bytes = self.data.len() as u64 - zero_bytes;
+        TX_BASE_GAS + zero_bytes * TX_DATA_ZERO_GAS + non_zero_bytes * TX_DATA_NON_ZERO_GAS
+    }
+    
+    /// Most the sender pays per gas: `max_fee_per_gas`, or `gas_price` for
+    /// legacy transactions
+    pub fn fee_cap(&self) -> U256 {
+        self.max_fee_per_gas.unwrap_or(self.gas_price)
+    }
+    
+    /// Most the transaction can cost its sender: value plus the fee cap for
+    /// the whole gas limit
+    pub fn max_cost(&self) -> U256 {
+        self.value.saturating_add(self.fee_cap().saturating_mul(U256::from(self.gas_limit)))
+    }
+    
+    /// Price per gas actually paid at `base_fee`
+    /// 
+    /// `min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)` for EIP-1559
+    /// transactions, `gas_price` for legacy ones.
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(max_priority_fee)) => max_fee.min(base_fee.saturating_add(max_priority_fee)),
+            _ => self.gas_price,
         }
-        data.extend_from_slice(&boost_bid_bytes);
-        
-        // Apply Keccak256 hash and return as H256
-        H256::from_slice(&keccak256(data))
+    }
+    
+    /// Part of the effective gas price above `base_fee` (what the sequencer earns)
+    pub fn effective_tip(&self, base_fee: U256) -> U256 {
+        self.effective_gas_price(base_fee).saturating_sub(base_fee)
+    }
+    
+    /// Compute the EIP-712 digest the sender signs
+    /// 
+    /// `keccak256(0x1901 ‖ domainSeparator ‖ hash)`, as produced by wallets'
+    /// `eth_signTypedData_v4` for the same domain and message.
+    /// 
+    /// # Arguments
+    /// * `domain` - The sequencer's signing domain (see `SigningConfig`)
+    pub fn signing_hash(&self, domain: &EIP712Domain) -> H256 {
+        let digest_input = [&[0x19, 0x01], &domain.separator()[..], self.hash().as_bytes()].concat();
+        H256::from(keccak256(digest_input))
+    }
+}
+
+/// User operation from a smart-contract wallet (ERC-4337 style)
+/// 
+/// A smart-contract wallet can't sign a [`UserTransaction`] itself, so it
+/// submits a user operation instead. The `signature` is opaque to the
+/// sequencer: the wallet's account validation hook decides whether it
+/// authorizes the operation (see `AccountValidator`). The wallet pays for gas.
+/// 
+/// # Fields
+/// - `sender`: The smart-contract wallet the operation is for
+/// - `nonce`: Wallet's sequence number
+/// - `call_data`: Call the wallet executes
+/// - `call_gas_limit`: Gas for executing `call_data`
+/// - `verification_gas_limit`: Gas for the wallet's validation
+/// - `pre_verification_gas`: Gas paid for overhead outside execution (calldata, bundling)
+/// - `max_fee_per_gas` / `max_priority_fee_per_gas`: EIP-1559 fee caps
+/// - `chain_id`: Chain the operation is meant for
+/// - `timestamp`: When the operation was created
+/// - `signature`: Wallet-specific authorization data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: u64,
+    #[serde(default)]
+    pub call_data: Bytes,
+    pub call_gas_limit: u64,
+    pub verification_gas_limit: u64,
+    pub pre_verification_gas: u64,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub chain_id: u64,
+    pub timestamp: u64,
+    pub signature: Bytes,
+}
+
+/// Type of [`UserOperation`] hashed by [`UserOperation::hash`] (the signature is not part of it)
+pub const USER_OPERATION_TYPE: &str = "UserOperation(address sender,uint64 nonce,bytes callData,uint64 callGasLimit,uint64 verificationGasLimit,uint64 preVerificationGas,uint256 maxFeePerGas,uint256 maxPriorityFeePerGas,uint64 chainId,uint64 timestamp)";
+
+impl UserOperation {
+    /// Compute the hash identifying the operation
+    /// 
+    /// Encoded like an EIP-712 `hashStruct` of [`USER_OPERATION_TYPE`]. This
+    /// is also what the wallet's owner signs.
+    pub fn hash(&self) -> H256 {
+        let tokens = [
+            Token::FixedBytes(keccak256(USER_OPERATION_TYPE).to_vec()),
+            Token::Address(self.sender),
+            Token::Uint(self.nonce.into()),
+            Token::FixedBytes(keccak256(&self.call_data).to_vec()),
+            Token::Uint(self.call_gas_limit.into()),
+            Token::Uint(self.verification_gas_limit.into()),
+            Token::Uint(self.pre_verification_gas.into()),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            Token::Uint(self.chain_id.into()),
+            Token::Uint(self.timestamp.into()),
+        ];
+        H256::from(keccak256(encode(&tokens)))
+    }
+    
+    /// Total gas the operation may use: verification, execution and overhead
+    pub fn gas_limit(&self) -> u64 {
+        self.call_gas_limit
+            .saturating_add(self.verification_gas_limit)
+            .saturating_add(self.pre_verification_gas)
+    }
+    
+    /// Most the operation can cost the wallet: the fee cap for its whole gas limit
+    pub fn max_cost(&self) -> U256 {
+        self.max_fee_per_gas.saturating_mul(U256::from(self.gas_limit()))
     }
 }
 
@@ -104,6 +247,7 @@ impl UserTransaction {
 /// # Use Cases
 /// - **Deposits**: Users deposit funds from L1 to L2
 /// - **Forced Exits**: Users withdraw funds if the sequencer is censoring them
+/// - **Messages**: L1 contracts call an L2 contract through the bridge
 /// 
 /// # Fields
 /// - `tx_hash`: Hash of this forced transaction
@@ -114,8 +258,11 @@ impl UserTransaction {
 /// - `gas_limit`: Maximum gas units this transaction can consume
 /// - `l1_tx_hash`: Hash of the originating L1 transaction
 /// - `l1_block_number`: L1 block where the event was emitted
-/// - `event_type`: Type of forced transaction (Deposit or ForcedExit)
+/// - `l1_log_index`: Index of the event's log within the L1 block
+/// - `event_type`: Type of forced transaction (Deposit, ForcedExit or Message)
 /// - `timestamp`: When the L1 event was detected
+/// - `bond`: Bond posted on L1 for an express-lane forced exit (`None` for the normal lane)
+/// - `data`: Calldata of a message for its target (empty otherwise)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForcedTransaction {
     pub tx_hash: H256,
@@ -126,8 +273,31 @@ pub struct ForcedTransaction {
     pub gas_limit: u64,
     pub l1_tx_hash: H256,
     pub l1_block_number: u64,
+    #[serde(default)]
+    pub l1_log_index: u64,
     pub event_type: ForcedEventType,
     pub timestamp: u64,
+    /// Express-lane bond; such exits are sealed into the very next batch
+    #[serde(default)]
+    pub bond: Option<U256>,
+    /// Left out when empty, so deposits and exits encode as they always did
+    #[serde(default, skip_serializing_if = "<[u8]>::is_empty")]
+    pub data: Bytes,
+}
+
+impl ForcedTransaction {
+    /// Whether this transaction uses the express lane (bonded forced exit)
+    pub fn is_express(&self) -> bool {
+        self.bond.is_some()
+    }
+    
+    /// Identity of the originating L1 event: transaction hash and log index
+    /// 
+    /// One L1 transaction can emit several bridge events, so the hash alone
+    /// is not unique.
+    pub fn l1_event_key(&self) -> (H256, u64) {
+        (self.l1_tx_hash, self.l1_log_index)
+    }
 }
 
 /// Type of forced transaction event from L1
@@ -135,27 +305,33 @@ pub struct ForcedTransaction {
 /// Distinguishes between different types of L1-originated transactions:
 /// - `Deposit`: User is depositing funds from L1 to L2
 /// - `ForcedExit`: User is forcing a withdrawal (censorship resistance)
+/// - `Message`: L1 contract calling an L2 contract with calldata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ForcedEventType {
     /// User depositing funds from L1 to their L2 account
     Deposit,
     /// User forcing a withdrawal from L2 to L1 (anti-censorship mechanism)
     ForcedExit,
+    /// Cross-domain message: `data` is executed against `to`, carrying `value`
+    Message,
 }
 
-/// Generic transaction (can be normal or forced)
+/// Generic transaction (normal, forced, or a user operation)
 /// 
 /// A unified type that can represent either:
 /// - Normal user transactions submitted via the RPC API
 /// - Forced transactions originating from L1
+/// - User operations from smart-contract wallets
 /// 
-/// This enum allows batches to contain a mix of both transaction types.
+/// This enum allows batches to contain a mix of all transaction types.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Transaction {
     /// Standard user transaction from the RPC API
     Normal(UserTransaction),
     /// Forced transaction from L1 (deposit or forced exit)
     Forced(ForcedTransaction),
+    /// User operation from a smart-contract wallet
+    UserOp(UserOperation),
 }
 
 impl Transaction {
@@ -167,6 +343,37 @@ impl Transaction {
         match self {
             Transaction::Normal(tx) => tx.gas_limit,
             Transaction::Forced(tx) => tx.gas_limit,
+            Transaction::UserOp(op) => op.gas_limit(),
+        }
+    }
+    
+    /// Check whether executing this transaction runs contract code
+    /// 
+    /// Transactions carrying calldata call into contract code, and a user
+    /// operation always runs its wallet's code. That code can read or change
+    /// any account, beyond the transaction's touched accounts.
+    pub fn calls_code(&self) -> bool {
+        match self {
+            Transaction::Normal(tx) => !tx.data.is_empty(),
+            Transaction::UserOp(_) => true,
+            Transaction::Forced(tx) => !tx.data.is_empty(),
+        }
+    }
+    
+    /// Accounts whose state executing this transaction reads or changes
+    /// 
+    /// The sender and recipient of a normal transaction, a user operation's
+    /// sender, a deposit's or message's recipient and a forced exit's sender.
+    /// Contract code the transaction runs can reach further accounts (see
+    /// [`Transaction::calls_code`]).
+    pub fn touched_accounts(&self) -> Vec<Address> {
+        match self {
+            Transaction::Normal(tx) => vec![tx.from, tx.to],
+            Transaction::UserOp(op) => vec![op.sender],
+            Transaction::Forced(tx) => match tx.event_type {
+                ForcedEventType::Deposit | ForcedEventType::Message => vec![tx.to],
+                ForcedEventType::ForcedExit => vec![tx.from],
+            },
         }
     }
 }
@@ -180,11 +387,99 @@ impl Transaction {
 /// - `address`: The account's Ethereum address
 /// - `balance`: Current balance in wei
 /// - `nonce`: Current nonce (number of transactions sent by this account)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// - `code_hash`: Hash of the account's contract code (zero without code)
+/// - `storage_root`: Root of the account's storage trie (zero without storage)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AccountState {
     pub address: Address,
     pub balance: U256,
     pub nonce: u64,
+    #[serde(default)]
+    pub code_hash: H256,
+    #[serde(default)]
+    pub storage_root: H256,
+}
+
+impl AccountState {
+    /// Compute a commitment to this account state
+    /// 
+    /// Keccak256 of address (20 bytes), balance (32 bytes, big-endian) and
+    /// nonce (8 bytes, big-endian), followed by the code hash and storage
+    /// root (32 bytes each) for contract accounts only, so the commitment of
+    /// an externally owned account is the same as before contracts existed.
+    /// Taken when an account is archived, and checked again when it is resurrected.
+    pub fn state_hash(&self) -> H256 {
+        let mut data = Vec::new();
+        data.extend_from_slice(self.address.as_bytes());
+        
+        let mut balance_bytes = [0u8; 32];
+        self.balance.to_big_endian(&mut balance_bytes);
+        data.extend_from_slice(&balance_bytes);
+        
+        data.extend_from_slice(&self.nonce.to_be_bytes());
+        if self.is_contract() {
+            data.extend_from_slice(self.code_hash.as_bytes());
+            data.extend_from_slice(self.storage_root.as_bytes());
+        }
+        H256::from_slice(&keccak256(data))
+    }
+    
+    /// Whether the account has code or storage
+    pub fn is_contract(&self) -> bool {
+        !self.code_hash.is_zero() || !self.storage_root.is_zero()
+    }
+}
+
+/// An inactive account moved out of the hot state cache
+/// 
+/// # Fields
+/// - `account`: The account state at archival time
+/// - `last_active_batch`: Last batch during which the account was modified
+/// - `archived_at_batch`: Batch after which the account was archived
+/// - `state_hash`: Commitment to `account` (see [`AccountState::state_hash`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedAccount {
+    pub account: AccountState,
+    pub last_active_batch: u64,
+    pub archived_at_batch: u64,
+    pub state_hash: H256,
+}
+
+/// The genesis a chain was started from, as recorded in the registry
+/// 
+/// # Fields
+/// - `chain_id`: Chain the genesis is for
+/// - `genesis_hash`: Hash identifying the chain (see `state::Genesis::hash`)
+/// - `state_root`: State root of the genesis allocation
+/// - `accounts`: Number of accounts allocated
+/// - `timestamp`: When the genesis was loaded (Unix seconds)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisRecord {
+    pub chain_id: u64,
+    pub genesis_hash: H256,
+    pub state_root: H256,
+    pub accounts: u64,
+    pub timestamp: u64,
+}
+
+/// Proof material recorded when an archived account is brought back
+/// 
+/// Ties the resurrected state to the commitment taken at archival, so a prover
+/// can show the account's state was carried over unchanged.
+/// 
+/// # Fields
+/// - `account`: The resurrected account state
+/// - `archived_at_batch`: Batch after which the account had been archived
+/// - `resurrected_at_batch`: Latest sealed batch when it was referenced again
+/// - `state_hash`: Commitment taken at archival
+/// - `verified`: Whether the resurrected state matches `state_hash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResurrectionWitness {
+    pub account: AccountState,
+    pub archived_at_batch: u64,
+    pub resurrected_at_batch: u64,
+    pub state_hash: H256,
+    pub verified: bool,
 }
 
 /// Sealed batch ready for execution
@@ -197,12 +492,124 @@ pub struct AccountState {
 /// - `transactions`: All transactions in this batch (normal + forced)
 /// - `prev_state_root`: State root hash before this batch (for verification)
 /// - `timestamp`: When this batch was sealed
+/// - `lanes`: The transactions grouped into lanes that touch no common
+///   account, which can execute concurrently (only if lanes are enabled)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Batch {
     pub batch_id: u64,
     pub transactions: Vec<Transaction>,
     pub prev_state_root: H256,
     pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lanes: Option<Vec<ExecutionLane>>,
+}
+
+impl Batch {
+    /// Keccak256 of the batch's compact JSON encoding
+    /// 
+    /// This is the digest the sequencer signs for externally proposed batches.
+    pub fn hash(&self) -> H256 {
+        let encoded = serde_json::to_vec(self).expect("batch serialization cannot fail");
+        H256::from_slice(&keccak256(encoded))
+    }
+}
+
+/// Transactions of a batch that can execute apart from its other lanes
+/// 
+/// No account is touched by transactions of two different lanes, so each
+/// lane can run on its own, its transactions in batch order, and the result
+/// is the same as executing the batch in order.
+/// 
+/// # Fields
+/// - `transactions`: Positions of the lane's transactions in the batch, ascending
+/// - `gas_limit`: Their total gas limit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionLane {
+    pub transactions: Vec<usize>,
+    pub gas_limit: u64,
+}
+
+/// Scheduling fairness of a sealed batch
+/// 
+/// Measured over the batch's normal transactions, taking each one's
+/// `timestamp` as its arrival time; ties keep their batch order.
+/// 
+/// # Fields
+/// - `batch_id`: The batch
+/// - `policy`: Scheduling policy that ordered it
+/// - `transactions`: Normal transactions measured
+/// - `mean_reorder_distance`: Mean number of positions a transaction moved
+///   from arrival order
+/// - `max_reorder_distance`: Most positions any transaction moved
+/// - `inversions`: Pairs of transactions placed in the opposite order of
+///   their arrival
+/// - `fee_weighted_inversions`: Inversions, each weighted by the overtaken
+///   (earlier) transaction's tip over the batch's mean tip
+/// - `mean_inclusion_latency_secs`: Mean time from arrival to the batch's timestamp
+/// - `senders`: Inclusion latency of each sender, worst mean first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchFairness {
+    pub batch_id: u64,
+    pub policy: String,
+    pub transactions: usize,
+    pub mean_reorder_distance: f64,
+    pub max_reorder_distance: usize,
+    pub inversions: u64,
+    pub fee_weighted_inversions: f64,
+    pub mean_inclusion_latency_secs: f64,
+    pub senders: Vec<SenderLatency>,
+}
+
+/// Inclusion latency of one sender's transactions in a batch
+/// 
+/// # Fields
+/// - `sender`: The sender
+/// - `transactions`: Its normal transactions in the batch
+/// - `mean_latency_secs`: Their mean time from arrival to the batch's timestamp
+/// - `max_latency_secs`: The longest such time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SenderLatency {
+    pub sender: Address,
+    pub transactions: usize,
+    pub mean_latency_secs: f64,
+    pub max_latency_secs: u64,
+}
+
+/// Externally proposed batch, sealed and signed by the sequencer
+/// 
+/// # Fields
+/// - `batch`: The sealed batch, in the proposer's order
+/// - `batch_hash`: `batch.hash()`, the signed digest
+/// - `signature`: Sequencer signature over `batch_hash`
+/// - `signer`: Sequencer address recovered from `signature`
+/// - `attestation`: The sequencer's BLS attestation of `batch_hash`, which
+///   other sequencers' attestations of the same ordering aggregate with
+///   (multi-sequencer mode only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedBatch {
+    pub batch: Batch,
+    pub batch_hash: H256,
+    pub signature: Signature,
+    pub signer: Address,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<BlsAttestation>,
+}
+
+/// BLS attestation of a digest by one or more sequencers
+/// 
+/// Attestations of the same digest aggregate into one, so a committee's
+/// agreement is checked with a single signature verification.
+/// 
+/// # Fields
+/// - `digest`: What was attested: a batch hash, or a soft confirmation digest
+/// - `signature`: BLS signature over `digest` (96 bytes, compressed), or the
+///   aggregate of the signers' signatures
+/// - `signers`: Compressed BLS public keys (48 bytes) of the signers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlsAttestation {
+    pub digest: H256,
+    pub signature: Bytes,
+    pub signers: Vec<Bytes>,
 }
 
 /// Batch metadata for registry
@@ -216,6 +623,9 @@ pub struct Batch {
 /// - `forced_tx_count`: Number of forced transactions from L1
 /// - `timestamp`: When the batch was created
 /// - `scheduling_policy`: Which policy was used ("FCFS" or "FeePriority")
+/// - `compression`: Codec the batch data posted to L1 is compressed with ("none" if not)
+/// - `raw_size`: Size of the batch's JSON encoding, in bytes
+/// - `compressed_size`: Size of the batch data posted to L1, in bytes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchMetadata {
     pub batch_id: u64,
@@ -223,6 +633,454 @@ pub struct BatchMetadata {
     pub forced_tx_count: usize,
     pub timestamp: u64,
     pub scheduling_policy: String,
+    pub compression: String,
+    pub raw_size: usize,
+    pub compressed_size: usize,
+}
+
+/// Where a sealed batch is in its L1 lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// Sealed, not yet sent to L1
+    Sealed,
+    /// Commitment sent to the rollup contract, not yet confirmed
+    Submitted,
+    /// Commitment mined and confirmed on L1
+    Committed,
+    /// Accepted by the rollup contract (`BatchVerified` or `BatchFinalized`)
+    Verified,
+    /// Commitment (or verification, if monitored) in a block the L1
+    /// `finalized` tag has reached
+    Finalized,
+    /// Commitment failed or reverted; the batch's transactions were reinjected
+    Failed,
+}
+
+/// L1 lifecycle status of a batch, stored in the database registry
+/// 
+/// # Fields
+/// - `batch_id`: The batch
+/// - `status`: Where the batch is in its lifecycle
+/// - `l1_tx_hash`: L1 transaction carrying the latest commitment, once submitted
+/// - `l1_block_number`: L1 block the commitment was mined in, once committed
+/// - `verified_tx_hash`: L1 transaction in which the rollup contract verified
+///   the batch, once verified
+/// - `verified_block_number`: L1 block of that transaction
+/// - `updated_at`: When the status last changed (Unix seconds)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchSubmission {
+    pub batch_id: u64,
+    pub status: BatchStatus,
+    pub l1_tx_hash: Option<H256>,
+    pub l1_block_number: Option<u64>,
+    pub verified_tx_hash: Option<H256>,
+    pub verified_block_number: Option<u64>,
+    pub updated_at: u64,
+}
+
+/// Where an L1 deposit is on its way to the recipient's L2 balance
+/// 
+/// Deposits move forward through `Detected`, `Confirmed`, `Included` and
+/// `Credited`. One whose batch failed goes back to `Confirmed` until it is
+/// batched again, and one whose L1 event was reorged out is `Reorged`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositStage {
+    /// Event seen on L1, waiting to be deep enough
+    Detected,
+    /// Deep enough, queued for the next batch
+    Confirmed,
+    /// Sealed into a batch; the L2 balance reflects it, the batch is not yet on L1
+    Included,
+    /// The batch crediting it is committed on L1
+    Credited,
+    /// Event reorged out of L1 before it was batched
+    Reorged,
+}
+
+impl DepositStage {
+    /// Name of the stage, as stored in the registry
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DepositStage::Detected => "detected",
+            DepositStage::Confirmed => "confirmed",
+            DepositStage::Included => "included",
+            DepositStage::Credited => "credited",
+            DepositStage::Reorged => "reorged",
+        }
+    }
+}
+
+/// Progress of an L1 deposit, stored in the database registry
+/// 
+/// # Fields
+/// - `l1_tx_hash`: Hash of the L1 transaction that emitted the deposit
+/// - `l1_log_index`: Index of the deposit's log within its L1 block
+/// - `l1_block_number`: L1 block of the deposit
+/// - `recipient`: L2 account credited
+/// - `value`: Amount deposited
+/// - `stage`: Where the deposit is
+/// - `batch_id`: Batch crediting the deposit, once included
+/// - `updated_at`: When the stage last changed (Unix seconds)
+/// - `l1_finality`: How final the deposit's L1 block is (`None` unless the
+///   L1 finality tags are tracked)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositStatus {
+    pub l1_tx_hash: H256,
+    pub l1_log_index: u64,
+    pub l1_block_number: u64,
+    pub recipient: Address,
+    pub value: U256,
+    pub stage: DepositStage,
+    pub batch_id: Option<u64>,
+    pub updated_at: u64,
+    pub l1_finality: Option<L1Finality>,
+}
+
+/// How final an L1 block is, by the node's `safe` and `finalized` tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum L1Finality {
+    /// Past the `safe` tag; may still be reorged out
+    Unsafe,
+    /// At or below the `safe` tag
+    Safe,
+    /// At or below the `finalized` tag; cannot be reorged out
+    Finalized,
+}
+
+/// The latest L1 blocks tagged `safe` and `finalized`, stored in the database registry
+/// 
+/// # Fields
+/// - `safe_block`: Latest safe block (`None` until read)
+/// - `finalized_block`: Latest finalized block (`None` until read)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityTags {
+    pub safe_block: Option<u64>,
+    pub finalized_block: Option<u64>,
+}
+
+impl FinalityTags {
+    /// How final L1 block `block_number` is
+    /// 
+    /// `None` while neither tag was read.
+    pub fn finality(&self, block_number: u64) -> Option<L1Finality> {
+        if self.finalized_block.is_some_and(|finalized| block_number <= finalized) {
+            return Some(L1Finality::Finalized);
+        }
+        if self.safe_block.is_some_and(|safe| block_number <= safe) {
+            return Some(L1Finality::Safe);
+        }
+        (self.safe_block.is_some() || self.finalized_block.is_some()).then_some(L1Finality::Unsafe)
+    }
+}
+
+/// Withdrawal to L1 produced by a forced exit
+/// 
+/// Each forced exit debited in a batch produces one withdrawal, numbered in
+/// batch order. The batch commits to its withdrawals in a Merkle root (see
+/// the batch withdrawals module), which the recipient claims against on L1.
+/// 
+/// # Fields
+/// - `batch_id`: Batch that debited the exit
+/// - `index`: Position of the withdrawal among the batch's withdrawals
+/// - `l1_tx_hash`: Hash of the L1 transaction that emitted the forced exit
+/// - `l1_log_index`: Index of the forced exit's log within its L1 block
+/// - `account`: L2 account debited
+/// - `recipient`: L1 address the amount is paid to
+/// - `amount`: Amount withdrawn
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Withdrawal {
+    pub batch_id: u64,
+    pub index: u64,
+    pub l1_tx_hash: H256,
+    pub l1_log_index: u64,
+    pub account: Address,
+    pub recipient: Address,
+    pub amount: U256,
+}
+
+impl Withdrawal {
+    /// Leaf committing to this withdrawal in its batch's withdrawal root
+    /// 
+    /// Keccak-256 of the ABI encoding of `(batch_id, index, account, recipient, amount)`.
+    pub fn leaf_hash(&self) -> H256 {
+        let encoded = encode(&[
+            Token::Uint(U256::from(self.batch_id)),
+            Token::Uint(U256::from(self.index)),
+            Token::Address(self.account),
+            Token::Address(self.recipient),
+            Token::Uint(self.amount),
+        ]);
+        H256::from(keccak256(encoded))
+    }
+}
+
+/// Observed outcome of a transaction
+/// 
+/// Recorded in the registry every time the sequencer makes a decision about a
+/// transaction, so its full history can be reconstructed later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionOutcome {
+    /// Passed validation and entered the pool
+    Accepted,
+    /// Refused at submission (validation failure or pool admission failure)
+    Rejected { reason: String },
+    /// Removed from the pool before being batched
+    Evicted,
+    /// Waited in the pool longer than the configured TTL
+    Expired,
+    /// Removed from the pool because an earlier nonce of its sender was given
+    /// back, leaving it unable to execute
+    Dropped,
+    /// Included in a sealed batch
+    Batched { batch_id: u64 },
+    /// Returned to the pool because its batch failed to post or was reverted
+    Reinjected { batch_id: u64 },
+}
+
+/// Registry record of a single transaction observation
+/// 
+/// One transaction may produce several records over its lifetime
+/// (e.g., `Accepted` followed by `Batched`).
+/// 
+/// # Fields
+/// - `tx_hash`: Hash of the observed transaction
+/// - `sender`: Sender's address
+/// - `nonce`: Sender nonce claimed by the transaction
+/// - `outcome`: What happened to the transaction
+/// - `timestamp`: When the observation was made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub tx_hash: H256,
+    pub sender: Address,
+    pub nonce: u64,
+    pub outcome: TransactionOutcome,
+    pub timestamp: u64,
+}
+
+/// API key with quota limits and usage counters
+/// 
+/// Issued through the admin API for metered RPC access. Usage counters are
+/// reset when the quota window elapses.
+/// 
+/// # Fields
+/// - `key`: The secret key clients send in the `X-Api-Key` header
+/// - `label`: Operator-chosen description (e.g., customer name)
+/// - `max_requests`: Request quota per window (`None` = unlimited)
+/// - `max_transactions`: Transaction submission quota per window (`None` = unlimited)
+/// - `requests`: Requests made in the current window
+/// - `transactions`: Transactions submitted in the current window
+/// - `window_start`: When the current quota window began
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyUsage {
+    pub key: String,
+    pub label: String,
+    pub max_requests: Option<u64>,
+    pub max_transactions: Option<u64>,
+    pub requests: u64,
+    pub transactions: u64,
+    pub window_start: u64,
+}
+
+/// Record of an express-lane bond forfeited for a spam exit
+/// 
+/// An express exit is considered spam when it cannot be honored (e.g., the exit
+/// value exceeds the sender's L2 balance). The sequencer records the forfeiture
+/// so the bond can be claimed on L1.
+/// 
+/// # Fields
+/// - `l1_tx_hash`: Hash of the L1 transaction that posted the bond
+/// - `sender`: Address that requested the exit
+/// - `bond`: Forfeited bond amount
+/// - `reason`: Why the exit was deemed spam
+/// - `batch_id`: Batch in which the exit was included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BondForfeiture {
+    pub l1_tx_hash: H256,
+    pub sender: Address,
+    pub bond: U256,
+    pub reason: String,
+    pub batch_id: u64,
+}
+
+/// A batch whose normal-transaction selection was changed by the sender guard
+/// 
+/// Under fee ordering, the orchestrator can require a minimum number of
+/// distinct senders per batch. When it swaps transactions to reach that
+/// minimum, the adjustment is recorded so its effect can be audited.
+/// 
+/// # Fields
+/// - `batch_id`: Batch whose selection was adjusted
+/// - `senders_before`: Distinct senders in the original selection
+/// - `senders_after`: Distinct senders after the adjustment
+/// - `displaced`: Transactions returned to the pool to make room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SenderGuardAdjustment {
+    pub batch_id: u64,
+    pub senders_before: usize,
+    pub senders_after: usize,
+    pub displaced: usize,
+}
+
+/// Transactions sealed contiguously and in order, or not at all, submitted with `sendBundle`
+/// 
+/// # Fields
+/// - `transactions`: The transactions, in the order they must execute
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub transactions: Vec<UserTransaction>,
+}
+
+impl Bundle {
+    /// Hash identifying the bundle: `keccak256` of its transaction hashes, in order
+    pub fn hash(&self) -> H256 {
+        let hashes: Vec<u8> = self.transactions.iter().flat_map(|tx| tx.hash().to_fixed_bytes()).collect();
+        H256::from(keccak256(hashes))
+    }
+    
+    /// Total gas limit of the bundle's transactions
+    pub fn gas_limit(&self) -> u64 {
+        self.transactions.iter().map(|tx| tx.gas_limit).sum()
+    }
+    
+    /// Number of transactions in the bundle
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+    
+    /// Check whether the bundle has no transactions
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+    
+    /// Check whether `sender` sends any of the bundle's transactions
+    pub fn involves(&self, sender: &Address) -> bool {
+        self.transactions.iter().any(|tx| tx.from == *sender)
+    }
+}
+
+/// Where a bundle was sealed
+/// 
+/// # Fields
+/// - `batch_id`: Batch the bundle was sealed in
+/// - `position`: Index of its first transaction in the batch
+/// - `bundle_hash`: Hash of the bundle (see `Bundle::hash`)
+/// - `size`: Number of transactions in the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleInclusion {
+    pub batch_id: u64,
+    pub position: usize,
+    pub bundle_hash: H256,
+    pub size: usize,
+}
+
+/// Transaction encrypted to an epoch's key, submitted with `sendEncryptedTransaction`
+/// 
+/// The sequencer orders it without seeing its contents, and decrypts it once
+/// the epoch is over and its decryption key released (see the encrypted
+/// lane in the pool module).
+/// 
+/// # Fields
+/// - `epoch`: Epoch whose decryption key opens the transaction
+/// - `gas_limit`: Gas limit of the transaction inside, public so a batch can
+///   make room for it before it is decrypted
+/// - `ephemeral_key`: The sender's one-time BLS public key (48 bytes compressed)
+/// - `ciphertext`: The JSON encoding of the `UserTransaction`, encrypted
+/// - `tag`: Authentication tag over the ciphertext
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedTransaction {
+    pub epoch: u64,
+    pub gas_limit: u64,
+    pub ephemeral_key: Bytes,
+    pub ciphertext: Bytes,
+    pub tag: H256,
+}
+
+impl EncryptedTransaction {
+    /// Compute the hash identifying the encrypted transaction
+    /// 
+    /// Keccak-256 of the ABI encoding of `(epoch, gas_limit, ephemeral_key, ciphertext, tag)`.
+    pub fn hash(&self) -> H256 {
+        let encoded = encode(&[
+            Token::Uint(U256::from(self.epoch)),
+            Token::Uint(U256::from(self.gas_limit)),
+            Token::Bytes(self.ephemeral_key.to_vec()),
+            Token::Bytes(self.ciphertext.to_vec()),
+            Token::FixedBytes(self.tag.as_bytes().to_vec()),
+        ]);
+        H256::from(keccak256(encoded))
+    }
+}
+
+/// Place an encrypted transaction was given in the encrypted lane
+/// 
+/// Returned by `sendEncryptedTransaction`, before anyone can decrypt the
+/// transaction. Decrypted transactions are sealed in lane order, so the
+/// receipt commits the sequencer to that order.
+/// 
+/// # Fields
+/// - `tx_hash`: Hash of the encrypted transaction (see [`EncryptedTransaction::hash`])
+/// - `epoch`: Epoch it is encrypted to
+/// - `position`: Its position in the lane, counted since the sequencer started
+/// - `commitment`: Hash chain over the lane up to it: `keccak256(previous || tx_hash)`,
+///   starting from zero
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionReceipt {
+    pub tx_hash: H256,
+    pub epoch: u64,
+    pub position: u64,
+    pub commitment: H256,
+}
+
+/// Sealed bid for a top-of-batch position, submitted with the `submitBid` RPC
+/// 
+/// Bids stay sealed until the batch they compete for is sealed (see the
+/// `Auction` scheduling policy).
+/// 
+/// # Fields
+/// - `tx_hash`: Pending transaction the bid places at the top of the batch
+/// - `amount`: Amount bid (in wei), owed to the sequencer if the bid wins
+/// - `signature`: Signature of the transaction's sender over [`AuctionBid::digest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionBid {
+    #[serde(rename = "txHash")]
+    pub tx_hash: H256,
+    pub amount: U256,
+    pub signature: Signature,
+}
+
+impl AuctionBid {
+    /// Digest the bidder signs
+    /// 
+    /// Keccak-256 of the ABI encoding of `(chain_id, tx_hash, amount)`, so a
+    /// bid cannot be replayed on another deployment.
+    pub fn digest(&self, chain_id: u64) -> H256 {
+        let encoded = encode(&[
+            Token::Uint(U256::from(chain_id)),
+            Token::FixedBytes(self.tx_hash.as_bytes().to_vec()),
+            Token::Uint(self.amount),
+        ]);
+        H256::from(keccak256(encoded))
+    }
+}
+
+/// Winning bid of a batch's auction, recorded for fee accounting
+/// 
+/// # Fields
+/// - `batch_id`: Batch the bid won a position in
+/// - `position`: Index of the transaction in the batch
+/// - `tx_hash`: Transaction placed there
+/// - `bidder`: Sender of the transaction, who owes the bid
+/// - `amount`: Amount bid (in wei)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionWin {
+    pub batch_id: u64,
+    pub position: usize,
+    pub tx_hash: H256,
+    pub bidder: Address,
+    pub amount: U256,
 }
 
 /// Validation errors
@@ -233,10 +1091,50 @@ pub struct BatchMetadata {
 pub enum ValidationError {
     /// Signature verification failed (transaction may be forged)
     InvalidSignature,
+    /// Signature components are out of range, so it can't be recovered from
+    MalformedSignature,
     /// Nonce doesn't match expected value (transaction out of order)
     InvalidNonce { expected: u64, got: u64 },
     /// Account doesn't have enough funds for value + gas fees
     InsufficientBalance { required: U256, available: U256 },
+    /// Transaction was signed for another chain (possible cross-deployment replay)
+    WrongChain { expected: u64, got: u64 },
+    /// Only one of the EIP-1559 fee fields is set
+    IncompleteFeeFields,
+    /// The priority fee cap exceeds the total fee cap
+    PriorityFeeAboveMaxFee { max_priority_fee_per_gas: U256, max_fee_per_gas: U256 },
+    /// The fee cap does not cover the current base fee
+    FeeCapBelowBaseFee { fee_cap: U256, base_fee: U256 },
+    /// The fee cap does not cover the pool's congestion-based fee floor
+    BelowFeeFloor { fee_cap: U256, fee_floor: U256 },
+    /// The gas limit does not cover the transaction's intrinsic gas
+    IntrinsicGasTooLow { gas_limit: u64, intrinsic_gas: u64 },
+    /// The sender already has as many transactions pending as the nonce lookahead allows
+    NonceTooFarAhead { nonce: u64, max_nonce: u64 },
+    /// The transferred value exceeds the configured maximum
+    ValueTooHigh { value: U256, max_value: U256 },
+    /// The calldata exceeds the configured maximum size
+    CalldataTooLarge { size: usize, max_size: usize },
+    /// The timestamp is further ahead of the sequencer's clock than allowed
+    TimestampTooFarAhead { timestamp: u64, max_timestamp: u64 },
+    /// The sender holds less than the L2 balance required to submit
+    BelowMinimumBalance { required: U256, available: U256 },
+    /// The sender has staked less on L1 than required to submit
+    InsufficientStake { required: U256, staked: U256 },
+    /// The sender is a contract account, which can't sign transactions
+    SenderIsContract { sender: Address },
+    /// A raw Ethereum transaction could not be decoded or is not supported
+    InvalidRawTransaction { reason: String },
+    /// The wallet's account validation hook refused the user operation
+    AccountValidationFailed { reason: String },
+    /// A BLS attestation is malformed, from outside the committee, or does not verify
+    InvalidAttestation { reason: String },
+    /// An encrypted transaction or decryption key is malformed, or does not decrypt
+    InvalidEncryption { reason: String },
+    /// The inclusion deadline or priority class cannot be honored
+    InvalidDeadline { reason: String },
+    /// A deployment-specific validation rule refused the transaction
+    RuleViolated { rule: String, reason: String },
 }
 
 /// Implements Display trait for user-friendly error messages
@@ -244,12 +1142,70 @@ impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ValidationError::InvalidSignature => write!(f, "Invalid transaction signature"),
+            ValidationError::MalformedSignature => write!(f, "Malformed transaction signature"),
             ValidationError::InvalidNonce { expected, got } => {
                 write!(f, "Invalid nonce: expected {}, got {}", expected, got)
             }
             ValidationError::InsufficientBalance { required, available } => {
                 write!(f, "Insufficient balance: required {}, available {}", required, available)
             }
+            ValidationError::WrongChain { expected, got } => {
+                write!(f, "Wrong chain ID: expected {}, got {}", expected, got)
+            }
+            ValidationError::IncompleteFeeFields => {
+                write!(f, "max_fee_per_gas and max_priority_fee_per_gas must be set together")
+            }
+            ValidationError::PriorityFeeAboveMaxFee { max_priority_fee_per_gas, max_fee_per_gas } => {
+                write!(f, "Max priority fee {} exceeds max fee {}", max_priority_fee_per_gas, max_fee_per_gas)
+            }
+            ValidationError::FeeCapBelowBaseFee { fee_cap, base_fee } => {
+                write!(f, "Fee cap {} is below the base fee of {}", fee_cap, base_fee)
+            }
+            ValidationError::BelowFeeFloor { fee_cap, fee_floor } => {
+                write!(f, "Fee cap {} is below the fee floor of {}", fee_cap, fee_floor)
+            }
+            ValidationError::IntrinsicGasTooLow { gas_limit, intrinsic_gas } => {
+                write!(f, "Gas limit {} is below the intrinsic gas of {}", gas_limit, intrinsic_gas)
+            }
+            ValidationError::NonceTooFarAhead { nonce, max_nonce } => {
+                write!(f, "Nonce {} is too far ahead of pending transactions (at most {})", nonce, max_nonce)
+            }
+            ValidationError::ValueTooHigh { value, max_value } => {
+                write!(f, "Value {} exceeds the maximum of {}", value, max_value)
+            }
+            ValidationError::CalldataTooLarge { size, max_size } => {
+                write!(f, "Calldata of {} bytes exceeds the maximum of {} bytes", size, max_size)
+            }
+            ValidationError::TimestampTooFarAhead { timestamp, max_timestamp } => {
+                write!(f, "Timestamp {} is too far in the future (at most {})", timestamp, max_timestamp)
+            }
+            ValidationError::BelowMinimumBalance { required, available } => {
+                write!(f, "Balance {} is below the {} required to submit", available, required)
+            }
+            ValidationError::InsufficientStake { required, staked } => {
+                write!(f, "L1 stake {} is below the {} required to submit", staked, required)
+            }
+            ValidationError::SenderIsContract { sender } => {
+                write!(f, "Sender {:?} is a contract account", sender)
+            }
+            ValidationError::InvalidRawTransaction { reason } => {
+                write!(f, "Invalid raw transaction: {}", reason)
+            }
+            ValidationError::AccountValidationFailed { reason } => {
+                write!(f, "Account validation failed: {}", reason)
+            }
+            ValidationError::InvalidAttestation { reason } => {
+                write!(f, "Invalid attestation: {}", reason)
+            }
+            ValidationError::InvalidEncryption { reason } => {
+                write!(f, "Invalid encryption: {}", reason)
+            }
+            ValidationError::InvalidDeadline { reason } => {
+                write!(f, "Invalid deadline: {}", reason)
+            }
+            ValidationError::RuleViolated { rule, reason } => {
+                write!(f, "Rejected by rule {}: {}", rule, reason)
+            }
         }
     }
 }
@@ -257,6 +1213,187 @@ impl std::fmt::Display for ValidationError {
 /// Implements Error trait so ValidationError can be used with anyhow and other error handling
 impl std::error::Error for ValidationError {}
 
+impl ValidationError {
+    /// Short snake_case name of the failure reason, for counting failures by kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ValidationError::InvalidSignature => "invalid_signature",
+            ValidationError::MalformedSignature => "malformed_signature",
+            ValidationError::InvalidNonce { .. } => "invalid_nonce",
+            ValidationError::InsufficientBalance { .. } => "insufficient_balance",
+            ValidationError::WrongChain { .. } => "wrong_chain",
+            ValidationError::IncompleteFeeFields => "incomplete_fee_fields",
+            ValidationError::PriorityFeeAboveMaxFee { .. } => "priority_fee_above_max_fee",
+            ValidationError::FeeCapBelowBaseFee { .. } => "fee_cap_below_base_fee",
+            ValidationError::BelowFeeFloor { .. } => "below_fee_floor",
+            ValidationError::IntrinsicGasTooLow { .. } => "intrinsic_gas_too_low",
+            ValidationError::NonceTooFarAhead { .. } => "nonce_too_far_ahead",
+            ValidationError::ValueTooHigh { .. } => "value_too_high",
+            ValidationError::CalldataTooLarge { .. } => "calldata_too_large",
+            ValidationError::TimestampTooFarAhead { .. } => "timestamp_too_far_ahead",
+            ValidationError::BelowMinimumBalance { .. } => "below_minimum_balance",
+            ValidationError::InsufficientStake { .. } => "insufficient_stake",
+            ValidationError::SenderIsContract { .. } => "sender_is_contract",
+            ValidationError::InvalidRawTransaction { .. } => "invalid_raw_transaction",
+            ValidationError::AccountValidationFailed { .. } => "account_validation_failed",
+            ValidationError::InvalidAttestation { .. } => "invalid_attestation",
+            ValidationError::InvalidEncryption { .. } => "invalid_encryption",
+            ValidationError::InvalidDeadline { .. } => "invalid_deadline",
+            ValidationError::RuleViolated { .. } => "rule_violated",
+        }
+    }
+}
+
+/// Transaction pool admission errors
+/// 
+/// Returned when a validated transaction cannot be admitted to the pool.
+/// These are distinct from `ValidationError`: the transaction itself is valid,
+/// but the pool refuses to hold it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PoolError {
+    /// Pool is at capacity and the transaction's fee is too low to evict anything
+    PoolFull { max_size: usize, min_gas_price: U256 },
+    /// An identical transaction (same hash) is already pending
+    AlreadyKnown { tx_hash: H256 },
+    /// The sender is on the pool denylist
+    Denied { sender: Address },
+    /// The gas price is below the current fee floor
+    Underpriced { gas_price: U256, min_gas_price: U256 },
+    /// Permissioned mode and the sender is not on the allowlist
+    NotAllowlisted { sender: Address },
+    /// The user operation lane holds as many operations as it may
+    UserOpLaneFull { capacity: usize },
+    /// The encrypted lane holds as many transactions as it may
+    EncryptedLaneFull { capacity: usize },
+    /// The bundle lane holds as many bundles as it may
+    BundleLaneFull { capacity: usize },
+    /// A bundle's sender has transactions pending outside the bundle
+    PendingOutsideBundle { sender: Address },
+    /// The transaction is encrypted to an epoch that is over or too far ahead
+    EpochOutOfRange { epoch: u64, min_epoch: u64, max_epoch: u64 },
+}
+
+/// Implements Display trait for user-friendly error messages
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolError::PoolFull { max_size, min_gas_price } => {
+                write!(f, "Transaction pool full ({} transactions): gas price must exceed {}", max_size, min_gas_price)
+            }
+            PoolError::AlreadyKnown { tx_hash } => {
+                write!(f, "Transaction {:?} already known", tx_hash)
+            }
+            PoolError::Denied { sender } => {
+                write!(f, "Sender {:?} is denylisted", sender)
+            }
+            PoolError::Underpriced { gas_price, min_gas_price } => {
+                write!(f, "Gas price {} is below the minimum of {}", gas_price, min_gas_price)
+            }
+            PoolError::NotAllowlisted { sender } => {
+                write!(f, "Sender {:?} is not allowlisted", sender)
+            }
+            PoolError::UserOpLaneFull { capacity } => {
+                write!(f, "User operation lane full ({} operations)", capacity)
+            }
+            PoolError::EncryptedLaneFull { capacity } => {
+                write!(f, "Encrypted lane full ({} transactions)", capacity)
+            }
+            PoolError::BundleLaneFull { capacity } => {
+                write!(f, "Bundle lane full ({} bundles)", capacity)
+            }
+            PoolError::PendingOutsideBundle { sender } => {
+                write!(f, "Sender {:?} has transactions pending outside the bundle", sender)
+            }
+            PoolError::EpochOutOfRange { epoch, min_epoch, max_epoch } => {
+                write!(f, "Epoch {} is not between {} and {}", epoch, min_epoch, max_epoch)
+            }
+        }
+    }
+}
+
+/// Implements Error trait so PoolError can be used with anyhow and other error handling
+impl std::error::Error for PoolError {}
+
+/// Reasons an externally proposed batch was rejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ProposalError {
+    /// The proposal contains no transactions
+    Empty,
+    /// The proposal has more transactions than a batch may hold
+    TooLarge { count: usize, max_batch_size: usize },
+    /// The same transaction appears twice
+    Duplicate { tx_hash: H256 },
+    /// The proposal does not start with the queued forced transactions, in L1 order
+    ForcedInclusion { expected: Vec<H256> },
+    /// A transaction is not pending, or skips one of its sender's earlier nonces
+    NotPending { tx_hash: H256 },
+    /// The transactions exceed the batch gas limit
+    GasLimitExceeded { gas: u64, max_gas_limit: u64 },
+}
+
+/// Implements Display trait for user-friendly error messages
+impl std::fmt::Display for ProposalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProposalError::Empty => write!(f, "Proposed batch is empty"),
+            ProposalError::TooLarge { count, max_batch_size } => {
+                write!(f, "Proposed batch has {} transactions, maximum is {}", count, max_batch_size)
+            }
+            ProposalError::Duplicate { tx_hash } => {
+                write!(f, "Transaction {:?} proposed twice", tx_hash)
+            }
+            ProposalError::ForcedInclusion { expected } => {
+                write!(f, "Proposed batch must start with the {} queued forced transactions", expected.len())
+            }
+            ProposalError::NotPending { tx_hash } => {
+                write!(f, "Transaction {:?} is not pending or is out of nonce order", tx_hash)
+            }
+            ProposalError::GasLimitExceeded { gas, max_gas_limit } => {
+                write!(f, "Proposed batch uses {} gas, limit is {}", gas, max_gas_limit)
+            }
+        }
+    }
+}
+
+/// Implements Error trait so ProposalError can be used with anyhow and other error handling
+impl std::error::Error for ProposalError {}
+
+/// Reasons a transaction could not be forwarded to a remote pool
+#[derive(Debug, Clone)]
+pub enum RemoteError {
+    /// The transaction was already forwarded (or is pending upstream)
+    AlreadyKnown { tx_hash: H256 },
+    /// Too many forwards in flight; the client should retry later
+    Busy { max_in_flight: usize },
+    /// The upstream sequencer refused it, with a JSON-RPC error code
+    Rpc { code: i32, message: String },
+    /// The upstream sequencer could not be reached or answered garbage
+    Unavailable { reason: String },
+}
+
+/// Implements Display trait for user-friendly error messages
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteError::AlreadyKnown { tx_hash } => {
+                write!(f, "Transaction {:?} already known", tx_hash)
+            }
+            RemoteError::Busy { max_in_flight } => {
+                write!(f, "Ingress busy ({} forwards in flight), retry later", max_in_flight)
+            }
+            RemoteError::Rpc { code, message } => {
+                write!(f, "Upstream error {}: {}", code, message)
+            }
+            RemoteError::Unavailable { reason } => {
+                write!(f, "Upstream sequencer unavailable: {}", reason)
+            }
+        }
+    }
+}
+
+/// Implements Error trait so RemoteError can be used with anyhow and other error handling
+impl std::error::Error for RemoteError {}
+
 /// Soft confirmation sent to users after validation
 /// 
 /// Provides immediate feedback to users after they submit a transaction.
@@ -267,11 +1404,161 @@ impl std::error::Error for ValidationError {}
 /// - `tx_hash`: Hash identifying the transaction
 /// - `status`: Whether the transaction was accepted or rejected
 /// - `timestamp`: When the confirmation was generated
+/// - `attestation`: The sequencer's BLS attestation of [`SoftConfirmation::digest`]
+///   (accepted transactions in multi-sequencer mode only)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SoftConfirmation {
     pub tx_hash: H256,
     pub status: ConfirmationStatus,
     pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<BlsAttestation>,
+}
+
+impl SoftConfirmation {
+    /// Digest attested for this confirmation
+    /// 
+    /// Keccak-256 of the ABI encoding of `(tx_hash, accepted, timestamp)`.
+    pub fn digest(&self) -> H256 {
+        let accepted = matches!(self.status, ConfirmationStatus::Accepted);
+        let encoded = encode(&[
+            Token::FixedBytes(self.tx_hash.as_bytes().to_vec()),
+            Token::Bool(accepted),
+            Token::Uint(U256::from(self.timestamp)),
+        ]);
+        H256::from(keccak256(encoded))
+    }
+}
+
+/// Fee estimate returned by the `estimateFee` RPC
+/// 
+/// # Fields
+/// - `min_gas_price`: Lowest gas price (or fee cap) the pool currently admits
+/// - `base_fee`: Current base fee per gas; tips are paid on top of it
+/// - `l1_base_fee`: Base fee per gas at the L1 head (with the gas oracle)
+/// - `l1_blob_base_fee`: Blob base fee at the L1 head (with the gas oracle, from Cancun)
+/// - `l1_fee_per_tx`: Estimated share of a transaction in posting a full batch to L1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub min_gas_price: U256,
+    pub base_fee: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_base_fee: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_blob_base_fee: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee_per_tx: Option<U256>,
+}
+
+/// Result of the `validateBundle` RPC
+/// 
+/// # Fields
+/// - `valid`: Whether every transaction in the bundle passed validation
+/// - `results`: Outcome of each transaction, in bundle order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleValidation {
+    pub valid: bool,
+    pub results: Vec<BundleTxResult>,
+}
+
+/// Outcome of one transaction in a validated bundle
+/// 
+/// # Fields
+/// - `tx_hash`: Hash of the transaction
+/// - `error`: Why the transaction failed validation (`None` if it passed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleTxResult {
+    pub tx_hash: H256,
+    pub error: Option<String>,
+}
+
+/// Result of the `admin_getValidationFailures` RPC
+/// 
+/// # Fields
+/// - `counts`: Failures since startup, by `ValidationError::kind`
+/// - `recent`: The most recent failures, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationFailureReport {
+    pub counts: BTreeMap<String, u64>,
+    pub recent: Vec<ValidationFailure>,
+}
+
+/// A transaction that failed validation
+/// 
+/// # Fields
+/// - `tx_hash`: Hash of the transaction
+/// - `sender`: Claimed sender of the transaction
+/// - `kind`: Failure reason, as `ValidationError::kind`
+/// - `error`: The full validation error message
+/// - `timestamp`: When validation failed (Unix seconds)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationFailure {
+    pub tx_hash: H256,
+    pub sender: Address,
+    pub kind: String,
+    pub error: String,
+    pub timestamp: u64,
+}
+
+/// What caused a [`StateChange`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateChangeKind {
+    /// Account created when first seen, with the devnet prefund if any
+    Creation,
+    /// Nonce bumped when a transaction was admitted
+    Admission,
+    /// Balance moved while sealing a batch (deposit, forced exit, transfer or fee)
+    Execution,
+    /// Account replaced from an external source
+    Sync,
+    /// Earlier changes undone (rolled-back batch or dropped transaction)
+    Undo,
+    /// Drifted account overwritten with the executor's canonical state
+    Reconciliation,
+    /// Code hash or storage of a contract account set
+    Contract,
+}
+
+/// A journaled change to an account
+/// 
+/// # Fields
+/// - `seq`: Position in the journal, increasing with every change
+/// - `address`: The account changed
+/// - `kind`: What caused the change
+/// - `tx_hash`: Transaction the change is for (`None` for syncs and batch rollbacks)
+/// - `batch_id`: Batch being sealed or rolled back (`None` outside batches)
+/// - `created`: Whether the account did not exist before
+/// - `balance_before` / `balance_after`: Balance around the change
+/// - `nonce_before` / `nonce_after`: Nonce around the change
+/// - `timestamp`: When the change was made (Unix seconds)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateChange {
+    pub seq: u64,
+    pub address: Address,
+    pub kind: StateChangeKind,
+    pub tx_hash: Option<H256>,
+    pub batch_id: Option<u64>,
+    pub created: bool,
+    pub balance_before: U256,
+    pub balance_after: U256,
+    pub nonce_before: u64,
+    pub nonce_after: u64,
+    pub timestamp: u64,
+}
+
+/// A consistent read of several accounts
+/// 
+/// # Fields
+/// - `batch_id`: Latest sealed batch when the accounts were read
+/// - `state_root`: State root at the time of the read
+/// - `accounts`: The accounts, in the order requested (unknown accounts read
+///   as they would be created)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub batch_id: u64,
+    pub state_root: H256,
+    pub accounts: Vec<AccountState>,
 }
 
 /// Status of a soft confirmation
@@ -0,0 +1,156 @@
+//! Account Abstraction Validation Module
+//!
+//! Validates user operations from smart-contract wallets. What authorizes an
+//! operation is up to each wallet, so the signature check is a pluggable
+//! [`AccountValidator`] hook (the counterpart of ERC-4337's `validateUserOp`).
+//! The sequencer itself only checks what applies to every wallet: chain, fee
+//! caps, nonce, and that the wallet can pay.
+
+use crate::{
+    config::SigningConfig,
+    pool::UserOpPool,
+    state::StateCache,
+    UserOperation, ValidationError,
+};
+use async_trait::async_trait;
+use ethers::types::{Address, Signature, U256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Account validation hook for smart-contract wallets
+///
+/// Decides whether `op.signature` authorizes the operation for `op.sender`.
+/// Failures are reported as [`ValidationError::AccountValidationFailed`].
+#[async_trait]
+pub trait AccountValidator: Send + Sync {
+    /// Check that the operation is authorized by its wallet
+    async fn validate_account(&self, op: &UserOperation) -> Result<(), ValidationError>;
+}
+
+/// Built-in hook for wallets controlled by a single owner key
+///
+/// Works like a minimal ERC-4337 account: the signature must be the owner's
+/// `personal_sign` signature of the operation hash. Wallets it doesn't know
+/// are refused.
+pub struct OwnerSignatureValidator {
+    /// Owner key of each known wallet
+    owners: HashMap<Address, Address>,
+}
+
+impl OwnerSignatureValidator {
+    /// Creates a hook knowing the given `(wallet, owner)` pairs
+    pub fn new(owners: impl IntoIterator<Item = (Address, Address)>) -> Self {
+        Self { owners: owners.into_iter().collect() }
+    }
+}
+
+#[async_trait]
+impl AccountValidator for OwnerSignatureValidator {
+    async fn validate_account(&self, op: &UserOperation) -> Result<(), ValidationError> {
+        let Some(owner) = self.owners.get(&op.sender) else {
+            return Err(ValidationError::AccountValidationFailed {
+                reason: format!("unknown wallet {:?}", op.sender),
+            });
+        };
+        let signature = Signature::try_from(op.signature.as_ref()).map_err(|_| {
+            ValidationError::AccountValidationFailed { reason: "malformed owner signature".to_string() }
+        })?;
+        match signature.recover(op.hash().as_bytes()) {
+            Ok(signer) if signer == *owner => Ok(()),
+            _ => Err(ValidationError::AccountValidationFailed {
+                reason: "not signed by the wallet owner".to_string(),
+            }),
+        }
+    }
+}
+
+/// Validator for user operations
+///
+/// Checks, in order:
+/// 1. Chain ID - the operation is meant for this deployment
+/// 2. Fee caps - consistent, and covering the base fee
+/// 3. Account validation - the wallet's hook authorizes the operation
+/// 4. Nonce - the wallet's next one
+/// 5. Balance - the wallet can pay the maximum cost of this and its pending operations
+pub struct UserOpValidator {
+    state_cache: StateCache,
+    /// Hook deciding whether a wallet authorized an operation
+    accounts: Arc<dyn AccountValidator>,
+    /// Chain ID operations must carry
+    chain_id: u64,
+    /// Current base fee per gas that fee caps must cover
+    base_fee: U256,
+    /// Lane whose pending operations count against the balance check
+    lane: Option<Arc<UserOpPool>>,
+}
+
+impl UserOpValidator {
+    /// Creates a validator using `accounts` as the account validation hook
+    pub fn new(state_cache: StateCache, accounts: Arc<dyn AccountValidator>) -> Self {
+        Self {
+            state_cache,
+            accounts,
+            chain_id: SigningConfig::default().chain_id,
+            base_fee: U256::zero(),
+            lane: None,
+        }
+    }
+
+    /// Require the chain ID of `signing`
+    pub fn with_signing(mut self, signing: &SigningConfig) -> Self {
+        self.chain_id = signing.chain_id;
+        self
+    }
+
+    /// Require fee caps to cover `base_fee` (zero by default)
+    pub fn with_base_fee(mut self, base_fee: U256) -> Self {
+        self.base_fee = base_fee;
+        self
+    }
+
+    /// Count the cost of operations pending in `lane` against wallet balances
+    pub fn with_lane(mut self, lane: Arc<UserOpPool>) -> Self {
+        self.lane = Some(lane);
+        self
+    }
+
+    /// Validate a user operation
+    ///
+    /// # Returns
+    /// * `Ok(())` if the operation passes every check
+    /// * `Err(ValidationError)` describing the first check that failed
+    pub async fn validate(&self, op: &UserOperation) -> Result<(), ValidationError> {
+        debug!("Validating user operation from {:?}", op.sender);
+        if op.chain_id != self.chain_id {
+            return Err(ValidationError::WrongChain { expected: self.chain_id, got: op.chain_id });
+        }
+        if op.max_priority_fee_per_gas > op.max_fee_per_gas {
+            return Err(ValidationError::PriorityFeeAboveMaxFee {
+                max_priority_fee_per_gas: op.max_priority_fee_per_gas,
+                max_fee_per_gas: op.max_fee_per_gas,
+            });
+        }
+        if op.max_fee_per_gas < self.base_fee {
+            return Err(ValidationError::FeeCapBelowBaseFee { fee_cap: op.max_fee_per_gas, base_fee: self.base_fee });
+        }
+
+        self.accounts.validate_account(op).await?;
+
+        let account = self.state_cache.get_account(&op.sender).await;
+        if op.nonce != account.nonce {
+            warn!("Nonce check failed for wallet {:?}: expected {}, got {}", op.sender, account.nonce, op.nonce);
+            return Err(ValidationError::InvalidNonce { expected: account.nonce, got: op.nonce });
+        }
+
+        let mut required = op.max_cost();
+        if let Some(lane) = &self.lane {
+            required = required.saturating_add(lane.pending_spend(&op.sender).await);
+        }
+        if account.balance < required {
+            warn!("Insufficient balance for wallet {:?}: required {}, available {}", op.sender, required, account.balance);
+            return Err(ValidationError::InsufficientBalance { required, available: account.balance });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! BLS Attestation Module
+//!
+//! In multi-sequencer mode every sequencer attests the orderings it seals and
+//! the soft confirmations it gives. Per-node ECDSA signatures must be checked
+//! one by one; BLS signatures (BLS12-381, public keys in G1) of the same
+//! digest add up to a single signature that is checked once against the
+//! signers' combined key.
+//!
+//! Signatures use the proof-of-possession ciphersuite of the Ethereum
+//! consensus layer. Aggregates are only as trustworthy as the committee list:
+//! it must hold keys whose owners proved possession of the secret key.
+
+use crate::{BlsAttestation, ValidationError};
+use blst::min_pk::{AggregateSignature, PublicKey, SecretKey, Signature};
+use blst::BLST_ERROR;
+use ethers::types::{Bytes, H256};
+use std::collections::HashSet;
+
+/// Domain separation tag of the proof-of-possession ciphersuite
+pub const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A sequencer's BLS key, for attesting digests
+pub struct BlsSigner {
+    secret: SecretKey,
+    public: Bytes,
+}
+
+impl BlsSigner {
+    /// Derives the key from a 32-byte seed (EIP-2333 key generation)
+    pub fn from_seed(seed: &H256) -> Result<Self, ValidationError> {
+        let secret = SecretKey::key_gen(seed.as_bytes(), &[]).map_err(|e| invalid(e, "bad key seed"))?;
+        let public = Bytes::from(secret.sk_to_pk().compress().to_vec());
+        Ok(Self { secret, public })
+    }
+
+    /// Compressed public key (48 bytes)
+    pub fn public_key(&self) -> &Bytes {
+        &self.public
+    }
+
+    /// Attest `digest` alone
+    pub fn attest(&self, digest: H256) -> BlsAttestation {
+        let signature = self.secret.sign(digest.as_bytes(), BLS_DST, &[]);
+        BlsAttestation {
+            digest,
+            signature: Bytes::from(signature.compress().to_vec()),
+            signers: vec![self.public.clone()],
+        }
+    }
+}
+
+/// Combine attestations of the same digest into one
+///
+/// # Returns
+/// * `Ok(BlsAttestation)` signed by every signer of `attestations`
+/// * `Err(ValidationError::InvalidAttestation)` if there are none, they attest
+///   different digests, or a signature is malformed
+pub fn aggregate_attestations(attestations: &[BlsAttestation]) -> Result<BlsAttestation, ValidationError> {
+    let Some(first) = attestations.first() else {
+        return Err(ValidationError::InvalidAttestation { reason: "nothing to aggregate".to_string() });
+    };
+    if attestations.iter().any(|attestation| attestation.digest != first.digest) {
+        return Err(ValidationError::InvalidAttestation { reason: "attestations of different digests".to_string() });
+    }
+    let signatures = attestations
+        .iter()
+        .map(|attestation| Signature::sig_validate(&attestation.signature, true))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(e, "malformed signature"))?;
+    let refs: Vec<&Signature> = signatures.iter().collect();
+    let aggregate = AggregateSignature::aggregate(&refs, false).map_err(|e| invalid(e, "aggregation failed"))?;
+    Ok(BlsAttestation {
+        digest: first.digest,
+        signature: Bytes::from(aggregate.to_signature().compress().to_vec()),
+        signers: attestations.iter().flat_map(|attestation| attestation.signers.iter().cloned()).collect(),
+    })
+}
+
+/// The sequencers whose attestations count
+pub struct BlsCommittee {
+    members: HashSet<Bytes>,
+    /// Distinct members an attestation needs
+    threshold: usize,
+}
+
+impl BlsCommittee {
+    /// Creates a committee of `members` (compressed public keys)
+    ///
+    /// An attestation needs `threshold` of them, or all of them if `None`.
+    pub fn new(members: &[Bytes], threshold: Option<usize>) -> Result<Self, ValidationError> {
+        for member in members {
+            PublicKey::key_validate(member).map_err(|e| invalid(e, "malformed committee key"))?;
+        }
+        let members: HashSet<Bytes> = members.iter().cloned().collect();
+        let threshold = threshold.unwrap_or(members.len());
+        if threshold == 0 || threshold > members.len() {
+            return Err(ValidationError::InvalidAttestation {
+                reason: format!("threshold {} is not between 1 and the committee size {}", threshold, members.len()),
+            });
+        }
+        Ok(Self { members, threshold })
+    }
+
+    /// Verify an attestation, typically an aggregate
+    ///
+    /// # Returns
+    /// * `Ok(())` if enough distinct committee members signed `digest`
+    /// * `Err(ValidationError::InvalidAttestation)` otherwise
+    pub fn verify(&self, attestation: &BlsAttestation) -> Result<(), ValidationError> {
+        let mut seen = HashSet::new();
+        let mut keys = Vec::with_capacity(attestation.signers.len());
+        for signer in &attestation.signers {
+            if !self.members.contains(signer) {
+                return Err(ValidationError::InvalidAttestation { reason: format!("{} is not in the committee", signer) });
+            }
+            if !seen.insert(signer) {
+                return Err(ValidationError::InvalidAttestation { reason: format!("{} signed twice", signer) });
+            }
+            keys.push(PublicKey::from_bytes(signer).map_err(|e| invalid(e, "malformed signer key"))?);
+        }
+        if keys.len() < self.threshold {
+            return Err(ValidationError::InvalidAttestation {
+                reason: format!("{} of the {} signers required", keys.len(), self.threshold),
+            });
+        }
+
+        let signature = Signature::sig_validate(&attestation.signature, true)
+            .map_err(|e| invalid(e, "malformed signature"))?;
+        let keys: Vec<&PublicKey> = keys.iter().collect();
+        match signature.fast_aggregate_verify(false, attestation.digest.as_bytes(), BLS_DST, &keys) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            e => Err(invalid(e, "signature does not verify")),
+        }
+    }
+}
+
+/// Map a blst error to `InvalidAttestation`
+fn invalid(error: BLST_ERROR, reason: &str) -> ValidationError {
+    ValidationError::InvalidAttestation { reason: format!("{} ({:?})", reason, error) }
+}
@@ -2,6 +2,32 @@
 //! 
 //! This module validates user transactions before they enter the pool.
 //! Performs signature verification, nonce checking, and balance validation.
+//! Signature checks can run in parallel on a worker pool (see [`ValidationWorkers`]).
+//! Checks are an ordered chain of [`ValidationRule`]s that deployments can extend.
+//! User operations from smart-contract wallets are validated separately, with
+//! a pluggable [`AccountValidator`] hook.
+//! Raw signed Ethereum transactions are decoded into user transactions by
+//! [`decode_raw_transaction`].
+//! Failures are counted by reason in [`ValidationTelemetry`].
+//! In multi-sequencer mode, orderings and soft confirmations are attested with
+//! aggregatable BLS signatures (see [`BlsSigner`] and [`BlsCommittee`]).
 
+mod account;
+mod bls;
+mod raw;
+mod rules;
+mod telemetry;
 mod validator;
-pub use validator::Validator;
\ No newline at end of file
+mod workers;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub use account::{AccountValidator, OwnerSignatureValidator, UserOpValidator};
+pub use bls::{aggregate_attestations, BlsCommittee, BlsSigner, BLS_DST};
+pub use raw::{decode_raw_transaction, verify_raw};
+pub use rules::ValidationRule;
+pub use telemetry::{ValidationTelemetry, DEFAULT_RECENT_FAILURES};
+pub use validator::Validator;
+pub use workers::{Turn, ValidationWorkers};
\ No newline at end of file
@@ -0,0 +1,82 @@
+//! Raw Transaction Decoding Module
+//!
+//! Decodes signed Ethereum transactions (legacy, EIP-2930 and EIP-1559, as
+//! sent with `eth_sendRawTransaction`) into the sequencer's `UserTransaction`,
+//! so standard wallets can submit without signing EIP-712 typed data.
+//!
+//! The converted transaction keeps the raw encoding. Its signature signs the
+//! raw transaction, so [`verify_raw`] checks it by decoding again instead of
+//! against the EIP-712 digest.
+
+use crate::{UserTransaction, ValidationError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Bytes, NameOrAddress};
+use ethers::utils::rlp::Rlp;
+
+/// Decode a signed Ethereum transaction
+///
+/// Recovers the signer and converts the transaction, stamped with `timestamp`.
+/// EIP-2930 access lists are dropped. The signature's `v` is normalized to 27/28.
+///
+/// # Returns
+/// * `Ok(UserTransaction)` carrying `raw`
+/// * `Err(ValidationError::InvalidRawTransaction)` if the encoding can't be
+///   decoded or recovered from, creates a contract, or is not replay-protected
+pub fn decode_raw_transaction(raw: &[u8], timestamp: u64) -> Result<UserTransaction, ValidationError> {
+    let invalid = |reason: &str| ValidationError::InvalidRawTransaction { reason: reason.to_string() };
+
+    let (tx, mut signature) = TypedTransaction::decode_signed(&Rlp::new(raw))
+        .map_err(|e| ValidationError::InvalidRawTransaction { reason: e.to_string() })?;
+    let from = signature.recover(tx.sighash()).map_err(|_| ValidationError::InvalidSignature)?;
+    signature.v = 27 + u64::from(signature.recovery_id().map_err(|_| ValidationError::MalformedSignature)?.to_byte());
+
+    let to = match tx.to() {
+        Some(NameOrAddress::Address(to)) => *to,
+        Some(NameOrAddress::Name(_)) => return Err(invalid("recipient must be an address")),
+        None => return Err(invalid("contract creation is not supported")),
+    };
+    let chain_id = tx.chain_id().ok_or_else(|| invalid("transaction is not replay-protected (no chain ID)"))?;
+    let (max_fee_per_gas, max_priority_fee_per_gas) = match &tx {
+        TypedTransaction::Eip1559(tx) => (tx.max_fee_per_gas, tx.max_priority_fee_per_gas),
+        _ => (None, None),
+    };
+    let nonce = tx.nonce().copied().unwrap_or_default();
+    let gas_limit = tx.gas().copied().unwrap_or_default();
+    if nonce.bits() > 64 || gas_limit.bits() > 64 || chain_id.bits() > 64 {
+        return Err(invalid("nonce, gas limit or chain ID out of range"));
+    }
+
+    Ok(UserTransaction {
+        from,
+        to,
+        value: tx.value().copied().unwrap_or_default(),
+        nonce: nonce.as_u64(),
+        // Ignored when the EIP-1559 fee caps are set
+        gas_price: tx.gas_price().unwrap_or_default(),
+        gas_limit: gas_limit.as_u64(),
+        signature,
+        timestamp,
+        boost_bid: None,
+        chain_id: chain_id.as_u64(),
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        data: tx.data().cloned().unwrap_or_default(),
+        raw: Some(Bytes::from(raw.to_vec())),
+        deadline: None,
+        priority_class: None,
+    })
+}
+
+/// Check that `tx` is exactly what its raw encoding decodes to
+///
+/// # Returns
+/// * `Ok(())` if the raw transaction was signed by `tx.from` and matches every field
+/// * `Err(ValidationError::InvalidSignature)` otherwise
+pub fn verify_raw(tx: &UserTransaction, raw: &[u8]) -> Result<(), ValidationError> {
+    let decoded = decode_raw_transaction(raw, tx.timestamp)?;
+    // The hash covers every field but the signature, including the sender
+    if decoded.hash() != tx.hash() || decoded.signature != tx.signature {
+        return Err(ValidationError::InvalidSignature);
+    }
+    Ok(())
+}
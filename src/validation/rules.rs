@@ -0,0 +1,67 @@
+//! Validation Rules Module
+//!
+//! Validation is an ordered chain of [`ValidationRule`]s. The built-in checks
+//! are rules like any other, so a deployment can switch some off (see
+//! `ValidationConfig`) and downstream rollups can add their own with
+//! [`Validator::with_rule`] instead of forking the validator.
+//!
+//! A rule has a stateless and a stateful half, matching the two validation
+//! stages. The stateless halves of all rules run before any stateful half.
+
+use super::Validator;
+use crate::{config::BuiltinRule, UserTransaction, ValidationError};
+use async_trait::async_trait;
+
+/// One check in the validation chain
+///
+/// Both halves pass by default, so a rule only implements the stage it needs.
+/// Custom rules report failures as [`ValidationError::RuleViolated`].
+#[async_trait]
+pub trait ValidationRule: Send + Sync {
+    /// Name identifying the rule in logs
+    fn name(&self) -> &str;
+
+    /// Check that needs no account state
+    ///
+    /// Runs on a validation worker thread, so it must not block on I/O.
+    fn check_stateless(&self, _validator: &Validator, _tx: &UserTransaction) -> Result<(), ValidationError> {
+        Ok(())
+    }
+
+    /// Check against account state, reached through `validator`
+    ///
+    /// Runs in submission order, after every rule's stateless check passed.
+    async fn check_state(&self, _validator: &Validator, _tx: &UserTransaction) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ValidationRule for BuiltinRule {
+    fn name(&self) -> &str {
+        BuiltinRule::name(self)
+    }
+
+    fn check_stateless(&self, validator: &Validator, tx: &UserTransaction) -> Result<(), ValidationError> {
+        match self {
+            BuiltinRule::Format => validator.check_format(tx),
+            BuiltinRule::Limits => validator.check_limits(tx),
+            BuiltinRule::ChainId => validator.check_chain_id(tx),
+            BuiltinRule::Fees => validator.check_fees(tx),
+            BuiltinRule::IntrinsicGas => validator.check_intrinsic_gas(tx),
+            BuiltinRule::Signature => validator.verify_signature(tx),
+            BuiltinRule::Stake | BuiltinRule::ExternallyOwned | BuiltinRule::Nonce | BuiltinRule::Balance => Ok(()),
+        }
+    }
+
+    async fn check_state(&self, validator: &Validator, tx: &UserTransaction) -> Result<(), ValidationError> {
+        match self {
+            BuiltinRule::Fees => validator.check_fee_floor(tx).await,
+            BuiltinRule::Stake => validator.check_stake(tx).await,
+            BuiltinRule::ExternallyOwned => validator.check_sender_code(tx).await,
+            BuiltinRule::Nonce => validator.check_nonce(tx).await,
+            BuiltinRule::Balance => validator.check_balance(tx).await,
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Validation Telemetry Module
+//!
+//! Counts validation failures by reason and keeps the most recent ones, so
+//! operators can tell a spike in bad nonces from one in bad signatures or
+//! unfunded senders at a glance. The counts are served on `/metrics`, and the
+//! counts and recent failures by the `admin_getValidationFailures` RPC.
+
+use crate::{UserTransaction, ValidationError, ValidationFailure, ValidationFailureReport};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Recent failures kept by default
+pub const DEFAULT_RECENT_FAILURES: usize = 256;
+
+/// Validation failure counters and recent-failure ring buffer
+pub struct ValidationTelemetry {
+    inner: Mutex<Inner>,
+    /// Most recent failures kept
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Failures since startup, by `ValidationError::kind`
+    counts: BTreeMap<&'static str, u64>,
+    /// Most recent failures, oldest first
+    recent: VecDeque<ValidationFailure>,
+}
+
+impl ValidationTelemetry {
+    /// Creates empty telemetry keeping the last `capacity` failures
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            capacity,
+        }
+    }
+
+    /// Record that `tx` failed validation with `error`
+    pub fn record(&self, tx: &UserTransaction, error: &ValidationError) {
+        let failure = ValidationFailure {
+            tx_hash: tx.hash(),
+            sender: tx.from,
+            kind: error.kind().to_string(),
+            error: error.to_string(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        let mut inner = self.inner.lock().unwrap();
+        *inner.counts.entry(error.kind()).or_insert(0) += 1;
+        if self.capacity == 0 {
+            return;
+        }
+        if inner.recent.len() >= self.capacity {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(failure);
+    }
+
+    /// Failure counts and the recent failures, newest first
+    pub fn report(&self) -> ValidationFailureReport {
+        let inner = self.inner.lock().unwrap();
+        ValidationFailureReport {
+            counts: inner.counts.iter().map(|(kind, count)| (kind.to_string(), *count)).collect(),
+            recent: inner.recent.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Render the failure counts as a Prometheus counter
+    pub fn to_prometheus(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP sequencer_validation_failures_total Transactions that failed validation, by reason");
+        let _ = writeln!(out, "# TYPE sequencer_validation_failures_total counter");
+        for (kind, count) in &inner.counts {
+            let _ = writeln!(out, "sequencer_validation_failures_total{{reason=\"{}\"}} {}", kind, count);
+        }
+        out
+    }
+}
@@ -0,0 +1,558 @@
+//! Tests for transaction validation
+//! 
+//! Verifies:
+//! - Nonces only queue a bounded distance behind pending transactions
+//! - Pending spend counts against the balance
+//! - Validating creates no account
+//! - Rules can be disabled and extended
+//! - Policy limits, stake gates, deadlines and priority classes are enforced
+//! - Transactions signed for another chain are refused
+//! - Gas limits cover the intrinsic gas, calldata priced per byte
+//! - Contract accounts cannot send
+//! - Bundles are validated in order against simulated state
+//! - User operations go through the account hook
+//! - Failures are counted by reason
+//! - BLS attestations aggregate and verify against the committee
+//! - Raw transactions decode to their signer
+//! - Validation workers hand off in submission order, past failed turns
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::{create_test_tx, create_test_tx_from},
+        pool::{TransactionPool, UserOpPool},
+        config::{BuiltinRule, DeadlineConfig, PriorityClass, SigningConfig, ValidationConfig},
+        state::StateCache,
+        validation::{aggregate_attestations, decode_raw_transaction, BlsCommittee, BlsSigner, OwnerSignatureValidator, UserOpValidator, ValidationRule, ValidationWorkers, Validator},
+        AccountState, PoolError, UserOperation, UserTransaction, ValidationError,
+    };
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Address, Bytes, H256, U256};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[test]
+    fn test_deadlines_and_priority_classes_are_checked_on_admission() {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let config = DeadlineConfig {
+            enabled: true,
+            min_tip_wei: 5,
+            classes: vec![PriorityClass { name: "express".to_string(), max_delay_secs: 10, min_tip_wei: 20 }],
+            ..DeadlineConfig::default()
+        };
+        let validator = Validator::new(StateCache::new()).with_deadlines(&config);
+        let with_deadline = |deadline: u64, gas_price: u64| UserTransaction { deadline: Some(deadline), ..create_test_tx(0, gas_price) };
+        
+        validator.check_limits(&with_deadline(now + 60, 10)).unwrap();
+        let too_soon = validator.check_limits(&with_deadline(now + 1, 10));
+        assert!(matches!(too_soon, Err(ValidationError::InvalidDeadline { .. })));
+        let underpriced = validator.check_limits(&with_deadline(now + 60, 4));
+        assert!(matches!(underpriced, Err(ValidationError::InvalidDeadline { .. })));
+        
+        // A class sets a deadline unless the transaction's own is earlier, and asks its own tip
+        let mut tx = UserTransaction { priority_class: Some("express".to_string()), ..create_test_tx(0, 10) };
+        validator.resolve_priority_class(&mut tx).unwrap();
+        assert!(tx.deadline.is_some_and(|deadline| deadline >= now + 10 && deadline <= now + 11));
+        assert!(matches!(validator.check_limits(&tx), Err(ValidationError::InvalidDeadline { .. })));
+        tx.gas_price = U256::from(20);
+        validator.check_limits(&tx).unwrap();
+        let mut tx = UserTransaction { deadline: Some(now + 7), priority_class: Some("express".to_string()), ..create_test_tx(0, 20) };
+        validator.resolve_priority_class(&mut tx).unwrap();
+        assert_eq!(tx.deadline, Some(now + 7));
+        tx.priority_class = Some("unknown".to_string());
+        assert!(validator.resolve_priority_class(&mut tx).is_err());
+        
+        // Nodes not offering deadlines refuse them
+        let disabled = Validator::new(StateCache::new()).with_deadlines(&DeadlineConfig::default());
+        assert!(matches!(disabled.check_limits(&with_deadline(now + 60, 10)), Err(ValidationError::InvalidDeadline { .. })));
+        disabled.check_limits(&create_test_tx(0, 10)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nonce_check_limits_queueing_behind_pending_transactions() {
+        let pool = Arc::new(TransactionPool::default());
+        let state_cache = StateCache::new();
+        state_cache.update(AccountState { address: Address::zero(), balance: U256::exp10(18), nonce: 0, ..Default::default() }).await;
+        let validator = Validator::new(state_cache.clone()).with_pool(pool.clone(), 2);
+        
+        // Nonces 1 and 2 queue behind pending nonce 0
+        for nonce in 0..3 {
+            let tx = create_test_tx(nonce, 10);
+            validator.check_state(&tx).await.unwrap();
+            let tx_hash = tx.hash();
+            pool.add(tx).await.unwrap();
+            state_cache.increment_nonce(&Address::zero(), tx_hash).await;
+        }
+        assert_eq!(pool.pending_nonces(&Address::zero()).await, vec![0, 1, 2]);
+        
+        let result = validator.check_state(&create_test_tx(3, 10)).await;
+        assert!(matches!(result, Err(ValidationError::NonceTooFarAhead { nonce: 3, max_nonce: 2 })));
+        
+        // Batching nonce 0 makes room; stale and skipped nonces are still refused
+        pool.take(1).await;
+        validator.check_state(&create_test_tx(3, 10)).await.unwrap();
+        let result = validator.check_state(&create_test_tx(2, 10)).await;
+        assert!(matches!(result, Err(ValidationError::InvalidNonce { expected: 3, got: 2 })));
+        let result = validator.check_state(&create_test_tx(4, 10)).await;
+        assert!(matches!(result, Err(ValidationError::InvalidNonce { expected: 3, got: 4 })));
+    }
+
+    #[tokio::test]
+    async fn test_balance_check_counts_pending_spend() {
+        let pool = Arc::new(TransactionPool::default());
+        let state_cache = StateCache::new();
+        // Each transaction costs 1000 + 10 * 21000 = 211_000; the balance covers two
+        state_cache.update(AccountState { address: Address::zero(), balance: U256::from(422_000), nonce: 0, ..Default::default() }).await;
+        let validator = Validator::new(state_cache.clone()).with_pool(pool.clone(), 64);
+        
+        for nonce in 0..2 {
+            let tx = create_test_tx(nonce, 10);
+            validator.check_state(&tx).await.unwrap();
+            let tx_hash = tx.hash();
+            pool.add(tx).await.unwrap();
+            state_cache.increment_nonce(&Address::zero(), tx_hash).await;
+        }
+        assert_eq!(pool.pending_spend(&Address::zero()).await, U256::from(422_000));
+        
+        let result = validator.check_state(&create_test_tx(2, 10)).await;
+        assert!(matches!(result, Err(ValidationError::InsufficientBalance { required, .. }) if required == U256::from(633_000)));
+        
+        // Spend is released as pending transactions leave the pool
+        pool.take(1).await;
+        assert_eq!(pool.pending_spend(&Address::zero()).await, U256::from(211_000));
+        validator.check_state(&create_test_tx(2, 10)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_validation_creates_no_accounts() {
+        let state_cache = StateCache::new().with_prefund(U256::exp10(18));
+        let validator = Validator::new(state_cache.clone());
+        let root = state_cache.state_root();
+        
+        // An unknown sender is checked as it would be created, prefund included
+        validator.check_state(&create_test_tx(0, 10)).await.unwrap();
+        let result = validator.check_state(&create_test_tx(5, 10)).await;
+        assert!(matches!(result, Err(ValidationError::InvalidNonce { expected: 0, got: 5 })));
+        assert_eq!(state_cache.get_nonce(&Address::zero()).await, None);
+        assert_eq!(state_cache.state_root(), root);
+        
+        // Admission creates it
+        state_cache.increment_nonce(&Address::zero(), create_test_tx(0, 10).hash()).await;
+        assert_eq!(state_cache.get_nonce(&Address::zero()).await, Some(1));
+        assert_eq!(state_cache.get_balance(&Address::zero()).await, Some(U256::exp10(18)));
+    }
+
+    /// Custom rule refusing transfers to one address
+    struct BlockedRecipient(Address);
+
+    #[async_trait::async_trait]
+    impl ValidationRule for BlockedRecipient {
+        fn name(&self) -> &str {
+            "blocked_recipient"
+        }
+
+        async fn check_state(&self, _validator: &Validator, tx: &UserTransaction) -> Result<(), ValidationError> {
+            if tx.to == self.0 {
+                return Err(ValidationError::RuleViolated { rule: self.name().to_string(), reason: "blocked".to_string() });
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validation_rules_can_be_disabled_and_extended() {
+        let unfunded = create_test_tx(0, 10);
+        let validator = Validator::new(StateCache::new());
+        assert!(matches!(validator.check_state(&unfunded).await, Err(ValidationError::InsufficientBalance { .. })));
+        
+        let validator = Validator::new(StateCache::new())
+            .with_config(&ValidationConfig { disabled_rules: vec![BuiltinRule::Balance], ..ValidationConfig::default() })
+            .with_rule(Arc::new(BlockedRecipient(Address::from_low_u64_be(0xbad))));
+        validator.check_state(&unfunded).await.unwrap();
+        
+        let mut blocked = create_test_tx(0, 10);
+        blocked.to = Address::from_low_u64_be(0xbad);
+        let result = validator.check_state(&blocked).await;
+        assert!(matches!(result, Err(ValidationError::RuleViolated { rule, .. }) if rule == "blocked_recipient"));
+    }
+
+    #[test]
+    fn test_policy_limits_reject_large_transactions() {
+        let config = ValidationConfig {
+            max_value_wei: Some(1000),
+            max_calldata_bytes: Some(4),
+            max_timestamp_skew_secs: Some(60),
+            ..ValidationConfig::default()
+        };
+        let validator = Validator::new(StateCache::new()).with_config(&config);
+        validator.check_limits(&create_test_tx(0, 10)).unwrap();
+        
+        let mut tx = create_test_tx(0, 10);
+        tx.value = U256::from(1001);
+        assert!(matches!(validator.check_limits(&tx), Err(ValidationError::ValueTooHigh { .. })));
+        
+        let mut tx = create_test_tx(0, 10);
+        tx.data = Bytes::from(vec![0u8; 5]);
+        assert!(matches!(validator.check_limits(&tx), Err(ValidationError::CalldataTooLarge { size: 5, max_size: 4 })));
+        
+        let mut tx = create_test_tx(0, 10);
+        tx.timestamp = u64::MAX;
+        assert!(matches!(validator.check_limits(&tx), Err(ValidationError::TimestampTooFarAhead { .. })));
+        
+        // Unset limits are not enforced
+        Validator::new(StateCache::new()).check_limits(&tx).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transactions_signed_for_another_chain_are_rejected() {
+        let state_cache = StateCache::new().with_prefund(U256::exp10(18));
+        let validator = Validator::new(state_cache).with_signing(&SigningConfig { chain_id: 31_337, ..SigningConfig::default() });
+        let wallet = LocalWallet::from_bytes(&[7u8; 32]).unwrap();
+        let sign = |chain_id: u64| {
+            let signing = SigningConfig { chain_id, ..SigningConfig::default() };
+            let mut tx = UserTransaction { from: wallet.address(), chain_id, ..create_test_tx(0, 10) };
+            tx.signature = wallet.sign_hash(tx.signing_hash(&signing.domain())).unwrap();
+            tx
+        };
+        
+        validator.validate(&sign(31_337)).await.unwrap();
+        let result = validator.validate(&sign(1)).await;
+        assert!(matches!(result, Err(ValidationError::WrongChain { expected: 31_337, got: 1 })));
+    }
+
+    #[test]
+    fn test_gas_limit_must_cover_intrinsic_gas() {
+        // Unsigned test transactions: skip the signature rules
+        let config = ValidationConfig {
+            disabled_rules: vec![BuiltinRule::Format, BuiltinRule::Signature],
+            ..ValidationConfig::default()
+        };
+        let validator = Validator::new(StateCache::new()).with_config(&config);
+        let mut tx = create_test_tx(0, 10);
+        assert_eq!(tx.intrinsic_gas(), 21_000);
+        validator.check_stateless(&tx).unwrap();
+        
+        tx.gas_limit = 20_999;
+        let result = validator.check_intrinsic_gas(&tx);
+        assert!(matches!(result, Err(ValidationError::IntrinsicGasTooLow { gas_limit: 20_999, intrinsic_gas: 21_000 })));
+        assert!(matches!(validator.check_stateless(&tx), Err(ValidationError::IntrinsicGasTooLow { .. })));
+    }
+
+    #[test]
+    fn test_intrinsic_gas_prices_calldata_by_byte() {
+        // 4 gas per zero byte, 16 per non-zero byte
+        let tx = UserTransaction { data: Bytes::from(vec![0x00, 0xa9, 0x00, 0x00, 0xff]), ..create_test_tx(0, 10) };
+        assert_eq!(tx.intrinsic_gas(), 21_000 + 3 * 4 + 2 * 16);
+        let tx = UserTransaction { data: Bytes::from(vec![0x00; 10]), ..create_test_tx(0, 10) };
+        assert_eq!(tx.intrinsic_gas(), 21_040);
+        let tx = UserTransaction { data: Bytes::from(vec![0x01; 10]), ..create_test_tx(0, 10) };
+        assert_eq!(tx.intrinsic_gas(), 21_160);
+    }
+
+    #[tokio::test]
+    async fn test_stake_gate_requires_minimum_balance_and_l1_stake() {
+        let state_cache = StateCache::new();
+        let config = ValidationConfig {
+            min_sender_balance_wei: Some(1000),
+            min_sender_stake_wei: Some(500),
+            ..ValidationConfig::default()
+        };
+        let validator = Validator::new(state_cache.clone()).with_config(&config);
+        let tx = create_test_tx(0, 10);
+        
+        let result = validator.check_stake(&tx).await;
+        assert!(matches!(result, Err(ValidationError::BelowMinimumBalance { .. })));
+        
+        state_cache.update(AccountState { address: Address::zero(), balance: U256::from(1000), nonce: 0, ..Default::default() }).await;
+        let result = validator.check_stake(&tx).await;
+        assert!(matches!(result, Err(ValidationError::InsufficientStake { staked, .. }) if staked.is_zero()));
+        
+        state_cache.add_stake(Address::zero(), U256::from(500)).await;
+        validator.check_stake(&tx).await.unwrap();
+        
+        // Unstaking drops the sender below the minimum again
+        state_cache.remove_stake(&Address::zero(), U256::from(1)).await;
+        assert!(matches!(validator.check_stake(&tx).await, Err(ValidationError::InsufficientStake { .. })));
+        
+        // Without minimums everyone may submit
+        Validator::new(StateCache::new()).check_stake(&tx).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transactions_from_contract_accounts_are_rejected() {
+        let state_cache = StateCache::new();
+        let validator = Validator::new(state_cache.clone());
+        let tx = create_test_tx(0, 10);
+        validator.check_sender_code(&tx).await.unwrap();
+
+        state_cache.set_code_hash(&tx.from, H256::repeat_byte(0xc0)).await;
+        let result = validator.check_sender_code(&tx).await;
+        assert!(matches!(result, Err(ValidationError::SenderIsContract { sender }) if sender == tx.from));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_validation_applies_transactions_in_order() {
+        let state_cache = StateCache::new();
+        state_cache.update(AccountState { address: Address::zero(), balance: U256::from(422_000), nonce: 0, ..Default::default() }).await;
+        // Unsigned test transactions: skip the signature rules
+        let config = ValidationConfig {
+            disabled_rules: vec![BuiltinRule::Format, BuiltinRule::Signature],
+            ..ValidationConfig::default()
+        };
+        let validator = Validator::new(state_cache.clone()).with_config(&config);
+        
+        // Each costs 211_000, so the balance covers two; the gap at nonce 3 fails on its own
+        let bundle = vec![create_test_tx(0, 10), create_test_tx(1, 10), create_test_tx(2, 10), create_test_tx(4, 10)];
+        let results = validator.validate_bundle(&bundle).await;
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(ValidationError::InsufficientBalance { .. })));
+        assert!(matches!(results[3], Err(ValidationError::InvalidNonce { expected: 2, got: 4 })));
+        
+        // The real state is untouched
+        assert_eq!(state_cache.get_nonce(&Address::zero()).await, Some(0));
+        assert_eq!(state_cache.get_balance(&Address::zero()).await, Some(U256::from(422_000)));
+    }
+
+    #[tokio::test]
+    async fn test_user_operations_are_validated_by_the_account_hook() {
+        let owner = LocalWallet::from_bytes(&[7u8; 32]).unwrap();
+        let wallet = Address::from_low_u64_be(0xaa);
+        let state_cache = StateCache::new();
+        // Enough for one operation of 121_000 gas at 10 wei
+        state_cache.update(AccountState { address: wallet, balance: U256::from(1_210_000), nonce: 0, ..Default::default() }).await;
+        let lane = Arc::new(UserOpPool::new(8));
+        let validator = UserOpValidator::new(state_cache.clone(), Arc::new(OwnerSignatureValidator::new([(wallet, owner.address())])))
+            .with_lane(lane.clone());
+        
+        let mut op = UserOperation {
+            sender: wallet,
+            nonce: 0,
+            call_data: Bytes::from(vec![1, 2, 3]),
+            call_gas_limit: 50_000,
+            verification_gas_limit: 50_000,
+            pre_verification_gas: 21_000,
+            max_fee_per_gas: U256::from(10),
+            max_priority_fee_per_gas: U256::from(1),
+            chain_id: 31_337,
+            timestamp: 0,
+            signature: Bytes::new(),
+        };
+        let result = validator.validate(&op).await;
+        assert!(matches!(result, Err(ValidationError::AccountValidationFailed { .. })));
+        
+        let stranger = LocalWallet::from_bytes(&[8u8; 32]).unwrap();
+        op.signature = stranger.sign_message(op.hash().as_bytes()).await.unwrap().to_vec().into();
+        let result = validator.validate(&op).await;
+        assert!(matches!(result, Err(ValidationError::AccountValidationFailed { .. })));
+        
+        op.signature = owner.sign_message(op.hash().as_bytes()).await.unwrap().to_vec().into();
+        validator.validate(&op).await.unwrap();
+        lane.add(op.clone()).await.unwrap();
+        assert!(matches!(lane.add(op.clone()).await, Err(PoolError::AlreadyKnown { .. })));
+        state_cache.increment_nonce(&wallet, op.hash()).await;
+        
+        // The pending operation's cost counts against the wallet's balance
+        op.nonce = 1;
+        op.signature = owner.sign_message(op.hash().as_bytes()).await.unwrap().to_vec().into();
+        let result = validator.validate(&op).await;
+        assert!(matches!(result, Err(ValidationError::InsufficientBalance { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_validation_failures_are_counted_by_reason() {
+        let state_cache = StateCache::new();
+        state_cache.update(AccountState { address: Address::zero(), balance: U256::from(1_000_000), nonce: 0, ..Default::default() }).await;
+        let validator = Validator::new(state_cache);
+        // Unsigned, and the sender's next nonce is 0
+        let unsigned = create_test_tx(0, 10);
+        let out_of_order = create_test_tx(5, 10);
+        assert!(validator.check_stateless(&unsigned).is_err());
+        assert!(validator.check_state(&out_of_order).await.is_err());
+        assert!(validator.check_state(&out_of_order).await.is_err());
+        validator.check_state(&unsigned).await.unwrap();
+        
+        // Simulated bundles are not counted
+        validator.validate_bundle(std::slice::from_ref(&out_of_order)).await;
+        
+        let report = validator.telemetry().report();
+        assert_eq!(report.counts.get("malformed_signature"), Some(&1));
+        assert_eq!(report.counts.get("invalid_nonce"), Some(&2));
+        assert_eq!(report.recent.len(), 3);
+        assert_eq!(report.recent[0].kind, "invalid_nonce");
+        assert_eq!(report.recent[2].tx_hash, unsigned.hash());
+        
+        let metrics = validator.telemetry().to_prometheus();
+        assert!(metrics.contains("sequencer_validation_failures_total{reason=\"invalid_nonce\"} 2"));
+    }
+
+    /// Custom rule making one sender's stateless check slow
+    struct SlowSender(Address, Duration);
+
+    #[async_trait::async_trait]
+    impl ValidationRule for SlowSender {
+        fn name(&self) -> &str {
+            "slow_sender"
+        }
+
+        fn check_stateless(&self, _validator: &Validator, tx: &UserTransaction) -> Result<(), ValidationError> {
+            if tx.from == self.0 {
+                std::thread::sleep(self.1);
+            }
+            Ok(())
+        }
+    }
+
+    /// Workers validating unsigned, unfunded test transactions
+    fn create_workers(slow_sender: u64) -> Arc<ValidationWorkers> {
+        let config = ValidationConfig {
+            disabled_rules: vec![BuiltinRule::Format, BuiltinRule::Signature, BuiltinRule::Balance],
+            ..ValidationConfig::default()
+        };
+        let validator = Validator::new(StateCache::new())
+            .with_config(&config)
+            .with_rule(Arc::new(SlowSender(Address::from_low_u64_be(slow_sender), Duration::from_millis(200))));
+        Arc::new(ValidationWorkers::new(Arc::new(validator), 4))
+    }
+
+    #[tokio::test]
+    async fn test_later_submissions_wait_for_earlier_turns() {
+        let workers = create_workers(0xff);
+        let turn = workers.validate(&create_test_tx_from(1, 0, 10)).await.unwrap();
+        let later = tokio::spawn({
+            let workers = workers.clone();
+            async move { workers.validate(&create_test_tx_from(2, 0, 10)).await.map(drop) }
+        });
+        
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!later.is_finished());
+        drop(turn);
+        timeout(Duration::from_secs(1), later).await.unwrap().unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_and_abandoned_turns_let_later_submissions_through() {
+        let workers = create_workers(0xff);
+        let turn = workers.validate(&create_test_tx_from(1, 0, 10)).await.unwrap();
+        
+        // One fails before its turn, another's client goes away while waiting
+        let wrong_chain = UserTransaction { chain_id: 1, ..create_test_tx_from(2, 0, 10) };
+        assert!(matches!(workers.validate(&wrong_chain).await, Err(ValidationError::WrongChain { .. })));
+        let abandoned = tokio::spawn({
+            let workers = workers.clone();
+            async move { workers.validate(&create_test_tx_from(3, 0, 10)).await.map(drop) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        abandoned.abort();
+        assert!(abandoned.await.unwrap_err().is_cancelled());
+        
+        let later = tokio::spawn({
+            let workers = workers.clone();
+            async move { workers.validate(&create_test_tx_from(4, 0, 10)).await.map(drop) }
+        });
+        drop(turn);
+        timeout(Duration::from_secs(1), later).await.unwrap().unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_slow_earlier_submissions_are_handed_off_first() {
+        let workers = create_workers(1);
+        let handed_off = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for sender in [1, 2] {
+            let (workers, handed_off) = (workers.clone(), handed_off.clone());
+            tasks.push(tokio::spawn(async move {
+                let turn = workers.validate(&create_test_tx_from(sender, 0, 10)).await.unwrap();
+                handed_off.lock().unwrap().push(sender);
+                drop(turn);
+            }));
+            // Let it take its turn before the next one arrives
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        for task in tasks {
+            timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+        }
+        
+        // The second one's checks finished first, but it waited for the first
+        assert_eq!(*handed_off.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_bls_attestations_aggregate_and_verify_against_committee() {
+        let signers: Vec<BlsSigner> = (1..=3u64).map(|i| BlsSigner::from_seed(&H256::from_low_u64_be(i)).unwrap()).collect();
+        let keys: Vec<Bytes> = signers.iter().map(|signer| signer.public_key().clone()).collect();
+        let committee = BlsCommittee::new(&keys, Some(2)).unwrap();
+        let digest = H256::from_low_u64_be(0xba7c);
+        
+        let aggregate = aggregate_attestations(&[signers[0].attest(digest), signers[2].attest(digest)]).unwrap();
+        assert_eq!(aggregate.signers, vec![keys[0].clone(), keys[2].clone()]);
+        committee.verify(&aggregate).unwrap();
+        
+        // Below the threshold, or signed over something else
+        assert!(committee.verify(&signers[1].attest(digest)).is_err());
+        let mut wrong_digest = aggregate.clone();
+        wrong_digest.digest = H256::from_low_u64_be(0xbad);
+        assert!(matches!(committee.verify(&wrong_digest), Err(ValidationError::InvalidAttestation { .. })));
+        
+        // Signers must be distinct committee members
+        let duplicate = aggregate_attestations(&[signers[0].attest(digest), signers[0].attest(digest)]).unwrap();
+        assert!(committee.verify(&duplicate).is_err());
+        let outsider = BlsSigner::from_seed(&H256::from_low_u64_be(4)).unwrap();
+        let with_outsider = aggregate_attestations(&[signers[0].attest(digest), outsider.attest(digest)]).unwrap();
+        assert!(committee.verify(&with_outsider).is_err());
+        
+        // Only attestations of the same digest aggregate
+        let mixed = [signers[0].attest(digest), signers[1].attest(H256::zero())];
+        assert!(aggregate_attestations(&mixed).is_err());
+        assert!(BlsCommittee::new(&keys, Some(4)).is_err());
+    }
+
+    #[test]
+    fn test_raw_transactions_decode_to_their_signer() {
+        use ethers::types::{transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, TransactionRequest};
+        
+        let wallet = LocalWallet::from_bytes(&[9u8; 32]).unwrap().with_chain_id(31_337u64);
+        let sign = |tx: TypedTransaction| tx.rlp_signed(&wallet.sign_transaction_sync(&tx).unwrap());
+        let recipient = Address::from_low_u64_be(0xbb);
+        let validator = Validator::new(StateCache::new());
+        
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(recipient)
+            .value(1000)
+            .nonce(3)
+            .gas(21_000)
+            .max_fee_per_gas(20)
+            .max_priority_fee_per_gas(2)
+            .chain_id(31_337)
+            .into();
+        let legacy: TypedTransaction = TransactionRequest::new()
+            .to(recipient)
+            .value(1000)
+            .nonce(3)
+            .gas(21_000)
+            .gas_price(20)
+            .chain_id(31_337)
+            .into();
+        for raw in [sign(eip1559.clone()), sign(legacy)] {
+            let tx = decode_raw_transaction(&raw, 0).unwrap();
+            assert_eq!(tx.from, wallet.address());
+            assert_eq!((tx.to, tx.nonce, tx.chain_id), (recipient, 3, 31_337));
+            assert_eq!(tx.raw.as_ref(), Some(&raw));
+            validator.check_stateless(&tx).unwrap();
+            
+            // Fields must match what was signed
+            let mut tampered = tx.clone();
+            tampered.value = U256::from(1001);
+            assert!(matches!(validator.verify_signature(&tampered), Err(ValidationError::InvalidSignature)));
+        }
+        
+        let mut creation = eip1559;
+        if let TypedTransaction::Eip1559(request) = &mut creation {
+            request.to = None;
+        }
+        let result = decode_raw_transaction(&sign(creation), 0);
+        assert!(matches!(result, Err(ValidationError::InvalidRawTransaction { .. })));
+        assert!(matches!(decode_raw_transaction(&[0x02, 0xc0], 0), Err(ValidationError::InvalidRawTransaction { .. })));
+    }
+}
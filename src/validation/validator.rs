@@ -1,39 +1,212 @@
 //! Transaction Validator Module
 //! 
 //! This module is responsible for validating user transactions before they
-//! are accepted into the transaction pool. It performs three main checks:
-//! 1. Signature verification - ensures the transaction is signed by the claimed sender
-//! 2. Nonce validation - ensures transactions are processed in order
-//! 3. Balance verification - ensures the sender has sufficient funds
+//! are accepted into the transaction pool. Validation runs in two stages.
+//! 
+//! The stateless stage is synchronous and touches no account state, so
+//! garbage is rejected before any state lock is taken:
+//! 1. Format - ensures the signature is well-formed before recovering it
+//! 2. Limits - enforces the configured value, calldata, timestamp and deadline limits
+//! 3. Chain ID - ensures the transaction was meant for this deployment
+//! 4. Fee fields - ensures EIP-1559 fee caps are consistent and cover the base fee
+//! 5. Intrinsic gas - ensures the gas limit covers the transaction's fixed cost
+//! 6. Signature verification - ensures the transaction is signed by the claimed sender
+//! 
+//! The stateful stage reads the state cache (and the pool, if set):
+//! 7. Fee floor - ensures the fee cap covers the pool's congestion-based fee floor
+//! 8. Stake - ensures the sender holds the configured minimum balance and L1 stake
+//! 9. Externally owned - ensures the sender is an account without code or storage
+//! 10. Nonce validation - ensures transactions are processed in order
+//! 11. Balance verification - ensures the sender has sufficient funds
+//! 
+//! Each check is a built-in [`ValidationRule`]; deployments can disable them
+//! or append their own (see [`Validator::with_rule`]).
+//! 
+//! The stages are separate methods so they can be pipelined: stateless checks
+//! of many transactions run in parallel while stateful checks run in arrival
+//! order (see `ValidationWorkers`).
+//! 
+//! Failures of either stage are recorded in the validator's
+//! [`ValidationTelemetry`].
 
-use crate::{UserTransaction, ValidationError, state::StateCache};
+use super::{verify_raw, ValidationRule, ValidationTelemetry, DEFAULT_RECENT_FAILURES};
+use crate::{
+    UserTransaction, ValidationError,
+    config::{BuiltinRule, DeadlineConfig, SigningConfig, ValidationConfig},
+    pool::{BundlePool, TransactionPool},
+    state::StateCache,
+};
 use anyhow::Result;
-use ethers::types::U256;
+use ethers::types::{transaction::eip712::EIP712Domain, U256};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, warn};
 
+/// Half the secp256k1 curve order, the largest `s` a signature may have
+/// (0x7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0, little-endian limbs)
+const SECP256K1_HALF_ORDER: U256 = U256([0xdfe92f46681b20a0, 0x5d576e7357a4501d, 0xffffffffffffffff, 0x7fffffffffffffff]);
+
 /// The transaction validator
 /// 
 /// Validates transactions against the current state before they enter the pool.
 /// Uses the state cache to check account nonces and balances.
+#[derive(Clone)]
 pub struct Validator {
     state_cache: StateCache,
+    /// EIP-712 domain signatures are checked against
+    domain: EIP712Domain,
+    /// Chain ID transactions must carry
+    chain_id: u64,
+    /// Current base fee per gas that fee caps must cover
+    base_fee: U256,
+    /// Pool whose pending transactions count against nonce and balance checks
+    pool: Option<Arc<TransactionPool>>,
+    /// Nonces a sender may queue past its earliest pending transaction
+    nonce_lookahead: u64,
+    /// Bundle lane whose pending transactions count against balance checks
+    bundles: Option<Arc<BundlePool>>,
+    /// Validation chain, run in order within each stage
+    rules: Vec<Arc<dyn ValidationRule>>,
+    /// Largest value a transaction may transfer
+    max_value: Option<U256>,
+    /// Largest calldata a transaction may carry, in bytes
+    max_calldata_bytes: Option<usize>,
+    /// Seconds a timestamp may be ahead of the local clock
+    max_timestamp_skew_secs: Option<u64>,
+    /// L2 balance a sender must hold to submit
+    min_sender_balance: Option<U256>,
+    /// L1 stake a sender must have to submit
+    min_sender_stake: Option<U256>,
+    /// Inclusion deadlines offered (transactions with one are refused if `None`)
+    deadlines: Option<DeadlineConfig>,
+    /// Failure counters and recent failures, shared between clones
+    telemetry: Arc<ValidationTelemetry>,
 }
 
 impl Validator {
     /// Creates a new validator with access to the state cache
     /// 
+    /// Signatures and chain IDs are checked against the default signing
+    /// domain; see [`Validator::with_signing`].
+    /// 
     /// # Arguments
     /// * `state_cache` - The state cache for looking up account data
     pub fn new(state_cache: StateCache) -> Self {
-        Self { state_cache }
+        let signing = SigningConfig::default();
+        Self {
+            state_cache,
+            domain: signing.domain(),
+            chain_id: signing.chain_id,
+            base_fee: U256::zero(),
+            pool: None,
+            nonce_lookahead: u64::MAX,
+            bundles: None,
+            rules: BuiltinRule::ALL
+                .into_iter()
+                .map(|rule| Arc::new(rule) as Arc<dyn ValidationRule>)
+                .collect(),
+            max_value: None,
+            max_calldata_bytes: None,
+            max_timestamp_skew_secs: None,
+            min_sender_balance: None,
+            min_sender_stake: None,
+            deadlines: None,
+            telemetry: Arc::new(ValidationTelemetry::new(DEFAULT_RECENT_FAILURES)),
+        }
+    }
+    
+    /// Check signatures in the EIP-712 domain described by `signing`, and
+    /// require its chain ID
+    pub fn with_signing(mut self, signing: &SigningConfig) -> Self {
+        self.domain = signing.domain();
+        self.chain_id = signing.chain_id;
+        self
+    }
+    
+    /// Require fee caps to cover `base_fee` (zero by default)
+    pub fn with_base_fee(mut self, base_fee: U256) -> Self {
+        self.base_fee = base_fee;
+        self
+    }
+    
+    /// Check nonces and balances against `pool`'s pending transactions
+    /// 
+    /// A sender may queue at most `nonce_lookahead` nonces past its earliest
+    /// pending one, and its pending transactions' cost counts against its balance.
+    pub fn with_pool(mut self, pool: Arc<TransactionPool>, nonce_lookahead: u64) -> Self {
+        self.pool = Some(pool);
+        self.nonce_lookahead = nonce_lookahead;
+        self
+    }
+    
+    /// Count the cost of the sender's transactions pending in `bundles` against its balance
+    pub fn with_bundles(mut self, bundles: Arc<BundlePool>) -> Self {
+        self.bundles = Some(bundles);
+        self
+    }
+    
+    /// Skip the built-in rules disabled in `config`, and enforce its limits
+    pub fn with_config(mut self, config: &ValidationConfig) -> Self {
+        self.max_value = config.max_value_wei.map(U256::from);
+        self.max_calldata_bytes = config.max_calldata_bytes;
+        self.max_timestamp_skew_secs = config.max_timestamp_skew_secs;
+        self.min_sender_balance = config.min_sender_balance_wei.map(U256::from);
+        self.min_sender_stake = config.min_sender_stake_wei.map(U256::from);
+        for disabled in &config.disabled_rules {
+            warn!("Validation rule {} is disabled", disabled.name());
+            self.rules.retain(|rule| rule.name() != disabled.name());
+        }
+        self
+    }
+    
+    /// Accept inclusion deadlines and priority classes, if enabled in `config`
+    pub fn with_deadlines(mut self, config: &DeadlineConfig) -> Self {
+        self.deadlines = config.enabled.then(|| config.clone());
+        self
+    }
+    
+    /// Append a custom rule to the validation chain
+    /// 
+    /// It runs after the built-in rules of each stage.
+    pub fn with_rule(mut self, rule: Arc<dyn ValidationRule>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+    
+    /// Account state the stateful checks read
+    pub fn state_cache(&self) -> &StateCache {
+        &self.state_cache
+    }
+    
+    /// Pool whose pending transactions the stateful checks count, if set
+    pub fn pool(&self) -> Option<&Arc<TransactionPool>> {
+        self.pool.as_ref()
+    }
+    
+    /// Base fee per gas that fee caps must cover
+    pub fn base_fee(&self) -> U256 {
+        self.base_fee
+    }
+    
+    /// Validation failures recorded so far
+    pub fn telemetry(&self) -> &ValidationTelemetry {
+        &self.telemetry
     }
     
     /// Validate a user transaction
     /// 
     /// Performs a comprehensive validation of the transaction by checking:
-    /// 1. Signature validity - is this transaction signed by the claimed sender?
-    /// 2. Nonce correctness - is this the next expected transaction from this account?
-    /// 3. Sufficient balance - does the account have enough funds for value + gas?
+    /// 1. Format - is the signature well-formed?
+    /// 2. Limits - are value, calldata size, timestamp and deadline within policy?
+    /// 3. Chain ID - is this transaction meant for this chain?
+    /// 4. Fee fields - are the fee caps consistent, and do they cover the base fee?
+    /// 5. Intrinsic gas - does the gas limit cover the transaction's fixed cost?
+    /// 6. Signature validity - is this transaction signed by the claimed sender?
+    /// 7. Fee floor - does the fee cap cover the pool's current fee floor?
+    /// 8. Stake - does the sender hold the minimum balance and L1 stake to submit?
+    /// 9. Externally owned - is the sender an account without code or storage?
+    /// 10. Nonce correctness - is this the next expected transaction from this account?
+    /// 11. Sufficient balance - does the account have enough funds for value + gas?
     /// 
     /// # Arguments
     /// * `tx` - The transaction to validate
@@ -43,22 +216,287 @@ impl Validator {
     /// * `Err(ValidationError)` if any validation check fails
     pub async fn validate(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
         debug!("Validating transaction from {:?}", tx.from);
+        self.check_stateless(tx)?;
+        self.check_state(tx).await?;
+        debug!("Transaction validation successful");
+        Ok(())
+    }
+    
+    /// Validate a bundle of transactions as if applied one after another
+    /// 
+    /// Each transaction is checked against the state left by the ones before
+    /// it: a valid transaction bumps its sender's nonce, charges its maximum
+    /// cost and credits its value to the recipient. Invalid ones leave the
+    /// simulated state untouched. Nothing is admitted to the pool and the
+    /// state cache is not modified.
+    /// 
+    /// # Returns
+    /// The validation result of each transaction, in bundle order
+    pub async fn validate_bundle(&self, txs: &[UserTransaction]) -> Vec<Result<(), ValidationError>> {
+        // Scratch copy of every account the bundle touches
+        let scratch = StateCache::new();
+        for address in txs.iter().flat_map(|tx| [tx.from, tx.to]) {
+            if scratch.get_balance(&address).await.is_none() {
+                scratch.update(self.state_cache.get_account(&address).await).await;
+                scratch.add_stake(address, self.state_cache.get_stake(&address).await).await;
+            }
+        }
+        // Simulated failures are not real submissions, so they are not recorded
+        let simulator = Validator {
+            state_cache: scratch.clone(),
+            telemetry: Arc::new(ValidationTelemetry::new(0)),
+            ..self.clone()
+        };
         
-        // Step 1: Verify the cryptographic signature
-        // This ensures the transaction was actually signed by the private key
-        // corresponding to the 'from' address
-        self.verify_signature(tx)?;
-        
-        // Step 2: Check the nonce (transaction sequence number)
-        // This ensures transactions are processed in order and prevents replay attacks
-        self.check_nonce(tx).await?;
-        
-        // Step 3: Check the account balance
-        // This ensures the sender has enough funds to cover both the transfer value
-        // and the gas costs
-        self.check_balance(tx).await?;
+        let mut results = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let result = simulator.validate(tx).await;
+            if result.is_ok() {
+                let mut sender = scratch.get_or_init_account(&tx.from).await;
+                sender.nonce += 1;
+                sender.balance = sender.balance.saturating_sub(tx.max_cost());
+                scratch.update(sender).await;
+                
+                let mut recipient = scratch.get_or_init_account(&tx.to).await;
+                recipient.balance = recipient.balance.saturating_add(tx.value);
+                scratch.update(recipient).await;
+            }
+            results.push(result);
+        }
+        results
+    }
+    
+    /// Run the stateless stage: every rule's stateless check, in order
+    /// 
+    /// With the default rules these are steps 1-6 of [`Validator::validate`],
+    /// cheapest first. They include signature recovery, the CPU-bound part of
+    /// validation, so they can run on a worker thread (see `ValidationWorkers`).
+    pub fn check_stateless(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let result = self.rules.iter().try_for_each(|rule| rule.check_stateless(self, tx));
+        self.record(tx, result)
+    }
+    
+    /// Run the stateful stage: every rule's check against account state, in order
+    /// 
+    /// With the default rules these are steps 7-11 of [`Validator::validate`].
+    pub async fn check_state(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let mut result = Ok(());
+        for rule in &self.rules {
+            result = rule.check_state(self, tx).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        self.record(tx, result)
+    }
+    
+    /// Record a failed stage in the telemetry, passing the result through
+    fn record(&self, tx: &UserTransaction, result: Result<(), ValidationError>) -> Result<(), ValidationError> {
+        if let Err(error) = &result {
+            self.telemetry.record(tx, error);
+        }
+        result
+    }
+    
+    /// Check that the signature is well-formed
+    /// 
+    /// `r` and `s` must be non-zero, `s` must be in the lower half of the curve
+    /// order (as in EIP-2, so a signature can't be altered into a second valid
+    /// one), and `v` must be a recovery ID (0/1 or 27/28).
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the signature can be recovered from
+    /// * `Err(ValidationError::MalformedSignature)` otherwise
+    pub fn check_format(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let signature = &tx.signature;
+        if signature.r.is_zero()
+            || signature.s.is_zero()
+            || signature.s > SECP256K1_HALF_ORDER
+            || !matches!(signature.v, 0 | 1 | 27 | 28)
+        {
+            warn!("Malformed signature from {:?}", tx.from);
+            return Err(ValidationError::MalformedSignature);
+        }
+        Ok(())
+    }
+    
+    /// Check the transaction against the configured policy limits
+    /// 
+    /// Each limit is only enforced if set (see `ValidationConfig`).
+    /// 
+    /// # Returns
+    /// * `Ok(())` if value, calldata size, timestamp and deadline are within the limits
+    /// * `Err(ValidationError)` describing the first limit exceeded
+    pub fn check_limits(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        if let Some(max_value) = self.max_value
+            && tx.value > max_value
+        {
+            return Err(ValidationError::ValueTooHigh { value: tx.value, max_value });
+        }
+        if let Some(max_size) = self.max_calldata_bytes
+            && tx.data.len() > max_size
+        {
+            return Err(ValidationError::CalldataTooLarge { size: tx.data.len(), max_size });
+        }
+        if let Some(skew) = self.max_timestamp_skew_secs {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let max_timestamp = now.saturating_add(skew);
+            if tx.timestamp > max_timestamp {
+                warn!("Timestamp check failed for {:?}: {} is past {}", tx.from, tx.timestamp, max_timestamp);
+                return Err(ValidationError::TimestampTooFarAhead { timestamp: tx.timestamp, max_timestamp });
+            }
+        }
+        self.check_deadline(tx)
+    }
+    
+    /// Set the deadline of a transaction naming a priority class
+    /// 
+    /// The class's deadline is `max_delay_secs` from now, or the
+    /// transaction's own deadline if that is earlier.
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the transaction names no class, or one that is configured
+    /// * `Err(ValidationError::InvalidDeadline)` otherwise
+    pub fn resolve_priority_class(&self, tx: &mut UserTransaction) -> Result<(), ValidationError> {
+        let Some(name) = &tx.priority_class else {
+            return Ok(());
+        };
+        let class = self.deadlines
+            .as_ref()
+            .and_then(|deadlines| deadlines.class(name))
+            .ok_or_else(|| ValidationError::InvalidDeadline { reason: format!("unknown priority class {}", name) })?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let deadline = now.saturating_add(class.max_delay_secs);
+        tx.deadline = Some(tx.deadline.map_or(deadline, |own| own.min(deadline)));
+        Ok(())
+    }
+    
+    /// Check that a deadline can be honored and is paid for
+    /// 
+    /// It must be at least `min_lead_secs` away, and the tip at the base fee
+    /// must cover the priority class's `min_tip_wei`, or the configured one
+    /// for explicit deadlines. Transactions without a deadline pass.
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the deadline is acceptable
+    /// * `Err(ValidationError::InvalidDeadline)` otherwise
+    pub fn check_deadline(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let Some(deadline) = tx.deadline else {
+            return Ok(());
+        };
+        let invalid = |reason: String| Err(ValidationError::InvalidDeadline { reason });
+        let Some(config) = &self.deadlines else {
+            return invalid("deadlines are not offered".to_string());
+        };
+        let min_tip = match &tx.priority_class {
+            Some(name) => match config.class(name) {
+                Some(class) => class.min_tip_wei,
+                None => return invalid(format!("unknown priority class {}", name)),
+            },
+            None => config.min_tip_wei,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let earliest = now.saturating_add(config.min_lead_secs);
+        if deadline < earliest {
+            return invalid(format!("{} is earlier than {}", deadline, earliest));
+        }
+        let tip = tx.effective_tip(self.base_fee);
+        if tip < U256::from(min_tip) {
+            return invalid(format!("tip {} is below the {} it requires", tip, min_tip));
+        }
+        Ok(())
+    }
+    
+    /// Check that the transaction is meant for this chain
+    /// 
+    /// The chain ID is also part of the signed data, so it cannot be changed
+    /// without invalidating the signature.
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the chain ID matches the configured one
+    /// * `Err(ValidationError::WrongChain)` otherwise
+    pub fn check_chain_id(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        if tx.chain_id != self.chain_id {
+            warn!("Chain ID check failed for {:?}: expected {}, got {}", tx.from, self.chain_id, tx.chain_id);
+            return Err(ValidationError::WrongChain {
+                expected: self.chain_id,
+                got: tx.chain_id,
+            });
+        }
+        Ok(())
+    }
+    
+    /// Check the fee fields against each other and the base fee
+    /// 
+    /// EIP-1559 transactions must set both caps, with the priority fee cap not
+    /// above the total. Every transaction's fee cap (`gas_price` for legacy
+    /// ones) must cover the current base fee.
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the fees are acceptable
+    /// * `Err(ValidationError)` describing the first problem found
+    pub fn check_fees(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+            (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) if max_priority_fee_per_gas > max_fee_per_gas => {
+                return Err(ValidationError::PriorityFeeAboveMaxFee { max_priority_fee_per_gas, max_fee_per_gas });
+            }
+            (Some(_), None) | (None, Some(_)) => return Err(ValidationError::IncompleteFeeFields),
+            _ => {}
+        }
         
-        debug!("Transaction validation successful");
+        let fee_cap = tx.fee_cap();
+        if fee_cap < self.base_fee {
+            warn!("Fee check failed for {:?}: fee cap {} below base fee {}", tx.from, fee_cap, self.base_fee);
+            return Err(ValidationError::FeeCapBelowBaseFee { fee_cap, base_fee: self.base_fee });
+        }
+        Ok(())
+    }
+    
+    /// Check that the fee cap covers the pool's fee floor
+    /// 
+    /// The floor rises as the pool fills up and as batches leave transactions
+    /// behind (see `DynamicFeeFloorConfig`), so under congestion transactions
+    /// that would only wait in the pool are refused up front. Only enforced
+    /// with a pool (see [`Validator::with_pool`]); local senders are exempt.
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the fee cap covers the floor
+    /// * `Err(ValidationError::BelowFeeFloor)` otherwise
+    pub async fn check_fee_floor(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        if pool.is_local(&tx.from) {
+            return Ok(());
+        }
+        let fee_floor = pool.fee_floor().await;
+        let fee_cap = tx.fee_cap();
+        if fee_cap < fee_floor {
+            warn!("Fee floor check failed for {:?}: fee cap {} below floor {}", tx.from, fee_cap, fee_floor);
+            return Err(ValidationError::BelowFeeFloor { fee_cap, fee_floor });
+        }
+        Ok(())
+    }
+    
+    /// Check that the gas limit covers the intrinsic gas
+    /// 
+    /// # Returns
+    /// * `Ok(())` if `gas_limit` is at least [`UserTransaction::intrinsic_gas`]
+    /// * `Err(ValidationError::IntrinsicGasTooLow)` otherwise
+    pub fn check_intrinsic_gas(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let intrinsic_gas = tx.intrinsic_gas();
+        if tx.gas_limit < intrinsic_gas {
+            return Err(ValidationError::IntrinsicGasTooLow { gas_limit: tx.gas_limit, intrinsic_gas });
+        }
         Ok(())
     }
     
@@ -68,20 +506,27 @@ impl Validator {
     /// by the private key corresponding to the 'from' address.
     /// 
     /// # Process
-    /// 1. Compute the hash of the transaction
+    /// 1. Compute the EIP-712 digest of the transaction in the validator's domain
     /// 2. Recover the public key/address from the signature
     /// 3. Compare the recovered address with the 'from' field
     /// 
+    /// A transaction decoded from a raw Ethereum transaction is instead
+    /// checked against that (see [`verify_raw`]).
+    /// 
     /// # Returns
     /// * `Ok(())` if the signature is valid
     /// * `Err(ValidationError::InvalidSignature)` if signature recovery fails or doesn't match
-    fn verify_signature(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
-        // Hash the transaction data
-        let tx_hash = tx.hash();
+    pub fn verify_signature(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        if let Some(raw) = &tx.raw {
+            return verify_raw(tx, raw);
+        }
+        
+        // Hash the transaction as typed data, the way the wallet did
+        let digest = tx.signing_hash(&self.domain);
         
         // Recover the signer's address from the signature
         // This uses ECDSA recovery which is a standard cryptographic operation
-        let recovered_address = tx.signature.recover(tx_hash)
+        let recovered_address = tx.signature.recover(digest)
             .map_err(|_| ValidationError::InvalidSignature)?;
         
         // Verify that the recovered address matches the claimed sender
@@ -94,11 +539,64 @@ impl Validator {
         Ok(())
     }
     
+    /// Check that the sender may submit at all
+    /// 
+    /// On cheap fee markets spam costs next to nothing, so a deployment can
+    /// require senders to hold a minimum L2 balance, or to have staked a
+    /// minimum on L1 (as recorded by the L1 listener), or both. Each minimum is
+    /// only enforced if set (see `ValidationConfig`).
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the sender meets every configured minimum
+    /// * `Err(ValidationError::BelowMinimumBalance)` if its L2 balance is too low
+    /// * `Err(ValidationError::InsufficientStake)` if its L1 stake is too low
+    pub async fn check_stake(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        if let Some(required) = self.min_sender_balance {
+            let available = self.state_cache.get_balance(&tx.from).await.unwrap_or_default();
+            if available < required {
+                warn!("Sender {:?} holds {} of the {} required to submit", tx.from, available, required);
+                return Err(ValidationError::BelowMinimumBalance { required, available });
+            }
+        }
+        if let Some(required) = self.min_sender_stake {
+            let staked = self.state_cache.get_stake(&tx.from).await;
+            if staked < required {
+                warn!("Sender {:?} staked {} of the {} required to submit", tx.from, staked, required);
+                return Err(ValidationError::InsufficientStake { required, staked });
+            }
+        }
+        Ok(())
+    }
+    
+    /// Check that the sender is not a contract account
+    /// 
+    /// A contract account has no private key, so a transaction signed for it
+    /// can only come from a key whose address collides with the contract's
+    /// (EIP-3607). Contracts act through user operations instead.
+    /// 
+    /// # Returns
+    /// * `Ok(())` if the sender has no code or storage
+    /// * `Err(ValidationError::SenderIsContract)` otherwise
+    pub async fn check_sender_code(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        let account = self.state_cache.get_account(&tx.from).await;
+        if account.is_contract() {
+            warn!("Transaction from contract account {:?}", tx.from);
+            return Err(ValidationError::SenderIsContract { sender: tx.from });
+        }
+        Ok(())
+    }
+    
     /// Check if the transaction nonce is valid
     /// 
     /// The nonce is a sequence number that ensures transactions from an account
     /// are processed in order. Each transaction must have a nonce equal to the
-    /// current account nonce.
+    /// current account nonce, which already counts the sender's pending
+    /// transactions, so nonce N+1 can be queued while N is pending.
+    /// 
+    /// With a pool (see [`Validator::with_pool`]), the sender's pending nonces
+    /// are consulted too: a nonce more than `nonce_lookahead` past the earliest
+    /// pending one is refused, so one sender can't fill the pool with a long
+    /// chain that only clears one batch at a time.
     /// 
     /// # Why nonces are important
     /// - Prevents replay attacks (reusing the same transaction)
@@ -108,9 +606,10 @@ impl Validator {
     /// # Returns
     /// * `Ok(())` if the nonce matches the expected value
     /// * `Err(ValidationError::InvalidNonce)` if the nonce is incorrect
-    async fn check_nonce(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
-        // Get the current account state from the cache
-        let account = self.state_cache.get_or_init_account(&tx.from).await;
+    /// * `Err(ValidationError::NonceTooFarAhead)` if it is past the lookahead
+    pub async fn check_nonce(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        // Get the current account state from the cache; validating creates no account
+        let account = self.state_cache.get_account(&tx.from).await;
         let expected_nonce = account.nonce;
         
         // Nonce must be exactly equal to the current account nonce
@@ -126,6 +625,17 @@ impl Validator {
             });
         }
         
+        // Queued behind pending transactions: only up to the lookahead
+        if let Some(pool) = &self.pool
+            && let Some(&earliest) = pool.pending_nonces(&tx.from).await.first()
+        {
+            let max_nonce = earliest.saturating_add(self.nonce_lookahead);
+            if tx.nonce > max_nonce {
+                warn!("Nonce {} from {:?} is past the lookahead (at most {})", tx.nonce, tx.from, max_nonce);
+                return Err(ValidationError::NonceTooFarAhead { nonce: tx.nonce, max_nonce });
+            }
+        }
+        
         Ok(())
     }
     
@@ -136,24 +646,32 @@ impl Validator {
     /// 2. The gas costs (fees paid to execute the transaction)
     /// 
     /// # Gas Cost Calculation
-    /// For simplicity, this implementation assumes a fixed gas limit of 21,000
-    /// (the standard cost for a basic ETH transfer). In production, this would
-    /// be computed based on the actual transaction complexity.
+    /// The sender must be able to pay for the full `gas_limit` at its fee cap,
+    /// since that is the most the transaction can cost.
+    /// 
+    /// # Pending Spend
+    /// With a pool (see [`Validator::with_pool`]), the maximum cost of the
+    /// sender's pending transactions is added, so several transactions that
+    /// each fit the balance can't together overdraw it. So is that of its
+    /// pending bundled transactions (see [`Validator::with_bundles`]).
     /// 
     /// # Returns
     /// * `Ok(())` if the account has sufficient balance
     /// * `Err(ValidationError::InsufficientBalance)` if funds are insufficient
-    async fn check_balance(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
-        // Fetch the current account state
-        let account = self.state_cache.get_or_init_account(&tx.from).await;
+    pub async fn check_balance(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        // Fetch the current account state, as it would be created if unknown
+        let account = self.state_cache.get_account(&tx.from).await;
         
-        // Calculate gas cost: gas_price * gas_limit
-        // In production, gas_limit would be estimated based on transaction complexity
-        let gas_limit = U256::from(21000); // Standard gas for basic transfer
-        let gas_cost = tx.gas_price * gas_limit;
+        // Calculate total funds required: transfer value + worst-case gas fees
+        let mut required = tx.max_cost();
         
-        // Calculate total funds required: transfer value + gas fees
-        let required = tx.value + gas_cost;
+        // Funds already committed by the sender's pending transactions
+        if let Some(pool) = &self.pool {
+            required = required.saturating_add(pool.pending_spend(&tx.from).await);
+        }
+        if let Some(bundles) = &self.bundles {
+            required = required.saturating_add(bundles.pending_spend(&tx.from).await);
+        }
         
         // Check if the account has sufficient balance
         if account.balance < required {
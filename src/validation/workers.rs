@@ -0,0 +1,175 @@
+//! Validation Worker Pool Module
+//!
+//! Runs the CPU-bound part of validation (signature recovery and the other
+//! stateless checks) on blocking threads, so concurrent submissions are
+//! verified in parallel instead of stalling the async handlers.
+//!
+//! The state checks and the pool hand-off still happen one at a time, in the
+//! order submissions arrived: a sender's nonce `n + 1` sent right after `n`
+//! must not be checked first just because its signature recovered sooner.
+
+use super::Validator;
+use crate::{UserTransaction, ValidationError};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, Semaphore};
+
+/// Parallel transaction validation with an ordered hand-off
+pub struct ValidationWorkers {
+    validator: Arc<Validator>,
+    /// Limits concurrent signature checks to the number of workers
+    permits: Arc<Semaphore>,
+    handoff: Arc<HandOff>,
+}
+
+impl ValidationWorkers {
+    /// Creates a pool verifying up to `workers` transactions at once
+    pub fn new(validator: Arc<Validator>, workers: usize) -> Self {
+        Self {
+            validator,
+            permits: Arc::new(Semaphore::new(workers.max(1))),
+            handoff: Arc::new(HandOff::default()),
+        }
+    }
+
+    /// The validator the workers run
+    pub fn validator(&self) -> &Arc<Validator> {
+        &self.validator
+    }
+
+    /// Run [`Validator::check_stateless`] on a worker thread
+    pub async fn check_stateless(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        self.run(tx, Validator::check_stateless).await
+    }
+
+    /// Run [`Validator::verify_signature`] on a worker thread
+    pub async fn verify_signature(&self, tx: &UserTransaction) -> Result<(), ValidationError> {
+        self.run(tx, Validator::verify_signature).await
+    }
+
+    /// Fully validate a transaction
+    ///
+    /// The stateless checks run in parallel with other submissions; the state
+    /// checks wait until every transaction submitted earlier has been handed
+    /// off (or has failed).
+    ///
+    /// # Returns
+    /// * `Ok(turn)` if the transaction is valid. The caller holds `turn` while
+    ///   adding it to the pool and updating the sender's nonce, then drops it
+    ///   to let the next submission through.
+    /// * `Err(ValidationError)` if any check fails
+    pub async fn validate(&self, tx: &UserTransaction) -> Result<Turn, ValidationError> {
+        // Taken before the first await, so the turn follows arrival order
+        let turn = Turn::take(self.handoff.clone());
+        self.check_stateless(tx).await?;
+        turn.wait().await;
+        self.validator.check_state(tx).await?;
+        Ok(turn)
+    }
+
+    /// Validate a bundle, each transaction against the state the earlier ones
+    /// would leave (see [`Validator::validate_bundle`])
+    ///
+    /// The stateless checks run on the workers first; the bundle is then
+    /// simulated in its turn, like a single submission.
+    ///
+    /// # Returns
+    /// * `Ok(turn)` if every transaction is valid, to be held until the bundle
+    ///   is queued and its senders' nonces are bumped
+    /// * `Err((index, error))` for the first invalid transaction
+    pub async fn validate_bundle(&self, txs: &[UserTransaction]) -> Result<Turn, (usize, ValidationError)> {
+        let turn = Turn::take(self.handoff.clone());
+        for (index, tx) in txs.iter().enumerate() {
+            self.check_stateless(tx).await.map_err(|e| (index, e))?;
+        }
+        turn.wait().await;
+        let results = self.validator.validate_bundle(txs).await;
+        match results.into_iter().enumerate().find_map(|(index, result)| result.err().map(|e| (index, e))) {
+            Some(failure) => Err(failure),
+            None => Ok(turn),
+        }
+    }
+
+    /// Run `check` on a blocking thread once a worker is free
+    async fn run(
+        &self,
+        tx: &UserTransaction,
+        check: fn(&Validator, &UserTransaction) -> Result<(), ValidationError>,
+    ) -> Result<(), ValidationError> {
+        let permit = self.permits.clone().acquire_owned().await.expect("validation semaphore is never closed");
+        let validator = self.validator.clone();
+        let tx = tx.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            check(&validator, &tx)
+        });
+        match task.await {
+            Ok(result) => result,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+}
+
+/// A submission's place in the hand-off order
+///
+/// Dropping it, at any point, lets later submissions through.
+pub struct Turn {
+    handoff: Arc<HandOff>,
+    ticket: u64,
+}
+
+impl Turn {
+    fn take(handoff: Arc<HandOff>) -> Self {
+        let ticket = handoff.next_ticket.fetch_add(1, Ordering::Relaxed);
+        Self { handoff, ticket }
+    }
+
+    /// Wait until every earlier turn has been dropped
+    async fn wait(&self) {
+        loop {
+            let notified = self.handoff.notify.notified();
+            tokio::pin!(notified);
+            // Registered before checking, so a release in between is not missed
+            notified.as_mut().enable();
+            if self.handoff.serving.lock().unwrap().next == self.ticket {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        let mut guard = self.handoff.serving.lock().unwrap();
+        let serving = &mut *guard;
+        if serving.next != self.ticket {
+            // Finished early (failed, or the client went away); skipped when reached
+            serving.finished.insert(self.ticket);
+            return;
+        }
+        serving.next += 1;
+        while serving.finished.remove(&serving.next) {
+            serving.next += 1;
+        }
+        self.handoff.notify.notify_waiters();
+    }
+}
+
+/// Shared state of the hand-off order
+#[derive(Default)]
+struct HandOff {
+    /// Ticket given to the next submission
+    next_ticket: AtomicU64,
+    serving: Mutex<Serving>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct Serving {
+    /// Ticket whose turn it is
+    next: u64,
+    /// Tickets dropped before their turn
+    finished: BTreeSet<u64>,
+}
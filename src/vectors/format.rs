@@ -0,0 +1,80 @@
+//! Test Vector Format
+//!
+//! Serializable fixtures shared by the generator and the verifier.
+
+use crate::{scheduler::SchedulingPolicyType, AccountState, ForcedTransaction, UserTransaction};
+use ethers::types::{transaction::eip712::EIP712Domain, Address, H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Version of the vector format, bumped whenever expected values change
+pub const VECTOR_FORMAT_VERSION: u32 = 6;
+
+/// A complete set of test vectors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// Format version the set was generated with
+    pub version: u32,
+    /// EIP-712 domain the transaction vectors are signed in
+    pub domain: EIP712Domain,
+    /// Signed transaction vectors
+    pub transactions: Vec<TransactionVector>,
+    /// Account commitment vectors
+    pub accounts: Vec<AccountVector>,
+    /// Expected state trie root over every account vector
+    pub state_root: H256,
+    /// Batch scheduling and encoding vectors
+    pub batches: Vec<BatchVector>,
+}
+
+/// A signed transaction and the values derived from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionVector {
+    /// Short description of what the vector covers
+    pub name: String,
+    /// Private key that signed the transaction (test-only key)
+    pub private_key: H256,
+    /// The signed transaction
+    pub transaction: UserTransaction,
+    /// Expected `UserTransaction::hash`
+    pub hash: H256,
+    /// Expected `UserTransaction::signing_hash` in the set's domain (what was signed)
+    pub signing_hash: H256,
+    /// Expected address recovered from the signature
+    pub signer: Address,
+}
+
+/// An account state and its commitment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountVector {
+    /// Short description of what the vector covers
+    pub name: String,
+    /// The account state
+    pub account: AccountState,
+    /// Expected `AccountState::state_hash`
+    pub state_hash: H256,
+}
+
+/// Scheduling inputs and the batch they must produce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchVector {
+    /// Short description of what the vector covers
+    pub name: String,
+    /// Policy used to order the normal transactions
+    pub policy: SchedulingPolicyType,
+    /// Batch identifier to seal with
+    pub batch_id: u64,
+    /// Batch timestamp to seal with
+    pub timestamp: u64,
+    /// Base fee per gas the normal transactions are ordered at
+    pub base_fee: U256,
+    /// Forced transactions, in L1 order
+    pub forced: Vec<ForcedTransaction>,
+    /// Normal transactions, in arrival order
+    pub normal: Vec<UserTransaction>,
+    /// Expected transaction hashes of the sealed batch, in order
+    pub ordered_hashes: Vec<H256>,
+    /// Expected state root before the batch (the set's state root)
+    pub prev_state_root: H256,
+    /// Expected canonical encoding of the sealed batch (compact JSON)
+    pub encoded: String,
+}
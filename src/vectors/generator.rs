@@ -0,0 +1,231 @@
+//! Test Vector Generator
+//!
+//! Builds a deterministic vector set from fixed test keys, addresses and
+//! timestamps, so two runs of the same version always produce identical output.
+
+use super::format::{AccountVector, BatchVector, TestVectors, TransactionVector, VECTOR_FORMAT_VERSION};
+use crate::{
+    config::SigningConfig,
+    scheduler::{Scheduler, SchedulingPolicyType},
+    state::StateTrie,
+    AccountState, Batch, ForcedEventType, ForcedTransaction, Transaction, UserTransaction,
+};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{transaction::eip712::EIP712Domain, Address, Bytes, Signature, H256, U256};
+
+/// Fixed batch timestamp used by every batch vector
+const BATCH_TIMESTAMP: u64 = 1_700_000_000;
+
+/// Fixed base fee per gas used by every batch vector
+const BASE_FEE: u64 = 15;
+
+/// Generate the canonical test vector set
+///
+/// # Returns
+/// * `Ok(vectors)` - The full vector set
+/// * `Err` if a test key cannot sign (only possible if the fixed keys are invalid)
+pub fn generate() -> anyhow::Result<TestVectors> {
+    let alice = H256::from_low_u64_be(1);
+    let bob = H256::from_low_u64_be(2);
+    let carol = H256::from_low_u64_be(3);
+    let recipient = Address::from_low_u64_be(0xdead);
+    let domain = SigningConfig::default().domain();
+
+    // ERC-20 transfer(address,uint256) call: zero and non-zero bytes both count towards intrinsic gas
+    let transfer_call: Bytes = [
+        &[0xa9, 0x05, 0x9c, 0xbb][..],
+        &H256::from(Address::from_low_u64_be(0xbeef))[..],
+        &H256::from_low_u64_be(1_000)[..],
+    ].concat().into();
+
+    let transactions = vec![
+        signed(&domain, "plain transfer", alice, recipient, U256::from(1_000), 0, U256::from(10), 1_000, None, None, Bytes::new())?,
+        signed(&domain, "second nonce from same sender", alice, recipient, U256::from(2_000), 1, U256::from(50), 1_500, None, None, Bytes::new())?,
+        signed(&domain, "high gas price", bob, recipient, U256::exp10(18), 0, U256::from(100), 2_000, None, None, Bytes::new())?,
+        signed(&domain, "boost bid", carol, recipient, U256::zero(), 0, U256::from(20), 6_000, Some(U256::from(500)), None, Bytes::new())?,
+        signed(&domain, "maximum value", carol, Address::zero(), U256::MAX, 1, U256::from(20), 7_000, None, None, Bytes::new())?,
+        // High fee cap but a small priority fee: the tip, not the cap, decides its rank
+        signed(&domain, "eip-1559 fees", bob, recipient, U256::from(5), 1, U256::zero(), 8_000, None,
+               Some((U256::from(1_000), U256::from(30))), Bytes::new())?,
+        signed(&domain, "contract call", alice, recipient, U256::zero(), 2, U256::zero(), 9_000, None,
+               Some((U256::from(100), U256::from(10))), transfer_call)?,
+    ];
+
+    let accounts = vec![
+        account("empty account", Address::zero(), U256::zero(), 0),
+        account("funded account", transactions[0].signer, U256::exp10(18), 2),
+        account("maximum balance and nonce", transactions[2].signer, U256::MAX, u64::MAX),
+    ];
+
+    let state_root = state_root(&accounts);
+
+    let forced = vec![forced_deposit(transactions[2].signer)];
+    let normal: Vec<UserTransaction> = transactions.iter().map(|v| v.transaction.clone()).collect();
+    let policies = [
+        ("fcfs", SchedulingPolicyType::Fcfs),
+        ("fee priority", SchedulingPolicyType::FeePriority),
+        ("time boost", SchedulingPolicyType::TimeBoost { time_window_ms: 5_000 }),
+        ("fair bft", SchedulingPolicyType::FairBft),
+        ("weighted fair", SchedulingPolicyType::WeightedFair { fee_weighted: false }),
+        ("fee-weighted fair", SchedulingPolicyType::WeightedFair { fee_weighted: true }),
+    ];
+    let batches = policies
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (name, policy))| {
+            batch(name, policy, idx as u64 + 1, state_root, forced.clone(), normal.clone())
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(TestVectors {
+        version: VECTOR_FORMAT_VERSION,
+        domain,
+        transactions,
+        accounts,
+        state_root,
+        batches,
+    })
+}
+
+/// Root of the state trie holding the vectors' accounts
+pub(super) fn state_root(accounts: &[AccountVector]) -> H256 {
+    let mut trie = StateTrie::new();
+    for vector in accounts {
+        trie.insert(&vector.account);
+    }
+    trie.root()
+}
+
+/// Seal a batch the way the sequencer does: forced first, then policy order
+pub(super) fn seal_batch(
+    policy: &SchedulingPolicyType,
+    batch_id: u64,
+    timestamp: u64,
+    prev_state_root: H256,
+    base_fee: U256,
+    forced: Vec<ForcedTransaction>,
+    normal: Vec<UserTransaction>,
+) -> Batch {
+    let scheduler = Scheduler::from_type(policy.clone());
+    Batch {
+        batch_id,
+        transactions: scheduler.schedule(forced, normal, base_fee),
+        prev_state_root,
+        timestamp,
+        lanes: None,
+    }
+}
+
+/// Hash identifying a transaction inside a batch
+pub(super) fn transaction_hash(tx: &Transaction) -> H256 {
+    match tx {
+        Transaction::Normal(tx) => tx.hash(),
+        Transaction::Forced(tx) => tx.tx_hash,
+        Transaction::UserOp(op) => op.hash(),
+    }
+}
+
+/// Build and sign a transaction vector
+#[allow(clippy::too_many_arguments)]
+fn signed(
+    domain: &EIP712Domain,
+    name: &str,
+    private_key: H256,
+    to: Address,
+    value: U256,
+    nonce: u64,
+    gas_price: U256,
+    timestamp: u64,
+    boost_bid: Option<U256>,
+    dynamic_fee: Option<(U256, U256)>,
+    data: Bytes,
+) -> anyhow::Result<TransactionVector> {
+    let wallet = LocalWallet::from_bytes(private_key.as_bytes())?;
+    let mut transaction = UserTransaction {
+        from: wallet.address(),
+        to,
+        value,
+        nonce,
+        gas_price,
+        // Replaced with the intrinsic gas once the calldata is set
+        gas_limit: 0,
+        // Replaced once the hash (which excludes the signature) is known
+        signature: Signature { r: U256::zero(), s: U256::zero(), v: 0 },
+        timestamp,
+        boost_bid,
+        chain_id: domain.chain_id.unwrap_or_default().as_u64(),
+        max_fee_per_gas: dynamic_fee.map(|(max_fee, _)| max_fee),
+        max_priority_fee_per_gas: dynamic_fee.map(|(_, max_priority_fee)| max_priority_fee),
+        data,
+        raw: None,
+        deadline: None,
+        priority_class: None,
+    };
+    transaction.gas_limit = transaction.intrinsic_gas();
+    let hash = transaction.hash();
+    let signing_hash = transaction.signing_hash(domain);
+    transaction.signature = wallet.sign_hash(signing_hash)?;
+
+    Ok(TransactionVector {
+        name: name.to_string(),
+        private_key,
+        signer: wallet.address(),
+        transaction,
+        hash,
+        signing_hash,
+    })
+}
+
+/// Build an account vector
+fn account(name: &str, address: Address, balance: U256, nonce: u64) -> AccountVector {
+    let account = AccountState { address, balance, nonce, ..Default::default() };
+    AccountVector {
+        name: name.to_string(),
+        state_hash: account.state_hash(),
+        account,
+    }
+}
+
+/// A deposit as the L1 listener would enqueue it
+fn forced_deposit(to: Address) -> ForcedTransaction {
+    ForcedTransaction {
+        tx_hash: H256::from_low_u64_be(0xf0),
+        from: to,
+        to,
+        value: U256::exp10(18),
+        nonce: 0,
+        gas_limit: 21_000,
+        l1_tx_hash: H256::from_low_u64_be(0xf1),
+        l1_block_number: 1,
+        l1_log_index: 0,
+        event_type: ForcedEventType::Deposit,
+        timestamp: 500,
+        bond: None,
+        data: Bytes::new(),
+    }
+}
+
+/// Build a batch vector by sealing the inputs under `policy`
+fn batch(
+    name: &str,
+    policy: SchedulingPolicyType,
+    batch_id: u64,
+    prev_state_root: H256,
+    forced: Vec<ForcedTransaction>,
+    normal: Vec<UserTransaction>,
+) -> anyhow::Result<BatchVector> {
+    let base_fee = U256::from(BASE_FEE);
+    let sealed = seal_batch(&policy, batch_id, BATCH_TIMESTAMP, prev_state_root, base_fee, forced.clone(), normal.clone());
+    Ok(BatchVector {
+        name: name.to_string(),
+        policy,
+        batch_id,
+        timestamp: BATCH_TIMESTAMP,
+        base_fee,
+        forced,
+        normal,
+        ordered_hashes: sealed.transactions.iter().map(transaction_hash).collect(),
+        prev_state_root: sealed.prev_state_root,
+        encoded: serde_json::to_string(&sealed)?,
+    })
+}
@@ -0,0 +1,36 @@
+//! Test Vectors Module
+//!
+//! This module generates and checks canonical test vectors: JSON fixtures that
+//! pin down how this sequencer hashes, signs, orders and encodes data, so that
+//! alternative implementations (verifiers, SDKs) can prove they are compatible.
+//!
+//! # Vector Kinds
+//! - **Transactions**: transactions signed in a fixed EIP-712 domain, with their
+//!   hash, signing digest and recovered signer
+//! - **Accounts**: account states with their `state_hash` commitment
+//! - **State root**: the state trie root over every account vector
+//! - **Batches**: scheduling inputs, the expected transaction order under a
+//!   policy, and the canonical JSON encoding of the sealed batch
+//!
+//! # State Roots
+//! Every batch vector is sealed on top of the state made of the account
+//! vectors, so its `prev_state_root` is the set's state root. The set carries
+//! a format `version`, bumped whenever expected values change.
+
+mod format;
+mod generator;
+mod verifier;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub use format::{
+    TestVectors,
+    TransactionVector,
+    AccountVector,
+    BatchVector,
+    VECTOR_FORMAT_VERSION,
+};
+pub use generator::generate;
+pub use verifier::{verify, VectorFailure};
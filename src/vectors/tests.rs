@@ -0,0 +1,115 @@
+//! Tests for test vector generation and verification
+
+#[cfg(test)]
+mod tests {
+    use crate::{state::StateCache, validation::Validator, vectors::{generate, verify, TestVectors}, ValidationError};
+    use ethers::types::transaction::eip712::{Eip712, TypedData};
+    use ethers::types::{H256, U256};
+    use serde_json::json;
+
+    #[test]
+    fn test_generated_vectors_verify_after_roundtrip() {
+        let vectors = generate().unwrap();
+        assert!(!vectors.transactions.is_empty());
+        assert_eq!(vectors.batches.len(), 6);
+
+        // Generation is deterministic
+        let json = serde_json::to_string_pretty(&vectors).unwrap();
+        assert_eq!(json, serde_json::to_string_pretty(&generate().unwrap()).unwrap());
+
+        let parsed: TestVectors = serde_json::from_str(&json).unwrap();
+        assert!(verify(&parsed).is_empty());
+    }
+
+    #[test]
+    fn test_signing_hash_matches_standard_typed_data() {
+        let vectors = generate().unwrap();
+        let tx = &vectors.transactions[6].transaction;
+        assert!(!tx.data.is_empty());
+
+        // The same message as a wallet would receive it for eth_signTypedData_v4
+        let typed: TypedData = serde_json::from_value(json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"},
+                ],
+                "UserTransaction": [
+                    {"name": "from", "type": "address"},
+                    {"name": "to", "type": "address"},
+                    {"name": "value", "type": "uint256"},
+                    {"name": "nonce", "type": "uint64"},
+                    {"name": "gasPrice", "type": "uint256"},
+                    {"name": "gasLimit", "type": "uint64"},
+                    {"name": "timestamp", "type": "uint64"},
+                    {"name": "boostBid", "type": "uint256"},
+                    {"name": "chainId", "type": "uint64"},
+                    {"name": "maxFeePerGas", "type": "uint256"},
+                    {"name": "maxPriorityFeePerGas", "type": "uint256"},
+                    {"name": "data", "type": "bytes"},
+                ],
+            },
+            "primaryType": "UserTransaction",
+            "domain": vectors.domain,
+            "message": {
+                "from": tx.from,
+                "to": tx.to,
+                "value": tx.value,
+                "nonce": tx.nonce,
+                "gasPrice": tx.gas_price,
+                "gasLimit": tx.gas_limit,
+                "timestamp": tx.timestamp,
+                "boostBid": tx.boost_bid.unwrap_or_default(),
+                "chainId": tx.chain_id,
+                "maxFeePerGas": tx.max_fee_per_gas.unwrap_or_default(),
+                "maxPriorityFeePerGas": tx.max_priority_fee_per_gas.unwrap_or_default(),
+                "data": tx.data,
+            },
+        })).unwrap();
+
+        assert_eq!(H256::from(typed.struct_hash().unwrap()), tx.hash());
+        assert_eq!(H256::from(typed.encode_eip712().unwrap()), tx.signing_hash(&vectors.domain));
+    }
+
+    #[test]
+    fn test_vector_signatures_are_low_s_and_high_s_copies_are_malformed() {
+        let vectors = generate().unwrap();
+        let validator = Validator::new(StateCache::new());
+        // secp256k1 curve order
+        let order = U256::from_str_radix("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141", 16).unwrap();
+        for vector in &vectors.transactions {
+            let mut tx = vector.transaction.clone();
+            validator.check_format(&tx).unwrap();
+            
+            // The same signature with s negated (and v flipped) recovers the same signer
+            tx.signature.s = order - tx.signature.s;
+            tx.signature.v ^= 1;
+            assert!(matches!(validator.check_format(&tx), Err(ValidationError::MalformedSignature)));
+        }
+    }
+
+    #[test]
+    fn test_tampered_vectors_are_reported() {
+        let mut vectors = generate().unwrap();
+        vectors.transactions[0].transaction.value += 1.into();
+        vectors.accounts[0].state_hash = H256::zero();
+        vectors.batches[1].ordered_hashes.reverse();
+        vectors.batches[2].prev_state_root = H256::zero();
+
+        let failed: Vec<String> = verify(&vectors).into_iter().map(|f| f.vector).collect();
+        assert!(failed.contains(&"transactions/plain transfer".to_string()));
+        assert!(failed.contains(&"accounts/empty account".to_string()));
+        assert!(failed.contains(&"batches/fee priority".to_string()));
+        assert!(failed.contains(&"batches/time boost".to_string()));
+
+        // Changing an account changes the state root every batch builds on
+        let mut vectors = generate().unwrap();
+        vectors.accounts[1].account.nonce += 1;
+        vectors.accounts[1].state_hash = vectors.accounts[1].account.state_hash();
+        let failed: Vec<String> = verify(&vectors).into_iter().map(|f| f.vector).collect();
+        assert!(failed.contains(&"state root".to_string()));
+        assert!(failed.contains(&"batches/fcfs".to_string()));
+    }
+}
@@ -0,0 +1,141 @@
+//! Test Vector Verifier
+//!
+//! Recomputes every expected value in a vector set with this implementation
+//! and reports each mismatch.
+
+use super::format::{AccountVector, BatchVector, TestVectors, TransactionVector, VECTOR_FORMAT_VERSION};
+use super::generator::{seal_batch, state_root, transaction_hash};
+use crate::Batch;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{transaction::eip712::EIP712Domain, H256};
+use std::fmt;
+
+/// A vector whose expected values don't match this implementation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorFailure {
+    /// Which vector failed, as `<kind>/<name>`
+    pub vector: String,
+    /// What did not match
+    pub reason: String,
+}
+
+impl fmt::Display for VectorFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.vector, self.reason)
+    }
+}
+
+/// Check a vector set against this implementation
+///
+/// # Returns
+/// Every mismatch found (empty if the whole set verifies)
+pub fn verify(vectors: &TestVectors) -> Vec<VectorFailure> {
+    let mut failures = Vec::new();
+    if vectors.version != VECTOR_FORMAT_VERSION {
+        failures.push(VectorFailure {
+            vector: "set".to_string(),
+            reason: format!("format version {} is not supported (expected {})",
+                            vectors.version, VECTOR_FORMAT_VERSION),
+        });
+        return failures;
+    }
+
+    for vector in &vectors.transactions {
+        check_transaction(&vectors.domain, vector, &mut failures);
+    }
+    for vector in &vectors.accounts {
+        check_account(vector, &mut failures);
+    }
+    // Batches are checked against the root of the accounts, not the expected one
+    let root = state_root(&vectors.accounts);
+    if root != vectors.state_root {
+        failures.push(VectorFailure {
+            vector: "state root".to_string(),
+            reason: format!("state root is {:?}, expected {:?}", root, vectors.state_root),
+        });
+    }
+    for vector in &vectors.batches {
+        check_batch(vector, root, &mut failures);
+    }
+    failures
+}
+
+fn check_transaction(domain: &EIP712Domain, vector: &TransactionVector, failures: &mut Vec<VectorFailure>) {
+    let mut fail = |reason: String| failures.push(VectorFailure {
+        vector: format!("transactions/{}", vector.name),
+        reason,
+    });
+
+    let hash = vector.transaction.hash();
+    if hash != vector.hash {
+        fail(format!("hash is {:?}, expected {:?}", hash, vector.hash));
+    }
+    let signing_hash = vector.transaction.signing_hash(domain);
+    if signing_hash != vector.signing_hash {
+        fail(format!("signing hash is {:?}, expected {:?}", signing_hash, vector.signing_hash));
+    }
+    match vector.transaction.signature.recover(signing_hash) {
+        Ok(signer) if signer == vector.signer => {}
+        Ok(signer) => fail(format!("signature recovers {:?}, expected {:?}", signer, vector.signer)),
+        Err(e) => fail(format!("signature does not recover: {}", e)),
+    }
+    if vector.transaction.from != vector.signer {
+        fail(format!("sender is {:?}, expected the signer {:?}", vector.transaction.from, vector.signer));
+    }
+    match LocalWallet::from_bytes(vector.private_key.as_bytes()) {
+        Ok(wallet) if wallet.address() == vector.signer => {}
+        Ok(wallet) => fail(format!("private key belongs to {:?}, expected {:?}", wallet.address(), vector.signer)),
+        Err(e) => fail(format!("invalid private key: {}", e)),
+    }
+}
+
+fn check_account(vector: &AccountVector, failures: &mut Vec<VectorFailure>) {
+    let state_hash = vector.account.state_hash();
+    if state_hash != vector.state_hash {
+        failures.push(VectorFailure {
+            vector: format!("accounts/{}", vector.name),
+            reason: format!("state hash is {:?}, expected {:?}", state_hash, vector.state_hash),
+        });
+    }
+}
+
+fn check_batch(vector: &BatchVector, state_root: H256, failures: &mut Vec<VectorFailure>) {
+    let mut fail = |reason: String| failures.push(VectorFailure {
+        vector: format!("batches/{}", vector.name),
+        reason,
+    });
+
+    let sealed = seal_batch(
+        &vector.policy,
+        vector.batch_id,
+        vector.timestamp,
+        state_root,
+        vector.base_fee,
+        vector.forced.clone(),
+        vector.normal.clone(),
+    );
+    let ordered: Vec<H256> = sealed.transactions.iter().map(transaction_hash).collect();
+    if ordered != vector.ordered_hashes {
+        fail(format!("ordering is {:?}, expected {:?}", ordered, vector.ordered_hashes));
+    }
+    if sealed.prev_state_root != vector.prev_state_root {
+        fail(format!("previous state root is {:?}, expected {:?}",
+                     sealed.prev_state_root, vector.prev_state_root));
+    }
+    match serde_json::to_string(&sealed) {
+        Ok(encoded) if encoded == vector.encoded => {}
+        Ok(_) => fail("encoding does not match".to_string()),
+        Err(e) => fail(format!("batch does not encode: {}", e)),
+    }
+
+    // The expected encoding must also decode back to the same batch
+    match serde_json::from_str::<Batch>(&vector.encoded) {
+        Ok(decoded) => {
+            let decoded_hashes: Vec<H256> = decoded.transactions.iter().map(transaction_hash).collect();
+            if decoded.batch_id != vector.batch_id || decoded_hashes != vector.ordered_hashes {
+                fail("encoding decodes to a different batch".to_string());
+            }
+        }
+        Err(e) => fail(format!("encoding does not decode: {}", e)),
+    }
+}